@@ -7,31 +7,62 @@ of sent funds before this expiration". Remember that it is P2P, so we have a def
 
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::{
-    Addr, BankMsg, Binary, Deps, DepsMut, Env, MessageInfo, Response,
-    StdResult, SubMsg, WasmMsg, from_binary, to_binary, entry_point
+    Addr, BankMsg, Binary, Coin, Decimal, Deps, DepsMut, Empty, Env, Event, IbcMsg, IbcTimeout,
+    MessageInfo, Reply, Response, StdError, StdResult, Storage, SubMsg, SubMsgResult, Uint128,
+    WasmMsg, coin, from_binary, to_binary, entry_point
 };
 use cosmwasm_std::Order::Ascending;
 use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
 
 use cw_storage_plus::Bound;
-use cw2::set_contract_version;
+use cw2::{get_contract_version, set_contract_version};
+use cw_utils::NativeBalance;
 use cw20::{
-    Balance, Cw20Coin, Cw20CoinVerified, Cw20ExecuteMsg, Cw20ReceiveMsg
+    Balance, BalanceResponse, Cw20Coin, Cw20CoinVerified, Cw20ExecuteMsg, Cw20QueryMsg,
+    Cw20ReceiveMsg, Expiration
 };
 
 use crate::error::ContractError;
-use crate::state::{all_swap_ids, AtomicSwap, OLD_SWAPS, SWAPS};
+use crate::parse::parse_hex_32;
+use crate::state::{
+    all_fee_exemptions, settled_swap_ids, swaps, AtomicSwap, Config, GenericBalance,
+    COLLECTED_FEES, CONFIG, FEE_EXEMPTIONS, INBOX, INBOX_COUNT, OLD_SWAPS, OPEN_SWAPS, PAYOUT_COUNT,
+    PENDING_PAYOUTS, RELEASE_COMMITS, REVEALED_HASHES, SETTLED_SWAPS, SPONSORED_USERS, SPONSORS,
+    SWAP_TOTALS, InboxEvent, PendingPayout, ReleaseCommit, Sponsor, SwapTotals, CHANGE_SEQ,
+    LAST_CHANGES, SWAP_CHANGES, SwapChange, CW20_LEDGER, NATIVE_LEDGER
+};
 use crate::msg::{
-    is_valid_name, BalanceHuman, CreateMsg, DetailsResponse, ExecuteMsg, InstantiateMsg,
-    ListResponse, QueryMsg, ReceiveMsg, MigrateMsg
+    is_valid_name, BalanceHuman, CollectedFeesResponse, ComputeHashResponse, ConfigResponse,
+    CreateMsg, DetailsBatchResponse, DetailsResponse, DumpStateResponse, EventOccurredResponse, ExecuteMsg, ExpiringSwap,
+    ExpiringWithinResponse, ExportResponse, ExportedSwap, ChangedSinceResponse, AttestationQueryMsg,
+    AttestedResponse, MetricsResponse, EventVerbosity, PayoutMode,
+    FeeExemptionsResponse, FeePayer, HashAlgorithm, HashPreview, IbcPayout, InboxEventKind,
+    InboxEventResponse, InboxResponse, InstantiateMsg, ListResponse,
+    LockType, OracleQueryMsg, Order, PriceQueryMsg, PriceResponse, OrderBookResponse, QueryMsg, ReceiveMsg, MigrateMsg,
+    Migration, OrderBookCursor,
+    PendingPayoutResponse, PendingPayoutsResponse, RefundResult, ReleaseResult, SponsorResponse,
+    SponsorsResponse, SwapChangeKind, SwapChangeResponse, SwapEntry, SwapStatus, UpdateConfigMsg
 };
 
 // Version info, for migration info
 const CONTRACT_NAME: &str = "crates.io:atomic-swap";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+// upstream contract this one is forked from, whose deployments can be migrated onto this one
+const UPSTREAM_CONTRACT_NAME: &str = "crates.io:cw20-atomic-swap";
+
+// Fees are expressed in basis points, this being 100%
+const MAX_FEE_BPS: u64 = 10_000;
+
+// number of blocks a release commitment must wait before being revealed
+const MIN_REVEAL_BLOCKS: u64 = 1;
+
+// most unread events kept in an inbox, the oldest being dropped past it
+const MAX_INBOX: usize = 50;
 
-/// Instantiation - default does not have any setup
+
+/// Instantiation - the only setup is the default configuration, with the instantiator as admin.
 /// An atomic swap contract should only be seen as an extension to a full-fledged Cw20 contract.
 /// This is because it should only be used for the swapping itself, rather than handling a lot
 /// of executions and instantiation logic.
@@ -46,11 +77,13 @@ const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 pub fn instantiate(
     deps  : DepsMut,
     _env  : Env,
-    _info : MessageInfo,
+    info  : MessageInfo,
     _msg  : InstantiateMsg,
 ) -> StdResult<Response> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
-    // No setup
+    // the instantiator is the admin, everything else can later be configured by them
+    CONFIG.save(deps.storage, &Config::new(info.sender))?;
+    OPEN_SWAPS.save(deps.storage, &0)?;
     Ok(Response::default())
 }
 
@@ -66,6 +99,62 @@ pub fn instantiate(
 /// * the error type Err
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
+    mut deps : DepsMut,
+    env      : Env,
+    info     : MessageInfo,
+    msg      : ExecuteMsg,
+) -> Result<Response, ContractError> {
+    let res = dispatch(deps.branch(), env, info, msg)?;
+    let config = CONFIG.load(deps.storage)?;
+    let res = apply_verbosity(res, &config.event_verbosity);
+
+    // with telemetry on, exporters scraping the events track the contract's health from them
+    if !config.telemetry {
+        return Ok(res);
+    }
+    let totals = SWAP_TOTALS.may_load(deps.storage)?.unwrap_or_default();
+    Ok(res.add_attributes(vec![
+        ("open_swaps", OPEN_SWAPS.load(deps.storage)?.to_string()),
+        ("created_total", totals.created.to_string()),
+        ("released_total", totals.released.to_string()),
+        ("refunded_total", totals.refunded.to_string()),
+    ]))
+}
+
+
+/// Attributes carried at every level - those swaps have always carried, which indexers rely on
+const BASELINE_ATTRIBUTES: [&str; 10] = [
+    "action", "id", "hash", "preimage", "recipient", "source", "to", "balance", "released",
+    "refunded",
+];
+
+
+/// Keep the attributes of an execute response the event verbosity carries. Handlers emit every
+/// attribute, and those beyond the configured verbosity are left out here - never the baseline
+/// ones.
+/// # Arguments
+/// * `res`       - the execute response
+/// * `verbosity` - the configured event verbosity
+/// # Returns
+///   The response, with the attributes beyond the verbosity left out
+fn apply_verbosity(mut res: Response, verbosity: &EventVerbosity) -> Response {
+    if let EventVerbosity::Minimal = verbosity {
+        res.attributes.retain(|attr| BASELINE_ATTRIBUTES.contains(&attr.key.as_str()));
+    }
+    res
+}
+
+
+/// Dispatch an execute message to its handler.
+/// # Arguments
+/// * `deps` - mutable dependency which has the storage (state) of the chain
+/// * `env`  - environment variables which include block information
+/// * `info` - initiator's information (including their address and balance)
+/// * `msg`  - the execute message
+/// # Returns
+/// * the execute response
+/// * the error type Err
+fn dispatch(
     deps : DepsMut,
     env  : Env,
     info : MessageInfo,
@@ -76,6 +165,9 @@ pub fn execute(
         // create - swap creation for native tokens
         // first, we send the funds to the contract, which will be stored in info storage
         ExecuteMsg::Create(msg) => {
+            if msg.source.is_some() {
+                return Err(ContractError::SourceNotAllowed);
+            }
             let sent_funds = info.funds.clone();
             execute_create(deps, env, info, msg, Balance::from(sent_funds))
         }
@@ -85,17 +177,106 @@ pub fn execute(
         // as the password that will be hashed on the smart contract (read more in execute_release)
         ExecuteMsg::Release {
             id,
-            preimage
-        } => execute_release(deps, env, id, preimage),
+            preimage,
+            amount
+        } => execute_release(deps, env, id, preimage, amount),
+
+        // commit-reveal release - commit to the preimage first, and reveal it in a later block
+        ExecuteMsg::CommitRelease {
+            id,
+            salted_hash
+        } => execute_commit_release(deps, env, info, id, salted_hash),
+        ExecuteMsg::RevealRelease {
+            id,
+            preimage,
+            salt,
+            amount
+        } => execute_reveal_release(deps, env, info, id, preimage, salt, amount),
 
         // refund - cancel transaction
         // it only requires the contract's id to let it return the funds back
         ExecuteMsg::Refund {
             id
-        } => execute_refund(deps, env, id),
+        } => execute_refund(deps, env, info, id),
+
+        // force refund - admin only, for swaps that never expire
+        ExecuteMsg::ForceRefund {
+            id
+        } => execute_force_refund(deps, info, id),
+
+        // retry payout - anyone redrives a failed Cw20 payout once its backoff delay passed
+        ExecuteMsg::RetryPayout {
+            id
+        } => execute_retry_payout(deps, env, info, id),
+
+        // accept / cancel - for swaps requiring acceptance, the recipient accepts them before they
+        // can be released, and the source can cancel them until then
+        ExecuteMsg::Accept {
+            id
+        } => execute_accept(deps, env, info, id),
+        ExecuteMsg::Cancel {
+            id
+        } => execute_cancel(deps, info, id),
 
         // receive - same with create but for Cw20 tokens
         ExecuteMsg::Receive(msg) => execute_receive(deps, env, info, msg),
+
+        // update config - admin only
+        ExecuteMsg::UpdateConfig(msg) => execute_update_config(deps, info, msg),
+
+        // withdraw fees - admin only
+        ExecuteMsg::WithdrawFees {} => execute_withdraw_fees(deps, info),
+
+        // fee exemptions - admin only
+        ExecuteMsg::AddFeeExemption {
+            address
+        } => execute_set_fee_exemption(deps, info, address, true),
+        ExecuteMsg::RemoveFeeExemption {
+            address
+        } => execute_set_fee_exemption(deps, info, address, false),
+
+        // counter proof - source only, to correlate with the matching swap on the other chain
+        ExecuteMsg::AttachCounterProof {
+            id,
+            counter_chain,
+            counter_tx
+        } => execute_attach_counter_proof(deps, info, id, counter_chain, counter_tx),
+
+        // mirrored create - create the swap here and its counter-swap on the partner contract
+        ExecuteMsg::CreateMirrored {
+            create,
+            partner_contract,
+            partner_create
+        } => execute_create_mirrored(deps, env, info, create, partner_contract, partner_create),
+
+        // ibc hook create - swap creation from an ICS-20 transfer, with the funds transferred
+        ExecuteMsg::IbcHookCreate(msg) => execute_ibc_hook_create(deps, env, info, msg),
+
+        // counter-swap create - a partner contract creating the counter-swap of a mirrored create
+        ExecuteMsg::CreateCounterSwap(msg) => execute_create_counter_swap(deps, env, info, msg),
+
+        // gas sponsors - the admin manages them, and they fund their budget
+        ExecuteMsg::SetSponsor {
+            address,
+            grant
+        } => execute_set_sponsor(deps, info, address, grant),
+        ExecuteMsg::FundSponsor {} => execute_fund_sponsor(deps, info),
+
+        // sponsored - a sponsor executes a message, granting a new user a fee allowance
+        ExecuteMsg::Sponsored {
+            user,
+            msg
+        } => execute_sponsored(deps, env, info, user, *msg),
+
+        // ack inbox - the sender marks the events of their inbox as read
+        ExecuteMsg::AckInbox {
+            ids
+        } => execute_ack_inbox(deps, info, ids),
+
+        // verify ledger - anyone checks the funds held against the ledger, pausing creates on drift
+        ExecuteMsg::VerifyLedger {
+            denom
+        } => execute_verify_ledger(deps, env, denom),
     }
 }
 
@@ -121,14 +302,80 @@ pub fn execute_create(
         return Err(ContractError::InvalidId {});
     }
 
+    // set the fee payer's deposit aside from the native funds sent, and take the creation fee out
+    // of the deposit if there is one, or the funds otherwise - the rest being the swap's balance
+    // (Cw20 swaps are created by the token contract, which cannot attach native funds, so they are
+    // exempt from the creation fee)
+    let config = CONFIG.load(deps.storage)?;
+    if config.creates_paused {
+        return Err(ContractError::CreatesPaused);
+    }
+    for attachment in [&msg.payout_msg, &msg.order_commitment].iter().copied().flatten() {
+        config.limits.check_attachment(attachment)?;
+    }
+    for memo in [&msg.counter_chain, &msg.counter_tx].iter().copied().flatten() {
+        config.limits.check_memo(memo)?;
+    }
+    let (balance, fee_payer) = match (balance, msg.fee_payer) {
+        (Balance::Native(coins), Some(payer)) => {
+            deps.api.addr_validate(&payer.address)?;
+            let principal = (coins - payer.deposit.clone())
+                .map_err(|_| ContractError::InvalidFeePayer)?;
+            let mut deposit = NativeBalance(vec![payer.deposit.clone()]);
+            if let Some(fee) = &config.creation_fee {
+                deposit = take_creation_fee(deps.storage, deposit, fee)?;
+            }
+            let left = Coin {
+                amount: deposit.0.first().map(|c| c.amount).unwrap_or_default(),
+                ..payer.deposit
+            };
+            (Balance::Native(principal), Some(FeePayer { deposit: left, ..payer }))
+        }
+        (_, Some(_)) => return Err(ContractError::InvalidFeePayer),
+        (Balance::Native(coins), None) => match &config.creation_fee {
+            Some(fee) => (Balance::Native(take_creation_fee(deps.storage, coins, fee)?), None),
+            None => (Balance::Native(coins), None),
+        },
+        (balance, None) => (balance, None),
+    };
+
     // this ignores 0 value coins, must have one or more with positive balance
     // ignoring zero-value is a common standard among Cw tokens
     if balance.is_empty() {
         return Err(ContractError::EmptyBalance {});
     }
+    config.limits.check_assets(GenericBalance::from(balance.clone()).asset_count())?;
+
+    // the fee payer's deposit needs to cover the highest fee the swap can be charged
+    if let Some(payer) = &fee_payer {
+        let principal = match &balance {
+            Balance::Native(coins)
+                if coins.0.len() == 1 && coins.0[0].denom == payer.deposit.denom =>
+            {
+                coins.0[0].amount
+            }
+            _ => return Err(ContractError::InvalidFeePayer),
+        };
+        let max_bps = config.release_fee_bps.max(config.refund_bounty_bps);
+        if payer.deposit.amount < principal.multiply_ratio(max_bps, MAX_FEE_BPS) {
+            return Err(ContractError::InvalidFeePayer);
+        }
+    }
+
+    // Ensure this is 32 bytes hex-encoded, and decode - unless the swap is locked on an oracle
+    // attestation instead, in which case the oracle needs to be a valid address
+    let hash = match &msg.lock_type {
+        Some(LockType::Oracle { oracle_addr, .. }) => {
+            deps.api.addr_validate(oracle_addr)?;
+            vec![]
+        }
+        _ => parse_hex_32(&msg.hash)?,
+    };
 
-    // Ensure this is 32 bytes hex-encoded, and decode
-    let hash = parse_hex_32(&msg.hash)?;
+    // a hash whose preimage is already public would let anyone release the swap right away
+    if !config.allow_revealed_hashes && REVEALED_HASHES.has(deps.storage, &hash) {
+        return Err(ContractError::HashRevealed);
+    }
 
     // Ensure that the swap has not expired
     // remember that Expiration struct will automatically update to the block once it expires
@@ -136,36 +383,213 @@ pub fn execute_create(
         return Err(ContractError::Expired {});
     }
 
+    // the swap is refunded to its source - the sender, unless they designate another address
+    let funder = info.sender;
+    let source = match &msg.source {
+        Some(source) => deps.api.addr_validate(source)?,
+        None => funder.clone(),
+    };
+
     // validate recipient address
     // the smart contract does not allow same sender and recipient
     let recipient = deps.api.addr_validate(&msg.recipient)?;
-    if recipient == info.sender {
+    if recipient == source {
         return Err(ContractError::SameSenderRecipient);
     }
 
+    // nor the contract itself on either side, as neither the payout nor the refund could be claimed
+    if recipient == env.contract.address || source == env.contract.address {
+        return Err(ContractError::SelfSwap);
+    }
+
+    // regulated venues only lock the funds of attested sources, and of attested funders should
+    // the source be another address
+    assert_attested(deps.as_ref(), &config, &source)?;
+    if funder != source {
+        assert_attested(deps.as_ref(), &config, &funder)?;
+    }
+
+    // a payout message only makes sense when the payout is done via Cw20 Send
+    if msg.payout_msg.is_some() && !matches!(balance, Balance::Cw20(_)) {
+        return Err(ContractError::PayoutMsgNotCw20);
+    }
+
+    // only Cw20 payouts are sent as submessages whose failure can be redirected
+    if msg.fallback_recipient.is_some() && !matches!(balance, Balance::Cw20(_)) {
+        return Err(ContractError::FallbackNotCw20);
+    }
+    let fallback_recipient = msg
+        .fallback_recipient
+        .map(|addr| deps.api.addr_validate(&addr))
+        .transpose()?;
+
+    // an IBC payout delivers native coins with an ICS-20 transfer, so it needs a complete route
+    if let Some(route) = &msg.ibc_payout {
+        if !matches!(balance, Balance::Native(_))
+            || route.channel.is_empty()
+            || route.remote_recipient.is_empty()
+            || route.timeout_seconds == 0
+        {
+            return Err(ContractError::InvalidIbcPayout);
+        }
+    }
+
+    // listing on the order book needs both what is asked and how much of it
+    let ask = match (msg.ask_denom, msg.ask_amount) {
+        (None, None) => None,
+        (Some(denom), Some(amount)) if !denom.is_empty() && !amount.is_zero() => {
+            Some(Coin { denom, amount })
+        }
+        _ => return Err(ContractError::InvalidAsk),
+    };
+
+    // installments are taken out of a single coin or token, which the swap must hold
+    let partial = msg.partial.unwrap_or_default();
+    if partial && matches!(&balance, Balance::Native(coins) if coins.0.len() != 1) {
+        return Err(ContractError::InvalidPartial);
+    }
+
+    // extending the expiration on installments needs installments, and an expiration to extend
+    // by at most the latest that can be represented
+    if let Some(ext) = &msg.auto_extend {
+        let in_range = match msg.expires {
+            Expiration::AtHeight(height) => height.checked_add(ext.max_total).is_some(),
+            Expiration::AtTime(time) => (u64::MAX - time.nanos()) / 1_000_000_000 >= ext.max_total,
+            Expiration::Never {} => false,
+        };
+        if !partial || ext.delta == 0 || !in_range {
+            return Err(ContractError::InvalidAutoExtend);
+        }
+    }
+
+    // the reveal deadline starts once the recipient accepts the swap or commits to its preimage,
+    // and must be reachable from the current height
+    if let Some(blocks) = msg.reveal_deadline {
+        let started_by = msg.accept_required.unwrap_or_default()
+            || msg.commit_reveal.unwrap_or_default();
+        if blocks == 0 || !started_by || env.block.height.checked_add(blocks).is_none() {
+            return Err(ContractError::InvalidRevealDeadline);
+        }
+    }
+
+    // each asset has a single place in the payout order
+    if let Some(order) = &msg.payout_order {
+        let mut keys: Vec<&String> = order.iter().collect();
+        keys.sort();
+        keys.dedup();
+        if keys.len() != order.len() {
+            return Err(ContractError::InvalidPayoutOrder);
+        }
+    }
+
+    // funds cannot be locked for longer than allowed, in case the preimage is lost
+    if let Some(latest) = config.latest_expiration(&env.block, &msg.expires) {
+        if msg.expires > latest {
+            return Err(ContractError::LockTooLong(latest));
+        }
+    }
+
+    // swaps that never expire can never be refunded by their sender, so they need to be allowed
+    if matches!(msg.expires, Expiration::Never {}) && !config.allow_never_expires {
+        return Err(ContractError::NeverExpires);
+    }
+
+    // reject the swap if the contract already holds its maximum number of open swaps
+    let open = OPEN_SWAPS.load(deps.storage)?;
+    if let Some(cap) = config.max_open_swaps {
+        if open >= cap {
+            return Err(ContractError::CapacityReached { open, cap });
+        }
+    }
+
     // create an atomic swap unit
     let swap = AtomicSwap {
         hash: Binary(hash),     // the preimage hash (initially stored in create msg)
         recipient,              // the recipient's smart contract
-        source: info.sender,    // the sender's smart contract
+        source,                 // the sender's smart contract, unless designated otherwise
         expires: msg.expires,   // expiration
-        balance,                // the balance which is sender's already sent funds on the contract
+        balance: balance.into(),// the balance which is sender's already sent funds on the contract
         memo: "Hello World".to_string(),
+        payout_msg: msg.payout_msg,
+        order_commitment: msg.order_commitment.clone(),
+        ibc_payout: msg.ibc_payout,
+        counter_chain: msg.counter_chain,
+        counter_tx: msg.counter_tx,
+        created_at: Some((env.block.height, env.block.time)),
+        lock_type: msg.lock_type,
+        ask,
+        fee_payer,
+        partial,
+        commit_reveal: msg.commit_reveal.unwrap_or_default(),
+        accept_required: msg.accept_required.unwrap_or_default(),
+        accepted: false,
+        auto_extend: msg.auto_extend,
+        extended: 0,
+        fallback_recipient,
+        private: msg.private.unwrap_or_default(),
+        reveal_deadline: msg.reveal_deadline,
+        reveal_by: None,
+        payout_order: msg.payout_order,
+        payout_mode: msg.payout_mode,
     };
 
     // Try to store it in SWAP, fail if the id already exists (unmodifiable swaps - they're atomic)
-    SWAPS.update(deps.storage, &msg.id, |existing| match existing {
+    let notified = swap.recipient.clone();
+    let private = swap.private;
+    let (source, locked) = (swap.source.clone(), balance_to_string(&swap.balance));
+    let holdings = swap_holdings(&swap);
+    swaps().update(deps.storage, &msg.id, |existing| match existing {
         None => Ok(swap),
         Some(_) => Err(ContractError::AlreadyExists {}),
     })?;
+    update_ledger(deps.storage, &holdings, true)?;
+    OPEN_SWAPS.save(deps.storage, &(open + 1))?;
+    update_totals(deps.storage, |totals| totals.created += 1)?;
+    if !private {
+        record_change(deps.storage, &msg.id, SwapChangeKind::Created)?;
+    }
+    notify(deps.storage, &notified, InboxEventKind::Created, &msg.id, env.block.height)?;
 
     // return the response
-    let res = Response::new()
+    let mut res = Response::new()
         .add_attribute("action", "create")
-        .add_attribute("id", msg.id)
+        .add_attribute("id", &msg.id)
         .add_attribute("hash", msg.hash)
-        .add_attribute("recipient", msg.recipient);
-    Ok(res)
+        .add_attribute("recipient", msg.recipient)
+        .add_attribute("source", source)
+        .add_attribute("balance", locked);
+    // bind the off-chain order to this swap by its hash, which is what observers can match against
+    if let Some(commitment) = msg.order_commitment {
+        let digest = Sha256::digest(commitment.as_slice());
+        res = res.add_attribute("order_commitment", hex::encode(digest));
+    }
+    // tag the swap's share of the pooled funds, for reconciliation
+    Ok(res.add_attribute("escrow_id", escrow_id(&env.contract.address, &msg.id)))
+}
+
+
+/// Take the creation fee out of the native funds sent to create a swap, and keep it in the
+/// collected fees until withdrawn by the admin.
+/// # Arguments
+/// * `storage` - the contract storage
+/// * `funds`   - the native funds sent
+/// * `fee`     - the creation fee
+/// # Returns
+/// * the remaining funds
+/// * the error type Err if the funds do not cover the fee
+fn take_creation_fee(
+    storage : &mut dyn Storage,
+    funds   : NativeBalance,
+    fee     : &Coin,
+) -> Result<NativeBalance, ContractError> {
+    if !funds.has(fee) {
+        return Err(ContractError::CreationFeeRequired(fee.to_string()));
+    }
+    COLLECTED_FEES.update(storage, &fee.denom, |collected| -> StdResult<_> {
+        Ok(collected.unwrap_or_default() + fee.amount)
+    })?;
+    update_ledger(storage, &GenericBalance { native: vec![fee.clone()], cw20: vec![] }, true)?;
+    Ok((funds - fee.clone())?)
 }
 
 
@@ -176,7 +600,7 @@ pub fn execute_create(
 /// * `env`     - environment variables which include block information
 /// * `info`    - initiator's information (including their address and balance)
 /// * `wrapper` - the Cw20 receive message (including a sender, amount, and the create msg)
-///               it is wrapped in binary (as it appears so)
+///   it is wrapped in binary (as it appears so)
 /// # Returns
 /// * the execute response
 pub fn execute_receive(
@@ -186,9 +610,15 @@ pub fn execute_receive(
     wrapper : Cw20ReceiveMsg,
 ) -> Result<Response, ContractError> {
     let unwrapped: ReceiveMsg = from_binary(&wrapper.msg)?;
+    // tokens taxing transfers deliver less than the amount sent, so with the check on the swap
+    // only locks what the contract received - capped to the amount sent, should it hold extra
+    let amount = match CONFIG.load(deps.storage)?.verify_received {
+        true => received(deps.as_ref(), &env.contract.address, &info.sender)?.min(wrapper.amount),
+        false => wrapper.amount,
+    };
     let token = Cw20CoinVerified {
         address: info.sender,
-        amount : wrapper.amount,
+        amount,
     };
     // we need to update the info... so the original sender is the one authorizing with these tokens
     let org_info = MessageInfo {
@@ -198,18 +628,129 @@ pub fn execute_receive(
     // we unwrap the wrapper message such that we can call create again
     // once we've converted the Cw20 Receive Message to the Create Message, we can call create
     let ReceiveMsg::Create(msg) = unwrapped;
+    if msg.source.is_some() {
+        return Err(ContractError::SourceNotAllowed);
+    }
     execute_create(deps, env, org_info, msg, Balance::Cw20(token))
 }
 
 
+/// Create mirrored - create a swap, and its counter-swap on a partner atomic swap contract, in
+/// the same transaction. The counter-swap is created by a message to the partner, which reverts the
+/// whole transaction, including this swap, if it fails.
+/// # Arguments
+/// * `deps`             - mutable dependency which has the storage (state) of the chain
+/// * `env`              - environment variables which include block information
+/// * `info`             - message info, with the native funds of both swaps
+/// * `create`           - the create message of the swap, whose ask funds the counter-swap
+/// * `partner_contract` - the partner atomic swap contract
+/// * `partner_create`   - the create message of the counter-swap
+/// # Returns
+/// * the execute response on Ok
+/// * the error type on Err
+pub fn execute_create_mirrored(
+    deps             : DepsMut,
+    env              : Env,
+    info             : MessageInfo,
+    create           : CreateMsg,
+    partner_contract : String,
+    partner_create   : CreateMsg,
+) -> Result<Response, ContractError> {
+    let partner = deps.api.addr_validate(&partner_contract)?;
+
+    // the ask funds the counter-swap, and the rest of the funds this swap
+    let partner_funds = match (&create.ask_denom, create.ask_amount) {
+        (Some(denom), Some(amount)) => coin(amount.u128(), denom),
+        _ => return Err(ContractError::InvalidAsk),
+    };
+    let mut funds = NativeBalance(info.funds.clone());
+    funds.normalize();
+    let funds = (funds - partner_funds.clone()).map_err(|_| ContractError::InvalidAsk)?;
+
+    // the sender is the source of the counter-swap, rather than this contract
+    let partner_create = CreateMsg {
+        source: Some(info.sender.to_string()),
+        ..partner_create
+    };
+    let res = execute_create(deps, env, info, create, Balance::Native(funds))?;
+    let msg = WasmMsg::Execute {
+        contract_addr : partner.to_string(),
+        msg           : to_binary(&ExecuteMsg::CreateCounterSwap(partner_create))?,
+        funds         : vec![partner_funds],
+    };
+    Ok(res
+        .add_message(msg)
+        .add_attribute("partner_contract", partner))
+}
+
+
+/// Create counter-swap - create the counter-swap of a mirrored create, sent by the partner
+/// contract, refunded to the source it names. Only a contract of the same code as this one can
+/// designate the source, as it set it to the sender of the mirrored create.
+/// # Arguments
+/// * `deps` - mutable dependency which has the storage (state) of the chain
+/// * `env`  - environment variables which include block information
+/// * `info` - message info, the sender being the partner contract, with the ask as funds
+/// * `msg`  - the create message of the counter-swap
+/// # Returns
+/// * the execute response on Ok
+/// * the error type on Err
+pub fn execute_create_counter_swap(
+    deps : DepsMut,
+    env  : Env,
+    info : MessageInfo,
+    msg  : CreateMsg,
+) -> Result<Response, ContractError> {
+    let code_id = |addr: &str| deps.querier.query_wasm_contract_info(addr).map(|res| res.code_id);
+    let partner = code_id(info.sender.as_str()).map_err(|_| ContractError::Unauthorized {})?;
+    if partner != code_id(env.contract.address.as_str())? {
+        return Err(ContractError::Unauthorized {});
+    }
+    let sent_funds = Balance::from(info.funds.clone());
+    execute_create(deps, env, info, msg, sent_funds)
+}
+
+
+/// IBC hook create - create a swap from an ICS-20 transfer whose memo is addressed to this
+/// contract. The ibc-hooks middleware executes the memo's message with the transferred funds, from
+/// an intermediate address derived from the channel and the remote sender, which nobody controls -
+/// hence the swap must name the source it is refunded to.
+/// # Arguments
+/// * `deps` - mutable dependency which has the storage (state) of the chain
+/// * `env`  - environment variables which include block information
+/// * `info` - message info, the sender being the intermediate address, with the funds transferred
+/// * `msg`  - the swap creation message, from the transfer's memo
+/// # Returns
+/// * the execute response on Ok
+/// * the error type on Err
+pub fn execute_ibc_hook_create(
+    deps : DepsMut,
+    env  : Env,
+    info : MessageInfo,
+    msg  : CreateMsg,
+) -> Result<Response, ContractError> {
+    if msg.source.is_none() {
+        return Err(ContractError::IbcHookSourceRequired);
+    }
+    let sent_funds = Balance::from(info.funds.clone());
+    let intermediate = info.sender.clone();
+    let res = execute_create(deps, env, info, msg, sent_funds)?;
+    Ok(res
+        .add_attribute("via", "ibc_hook")
+        .add_attribute("intermediate_sender", intermediate))
+}
+
+
 /// Release - both ends have successfully locked their tokens.
 /// Since this is release phase, it can only be called when the preimage has indeed been publicized,
-/// which only occurs when both parties have locked their tokens on the smart contract.
+/// which only occurs when both parties have locked their tokens on the smart contract. Partial
+/// swaps can be released in installments, staying open until drained.
 /// # Arguments
 /// * `deps`     - mutable dependency which has the storage (state) of the chain
 /// * `env`      - environment variables which include block information
 /// * `id`       - sender's smart contract ID
 /// * `preimage` - the password before hashed to allow the release of tokens
+/// * `amount`   - the installment to release, the whole remaining balance if None
 /// # Returns
 /// * the execute response
 /// * the error type Err
@@ -218,36 +759,237 @@ pub fn execute_release(
     env      : Env,
     id       : String,
     preimage : String,
+    amount   : Option<Uint128>,
+) -> Result<Response, ContractError> {
+    release_swap(deps, env, id, preimage, amount, false)
+}
+
+
+/// Commit to the preimage of a swap, without revealing it - the first step of a commit-reveal
+/// release. A new commitment of the same sender replaces theirs.
+/// # Arguments
+/// * `deps`        - mutable dependency which has the storage (state) of the chain
+/// * `env`         - environment variables which include block information
+/// * `info`        - the committer's information
+/// * `id`          - human-readable swap id
+/// * `salted_hash` - hex-encoded sha-256 hash of the preimage followed by a salt
+/// # Returns
+/// * the execute response
+/// * the error type Err
+pub fn execute_commit_release(
+    deps        : DepsMut,
+    env         : Env,
+    info        : MessageInfo,
+    id          : String,
+    salted_hash : String,
 ) -> Result<Response, ContractError> {
-    let swap = SWAPS.load(deps.storage, &id)?;
+    let mut swap = swaps().load(deps.storage, &id)?;
     if swap.is_expired(&env.block) {
         return Err(ContractError::Expired {});
     }
+    if info.sender == swap.recipient && swap.reveal_by.is_none() {
+        swap.start_reveal_deadline(&env.block)?;
+        swaps().save(deps.storage, &id, &swap)?;
+    }
+    let commit = ReleaseCommit {
+        salted_hash : Binary(parse_hex_32(&salted_hash)?),
+        height      : env.block.height,
+    };
+    RELEASE_COMMITS.save(deps.storage, (&id, &info.sender), &commit)?;
+    Ok(Response::new()
+        .add_attribute("action", "commit_release")
+        .add_attribute("id", id)
+        .add_attribute("committer", info.sender))
+}
+
 
-    // check whether the preimage matches the hash or not
-    let hash = Sha256::digest(preimage.as_bytes());
-    if hash.as_slice() != swap.hash.as_slice() {
+/// Reveal the preimage committed to, and release the swap with it - the second step of a
+/// commit-reveal release, in a later block than the commitment.
+/// # Arguments
+/// * `deps`     - mutable dependency which has the storage (state) of the chain
+/// * `env`      - environment variables which include block information
+/// * `info`     - the committer's information
+/// * `id`       - human-readable swap id
+/// * `preimage` - the preimage committed to
+/// * `salt`     - the salt committed with it
+/// * `amount`   - the installment to release, the whole remaining balance if None
+/// # Returns
+/// * the execute response
+/// * the error type Err
+pub fn execute_reveal_release(
+    mut deps : DepsMut,
+    env      : Env,
+    info     : MessageInfo,
+    id       : String,
+    preimage : String,
+    salt     : String,
+    amount   : Option<Uint128>,
+) -> Result<Response, ContractError> {
+    let key = (id.as_str(), &info.sender);
+    let commit = RELEASE_COMMITS.may_load(deps.storage, key)?.ok_or(ContractError::NoCommit)?;
+    let reveal_height = commit.height + MIN_REVEAL_BLOCKS;
+    if env.block.height < reveal_height {
+        return Err(ContractError::RevealTooEarly(reveal_height));
+    }
+    let salted_hash = Sha256::digest([preimage.as_bytes(), salt.as_bytes()].concat().as_slice());
+    if salted_hash.as_slice() != commit.salted_hash.as_slice() {
         return Err(ContractError::InvalidPreimage {});
     }
+    let res = release_swap(deps.branch(), env, id.clone(), preimage, amount, true)?;
+    RELEASE_COMMITS.remove(deps.storage, (&id, &info.sender));
+    Ok(res.add_attribute("committer", info.sender))
+}
 
-    // Delete the swap on storage
-    SWAPS.remove(deps.storage, &id);
 
-    // Send the tokens out
-    let msgs = send_tokens(&swap.recipient, swap.balance)?;
-    Ok(Response::new()
+/// Release a swap with its preimage, or its oracle's attestation.
+/// # Arguments
+/// * `deps`      - mutable dependency which has the storage (state) of the chain
+/// * `env`       - environment variables which include block information
+/// * `id`        - human-readable swap id
+/// * `preimage`  - the preimage of the swap's hash
+/// * `amount`    - the installment to release, the whole remaining balance if None
+/// * `committed` - whether the preimage was committed to before being revealed
+/// # Returns
+/// * the execute response
+/// * the error type Err
+fn release_swap(
+    deps      : DepsMut,
+    env       : Env,
+    id        : String,
+    preimage  : String,
+    amount    : Option<Uint128>,
+    committed : bool,
+) -> Result<Response, ContractError> {
+    let mut swap = swaps().load(deps.storage, &id)?;
+    if swap.is_expired(&env.block) {
+        return Err(ContractError::Expired {});
+    }
+    if swap.accept_required && !swap.accepted {
+        return Err(ContractError::NotAccepted);
+    }
+    if swap.commit_reveal && !committed {
+        return Err(ContractError::CommitRequired);
+    }
+
+    // check whether the oracle attests the event, or otherwise the preimage matches the hash
+    match &swap.lock_type {
+        Some(LockType::Oracle { oracle_addr, event_id }) => {
+            let query = OracleQueryMsg::EventOccurred { event_id: event_id.clone() };
+            let res: EventOccurredResponse = deps.querier.query_wasm_smart(oracle_addr, &query)?;
+            if !res.occurred {
+                return Err(ContractError::EventNotAttested);
+            }
+        }
+        _ => {
+            let hash = hash_preimage(&HashAlgorithm::Sha256, &preimage);
+            if hash.as_slice() != swap.hash.as_slice() {
+                return Err(ContractError::InvalidPreimage {});
+            }
+        }
+    }
+
+    // Take the installment out of the swap, or its whole balance
+    let released = match amount {
+        Some(amount) if swap.partial => take_installment(&mut swap.balance, amount)?,
+        Some(_) => return Err(ContractError::InvalidPartial),
+        None => std::mem::take(&mut swap.balance),
+    };
+
+    // regulated venues only pay attested recipients out
+    let config = CONFIG.load(deps.storage)?;
+    assert_attested(deps.as_ref(), &config, &swap.recipient)?;
+
+    // Deduct the release fee, unless the recipient is exempt from it
+    let fee_bps = if FEE_EXEMPTIONS.has(deps.storage, &swap.recipient) {
+        0
+    } else {
+        config.release_fee_bps
+    };
+    let released_total = balance_to_string(&released);
+    let (payout, fee, deposit_left) = settle_fees(released, &swap.fee_payer, fee_bps);
+    let result = ReleaseResult {
+        id        : id.clone(),
+        recipient : swap.recipient.to_string(),
+        amount    : payout.clone().into(),
+    };
+
+    // Send the tokens out - to another chain if routed over IBC - and the fee to the admin
+    let mut msgs = match &swap.ibc_payout {
+        Some(route) => {
+            let GenericBalance { native, cw20 } = payout;
+            let mut msgs = ibc_transfers(&env, route, native);
+            let rest = GenericBalance { native: vec![], cw20 };
+            msgs.extend(payout_tokens(deps.storage, &config, &id, &swap, rest)?);
+            msgs
+        }
+        None => payout_tokens(deps.storage, &config, &id, &swap, payout)?,
+    };
+    msgs.extend(send_tokens(&config.admin, fee.clone(), None)?);
+
+    // the preimage is public from now on, so the hash can no longer lock new swaps
+    if !matches!(swap.lock_type, Some(LockType::Oracle { .. })) {
+        REVEALED_HASHES.save(deps.storage, swap.hash.as_slice(), &Empty {})?;
+    }
+
+    // Delete the swap on storage once drained, otherwise keep it open with what is left of its
+    // balance and of the fee payer's deposit, extending its expiration if it auto extends
+    let drained = swap.balance.is_empty();
+    let mut extension = 0;
+    if drained {
+        close_swap(deps.storage, &id, true)?;
+        // swaps migrated from older versions do not know when they were created
+        if let Some((created_height, _)) = swap.created_at {
+            let blocks = env.block.height.saturating_sub(created_height);
+            update_totals(deps.storage, |totals| {
+                totals.release_blocks = totals.release_blocks.saturating_add(blocks);
+                totals.timed_releases += 1;
+            })?;
+        }
+        msgs.extend(return_deposit(&swap.fee_payer, deposit_left)?);
+    } else {
+        if let Some(payer) = swap.fee_payer.as_mut() {
+            payer.deposit.amount = deposit_left.native.iter().map(|c| c.amount).sum();
+        }
+        extension = swap.auto_extend(config.latest_expiration(&env.block, &swap.expires));
+        save_swap(deps.storage, &id, &swap)?;
+        if !swap.private {
+            record_change(deps.storage, &id, SwapChangeKind::Released)?;
+        }
+    }
+    notify(deps.storage, &swap.source, InboxEventKind::Released, &id, env.block.height)?;
+    let mut res = Response::new()
         .add_submessages(msgs)
+        .set_data(to_binary(&result)?)
         .add_attribute("action", "release")
         .add_attribute("id", id)
         .add_attribute("preimage", preimage)
-        .add_attribute("to", swap.recipient.to_string()))
+        .add_attribute("to", swap.recipient.to_string())
+        .add_attribute("released", released_total);
+    if !fee.is_empty() {
+        res = res.add_attribute("fee", balance_to_string(&fee));
+    }
+    if !drained {
+        res = res.add_attribute("remaining", balance_to_string(&swap.balance));
+    }
+    if extension > 0 {
+        res = res.add_attribute("expires", swap.expires.to_string());
+    }
+    if let Some(order) = &swap.payout_order {
+        res = res.add_attribute("payout_order", order.join(","));
+    }
+    if let Some(mode) = &swap.payout_mode {
+        res = res.add_attribute("payout_mode", mode.as_str());
+    }
+    Ok(res)
 }
 
 
-/// Refund - refund can only occur when the swap has expired.
+/// Refund - refund can only occur when the swap has expired. Anyone can trigger it, and keepers
+/// (anyone other than the source) are paid the configured bounty out of the refunded tokens.
 /// # Arguments
 /// * `deps` - mutable dependency which has the storage (state) of the chain
 /// * `env`  - environment variables which include block information
+/// * `info` - the caller's information
 /// * `id`   - human-readable swap id
 /// # Returns
 /// * the execute response
@@ -255,204 +997,2239 @@ pub fn execute_release(
 pub fn execute_refund(
     deps : DepsMut, 
     env  : Env, 
+    info : MessageInfo,
     id   : String
 ) -> Result<Response, ContractError> {
-    let swap = SWAPS.load(deps.storage, &id)?;
+    let swap = swaps().load(deps.storage, &id)?;
 
-    // refund is not possible if the swap has not expired
-    if !swap.is_expired(&env.block) {
+    // refund is not possible if the swap has not expired - unless it missed its reveal deadline,
+    // in which case its source can take it back early
+    let early = info.sender == swap.source && swap.reveal_missed(&env.block);
+    if !swap.is_expired(&env.block) && !early {
         return Err(ContractError::NotExpired {});
     }
 
-    // We delete the swap
-    SWAPS.remove(deps.storage, &id);
+    // We delete the swap, and let both parties know it expired
+    close_swap(deps.storage, &id, false)?;
+    notify(deps.storage, &swap.source, InboxEventKind::Expired, &id, env.block.height)?;
+    if swap.recipient != swap.source {
+        notify(deps.storage, &swap.recipient, InboxEventKind::Expired, &id, env.block.height)?;
+    }
 
-    // and send the tokens back to the source (initiator)
-    let msgs = send_tokens(&swap.source, swap.balance)?;
-    Ok(Response::new()
+    // carve the keeper bounty out of the refund, unless the source refunds themselves
+    let config = CONFIG.load(deps.storage)?;
+    let bounty_bps = if info.sender == swap.source { 0 } else { config.refund_bounty_bps };
+    let refunded = balance_to_string(&swap.balance);
+    let (refund, bounty, deposit_left) = settle_fees(swap.balance, &swap.fee_payer, bounty_bps);
+    let result = RefundResult {
+        id     : id.clone(),
+        source : swap.source.to_string(),
+        amount : refund.clone().into(),
+    };
+
+    // and send the tokens back to the source (initiator), and the bounty to the keeper
+    let mut msgs = send_tokens(&swap.source, refund, None)?;
+    msgs.extend(send_tokens(&info.sender, bounty.clone(), None)?);
+    msgs.extend(return_deposit(&swap.fee_payer, deposit_left)?);
+    let mut res = Response::new()
         .add_submessages(msgs)
+        .set_data(to_binary(&result)?)
         .add_attribute("action", "refund")
         .add_attribute("id", id)
-        .add_attribute("to", swap.source.to_string()))
-}
-
-/// Parse hex 32-byte string to ensure that it is of correct format. Helper function so private.
-/// # Arguments
-/// * `data` - the 32-byte string
-/// # Returns
-/// * array of bytes (u8)
-/// * the error type Err
-fn parse_hex_32(data: &str) -> Result<Vec<u8>, ContractError> {
-    match hex::decode(data) {
-        Ok(bin) => 
-            if bin.len() == 32 { Ok(bin) } 
-            else { Err(ContractError::InvalidHash(bin.len() * 2)) }
-        Err(e) => Err(ContractError::ParseError(e.to_string())),
+        .add_attribute("to", swap.source.to_string())
+        .add_attribute("refunded", refunded);
+    if !bounty.is_empty() {
+        res = res
+            .add_attribute("keeper", info.sender)
+            .add_attribute("bounty", balance_to_string(&bounty));
     }
+    Ok(res)
 }
 
-
-/// Get the required messages for sending a specific amount of token already on the contract to the specified
-/// address. This is used when releasing the locked tokens, or refunding back to initiator.
+/// Force refund - since a swap that never expires cannot be refunded, the admin can return its
+/// tokens back to the source (initiator).
 /// # Arguments
-/// * `to`     - the specified destination address to send tokens to
-/// * `amount` - the balance on smart contract
+/// * `deps` - mutable dependency which has the storage (state) of the chain
+/// * `info` - sender's information, which must be the admin
+/// * `id`   - human-readable swap id
 /// # Returns
-/// * array of bytes (u8)
+/// * the execute response
 /// * the error type Err
-fn send_tokens(to: &Addr, amount: Balance) -> StdResult<Vec<SubMsg>> {
-    // sending zero amount
-    if amount.is_empty() {
-        Ok(vec![])
-    }
-    // sending some other amount
-    else {
-        match amount {
-
-            // native coin will simply use the standard Bank Send message (it is compatible to it)
-            Balance::Native(coins) => {
-                let msg = BankMsg::Send {
-                    to_address: to.into(),
-                    amount: coins.into_vec(),
-                };
-                Ok(vec![SubMsg::new(msg)])
-            }
+pub fn execute_force_refund(
+    deps : DepsMut,
+    info : MessageInfo,
+    id   : String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
 
-            // Cw20 coin will be sent in a different, more sophisticated way
-            // This has to do with how different smart contracts (even if internally logically) communicate
-            Balance::Cw20(coin) => {
-                let msg = Cw20ExecuteMsg::Transfer {
-                    recipient: to.into(),
-                    amount: coin.amount,
-                };
-                let exec = WasmMsg::Execute {
-                    contract_addr: coin.address.into(),
-                    msg: to_binary(&msg)?,
-                    funds: vec![],
-                };
-                Ok(vec![SubMsg::new(exec)])
-            }
-        }
+    // other swaps will eventually expire and be refundable by anyone
+    let swap = swaps().load(deps.storage, &id)?;
+    if !matches!(swap.expires, Expiration::Never {}) {
+        return Err(ContractError::NotNeverExpires);
     }
+
+    close_swap(deps.storage, &id, false)?;
+    let (refund, _, deposit_left) = settle_fees(swap.balance, &swap.fee_payer, 0);
+    let result = RefundResult {
+        id     : id.clone(),
+        source : swap.source.to_string(),
+        amount : refund.clone().into(),
+    };
+    let mut msgs = send_tokens(&swap.source, refund, None)?;
+    msgs.extend(return_deposit(&swap.fee_payer, deposit_left)?);
+    Ok(Response::new()
+        .add_submessages(msgs)
+        .set_data(to_binary(&result)?)
+        .add_attribute("action", "force_refund")
+        .add_attribute("id", id)
+        .add_attribute("to", swap.source.to_string()))
 }
 
 
-/// Query - there are 2 types of queries: listing and retrieving details of a specified smart contract
+/// Accept - the recipient of a swap requiring acceptance accepts it, so that it can be released.
+/// Swaps are not released to recipients who did not ask for them, like unsolicited phishing swaps.
 /// # Arguments
 /// * `deps` - mutable dependency which has the storage (state) of the chain
-/// * `_env` - environment variables which include block information
-/// * `msg`  - the query message
+/// * `env`  - environment variables which include block information
+/// * `info` - sender's information, which must be the swap's recipient
+/// * `id`   - human-readable swap id
 /// # Returns
-/// * array of bytes (u8)
+/// * the execute response
+/// * the error type Err
+pub fn execute_accept(
+    deps : DepsMut,
+    env  : Env,
+    info : MessageInfo,
+    id   : String,
+) -> Result<Response, ContractError> {
+    let mut swap = swaps().load(deps.storage, &id)?;
+    if info.sender != swap.recipient {
+        return Err(ContractError::Unauthorized {});
+    }
+    if !swap.accept_required {
+        return Err(ContractError::AcceptNotRequired);
+    }
+    if swap.accepted {
+        return Err(ContractError::AlreadyAccepted);
+    }
+    if swap.is_expired(&env.block) {
+        return Err(ContractError::Expired {});
+    }
+
+    swap.accepted = true;
+    swap.start_reveal_deadline(&env.block)?;
+    swaps().save(deps.storage, &id, &swap)?;
+    Ok(Response::new()
+        .add_attribute("action", "accept")
+        .add_attribute("id", id)
+        .add_attribute("by", info.sender))
+}
+
+
+/// Cancel - the source of a swap requiring acceptance takes it back before the recipient accepted
+/// it, without waiting for it to expire.
+/// # Arguments
+/// * `deps` - mutable dependency which has the storage (state) of the chain
+/// * `info` - sender's information, which must be the swap's source
+/// * `id`   - human-readable swap id
+/// # Returns
+/// * the execute response
+/// * the error type Err
+pub fn execute_cancel(
+    deps : DepsMut,
+    info : MessageInfo,
+    id   : String,
+) -> Result<Response, ContractError> {
+    let swap = swaps().load(deps.storage, &id)?;
+    if info.sender != swap.source {
+        return Err(ContractError::Unauthorized {});
+    }
+    if !swap.accept_required {
+        return Err(ContractError::AcceptNotRequired);
+    }
+    if swap.accepted {
+        return Err(ContractError::AlreadyAccepted);
+    }
+
+    close_swap(deps.storage, &id, false)?;
+    let (refund, _, deposit_left) = settle_fees(swap.balance, &swap.fee_payer, 0);
+    let result = RefundResult {
+        id     : id.clone(),
+        source : swap.source.to_string(),
+        amount : refund.clone().into(),
+    };
+    let mut msgs = send_tokens(&swap.source, refund, None)?;
+    msgs.extend(return_deposit(&swap.fee_payer, deposit_left)?);
+    Ok(Response::new()
+        .add_submessages(msgs)
+        .set_data(to_binary(&result)?)
+        .add_attribute("action", "cancel")
+        .add_attribute("id", id)
+        .add_attribute("to", swap.source.to_string()))
+}
+
+
+/// Attach counter proof - only the source can attach metadata on the matching swap on the
+/// counterparty chain, so that observers can correlate both swaps. It is informational only.
+/// # Arguments
+/// * `deps`          - mutable dependency which has the storage (state) of the chain
+/// * `info`          - sender's information, which must be the swap's source
+/// * `id`            - human-readable swap id
+/// * `counter_chain` - chain id of the counterparty chain, unchanged if None
+/// * `counter_tx`    - transaction creating the matching swap, unchanged if None
+/// # Returns
+/// * the execute response
+/// * the error type Err
+pub fn execute_attach_counter_proof(
+    deps          : DepsMut,
+    info          : MessageInfo,
+    id            : String,
+    counter_chain : Option<String>,
+    counter_tx    : Option<String>,
+) -> Result<Response, ContractError> {
+    let mut swap = swaps().load(deps.storage, &id)?;
+    if info.sender != swap.source {
+        return Err(ContractError::Unauthorized {});
+    }
+    let limits = CONFIG.load(deps.storage)?.limits;
+    for memo in [&counter_chain, &counter_tx].iter().copied().flatten() {
+        limits.check_memo(memo)?;
+    }
+
+    let mut res = Response::new()
+        .add_attribute("action", "attach_counter_proof")
+        .add_attribute("id", &id);
+    if let Some(chain) = counter_chain {
+        res = res.add_attribute("counter_chain", &chain);
+        swap.counter_chain = Some(chain);
+    }
+    if let Some(tx) = counter_tx {
+        res = res.add_attribute("counter_tx", &tx);
+        swap.counter_tx = Some(tx);
+    }
+    swaps().save(deps.storage, &id, &swap)?;
+    Ok(res)
+}
+
+
+/// Update config - only the admin can update the contract configuration.
+/// # Arguments
+/// * `deps` - mutable dependency which has the storage (state) of the chain
+/// * `info` - sender's information, which must be the admin
+/// * `msg`  - the update config message, only the set fields are updated
+/// # Returns
+/// * the execute response
+/// * the error type Err
+pub fn execute_update_config(
+    deps : DepsMut,
+    info : MessageInfo,
+    msg  : UpdateConfigMsg,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if let Some(admin) = msg.admin {
+        config.admin = deps.api.addr_validate(&admin)?;
+    }
+    // a cap of 0 is used to remove the cap entirely
+    if let Some(cap) = msg.max_open_swaps {
+        config.max_open_swaps = if cap == 0 { None } else { Some(cap) };
+    }
+    if let Some(fee_bps) = msg.release_fee_bps {
+        if fee_bps > MAX_FEE_BPS {
+            return Err(ContractError::InvalidFee(fee_bps));
+        }
+        config.release_fee_bps = fee_bps;
+    }
+    if let Some(allow) = msg.allow_never_expires {
+        config.allow_never_expires = allow;
+    }
+    // a zero amount is used to remove the creation fee entirely
+    if let Some(fee) = msg.creation_fee {
+        config.creation_fee = if fee.amount.is_zero() { None } else { Some(fee) };
+    }
+    if let Some(allow) = msg.allow_revealed_hashes {
+        config.allow_revealed_hashes = allow;
+    }
+    if let Some(telemetry) = msg.telemetry {
+        config.telemetry = telemetry;
+    }
+    // an empty address is used to remove the price oracle entirely
+    if let Some(oracle) = msg.price_oracle {
+        config.price_oracle = match oracle.is_empty() {
+            true => None,
+            false => Some(deps.api.addr_validate(&oracle)?),
+        };
+    }
+    // a policy of 0 attempts is used to remove the payout retries entirely
+    if let Some(retry) = msg.payout_retry {
+        config.payout_retry = if retry.max_attempts == 0 { None } else { Some(retry) };
+    }
+    if let Some(limits) = msg.limits {
+        limits.validate()?;
+        config.limits = limits;
+    }
+    if let Some(paused) = msg.creates_paused {
+        config.creates_paused = paused;
+    }
+    if let Some(verify) = msg.verify_received {
+        config.verify_received = verify;
+    }
+    if let Some(verbosity) = msg.event_verbosity {
+        config.event_verbosity = verbosity;
+    }
+    // a limit of 0 is used to remove the payout gas limit entirely
+    if let Some(gas_limit) = msg.payout_gas_limit {
+        config.payout_gas_limit = if gas_limit == 0 { None } else { Some(gas_limit) };
+    }
+    // an empty address is used to remove the attestation gate entirely
+    if let Some(attestation) = msg.attestation {
+        config.attestation = match attestation.is_empty() {
+            true => None,
+            false => Some(deps.api.addr_validate(&attestation)?),
+        };
+    }
+    // a duration of 0 blocks and 0 seconds is used to remove the maximum lock duration entirely
+    if let Some(max) = msg.max_lock_duration {
+        config.max_lock_duration = match max.blocks == 0 && max.seconds == 0 {
+            true => None,
+            false => Some(max),
+        };
+    }
+    if let Some(bounty_bps) = msg.refund_bounty_bps {
+        if bounty_bps > MAX_FEE_BPS {
+            return Err(ContractError::InvalidFee(bounty_bps));
+        }
+        config.refund_bounty_bps = bounty_bps;
+    }
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_config")
+        .add_attribute("admin", config.admin))
+}
+
+
+/// Withdraw fees - only the admin can withdraw the collected creation fees, which are sent to them.
+/// # Arguments
+/// * `deps` - mutable dependency which has the storage (state) of the chain
+/// * `info` - sender's information, which must be the admin
+/// # Returns
+/// * the execute response
+/// * the error type Err
+pub fn execute_withdraw_fees(
+    deps : DepsMut,
+    info : MessageInfo,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let fees = query_collected_fees(deps.as_ref())?.fees;
+    if fees.is_empty() {
+        return Err(ContractError::NoFees);
+    }
+    for fee in &fees {
+        COLLECTED_FEES.remove(deps.storage, &fee.denom);
+    }
+
+    let fees = GenericBalance { native: fees, cw20: vec![] };
+    update_ledger(deps.storage, &fees, false)?;
+    let msgs = send_tokens(&config.admin, fees.clone(), None)?;
+    Ok(Response::new()
+        .add_submessages(msgs)
+        .add_attribute("action", "withdraw_fees")
+        .add_attribute("amount", balance_to_string(&fees)))
+}
+
+
+/// Fee exemption - only the admin can add or remove recipients exempt from the release fee.
+/// # Arguments
+/// * `deps`    - mutable dependency which has the storage (state) of the chain
+/// * `info`    - sender's information, which must be the admin
+/// * `address` - the recipient address to exempt, or to remove the exemption of
+/// * `exempt`  - whether to add or remove the exemption
+/// # Returns
+/// * the execute response
+/// * the error type Err
+pub fn execute_set_fee_exemption(
+    deps    : DepsMut,
+    info    : MessageInfo,
+    address : String,
+    exempt  : bool,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let addr = deps.api.addr_validate(&address)?;
+    let action = if exempt {
+        FEE_EXEMPTIONS.save(deps.storage, &addr, &Empty {})?;
+        "add_fee_exemption"
+    } else {
+        FEE_EXEMPTIONS.remove(deps.storage, &addr);
+        "remove_fee_exemption"
+    };
+    Ok(Response::new()
+        .add_attribute("action", action)
+        .add_attribute("address", addr))
+}
+
+
+/// Set a gas sponsor - only the admin can register, update or remove sponsors. What is left of the
+/// budget of a sponsor removed, or whose grant changes denom, is refunded to it.
+/// # Arguments
+/// * `deps`    - mutable dependency which has the storage (state) of the chain
+/// * `info`    - sender's information, which must be the admin
+/// * `address` - the sponsor's address
+/// * `grant`   - the spend limit of each fee allowance granted, None to remove the sponsor
+/// # Returns
+/// * the execute response
+/// * the error type Err
+pub fn execute_set_sponsor(
+    deps    : DepsMut,
+    info    : MessageInfo,
+    address : String,
+    grant   : Option<Coin>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let addr = deps.api.addr_validate(&address)?;
+    let existing = SPONSORS.may_load(deps.storage, &addr)?;
+    let mut res = Response::new()
+        .add_attribute("action", "set_sponsor")
+        .add_attribute("address", addr.as_str());
+
+    // the budget left is refunded unless it stays in the grant's denom
+    let keep_budget = match (&existing, &grant) {
+        (Some(old), Some(new)) => old.grant.denom == new.denom,
+        _ => false,
+    };
+    if let Some(old) = &existing {
+        if !keep_budget && !old.budget.is_zero() {
+            let budget = GenericBalance {
+                native : vec![coin(old.budget.u128(), &old.grant.denom)],
+                cw20   : vec![],
+            };
+            update_ledger(deps.storage, &budget, false)?;
+            res = res.add_message(BankMsg::Send {
+                to_address : addr.to_string(),
+                amount     : budget.native,
+            });
+        }
+    }
+    match grant {
+        Some(grant) => {
+            if grant.amount.is_zero() {
+                return Err(ContractError::EmptyBalance {});
+            }
+            res = res.add_attribute("grant", grant.to_string());
+            let sponsor = match existing {
+                Some(old) if keep_budget => Sponsor { grant, ..old },
+                Some(old) => Sponsor { grant, budget: Uint128::zero(), ..old },
+                None => Sponsor {
+                    grant,
+                    budget  : Uint128::zero(),
+                    grants  : 0,
+                    granted : Uint128::zero(),
+                },
+            };
+            SPONSORS.save(deps.storage, &addr, &sponsor)?;
+        }
+        None => SPONSORS.remove(deps.storage, &addr),
+    }
+    Ok(res)
+}
+
+
+/// Fund a sponsor's budget - only a sponsor can fund its own, in the denom of its grant.
+/// # Arguments
+/// * `deps` - mutable dependency which has the storage (state) of the chain
+/// * `info` - sender's information, which must be a sponsor, with the funds sent
+/// # Returns
+/// * the execute response
+/// * the error type Err
+pub fn execute_fund_sponsor(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let mut sponsor = SPONSORS
+        .may_load(deps.storage, &info.sender)?
+        .ok_or(ContractError::NotSponsor)?;
+    let denom = sponsor.grant.denom.clone();
+    if info.funds.is_empty() || info.funds.iter().any(|c| c.denom != denom) {
+        return Err(ContractError::InvalidSponsorFunds(denom));
+    }
+    let amount: Uint128 = info.funds.iter().map(|c| c.amount).sum();
+    sponsor.budget += amount;
+    SPONSORS.save(deps.storage, &info.sender, &sponsor)?;
+    update_ledger(deps.storage, &GenericBalance { native: info.funds, cw20: vec![] }, true)?;
+    Ok(Response::new()
+        .add_attribute("action", "fund_sponsor")
+        .add_attribute("sponsor", info.sender)
+        .add_attribute("budget", coin(sponsor.budget.u128(), denom).to_string()))
+}
+
+
+/// Sponsored execution - execute a message as the sponsor, and grant the user a fee allowance out
+/// of the sponsor's budget, unless the user was granted one before. With the "stargate" feature,
+/// the contract grants the allowance from its own account, which the budget was funded to -
+/// otherwise the budget is left untouched, and the sponsor grants the allowance off chain.
+/// # Arguments
+/// * `deps` - mutable dependency which has the storage (state) of the chain
+/// * `env`  - environment variables which include block information
+/// * `info` - sender's information, which must be a sponsor
+/// * `user` - the user sponsored
+/// * `msg`  - the message executed as the sponsor
+/// # Returns
+/// * the execute response
+/// * the error type Err
+pub fn execute_sponsored(
+    deps : DepsMut,
+    env  : Env,
+    info : MessageInfo,
+    user : String,
+    msg  : ExecuteMsg,
+) -> Result<Response, ContractError> {
+    if let ExecuteMsg::Sponsored { .. } = msg {
+        return Err(ContractError::NestedSponsored);
+    }
+    let mut sponsor = SPONSORS
+        .may_load(deps.storage, &info.sender)?
+        .ok_or(ContractError::NotSponsor)?;
+    let user = deps.api.addr_validate(&user)?;
+
+    let granted = !SPONSORED_USERS.has(deps.storage, &user);
+    if granted {
+        // the budget only pays for the allowances the contract grants on chain - without the
+        // "stargate" feature, the sponsor grants them off chain, out of its own account
+        if cfg!(feature = "stargate") {
+            if sponsor.budget < sponsor.grant.amount {
+                return Err(ContractError::SponsorBudgetExhausted { budget: sponsor.budget });
+            }
+            sponsor.budget -= sponsor.grant.amount;
+            let grant = GenericBalance { native: vec![sponsor.grant.clone()], cw20: vec![] };
+            update_ledger(deps.storage, &grant, false)?;
+        }
+        sponsor.grants += 1;
+        sponsor.granted += sponsor.grant.amount;
+        SPONSORS.save(deps.storage, &info.sender, &sponsor)?;
+        SPONSORED_USERS.save(deps.storage, &user, &info.sender)?;
+    }
+
+    #[cfg(feature = "stargate")]
+    let contract = env.contract.address.clone();
+    let sponsor_addr = info.sender.clone();
+    let mut res = dispatch(deps, env, info, msg)?
+        .add_attribute("sponsor", sponsor_addr)
+        .add_attribute("sponsored_user", user.as_str());
+    if granted {
+        res = res.add_attribute("fee_grant", sponsor.grant.to_string());
+        #[cfg(feature = "stargate")]
+        {
+            let grant = crate::feegrant::grant_allowance_msg(&contract, &user, &sponsor.grant);
+            res = res.add_message(grant);
+        }
+    }
+    Ok(res)
+}
+
+
+/// Acknowledge inbox events - the sender marks events of their inbox as read, removing them.
+/// # Arguments
+/// * `deps` - mutable dependency which has the storage (state) of the chain
+/// * `info` - sender's information, whose inbox the events are in
+/// * `ids`  - ids of the events read
+/// # Returns
+/// * the execute response
+/// * the error type Err
+pub fn execute_ack_inbox(
+    deps : DepsMut,
+    info : MessageInfo,
+    ids  : Vec<u64>,
+) -> Result<Response, ContractError> {
+    let mut acked = 0u64;
+    for id in ids {
+        if INBOX.has(deps.storage, (&info.sender, id)) {
+            INBOX.remove(deps.storage, (&info.sender, id));
+            acked += 1;
+        }
+    }
+    Ok(Response::new()
+        .add_attribute("action", "ack_inbox")
+        .add_attribute("acked", acked.to_string()))
+}
+
+
+/// Verify ledger - compare the funds of a denom the contract holds with those its ledger expects it
+/// to. Holding more is harmless, i.e. funds sent to the contract directly, but holding less means
+/// the accounting drifted: creates are paused until the admin resumes them, and an alert event is
+/// emitted for the admin. The check itself succeeds either way, so that the pause is kept.
+/// # Arguments
+/// * `deps`  - mutable dependency which has the storage (state) of the chain
+/// * `env`   - environment variables, with this contract's address
+/// * `denom` - the native denom verified
+/// # Returns
+/// * the execute response
+/// * the error type Err
+pub fn execute_verify_ledger(
+    deps  : DepsMut,
+    env   : Env,
+    denom : String,
+) -> Result<Response, ContractError> {
+    let expected = NATIVE_LEDGER.may_load(deps.storage, &denom)?.unwrap_or_default();
+    let held = deps.querier.query_balance(&env.contract.address, &denom)?.amount;
+    let res = Response::new()
+        .add_attribute("action", "verify_ledger")
+        .add_attribute("denom", &denom)
+        .add_attribute("expected", expected)
+        .add_attribute("held", held);
+    if held >= expected {
+        return Ok(res.add_attribute("drift", "false"));
+    }
+
+    let mut config = CONFIG.load(deps.storage)?;
+    config.creates_paused = true;
+    CONFIG.save(deps.storage, &config)?;
+    let alert = Event::new("ledger_drift")
+        .add_attribute("admin", config.admin)
+        .add_attribute("denom", denom)
+        .add_attribute("shortfall", expected - held);
+    Ok(res
+        .add_attribute("drift", "true")
+        .add_event(alert))
+}
+
+
+/// Remove a swap that has been settled (released or refunded), retaining its id by how it was
+/// settled and recording the change unless it is private, and update the swap counters.
+/// # Arguments
+/// * `storage`  - the contract storage
+/// * `id`       - human-readable swap id
+/// * `released` - whether the swap was released, or refunded
+/// # Returns
+/// * the error type Err
+fn close_swap(storage: &mut dyn Storage, id: &str, released: bool) -> StdResult<()> {
+    let swap = swaps().load(storage, id)?;
+    swaps().remove(storage, id)?;
+    update_ledger(storage, &swap_holdings(&swap), false)?;
+    // the commits left go with the swap, so that none is revealed against a swap reusing its id
+    let committers = RELEASE_COMMITS
+        .prefix(id)
+        .keys(storage, None, None, Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for committer in &committers {
+        RELEASE_COMMITS.remove(storage, (id, committer));
+    }
+    if !swap.private {
+        let settled = if released { "released" } else { "refunded" };
+        SETTLED_SWAPS.save(storage, (settled, id), &Empty {})?;
+        let kind = if released { SwapChangeKind::Released } else { SwapChangeKind::Refunded };
+        record_change(storage, id, kind)?;
+    }
+    OPEN_SWAPS.update(storage, |open| -> StdResult<_> { Ok(open.saturating_sub(1)) })?;
+    update_totals(storage, |totals| match released {
+        true => totals.released += 1,
+        false => totals.refunded += 1,
+    })
+}
+
+
+/// Update the lifetime totals of the swaps.
+fn update_totals(storage: &mut dyn Storage, update: impl FnOnce(&mut SwapTotals)) -> StdResult<()> {
+    let mut totals = SWAP_TOTALS.may_load(storage)?.unwrap_or_default();
+    update(&mut totals);
+    SWAP_TOTALS.save(storage, &totals)
+}
+
+
+/// Record the latest change of a public swap's state, dropping its previous change so that each
+/// swap is synced once, with its current state.
+/// # Arguments
+/// * `storage` - the contract storage
+/// * `id`      - human-readable swap id
+/// * `kind`    - kind of the change
+/// # Returns
+/// * the error type Err
+fn record_change(storage: &mut dyn Storage, id: &str, kind: SwapChangeKind) -> StdResult<()> {
+    let seq = CHANGE_SEQ.may_load(storage)?.unwrap_or_default() + 1;
+    CHANGE_SEQ.save(storage, &seq)?;
+    if let Some(previous) = LAST_CHANGES.may_load(storage, id)? {
+        SWAP_CHANGES.remove(storage, previous);
+    }
+    LAST_CHANGES.save(storage, id, &seq)?;
+    SWAP_CHANGES.save(storage, seq, &SwapChange { swap_id: id.to_string(), kind })
+}
+
+
+/// Notify a party of a swap event in its inbox, dropping its oldest unread event if full.
+/// # Arguments
+/// * `storage` - the contract storage
+/// * `address` - the party notified
+/// * `kind`    - kind of the event
+/// * `swap_id` - human-readable swap id
+/// * `height`  - block height the event happened at
+/// # Returns
+/// * the error type Err
+fn notify(
+    storage : &mut dyn Storage,
+    address : &Addr,
+    kind    : InboxEventKind,
+    swap_id : &str,
+    height  : u64,
+) -> StdResult<()> {
+    let id = INBOX_COUNT.may_load(storage)?.unwrap_or_default() + 1;
+    INBOX_COUNT.save(storage, &id)?;
+    let event = InboxEvent { kind, swap_id: swap_id.to_string(), height };
+    INBOX.save(storage, (address, id), &event)?;
+
+    let unread = INBOX
+        .prefix(address)
+        .keys(storage, None, None, Ascending)
+        .take(MAX_INBOX + 1)
+        .collect::<StdResult<Vec<_>>>()?;
+    if unread.len() > MAX_INBOX {
+        INBOX.remove(storage, (address, unread[0]));
+    }
+    Ok(())
+}
+
+
+/// Hash a preimage with the given algorithm - the single place where hashlocks are computed, so
+/// that release and the hash preview query always agree.
+/// # Arguments
+/// * `algorithm` - the hash algorithm
+/// * `preimage`  - the preimage, hashed as its raw UTF-8 bytes
+/// # Returns
+///   The hash bytes
+fn hash_preimage(algorithm: &HashAlgorithm, preimage: &str) -> Vec<u8> {
+    match algorithm {
+        HashAlgorithm::Sha256 => Sha256::digest(preimage.as_bytes()).to_vec(),
+    }
+}
+
+
+/// Deterministic escrow identifier of a swap, so that external systems can tag the funds of each
+/// swap even though they are pooled in the contract's single address.
+/// # Arguments
+/// * `contract` - the contract address
+/// * `id`       - the swap id
+/// # Returns
+///   Hex-encoded sha-256 hash of the contract address followed by the swap id
+pub fn escrow_id(contract: &Addr, id: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.input(contract.as_bytes());
+    hasher.input(id.as_bytes());
+    hex::encode(hasher.result())
+}
+
+
+/// Split a balance into the payout and the fee (or bounty) taken from it.
+/// # Arguments
+/// * `balance` - the balance to split
+/// * `fee_bps` - the fee in basis points
+/// # Returns
+///   The payout balance, and the fee balance (both without zero amounts)
+fn split_fee(balance: GenericBalance, fee_bps: u64) -> (GenericBalance, GenericBalance) {
+    let fee_of = |amount: Uint128| amount.multiply_ratio(fee_bps, MAX_FEE_BPS);
+    let mut payout = GenericBalance::default();
+    let mut fee = GenericBalance::default();
+    for coin in balance.native {
+        let coin_fee = fee_of(coin.amount);
+        if coin.amount > coin_fee {
+            payout.native.push(Coin { denom: coin.denom.clone(), amount: coin.amount - coin_fee });
+        }
+        if !coin_fee.is_zero() {
+            fee.native.push(Coin { denom: coin.denom, amount: coin_fee });
+        }
+    }
+    for coin in balance.cw20 {
+        let coin_fee = fee_of(coin.amount);
+        if coin.amount > coin_fee {
+            let amount = coin.amount - coin_fee;
+            payout.cw20.push(Cw20CoinVerified { address: coin.address.clone(), amount });
+        }
+        if !coin_fee.is_zero() {
+            fee.cw20.push(Cw20CoinVerified { address: coin.address, amount: coin_fee });
+        }
+    }
+    (payout, fee)
+}
+
+
+/// Take an installment out of the balance of a partial swap, which holds a single coin or token.
+/// # Arguments
+/// * `balance` - the swap's balance, reduced by the installment
+/// * `amount`  - the installment, at most the balance
+/// # Returns
+/// * the installment on Ok
+/// * the error type on Err
+fn take_installment(
+    balance : &mut GenericBalance,
+    amount  : Uint128,
+) -> Result<GenericBalance, ContractError> {
+    let held = match (balance.native.as_mut_slice(), balance.cw20.as_mut_slice()) {
+        ([coin], []) => &mut coin.amount,
+        ([], [coin]) => &mut coin.amount,
+        _ => return Err(ContractError::InvalidPartial),
+    };
+    if amount.is_zero() || amount > *held {
+        return Err(ContractError::InvalidInstallment);
+    }
+    *held -= amount;
+
+    // the installment is the same single coin or token, for the amount taken
+    let mut installment = balance.clone();
+    installment.native.iter_mut().for_each(|coin| coin.amount = amount);
+    installment.cw20.iter_mut().for_each(|coin| coin.amount = amount);
+    Ok(installment)
+}
+
+
+/// Settle the fee of a closing swap - out of the fee payer's deposit if there is one, capped at
+/// the deposit, and out of the balance otherwise.
+/// # Arguments
+/// * `balance`   - the swap's balance
+/// * `fee_payer` - the swap's fee payer, if any
+/// * `fee_bps`   - fee in basis points of the balance
+/// # Returns
+///   What is left of the balance, the fee, and what is left of the deposit
+fn settle_fees(
+    balance   : GenericBalance,
+    fee_payer : &Option<FeePayer>,
+    fee_bps   : u64,
+) -> (GenericBalance, GenericBalance, GenericBalance) {
+    let payer = match fee_payer {
+        Some(payer) => payer,
+        None => {
+            let (rest, fee) = split_fee(balance, fee_bps);
+            return (rest, fee, GenericBalance::default());
+        }
+    };
+    let principal: Uint128 = balance.native
+        .iter()
+        .filter(|coin| coin.denom == payer.deposit.denom)
+        .map(|coin| coin.amount)
+        .sum();
+    let fee = principal.multiply_ratio(fee_bps, MAX_FEE_BPS).min(payer.deposit.amount);
+    let of_deposit = |amount: Uint128| {
+        match amount.is_zero() {
+            true => GenericBalance::default(),
+            false => GenericBalance {
+                native: vec![coin(amount.u128(), &payer.deposit.denom)],
+                cw20: vec![],
+            },
+        }
+    };
+    (balance, of_deposit(fee), of_deposit(payer.deposit.amount - fee))
+}
+
+
+/// Return what is left of a fee payer's deposit to them.
+/// # Arguments
+/// * `fee_payer` - the swap's fee payer, if any
+/// * `left`      - what is left of the deposit
+/// # Returns
+///   The messages sending it back
+fn return_deposit(fee_payer: &Option<FeePayer>, left: GenericBalance) -> StdResult<Vec<SubMsg>> {
+    match fee_payer {
+        Some(payer) => send_tokens(&Addr::unchecked(&payer.address), left, None),
+        None => Ok(vec![]),
+    }
+}
+
+
+/// Format a balance for event attributes, in the same way as coins (i.e. `100utoken,5uatom`), with
+/// the token contract address as denom for Cw20 balances.
+/// # Arguments
+/// * `balance` - the balance to format
+/// # Returns
+///   The formatted balance
+fn balance_to_string(balance: &GenericBalance) -> String {
+    balance.native
+        .iter()
+        .map(|coin| coin.to_string())
+        .chain(balance.cw20.iter().map(|coin| format!("{}{}", coin.amount, coin.address)))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+
+/// Get the ICS-20 transfer messages delivering native coins along an IBC payout route, one per
+/// coin. The timeout is counted from the current block.
+/// # Arguments
+/// * `env`   - environment variables which include block information
+/// * `route` - the IBC payout route
+/// * `coins` - the native coins to transfer
+/// # Returns
+///   The transfer messages
+fn ibc_transfers(env: &Env, route: &IbcPayout, coins: Vec<Coin>) -> Vec<SubMsg> {
+    let timeout = IbcTimeout::with_timestamp(env.block.time.plus_seconds(route.timeout_seconds));
+    coins
+        .into_iter()
+        .map(|amount| SubMsg::new(IbcMsg::Transfer {
+            channel_id: route.channel.clone(),
+            to_address: route.remote_recipient.clone(),
+            amount,
+            timeout: timeout.clone(),
+        }))
+        .collect()
+}
+
+
+/// Get the required messages for sending a specific amount of token already on the contract to the specified
+/// address. This is used when releasing the locked tokens, or refunding back to initiator.
+/// # Arguments
+/// * `to`         - the specified destination address to send tokens to
+/// * `amount`     - the balance on smart contract
+/// * `payout_msg` - if set, Cw20 tokens are sent with `Send` and this message rather than `Transfer`
+/// # Returns
+/// * array of bytes (u8)
+/// * the error type Err
+fn send_tokens(
+    to         : &Addr,
+    amount     : GenericBalance,
+    payout_msg : Option<Binary>,
+) -> StdResult<Vec<SubMsg>> {
+    let mut msgs = vec![];
+
+    // native coins will simply use the standard Bank Send message (it is compatible to it), zero
+    // amounts being skipped
+    let coins: Vec<Coin> = amount.native.into_iter().filter(|c| !c.amount.is_zero()).collect();
+    if !coins.is_empty() {
+        msgs.push(SubMsg::new(BankMsg::Send { to_address: to.into(), amount: coins }));
+    }
+
+    // Cw20 coins will be sent in a different, more sophisticated way
+    // This has to do with how different smart contracts (even if internally logically) communicate
+    // If a payout message is attached, use Send so that the recipient contract's receive
+    // hook is triggered (i.e. payouts directly into a vault or DEX contract)
+    for coin in amount.cw20.into_iter().filter(|c| !c.amount.is_zero()) {
+        msgs.push(SubMsg::new(cw20_transfer(to, coin, &payout_msg)?));
+    }
+    Ok(msgs)
+}
+
+
+/// Get the message transferring a Cw20 token held by the contract, with `Send` if a payout message
+/// is attached and `Transfer` otherwise.
+fn cw20_transfer(
+    to         : &Addr,
+    coin       : Cw20CoinVerified,
+    payout_msg : &Option<Binary>,
+) -> StdResult<WasmMsg> {
+    let msg = match payout_msg {
+        Some(msg) => Cw20ExecuteMsg::Send {
+            contract: to.into(),
+            amount: coin.amount,
+            msg: msg.clone(),
+        },
+        None => Cw20ExecuteMsg::Transfer {
+            recipient: to.into(),
+            amount: coin.amount,
+        },
+    };
+    Ok(WasmMsg::Execute {
+        contract_addr: coin.address.into(),
+        msg: to_binary(&msg)?,
+        funds: vec![],
+    })
+}
+
+
+/// An asset of a released balance, paid out on its own
+enum PayoutAsset {
+    Native(Vec<Coin>),
+    Cw20(Cw20CoinVerified),
+}
+
+impl PayoutAsset {
+    /// The native denom or Cw20 contract address the asset is listed by in payout orders
+    fn key(&self) -> &str {
+        match self {
+            PayoutAsset::Native(coins) => coins.first().map_or("", |coin| coin.denom.as_str()),
+            PayoutAsset::Cw20(token) => token.address.as_str(),
+        }
+    }
+}
+
+
+/// Split a released balance into the assets paid out, skipping zero amounts. Without a payout
+/// order, the native coins are paid out together, followed by each Cw20 token. With one, each
+/// coin is paid out on its own, the assets listed first in their listed order.
+fn payout_assets(amount: GenericBalance, order: &Option<Vec<String>>) -> Vec<PayoutAsset> {
+    let native = amount.native.into_iter().filter(|c| !c.amount.is_zero());
+    let cw20 = amount.cw20.into_iter().filter(|c| !c.amount.is_zero()).map(PayoutAsset::Cw20);
+    let order = match order {
+        Some(order) => order,
+        None => {
+            let coins: Vec<Coin> = native.collect();
+            let mut assets = vec![];
+            if !coins.is_empty() {
+                assets.push(PayoutAsset::Native(coins));
+            }
+            assets.extend(cw20);
+            return assets;
+        }
+    };
+    let mut assets: Vec<PayoutAsset> = native
+        .map(|coin| PayoutAsset::Native(vec![coin]))
+        .chain(cw20)
+        .collect();
+    // stable, so that the assets not listed keep their default order
+    assets.sort_by_key(|asset| {
+        order.iter().position(|key| key == asset.key()).unwrap_or(order.len())
+    });
+    assets
+}
+
+
+/// Get the messages paying a released balance out to the swap's recipient, in the swap's payout
+/// order if it has one. Unless the swap's payouts are atomic, each Cw20 payout is tracked as
+/// pending and sent as a submessage replying to the contract when the swap's payouts are best
+/// effort, or with a payout retry policy, a payout gas limit or a fallback recipient, so that its
+/// failure is caught and redirected to the fallback recipient or queued for retry, rather than
+/// failing the release. Cw20 payouts are sent with the payout gas limit, if any.
+/// # Arguments
+/// * `storage` - the contract storage, with the pending payouts
+/// * `config`  - the contract configuration, with the payout retry policy and gas limit
+/// * `id`      - the swap id
+/// * `swap`    - the swap released, with its recipient and payout message
+/// * `amount`  - the released balance to pay out
+/// # Returns
+/// * the payout messages
+/// * the standard error type Err
+fn payout_tokens(
+    storage : &mut dyn Storage,
+    config  : &Config,
+    id      : &str,
+    swap    : &AtomicSwap,
+    amount  : GenericBalance,
+) -> StdResult<Vec<SubMsg>> {
+    let tracked = match swap.payout_mode {
+        Some(PayoutMode::Atomic) => false,
+        Some(PayoutMode::BestEffort) => true,
+        None => {
+            config.payout_retry.is_some()
+                || config.payout_gas_limit.is_some()
+                || swap.fallback_recipient.is_some()
+        }
+    };
+    let mut msgs = vec![];
+    let mut count = PAYOUT_COUNT.may_load(storage)?.unwrap_or_default();
+    for asset in payout_assets(amount, &swap.payout_order) {
+        let token = match asset {
+            PayoutAsset::Native(coins) => {
+                let send = BankMsg::Send { to_address: swap.recipient.to_string(), amount: coins };
+                msgs.push(SubMsg::new(send));
+                continue;
+            }
+            PayoutAsset::Cw20(token) if !tracked => {
+                let exec = cw20_transfer(&swap.recipient, token, &swap.payout_msg)?;
+                msgs.push(limit_gas(SubMsg::new(exec), config.payout_gas_limit));
+                continue;
+            }
+            PayoutAsset::Cw20(token) => token,
+        };
+        count += 1;
+        let exec = cw20_transfer(&swap.recipient, token.clone(), &swap.payout_msg)?;
+        msgs.push(limit_gas(SubMsg::reply_always(exec, count), config.payout_gas_limit));
+        let payout = PendingPayout {
+            swap_id    : id.to_string(),
+            recipient  : swap.recipient.clone(),
+            token,
+            payout_msg : swap.payout_msg.clone(),
+            attempts   : 0,
+            retry_at   : 0,
+            fallback   : swap.fallback_recipient.clone(),
+        };
+        PENDING_PAYOUTS.save(storage, count, &payout)?;
+        update_ledger(storage, &GenericBalance { native: vec![], cw20: vec![payout.token] }, true)?;
+    }
+    PAYOUT_COUNT.save(storage, &count)?;
+    Ok(msgs)
+}
+
+
+/// Set the gas limit of a payout submessage, if any.
+fn limit_gas(msg: SubMsg, gas_limit: Option<u64>) -> SubMsg {
+    match gas_limit {
+        Some(gas_limit) => msg.with_gas_limit(gas_limit),
+        None => msg,
+    }
+}
+
+
+/// Retry payout - redrive a failed Cw20 payout. Anyone can retry it once its backoff delay passed,
+/// until the attempts of the retry policy are exhausted. From then on, only its recipient can, at
+/// any time. Retries are sent with the payout gas limit, if any, but for the recipient's own, who
+/// can give a payout that keeps running out of gas all the gas it needs.
+/// # Arguments
+/// * `deps` - mutable dependency which has the storage (state) of the chain
+/// * `env`  - environment variables which include block information
+/// * `info` - the caller's information
+/// * `id`   - the payout id
+/// # Returns
+/// * the execute response
+/// * the error type Err
+pub fn execute_retry_payout(
+    deps : DepsMut,
+    env  : Env,
+    info : MessageInfo,
+    id   : u64,
+) -> Result<Response, ContractError> {
+    let payout = PENDING_PAYOUTS.load(deps.storage, id)?;
+    let config = CONFIG.load(deps.storage)?;
+    let by_recipient = info.sender == payout.recipient;
+    if !by_recipient {
+        let max_attempts = config.payout_retry.map_or(0, |r| r.max_attempts);
+        if payout.attempts >= max_attempts {
+            return Err(ContractError::Unauthorized {});
+        }
+        if env.block.height < payout.retry_at {
+            return Err(ContractError::RetryTooEarly(payout.retry_at));
+        }
+    }
+
+    let exec = cw20_transfer(&payout.recipient, payout.token, &payout.payout_msg)?;
+    let gas_limit = if by_recipient { None } else { config.payout_gas_limit };
+    Ok(Response::new()
+        .add_submessage(limit_gas(SubMsg::reply_always(exec, id), gas_limit))
+        .add_attribute("action", "retry_payout")
+        .add_attribute("payout_id", id.to_string())
+        .add_attribute("attempt", (payout.attempts + 1).to_string()))
+}
+
+
+/// Reply - the outcome of a tracked Cw20 payout. A delivered payout is done with, while a failed
+/// one is redirected to the swap's fallback recipient if it has one, and otherwise waits to be
+/// retried, the delay doubling with each failure.
+/// # Arguments
+/// * `deps` - mutable dependency which has the storage (state) of the chain
+/// * `env`  - environment variables which include block information
+/// * `msg`  - the reply, whose id is the payout id
+/// # Returns
+/// * the response
+/// * the error type Err
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+    let mut payout = PENDING_PAYOUTS.load(deps.storage, msg.id)?;
+    let token = GenericBalance { native: vec![], cw20: vec![payout.token.clone()] };
+    let res = Response::new().add_attribute("payout_id", msg.id.to_string());
+    match msg.result {
+        SubMsgResult::Ok(_) => {
+            PENDING_PAYOUTS.remove(deps.storage, msg.id);
+            update_ledger(deps.storage, &token, false)?;
+            Ok(res
+                .add_attribute("action", "payout_delivered")
+                .add_attribute("path", "primary"))
+        }
+        SubMsgResult::Err(err) => {
+            // redirected with a plain transfer, which fails the release if it fails as well
+            if let Some(fallback) = payout.fallback {
+                PENDING_PAYOUTS.remove(deps.storage, msg.id);
+                update_ledger(deps.storage, &token, false)?;
+                let exec = cw20_transfer(&fallback, payout.token, &None)?;
+                return Ok(res
+                    .add_message(exec)
+                    .add_attribute("action", "payout_redirected")
+                    .add_attribute("path", "fallback")
+                    .add_attribute("to", fallback)
+                    .add_attribute("error", err));
+            }
+            let base_delay = CONFIG.load(deps.storage)?.payout_retry.map_or(0, |r| r.base_delay);
+            let backoff = 1u64.checked_shl(payout.attempts).unwrap_or(u64::MAX);
+            payout.attempts += 1;
+            payout.retry_at = env.block.height.saturating_add(base_delay.saturating_mul(backoff));
+            PENDING_PAYOUTS.save(deps.storage, msg.id, &payout)?;
+            Ok(res
+                .add_attribute("action", "payout_failed")
+                .add_attribute("attempts", payout.attempts.to_string())
+                .add_attribute("retry_at", payout.retry_at.to_string())
+                .add_attribute("error", err))
+        }
+    }
+}
+
+
+/// Query - there are 2 types of queries: listing and retrieving details of a specified smart contract
+/// # Arguments
+/// * `deps` - mutable dependency which has the storage (state) of the chain
+/// * `env`  - environment variables which include block information
+/// * `msg`  - the query message
+/// # Returns
+/// * array of bytes (u8)
 /// * the error type Err
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
 
-        // listing is retrieving the list of swaps starting after a specific point with a limit
-        QueryMsg::List {
-            start_after,
-            limit
-        } => to_binary(&query_list(deps, start_after, limit)?),
+        // listing is retrieving the list of swaps starting after a specific point with a limit
+        QueryMsg::List {
+            start_after,
+            limit,
+            status
+        } => to_binary(&query_list(deps, env, start_after, limit, status)?),
+
+        // listing the swaps between two counterparties, through the pair index
+        QueryMsg::ListByPair {
+            source,
+            recipient,
+            start_after,
+            limit
+        } => to_binary(&query_list_by_pair(deps, source, recipient, start_after, limit)?),
+
+        // details is simply the details of a swap, indexed by human-readable swap's id
+        QueryMsg::Details {
+            id
+        } => to_binary(&query_details(deps, env, id)?),
+
+        // details batch is the details of several swaps at once, up to the details batch limit
+        QueryMsg::DetailsBatch {
+            ids
+        } => to_binary(&query_details_batch(deps, env, ids)?),
+
+        // compute hash previews the hash of a preimage under all supported algorithms
+        QueryMsg::ComputeHash {
+            preimage
+        } => to_binary(&query_compute_hash(preimage)),
+
+        // config is the contract configuration, along with the current number of open swaps
+        QueryMsg::Config {} => to_binary(&query_config(deps)?),
+
+        // collected fees are the creation fees not yet withdrawn
+        QueryMsg::CollectedFees {} => to_binary(&query_collected_fees(deps)?),
+
+        // listing the swaps opened at least some time ago, i.e. to follow up on stale ones
+        QueryMsg::ListOlderThan {
+            age_seconds,
+            start_after,
+            limit
+        } => to_binary(&query_list_older_than(deps, env, age_seconds, start_after, limit)?),
+
+        // swaps about to expire, for alerting counterparties before they become refundable
+        QueryMsg::ExpiringWithin {
+            blocks,
+            seconds,
+            start_after,
+            limit
+        } => to_binary(&query_expiring_within(deps, env, blocks, seconds, start_after, limit)?),
+
+        // order book is the open swaps on a pair, by implied price
+        QueryMsg::OrderBook {
+            give_denom,
+            want_denom,
+            start_after,
+            limit
+        } => to_binary(&query_order_book(deps, env, give_denom, want_denom, start_after, limit)?),
+
+        // export is the list of open swaps with their raw stored fields, for audits
+        QueryMsg::Export {
+            start_after,
+            limit
+        } => to_binary(&query_export(deps, env, start_after, limit)?),
+
+        // state dump, to restore the open swaps into another instance
+        QueryMsg::DumpState {
+            start_after,
+            limit
+        } => to_binary(&query_dump_state(deps, start_after, limit)?),
+
+        // fee exemptions is the list of recipients exempt from the release fee
+        QueryMsg::FeeExemptions {
+            start_after,
+            limit
+        } => to_binary(&query_fee_exemptions(deps, start_after, limit)?),
+
+        // pending payouts are the failed Cw20 payouts waiting to be retried
+        QueryMsg::PendingPayouts {
+            start_after,
+            limit
+        } => to_binary(&query_pending_payouts(deps, start_after, limit)?),
+
+        // sponsors is the list of gas sponsors, with their budget and usage
+        QueryMsg::Sponsors {
+            start_after,
+            limit
+        } => to_binary(&query_sponsors(deps, start_after, limit)?),
+
+        // inbox is the unread events of the swaps an address is a party of
+        QueryMsg::Inbox {
+            address,
+            start_after,
+            limit
+        } => to_binary(&query_inbox(deps, address, start_after, limit)?),
+        QueryMsg::ChangedSince {
+            event_seq,
+            limit
+        } => to_binary(&query_changed_since(deps, event_seq, limit)?),
+        QueryMsg::Metrics {} => to_binary(&query_metrics(deps)?),
+    }
+}
+
+
+/// Querying the hashes of a preimage, so that counterparties can make sure both chains agree on
+/// the hashlock before funding their swaps.
+/// # Arguments
+/// * `preimage` - the preimage to hash
+/// # Returns
+///   The hash of the preimage under each supported algorithm
+fn query_compute_hash(preimage: String) -> ComputeHashResponse {
+    let hashes = HashAlgorithm::all()
+        .into_iter()
+        .map(|algorithm| HashPreview {
+            hash: hex::encode(hash_preimage(&algorithm, &preimage)),
+            algorithm,
+        })
+        .collect();
+    ComputeHashResponse { hashes }
+}
+
+
+/// Querying the contract configuration.
+/// # Arguments
+/// * `deps` - mutable dependency which has the storage (state) of the chain
+/// # Returns
+///   The configuration response
+fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    Ok(ConfigResponse {
+        admin          : config.admin.into(),
+        max_open_swaps : config.max_open_swaps,
+        open_swaps     : OPEN_SWAPS.load(deps.storage)?,
+        release_fee_bps: config.release_fee_bps,
+        allow_never_expires: config.allow_never_expires,
+        refund_bounty_bps: config.refund_bounty_bps,
+        creation_fee: config.creation_fee,
+        allow_revealed_hashes: config.allow_revealed_hashes,
+        price_oracle: config.price_oracle.map(Into::into),
+        telemetry: config.telemetry,
+        payout_retry: config.payout_retry,
+        limits: config.limits,
+        max_lock_duration: config.max_lock_duration,
+        creates_paused: config.creates_paused,
+        attestation: config.attestation.map(Into::into),
+        verify_received: config.verify_received,
+        event_verbosity: config.event_verbosity,
+        payout_gas_limit: config.payout_gas_limit,
+    })
+}
+
+
+/// Querying the settlement metrics of the swaps - how long swaps take to be released, and how many
+/// are refunded rather than released.
+/// # Arguments
+/// * `deps` - dependency which has the storage (state) of the chain
+/// # Returns
+///   The metrics response
+fn query_metrics(deps: Deps) -> StdResult<MetricsResponse> {
+    let totals = SWAP_TOTALS.may_load(deps.storage)?.unwrap_or_default();
+    let avg_release_blocks = match totals.timed_releases {
+        0 => None,
+        count => Some(Decimal::from_ratio(totals.release_blocks, count)),
+    };
+    let refund_rate = match totals.released + totals.refunded {
+        0 => None,
+        settled => Some(Decimal::from_ratio(totals.refunded, settled)),
+    };
+    Ok(MetricsResponse {
+        created        : totals.created,
+        released       : totals.released,
+        refunded       : totals.refunded,
+        release_blocks : totals.release_blocks,
+        timed_releases : totals.timed_releases,
+        avg_release_blocks,
+        refund_rate,
+    })
+}
+
+
+/// Querying details of a swap; query by its human-readable id.
+/// # Arguments
+/// * `deps` - mutable dependency which has the storage (state) of the chain
+/// * `env`  - environment variables, with this contract's address
+/// * `id`   - swap id
+/// # Returns
+///   The details of the swap
+fn query_details(deps: Deps, env: Env, id: String) -> StdResult<DetailsResponse> {
+    // load is a mapping method that takes in a storage and a key
+    // in this case, the id is the swap id named by the initiator, and value being AtomicSwap
+    // swaps() = IndexedMap<swap_id:String, pending:AtomicSwap>
+    let swap = swaps().load(deps.storage, &id)?;
+    let value = estimate_value(deps, &swap.balance)?;
+
+    // Convert balance to human balance
+    let balance_human = BalanceHuman::from(swap.balance);
+
+    // return the details of the swap
+    let details = DetailsResponse {
+        escrow_id: escrow_id(&env.contract.address, &id),
+        id,
+        hash: hex::encode(swap.hash.as_slice()),
+        recipient: swap.recipient.into(),
+        source: swap.source.into(),
+        expires: swap.expires,
+        balance: balance_human,
+        payout_msg: swap.payout_msg,
+        order_commitment: swap.order_commitment,
+        ibc_payout: swap.ibc_payout,
+        counter_chain: swap.counter_chain,
+        counter_tx: swap.counter_tx,
+        created_at: swap.created_at,
+        lock_type: swap.lock_type,
+        ask: swap.ask,
+        fee_payer: swap.fee_payer,
+        partial: swap.partial,
+        commit_reveal: swap.commit_reveal,
+        accept_required: swap.accept_required,
+        accepted: swap.accepted,
+        auto_extend: swap.auto_extend,
+        extended: swap.extended,
+        fallback_recipient: swap.fallback_recipient.map(Into::into),
+        private: swap.private,
+        reveal_deadline: swap.reveal_deadline,
+        reveal_by: swap.reveal_by,
+        payout_order: swap.payout_order,
+        payout_mode: swap.payout_mode,
+        value,
+    };
+    Ok(details)
+}
+
+
+/// Querying details of several swaps at once, by their human-readable ids.
+/// # Arguments
+/// * `deps` - dependency which has the storage (state) of the chain
+/// * `env`  - environment variables, with this contract's address
+/// * `ids`  - swap ids, at most the details batch limit
+/// # Returns
+///   The details of the swaps, in the order of the ids
+fn query_details_batch(deps: Deps, env: Env, ids: Vec<String>) -> StdResult<DetailsBatchResponse> {
+    CONFIG.load(deps.storage)?
+        .limits
+        .check_details_batch(ids.len())
+        .map_err(|err| StdError::generic_err(err.to_string()))?;
+    let swaps = ids
+        .into_iter()
+        .map(|id| query_details(deps, env.clone(), id))
+        .collect::<StdResult<_>>()?;
+    Ok(DetailsBatchResponse { swaps })
+}
+
+
+/// Estimate the value of a balance in the price oracle's base denom, at the current prices.
+/// # Arguments
+/// * `deps`    - dependency which has the storage (state) of the chain, and the querier
+/// * `balance` - the balance to value
+/// # Returns
+///   The value, or None if there is no price oracle or it fails to price one of the assets
+fn estimate_value(deps: Deps, balance: &GenericBalance) -> StdResult<Option<Uint128>> {
+    let oracle = match CONFIG.load(deps.storage)?.price_oracle {
+        Some(oracle) => oracle,
+        None => return Ok(None),
+    };
+    let assets = balance.native
+        .iter()
+        .map(|c| (c.denom.clone(), c.amount))
+        .chain(balance.cw20.iter().map(|t| (t.address.to_string(), t.amount)));
+    let mut value = Uint128::zero();
+    for (asset, amount) in assets {
+        let query = PriceQueryMsg::Price { asset };
+        let price = match deps.querier.query_wasm_smart::<PriceResponse>(&oracle, &query) {
+            Ok(res) => res.price,
+            Err(_) => return Ok(None),
+        };
+        let worth = amount.checked_multiply_ratio(price.atomics(), Decimal::one().atomics());
+        match worth.ok().and_then(|worth| value.checked_add(worth).ok()) {
+            Some(sum) => value = sum,
+            None => return Ok(None),
+        }
+    }
+    Ok(Some(value))
+}
+
+
+// Settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+
+/// Querying a list of swaps, either all open swaps or those of a status, private swaps left out.
+/// Settled swaps are listed from their own status-prefixed keys.
+/// # Arguments
+/// * `deps`        - mutable dependency which has the storage (state) of the chain
+/// * `env`         - environment variables which include block information
+/// * `start_after` - the starting point of query
+/// * `limit`       - the list size limit
+/// * `status`      - the status of the swaps listed, None for every open swap
+/// # Returns
+///   The list of responses
+fn query_list(
+    deps        : Deps,
+    env         : Env,
+    start_after : Option<String>,
+    limit       : Option<u32>,
+    status      : Option<SwapStatus>,
+) -> StdResult<ListResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.as_ref().map(|s| Bound::exclusive(s.as_str()));
+
+    let swaps = match status {
+        None | Some(SwapStatus::Open) | Some(SwapStatus::Expired) => {
+            let expired = status.as_ref().map(|status| *status == SwapStatus::Expired);
+            swaps()
+                .range(deps.storage, start, None, Ascending)
+                .filter(|item| match item {
+                    Ok((_, swap)) => {
+                        !swap.private
+                            && expired.is_none_or(|expired| swap.is_expired(&env.block) == expired)
+                    }
+                    Err(_) => true,
+                })
+                .take(limit)
+                .map(|item| item.map(|(id, _)| id))
+                .collect::<StdResult<_>>()?
+        }
+        Some(SwapStatus::Released) => settled_swap_ids(deps.storage, "released", start, limit)?,
+        Some(SwapStatus::Refunded) => settled_swap_ids(deps.storage, "refunded", start, limit)?,
+    };
+    Ok(ListResponse { swaps })
+}
 
-        // details is simply the details of a swap, indexed by human-readable swap's id
-        QueryMsg::Details {
-            id
-        } => to_binary(&query_details(deps, id)?),
-    }
+
+/// Querying the list of swaps from a source to a recipient, private swaps left out.
+/// # Arguments
+/// * `deps`        - mutable dependency which has the storage (state) of the chain
+/// * `source`      - the source of the swaps
+/// * `recipient`   - the recipient of the swaps
+/// * `start_after` - the starting point of query
+/// * `limit`       - the list size limit
+/// # Returns
+///   The list of swap ids
+fn query_list_by_pair(
+    deps        : Deps,
+    source      : String,
+    recipient   : String,
+    start_after : Option<String>,
+    limit       : Option<u32>,
+) -> StdResult<ListResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let pair = (deps.api.addr_validate(&source)?, deps.api.addr_validate(&recipient)?);
+    let start = start_after.map(Bound::exclusive);
+
+    let swaps = swaps()
+        .idx
+        .pair
+        .prefix(pair)
+        .range(deps.storage, start, None, Ascending)
+        .filter(|item| !matches!(item, Ok((_, swap)) if swap.private))
+        .take(limit)
+        .map(|item| item.map(|(id, _)| id))
+        .collect::<StdResult<_>>()?;
+    Ok(ListResponse { swaps })
 }
 
 
-/// Querying details of a swap; query by its human-readable id.
+/// Querying a list of swaps created at least some time ago, private swaps left out. Swaps whose
+/// creation is unknown are considered old enough.
 /// # Arguments
-/// * `deps` - mutable dependency which has the storage (state) of the chain
-/// * `id`   - swap id
+/// * `deps`        - mutable dependency which has the storage (state) of the chain
+/// * `env`         - environment variables which include block information
+/// * `age_seconds` - the minimum age of the swaps
+/// * `start_after` - the starting point of query
+/// * `limit`       - the list size limit
 /// # Returns
-///   The details of the swap
-fn query_details(deps: Deps, id: String) -> StdResult<DetailsResponse> {
-    // load is a mapping method that takes in a storage and a key
-    // in this case, the id is the swap id named by the initiator, and value being AtomicSwap
-    // SWAPS = Map<swap_id:String, pending:AtomicSwap>
-    let swap = SWAPS.load(deps.storage, &id)?;
+///   The list of swap ids
+fn query_list_older_than(
+    deps        : Deps,
+    env         : Env,
+    age_seconds : u64,
+    start_after : Option<String>,
+    limit       : Option<u32>,
+) -> StdResult<ListResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.as_ref().map(|s| Bound::exclusive(s.as_str()));
+    let cutoff = env.block.time.seconds().saturating_sub(age_seconds);
 
-    // Convert balance to human balance
-    let balance_human = match swap.balance {
-        Balance::Native(coins) => BalanceHuman::Native(coins.into_vec()),
-        Balance::Cw20(coin) => BalanceHuman::Cw20(Cw20Coin {
-            address: coin.address.into(),
-            amount: coin.amount,
-        }),
+    let swaps = swaps()
+        .range(deps.storage, start, None, Ascending)
+        .filter(|item| match item {
+            Ok((_, swap)) => {
+                !swap.private
+                    && swap.created_at.is_none_or(|(_, time)| time.seconds() <= cutoff)
+            }
+            Err(_) => true,
+        })
+        .take(limit)
+        .map(|item| item.map(|(id, _)| id))
+        .collect::<StdResult<_>>()?;
+    Ok(ListResponse { swaps })
+}
+
+
+/// Querying a list of swaps not expired yet, but expiring within a window from the current block,
+/// private swaps left out.
+/// Swaps expiring at a height are matched against the window in blocks, and those expiring at a
+/// time against the window in seconds.
+/// # Arguments
+/// * `deps`        - mutable dependency which has the storage (state) of the chain
+/// * `env`         - environment variables which include block information
+/// * `blocks`      - the window in blocks, None to leave out swaps expiring at a height
+/// * `seconds`     - the window in seconds, None to leave out swaps expiring at a time
+/// * `start_after` - the starting point of query
+/// * `limit`       - the list size limit
+/// # Returns
+///   The expiring swaps, and the current block
+fn query_expiring_within(
+    deps        : Deps,
+    env         : Env,
+    blocks      : Option<u64>,
+    seconds     : Option<u64>,
+    start_after : Option<String>,
+    limit       : Option<u32>,
+) -> StdResult<ExpiringWithinResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.as_ref().map(|s| Bound::exclusive(s.as_str()));
+    let block = &env.block;
+    let expiring = |expires: &Expiration| match (expires, blocks, seconds) {
+        (Expiration::AtHeight(height), Some(blocks), _) => {
+            *height > block.height && *height <= block.height.saturating_add(blocks)
+        }
+        (Expiration::AtTime(time), _, Some(seconds)) => {
+            *time > block.time && *time <= block.time.plus_seconds(seconds)
+        }
+        _ => false,
     };
 
-    // return the details of the swap
-    let details = DetailsResponse {
-        id,
-        hash: hex::encode(swap.hash.as_slice()),
-        recipient: swap.recipient.into(),
-        source: swap.source.into(),
-        expires: swap.expires,
-        balance: balance_human,
+    let swaps = swaps()
+        .range(deps.storage, start, None, Ascending)
+        .filter(|item| match item {
+            Ok((_, swap)) => !swap.private && expiring(&swap.expires),
+            Err(_) => true,
+        })
+        .take(limit)
+        .map(|item| item.map(|(id, swap)| ExpiringSwap { id, expires: swap.expires }))
+        .collect::<StdResult<_>>()?;
+    Ok(ExpiringWithinResponse {
+        height : block.height,
+        time   : block.time,
+        swaps,
+    })
+}
+
+
+/// Querying the order book of a pair - the open swaps giving a single denom and asking another in
+/// exchange, cheapest implied price first, then by id. Private swaps are left out.
+/// # Arguments
+/// * `deps`        - mutable dependency which has the storage (state) of the chain
+/// * `env`         - environment variables which include block information
+/// * `give_denom`  - the native denom or Cw20 address given by the swaps
+/// * `want_denom`  - the denom asked in exchange
+/// * `start_after` - the position in the book to start after
+/// * `limit`       - the list size limit
+/// # Returns
+///   The orders, and the position to start the next page after
+fn query_order_book(
+    deps        : Deps,
+    env         : Env,
+    give_denom  : String,
+    want_denom  : String,
+    start_after : Option<OrderBookCursor>,
+    limit       : Option<u32>,
+) -> StdResult<OrderBookResponse> {
+    // swaps which are not orders are indexed under empty denoms, out of any book
+    if give_denom.is_empty() || want_denom.is_empty() {
+        return Ok(OrderBookResponse { orders: vec![], next: None });
+    }
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|cursor| {
+        Bound::exclusive((cursor.price.atomics().to_be_bytes().to_vec(), cursor.id))
+    });
+
+    let mut orders = vec![];
+    let book = swaps().idx.book.sub_prefix((give_denom, want_denom));
+    for item in book.range(deps.storage, start, None, Ascending) {
+        if orders.len() == limit {
+            break;
+        }
+        let (id, swap) = item?;
+        if swap.is_expired(&env.block) {
+            continue;
+        }
+        let (give_amount, ask_amount) = match swap.order() {
+            Some((_, give_amount, ask)) => (give_amount, ask.amount),
+            None => continue,
+        };
+        orders.push(Order {
+            id,
+            give_amount,
+            ask_amount,
+            price: Decimal::from_ratio(ask_amount, give_amount),
+        });
+    }
+    let next = match orders.last() {
+        Some(order) if orders.len() == limit => {
+            Some(OrderBookCursor { price: order.price, id: order.id.clone() })
+        }
+        _ => None,
     };
-    Ok(details)
+    Ok(OrderBookResponse { orders, next })
 }
 
 
-// Settings for pagination
-const MAX_LIMIT: u32 = 30;
-const DEFAULT_LIMIT: u32 = 10;
+/// Querying the open swaps exactly as stored, along with their status, so that they can be
+/// reconciled off-chain.
+/// # Arguments
+/// * `deps`        - mutable dependency which has the storage (state) of the chain
+/// * `env`         - environment variables which include block information
+/// * `start_after` - the starting point of query
+/// * `limit`       - the list size limit
+/// # Returns
+///   The exported swaps
+fn query_export(
+    deps        : Deps,
+    env         : Env,
+    start_after : Option<String>,
+    limit       : Option<u32>,
+) -> StdResult<ExportResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.as_ref().map(|s| Bound::exclusive(s.as_str()));
 
-/// Querying a list of swaps
+    let swaps = swaps()
+        .range(deps.storage, start, None, Ascending)
+        .take(limit)
+        .map(|item| {
+            let (id, swap) = item?;
+            let status = if swap.is_expired(&env.block) {
+                SwapStatus::Expired
+            } else {
+                SwapStatus::Open
+            };
+            Ok(ExportedSwap { id, status, swap })
+        })
+        .collect::<StdResult<_>>()?;
+    Ok(ExportResponse { swaps })
+}
+
+
+/// Querying the open swaps as stored, to be restored into another instance.
 /// # Arguments
 /// * `deps`        - mutable dependency which has the storage (state) of the chain
 /// * `start_after` - the starting point of query
 /// * `limit`       - the list size limit
 /// # Returns
-///   The list of responses
-fn query_list(
+///   The swap entries
+fn query_dump_state(
     deps        : Deps,
     start_after : Option<String>,
     limit       : Option<u32>,
-) -> StdResult<ListResponse> {
+) -> StdResult<DumpStateResponse> {
     let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
     let start = start_after.as_ref().map(|s| Bound::exclusive(s.as_str()));
 
-    Ok(ListResponse {
-        swaps: all_swap_ids(deps.storage, start, limit)?,
+    let entries = swaps()
+        .range(deps.storage, start, None, Ascending)
+        .take(limit)
+        .map(|item| item.map(|(id, swap)| SwapEntry { id, swap }))
+        .collect::<StdResult<_>>()?;
+    Ok(DumpStateResponse { entries })
+}
+
+
+/// Querying the creation fees collected and not yet withdrawn.
+/// # Arguments
+/// * `deps` - mutable dependency which has the storage (state) of the chain
+/// # Returns
+///   The collected fees
+fn query_collected_fees(deps: Deps) -> StdResult<CollectedFeesResponse> {
+    let fees = COLLECTED_FEES
+        .range(deps.storage, None, None, Ascending)
+        .map(|item| item.map(|(denom, amount)| Coin { denom, amount }))
+        .collect::<StdResult<_>>()?;
+    Ok(CollectedFeesResponse { fees })
+}
+
+
+/// Querying a list of fee exempt recipients
+/// # Arguments
+/// * `deps`        - mutable dependency which has the storage (state) of the chain
+/// * `start_after` - the starting point of query
+/// * `limit`       - the list size limit
+/// # Returns
+///   The list of fee exempt addresses
+fn query_fee_exemptions(
+    deps        : Deps,
+    start_after : Option<String>,
+    limit       : Option<u32>,
+) -> StdResult<FeeExemptionsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_addr = start_after.map(|s| deps.api.addr_validate(&s)).transpose()?;
+    let start = start_addr.as_ref().map(Bound::exclusive);
+
+    Ok(FeeExemptionsResponse {
+        addresses: all_fee_exemptions(deps.storage, start, limit)?
+            .into_iter()
+            .map(String::from)
+            .collect(),
+    })
+}
+
+
+/// Querying the gas sponsors
+/// # Arguments
+/// * `deps`        - dependency which has the storage (state) of the chain
+/// * `start_after` - the starting point of query
+/// * `limit`       - the list size limit
+/// # Returns
+///   The list of sponsors, with their budget and usage
+fn query_sponsors(
+    deps        : Deps,
+    start_after : Option<String>,
+    limit       : Option<u32>,
+) -> StdResult<SponsorsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_addr = start_after.map(|s| deps.api.addr_validate(&s)).transpose()?;
+    let start = start_addr.as_ref().map(Bound::exclusive);
+
+    let sponsors = SPONSORS
+        .range(deps.storage, start, None, Ascending)
+        .take(limit)
+        .map(|item| {
+            item.map(|(address, sponsor)| SponsorResponse {
+                address : address.into(),
+                grant   : sponsor.grant,
+                budget  : sponsor.budget,
+                grants  : sponsor.grants,
+                granted : sponsor.granted,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+    Ok(SponsorsResponse { sponsors })
+}
+
+
+/// Querying the unread events in the inbox of an address, oldest first
+/// # Arguments
+/// * `deps`        - dependency which has the storage (state) of the chain
+/// * `address`     - the address whose inbox is queried
+/// * `start_after` - the event id to start after
+/// * `limit`       - the list size limit
+/// # Returns
+///   The list of unread events
+fn query_inbox(
+    deps        : Deps,
+    address     : String,
+    start_after : Option<u64>,
+    limit       : Option<u32>,
+) -> StdResult<InboxResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let address = deps.api.addr_validate(&address)?;
+    let start = start_after.map(Bound::exclusive);
+
+    let events = INBOX
+        .prefix(&address)
+        .range(deps.storage, start, None, Ascending)
+        .take(limit)
+        .map(|item| {
+            item.map(|(id, event)| InboxEventResponse {
+                id,
+                kind    : event.kind,
+                swap_id : event.swap_id,
+                height  : event.height,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+    Ok(InboxResponse { events })
+}
+
+
+/// Querying the public swaps changed after a change, each once with its latest change, in the
+/// order of their latest change
+/// # Arguments
+/// * `deps`      - dependency which has the storage (state) of the chain
+/// * `event_seq` - the sequence number of the change to start after, 0 for every swap
+/// * `limit`     - the list size limit
+/// # Returns
+///   The list of changes, and the sequence number of the latest change of all
+fn query_changed_since(
+    deps      : Deps,
+    event_seq : u64,
+    limit     : Option<u32>,
+) -> StdResult<ChangedSinceResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = Some(Bound::exclusive(event_seq));
+
+    let changes = SWAP_CHANGES
+        .range(deps.storage, start, None, Ascending)
+        .take(limit)
+        .map(|item| {
+            item.map(|(seq, change)| SwapChangeResponse {
+                seq,
+                id   : change.swap_id,
+                kind : change.kind,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+    Ok(ChangedSinceResponse {
+        changes,
+        last_seq : CHANGE_SEQ.may_load(deps.storage)?.unwrap_or_default(),
     })
 }
 
 
-/// Migrate atomic swap smart contract.
+/// Querying the failed Cw20 payouts waiting to be retried
+/// # Arguments
+/// * `deps`        - dependency which has the storage (state) of the chain
+/// * `start_after` - the payout id to start after
+/// * `limit`       - the list size limit
+/// # Returns
+///   The list of pending payouts
+fn query_pending_payouts(
+    deps        : Deps,
+    start_after : Option<u64>,
+    limit       : Option<u32>,
+) -> StdResult<PendingPayoutsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let payouts = PENDING_PAYOUTS
+        .range(deps.storage, start, None, Ascending)
+        .take(limit)
+        .map(|item| {
+            item.map(|(id, payout)| PendingPayoutResponse {
+                id,
+                swap_id   : payout.swap_id,
+                recipient : payout.recipient.into(),
+                token     : Cw20Coin {
+                    address : payout.token.address.into(),
+                    amount  : payout.token.amount,
+                },
+                attempts  : payout.attempts,
+                retry_at  : payout.retry_at,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+    Ok(PendingPayoutsResponse { payouts })
+}
+
+
+/// Close the swaps locked with the contract itself as their recipient or source, which could never
+/// be released nor refunded. Their funds go back to the source, or to `refund_to` if the source is
+/// the contract, and the fee payers get their deposit back.
+/// # Arguments
+/// * `storage`   - the storage (state) of the chain
+/// * `contract`  - this contract's address
+/// * `refund_to` - the address refunded instead of the contract
+/// # Returns
+///   The messages sending the funds back
+fn rescue_self_swaps(
+    storage   : &mut dyn Storage,
+    contract  : &Addr,
+    refund_to : &Addr,
+) -> StdResult<Vec<SubMsg>> {
+    let stuck = swaps()
+        .range(storage, None, None, Ascending)
+        .collect::<StdResult<Vec<_>>>()?
+        .into_iter()
+        .filter(|(_, swap)| swap.recipient == *contract || swap.source == *contract)
+        .collect::<Vec<_>>();
+
+    let mut msgs = vec![];
+    for (id, swap) in stuck {
+        close_swap(storage, &id, false)?;
+        let to = if swap.source == *contract { refund_to } else { &swap.source };
+        msgs.extend(send_tokens(to, swap.balance, None)?);
+        if let Some(payer) = &swap.fee_payer {
+            let deposit = GenericBalance { native: vec![payer.deposit.clone()], cw20: vec![] };
+            msgs.extend(return_deposit(&swap.fee_payer, deposit)?);
+        }
+    }
+    Ok(msgs)
+}
+
+
+/// Migrate atomic swap smart contract - upgrading an older version of it, converting a deployment
+/// of the upstream cw20-atomic-swap contract, restoring the swaps dumped from another instance, or
+/// rescuing the swaps locked with the contract itself.
 /// # Arguments
 /// * `deps` - mutable dependency which has the storage (state) of the chain
-/// * `_env` - environment variables which include block information
-/// * `_msg` - the Cw20-base Migrate message
+/// * `env`  - environment variables which include block information
+/// * `msg`  - the migrate message, with what is migrated from
 /// # Returns
 /// * migrate response on Ok
 /// * the error on Err
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn migrate(
     deps: DepsMut,
-    _env: Env,
-    _msg: MigrateMsg
+    env : Env,
+    msg : MigrateMsg
 ) -> Result<Response, ContractError> {
-    // Build reverse map of swaps
-    let data = OLD_SWAPS
+    let migration = match msg {
+        MigrateMsg::Migration(migration) => migration,
+        MigrateMsg::Empty {} => Migration::Upgrade {},
+    };
+    let mut msgs = vec![];
+    match migration {
+        Migration::Upgrade {} => {
+            // Build reverse map of swaps
+            let data = OLD_SWAPS
+                .range(deps.storage, None, None, Ascending)
+                .collect::<StdResult<Vec<_>>>()?;
+            // migrating to this contract - pulling storage from original to this
+            for (sender, old_swap) in data {
+                swaps().save(deps.storage, &sender, &old_swap.upgrade("Hello World".to_string()))?;
+            }
+        }
+        Migration::FromCw20AtomicSwap {} => {
+            // the upstream contract keeps its swaps under the same layout as the old swaps
+            let version = get_contract_version(deps.storage)?;
+            if version.contract != UPSTREAM_CONTRACT_NAME {
+                return Err(ContractError::UnsupportedMigration(version.contract));
+            }
+            let data = OLD_SWAPS
+                .range(deps.storage, None, None, Ascending)
+                .collect::<StdResult<Vec<_>>>()?;
+            for (id, old_swap) in data {
+                swaps().save(deps.storage, &id, &old_swap.upgrade(String::new()))?;
+                OLD_SWAPS.remove(deps.storage, &id);
+            }
+        }
+        Migration::RestoreState { entries } => {
+            // restoring replays swaps into an instance of this contract, never over open swaps
+            let version = get_contract_version(deps.storage)?;
+            if version.contract != CONTRACT_NAME {
+                return Err(ContractError::UnsupportedMigration(version.contract));
+            }
+            CONFIG.load(deps.storage)?.limits.check_batch(entries.len())?;
+            for entry in entries {
+                if swaps().has(deps.storage, &entry.id) {
+                    return Err(ContractError::AlreadyExists);
+                }
+                swaps().save(deps.storage, &entry.id, &entry.swap)?;
+            }
+            assert_backed(deps.as_ref(), &env.contract.address)?;
+        }
+        Migration::RescueSelfSwaps { refund_to } => {
+            let version = get_contract_version(deps.storage)?;
+            if version.contract != CONTRACT_NAME {
+                return Err(ContractError::UnsupportedMigration(version.contract));
+            }
+            let refund_to = deps.api.addr_validate(&refund_to)?;
+            msgs = rescue_self_swaps(deps.storage, &env.contract.address, &refund_to)?;
+        }
+    }
+
+    // older versions have no configuration - the contract admin (who is migrating) becomes its admin
+    if CONFIG.may_load(deps.storage)?.is_none() {
+        let admin = deps.querier
+            .query_wasm_contract_info(&env.contract.address)?
+            .admin
+            .ok_or_else(|| StdError::generic_err("Contract has no admin"))?;
+        CONFIG.save(deps.storage, &Config::new(deps.api.addr_validate(&admin)?))?;
+    }
+    // re-save every swap, so that the swaps stored before the pair index existed get indexed, and
+    // recount the open swaps and rebuild the ledger, since migrated swaps were never accounted
+    let stored = swaps()
         .range(deps.storage, None, None, Ascending)
         .collect::<StdResult<Vec<_>>>()?;
-    // migrating to this contract - pulling storage from original to this
-    for (sender, old_swap) in data {
-        let swap = AtomicSwap {
-            hash      : old_swap.hash,
-            recipient : old_swap.recipient,
-            source    : old_swap.source,
-            expires   : old_swap.expires,
-            balance   : old_swap.balance,
-            memo      : "Hello World".to_string(),
-        };
-        SWAPS.save(deps.storage, &sender, &swap)?;
+    for (id, swap) in &stored {
+        swaps().save(deps.storage, id, swap)?;
     }
-    Ok(Response::default())
+    let open = stored.len() as u64;
+    OPEN_SWAPS.save(deps.storage, &open)?;
+    rebuild_ledger(deps.storage)?;
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    Ok(Response::new()
+        .add_submessages(msgs)
+        .add_attribute("action", "migrate")
+        .add_attribute("open_swaps", open.to_string()))
+}
+
+
+/// Ensure that the contract holds enough funds to pay out every open swap, fee payer deposit and
+/// collected fee.
+/// # Arguments
+/// * `deps`     - dependency which has the storage (state) of the chain
+/// * `contract` - this contract's address
+/// # Returns
+/// * the error type Err if any denom or Cw20 token held falls short
+fn assert_backed(deps: Deps, contract: &Addr) -> Result<(), ContractError> {
+    let (native, cw20) = expected_holdings(deps.storage)?;
+    for required in native.into_vec() {
+        let held = deps.querier.query_balance(contract, &required.denom)?;
+        if held.amount < required.amount {
+            return Err(ContractError::UnbackedState(required.to_string()));
+        }
+    }
+    for (token, required) in cw20 {
+        let query = Cw20QueryMsg::Balance { address: contract.to_string() };
+        let held: BalanceResponse = deps.querier.query_wasm_smart(&token, &query)?;
+        if held.balance < required {
+            return Err(ContractError::UnbackedState(format!("{}{}", required, token)));
+        }
+    }
+    Ok(())
+}
+
+
+/// Assert that an address holds a valid attestation of the attestation contract, if one is set.
+/// A failing query counts as no attestation.
+/// # Arguments
+/// * `deps`    - dependency which has the querier
+/// * `config`  - the contract configuration
+/// * `address` - the attested address
+/// # Returns
+/// * the error type Err if the address is not attested
+fn assert_attested(deps: Deps, config: &Config, address: &Addr) -> Result<(), ContractError> {
+    let attestation = match &config.attestation {
+        Some(attestation) => attestation,
+        None => return Ok(()),
+    };
+    let query = AttestationQueryMsg::Attested { address: address.to_string() };
+    let res = deps.querier.query_wasm_smart::<AttestedResponse>(attestation, &query);
+    match res {
+        Ok(AttestedResponse { attested: true }) => Ok(()),
+        _ => Err(ContractError::AttestationRequired(address.to_string())),
+    }
+}
+
+
+/// The Cw20 tokens the contract received and has not accounted for yet - its balance beyond what
+/// its ledger expects it to hold. Received tokens are credited before the receive hook is called,
+/// so within the hook this is what the token delivered.
+/// # Arguments
+/// * `deps`     - dependency which has the storage (state) of the chain, and the querier
+/// * `contract` - this contract's address
+/// * `token`    - the Cw20 token contract
+/// # Returns
+///   The amount received
+fn received(deps: Deps, contract: &Addr, token: &Addr) -> StdResult<Uint128> {
+    let expected = CW20_LEDGER.may_load(deps.storage, token)?.unwrap_or_default();
+    let query = Cw20QueryMsg::Balance { address: contract.to_string() };
+    let held: BalanceResponse = deps.querier.query_wasm_smart(token, &query)?;
+    Ok(held.balance.saturating_sub(expected))
+}
+
+
+/// The funds the contract is expected to hold - every open swap's balance, fee payer deposit,
+/// pending Cw20 payout, collected fee and sponsor budget - derived from its whole state, which only
+/// migrations go through. The ledger keeps track of them otherwise.
+/// # Arguments
+/// * `storage` - the contract storage
+/// # Returns
+///   The native funds, and the Cw20 tokens by token contract
+fn expected_holdings(
+    storage: &dyn Storage,
+) -> StdResult<(NativeBalance, BTreeMap<Addr, Uint128>)> {
+    let mut native = NativeBalance::default();
+    let mut cw20: BTreeMap<Addr, Uint128> = BTreeMap::new();
+    for item in swaps().range(storage, None, None, Ascending) {
+        let (_, swap) = item?;
+        swap.balance.native.into_iter().for_each(|c| native += c);
+        for coin in swap.balance.cw20 {
+            *cw20.entry(coin.address).or_default() += coin.amount;
+        }
+        if let Some(payer) = swap.fee_payer {
+            native += payer.deposit;
+        }
+    }
+    for item in COLLECTED_FEES.range(storage, None, None, Ascending) {
+        let (denom, amount) = item?;
+        native += Coin { denom, amount };
+    }
+    for item in PENDING_PAYOUTS.range(storage, None, None, Ascending) {
+        let (_, payout) = item?;
+        *cw20.entry(payout.token.address).or_default() += payout.token.amount;
+    }
+    for item in SPONSORS.range(storage, None, None, Ascending) {
+        let (_, sponsor) = item?;
+        native += coin(sponsor.budget.u128(), sponsor.grant.denom);
+    }
+    Ok((native, cw20))
+}
+
+
+/// Rebuild the ledger of the funds the contract is expected to hold from its state.
+/// # Arguments
+/// * `storage` - the contract storage
+/// # Returns
+/// * the error type Err
+fn rebuild_ledger(storage: &mut dyn Storage) -> StdResult<()> {
+    let denoms = NATIVE_LEDGER
+        .keys(storage, None, None, Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for denom in denoms {
+        NATIVE_LEDGER.remove(storage, &denom);
+    }
+    let tokens = CW20_LEDGER
+        .keys(storage, None, None, Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for token in tokens {
+        CW20_LEDGER.remove(storage, &token);
+    }
+    let (native, cw20) = expected_holdings(storage)?;
+    let cw20 = cw20
+        .into_iter()
+        .map(|(address, amount)| Cw20CoinVerified { address, amount })
+        .collect();
+    update_ledger(storage, &GenericBalance { native: native.into_vec(), cw20 }, true)
+}
+
+
+/// Credit the ledger of the funds the contract is expected to hold with funds it took in, or debit
+/// it with funds it let go of.
+/// # Arguments
+/// * `storage` - the contract storage
+/// * `funds`   - the funds credited or debited
+/// * `credit`  - whether the funds are credited, or debited
+/// # Returns
+/// * the error type Err
+fn update_ledger(storage: &mut dyn Storage, funds: &GenericBalance, credit: bool) -> StdResult<()> {
+    let apply = |held: Uint128, amount: Uint128| match credit {
+        true => held + amount,
+        false => held.saturating_sub(amount),
+    };
+    for coin in &funds.native {
+        let held = NATIVE_LEDGER.may_load(storage, &coin.denom)?.unwrap_or_default();
+        NATIVE_LEDGER.save(storage, &coin.denom, &apply(held, coin.amount))?;
+    }
+    for token in &funds.cw20 {
+        let held = CW20_LEDGER.may_load(storage, &token.address)?.unwrap_or_default();
+        CW20_LEDGER.save(storage, &token.address, &apply(held, token.amount))?;
+    }
+    Ok(())
+}
+
+
+/// The funds held for a swap - its balance, along with its fee payer's deposit.
+fn swap_holdings(swap: &AtomicSwap) -> GenericBalance {
+    let mut holdings = swap.balance.clone();
+    if let Some(payer) = &swap.fee_payer {
+        holdings.native.push(payer.deposit.clone());
+    }
+    holdings
+}
+
+
+/// Save an open swap, updating the ledger with the change of the funds held for it.
+/// # Arguments
+/// * `storage` - the contract storage
+/// * `id`      - human-readable swap id
+/// * `swap`    - the swap saved
+/// # Returns
+/// * the error type Err
+fn save_swap(storage: &mut dyn Storage, id: &str, swap: &AtomicSwap) -> StdResult<()> {
+    if let Some(old) = swaps().may_load(storage, id)? {
+        update_ledger(storage, &swap_holdings(&old), false)?;
+    }
+    update_ledger(storage, &swap_holdings(swap), true)?;
+    swaps().save(storage, id, swap)
 }
 
 
@@ -6,19 +6,32 @@ Testing for contract.
 mod tests {
     use crate::contract::*;
     use crate::error::ContractError;
+    use crate::state::{swaps, AtomicSwap, OldAtomicSwap, CW20_LEDGER, NATIVE_LEDGER, OLD_SWAPS};
     use crate::msg::{
         InstantiateMsg, CreateMsg, ExecuteMsg, QueryMsg, ReceiveMsg,
-        ListResponse, DetailsResponse, BalanceHuman
+        ListResponse, DetailsResponse, BalanceHuman, ConfigResponse, UpdateConfigMsg,
+        ComputeHashResponse, HashAlgorithm, HashPreview, FeeExemptionsResponse, OrderBookCursor,
+        CollectedFeesResponse, IbcPayout, ExportResponse, SwapStatus, LockType, OracleQueryMsg,
+        EventOccurredResponse, OrderBookResponse, FeePayer, MigrateMsg, ExpiringWithinResponse,
+        DumpStateResponse, PriceQueryMsg, PriceResponse, ReleaseResult, RefundResult, PayoutRetry,
+        PendingPayoutsResponse, DetailsBatchResponse, SponsorResponse, SponsorsResponse,
+        ExpiryExtension, InboxEventKind, InboxResponse, LockDuration, ChangedSinceResponse,
+        SwapChangeKind, AttestationQueryMsg, AttestedResponse, MetricsResponse, EventVerbosity,
+        PayoutMode, Migration
     };
 
     use sha2::{Digest, Sha256};
     use cosmwasm_std::{
-        coins, from_binary, to_binary, StdError, Uint128,
-        Timestamp, BankMsg, Env, SubMsg, WasmMsg
+        coin, coins, from_binary, to_binary, Binary, Decimal, StdError, Uint128,
+        Timestamp, BankMsg, Env, IbcMsg, IbcTimeout, SubMsg, WasmMsg, WasmQuery, SystemResult,
+        ContractResult, ContractInfoResponse, Addr, Reply, SubMsgResponse, SubMsgResult, Event,
+        ReplyOn, Order
     };
     use cosmwasm_std::testing::{
-        mock_dependencies, mock_env, mock_info
+        mock_dependencies, mock_dependencies_with_balance, mock_env, mock_info, MOCK_CONTRACT_ADDR
     };
+    use crate::limits::Limits;
+    use crate::testing::Scenario;
     use cw20::{
         Cw20Coin, Cw20ExecuteMsg, Cw20ReceiveMsg, Expiration
     };
@@ -35,12 +48,12 @@ mod tests {
 
     /// Default hashed of the preimage
     fn real_hash() -> String {
-        hex::encode(&Sha256::digest(preimage().as_bytes()))
+        hex::encode(Sha256::digest(preimage().as_bytes()))
     }
 
     /// Hashed of the custom preimage
     fn custom_hash(int: u16) -> String {
-        hex::encode(&Sha256::digest(custom_preimage(int).as_bytes()))
+        hex::encode(Sha256::digest(custom_preimage(int).as_bytes()))
     }
 
     /// Mock block height within the chain
@@ -60,6 +73,7 @@ mod tests {
             hash: "4d9dbecbaaf42653d09a95c7e1986a047ce98afab5f9f8a4f98b20aa9913c984".to_string(),
             recipient: "orai1tcenqk4f26vdz97ewdfcefr3akntzghxj7gcaw".to_string(),
             expires: Expiration::AtHeight(22222222),
+            ..Default::default()
         };
         let msg = ReceiveMsg::Create(create_msg);
         println!("\n{}\n", to_binary(&msg).unwrap())
@@ -96,6 +110,7 @@ mod tests {
                 hash: real_hash(),
                 recipient: String::from("rcpt0001"),
                 expires: Expiration::AtHeight(123456),
+                ..Default::default()
             };
             let err = execute(
                 deps.as_mut(),
@@ -114,6 +129,7 @@ mod tests {
             hash: real_hash(),
             recipient: "rcpt0001".into(),
             expires: Expiration::AtHeight(123456),
+            ..Default::default()
         };
         let err = execute(
             deps.as_mut(), mock_env(), info, ExecuteMsg::Create(create)
@@ -127,6 +143,7 @@ mod tests {
             hash: real_hash(),
             recipient: "rcpt0001".into(),
             expires: Expiration::AtTime(Timestamp::from_seconds(1)),
+            ..Default::default()
         };
         let err = execute(
             deps.as_mut(), mock_env(), info, ExecuteMsg::Create(create)
@@ -140,14 +157,26 @@ mod tests {
             hash: "bu115h17".to_string(),
             recipient: "rcpt0001".into(),
             expires: Expiration::AtHeight(123456),
+            ..Default::default()
         };
         let err = execute(
             deps.as_mut(), mock_env(), info, ExecuteMsg::Create(create)
         ).unwrap_err();
-        assert_eq!(
-            err,
-            ContractError::ParseError("Invalid character \'u\' at position 1".into())
-        );
+        assert_eq!(err, ContractError::InvalidHexChar { position: 1, char: 'u' });
+
+        // Cannot create, hash of the wrong length
+        let info = mock_info(&sender, &balance);
+        let create = CreateMsg {
+            id: "swap0001".to_string(),
+            hash: real_hash()[..62].to_string(),
+            recipient: "rcpt0001".into(),
+            expires: Expiration::AtHeight(123456),
+            ..Default::default()
+        };
+        let err = execute(
+            deps.as_mut(), mock_env(), info, ExecuteMsg::Create(create)
+        ).unwrap_err();
+        assert_eq!(err, ContractError::WrongLength { got: 62, expected: 64 });
 
         // Can create, all valid
         let info = mock_info(&sender, &balance);
@@ -156,6 +185,7 @@ mod tests {
             hash: real_hash(),
             recipient: "rcpt0001".into(),
             expires: Expiration::AtHeight(123456),
+            ..Default::default()
         };
         let res = execute(
             deps.as_mut(), mock_env(), info, ExecuteMsg::Create(create)
@@ -171,6 +201,7 @@ mod tests {
             hash: real_hash(),
             recipient: "rcpt0001".into(),
             expires: Expiration::AtHeight(123456),
+            ..Default::default()
         };
         let err = execute(
             deps.as_mut(), mock_env(), info, ExecuteMsg::Create(create)
@@ -195,6 +226,7 @@ mod tests {
             hash: real_hash(),
             recipient: "rcpt0001".into(),
             expires: Expiration::AtHeight(123456),
+            ..Default::default()
         };
         execute(
             deps.as_mut(),
@@ -211,6 +243,7 @@ mod tests {
         let release = ExecuteMsg::Release {
             id: "swap0002".to_string(),
             preimage: preimage(),
+            amount: None,
         };
         let err = execute(deps.as_mut(), mock_env(), info.clone(), release).unwrap_err();
         assert!(matches!(err, ContractError::Std(StdError::NotFound { .. })));
@@ -219,6 +252,7 @@ mod tests {
         let release = ExecuteMsg::Release {
             id: "swap0001".to_string(),
             preimage: "bu115h17".to_string(),
+            amount: None,
         };
         let err = execute(deps.as_mut(), mock_env(), info.clone(), release).unwrap_err();
         assert_eq!(
@@ -230,6 +264,7 @@ mod tests {
         let release = ExecuteMsg::Release {
             id: "swap0001".to_string(),
             preimage: hex::encode(b"This is 32 bytes, but incorrect."),
+            amount: None,
         };
         let err = execute(deps.as_mut(), mock_env(), info, release).unwrap_err();
         assert!(matches!(err, ContractError::InvalidPreimage {}));
@@ -240,6 +275,7 @@ mod tests {
         let release = ExecuteMsg::Release {
             id: "swap0001".to_string(),
             preimage: preimage(),
+            amount: None,
         };
         let err = execute(deps.as_mut(), env, info, release).unwrap_err();
         assert!(matches!(err, ContractError::Expired));
@@ -249,6 +285,7 @@ mod tests {
         let release = ExecuteMsg::Release {
             id: "swap0001".to_string(),
             preimage: preimage(),
+            amount: None,
         };
         let res = execute(
             deps.as_mut(), mock_env(), info.clone(), release.clone()
@@ -285,6 +322,7 @@ mod tests {
             hash: real_hash(),
             recipient: "rcpt0001".into(),
             expires: Expiration::AtHeight(123456),
+            ..Default::default()
         };
         execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Create(create)).unwrap();
 
@@ -303,7 +341,7 @@ mod tests {
             id: "swap0001".to_string(),
         };
         let err = execute(deps.as_mut(), mock_env(), info, refund).unwrap_err();
-        assert!(matches!(err, ContractError::NotExpired { .. }));
+        assert!(matches!(err, ContractError::NotExpired));
 
         // Anyone can refund, if already expired
         let env = mock_env_height(123457);
@@ -347,6 +385,7 @@ mod tests {
             hash: custom_hash(1),
             recipient: "rcpt0001".into(),
             expires: Expiration::AtHeight(123456),
+            ..Default::default()
         };
         execute(
             deps.as_mut(),
@@ -362,6 +401,7 @@ mod tests {
             hash: custom_hash(2),
             recipient: "rcpt0002".into(),
             expires: Expiration::AtTime(Timestamp::from_seconds(2_000_000_000)),
+            ..Default::default()
         };
         execute(
             deps.as_mut(),
@@ -375,6 +415,7 @@ mod tests {
         let query_msg = QueryMsg::List {
             start_after: None,
             limit: None,
+            status: None,
         };
         let ids: ListResponse =
             from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
@@ -396,6 +437,29 @@ mod tests {
                 source: sender1,
                 expires: create1.expires,
                 balance: BalanceHuman::Native(balance.clone()),
+                payout_msg: None,
+                order_commitment: None,
+                ibc_payout: None,
+                counter_chain: None,
+                counter_tx: None,
+                created_at: Some((mock_env().block.height, mock_env().block.time)),
+                lock_type: None,
+                ask: None,
+                fee_payer: None,
+                partial: false,
+                commit_reveal: false,
+                accept_required: false,
+                accepted: false,
+                auto_extend: None,
+                extended: 0,
+                fallback_recipient: None,
+                private: false,
+                reveal_deadline: None,
+                reveal_by: None,
+                payout_order: None,
+                payout_mode: None,
+                escrow_id: escrow_id(&mock_env().contract.address, &ids.swaps[0]),
+                value: None,
             }
         );
 
@@ -414,6 +478,29 @@ mod tests {
                 source: sender2,
                 expires: create2.expires,
                 balance: BalanceHuman::Native(balance),
+                payout_msg: None,
+                order_commitment: None,
+                ibc_payout: None,
+                counter_chain: None,
+                counter_tx: None,
+                created_at: Some((mock_env().block.height, mock_env().block.time)),
+                lock_type: None,
+                ask: None,
+                fee_payer: None,
+                partial: false,
+                commit_reveal: false,
+                accept_required: false,
+                accepted: false,
+                auto_extend: None,
+                extended: 0,
+                fallback_recipient: None,
+                private: false,
+                reveal_deadline: None,
+                reveal_by: None,
+                payout_order: None,
+                payout_mode: None,
+                escrow_id: escrow_id(&mock_env().contract.address, &ids.swaps[1]),
+                value: None,
             }
         );
     }
@@ -440,6 +527,7 @@ mod tests {
             hash: real_hash(),
             recipient: native_rcpt.clone(),
             expires: Expiration::AtHeight(123456),
+            ..Default::default()
         };
         let info = mock_info(&native_sender, &native_coins);
         let res = execute(
@@ -463,6 +551,7 @@ mod tests {
             hash: real_hash(),
             recipient: cw20_rcpt.clone(),
             expires: Expiration::AtHeight(123000),
+            ..Default::default()
         };
         let receive = Cw20ReceiveMsg {
             sender: cw20_sender,
@@ -491,6 +580,7 @@ mod tests {
             ExecuteMsg::Release {
                 id: cw20_swap_id.clone(),
                 preimage: preimage(),
+                amount: None,
             },
         )
         .unwrap();
@@ -524,6 +614,7 @@ mod tests {
         let release = ExecuteMsg::Release {
             id: native_swap_id.clone(),
             preimage,
+            amount: None,
         };
         let res = execute(deps.as_mut(), mock_env(), info, release).unwrap();
         assert_eq!(1, res.messages.len());
@@ -562,6 +653,7 @@ mod tests {
             hash: real_hash(),
             recipient: native_rcpt.clone(),
             expires: Expiration::AtHeight(123456),
+            ..Default::default()
         };
         let info = mock_info(&native_sender, &native_coins);
         let res = execute(
@@ -573,4 +665,3085 @@ mod tests {
             _ => panic!()
         }
     }
-}
\ No newline at end of file
+
+    /// test that a Cw20 swap with payout message releases via Send, and native swaps reject it
+    #[test]
+    fn test_cw20_payout_msg() {
+        let mut deps = mock_dependencies();
+
+        let info = mock_info("anyone", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, InstantiateMsg {}).unwrap();
+
+        let payout_msg = to_binary("deposit").unwrap();
+
+        // Cannot create, payout message on native swap
+        let info = mock_info("a_on_x", &coins(1000, "tokens_native"));
+        let create = CreateMsg {
+            id: "native_swap".to_string(),
+            hash: real_hash(),
+            recipient: "b_on_x".into(),
+            expires: Expiration::AtHeight(123456),
+            payout_msg: Some(payout_msg.clone()),
+            ..Default::default()
+        };
+        let err = execute(
+            deps.as_mut(), mock_env(), info, ExecuteMsg::Create(create)
+        ).unwrap_err();
+        assert_eq!(err, ContractError::PayoutMsgNotCw20);
+
+        // Can create, payout message on Cw20 swap
+        let create = CreateMsg {
+            id: "cw20_swap".to_string(),
+            hash: real_hash(),
+            recipient: "vault_contract".into(),
+            expires: Expiration::AtHeight(123456),
+            payout_msg: Some(payout_msg.clone()),
+            ..Default::default()
+        };
+        let receive = Cw20ReceiveMsg {
+            sender: "a_on_y".to_string(),
+            amount: Uint128::new(500),
+            msg: to_binary(&ExecuteMsg::Create(create)).unwrap(),
+        };
+        let info = mock_info("my_cw20_token", &[]);
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Receive(receive)).unwrap();
+
+        // Release is done via Cw20 Send with the attached message
+        let info = mock_info("somebody", &[]);
+        let release = ExecuteMsg::Release {
+            id: "cw20_swap".to_string(),
+            preimage: preimage(),
+            amount: None,
+        };
+        let res = execute(deps.as_mut(), mock_env(), info, release).unwrap();
+        assert_eq!(1, res.messages.len());
+        let send_msg = Cw20ExecuteMsg::Send {
+            contract: "vault_contract".to_string(),
+            amount: Uint128::new(500),
+            msg: payout_msg,
+        };
+        assert_eq!(
+            res.messages[0],
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: "my_cw20_token".to_string(),
+                msg: to_binary(&send_msg).unwrap(),
+                funds: vec![],
+            })
+        );
+    }
+
+    /// test that the open swaps cap is enforced, and only the admin can change it
+    #[test]
+    fn test_max_open_swaps() {
+        let mut deps = mock_dependencies();
+
+        let info = mock_info("admin", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, InstantiateMsg {}).unwrap();
+
+        // Cannot update config, not the admin
+        let update = UpdateConfigMsg {
+            max_open_swaps: Some(1),
+            ..Default::default()
+        };
+        let info = mock_info("somebody", &[]);
+        let err = execute(
+            deps.as_mut(), mock_env(), info, ExecuteMsg::UpdateConfig(update.clone())
+        ).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        // Can update config as admin
+        let info = mock_info("admin", &[]);
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::UpdateConfig(update)).unwrap();
+
+        // Can create the first swap
+        let info = mock_info("sender0001", &coins(100, "tokens"));
+        let create = CreateMsg {
+            id: "swap0001".to_string(),
+            hash: custom_hash(1),
+            recipient: "rcpt0001".into(),
+            expires: Expiration::AtHeight(123456),
+            ..Default::default()
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), ExecuteMsg::Create(create)).unwrap();
+
+        // Cannot create the second swap, cap reached
+        let create = CreateMsg {
+            id: "swap0002".to_string(),
+            hash: custom_hash(2),
+            recipient: "rcpt0001".into(),
+            expires: Expiration::AtHeight(123456),
+            ..Default::default()
+        };
+        let err = execute(
+            deps.as_mut(), mock_env(), info.clone(), ExecuteMsg::Create(create.clone())
+        ).unwrap_err();
+        assert_eq!(err, ContractError::CapacityReached { open: 1, cap: 1 });
+
+        // Once the first swap is released, a new one can be created
+        let release = ExecuteMsg::Release {
+            id: "swap0001".to_string(),
+            preimage: custom_preimage(1),
+            amount: None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("somebody", &[]), release).unwrap();
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Create(create)).unwrap();
+
+        let config: ConfigResponse = from_binary(
+            &query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()
+        ).unwrap();
+        assert_eq!(
+            config,
+            ConfigResponse {
+                admin: "admin".to_string(),
+                max_open_swaps: Some(1),
+                open_swaps: 1,
+                release_fee_bps: 0,
+                allow_never_expires: false,
+                refund_bounty_bps: 0,
+                creation_fee: None,
+                allow_revealed_hashes: false,
+                price_oracle: None,
+                telemetry: false,
+                payout_retry: None,
+                limits: Default::default(),
+                max_lock_duration: None,
+                creates_paused: false,
+                attestation: None,
+                verify_received: false,
+                event_verbosity: EventVerbosity::Standard,
+                payout_gas_limit: None,
+            }
+        );
+    }
+
+    /// test that the hash preview matches the hash used to lock and release a swap
+    #[test]
+    fn test_compute_hash() {
+        let deps = mock_dependencies();
+
+        let query_msg = QueryMsg::ComputeHash { preimage: preimage() };
+        let res: ComputeHashResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(
+            res.hashes,
+            vec![HashPreview {
+                algorithm: HashAlgorithm::Sha256,
+                hash: real_hash(),
+            }]
+        );
+    }
+
+    /// test that the release fee goes to the admin, except for exempt recipients
+    #[test]
+    fn test_release_fee_exemption() {
+        let mut deps = mock_dependencies();
+
+        let info = mock_info("admin", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, InstantiateMsg {}).unwrap();
+
+        // Cannot set a fee over 100%
+        let update = UpdateConfigMsg {
+            release_fee_bps: Some(10_001),
+            ..Default::default()
+        };
+        let err = execute(
+            deps.as_mut(), mock_env(), mock_info("admin", &[]), ExecuteMsg::UpdateConfig(update)
+        ).unwrap_err();
+        assert_eq!(err, ContractError::InvalidFee(10_001));
+
+        // 1% release fee
+        let update = UpdateConfigMsg {
+            release_fee_bps: Some(100),
+            ..Default::default()
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), ExecuteMsg::UpdateConfig(update))
+            .unwrap();
+
+        // Cannot add exemption, not the admin
+        let exempt = ExecuteMsg::AddFeeExemption { address: "market_maker".to_string() };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("somebody", &[]), exempt.clone())
+            .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), exempt).unwrap();
+
+        let query_msg = QueryMsg::FeeExemptions { start_after: None, limit: None };
+        let res: FeeExemptionsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(res.addresses, vec!["market_maker"]);
+
+        // One swap to a regular recipient, one to the exempt one
+        for (id, recipient) in [("swap0001", "rcpt0001"), ("swap0002", "market_maker")] {
+            let create = CreateMsg {
+                id: id.to_string(),
+                hash: real_hash(),
+                recipient: recipient.to_string(),
+                expires: Expiration::AtHeight(123456),
+                ..Default::default()
+            };
+            let info = mock_info("sender0001", &coins(1000, "tokens"));
+            execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Create(create)).unwrap();
+        }
+
+        // Regular recipient pays the fee, which goes to the admin
+        let release = ExecuteMsg::Release {
+            id: "swap0001".to_string(),
+            preimage: preimage(),
+            amount: None,
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("somebody", &[]), release).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![
+                SubMsg::new(BankMsg::Send {
+                    to_address: "rcpt0001".to_string(),
+                    amount: coins(990, "tokens"),
+                }),
+                SubMsg::new(BankMsg::Send {
+                    to_address: "admin".to_string(),
+                    amount: coins(10, "tokens"),
+                }),
+            ]
+        );
+
+        // Exempt recipient receives everything
+        let release = ExecuteMsg::Release {
+            id: "swap0002".to_string(),
+            preimage: preimage(),
+            amount: None,
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("somebody", &[]), release).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: "market_maker".to_string(),
+                amount: coins(1000, "tokens"),
+            })]
+        );
+    }
+
+    /// test that the order commitment is hashed into the create event and returned in details
+    #[test]
+    fn test_order_commitment() {
+        let mut deps = mock_dependencies();
+
+        let info = mock_info("anyone", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, InstantiateMsg {}).unwrap();
+
+        let commitment = Binary::from(b"signed term sheet".to_vec());
+        let create = CreateMsg {
+            id: "swap0001".to_string(),
+            hash: real_hash(),
+            recipient: "rcpt0001".into(),
+            expires: Expiration::AtHeight(123456),
+            order_commitment: Some(commitment.clone()),
+            ..Default::default()
+        };
+        let info = mock_info("sender0001", &coins(1000, "tokens"));
+        let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Create(create)).unwrap();
+        let expected = hex::encode(Sha256::digest(b"signed term sheet"));
+        assert_eq!(("order_commitment", expected.as_str()), res.attributes[6]);
+
+        let query_msg = QueryMsg::Details { id: "swap0001".to_string() };
+        let res: DetailsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(res.order_commitment, Some(commitment));
+    }
+
+    /// test that swaps never expiring are rejected, unless allowed, and can then be force refunded
+    #[test]
+    fn test_never_expires() {
+        let mut deps = mock_dependencies();
+
+        let info = mock_info("admin", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, InstantiateMsg {}).unwrap();
+
+        let create = CreateMsg {
+            id: "swap0001".to_string(),
+            hash: real_hash(),
+            recipient: "rcpt0001".into(),
+            expires: Expiration::Never {},
+            ..Default::default()
+        };
+        let info = mock_info("sender0001", &coins(1000, "tokens"));
+
+        // Cannot create, never expires
+        let err = execute(
+            deps.as_mut(), mock_env(), info.clone(), ExecuteMsg::Create(create.clone())
+        ).unwrap_err();
+        assert_eq!(err, ContractError::NeverExpires);
+
+        // Can create once allowed by the admin
+        let update = UpdateConfigMsg {
+            allow_never_expires: Some(true),
+            ..Default::default()
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), ExecuteMsg::UpdateConfig(update))
+            .unwrap();
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Create(create)).unwrap();
+
+        // Cannot refund, it never expires
+        let refund = ExecuteMsg::Refund { id: "swap0001".to_string() };
+        let err = execute(deps.as_mut(), mock_env_height(99_999_999), mock_info("somebody", &[]), refund)
+            .unwrap_err();
+        assert_eq!(err, ContractError::NotExpired);
+
+        // Cannot force refund, not the admin
+        let force_refund = ExecuteMsg::ForceRefund { id: "swap0001".to_string() };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("somebody", &[]), force_refund.clone())
+            .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        // Admin can force refund back to the sender
+        let res = execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), force_refund).unwrap();
+        assert_eq!(("action", "force_refund"), res.attributes[0]);
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: "sender0001".to_string(),
+                amount: coins(1000, "tokens"),
+            })]
+        );
+    }
+
+    /// test that keepers triggering a refund are paid the bounty, but not the source itself
+    #[test]
+    fn test_refund_bounty() {
+        let mut deps = mock_dependencies();
+
+        let info = mock_info("admin", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, InstantiateMsg {}).unwrap();
+
+        // 0.5% bounty
+        let update = UpdateConfigMsg {
+            refund_bounty_bps: Some(50),
+            ..Default::default()
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), ExecuteMsg::UpdateConfig(update))
+            .unwrap();
+
+        for id in ["swap0001", "swap0002"] {
+            let create = CreateMsg {
+                id: id.to_string(),
+                hash: real_hash(),
+                recipient: "rcpt0001".into(),
+                expires: Expiration::AtHeight(123456),
+                ..Default::default()
+            };
+            let info = mock_info("sender0001", &coins(1000, "tokens"));
+            execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Create(create)).unwrap();
+        }
+        let env = mock_env_height(123457);
+
+        // A keeper refunds and gets the bounty
+        let refund = ExecuteMsg::Refund { id: "swap0001".to_string() };
+        let res = execute(deps.as_mut(), env.clone(), mock_info("keeper", &[]), refund).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![
+                SubMsg::new(BankMsg::Send {
+                    to_address: "sender0001".to_string(),
+                    amount: coins(995, "tokens"),
+                }),
+                SubMsg::new(BankMsg::Send {
+                    to_address: "keeper".to_string(),
+                    amount: coins(5, "tokens"),
+                }),
+            ]
+        );
+        assert_eq!(("bounty", "5tokens"), res.attributes[5]);
+
+        // The source refunding themselves pays no bounty
+        let refund = ExecuteMsg::Refund { id: "swap0002".to_string() };
+        let res = execute(deps.as_mut(), env, mock_info("sender0001", &[]), refund).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: "sender0001".to_string(),
+                amount: coins(1000, "tokens"),
+            })]
+        );
+    }
+
+    /// test that the creation fee is taken out of the sent funds, and can be withdrawn by the admin
+    #[test]
+    fn test_creation_fee() {
+        let mut deps = mock_dependencies();
+
+        let info = mock_info("admin", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, InstantiateMsg {}).unwrap();
+
+        let update = UpdateConfigMsg {
+            creation_fee: Some(coin(10, "fee_token")),
+            ..Default::default()
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), ExecuteMsg::UpdateConfig(update))
+            .unwrap();
+
+        let create = CreateMsg {
+            id: "swap0001".to_string(),
+            hash: real_hash(),
+            recipient: "rcpt0001".into(),
+            expires: Expiration::AtHeight(123456),
+            ..Default::default()
+        };
+
+        // Cannot create, fee not paid
+        let info = mock_info("sender0001", &coins(1000, "tokens"));
+        let err = execute(
+            deps.as_mut(), mock_env(), info, ExecuteMsg::Create(create.clone())
+        ).unwrap_err();
+        assert_eq!(err, ContractError::CreationFeeRequired("10fee_token".to_string()));
+
+        // Can create, fee paid alongside the swap's tokens
+        let info = mock_info("sender0001", &[coin(10, "fee_token"), coin(1000, "tokens")]);
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Create(create)).unwrap();
+
+        // Only the tokens are locked in the swap
+        let query_msg = QueryMsg::Details { id: "swap0001".to_string() };
+        let res: DetailsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(res.balance, BalanceHuman::Native(coins(1000, "tokens")));
+
+        let res: CollectedFeesResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::CollectedFees {}).unwrap())
+                .unwrap();
+        assert_eq!(res.fees, coins(10, "fee_token"));
+
+        // Cw20 swaps are exempt, their token contract cannot attach native funds
+        let create = CreateMsg {
+            id: "swap0002".to_string(),
+            hash: real_hash(),
+            recipient: "rcpt0001".into(),
+            expires: Expiration::AtHeight(123456),
+            ..Default::default()
+        };
+        let receive = Cw20ReceiveMsg {
+            sender: "sender0001".to_string(),
+            amount: Uint128::new(500),
+            msg: to_binary(&ExecuteMsg::Create(create)).unwrap(),
+        };
+        let info = mock_info("my_cw20_token", &[]);
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Receive(receive)).unwrap();
+        let res: CollectedFeesResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::CollectedFees {}).unwrap())
+                .unwrap();
+        assert_eq!(res.fees, coins(10, "fee_token"));
+
+        // Cannot withdraw, not the admin
+        let err = execute(
+            deps.as_mut(), mock_env(), mock_info("somebody", &[]), ExecuteMsg::WithdrawFees {}
+        ).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        // Admin withdraws the fees, after which there is nothing left
+        let res = execute(
+            deps.as_mut(), mock_env(), mock_info("admin", &[]), ExecuteMsg::WithdrawFees {}
+        ).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: "admin".to_string(),
+                amount: coins(10, "fee_token"),
+            })]
+        );
+        let err = execute(
+            deps.as_mut(), mock_env(), mock_info("admin", &[]), ExecuteMsg::WithdrawFees {}
+        ).unwrap_err();
+        assert_eq!(err, ContractError::NoFees);
+    }
+
+    /// test that a native payout routed over IBC is delivered with an ICS-20 transfer on release
+    #[test]
+    fn test_ibc_payout() {
+        let mut deps = mock_dependencies();
+
+        let info = mock_info("anyone", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, InstantiateMsg {}).unwrap();
+
+        let route = IbcPayout {
+            channel: "channel-7".to_string(),
+            remote_recipient: "osmo1recipient".to_string(),
+            timeout_seconds: 600,
+        };
+
+        // Cannot create, route without a timeout
+        let create = CreateMsg {
+            id: "ibc_swap".to_string(),
+            hash: real_hash(),
+            recipient: "rcpt0001".into(),
+            expires: Expiration::AtHeight(123456),
+            ibc_payout: Some(IbcPayout { timeout_seconds: 0, ..route.clone() }),
+            ..Default::default()
+        };
+        let info = mock_info("sender0001", &coins(1000, "tokens"));
+        let err = execute(
+            deps.as_mut(), mock_env(), info.clone(), ExecuteMsg::Create(create.clone())
+        ).unwrap_err();
+        assert_eq!(err, ContractError::InvalidIbcPayout);
+
+        // Can create with a complete route
+        let create = CreateMsg { ibc_payout: Some(route.clone()), ..create };
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Create(create)).unwrap();
+
+        let query_msg = QueryMsg::Details { id: "ibc_swap".to_string() };
+        let res: DetailsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(res.ibc_payout, Some(route));
+
+        // Release transfers the tokens to the remote recipient, timing out from the release block
+        let env = mock_env();
+        let release = ExecuteMsg::Release {
+            id: "ibc_swap".to_string(),
+            preimage: preimage(),
+            amount: None,
+        };
+        let res = execute(deps.as_mut(), env.clone(), mock_info("somebody", &[]), release).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(IbcMsg::Transfer {
+                channel_id: "channel-7".to_string(),
+                to_address: "osmo1recipient".to_string(),
+                amount: coin(1000, "tokens"),
+                timeout: IbcTimeout::with_timestamp(env.block.time.plus_seconds(600)),
+            })]
+        );
+    }
+
+    /// test that the source can record the matching swap on the counterparty chain
+    #[test]
+    fn test_counter_proof() {
+        let mut deps = mock_dependencies();
+
+        let info = mock_info("anyone", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, InstantiateMsg {}).unwrap();
+
+        let create = CreateMsg {
+            id: "swap0001".to_string(),
+            hash: real_hash(),
+            recipient: "rcpt0001".into(),
+            expires: Expiration::AtHeight(123456),
+            counter_chain: Some("osmosis-1".to_string()),
+            ..Default::default()
+        };
+        let info = mock_info("sender0001", &coins(1000, "tokens"));
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Create(create)).unwrap();
+
+        // Cannot attach, not the source
+        let attach = ExecuteMsg::AttachCounterProof {
+            id: "swap0001".to_string(),
+            counter_chain: None,
+            counter_tx: Some("ABCDEF".to_string()),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("rcpt0001", &[]), attach.clone())
+            .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        // The source attaches the transaction, leaving the chain unchanged
+        execute(deps.as_mut(), mock_env(), mock_info("sender0001", &[]), attach).unwrap();
+        let query_msg = QueryMsg::Details { id: "swap0001".to_string() };
+        let res: DetailsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(res.counter_chain, Some("osmosis-1".to_string()));
+        assert_eq!(res.counter_tx, Some("ABCDEF".to_string()));
+    }
+
+    /// test that the export query returns the swaps as stored, with their status
+    #[test]
+    fn test_export() {
+        let mut deps = mock_dependencies();
+
+        let info = mock_info("anyone", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, InstantiateMsg {}).unwrap();
+
+        let env = mock_env_height(1000);
+        for (id, expires) in [("swap0001", 1500), ("swap0002", 1100)] {
+            let create = CreateMsg {
+                id: id.to_string(),
+                hash: real_hash(),
+                recipient: "rcpt0001".into(),
+                expires: Expiration::AtHeight(expires),
+                ..Default::default()
+            };
+            let info = mock_info("sender0001", &coins(1000, "tokens"));
+            execute(deps.as_mut(), env.clone(), info, ExecuteMsg::Create(create)).unwrap();
+        }
+
+        // the second swap has expired by then
+        let query_msg = QueryMsg::Export { start_after: None, limit: None };
+        let res: ExportResponse =
+            from_binary(&query(deps.as_ref(), mock_env_height(1200), query_msg).unwrap()).unwrap();
+        assert_eq!(res.swaps.len(), 2);
+        assert_eq!(res.swaps[0].id, "swap0001");
+        assert_eq!(res.swaps[0].status, SwapStatus::Open);
+        assert_eq!(res.swaps[1].status, SwapStatus::Expired);
+
+        // raw fields, including the hash bytes and the creation block
+        let swap = &res.swaps[0].swap;
+        assert_eq!(swap.hash, Binary(hex::decode(real_hash()).unwrap()));
+        assert_eq!(swap.created_at, Some((1000, env.block.time)));
+    }
+
+    /// test that swaps can be listed by age, from their creation time
+    #[test]
+    fn test_list_older_than() {
+        let mut deps = mock_dependencies();
+
+        let info = mock_info("anyone", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, InstantiateMsg {}).unwrap();
+
+        // one swap created now, the other an hour later
+        let mut env = mock_env();
+        for id in ["swap0001", "swap0002"] {
+            let create = CreateMsg {
+                id: id.to_string(),
+                hash: real_hash(),
+                recipient: "rcpt0001".into(),
+                expires: Expiration::AtHeight(123456),
+                ..Default::default()
+            };
+            let info = mock_info("sender0001", &coins(1000, "tokens"));
+            execute(deps.as_mut(), env.clone(), info, ExecuteMsg::Create(create)).unwrap();
+            env.block.time = env.block.time.plus_seconds(3600);
+        }
+
+        let query_msg = QueryMsg::Details { id: "swap0002".to_string() };
+        let res: DetailsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        let created = mock_env().block;
+        assert_eq!(res.created_at, Some((created.height, created.time.plus_seconds(3600))));
+
+        // two hours after the first creation, only the first swap is over 90 minutes old
+        let query_msg = QueryMsg::ListOlderThan { age_seconds: 5400, start_after: None, limit: None };
+        let res: ListResponse = from_binary(&query(deps.as_ref(), env, query_msg).unwrap()).unwrap();
+        assert_eq!(res.swaps, vec!["swap0001".to_string()]);
+    }
+
+    /// test that an oracle-locked swap is released once the oracle attests the event
+    #[test]
+    fn test_oracle_lock() {
+        let mut deps = mock_dependencies();
+
+        let info = mock_info("anyone", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, InstantiateMsg {}).unwrap();
+
+        // the oracle only attests "match_42"
+        deps.querier.update_wasm(|query| match query {
+            WasmQuery::Smart { contract_addr, msg } if contract_addr == "oracle" => {
+                let OracleQueryMsg::EventOccurred { event_id } = from_binary(msg).unwrap();
+                let res = EventOccurredResponse { occurred: event_id == "match_42" };
+                SystemResult::Ok(ContractResult::Ok(to_binary(&res).unwrap()))
+            }
+            _ => panic!("unexpected query"),
+        });
+
+        // no hash needed with an oracle lock
+        for (id, event_id) in [("swap0001", "match_41"), ("swap0002", "match_42")] {
+            let create = CreateMsg {
+                id: id.to_string(),
+                recipient: "rcpt0001".into(),
+                expires: Expiration::AtHeight(123456),
+                lock_type: Some(LockType::Oracle {
+                    oracle_addr: "oracle".to_string(),
+                    event_id: event_id.to_string(),
+                }),
+                ..Default::default()
+            };
+            let info = mock_info("sender0001", &coins(1000, "tokens"));
+            execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Create(create)).unwrap();
+        }
+
+        // Cannot release, the event did not occur
+        let release = |id: &str| ExecuteMsg::Release {
+            id: id.to_string(),
+            preimage: String::new(),
+            amount: None,
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("somebody", &[]), release("swap0001"))
+            .unwrap_err();
+        assert_eq!(err, ContractError::EventNotAttested);
+
+        // Can release, the event occurred
+        let res = execute(deps.as_mut(), mock_env(), mock_info("somebody", &[]), release("swap0002"))
+            .unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: "rcpt0001".to_string(),
+                amount: coins(1000, "tokens"),
+            })]
+        );
+    }
+
+    /// test that the order book lists the open swaps of a pair by implied price
+    #[test]
+    fn test_order_book() {
+        let mut deps = mock_dependencies();
+
+        let info = mock_info("anyone", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, InstantiateMsg {}).unwrap();
+
+        // Cannot create, amount asked without a denom
+        let create = CreateMsg {
+            id: "swap0000".to_string(),
+            hash: real_hash(),
+            recipient: "rcpt0001".into(),
+            expires: Expiration::AtHeight(123456),
+            ask_amount: Some(Uint128::new(10)),
+            ..Default::default()
+        };
+        let info = mock_info("sender0001", &coins(1000, "uatom"));
+        let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Create(create)).unwrap_err();
+        assert_eq!(err, ContractError::InvalidAsk);
+
+        // three atom offers asking osmo, and one asking something else
+        let offers = [
+            ("swap0001", 1000, "uosmo", 3000),
+            ("swap0002", 500, "uosmo", 1000),
+            ("swap0003", 100, "ujuno", 100),
+            ("swap0004", 2000, "uosmo", 2000),
+        ];
+        for (id, give, want, ask) in offers {
+            let create = CreateMsg {
+                id: id.to_string(),
+                hash: real_hash(),
+                recipient: "rcpt0001".into(),
+                expires: Expiration::AtHeight(123456),
+                ask_denom: Some(want.to_string()),
+                ask_amount: Some(Uint128::new(ask)),
+                ..Default::default()
+            };
+            let info = mock_info("sender0001", &coins(give, "uatom"));
+            execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Create(create)).unwrap();
+        }
+
+        let query_msg = QueryMsg::OrderBook {
+            give_denom: "uatom".to_string(),
+            want_denom: "uosmo".to_string(),
+            start_after: None,
+            limit: None,
+        };
+        let res: OrderBookResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        let ids: Vec<_> = res.orders.iter().map(|o| o.id.as_str()).collect();
+        assert_eq!(ids, vec!["swap0004", "swap0002", "swap0001"]);
+        assert_eq!(res.next, None);
+
+        // Paginated by price across pages
+        let page = |start_after: Option<OrderBookCursor>| {
+            let query_msg = QueryMsg::OrderBook {
+                give_denom: "uatom".to_string(),
+                want_denom: "uosmo".to_string(),
+                start_after,
+                limit: Some(2),
+            };
+            let res: OrderBookResponse =
+                from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+            let ids: Vec<_> = res.orders.into_iter().map(|o| o.id).collect();
+            (ids, res.next)
+        };
+        let (ids, next) = page(None);
+        assert_eq!(ids, vec!["swap0004", "swap0002"]);
+        let price = cosmwasm_std::Decimal::from_ratio(2u128, 1u128);
+        assert_eq!(next, Some(OrderBookCursor { price, id: "swap0002".to_string() }));
+        assert_eq!(page(next), (vec!["swap0001".to_string()], None));
+        assert_eq!(res.orders[0].price, cosmwasm_std::Decimal::one());
+    }
+
+    /// test that a fee payer's deposit covers the fees, so that the whole principal is paid out
+    #[test]
+    fn test_fee_payer() {
+        let mut deps = mock_dependencies();
+
+        let info = mock_info("admin", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, InstantiateMsg {}).unwrap();
+
+        // 1% release fee, 0.5% refund bounty
+        let update = UpdateConfigMsg {
+            release_fee_bps: Some(100),
+            refund_bounty_bps: Some(50),
+            ..Default::default()
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), ExecuteMsg::UpdateConfig(update))
+            .unwrap();
+
+        let create = |id: &str, deposit: u128| CreateMsg {
+            id: id.to_string(),
+            hash: real_hash(),
+            recipient: "rcpt0001".into(),
+            expires: Expiration::AtHeight(123456),
+            fee_payer: Some(FeePayer {
+                address: "payer".to_string(),
+                deposit: coin(deposit, "tokens"),
+            }),
+            ..Default::default()
+        };
+
+        // Cannot create, the deposit does not cover the release fee
+        let info = mock_info("sender0001", &coins(1005, "tokens"));
+        let err = execute(
+            deps.as_mut(), mock_env(), info, ExecuteMsg::Create(create("swap0001", 5))
+        ).unwrap_err();
+        assert_eq!(err, ContractError::InvalidFeePayer);
+
+        // Cannot create, the deposit is in another denom than the principal
+        let mut msg = create("swap0001", 10);
+        msg.fee_payer.as_mut().unwrap().deposit = coin(10, "other");
+        let info = mock_info("sender0001", &[coin(1000, "tokens"), coin(10, "other")]);
+        let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Create(msg)).unwrap_err();
+        assert_eq!(err, ContractError::InvalidFeePayer);
+
+        // Can create, with more than enough deposited
+        let info = mock_info("sender0001", &coins(1015, "tokens"));
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Create(create("swap0001", 15)))
+            .unwrap();
+        let info = mock_info("sender0001", &coins(1010, "tokens"));
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Create(create("swap0002", 10)))
+            .unwrap();
+
+        // The deposit is kept apart from the principal
+        let query_msg = QueryMsg::Details { id: "swap0001".to_string() };
+        let res: DetailsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(res.balance, BalanceHuman::Native(coins(1000, "tokens")));
+        assert_eq!(res.fee_payer.unwrap().deposit, coin(15, "tokens"));
+
+        // Release pays the whole principal, the fee out of the deposit, and the rest back
+        let release = ExecuteMsg::Release {
+            id: "swap0001".to_string(),
+            preimage: preimage(),
+            amount: None,
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("somebody", &[]), release).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![
+                SubMsg::new(BankMsg::Send {
+                    to_address: "rcpt0001".to_string(),
+                    amount: coins(1000, "tokens"),
+                }),
+                SubMsg::new(BankMsg::Send {
+                    to_address: "admin".to_string(),
+                    amount: coins(10, "tokens"),
+                }),
+                SubMsg::new(BankMsg::Send {
+                    to_address: "payer".to_string(),
+                    amount: coins(5, "tokens"),
+                }),
+            ]
+        );
+
+        // Refund does likewise with the keeper bounty
+        let refund = ExecuteMsg::Refund { id: "swap0002".to_string() };
+        let env = mock_env_height(123457);
+        let res = execute(deps.as_mut(), env, mock_info("keeper", &[]), refund).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![
+                SubMsg::new(BankMsg::Send {
+                    to_address: "sender0001".to_string(),
+                    amount: coins(1000, "tokens"),
+                }),
+                SubMsg::new(BankMsg::Send {
+                    to_address: "keeper".to_string(),
+                    amount: coins(5, "tokens"),
+                }),
+                SubMsg::new(BankMsg::Send {
+                    to_address: "payer".to_string(),
+                    amount: coins(5, "tokens"),
+                }),
+            ]
+        );
+    }
+
+    /// test that each swap gets a distinct escrow id derived from the contract address and its id
+    #[test]
+    fn test_escrow_id() {
+        let mut deps = mock_dependencies();
+
+        let info = mock_info("anyone", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, InstantiateMsg {}).unwrap();
+
+        let create = CreateMsg {
+            id: "swap0001".to_string(),
+            hash: real_hash(),
+            recipient: "rcpt0001".into(),
+            expires: Expiration::AtHeight(123456),
+            ..Default::default()
+        };
+        let info = mock_info("sender0001", &coins(1000, "tokens"));
+        let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Create(create)).unwrap();
+
+        // sha-256 of the contract address followed by the swap id
+        let expected = hex::encode(Sha256::digest(
+            format!("{}swap0001", mock_env().contract.address).as_bytes()
+        ));
+        assert_eq!(("escrow_id", expected.as_str()), res.attributes[6]);
+
+        let query_msg = QueryMsg::Details { id: "swap0001".to_string() };
+        let res: DetailsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(res.escrow_id, expected);
+        assert_ne!(res.escrow_id, escrow_id(&mock_env().contract.address, "swap0002"));
+    }
+
+    /// test that an upstream cw20-atomic-swap deployment is taken over with its swaps converted
+    #[test]
+    fn test_migrate_from_cw20_atomic_swap() {
+        let mut deps = mock_dependencies();
+        deps.querier.update_wasm(|query| match query {
+            WasmQuery::ContractInfo { .. } => {
+                let mut info = ContractInfoResponse::default();
+                info.admin = Some("admin".to_string());
+                SystemResult::Ok(ContractResult::Ok(to_binary(&info).unwrap()))
+            }
+            _ => panic!("unexpected query"),
+        });
+
+        let old_swap = OldAtomicSwap {
+            hash: Binary::from(hex::decode(real_hash()).unwrap()),
+            recipient: Addr::unchecked("rcpt0001"),
+            source: Addr::unchecked("sender0001"),
+            expires: Expiration::AtHeight(123456),
+            balance: cw20::Balance::from(coins(1000, "tokens")),
+        };
+        OLD_SWAPS.save(deps.as_mut().storage, "swap0001", &old_swap).unwrap();
+
+        // Cannot migrate, not an upstream deployment
+        cw2::set_contract_version(deps.as_mut().storage, "crates.io:other", "1.0.0").unwrap();
+        let msg = MigrateMsg::from(Migration::FromCw20AtomicSwap {});
+        let err = migrate(deps.as_mut(), mock_env(), msg.clone()).unwrap_err();
+        assert_eq!(err, ContractError::UnsupportedMigration("crates.io:other".to_string()));
+
+        // Migrates the upstream deployment, its admin becoming the admin
+        cw2::set_contract_version(deps.as_mut().storage, "crates.io:cw20-atomic-swap", "1.1.0")
+            .unwrap();
+        migrate(deps.as_mut(), mock_env(), msg).unwrap();
+        assert!(OLD_SWAPS.is_empty(deps.as_ref().storage));
+        let version = cw2::get_contract_version(deps.as_ref().storage).unwrap();
+        assert_eq!(version.contract, "crates.io:atomic-swap");
+
+        let query_msg = QueryMsg::Details { id: "swap0001".to_string() };
+        let res: DetailsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(res.hash, real_hash());
+        assert_eq!(res.balance, BalanceHuman::Native(coins(1000, "tokens")));
+        assert_eq!(res.created_at, None);
+
+        let res: ConfigResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+        assert_eq!(res.admin, "admin");
+    }
+
+    /// test that the swaps about to expire are listed, by height or time depending on the swap
+    #[test]
+    fn test_expiring_within() {
+        let mut deps = mock_dependencies();
+
+        let info = mock_info("anyone", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, InstantiateMsg {}).unwrap();
+
+        let env = mock_env();
+        let expirations = [
+            ("swap0001", Expiration::AtHeight(env.block.height + 10)),
+            ("swap0002", Expiration::AtHeight(env.block.height + 1000)),
+            ("swap0003", Expiration::AtTime(env.block.time.plus_seconds(60))),
+            ("swap0004", Expiration::AtTime(env.block.time.plus_seconds(6000))),
+        ];
+        for (id, expires) in expirations {
+            let create = CreateMsg {
+                id: id.to_string(),
+                hash: real_hash(),
+                recipient: "rcpt0001".into(),
+                expires,
+                ..Default::default()
+            };
+            let info = mock_info("sender0001", &coins(1000, "tokens"));
+            execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Create(create)).unwrap();
+        }
+
+        let expiring = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, env: Env, blocks, seconds| {
+            let query_msg =
+                QueryMsg::ExpiringWithin { blocks, seconds, start_after: None, limit: None };
+            let res: ExpiringWithinResponse =
+                from_binary(&query(deps.as_ref(), env, query_msg).unwrap()).unwrap();
+            res.swaps.into_iter().map(|swap| swap.id).collect::<Vec<_>>()
+        };
+        assert_eq!(expiring(&deps, mock_env(), Some(100), None), vec!["swap0001"]);
+        assert_eq!(expiring(&deps, mock_env(), None, Some(120)), vec!["swap0003"]);
+        assert_eq!(expiring(&deps, mock_env(), Some(100), Some(120)), vec!["swap0001", "swap0003"]);
+
+        // paginated by id
+        let query_msg = QueryMsg::ExpiringWithin {
+            blocks      : Some(100),
+            seconds     : Some(120),
+            start_after : Some("swap0001".to_string()),
+            limit       : None,
+        };
+        let res: ExpiringWithinResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(res.swaps.len(), 1);
+        assert_eq!(res.swaps[0].id, "swap0003");
+
+        // swaps already expired are not listed
+        let env = mock_env_height(env.block.height + 10);
+        assert_eq!(expiring(&deps, env, Some(2000), None), vec!["swap0002"]);
+    }
+
+    /// test that the open swaps dumped from an instance are restored into another, if backed
+    #[test]
+    fn test_dump_restore_state() {
+        let mut deps = mock_dependencies();
+
+        let info = mock_info("anyone", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, InstantiateMsg {}).unwrap();
+        for id in ["swap0001", "swap0002"] {
+            let create = CreateMsg {
+                id: id.to_string(),
+                hash: real_hash(),
+                recipient: "rcpt0001".into(),
+                expires: Expiration::AtHeight(123456),
+                ..Default::default()
+            };
+            let info = mock_info("sender0001", &coins(500, "tokens"));
+            execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Create(create)).unwrap();
+        }
+        let query_msg = QueryMsg::DumpState { start_after: None, limit: None };
+        let dump: DumpStateResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(dump.entries.len(), 2);
+        let restore = MigrateMsg::from(Migration::RestoreState { entries: dump.entries });
+
+        // Cannot restore, the new instance does not hold enough to back the swaps
+        let mut deps = mock_dependencies_with_balance(&coins(999, "tokens"));
+        let info = mock_info("admin", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, InstantiateMsg {}).unwrap();
+        let err = migrate(deps.as_mut(), mock_env(), restore.clone()).unwrap_err();
+        assert_eq!(err, ContractError::UnbackedState("1000tokens".to_string()));
+
+        // Restores into an instance holding the swaps' funds
+        let mut deps = mock_dependencies_with_balance(&coins(1000, "tokens"));
+        let info = mock_info("admin", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, InstantiateMsg {}).unwrap();
+        migrate(deps.as_mut(), mock_env(), restore.clone()).unwrap();
+
+        let query_msg = QueryMsg::Details { id: "swap0002".to_string() };
+        let res: DetailsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(res.source, "sender0001");
+        assert_eq!(res.balance, BalanceHuman::Native(coins(500, "tokens")));
+
+        // Cannot restore over open swaps
+        let err = migrate(deps.as_mut(), mock_env(), restore).unwrap_err();
+        assert_eq!(err, ContractError::AlreadyExists);
+    }
+
+    /// test that a mirrored create locks the swap and creates its counter-swap on the partner
+    #[test]
+    fn test_create_mirrored() {
+        let mut deps = mock_dependencies();
+
+        let info = mock_info("anyone", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, InstantiateMsg {}).unwrap();
+
+        let create = CreateMsg {
+            id: "swap0001".to_string(),
+            hash: real_hash(),
+            recipient: "rcpt0001".into(),
+            expires: Expiration::AtHeight(123456),
+            ask_denom: Some("other".to_string()),
+            ask_amount: Some(Uint128::new(300)),
+            ..Default::default()
+        };
+        let partner_create = CreateMsg {
+            id: "swap0002".to_string(),
+            hash: real_hash(),
+            recipient: "rcpt0002".into(),
+            expires: Expiration::AtHeight(123000),
+            ..Default::default()
+        };
+        let mirrored = |create: CreateMsg| ExecuteMsg::CreateMirrored {
+            create,
+            partner_contract: "partner".to_string(),
+            partner_create: partner_create.clone(),
+        };
+
+        // Cannot create, the ask is not attached
+        let info = mock_info("sender0001", &coins(1000, "tokens"));
+        let err = execute(deps.as_mut(), mock_env(), info, mirrored(create.clone())).unwrap_err();
+        assert_eq!(err, ContractError::InvalidAsk);
+
+        // Creates the swap with the rest of the funds, and its counter-swap with the ask
+        let info = mock_info("sender0001", &[coin(1000, "tokens"), coin(300, "other")]);
+        let res = execute(deps.as_mut(), mock_env(), info, mirrored(create)).unwrap();
+        assert_eq!(("partner_contract", "partner"), res.attributes.last().unwrap());
+        let expected = ExecuteMsg::CreateCounterSwap(CreateMsg {
+            source: Some("sender0001".to_string()),
+            ..partner_create.clone()
+        });
+        assert_eq!(res.messages, vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: "partner".to_string(),
+            msg: to_binary(&expected).unwrap(),
+            funds: coins(300, "other"),
+        })]);
+
+        let query_msg = QueryMsg::Details { id: "swap0001".to_string() };
+        let res: DetailsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(res.source, "sender0001");
+        assert_eq!(res.balance, BalanceHuman::Native(coins(1000, "tokens")));
+
+        // Cannot designate the source on a plain create
+        let info = mock_info("partner", &coins(300, "other"));
+        let plain = ExecuteMsg::Create(CreateMsg {
+            source: Some("sender0001".to_string()),
+            ..partner_create.clone()
+        });
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), plain).unwrap_err();
+        assert_eq!(err, ContractError::SourceNotAllowed);
+        let receive = Cw20ReceiveMsg {
+            sender: "partner".to_string(),
+            amount: Uint128::new(300),
+            msg: to_binary(&ReceiveMsg::Create(CreateMsg {
+                source: Some("sender0001".to_string()),
+                ..partner_create.clone()
+            }))
+            .unwrap(),
+        };
+        let token = mock_info("my_cw20_token", &[]);
+        let err = execute(deps.as_mut(), mock_env(), token, ExecuteMsg::Receive(receive))
+            .unwrap_err();
+        assert_eq!(err, ContractError::SourceNotAllowed);
+
+        // Nor create a counter-swap, unless a contract of the same code
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), expected.clone()).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        deps.querier.update_wasm(|query| match query {
+            WasmQuery::ContractInfo { contract_addr } => {
+                let mut info = ContractInfoResponse::default();
+                info.code_id = if contract_addr == "partner" { 2 } else { 1 };
+                SystemResult::Ok(ContractResult::Ok(to_binary(&info).unwrap()))
+            }
+            _ => panic!("unexpected query"),
+        });
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), expected.clone()).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        // The counter-swap, created by the partner, refunds to the designated source
+        deps.querier.update_wasm(|query| match query {
+            WasmQuery::ContractInfo { .. } => {
+                let mut info = ContractInfoResponse::default();
+                info.code_id = 1;
+                SystemResult::Ok(ContractResult::Ok(to_binary(&info).unwrap()))
+            }
+            _ => panic!("unexpected query"),
+        });
+        execute(deps.as_mut(), mock_env(), info, expected).unwrap();
+        let refund = ExecuteMsg::Refund { id: "swap0002".to_string() };
+        let res = execute(deps.as_mut(), mock_env_height(123001), mock_info("anyone", &[]), refund)
+            .unwrap();
+        assert_eq!(res.messages, vec![SubMsg::new(BankMsg::Send {
+            to_address: "sender0001".to_string(),
+            amount: coins(300, "other"),
+        })]);
+    }
+
+    /// test that a partial swap is released in installments, staying open until drained
+    #[test]
+    fn test_partial_release() {
+        let mut deps = mock_dependencies();
+
+        let info = mock_info("anyone", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, InstantiateMsg {}).unwrap();
+
+        let create = |id: &str, partial: Option<bool>| CreateMsg {
+            id: id.to_string(),
+            hash: real_hash(),
+            recipient: "rcpt0001".into(),
+            expires: Expiration::AtHeight(123456),
+            partial,
+            ..Default::default()
+        };
+        let release = |id: &str, amount: Option<u128>| ExecuteMsg::Release {
+            id: id.to_string(),
+            preimage: preimage(),
+            amount: amount.map(Uint128::new),
+        };
+
+        // Cannot create a partial swap of several coins
+        let info = mock_info("sender0001", &[coin(1000, "tokens"), coin(10, "other")]);
+        let msg = ExecuteMsg::Create(create("swap0001", Some(true)));
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidPartial);
+
+        // Cannot release an installment of a regular swap
+        let info = mock_info("sender0001", &coins(1000, "tokens"));
+        let msg = ExecuteMsg::Create(create("swap0001", None));
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        let msg = release("swap0001", Some(300));
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidPartial);
+
+        // Cannot release an empty installment, or more than the balance
+        let msg = ExecuteMsg::Create(create("swap0002", Some(true)));
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        for amount in [0, 1001] {
+            let msg = release("swap0002", Some(amount));
+            let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+            assert_eq!(err, ContractError::InvalidInstallment);
+        }
+
+        // Releases an installment, the swap staying open with the rest
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), release("swap0002", Some(300)))
+            .unwrap();
+        assert_eq!(res.messages, vec![SubMsg::new(BankMsg::Send {
+            to_address: "rcpt0001".to_string(),
+            amount: coins(300, "tokens"),
+        })]);
+        assert_eq!(("remaining", "700tokens"), res.attributes.last().unwrap());
+        let query_msg = QueryMsg::Details { id: "swap0002".to_string() };
+        let res: DetailsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg.clone()).unwrap()).unwrap();
+        assert!(res.partial);
+        assert_eq!(res.balance, BalanceHuman::Native(coins(700, "tokens")));
+
+        // Releases the rest, closing the swap
+        let res = execute(deps.as_mut(), mock_env(), info, release("swap0002", None)).unwrap();
+        assert_eq!(res.messages, vec![SubMsg::new(BankMsg::Send {
+            to_address: "rcpt0001".to_string(),
+            amount: coins(700, "tokens"),
+        })]);
+        assert!(query(deps.as_ref(), mock_env(), query_msg).is_err());
+    }
+
+    /// test that swaps cannot be locked with the contract itself, and that those which were are
+    /// rescued by migration
+    #[test]
+    fn test_self_swap() {
+        let mut deps = mock_dependencies();
+
+        let info = mock_info("admin", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, InstantiateMsg {}).unwrap();
+        let contract = mock_env().contract.address.to_string();
+
+        // Cannot create, the contract is the recipient or the source
+        let create = CreateMsg {
+            id: "swap0001".to_string(),
+            hash: real_hash(),
+            recipient: contract.clone(),
+            expires: Expiration::AtHeight(123456),
+            ..Default::default()
+        };
+        let info = mock_info("sender0001", &coins(100, "tokens"));
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), ExecuteMsg::Create(create))
+            .unwrap_err();
+        assert_eq!(err, ContractError::SelfSwap);
+        let create = CreateMsg {
+            id: "swap0001".to_string(),
+            hash: real_hash(),
+            recipient: "rcpt0001".into(),
+            source: Some(contract.clone()),
+            expires: Expiration::AtHeight(123456),
+            ..Default::default()
+        };
+        let msg = ExecuteMsg::IbcHookCreate(create);
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+        assert_eq!(err, ContractError::SelfSwap);
+
+        // Swaps locked with the contract before the check existed
+        for id in ["swap0001", "swap0002", "swap0003"] {
+            let create = CreateMsg {
+                id: id.to_string(),
+                hash: real_hash(),
+                recipient: "rcpt0001".into(),
+                expires: Expiration::AtHeight(123456),
+                ..Default::default()
+            };
+            execute(deps.as_mut(), mock_env(), info.clone(), ExecuteMsg::Create(create)).unwrap();
+        }
+        let mut swap = swaps().load(deps.as_ref().storage, "swap0001").unwrap();
+        swap.recipient = Addr::unchecked(&contract);
+        swaps().save(deps.as_mut().storage, "swap0001", &swap).unwrap();
+        let mut swap = swaps().load(deps.as_ref().storage, "swap0002").unwrap();
+        swap.source = Addr::unchecked(&contract);
+        swaps().save(deps.as_mut().storage, "swap0002", &swap).unwrap();
+
+        // Rescued, refunding the source or the address given instead of the contract
+        let msg = Migration::RescueSelfSwaps { refund_to: "admin".to_string() }.into();
+        let res = migrate(deps.as_mut(), mock_env(), msg).unwrap();
+        assert_eq!(res.messages, vec![
+            SubMsg::new(BankMsg::Send {
+                to_address: "sender0001".to_string(),
+                amount: coins(100, "tokens"),
+            }),
+            SubMsg::new(BankMsg::Send {
+                to_address: "admin".to_string(),
+                amount: coins(100, "tokens"),
+            }),
+        ]);
+        assert!(res.attributes.iter().any(|a| a.key == "open_swaps" && a.value == "1"));
+        assert!(swaps().has(deps.as_ref().storage, "swap0003"));
+    }
+
+    /// test that the swaps between two counterparties are listed through the pair index, including
+    /// those stored before the index existed once migrated
+    #[test]
+    fn test_list_by_pair() {
+        let mut deps = mock_dependencies();
+
+        let info = mock_info("anyone", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, InstantiateMsg {}).unwrap();
+        let pairs = [
+            ("swap0001", "sender0001", "rcpt0001"),
+            ("swap0002", "sender0001", "rcpt0002"),
+            ("swap0003", "rcpt0001", "sender0001"),
+            ("swap0004", "sender0001", "rcpt0001"),
+        ];
+        for (id, source, recipient) in pairs {
+            let create = CreateMsg {
+                id: id.to_string(),
+                hash: real_hash(),
+                recipient: recipient.to_string(),
+                expires: Expiration::AtHeight(123456),
+                ..Default::default()
+            };
+            let info = mock_info(source, &coins(100, "tokens"));
+            execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Create(create)).unwrap();
+        }
+
+        let list_by_pair = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, start_after, limit| {
+            let query_msg = QueryMsg::ListByPair {
+                source: "sender0001".to_string(),
+                recipient: "rcpt0001".to_string(),
+                start_after,
+                limit,
+            };
+            let res: ListResponse =
+                from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+            res.swaps
+        };
+        assert_eq!(list_by_pair(&deps, None, None), vec!["swap0001", "swap0004"]);
+        assert_eq!(list_by_pair(&deps, None, Some(1)), vec!["swap0001"]);
+        assert_eq!(list_by_pair(&deps, Some("swap0001".to_string()), None), vec!["swap0004"]);
+
+        // closed swaps are no longer listed
+        let release = ExecuteMsg::Release {
+            id: "swap0001".to_string(),
+            preimage: preimage(),
+            amount: None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), release).unwrap();
+        assert_eq!(list_by_pair(&deps, None, None), vec!["swap0004"]);
+
+        // swaps stored without the index are indexed by migrating
+        let unindexed: cw_storage_plus::Map<&str, AtomicSwap> =
+            cw_storage_plus::Map::new("new_atomic_swap");
+        let swap = swaps().load(deps.as_ref().storage, "swap0004").unwrap();
+        unindexed.save(deps.as_mut().storage, "swap0005", &swap).unwrap();
+        assert_eq!(list_by_pair(&deps, None, None), vec!["swap0004"]);
+        migrate(deps.as_mut(), mock_env(), Migration::Upgrade {}.into()).unwrap();
+        assert_eq!(list_by_pair(&deps, None, None), vec!["swap0004", "swap0005"]);
+
+        // the empty message older versions migrated with upgrades as well
+        let msg: MigrateMsg = from_binary(&Binary::from(b"{}".to_vec())).unwrap();
+        assert_eq!(msg, MigrateMsg::Empty {});
+        migrate(deps.as_mut(), mock_env(), msg).unwrap();
+        let msg: MigrateMsg = from_binary(&Binary::from(br#"{"upgrade":{}}"#.to_vec())).unwrap();
+        assert_eq!(msg, Migration::Upgrade {}.into());
+        assert!(from_binary::<MigrateMsg>(&Binary::from(br#"{"bogus":{}}"#.to_vec())).is_err());
+    }
+
+
+    /// Revealed hash test - once a swap is released, its hash can no longer lock new swaps unless
+    /// allowed by the configuration
+    #[test]
+    fn test_revealed_hash() {
+        let mut deps = mock_dependencies();
+
+        let info = mock_info("admin", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, InstantiateMsg {}).unwrap();
+        let create = |id: &str| CreateMsg {
+            id: id.to_string(),
+            hash: real_hash(),
+            recipient: "rcpt0001".to_string(),
+            expires: Expiration::AtHeight(123456),
+            ..Default::default()
+        };
+        let info = mock_info("sender0001", &coins(100, "tokens"));
+        execute(deps.as_mut(), mock_env(), info.clone(), ExecuteMsg::Create(create("swap0001")))
+            .unwrap();
+
+        // a swap can share a hash which has not been revealed yet
+        execute(deps.as_mut(), mock_env(), info.clone(), ExecuteMsg::Create(create("swap0002")))
+            .unwrap();
+
+        // releasing reveals the preimage, after which the hash is rejected
+        let release = ExecuteMsg::Release {
+            id: "swap0001".to_string(),
+            preimage: preimage(),
+            amount: None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), release).unwrap();
+        let err = execute(
+            deps.as_mut(), mock_env(), info.clone(), ExecuteMsg::Create(create("swap0003"))
+        ).unwrap_err();
+        assert_eq!(err, ContractError::HashRevealed);
+
+        // other hashes are still accepted
+        let other = CreateMsg { hash: custom_hash(1), ..create("swap0003") };
+        execute(deps.as_mut(), mock_env(), info.clone(), ExecuteMsg::Create(other)).unwrap();
+
+        // unless the admin allows revealed hashes
+        let update = UpdateConfigMsg { allow_revealed_hashes: Some(true), ..Default::default() };
+        execute(
+            deps.as_mut(), mock_env(), mock_info("admin", &[]), ExecuteMsg::UpdateConfig(update)
+        ).unwrap();
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Create(create("swap0004"))).unwrap();
+    }
+
+
+    /// Price oracle test - the details of a swap hold the value of its balance once an oracle is
+    /// configured, unless an asset is not priced
+    #[test]
+    fn test_price_oracle() {
+        let mut deps = mock_dependencies();
+
+        let info = mock_info("admin", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, InstantiateMsg {}).unwrap();
+
+        // the oracle only prices "tokens", at 1.5, and the Cw20 token at 0.25
+        deps.querier.update_wasm(|query| match query {
+            WasmQuery::Smart { contract_addr, msg } if contract_addr == "prices" => {
+                let PriceQueryMsg::Price { asset } = from_binary(msg).unwrap();
+                let price = match asset.as_str() {
+                    "tokens" => Decimal::percent(150),
+                    "cw20token" => Decimal::percent(25),
+                    _ => return SystemResult::Ok(ContractResult::Err("no price".to_string())),
+                };
+                SystemResult::Ok(ContractResult::Ok(to_binary(&PriceResponse { price }).unwrap()))
+            }
+            _ => panic!("unexpected query"),
+        });
+
+        let create = |id: &str| CreateMsg {
+            id: id.to_string(),
+            hash: real_hash(),
+            recipient: "rcpt0001".to_string(),
+            expires: Expiration::AtHeight(123456),
+            ..Default::default()
+        };
+        let info = mock_info("sender0001", &coins(1000, "tokens"));
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Create(create("swap0001"))).unwrap();
+        let info = mock_info("sender0001", &coins(1000, "other"));
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Create(create("swap0002"))).unwrap();
+        let receive = Cw20ReceiveMsg {
+            sender: "sender0001".to_string(),
+            amount: Uint128::new(400),
+            msg: to_binary(&ReceiveMsg::Create(create("swap0003"))).unwrap(),
+        };
+        let info = mock_info("cw20token", &[]);
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Receive(receive)).unwrap();
+
+        let value = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, id: &str| {
+            let query_msg = QueryMsg::Details { id: id.to_string() };
+            let res: DetailsResponse =
+                from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+            res.value
+        };
+
+        // no value without an oracle
+        assert_eq!(value(&deps, "swap0001"), None);
+
+        let update = UpdateConfigMsg {
+            price_oracle: Some("prices".to_string()),
+            ..Default::default()
+        };
+        let info = mock_info("admin", &[]);
+        execute(deps.as_mut(), mock_env(), info.clone(), ExecuteMsg::UpdateConfig(update)).unwrap();
+        assert_eq!(value(&deps, "swap0001"), Some(Uint128::new(1500)));
+        assert_eq!(value(&deps, "swap0002"), None);
+        assert_eq!(value(&deps, "swap0003"), Some(Uint128::new(100)));
+
+        // removing the oracle
+        let update = UpdateConfigMsg {
+            price_oracle: Some(String::new()),
+            ..Default::default()
+        };
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::UpdateConfig(update)).unwrap();
+        assert_eq!(value(&deps, "swap0001"), None);
+    }
+
+
+    /// Commit-reveal test - a commit-reveal swap is only released by revealing a commitment, in a
+    /// later block, by the committer
+    #[test]
+    fn test_commit_reveal() {
+        let mut deps = mock_dependencies();
+
+        let info = mock_info("anyone", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, InstantiateMsg {}).unwrap();
+        let create = CreateMsg {
+            id: "swap0001".to_string(),
+            hash: real_hash(),
+            recipient: "rcpt0001".to_string(),
+            expires: Expiration::AtHeight(123456),
+            commit_reveal: Some(true),
+            ..Default::default()
+        };
+        let info = mock_info("sender0001", &coins(1000, "tokens"));
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Create(create.clone())).unwrap();
+
+        // Cannot release with the bare preimage
+        let release = ExecuteMsg::Release {
+            id: "swap0001".to_string(),
+            preimage: preimage(),
+            amount: None,
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), release).unwrap_err();
+        assert_eq!(err, ContractError::CommitRequired);
+
+        // Commit to the preimage
+        let salt = "some salt".to_string();
+        let salted = [preimage().as_bytes(), salt.as_bytes()].concat();
+        let commit = ExecuteMsg::CommitRelease {
+            id: "swap0001".to_string(),
+            salted_hash: hex::encode(Sha256::digest(&salted)),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("rcpt0001", &[]), commit.clone()).unwrap();
+        execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), commit).unwrap();
+        let reveal = |salt: &str| ExecuteMsg::RevealRelease {
+            id: "swap0001".to_string(),
+            preimage: preimage(),
+            salt: salt.to_string(),
+            amount: None,
+        };
+
+        // Cannot reveal in the same block, nor without a commitment, nor another salt
+        let height = mock_env().block.height;
+        let err = execute(deps.as_mut(), mock_env(), mock_info("rcpt0001", &[]), reveal(&salt))
+            .unwrap_err();
+        assert_eq!(err, ContractError::RevealTooEarly(height + 1));
+        let next = mock_env_height(height + 1);
+        let err = execute(deps.as_mut(), next.clone(), mock_info("sender0001", &[]), reveal(&salt))
+            .unwrap_err();
+        assert_eq!(err, ContractError::NoCommit);
+        let err = execute(deps.as_mut(), next.clone(), mock_info("rcpt0001", &[]), reveal("other"))
+            .unwrap_err();
+        assert_eq!(err, ContractError::InvalidPreimage {});
+
+        // Reveal in a later block
+        let res = execute(deps.as_mut(), next.clone(), mock_info("rcpt0001", &[]), reveal(&salt))
+            .unwrap();
+        assert_eq!(res.messages, vec![SubMsg::new(BankMsg::Send {
+            to_address: "rcpt0001".to_string(),
+            amount: coins(1000, "tokens"),
+        })]);
+        assert!(swaps().may_load(deps.as_ref().storage, "swap0001").unwrap().is_none());
+
+        // the other commits went with the swap, so none is revealed against one reusing its id
+        let info = mock_info("sender0001", &coins(1000, "tokens"));
+        let create = CreateMsg { hash: custom_hash(1), ..create };
+        execute(deps.as_mut(), next.clone(), info, ExecuteMsg::Create(create)).unwrap();
+        let err = execute(deps.as_mut(), next, mock_info("bot", &[]), reveal(&salt)).unwrap_err();
+        assert_eq!(err, ContractError::NoCommit);
+    }
+
+
+    /// Telemetry test - with telemetry on, every execute response carries the swap counters
+    #[test]
+    fn test_telemetry() {
+        let mut deps = mock_dependencies();
+
+        let info = mock_info("admin", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, InstantiateMsg {}).unwrap();
+        let create = |id: &str| CreateMsg {
+            id: id.to_string(),
+            hash: real_hash(),
+            recipient: "rcpt0001".to_string(),
+            expires: Expiration::AtHeight(123456),
+            ..Default::default()
+        };
+        let info = mock_info("sender0001", &coins(100, "tokens"));
+        let msg = ExecuteMsg::Create(create("swap0001"));
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        assert!(!res.attributes.iter().any(|attr| attr.key == "open_swaps"));
+
+        let update = UpdateConfigMsg { telemetry: Some(true), ..Default::default() };
+        let admin = mock_info("admin", &[]);
+        execute(deps.as_mut(), mock_env(), admin, ExecuteMsg::UpdateConfig(update)).unwrap();
+        execute(deps.as_mut(), mock_env(), info.clone(), ExecuteMsg::Create(create("swap0002")))
+            .unwrap();
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Create(create("swap0003"))).unwrap();
+        let release = ExecuteMsg::Release {
+            id: "swap0001".to_string(),
+            preimage: preimage(),
+            amount: None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), release).unwrap();
+        let refund = ExecuteMsg::Refund { id: "swap0002".to_string() };
+        let res = execute(deps.as_mut(), mock_env_height(123457), mock_info("anyone", &[]), refund)
+            .unwrap();
+        let counters = &res.attributes[res.attributes.len() - 4..];
+        assert_eq!(counters, [
+            ("open_swaps", "1"),
+            ("created_total", "3"),
+            ("released_total", "1"),
+            ("refunded_total", "1"),
+        ]);
+    }
+
+    #[test]
+    fn test_result_data() {
+        let mut deps = mock_dependencies();
+
+        let info = mock_info("admin", &[]);
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg {}).unwrap();
+        let update = UpdateConfigMsg { release_fee_bps: Some(100), ..Default::default() };
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::UpdateConfig(update)).unwrap();
+
+        // the release data holds the payout, net of the fee
+        let create = CreateMsg {
+            id: "swap0001".to_string(),
+            hash: real_hash(),
+            recipient: "rcpt0001".to_string(),
+            expires: Expiration::AtHeight(123456),
+            ..Default::default()
+        };
+        let info = mock_info("sender0001", &coins(1000, "tokens"));
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Create(create)).unwrap();
+        let release = ExecuteMsg::Release {
+            id: "swap0001".to_string(),
+            preimage: preimage(),
+            amount: None,
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), release).unwrap();
+        let result: ReleaseResult = from_binary(&res.data.unwrap()).unwrap();
+        assert_eq!(result, ReleaseResult {
+            id: "swap0001".to_string(),
+            recipient: "rcpt0001".to_string(),
+            amount: BalanceHuman::Native(coins(990, "tokens")),
+        });
+
+        // the refund data holds what the source gets back
+        let create = CreateMsg {
+            id: "swap0002".to_string(),
+            hash: custom_hash(1),
+            recipient: "rcpt0001".to_string(),
+            expires: Expiration::AtHeight(123456),
+            ..Default::default()
+        };
+        let receive = Cw20ReceiveMsg {
+            sender: "sender0001".to_string(),
+            amount: Uint128::new(500),
+            msg: to_binary(&ReceiveMsg::Create(create)).unwrap(),
+        };
+        let token = mock_info("token0001", &[]);
+        execute(deps.as_mut(), mock_env(), token, ExecuteMsg::Receive(receive)).unwrap();
+        let refund = ExecuteMsg::Refund { id: "swap0002".to_string() };
+        let res = execute(deps.as_mut(), mock_env_height(123457), mock_info("anyone", &[]), refund)
+            .unwrap();
+        let result: RefundResult = from_binary(&res.data.unwrap()).unwrap();
+        assert_eq!(result, RefundResult {
+            id: "swap0002".to_string(),
+            source: "sender0001".to_string(),
+            amount: BalanceHuman::Cw20(Cw20Coin {
+                address: "token0001".to_string(),
+                amount: Uint128::new(500),
+            }),
+        });
+    }
+
+    #[test]
+    fn test_accept_required() {
+        let mut deps = mock_dependencies();
+
+        let info = mock_info("admin", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, InstantiateMsg {}).unwrap();
+        let create = |id: &str, hash: String| CreateMsg {
+            id: id.to_string(),
+            hash,
+            recipient: "rcpt0001".to_string(),
+            expires: Expiration::AtHeight(123456),
+            accept_required: Some(true),
+            ..Default::default()
+        };
+        let info = mock_info("sender0001", &coins(100, "tokens"));
+        let msg = ExecuteMsg::Create(create("swap0001", real_hash()));
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        // not released before the recipient accepts it, which only they can do
+        let release = ExecuteMsg::Release {
+            id: "swap0001".to_string(),
+            preimage: preimage(),
+            amount: None,
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), release.clone())
+            .unwrap_err();
+        assert_eq!(err, ContractError::NotAccepted);
+        let accept = ExecuteMsg::Accept { id: "swap0001".to_string() };
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), accept.clone()).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let rcpt = mock_info("rcpt0001", &[]);
+        execute(deps.as_mut(), mock_env(), rcpt.clone(), accept.clone()).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), rcpt.clone(), accept).unwrap_err();
+        assert_eq!(err, ContractError::AlreadyAccepted);
+        let query_msg = QueryMsg::Details { id: "swap0001".to_string() };
+        let details: DetailsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert!(details.accept_required && details.accepted);
+
+        // once accepted, the source can no longer cancel it
+        let cancel = ExecuteMsg::Cancel { id: "swap0001".to_string() };
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), cancel).unwrap_err();
+        assert_eq!(err, ContractError::AlreadyAccepted);
+        execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), release).unwrap();
+
+        // an unaccepted swap is cancelled by its source before it expires
+        let msg = ExecuteMsg::Create(create("swap0002", custom_hash(1)));
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        let cancel = ExecuteMsg::Cancel { id: "swap0002".to_string() };
+        let err = execute(deps.as_mut(), mock_env(), rcpt, cancel.clone()).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), cancel).unwrap();
+        assert_eq!(res.messages, vec![SubMsg::new(BankMsg::Send {
+            to_address: "sender0001".to_string(),
+            amount: coins(100, "tokens"),
+        })]);
+        assert!(swaps().may_load(deps.as_ref().storage, "swap0002").unwrap().is_none());
+
+        // swaps not requiring acceptance can be neither accepted nor cancelled
+        let msg = ExecuteMsg::Create(CreateMsg {
+            accept_required: None,
+            ..create("swap0003", custom_hash(2))
+        });
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        let cancel = ExecuteMsg::Cancel { id: "swap0003".to_string() };
+        let err = execute(deps.as_mut(), mock_env(), info, cancel).unwrap_err();
+        assert_eq!(err, ContractError::AcceptNotRequired);
+    }
+
+    #[test]
+    fn test_payout_retry() {
+        let mut deps = mock_dependencies();
+
+        let admin = mock_info("admin", &[]);
+        instantiate(deps.as_mut(), mock_env(), admin.clone(), InstantiateMsg {}).unwrap();
+        let retry = PayoutRetry { max_attempts: 2, base_delay: 10 };
+        let update = UpdateConfigMsg { payout_retry: Some(retry), ..Default::default() };
+        execute(deps.as_mut(), mock_env(), admin, ExecuteMsg::UpdateConfig(update)).unwrap();
+
+        // the Cw20 payout is sent as a submessage replying to the contract
+        let create = CreateMsg {
+            id: "swap0001".to_string(),
+            hash: real_hash(),
+            recipient: "rcpt0001".to_string(),
+            expires: Expiration::AtHeight(123456),
+            ..Default::default()
+        };
+        let receive = Cw20ReceiveMsg {
+            sender: "sender0001".to_string(),
+            amount: Uint128::new(500),
+            msg: to_binary(&ReceiveMsg::Create(create)).unwrap(),
+        };
+        let token = mock_info("token0001", &[]);
+        execute(deps.as_mut(), mock_env(), token, ExecuteMsg::Receive(receive)).unwrap();
+        let release = ExecuteMsg::Release {
+            id: "swap0001".to_string(),
+            preimage: preimage(),
+            amount: None,
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), release).unwrap();
+        let transfer = WasmMsg::Execute {
+            contract_addr: "token0001".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "rcpt0001".to_string(),
+                amount: Uint128::new(500),
+            }).unwrap(),
+            funds: vec![],
+        };
+        assert_eq!(res.messages, vec![SubMsg::reply_always(transfer.clone(), 1)]);
+
+        // a failed payout waits to be retried, the delay doubling with each failure
+        let failed = |id: u64| Reply { id, result: SubMsgResult::Err("paused".to_string()) };
+        reply(deps.as_mut(), mock_env_height(100), failed(1)).unwrap();
+        let pending = |deps: Deps| -> PendingPayoutsResponse {
+            let msg = QueryMsg::PendingPayouts { start_after: None, limit: None };
+            from_binary(&query(deps, mock_env(), msg).unwrap()).unwrap()
+        };
+        let payouts = pending(deps.as_ref()).payouts;
+        assert_eq!((payouts[0].attempts, payouts[0].retry_at), (1, 110));
+        let retry = ExecuteMsg::RetryPayout { id: 1 };
+        let anyone = mock_info("anyone", &[]);
+        let err = execute(deps.as_mut(), mock_env_height(109), anyone.clone(), retry.clone())
+            .unwrap_err();
+        assert_eq!(err, ContractError::RetryTooEarly(110));
+        let res = execute(deps.as_mut(), mock_env_height(110), anyone.clone(), retry.clone())
+            .unwrap();
+        assert_eq!(res.messages, vec![SubMsg::reply_always(transfer.clone(), 1)]);
+        reply(deps.as_mut(), mock_env_height(110), failed(1)).unwrap();
+        let payouts = pending(deps.as_ref()).payouts;
+        assert_eq!((payouts[0].attempts, payouts[0].retry_at), (2, 130));
+
+        // once the attempts are exhausted, only the recipient can retry, with no delay
+        let err = execute(deps.as_mut(), mock_env_height(130), anyone.clone(), retry.clone())
+            .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let rcpt = mock_info("rcpt0001", &[]);
+        execute(deps.as_mut(), mock_env_height(111), rcpt, retry).unwrap();
+        let delivered = Reply {
+            id: 1,
+            result: SubMsgResult::Ok(SubMsgResponse { events: vec![], data: None }),
+        };
+        reply(deps.as_mut(), mock_env_height(111), delivered).unwrap();
+        assert!(pending(deps.as_ref()).payouts.is_empty());
+    }
+
+    #[test]
+    fn test_limits() {
+        let mut deps = mock_dependencies();
+
+        let admin = mock_info("admin", &[]);
+        instantiate(deps.as_mut(), mock_env(), admin.clone(), InstantiateMsg {}).unwrap();
+
+        // none of the limits can be zero
+        let zero = Limits { max_details_batch: 0, ..Default::default() };
+        let update = UpdateConfigMsg { limits: Some(zero), ..Default::default() };
+        let update = ExecuteMsg::UpdateConfig(update);
+        let err = execute(deps.as_mut(), mock_env(), admin.clone(), update).unwrap_err();
+        assert_eq!(err, ContractError::InvalidLimits);
+        let limits = Limits {
+            max_memo_bytes       : 8,
+            max_attachment_bytes : 4,
+            max_batch_size       : 1,
+            max_details_batch    : 1,
+            max_assets           : 1,
+        };
+        let update = UpdateConfigMsg { limits: Some(limits.clone()), ..Default::default() };
+        execute(deps.as_mut(), mock_env(), admin, ExecuteMsg::UpdateConfig(update)).unwrap();
+        let config: ConfigResponse = from_binary(
+            &query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()
+        ).unwrap();
+        assert_eq!(config.limits, limits);
+
+        // memos and attachments over their limits are rejected
+        let create = |id: &str| CreateMsg {
+            id: id.to_string(),
+            hash: real_hash(),
+            recipient: "rcpt0001".to_string(),
+            expires: Expiration::AtHeight(123456),
+            ..Default::default()
+        };
+        let sender = mock_info("sender0001", &coins(100, "tokens"));
+        let memo = Some("cosmoshub-4".to_string());
+        let msg = CreateMsg { counter_chain: memo, ..create("swap0001") };
+        let err = execute(deps.as_mut(), mock_env(), sender.clone(), ExecuteMsg::Create(msg))
+            .unwrap_err();
+        assert_eq!(err, ContractError::MemoTooLong { max: 8 });
+        let attachment = Some(Binary::from(b"order"));
+        let msg = CreateMsg { order_commitment: attachment, ..create("swap0001") };
+        let err = execute(deps.as_mut(), mock_env(), sender.clone(), ExecuteMsg::Create(msg))
+            .unwrap_err();
+        assert_eq!(err, ContractError::AttachmentTooLarge { max: 4 });
+        let two_assets = mock_info("sender0001", &[coin(100, "tokens"), coin(100, "uosmo")]);
+        let msg = ExecuteMsg::Create(create("swap0001"));
+        let err = execute(deps.as_mut(), mock_env(), two_assets, msg).unwrap_err();
+        assert_eq!(err, ContractError::TooManyAssets { max: 1 });
+        let msg = CreateMsg { counter_chain: Some("osmo-1".to_string()), ..create("swap0001") };
+        execute(deps.as_mut(), mock_env(), sender.clone(), ExecuteMsg::Create(msg)).unwrap();
+
+        // the details batch is limited too
+        let batch = |ids: &[&str]| QueryMsg::DetailsBatch {
+            ids: ids.iter().map(|id| id.to_string()).collect(),
+        };
+        let res: DetailsBatchResponse = from_binary(
+            &query(deps.as_ref(), mock_env(), batch(&["swap0001"])).unwrap()
+        ).unwrap();
+        assert_eq!(res.swaps.len(), 1);
+        assert_eq!(res.swaps[0].id, "swap0001");
+        let err = query(deps.as_ref(), mock_env(), batch(&["swap0001", "swap0002"])).unwrap_err();
+        assert_eq!(err, StdError::generic_err("Batch too large: at most 1 entries"));
+    }
+
+    #[test]
+    fn test_list_status() {
+        let mut deps = mock_dependencies();
+
+        let info = mock_info("anyone", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, InstantiateMsg {}).unwrap();
+
+        let env = mock_env_height(1000);
+        let swaps = [
+            ("swap0001", 1500),
+            ("swap0002", 1100),
+            ("swap0003", 1500),
+            ("swap0004", 1100),
+        ];
+        for (n, &(id, expires)) in swaps.iter().enumerate() {
+            let create = CreateMsg {
+                id: id.to_string(),
+                hash: custom_hash(n as u16 + 1),
+                recipient: "rcpt0001".into(),
+                expires: Expiration::AtHeight(expires),
+                ..Default::default()
+            };
+            let info = mock_info("sender0001", &coins(1000, "tokens"));
+            execute(deps.as_mut(), env.clone(), info, ExecuteMsg::Create(create)).unwrap();
+        }
+
+        // the third swap is released, and the fourth refunded once expired
+        let release = ExecuteMsg::Release {
+            id: "swap0003".to_string(),
+            preimage: custom_preimage(3),
+            amount: None,
+        };
+        execute(deps.as_mut(), env, mock_info("anyone", &[]), release).unwrap();
+        let refund = ExecuteMsg::Refund { id: "swap0004".to_string() };
+        execute(deps.as_mut(), mock_env_height(1200), mock_info("sender0001", &[]), refund)
+            .unwrap();
+
+        // each status lists its own swaps, settled ones included
+        let list = |start_after: Option<&str>, status: Option<SwapStatus>| -> Vec<String> {
+            let msg = QueryMsg::List {
+                start_after: start_after.map(String::from),
+                limit: None,
+                status,
+            };
+            let res: ListResponse =
+                from_binary(&query(deps.as_ref(), mock_env_height(1200), msg).unwrap()).unwrap();
+            res.swaps
+        };
+        assert_eq!(list(None, None), vec!["swap0001", "swap0002"]);
+        assert_eq!(list(None, Some(SwapStatus::Open)), vec!["swap0001"]);
+        assert_eq!(list(None, Some(SwapStatus::Expired)), vec!["swap0002"]);
+        assert_eq!(list(None, Some(SwapStatus::Released)), vec!["swap0003"]);
+        assert_eq!(list(None, Some(SwapStatus::Refunded)), vec!["swap0004"]);
+        assert!(list(Some("swap0003"), Some(SwapStatus::Released)).is_empty());
+    }
+
+    #[test]
+    fn test_ibc_hook_create() {
+        let mut deps = mock_dependencies();
+
+        let info = mock_info("anyone", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, InstantiateMsg {}).unwrap();
+
+        // the intermediate sender cannot be refunded, so the source must be named
+        let create = CreateMsg {
+            id: "swap0001".to_string(),
+            hash: real_hash(),
+            recipient: "rcpt0001".to_string(),
+            expires: Expiration::AtHeight(123456),
+            ..Default::default()
+        };
+        let intermediate = mock_info("hook0001", &coins(1000, "ibc/ATOM"));
+        let msg = ExecuteMsg::IbcHookCreate(create.clone());
+        let err = execute(deps.as_mut(), mock_env(), intermediate.clone(), msg).unwrap_err();
+        assert_eq!(err, ContractError::IbcHookSourceRequired);
+
+        // the transferred funds are locked, and refunded to the source
+        let create = CreateMsg { source: Some("user0001".to_string()), ..create };
+        let msg = ExecuteMsg::IbcHookCreate(create);
+        let res = execute(deps.as_mut(), mock_env(), intermediate, msg).unwrap();
+        assert!(res.attributes.iter().any(|a| a.key == "via" && a.value == "ibc_hook"));
+        let query_msg = QueryMsg::Details { id: "swap0001".to_string() };
+        let details: DetailsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(details.source, "user0001");
+        assert_eq!(details.balance, BalanceHuman::Native(coins(1000, "ibc/ATOM")));
+
+        let refund = ExecuteMsg::Refund { id: "swap0001".to_string() };
+        let user = mock_info("user0001", &[]);
+        let res = execute(deps.as_mut(), mock_env_height(123457), user, refund).unwrap();
+        assert_eq!(res.messages, vec![SubMsg::new(BankMsg::Send {
+            to_address: "user0001".to_string(),
+            amount: coins(1000, "ibc/ATOM"),
+        })]);
+    }
+
+    #[test]
+    fn test_scenario_expiry() {
+        let expires_in = 100;
+        let create = |id: &str, hash: String, partial: Option<bool>, height: u64| CreateMsg {
+            id: id.to_string(),
+            hash,
+            recipient: "rcpt0001".into(),
+            expires: Expiration::AtHeight(height + expires_in),
+            partial,
+            ..Default::default()
+        };
+        let funds = coins(1000, "tokens");
+        let start = mock_env().block.height;
+        let refund = ExecuteMsg::Refund { id: "swap0001".to_string() };
+
+        // an installment is released in time, the rest is refunded once expired
+        let scenario = Scenario::new()
+            .create("sender0001", &funds, create("swap0001", real_hash(), Some(true), start))
+            .advance_blocks(10)
+            .release_partial("swap0001", &preimage(), Some(Uint128::new(300)))
+            .execute_err("sender0001", &[], refund.clone(), ContractError::NotExpired)
+            .at_height(start + expires_in);
+        let balance = scenario.details("swap0001").balance;
+        assert_eq!(balance, BalanceHuman::Native(coins(700, "tokens")));
+        let release = ExecuteMsg::Release {
+            id: "swap0001".to_string(),
+            preimage: preimage(),
+            amount: None,
+        };
+        let scenario = scenario
+            .execute_err("anyone", &[], release, ContractError::Expired)
+            .execute("sender0001", &[], refund);
+        assert_eq!(scenario.last.messages, vec![SubMsg::new(BankMsg::Send {
+            to_address: "sender0001".to_string(),
+            amount: coins(700, "tokens"),
+        })]);
+        assert!(!scenario.exists("swap0001"));
+
+        // a swap created later expires later, and is released on its last block
+        let height = scenario.height();
+        let scenario = scenario
+            .create("sender0001", &funds, create("swap0002", custom_hash(1), None, height))
+            .advance_blocks(expires_in - 1)
+            .release("swap0002", &custom_preimage(1));
+        assert!(!scenario.exists("swap0002"));
+        assert_eq!(scenario.height(), height + expires_in - 1);
+    }
+
+    #[test]
+    fn test_sponsors() {
+        let mut deps = mock_dependencies();
+
+        let info = mock_info("admin0001", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, InstantiateMsg {}).unwrap();
+
+        // only the admin registers sponsors
+        let set_sponsor = ExecuteMsg::SetSponsor {
+            address: "sponsor0001".to_string(),
+            grant: Some(coin(50, "ugas")),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), set_sponsor.clone())
+            .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        execute(deps.as_mut(), mock_env(), mock_info("admin0001", &[]), set_sponsor).unwrap();
+
+        // budgets are funded in the grant's denom, by the sponsor
+        let fund = ExecuteMsg::FundSponsor {};
+        let anyone = mock_info("anyone", &coins(100, "ugas"));
+        let err = execute(deps.as_mut(), mock_env(), anyone, fund.clone()).unwrap_err();
+        assert_eq!(err, ContractError::NotSponsor);
+        let sponsor = mock_info("sponsor0001", &coins(100, "atom"));
+        let err = execute(deps.as_mut(), mock_env(), sponsor, fund.clone()).unwrap_err();
+        assert_eq!(err, ContractError::InvalidSponsorFunds("ugas".to_string()));
+        let sponsor = mock_info("sponsor0001", &coins(80, "ugas"));
+        execute(deps.as_mut(), mock_env(), sponsor, fund).unwrap();
+
+        // a sponsored swap creation grants its new user an allowance, only once
+        let sponsored = |swap: u16, user: &str| ExecuteMsg::Sponsored {
+            user: user.to_string(),
+            msg: Box::new(ExecuteMsg::Create(CreateMsg {
+                id: format!("swap{:04}", swap),
+                hash: custom_hash(swap),
+                recipient: user.to_string(),
+                expires: Expiration::AtHeight(123456),
+                ..Default::default()
+            })),
+        };
+        let sponsor = mock_info("sponsor0001", &coins(1000, "tokens"));
+        let msg = sponsored(1, "new0001");
+        let res = execute(deps.as_mut(), mock_env(), sponsor.clone(), msg).unwrap();
+        assert!(res.attributes.iter().any(|a| a.key == "fee_grant" && a.value == "50ugas"));
+        #[cfg(feature = "stargate")]
+        assert!(res.messages.iter().any(|m| matches!(&m.msg, cosmwasm_std::CosmosMsg::Stargate {
+            type_url, ..
+        } if type_url == "/cosmos.feegrant.v1beta1.MsgGrantAllowance")));
+        let query_msg = QueryMsg::Details { id: "swap0001".to_string() };
+        let details: DetailsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(details.source, "sponsor0001");
+        let msg = sponsored(2, "new0001");
+        let res = execute(deps.as_mut(), mock_env(), sponsor.clone(), msg).unwrap();
+        assert!(!res.attributes.iter().any(|a| a.key == "fee_grant"));
+
+        // the budget left cannot cover another user, and sponsored executions do not nest
+        let msg = sponsored(3, "new0002");
+        #[cfg(feature = "stargate")]
+        {
+            let err = execute(deps.as_mut(), mock_env(), sponsor.clone(), msg).unwrap_err();
+            assert_eq!(err, ContractError::SponsorBudgetExhausted { budget: Uint128::new(30) });
+        }
+        // unless granted off chain, where the budget is not drawn on
+        #[cfg(not(feature = "stargate"))]
+        {
+            let res = execute(deps.as_mut(), mock_env(), sponsor.clone(), msg).unwrap();
+            assert!(res.attributes.iter().any(|a| a.key == "fee_grant" && a.value == "50ugas"));
+            let stargate = |m: &SubMsg| matches!(m.msg, cosmwasm_std::CosmosMsg::Stargate { .. });
+            assert!(!res.messages.iter().any(stargate));
+        }
+        let nested = ExecuteMsg::Sponsored {
+            user: "new0002".to_string(),
+            msg: Box::new(sponsored(3, "new0002")),
+        };
+        let err = execute(deps.as_mut(), mock_env(), sponsor, nested).unwrap_err();
+        assert_eq!(err, ContractError::NestedSponsored);
+
+        // the usage is accounted per sponsor
+        let query_msg = QueryMsg::Sponsors { start_after: None, limit: None };
+        let res: SponsorsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        let (budget, grants) = match cfg!(feature = "stargate") {
+            true => (30, 1),
+            false => (80, 2),
+        };
+        assert_eq!(res.sponsors, vec![SponsorResponse {
+            address: "sponsor0001".to_string(),
+            grant: coin(50, "ugas"),
+            budget: Uint128::new(budget),
+            grants,
+            granted: Uint128::new(50 * grants as u128),
+        }]);
+
+        // removing the sponsor refunds what is left of its budget
+        let remove = ExecuteMsg::SetSponsor { address: "sponsor0001".to_string(), grant: None };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("admin0001", &[]), remove).unwrap();
+        assert_eq!(res.messages, vec![SubMsg::new(BankMsg::Send {
+            to_address: "sponsor0001".to_string(),
+            amount: coins(budget, "ugas"),
+        })]);
+    }
+
+    #[test]
+    fn test_auto_extend() {
+        let start = mock_env().block.height;
+        let create = |partial: Option<bool>| CreateMsg {
+            id: "swap0001".to_string(),
+            hash: real_hash(),
+            recipient: "rcpt0001".into(),
+            expires: Expiration::AtHeight(start + 20),
+            partial,
+            auto_extend: Some(ExpiryExtension { delta: 10, max_total: 15 }),
+            ..Default::default()
+        };
+        let funds = coins(1000, "tokens");
+        let installment = Some(Uint128::new(100));
+
+        // only partial swaps extend their expiration, within the range of expirations
+        let err = || ContractError::InvalidAutoExtend;
+        let unbounded = CreateMsg {
+            auto_extend: Some(ExpiryExtension { delta: u64::MAX, max_total: u64::MAX }),
+            ..create(Some(true))
+        };
+        let scenario = Scenario::new()
+            .execute_err("sender0001", &funds, ExecuteMsg::Create(create(None)), err())
+            .execute_err("sender0001", &funds, ExecuteMsg::Create(unbounded), err());
+
+        // every installment extends it, up to the cap
+        let scenario = scenario
+            .create("sender0001", &funds, create(Some(true)))
+            .advance_blocks(19)
+            .release_partial("swap0001", &preimage(), installment);
+        assert_eq!(scenario.details("swap0001").expires, Expiration::AtHeight(start + 30));
+        let scenario = scenario
+            .advance_blocks(10)
+            .release_partial("swap0001", &preimage(), installment);
+        let details = scenario.details("swap0001");
+        assert_eq!((details.expires, details.extended), (Expiration::AtHeight(start + 35), 15));
+        let scenario = scenario.release_partial("swap0001", &preimage(), installment);
+        assert_eq!(scenario.details("swap0001").expires, Expiration::AtHeight(start + 35));
+
+        // so that once the cap is reached, the swap expires as it would have
+        let refund = ExecuteMsg::Refund { id: "swap0001".to_string() };
+        let scenario = scenario
+            .at_height(start + 35)
+            .execute("sender0001", &[], refund);
+        assert!(!scenario.exists("swap0001"));
+    }
+
+
+    #[test]
+    fn test_inbox() {
+        let start = mock_env().block.height;
+        let create = |id: &str, expires: u64| CreateMsg {
+            id: id.to_string(),
+            hash: real_hash(),
+            recipient: "rcpt0001".into(),
+            expires: Expiration::AtHeight(start + expires),
+            partial: Some(true),
+            ..Default::default()
+        };
+        let inbox = |scenario: &Scenario, address: &str| {
+            let msg = QueryMsg::Inbox {
+                address: address.to_string(),
+                start_after: None,
+                limit: Some(30),
+            };
+            scenario
+                .query::<InboxResponse>(msg)
+                .events
+                .into_iter()
+                .map(|e| (e.id, e.kind, e.swap_id))
+                .collect::<Vec<_>>()
+        };
+        let funds = coins(1000, "tokens");
+
+        // the recipient is notified of the swaps created for them
+        let scenario = Scenario::new()
+            .create("sender0001", &funds, create("swap0001", 100))
+            .create("sender0001", &funds, create("swap0002", 10));
+        assert_eq!(inbox(&scenario, "rcpt0001"), vec![
+            (1, InboxEventKind::Created, "swap0001".to_string()),
+            (2, InboxEventKind::Created, "swap0002".to_string()),
+        ]);
+        assert!(inbox(&scenario, "sender0001").is_empty());
+
+        // the source of its swap being released, and both parties of its swap expiring
+        let scenario = scenario
+            .release_partial("swap0001", &preimage(), Some(Uint128::new(100)))
+            .at_height(start + 10)
+            .refund("keeper0001", "swap0002");
+        assert_eq!(inbox(&scenario, "sender0001"), vec![
+            (3, InboxEventKind::Released, "swap0001".to_string()),
+            (4, InboxEventKind::Expired, "swap0002".to_string()),
+        ]);
+        let expired = (5, InboxEventKind::Expired, "swap0002".to_string());
+        assert_eq!(inbox(&scenario, "rcpt0001")[2], expired);
+
+        // events are removed once acknowledged by the party, unknown ids being ignored
+        let mut scenario = scenario
+            .execute("sender0001", &[], ExecuteMsg::AckInbox { ids: vec![1, 3] });
+        assert_eq!(inbox(&scenario, "rcpt0001").len(), 3);
+        assert_eq!(inbox(&scenario, "sender0001").len(), 1);
+        let res = scenario
+            .try_execute("rcpt0001", &[], ExecuteMsg::AckInbox { ids: vec![1, 2, 99] })
+            .unwrap();
+        assert!(res.attributes.iter().any(|a| a.key == "acked" && a.value == "2"));
+        assert_eq!(inbox(&scenario, "rcpt0001"), vec![
+            (5, InboxEventKind::Expired, "swap0002".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn test_inbox_full() {
+        let funds = coins(10, "tokens");
+        let mut scenario = Scenario::new();
+        for i in 0..51 {
+            let create = CreateMsg {
+                id: format!("swap{:04}", i),
+                hash: real_hash(),
+                recipient: "rcpt0001".into(),
+                expires: Expiration::AtHeight(mock_env().block.height + 100),
+                ..Default::default()
+            };
+            scenario = scenario.create("sender0001", &funds, create);
+        }
+
+        // past the cap, the oldest unread events are dropped
+        let msg = QueryMsg::Inbox {
+            address: "rcpt0001".to_string(),
+            start_after: Some(40),
+            limit: None,
+        };
+        let events = scenario.query::<InboxResponse>(msg).events;
+        assert_eq!(events.iter().map(|e| e.id).collect::<Vec<_>>(), (41..=50).collect::<Vec<_>>());
+        let msg = QueryMsg::Inbox {
+            address: "rcpt0001".to_string(),
+            start_after: None,
+            limit: Some(1),
+        };
+        assert_eq!(scenario.query::<InboxResponse>(msg).events[0].swap_id, "swap0001");
+    }
+
+    #[test]
+    fn test_max_lock_duration() {
+        let env = mock_env();
+        let start = env.block.height;
+        let create = |id: &str, hash: String, expires: Expiration| CreateMsg {
+            id: id.to_string(),
+            hash,
+            recipient: "rcpt0001".into(),
+            expires,
+            partial: Some(true),
+            auto_extend: Some(ExpiryExtension { delta: 50, max_total: 500 }),
+            ..Default::default()
+        };
+        let funds = coins(1000, "tokens");
+        let max = |blocks, seconds| UpdateConfigMsg {
+            max_lock_duration: Some(LockDuration { blocks, seconds }),
+            ..Default::default()
+        };
+
+        // swaps expiring at a height are bounded, those expiring at a time are not
+        let err = ContractError::LockTooLong(Expiration::AtHeight(start + 100));
+        let too_long = create("swap0001", real_hash(), Expiration::AtHeight(start + 101));
+        let longest = create("swap0001", real_hash(), Expiration::AtHeight(start + 100));
+        let far = Expiration::AtTime(env.block.time.plus_seconds(100_000_000));
+        let scenario = Scenario::new()
+            .config(max(100, 0))
+            .execute_err("sender0001", &funds, ExecuteMsg::Create(too_long), err)
+            .create("sender0001", &funds, longest)
+            .create("sender0001", &funds, create("swap0002", real_hash(), far));
+        let config: ConfigResponse = scenario.query(QueryMsg::Config {});
+        assert_eq!(config.max_lock_duration, Some(LockDuration { blocks: 100, seconds: 0 }));
+
+        // extensions stop at the maximum duration from the block they are made at
+        let scenario = scenario
+            .advance_blocks(10)
+            .release_partial("swap0001", &preimage(), Some(Uint128::new(100)));
+        let details = scenario.details("swap0001");
+        assert_eq!((details.expires, details.extended), (Expiration::AtHeight(start + 110), 10));
+
+        // the maximum is removed with a duration of 0
+        let expires = Expiration::AtHeight(start + 100_000);
+        let scenario = scenario
+            .config(max(0, 0))
+            .create("sender0001", &funds, create("swap0003", custom_hash(1), expires));
+        let config: ConfigResponse = scenario.query(QueryMsg::Config {});
+        assert_eq!(config.max_lock_duration, None);
+    }
+
+    #[test]
+    fn test_fallback_recipient() {
+        let mut deps = mock_dependencies();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), InstantiateMsg {}).unwrap();
+        let create = |id: &str, hash: String| CreateMsg {
+            id: id.to_string(),
+            hash,
+            recipient: "vault0001".to_string(),
+            expires: Expiration::AtHeight(123456),
+            payout_msg: Some(Binary::from(b"deposit")),
+            fallback_recipient: Some("rcpt0002".to_string()),
+            ..Default::default()
+        };
+        let receive = |create: CreateMsg| ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: "sender0001".to_string(),
+            amount: Uint128::new(500),
+            msg: to_binary(&ReceiveMsg::Create(create)).unwrap(),
+        });
+        let release = |id: &str, preimage: String| ExecuteMsg::Release {
+            id: id.to_string(),
+            preimage,
+            amount: None,
+        };
+        let token = mock_info("token0001", &[]);
+        let attr = |res: &Response, key: &str| {
+            res.attributes.iter().find(|a| a.key == key).map(|a| a.value.clone())
+        };
+
+        // only Cw20 payouts can be redirected
+        let mut native = create("swap0001", real_hash());
+        native.payout_msg = None;
+        let info = mock_info("sender0001", &coins(500, "tokens"));
+        let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Create(native)).unwrap_err();
+        assert_eq!(err, ContractError::FallbackNotCw20);
+
+        // the payout to the recipient is sent as a submessage replying to the contract
+        execute(deps.as_mut(), mock_env(), token.clone(), receive(create("swap0001", real_hash())))
+            .unwrap();
+        let msg = QueryMsg::Details { id: "swap0001".to_string() };
+        let details: DetailsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(details.fallback_recipient, Some("rcpt0002".to_string()));
+        let anyone = mock_info("anyone", &[]);
+        let release1 = release("swap0001", preimage());
+        let res = execute(deps.as_mut(), mock_env(), anyone.clone(), release1).unwrap();
+        let send = WasmMsg::Execute {
+            contract_addr: "token0001".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Send {
+                contract: "vault0001".to_string(),
+                amount: Uint128::new(500),
+                msg: Binary::from(b"deposit"),
+            }).unwrap(),
+            funds: vec![],
+        };
+        assert_eq!(res.messages, vec![SubMsg::reply_always(send, 1)]);
+
+        // if it fails, the tokens are transferred to the fallback recipient instead of parked
+        let failed = Reply { id: 1, result: SubMsgResult::Err("no receive hook".to_string()) };
+        let res = reply(deps.as_mut(), mock_env(), failed).unwrap();
+        let transfer = WasmMsg::Execute {
+            contract_addr: "token0001".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "rcpt0002".to_string(),
+                amount: Uint128::new(500),
+            }).unwrap(),
+            funds: vec![],
+        };
+        assert_eq!(res.messages, vec![SubMsg::new(transfer)]);
+        assert_eq!(attr(&res, "path"), Some("fallback".to_string()));
+        let msg = QueryMsg::PendingPayouts { start_after: None, limit: None };
+        let pending: PendingPayoutsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert!(pending.payouts.is_empty());
+
+        // and delivered payouts record the primary path
+        execute(deps.as_mut(), mock_env(), token, receive(create("swap0002", custom_hash(1))))
+            .unwrap();
+        execute(deps.as_mut(), mock_env(), anyone, release("swap0002", custom_preimage(1)))
+            .unwrap();
+        let delivered = Reply {
+            id: 2,
+            result: SubMsgResult::Ok(SubMsgResponse { events: vec![], data: None }),
+        };
+        let res = reply(deps.as_mut(), mock_env(), delivered).unwrap();
+        assert_eq!(attr(&res, "path"), Some("primary".to_string()));
+    }
+
+    #[test]
+    fn test_private_swap() {
+        let create = |id: &str, hash: String, private: bool| CreateMsg {
+            id: id.to_string(),
+            hash,
+            recipient: "rcpt0001".into(),
+            expires: Expiration::AtHeight(mock_env().block.height + 100),
+            ask_denom: Some("uatom".to_string()),
+            ask_amount: Some(Uint128::new(100)),
+            private: Some(private),
+            ..Default::default()
+        };
+        let funds = coins(1000, "tokens");
+        let scenario = Scenario::new()
+            .create("sender0001", &funds, create("swap0001", real_hash(), false))
+            .create("sender0001", &funds, create("swap0002", custom_hash(1), true));
+
+        // private swaps are left out of every listing
+        let ids = vec!["swap0001".to_string()];
+        let list = QueryMsg::List { start_after: None, limit: None, status: None };
+        assert_eq!(scenario.query::<ListResponse>(list).swaps, ids);
+        let by_pair = QueryMsg::ListByPair {
+            source: "sender0001".to_string(),
+            recipient: "rcpt0001".to_string(),
+            start_after: None,
+            limit: None,
+        };
+        assert_eq!(scenario.query::<ListResponse>(by_pair).swaps, ids);
+        let older = QueryMsg::ListOlderThan { age_seconds: 0, start_after: None, limit: None };
+        assert_eq!(scenario.query::<ListResponse>(older).swaps, ids);
+        let expiring = QueryMsg::ExpiringWithin {
+            blocks: Some(100),
+            seconds: None,
+            start_after: None,
+            limit: None,
+        };
+        let res: ExpiringWithinResponse = scenario.query(expiring);
+        assert_eq!(res.swaps.into_iter().map(|swap| swap.id).collect::<Vec<_>>(), ids);
+        let book = QueryMsg::OrderBook {
+            give_denom: "tokens".to_string(),
+            want_denom: "uatom".to_string(),
+            start_after: None,
+            limit: None,
+        };
+        let res: OrderBookResponse = scenario.query(book);
+        assert_eq!(res.orders.into_iter().map(|order| order.id).collect::<Vec<_>>(), ids);
+
+        // but found by their exact id
+        assert!(scenario.details("swap0002").private);
+        assert!(!scenario.details("swap0001").private);
+
+        // and their id is not retained once settled
+        let scenario = scenario
+            .release("swap0001", &preimage())
+            .release("swap0002", &custom_preimage(1));
+        let released = QueryMsg::List {
+            start_after: None,
+            limit: None,
+            status: Some(SwapStatus::Released),
+        };
+        assert_eq!(scenario.query::<ListResponse>(released).swaps, ids);
+    }
+
+    #[test]
+    fn test_changed_since() {
+        let create = |id: &str, hash: String, private: bool| CreateMsg {
+            id: id.to_string(),
+            hash,
+            recipient: "rcpt0001".into(),
+            expires: Expiration::AtHeight(mock_env().block.height + 100),
+            private: Some(private),
+            ..Default::default()
+        };
+        let funds = coins(1000, "tokens");
+        let changed_since = |scenario: &Scenario, event_seq: u64| {
+            let res: ChangedSinceResponse =
+                scenario.query(QueryMsg::ChangedSince { event_seq, limit: None });
+            let changes = res.changes.into_iter().map(|c| (c.seq, c.id, c.kind));
+            (changes.collect::<Vec<_>>(), res.last_seq)
+        };
+
+        // private swaps are not recorded
+        let scenario = Scenario::new()
+            .create("sender0001", &funds, create("swap0001", real_hash(), false))
+            .create("sender0001", &funds, create("swap0002", custom_hash(1), false))
+            .create("sender0001", &funds, create("swap0003", custom_hash(2), true));
+        let (changes, last_seq) = changed_since(&scenario, 0);
+        assert_eq!(changes, vec![
+            (1, "swap0001".to_string(), SwapChangeKind::Created),
+            (2, "swap0002".to_string(), SwapChangeKind::Created),
+        ]);
+        assert_eq!(last_seq, 2);
+
+        // a client synced to the last change only reads what changed since
+        let scenario = scenario
+            .release("swap0001", &preimage())
+            .advance_blocks(100)
+            .refund("sender0001", "swap0002");
+        let (changes, last_seq) = changed_since(&scenario, 2);
+        assert_eq!(changes, vec![
+            (3, "swap0001".to_string(), SwapChangeKind::Released),
+            (4, "swap0002".to_string(), SwapChangeKind::Refunded),
+        ]);
+        assert_eq!(last_seq, 4);
+
+        // and each swap is listed once, with its latest change
+        let (changes, _) = changed_since(&scenario, 0);
+        assert_eq!(changes.len(), 2);
+    }
+
+    #[test]
+    fn test_verify_ledger() {
+        let create = |id: &str, hash: String| CreateMsg {
+            id: id.to_string(),
+            hash,
+            recipient: "rcpt0001".into(),
+            expires: Expiration::AtHeight(mock_env().block.height + 100),
+            ..Default::default()
+        };
+        let verify = ExecuteMsg::VerifyLedger { denom: "tokens".to_string() };
+        let attr = |res: &Response, key: &str| {
+            res.attributes.iter().find(|a| a.key == key).map(|a| a.value.clone())
+        };
+
+        // the funds held match the ledger
+        let funds = coins(1000, "tokens");
+        let mut scenario =
+            Scenario::new().create("sender0001", &funds, create("swap0001", real_hash()));
+        scenario.deps.querier.update_balance(MOCK_CONTRACT_ADDR, coins(1000, "tokens"));
+        let mut scenario = scenario.execute("anyone", &[], verify.clone());
+        assert_eq!(attr(&scenario.last, "drift"), Some("false".to_string()));
+        assert!(scenario.last.events.is_empty());
+
+        // a shortfall pauses creates, and alerts the admin
+        scenario.deps.querier.update_balance(MOCK_CONTRACT_ADDR, coins(900, "tokens"));
+        let scenario = scenario.execute("anyone", &[], verify);
+        assert_eq!(attr(&scenario.last, "drift"), Some("true".to_string()));
+        let alert = Event::new("ledger_drift")
+            .add_attribute("admin", "admin")
+            .add_attribute("denom", "tokens")
+            .add_attribute("shortfall", "100");
+        assert_eq!(scenario.last.events, vec![alert]);
+        let config: ConfigResponse = scenario.query(QueryMsg::Config {});
+        assert!(config.creates_paused);
+        let paused = ExecuteMsg::Create(create("swap0002", custom_hash(1)));
+        let scenario =
+            scenario.execute_err("sender0001", &funds, paused, ContractError::CreatesPaused);
+
+        // until the admin resumes them
+        let resume = UpdateConfigMsg { creates_paused: Some(false), ..Default::default() };
+        scenario
+            .config(resume)
+            .create("sender0001", &funds, create("swap0002", custom_hash(1)));
+    }
+
+
+    /// Ledger test - the ledger follows the funds held for the state through creates, installments,
+    /// releases, refunds and withdrawals, without deriving them from the whole state
+    #[test]
+    fn test_ledger() {
+        let assert_ledger = |scenario: &Scenario| {
+            let (native, cw20) = expected_holdings(&scenario.deps.storage).unwrap();
+            let native: Vec<_> =
+                native.into_vec().into_iter().map(|c| (c.denom, c.amount)).collect();
+            let ledger: Vec<_> = NATIVE_LEDGER
+                .range(&scenario.deps.storage, None, None, Order::Ascending)
+                .map(|item| item.unwrap())
+                .filter(|(_, amount)| !amount.is_zero())
+                .collect();
+            assert_eq!(ledger, native);
+            let cw20: Vec<_> = cw20.into_iter().collect();
+            let ledger: Vec<_> = CW20_LEDGER
+                .range(&scenario.deps.storage, None, None, Order::Ascending)
+                .map(|item| item.unwrap())
+                .filter(|(_, amount)| !amount.is_zero())
+                .collect();
+            assert_eq!(ledger, cw20);
+        };
+        let expires = mock_env().block.height + 100;
+        let create = |id: &str, hash: String| CreateMsg {
+            id: id.to_string(),
+            hash,
+            recipient: "rcpt0001".into(),
+            expires: Expiration::AtHeight(expires),
+            ..Default::default()
+        };
+
+        // creation fees, and fee payer deposits along with the balances
+        let fees = UpdateConfigMsg {
+            creation_fee: Some(coin(10, "tokens")),
+            release_fee_bps: Some(100),
+            ..Default::default()
+        };
+        let partial = CreateMsg {
+            partial: Some(true),
+            fee_payer: Some(FeePayer {
+                address: "payer".to_string(),
+                deposit: coin(50, "tokens"),
+            }),
+            ..create("swap0002", custom_hash(1))
+        };
+        let scenario = Scenario::new()
+            .config(fees)
+            .create("sender0001", &coins(1000, "tokens"), create("swap0001", real_hash()))
+            .create("sender0001", &coins(550, "tokens"), partial);
+        assert_ledger(&scenario);
+        let receive = Cw20ReceiveMsg {
+            sender: "sender0001".to_string(),
+            amount: Uint128::new(700),
+            msg: to_binary(&ReceiveMsg::Create(create("swap0003", custom_hash(2)))).unwrap(),
+        };
+        let scenario = scenario.execute("token", &[], ExecuteMsg::Receive(receive));
+        assert_ledger(&scenario);
+
+        // installments, releases and refunds
+        let installment = Some(Uint128::new(100));
+        let scenario = scenario.release_partial("swap0002", &custom_preimage(1), installment);
+        assert_ledger(&scenario);
+        let scenario = scenario.release("swap0001", &preimage());
+        assert_ledger(&scenario);
+        let scenario = scenario.advance_blocks(100).refund("sender0001", "swap0003");
+        assert_ledger(&scenario);
+
+        // and withdrawals of the fees collected
+        let scenario = scenario.execute("admin", &[], ExecuteMsg::WithdrawFees {});
+        assert_ledger(&scenario);
+    }
+
+
+    /// Attestation test - once an attestation contract is configured, only attested sources can
+    /// create swaps, and only attested recipients can be paid out
+    #[test]
+    fn test_attestation() {
+        let attest = |attested: &'static [&'static str]| {
+            move |query: &WasmQuery| match query {
+                WasmQuery::Smart { contract_addr, msg } if contract_addr == "kyc" => {
+                    let AttestationQueryMsg::Attested { address } = from_binary(msg).unwrap();
+                    let res = AttestedResponse { attested: attested.contains(&address.as_str()) };
+                    SystemResult::Ok(ContractResult::Ok(to_binary(&res).unwrap()))
+                }
+                _ => panic!("unexpected query"),
+            }
+        };
+        let create = |id: &str, hash: String| CreateMsg {
+            id: id.to_string(),
+            hash,
+            recipient: "rcpt0001".into(),
+            expires: Expiration::AtHeight(mock_env().block.height + 100),
+            ..Default::default()
+        };
+        let funds = coins(1000, "tokens");
+
+        // swaps created before the gate are unaffected by it until released
+        let gate = UpdateConfigMsg { attestation: Some("kyc".to_string()), ..Default::default() };
+        let mut scenario = Scenario::new()
+            .create("sender0001", &funds, create("swap0001", real_hash()))
+            .config(gate);
+        let config: ConfigResponse = scenario.query(QueryMsg::Config {});
+        assert_eq!(config.attestation, Some("kyc".to_string()));
+
+        // only the sender is attested, so they can create but the recipient cannot be paid out
+        scenario.deps.querier.update_wasm(attest(&["sender0001"]));
+        let release = ExecuteMsg::Release {
+            id       : "swap0001".to_string(),
+            preimage : preimage(),
+            amount   : None,
+        };
+        let required = |address: &str| ContractError::AttestationRequired(address.to_string());
+        let scenario = scenario
+            .create("sender0001", &funds, create("swap0002", custom_hash(1)))
+            .execute_err("anyone", &[], release.clone(), required("rcpt0001"))
+            .execute_err(
+                "sender0002",
+                &funds,
+                ExecuteMsg::Create(create("swap0003", custom_hash(2))),
+                required("sender0002"),
+            );
+
+        // nor can an unattested sender fund a swap named after an attested source
+        let named = CreateMsg {
+            source: Some("sender0001".to_string()),
+            ..create("swap0003", custom_hash(2))
+        };
+        let mut scenario = scenario.execute_err(
+            "sender0002",
+            &funds,
+            ExecuteMsg::IbcHookCreate(named),
+            required("sender0002"),
+        );
+
+        // once the recipient is attested, the swap is released
+        scenario.deps.querier.update_wasm(attest(&["sender0001", "rcpt0001"]));
+        let scenario = scenario.execute("anyone", &[], release);
+        assert!(!scenario.exists("swap0001"));
+
+        // removing the gate lets anyone create again
+        let ungate = UpdateConfigMsg { attestation: Some(String::new()), ..Default::default() };
+        scenario
+            .config(ungate)
+            .create("sender0002", &funds, create("swap0003", custom_hash(2)));
+    }
+
+
+    /// Metrics test - the average blocks to release and the refund rate follow the settlements
+    #[test]
+    fn test_metrics() {
+        let create = |id: &str, hash: String| CreateMsg {
+            id: id.to_string(),
+            hash,
+            recipient: "rcpt0001".into(),
+            expires: Expiration::AtHeight(mock_env().block.height + 100),
+            ..Default::default()
+        };
+        let funds = coins(1000, "tokens");
+
+        // nothing to average before the first settlement
+        let scenario = Scenario::new()
+            .create("sender0001", &funds, create("swap0001", real_hash()))
+            .create("sender0001", &funds, create("swap0002", custom_hash(1)))
+            .create("sender0001", &funds, create("swap0003", custom_hash(2)));
+        let metrics: MetricsResponse = scenario.query(QueryMsg::Metrics {});
+        assert_eq!(metrics.created, 3);
+        assert_eq!(metrics.avg_release_blocks, None);
+        assert_eq!(metrics.refund_rate, None);
+
+        // released 10 and 20 blocks after their creation, then one refunded once expired
+        let scenario = scenario
+            .advance_blocks(10)
+            .release("swap0001", &preimage())
+            .advance_blocks(10)
+            .release("swap0002", &custom_preimage(1))
+            .advance_blocks(100)
+            .refund("anyone", "swap0003");
+        let metrics: MetricsResponse = scenario.query(QueryMsg::Metrics {});
+        assert_eq!(metrics.released, 2);
+        assert_eq!(metrics.refunded, 1);
+        assert_eq!(metrics.release_blocks, 30);
+        assert_eq!(metrics.timed_releases, 2);
+        assert_eq!(metrics.avg_release_blocks, Some(Decimal::from_ratio(15u128, 1u128)));
+        assert_eq!(metrics.refund_rate, Some(Decimal::from_ratio(1u128, 3u128)));
+    }
+
+
+    /// Verify received test - with the check on, a swap of a token taxing transfers locks what the
+    /// contract received, rather than what was sent
+    #[test]
+    fn test_verify_received() {
+        // the token takes a 2% tax on transfers, delivering 980 of every 1000 sent
+        let balance = |held: u128| {
+            move |query: &WasmQuery| match query {
+                WasmQuery::Smart { contract_addr, .. } if contract_addr == "taxtoken" => {
+                    let res = cw20::BalanceResponse { balance: Uint128::new(held) };
+                    SystemResult::Ok(ContractResult::Ok(to_binary(&res).unwrap()))
+                }
+                _ => panic!("unexpected query"),
+            }
+        };
+        let receive = |id: &str, hash: String| ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: "sender0001".to_string(),
+            amount: Uint128::new(1000),
+            msg: to_binary(&ReceiveMsg::Create(CreateMsg {
+                id: id.to_string(),
+                hash,
+                recipient: "rcpt0001".into(),
+                expires: Expiration::AtHeight(mock_env().block.height + 100),
+                ..Default::default()
+            })).unwrap(),
+        });
+        let locked = |scenario: &Scenario, id: &str| match scenario.details(id).balance {
+            BalanceHuman::Cw20(coin) => coin.amount.u128(),
+            balance => panic!("unexpected balance {:?}", balance),
+        };
+
+        // the amount sent is trusted until the check is on
+        let verify = UpdateConfigMsg { verify_received: Some(true), ..Default::default() };
+        let mut scenario = Scenario::new()
+            .execute("taxtoken", &[], receive("swap0001", real_hash()))
+            .config(verify);
+        assert_eq!(locked(&scenario, "swap0001"), 1000);
+        let config: ConfigResponse = scenario.query(QueryMsg::Config {});
+        assert!(config.verify_received);
+
+        // then only what was received beyond the swaps already locked
+        scenario.deps.querier.update_wasm(balance(1980));
+        let mut scenario = scenario.execute("taxtoken", &[], receive("swap0002", custom_hash(1)));
+        assert_eq!(locked(&scenario, "swap0002"), 980);
+        scenario.deps.querier.update_wasm(balance(2960));
+        let scenario = scenario.execute("taxtoken", &[], receive("swap0003", custom_hash(2)));
+        assert_eq!(locked(&scenario, "swap0003"), 980);
+
+        // as accounted in the ledger, rather than from the swaps themselves
+        let ledger = CW20_LEDGER.load(&scenario.deps.storage, &Addr::unchecked("taxtoken"));
+        assert_eq!(ledger.unwrap(), Uint128::new(2960));
+    }
+
+
+    /// Reveal deadline test - once the recipient accepts, the source can refund the swap early
+    /// should it not be released in time
+    #[test]
+    fn test_reveal_deadline() {
+        let create = |id: &str, hash: String, reveal_deadline: Option<u64>| CreateMsg {
+            id: id.to_string(),
+            hash,
+            recipient: "rcpt0001".into(),
+            expires: Expiration::AtHeight(mock_env().block.height + 100),
+            accept_required: Some(true),
+            reveal_deadline,
+            ..Default::default()
+        };
+        let funds = coins(1000, "tokens");
+        let accept = |id: &str| ExecuteMsg::Accept { id: id.to_string() };
+        let refund = ExecuteMsg::Refund { id: "swap0001".to_string() };
+
+        // a deadline needs something to start it, and cannot be 0 nor out of range
+        let no_start = CreateMsg {
+            accept_required: None,
+            ..create("swap0001", real_hash(), Some(5))
+        };
+        let scenario = Scenario::new()
+            .execute_err(
+                "sender0001",
+                &funds,
+                ExecuteMsg::Create(no_start),
+                ContractError::InvalidRevealDeadline,
+            )
+            .execute_err(
+                "sender0001",
+                &funds,
+                ExecuteMsg::Create(create("swap0001", real_hash(), Some(0))),
+                ContractError::InvalidRevealDeadline,
+            )
+            .execute_err(
+                "sender0001",
+                &funds,
+                ExecuteMsg::Create(create("swap0001", real_hash(), Some(u64::MAX))),
+                ContractError::InvalidRevealDeadline,
+            );
+
+        // the deadline only starts once accepted
+        let mut scenario = scenario
+            .create("sender0001", &funds, create("swap0001", real_hash(), Some(5)))
+            .advance_blocks(10)
+            .execute_err("sender0001", &[], refund.clone(), ContractError::NotExpired)
+            .execute("rcpt0001", &[], accept("swap0001"));
+        let details = scenario.details("swap0001");
+        assert_eq!(details.reveal_deadline, Some(5));
+        assert_eq!(details.reveal_by, Some(scenario.env.block.height + 5));
+
+        // within the window, the swap is not refundable yet
+        scenario = scenario
+            .advance_blocks(4)
+            .execute_err("sender0001", &[], refund.clone(), ContractError::NotExpired)
+            .advance_blocks(1);
+
+        // once missed, only the source can refund it early
+        scenario = scenario
+            .execute_err("anyone", &[], refund, ContractError::NotExpired)
+            .refund("sender0001", "swap0001");
+        assert!(!scenario.exists("swap0001"));
+
+        // a swap released in time settles as usual
+        let scenario = scenario
+            .create("sender0001", &funds, create("swap0002", custom_hash(1), Some(5)))
+            .execute("rcpt0001", &[], accept("swap0002"))
+            .advance_blocks(4)
+            .release("swap0002", &custom_preimage(1));
+        assert!(!scenario.exists("swap0002"));
+    }
+
+
+    /// Event verbosity test - the attributes carried follow the configured verbosity
+    #[test]
+    fn test_event_verbosity() {
+        let create = |id: &str, hash: String| CreateMsg {
+            id: id.to_string(),
+            hash,
+            recipient: "rcpt0001".into(),
+            expires: Expiration::AtHeight(mock_env().block.height + 100),
+            ..Default::default()
+        };
+        let keys = |scenario: &Scenario| -> Vec<String> {
+            scenario.last.attributes.iter().map(|attr| attr.key.clone()).collect()
+        };
+        let verbosity = |verbosity: EventVerbosity| UpdateConfigMsg {
+            event_verbosity: Some(verbosity),
+            ..Default::default()
+        };
+        let funds = coins(1000, "tokens");
+
+        // standard by default, carrying every attribute, the full balances included
+        let scenario = Scenario::new()
+            .create("sender0001", &funds, create("swap0001", real_hash()));
+        assert_eq!(keys(&scenario), vec![
+            "action", "id", "hash", "recipient", "source", "balance", "escrow_id",
+        ]);
+        assert!(scenario.last.attributes.iter().any(|attr| attr.value == "1000tokens"));
+        let scenario = scenario.release("swap0001", &preimage());
+        assert_eq!(keys(&scenario), vec!["action", "id", "preimage", "to", "released"]);
+
+        // as does verbose
+        let scenario = scenario
+            .config(verbosity(EventVerbosity::Verbose))
+            .create("sender0001", &funds, create("swap0002", custom_hash(1)));
+        assert!(keys(&scenario).contains(&"escrow_id".to_string()));
+        let scenario = scenario.release("swap0002", &custom_preimage(1));
+        assert!(keys(&scenario).contains(&"released".to_string()));
+
+        // minimal only leaves out the attributes added to the baseline ones
+        let scenario = scenario
+            .config(verbosity(EventVerbosity::Minimal))
+            .create("sender0001", &funds, create("swap0003", custom_hash(2)));
+        assert_eq!(keys(&scenario), vec!["action", "id", "hash", "recipient", "source", "balance"]);
+        let scenario = scenario.release("swap0003", &custom_preimage(2));
+        assert_eq!(keys(&scenario), vec!["action", "id", "preimage", "to", "released"]);
+        let config: ConfigResponse = scenario.query(QueryMsg::Config {});
+        assert_eq!(config.event_verbosity, EventVerbosity::Minimal);
+    }
+
+
+    /// Payout order and mode test - the assets are paid out in the swap's order, and best-effort
+    /// payouts are left to be claimed when they fail rather than failing the release
+    #[test]
+    fn test_payout_order_and_mode() {
+        let create = |id: &str, hash: String| CreateMsg {
+            id: id.to_string(),
+            hash,
+            recipient: "rcpt0001".into(),
+            expires: Expiration::AtHeight(mock_env().block.height + 100),
+            ..Default::default()
+        };
+        let bank = |denom: &str| SubMsg::new(BankMsg::Send {
+            to_address : "rcpt0001".to_string(),
+            amount     : coins(100, denom),
+        });
+        let funds = vec![coin(100, "uatom"), coin(100, "uosmo"), coin(100, "utoken")];
+
+        // an asset cannot be listed twice
+        let twice = CreateMsg {
+            payout_order: Some(vec!["uatom".to_string(), "uatom".to_string()]),
+            ..create("swap0001", real_hash())
+        };
+        let scenario = Scenario::new().execute_err(
+            "sender0001",
+            &funds,
+            ExecuteMsg::Create(twice),
+            ContractError::InvalidPayoutOrder,
+        );
+
+        // listed assets first in their order, the others after
+        let ordered = CreateMsg {
+            payout_order: Some(vec!["utoken".to_string(), "uatom".to_string()]),
+            ..create("swap0001", real_hash())
+        };
+        let scenario = scenario
+            .create("sender0001", &funds, ordered)
+            .release("swap0001", &preimage());
+        assert_eq!(scenario.last.messages, vec![bank("utoken"), bank("uatom"), bank("uosmo")]);
+
+        // best effort tracks the Cw20 payout, even without a retry policy
+        let receive = |create: CreateMsg| ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender : "sender0001".to_string(),
+            amount : Uint128::new(500),
+            msg    : to_binary(&ReceiveMsg::Create(create)).unwrap(),
+        });
+        let best_effort = CreateMsg {
+            payout_mode: Some(PayoutMode::BestEffort),
+            ..create("swap0002", custom_hash(1))
+        };
+        let mut scenario = scenario
+            .execute("token0001", &[], receive(best_effort))
+            .release("swap0002", &custom_preimage(1));
+        assert!(matches!(scenario.last.messages[0].reply_on, ReplyOn::Always));
+        let mode = scenario.last.attributes.iter().find(|attr| attr.key == "payout_mode");
+        assert_eq!(mode.map(|attr| attr.value.as_str()), Some("best_effort"));
+
+        // a failed payout does not fail the release, and is left for the recipient to claim
+        let id = scenario.last.messages[0].id;
+        let failed = Reply { id, result: SubMsgResult::Err("frozen".to_string()) };
+        reply(scenario.deps.as_mut(), scenario.env.clone(), failed).unwrap();
+        let claim = ExecuteMsg::RetryPayout { id };
+        let scenario = scenario
+            .execute_err("anyone", &[], claim.clone(), ContractError::Unauthorized {})
+            .execute("rcpt0001", &[], claim);
+
+        // atomic payouts are never tracked, even with a retry policy
+        let retry = PayoutRetry { max_attempts: 2, base_delay: 10 };
+        let atomic = CreateMsg {
+            payout_mode: Some(PayoutMode::Atomic),
+            ..create("swap0003", custom_hash(2))
+        };
+        let scenario = scenario
+            .config(UpdateConfigMsg { payout_retry: Some(retry), ..Default::default() })
+            .execute("token0001", &[], receive(atomic))
+            .release("swap0003", &custom_preimage(2));
+        assert!(matches!(scenario.last.messages[0].reply_on, ReplyOn::Never));
+    }
+
+    #[test]
+    fn test_payout_gas_limit() {
+        let mut deps = mock_dependencies();
+
+        let admin = mock_info("admin", &[]);
+        instantiate(deps.as_mut(), mock_env(), admin.clone(), InstantiateMsg {}).unwrap();
+        let update = UpdateConfigMsg { payout_gas_limit: Some(200_000), ..Default::default() };
+        let update = ExecuteMsg::UpdateConfig(update);
+        execute(deps.as_mut(), mock_env(), admin.clone(), update).unwrap();
+
+        // the Cw20 payout is limited, and tracked so that running out of gas does not fail the
+        // release
+        let create = CreateMsg {
+            id: "swap0001".to_string(),
+            hash: real_hash(),
+            recipient: "rcpt0001".to_string(),
+            expires: Expiration::AtHeight(123456),
+            ..Default::default()
+        };
+        let receive = Cw20ReceiveMsg {
+            sender: "sender0001".to_string(),
+            amount: Uint128::new(500),
+            msg: to_binary(&ReceiveMsg::Create(create)).unwrap(),
+        };
+        let token = mock_info("token0001", &[]);
+        execute(deps.as_mut(), mock_env(), token, ExecuteMsg::Receive(receive)).unwrap();
+        let release = ExecuteMsg::Release {
+            id: "swap0001".to_string(),
+            preimage: preimage(),
+            amount: None,
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), release).unwrap();
+        let transfer = WasmMsg::Execute {
+            contract_addr: "token0001".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "rcpt0001".to_string(),
+                amount: Uint128::new(500),
+            }).unwrap(),
+            funds: vec![],
+        };
+        let limited = SubMsg::reply_always(transfer.clone(), 1).with_gas_limit(200_000);
+        assert_eq!(res.messages, vec![limited]);
+
+        // out of gas, the payout waits to be claimed by its recipient, with all the gas it needs
+        let out_of_gas = Reply { id: 1, result: SubMsgResult::Err("out of gas".to_string()) };
+        reply(deps.as_mut(), mock_env(), out_of_gas).unwrap();
+        let msg = QueryMsg::PendingPayouts { start_after: None, limit: None };
+        let pending: PendingPayoutsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(pending.payouts.len(), 1);
+        let retry = ExecuteMsg::RetryPayout { id: 1 };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), retry.clone())
+            .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let res = execute(deps.as_mut(), mock_env(), mock_info("rcpt0001", &[]), retry).unwrap();
+        assert_eq!(res.messages, vec![SubMsg::reply_always(transfer, 1)]);
+
+        // a limit of 0 removes it
+        let update = UpdateConfigMsg { payout_gas_limit: Some(0), ..Default::default() };
+        execute(deps.as_mut(), mock_env(), admin, ExecuteMsg::UpdateConfig(update)).unwrap();
+        let config: ConfigResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+        assert_eq!(config.payout_gas_limit, None);
+    }
+}
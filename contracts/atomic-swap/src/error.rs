@@ -2,7 +2,8 @@
 Error types to handle failed smart contract operations.
 */
 
-use cosmwasm_std::StdError;
+use cosmwasm_std::{StdError, Uint128};
+use cw20::Expiration;
 use thiserror::Error;
 
 /// Atomic swap smart contract error type
@@ -12,9 +13,13 @@ pub enum ContractError {
     #[error("{0}")]
     Std(#[from] StdError),
 
-    /// Command parsing error
-    #[error("Hash parse error: {0}")]
-    ParseError(String),
+    /// A hex input holds a character which is not a hex digit
+    #[error("Invalid hex character '{char}' at position {position}")]
+    InvalidHexChar { position: usize, char: char },
+
+    /// A hex input does not have the expected length, in characters
+    #[error("Invalid length ({got} chars): must be {expected} characters")]
+    WrongLength { got: usize, expected: usize },
 
     /// Error where swap id (of either sender or recipient) is not valid
     #[error("Invalid atomic swap id")]
@@ -24,14 +29,20 @@ pub enum ContractError {
     #[error("Invalid preimage - preimage does not match hash")]
     InvalidPreimage {},
 
-    /// Error where the hash is not valid
-    #[error("Invalid hash ({0} chars): must be 64 characters")]
-    InvalidHash(usize),
-
     /// Zero balance error - smart contracts do not allow empty swaps
     #[error("Send some coins to create an atomic swap")]
     EmptyBalance {},
 
+    /// Swap that never expires error - such swaps could never be refunded, so they are rejected
+    /// unless explicitly allowed by the configuration
+    #[error("Atomic swap must expire")]
+    NeverExpires,
+
+    /// Force refund is only for swaps that never expire - others can be refunded by anyone once
+    /// expired
+    #[error("Only swaps that never expire can be force refunded")]
+    NotNeverExpires,
+
     /// Not expired swap error - used for refund since locked tokens before expiration cannot
     /// be refunded
     #[error("Atomic swap not yet expired")]
@@ -46,7 +57,176 @@ pub enum ContractError {
     #[error("Atomic swap already exists")]
     AlreadyExists,
 
+    /// Payout message is attached to a swap whose balance is not in Cw20 tokens
+    #[error("Payout message is only supported for Cw20 swaps")]
+    PayoutMsgNotCw20,
+
+    /// Fallback recipient is set on a swap whose balance is not in Cw20 tokens
+    #[error("Fallback recipient is only supported for Cw20 swaps")]
+    FallbackNotCw20,
+
+    /// Sender is not allowed to perform an admin-only operation
+    #[error("Unauthorized")]
+    Unauthorized {},
+
+    /// Fee in basis points must be at most 10000 (100%)
+    #[error("Invalid fee ({0} bps): must be at most 10000")]
+    InvalidFee(u64),
+
+    /// A fee payer requires a native swap of a single coin, and a deposit in the same denom
+    /// covering its fees
+    #[error("Invalid fee payer")]
+    InvalidFeePayer,
+
+    /// The swap must be released by committing to its preimage, then revealing it
+    #[error("Atomic swap must be released by commit-reveal")]
+    CommitRequired,
+
+    /// The sender has no release commitment for the swap
+    #[error("No release commitment for this swap")]
+    NoCommit,
+
+    /// A release commitment can only be revealed in a later block than it was made in
+    #[error("Release commitment cannot be revealed before block {0}")]
+    RevealTooEarly(u64),
+
+    /// The swap requires acceptance by its recipient, who did not accept it yet
+    #[error("Atomic swap not yet accepted by its recipient")]
+    NotAccepted,
+
+    /// The recipient already accepted the swap, which can no longer be cancelled
+    #[error("Atomic swap already accepted")]
+    AlreadyAccepted,
+
+    /// Only swaps requiring acceptance can be accepted, or cancelled before they are
+    #[error("Atomic swap does not require acceptance")]
+    AcceptNotRequired,
+
+    /// A failed payout can only be retried once its backoff delay has passed
+    #[error("Payout cannot be retried before block {0}")]
+    RetryTooEarly(u64),
+
+    /// Installments require a partial swap of a single native coin or Cw20 token
+    #[error("Only partial swaps of a single coin can be released in installments")]
+    InvalidPartial,
+
+    /// An installment must be non-zero and at most the remaining balance of the swap
+    #[error("Invalid installment")]
+    InvalidInstallment,
+
+    /// An order book listing requires both a non-empty ask denom and a non-zero ask amount
+    #[error("Invalid ask")]
+    InvalidAsk,
+
+    /// A swap created through an IBC hook must name the source it is refunded to
+    #[error("IBC hook swaps require a source")]
+    IbcHookSourceRequired,
+
+    /// Only the counter-swaps of mirrored creates and IBC hook swaps can designate their source
+    #[error("Only mirrored counter-swaps and IBC hook swaps can designate a source")]
+    SourceNotAllowed,
+
+    /// The oracle of an oracle-locked swap does not attest the event yet
+    #[error("Event not attested by the oracle")]
+    EventNotAttested,
+
+    /// An IBC payout requires a native swap, a channel, a remote recipient and a timeout
+    #[error("Invalid IBC payout")]
+    InvalidIbcPayout,
+
+    /// Native funds sent to create a swap do not cover the creation fee
+    #[error("Creation fee of {0} required")]
+    CreationFeeRequired(String),
+
+    /// There are no collected fees to withdraw
+    #[error("No fees to withdraw")]
+    NoFees,
+
+    /// The maximum number of open swaps has been reached - no new swap can be created until some
+    /// of the currently open swaps are released or refunded
+    #[error("Maximum open swaps reached ({open}/{cap})")]
+    CapacityReached { open: u64, cap: u64 },
+
+    /// The preimage of the swap's hash was already revealed, so anyone could release the swap
+    #[error("Hash already revealed: its preimage is public")]
+    HashRevealed,
+
+    /// Free text attached to a swap exceeds the memo size limit
+    #[error("Memo too long: at most {max} bytes")]
+    MemoTooLong { max: u32 },
+
+    /// A binary attachment of a swap exceeds the attachment size limit
+    #[error("Attachment too large: at most {max} bytes")]
+    AttachmentTooLarge { max: u32 },
+
+    /// A batch holds more entries than its limit
+    #[error("Batch too large: at most {max} entries")]
+    BatchTooLarge { max: u32 },
+
+    /// None of the payload limits can be zero
+    #[error("Invalid limits: none can be zero")]
+    InvalidLimits,
+
+    /// A swap holds more distinct assets than its limit
+    #[error("Too many assets: at most {max} per swap")]
+    TooManyAssets { max: u32 },
+
     /// Smart contract does not allow the same sender and recipient
     #[error("Sender and recipient cannot be the same")]
     SameSenderRecipient,
+
+    /// The contract itself can be neither the recipient nor the source of a swap
+    #[error("The contract cannot be the recipient or the source of a swap")]
+    SelfSwap,
+
+    /// The deployment being migrated is not of the contract the migration expects
+    #[error("Cannot migrate from {0}")]
+    UnsupportedMigration(String),
+
+    /// The contract does not hold enough funds to back its open swaps
+    #[error("Open swaps are not backed by the contract's funds: {0} required")]
+    UnbackedState(String),
+
+    /// The sender is not a registered gas sponsor
+    #[error("Not a sponsor")]
+    NotSponsor,
+
+    /// The sponsor's budget cannot cover another fee allowance
+    #[error("Sponsor budget exhausted: {budget} left")]
+    SponsorBudgetExhausted { budget: Uint128 },
+
+    /// Sponsor budgets are funded in the denom of the sponsor's grant, and nothing else
+    #[error("Sponsor budget must be funded in {0} only")]
+    InvalidSponsorFunds(String),
+
+    /// The swap would lock its funds for longer than the maximum lock duration
+    #[error("Atomic swap locked for too long: must expire by {0}")]
+    LockTooLong(Expiration),
+
+    /// Auto extension needs a partial swap expiring at a height or a time, a non-zero delta, and a
+    /// cap keeping the expiration within range
+    #[error("Invalid auto extension")]
+    InvalidAutoExtend,
+
+    /// Creates are paused, the funds held having drifted from the ledger
+    #[error("Creates are paused")]
+    CreatesPaused,
+
+    /// The source of a created swap, or the recipient of a released one, is not attested by the
+    /// attestation contract
+    #[error("Attestation required for {0}")]
+    AttestationRequired(String),
+
+    /// A reveal deadline needs a swap requiring acceptance or a commit-reveal release, to start
+    /// counting from, and a non-zero number of blocks within the range of block heights
+    #[error("Invalid reveal deadline")]
+    InvalidRevealDeadline,
+
+    /// A payout order cannot list an asset twice
+    #[error("Invalid payout order")]
+    InvalidPayoutOrder,
+
+    /// A sponsored execution cannot wrap another one
+    #[error("Sponsored executions cannot be nested")]
+    NestedSponsored,
 }
\ No newline at end of file
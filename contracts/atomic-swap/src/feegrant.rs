@@ -0,0 +1,60 @@
+/*
+Fee grants of the gas sponsors, as the stargate message of the x/feegrant module's
+MsgGrantAllowance, granting a basic allowance from the contract's account. The protobuf encoding is
+written out by hand, the messages being small and fixed, rather than pulling in a protobuf crate.
+*/
+
+use cosmwasm_std::{Addr, Coin, CosmosMsg};
+
+const MSG_GRANT_ALLOWANCE_URL: &str = "/cosmos.feegrant.v1beta1.MsgGrantAllowance";
+const BASIC_ALLOWANCE_URL: &str = "/cosmos.feegrant.v1beta1.BasicAllowance";
+
+
+/// The message granting a basic fee allowance, capped at the spend limit and without expiration.
+/// # Arguments
+/// * `granter`     - the account paying the fees, i.e. this contract
+/// * `grantee`     - the account whose fees are paid
+/// * `spend_limit` - the most fees the grantee can spend
+/// # Returns
+///   The stargate message
+pub fn grant_allowance_msg(granter: &Addr, grantee: &Addr, spend_limit: &Coin) -> CosmosMsg {
+    // cosmos.base.v1beta1.Coin
+    let mut coin = Vec::new();
+    encode_bytes(&mut coin, 1, spend_limit.denom.as_bytes());
+    encode_bytes(&mut coin, 2, spend_limit.amount.to_string().as_bytes());
+    // cosmos.feegrant.v1beta1.BasicAllowance
+    let mut allowance = Vec::new();
+    encode_bytes(&mut allowance, 1, &coin);
+    // google.protobuf.Any
+    let mut any = Vec::new();
+    encode_bytes(&mut any, 1, BASIC_ALLOWANCE_URL.as_bytes());
+    encode_bytes(&mut any, 2, &allowance);
+    // cosmos.feegrant.v1beta1.MsgGrantAllowance
+    let mut msg = Vec::new();
+    encode_bytes(&mut msg, 1, granter.as_bytes());
+    encode_bytes(&mut msg, 2, grantee.as_bytes());
+    encode_bytes(&mut msg, 3, &any);
+
+    CosmosMsg::Stargate {
+        type_url : MSG_GRANT_ALLOWANCE_URL.to_string(),
+        value    : msg.into(),
+    }
+}
+
+
+/// Append a length-delimited protobuf field - strings, bytes and embedded messages.
+fn encode_bytes(buf: &mut Vec<u8>, field: u64, bytes: &[u8]) {
+    encode_varint(buf, field << 3 | 2);
+    encode_varint(buf, bytes.len() as u64);
+    buf.extend_from_slice(bytes);
+}
+
+
+/// Append a protobuf varint.
+fn encode_varint(buf: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buf.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
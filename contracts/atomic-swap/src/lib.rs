@@ -1,6 +1,12 @@
 pub mod contract;
+#[cfg(feature = "stargate")]
+pub mod feegrant;
+pub mod limits;
 pub mod state;
 pub mod msg;
+pub mod parse;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 mod error;
 
 pub use error::ContractError;
\ No newline at end of file
@@ -0,0 +1,102 @@
+/*
+Payload limits of the atomic swap contract, checked in a single place rather than by each feature.
+Every limit has a default, which the admin can override in the configuration.
+*/
+
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::Binary;
+
+use crate::error::ContractError;
+
+
+/// Payload size limits
+#[cw_serde]
+pub struct Limits {
+    /// Maximum size in bytes of the free text attached to a swap (counter chain, counter tx)
+    pub max_memo_bytes       : u32,
+    /// Maximum size in bytes of each binary attachment of a swap (payout message, order commitment)
+    pub max_attachment_bytes : u32,
+    /// Maximum number of entries in a batch (restored swaps)
+    pub max_batch_size       : u32,
+    /// Maximum number of ids in a details batch query
+    pub max_details_batch    : u32,
+    /// Maximum number of distinct assets (native denoms and Cw20 tokens) a swap holds, the
+    /// default for limits stored before
+    #[serde(default = "default_max_assets")]
+    pub max_assets           : u32,
+}
+
+/// Default maximum number of distinct assets a swap holds
+fn default_max_assets() -> u32 {
+    10
+}
+
+/// Default limits, used until overridden by the admin
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            max_memo_bytes       : 256,
+            max_attachment_bytes : 4096,
+            max_batch_size       : 100,
+            max_details_batch    : 30,
+            max_assets           : default_max_assets(),
+        }
+    }
+}
+
+impl Limits {
+    /// Ensure that none of the limits is zero, which would reject every payload
+    pub fn validate(&self) -> Result<(), ContractError> {
+        let limits = [
+            self.max_memo_bytes,
+            self.max_attachment_bytes,
+            self.max_batch_size,
+            self.max_details_batch,
+            self.max_assets,
+        ];
+        if limits.contains(&0) {
+            return Err(ContractError::InvalidLimits);
+        }
+        Ok(())
+    }
+
+    /// Ensure that a free text attached to a swap is within the memo size limit
+    pub fn check_memo(&self, memo: &str) -> Result<(), ContractError> {
+        if memo.len() > self.max_memo_bytes as usize {
+            return Err(ContractError::MemoTooLong { max: self.max_memo_bytes });
+        }
+        Ok(())
+    }
+
+    /// Ensure that a binary attachment of a swap is within the attachment size limit
+    pub fn check_attachment(&self, attachment: &Binary) -> Result<(), ContractError> {
+        if attachment.len() > self.max_attachment_bytes as usize {
+            return Err(ContractError::AttachmentTooLarge { max: self.max_attachment_bytes });
+        }
+        Ok(())
+    }
+
+    /// Ensure that a batch is within the batch size limit
+    pub fn check_batch(&self, size: usize) -> Result<(), ContractError> {
+        if size > self.max_batch_size as usize {
+            return Err(ContractError::BatchTooLarge { max: self.max_batch_size });
+        }
+        Ok(())
+    }
+
+    /// Ensure that the balance of a swap is within its distinct asset count limit
+    pub fn check_assets(&self, count: usize) -> Result<(), ContractError> {
+        if count > self.max_assets as usize {
+            return Err(ContractError::TooManyAssets { max: self.max_assets });
+        }
+        Ok(())
+    }
+
+    /// Ensure that a details batch query is within its id count limit
+    pub fn check_details_batch(&self, size: usize) -> Result<(), ContractError> {
+        if size > self.max_details_batch as usize {
+            return Err(ContractError::BatchTooLarge { max: self.max_details_batch });
+        }
+        Ok(())
+    }
+}
@@ -2,12 +2,13 @@
 The request messages sent to the blockchain server to an atomic swap smart contract.
 */
 
-use cosmwasm_std::Coin;
-use schemars::JsonSchema;
-use serde::{Deserialize, Serialize};
+use cosmwasm_std::{Binary, Coin, Decimal, Timestamp, Uint128};
 use cosmwasm_schema::{cw_serde, QueryResponses};
 use cw20::{Cw20Coin, Cw20ReceiveMsg, Expiration};
 
+use crate::limits::Limits;
+use crate::state::{AtomicSwap, GenericBalance};
+
 
 /// Instantiate message for the atomic swap does not inherently require anything other than
 /// its own existence (at least for now). So we won't need to pass in anything.
@@ -20,22 +21,210 @@ pub struct InstantiateMsg {}
 /// * `Refund`  - cancels the swap and retrieve all remaining tokens
 /// * `Receive` - Handling the receiving end
 #[cw_serde]
+#[allow(clippy::large_enum_variant)]
 pub enum ExecuteMsg {
     Create(CreateMsg),
-    /// Release sends all tokens to the recipient.
+    /// Release sends all tokens to the recipient, or an installment of them for partial swaps.
     Release {
         id: String,
         /// This is the preimage, must be exactly 32 bytes in hex (64 chars)
         /// to release: sha256(from_hex(preimage)) == from_hex(hash)
+        /// Ignored for swaps with an oracle lock.
         preimage: String,
+        /// Amount of the installment to release, only for partial swaps - the whole remaining
+        /// balance if None. The swap stays open with the rest until drained or expired.
+        amount: Option<Uint128>,
+    },
+    /// First step of a front-running safe release: commit to the preimage without revealing it.
+    /// Required for swaps created with `commit_reveal`, whose preimage can only be revealed by
+    /// `RevealRelease`, from the block after the commitment on.
+    CommitRelease {
+        id: String,
+        /// Hex-encoded sha-256 hash of the preimage followed by a salt (must be 64 chars)
+        salted_hash: String,
     },
-    /// Refund returns all remaining tokens to the original sender,
+    /// Second step of a front-running safe release: reveal the committed preimage and salt, which
+    /// releases the swap like `Release`. Only the committer can reveal their commitment, so the
+    /// preimage seen in the mempool cannot be used to release the swap first.
+    RevealRelease {
+        id: String,
+        preimage: String,
+        salt: String,
+        /// Amount of the installment to release, only for partial swaps
+        amount: Option<Uint128>,
+    },
+    /// Refund returns all remaining tokens to the original sender. If triggered by anyone else,
+    /// the configured refund bounty is paid to them out of the refunded tokens.
     Refund {
         id: String,
     },
+    /// Accept a swap created with `accept_required`, which can only be released from then on. Only
+    /// callable by the recipient.
+    Accept {
+        id: String,
+    },
+    /// Cancel a swap created with `accept_required` that the recipient did not accept yet,
+    /// returning all tokens to the source whether it expired or not. Only callable by the source.
+    Cancel {
+        id: String,
+    },
+    /// Retry a failed Cw20 payout, once its backoff delay has passed. Anyone can retry it until
+    /// the attempts of the retry policy are exhausted, and only its recipient afterwards.
+    RetryPayout {
+        id: u64,
+    },
     /// Receive is required in any Cw20 implementation in order to manage the Send/Receive flow.
     /// In the context of atomic swap, it is identical to Create, only that it is used for Cw20.
     Receive(Cw20ReceiveMsg),
+    /// Update the contract configuration. Only callable by the admin.
+    UpdateConfig(UpdateConfigMsg),
+    /// Force refund of a swap that never expires, returning all tokens to the original sender.
+    /// Only callable by the admin.
+    ForceRefund {
+        id: String,
+    },
+    /// Withdraw all the collected creation fees to the admin. Only callable by the admin.
+    WithdrawFees {},
+    /// Exempt a recipient from the release fee. Only callable by the admin.
+    AddFeeExemption { address: String },
+    /// Remove a recipient's release fee exemption. Only callable by the admin.
+    RemoveFeeExemption { address: String },
+    /// Attach metadata on the matching swap on the counterparty chain. Only callable by the swap's
+    /// source. Fields left as None remain unchanged.
+    AttachCounterProof {
+        id: String,
+        counter_chain: Option<String>,
+        counter_tx: Option<String>,
+    },
+    /// Create a swap, along with its counter-swap on a partner atomic swap contract, in the same
+    /// transaction - either both are created or neither is. The swap's ask is sent to the partner
+    /// to fund the counter-swap, whose source is the sender; the rest of the funds fund the swap.
+    CreateMirrored {
+        create: CreateMsg,
+        partner_contract: String,
+        partner_create: CreateMsg,
+    },
+    /// Create a swap from an ICS-20 transfer, through the ibc-hooks middleware: the transfer's memo
+    /// is `{"wasm": {"contract": "<this contract>", "msg": {"ibc_hook_create": {...}}}}`, and the
+    /// transferred funds are the swap's balance. The middleware executes this message from an
+    /// intermediate address nobody controls, so `source` must be set to the address refunds go to.
+    IbcHookCreate(CreateMsg),
+    /// Create the counter-swap of a `CreateMirrored`, refunded to the `source` it names - the
+    /// sender of the mirrored create. Only callable by an atomic swap contract instantiated from
+    /// the same code as this one.
+    CreateCounterSwap(CreateMsg),
+    /// Register a gas sponsor granting fee allowances of `grant` each, or update its grant, or
+    /// remove it with None - refunding what is left of its budget. Only callable by the admin.
+    SetSponsor {
+        address: String,
+        grant: Option<Coin>,
+    },
+    /// Fund the sender's sponsor budget with the funds sent, in the denom of its grant. Only
+    /// callable by a sponsor.
+    FundSponsor {},
+    /// Execute `msg` as the sponsor, and grant `user` a fee allowance out of the sponsor's budget,
+    /// so that a new user can claim swaps without holding gas. Users are granted an allowance only
+    /// once. The allowance is only granted on chain with the "stargate" feature, and otherwise
+    /// emitted in the events for an off-chain sponsor to grant, without drawing on the budget.
+    /// Only callable by a sponsor.
+    Sponsored {
+        user: String,
+        msg: Box<ExecuteMsg>,
+    },
+    /// Mark the sender's inbox events with these ids as read, removing them from their inbox.
+    /// Unknown ids are ignored.
+    AckInbox { ids: Vec<u64> },
+    /// Compare the funds of the denom the contract holds with those its ledger expects it to -
+    /// open swaps, fee payer deposits, collected fees and sponsor budgets. If it holds less,
+    /// creates are paused and an alert is emitted for the admin. Anyone can verify.
+    VerifyLedger { denom: String },
+}
+
+/// The update config message. Fields left as None remain unchanged.
+#[cw_serde]
+#[derive(Default)]
+pub struct UpdateConfigMsg {
+    /// Transfer the admin role to another address
+    pub admin: Option<String>,
+    /// Maximum number of open swaps at a time, 0 removes the cap
+    pub max_open_swaps: Option<u64>,
+    /// Fee on released balances in basis points, 0 disables fees
+    pub release_fee_bps: Option<u64>,
+    /// Whether swaps that never expire can be created
+    pub allow_never_expires: Option<bool>,
+    /// Bounty paid to third parties triggering refunds, in basis points of the refunded balance
+    pub refund_bounty_bps: Option<u64>,
+    /// Flat fee taken from the native funds of every created native swap, a zero amount removes it.
+    /// Cw20 swaps are exempt, their token contract being unable to attach native funds
+    pub creation_fee: Option<Coin>,
+    /// Whether swaps can be locked with a hash whose preimage was already revealed
+    pub allow_revealed_hashes: Option<bool>,
+    /// Price oracle valuing the swap balances in the details query, an empty address removes it
+    pub price_oracle: Option<String>,
+    /// Whether every execute response carries the swap counters as attributes
+    pub telemetry: Option<bool>,
+    /// Retry policy of failed Cw20 payouts, a policy of 0 attempts removes it
+    pub payout_retry: Option<PayoutRetry>,
+    /// Payload size limits, replacing the current ones - none can be zero
+    pub limits: Option<Limits>,
+    /// Maximum duration funds can be locked in a swap for, 0 blocks and 0 seconds removes it
+    pub max_lock_duration: Option<LockDuration>,
+    /// Whether creates are paused, i.e. to resume them once a drift of the ledger is resolved
+    pub creates_paused: Option<bool>,
+    /// Attestation contract sources and recipients must be attested by, an empty address removes it
+    pub attestation: Option<String>,
+    /// Whether Cw20 swaps lock the amount the contract received, rather than the amount sent
+    pub verify_received: Option<bool>,
+    /// Which attributes the execute responses carry
+    pub event_verbosity: Option<EventVerbosity>,
+    /// Gas limit of the Cw20 payouts of released swaps, 0 removes it
+    pub payout_gas_limit: Option<u64>,
+}
+
+/// Verbosity of the attributes the execute responses carry, trading the richness of what indexers
+/// see against the gas paid for the events
+#[cw_serde]
+#[derive(Default)]
+pub enum EventVerbosity {
+    /// Only the baseline attributes - the action, swap id, hash or preimage, parties and balances -
+    /// leaving out those added since, such as fees, payout details and escrow tags
+    Minimal,
+    /// Every attribute
+    #[default]
+    Standard,
+    /// Every attribute, as `Standard` does
+    Verbose,
+}
+
+/// Retry policy of failed Cw20 payouts. A failed payout no longer fails the release, but waits to
+/// be retried with `RetryPayout`, the delay between attempts doubling after each failure.
+#[cw_serde]
+pub struct PayoutRetry {
+    /// Failed attempts after which only the recipient can retry the payout, with no delay
+    pub max_attempts : u32,
+    /// Blocks to wait before the first retry
+    pub base_delay   : u64,
+}
+
+/// Delivery guarantee of the payouts of a released swap
+#[cw_serde]
+pub enum PayoutMode {
+    /// Every asset is delivered, or the release fails as a whole
+    Atomic,
+    /// Each Cw20 asset is delivered on its own, a failed one not failing the release but waiting to
+    /// be claimed by the recipient with `RetryPayout`. Native coins are sent by the bank, which
+    /// does not fail on a valid recipient.
+    BestEffort,
+}
+
+impl PayoutMode {
+    /// The mode, as carried by the events
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PayoutMode::Atomic => "atomic",
+            PayoutMode::BestEffort => "best_effort",
+        }
+    }
 }
 
 /// Receive message is basically just the create message, for whatever reason
@@ -46,6 +235,7 @@ pub enum ReceiveMsg {
 
 /// The create message
 #[cw_serde]
+#[derive(Default)]
 pub struct CreateMsg {
     /// id is a human-readable name for the swap to use later.
     /// 3-20 bytes of utf-8 text
@@ -57,6 +247,155 @@ pub struct CreateMsg {
     /// You can set expiration at time or at block height the contract is valid at.
     /// After the contract is expired, it can be returned to the original funder.
     pub expires: Expiration,
+    /// Optional message attached to a Cw20 payout. If set, the released Cw20 balance is delivered
+    /// with `Cw20ExecuteMsg::Send` so the recipient contract's receive hook gets invoked, instead
+    /// of a plain `Transfer`. Only valid for Cw20 swaps.
+    pub payout_msg: Option<Binary>,
+    /// Optional commitment to an off-chain order (i.e. a signed term sheet). Its sha-256 hash is
+    /// emitted in the create event, binding the off-chain terms to this swap.
+    pub order_commitment: Option<Binary>,
+    /// Optional IBC route for the payout. If set, the released native balance is delivered with an
+    /// ICS-20 transfer to an address on another chain, instead of a local bank send. Only valid for
+    /// native swaps.
+    pub ibc_payout: Option<IbcPayout>,
+    /// Optional chain id of the counterparty chain holding the matching swap
+    pub counter_chain: Option<String>,
+    /// Optional hash of the transaction creating the matching swap on the counterparty chain
+    pub counter_tx: Option<String>,
+    /// Optional lock type, the swap is hashlocked by default. With an oracle lock, `hash` is
+    /// ignored and the swap is released once the oracle attests the event occurred.
+    pub lock_type: Option<LockType>,
+    /// Optional denom (or Cw20 address) asked in exchange on the other side, listing the swap on
+    /// the order book along with `ask_amount`. Informational only.
+    pub ask_denom: Option<String>,
+    /// Optional amount asked in exchange on the other side, must be set along with `ask_denom`
+    pub ask_amount: Option<Uint128>,
+    /// Optional fee payer, setting part of the attached native funds aside to cover the protocol
+    /// fees, so that the whole principal is released or refunded. Only valid for native swaps of a
+    /// single coin, with a deposit in the same denom.
+    pub fee_payer: Option<FeePayer>,
+    /// Optional address the swap is refunded to, and which acts as its source - the sender by
+    /// default. Only honoured on `IbcHookCreate` and `CreateCounterSwap`, rejected elsewhere.
+    pub source: Option<String>,
+    /// Optional flag allowing the swap to be released in several installments. Only valid for
+    /// swaps of a single native coin or Cw20 token.
+    pub partial: Option<bool>,
+    /// Optional flag requiring the swap to be released by `CommitRelease` then `RevealRelease`,
+    /// rather than `Release`, so that its preimage cannot be front-run.
+    pub commit_reveal: Option<bool>,
+    /// Optional flag requiring the recipient to `Accept` the swap before it can be released. Until
+    /// then, the source can `Cancel` it at any time.
+    pub accept_required: Option<bool>,
+    /// Optional extension of the expiration on every installment released, so that an active deal
+    /// does not expire mid-stream. Only valid for partial swaps expiring at a height or a time.
+    pub auto_extend: Option<ExpiryExtension>,
+    /// Optional address the Cw20 payout is redirected to if the recipient cannot receive it, i.e.
+    /// its receive hook fails. Only valid for Cw20 swaps.
+    pub fallback_recipient: Option<String>,
+    /// Optional flag leaving the swap out of the listing queries, so that it is only found by its
+    /// exact id
+    pub private: Option<bool>,
+    /// Optional number of blocks the swap must be released within, once the recipient accepts it
+    /// or commits to its preimage - after which the source can refund it before it expires. Only
+    /// valid for swaps requiring acceptance or a commit-reveal release, and must not be 0.
+    pub reveal_deadline: Option<u64>,
+    /// Optional order the assets are paid out in on release, by native denom or Cw20 contract
+    /// address - assets not listed follow, natives first. Must not list an asset twice.
+    pub payout_order: Option<Vec<String>>,
+    /// Optional guarantee of the payouts on release, following the payout retry policy and the
+    /// fallback recipient if unset
+    pub payout_mode: Option<PayoutMode>,
+}
+
+/// Maximum duration funds can be locked in a swap for, from the block it is created or extended at,
+/// so that a lost preimage does not lock them for years
+#[cw_serde]
+pub struct LockDuration {
+    /// Maximum duration of swaps expiring at a height, unbounded if 0
+    pub blocks  : u64,
+    /// Maximum duration of swaps expiring at a time, unbounded if 0
+    pub seconds : u64,
+}
+
+/// Extension of a partial swap's expiration on every installment released, in blocks for swaps
+/// expiring at a height and in seconds for swaps expiring at a time
+#[cw_serde]
+pub struct ExpiryExtension {
+    /// Extension on every installment released
+    pub delta: u64,
+    /// Cap on the total extension over the life of the swap
+    pub max_total: u64,
+}
+
+/// A fee payer and its deposit, covering the protocol fees of a swap instead of its principal
+#[cw_serde]
+pub struct FeePayer {
+    /// Address refunded whatever the deposit does not end up covering
+    pub address: String,
+    /// Part of the attached native funds set aside for fees
+    pub deposit: Coin,
+}
+
+/// What releasing a swap is conditioned on
+#[cw_serde]
+pub enum LockType {
+    /// Releasing requires the preimage of the swap's hash
+    Hash {},
+    /// Releasing requires the oracle contract to attest that the event occurred
+    Oracle { oracle_addr: String, event_id: String },
+}
+
+/// Query message expected to be supported by an oracle contract
+#[cw_serde]
+pub enum OracleQueryMsg {
+    /// Whether the event occurred
+    EventOccurred { event_id: String },
+}
+
+/// Response expected from an oracle contract
+#[cw_serde]
+pub struct EventOccurredResponse {
+    pub occurred: bool,
+}
+
+/// Query message expected to be supported by a price oracle contract
+#[cw_serde]
+pub enum PriceQueryMsg {
+    /// Price of one unit of the asset - a native denom or a Cw20 token address - in the oracle's
+    /// base denom
+    Price { asset: String },
+}
+
+/// Response expected from a price oracle contract
+#[cw_serde]
+pub struct PriceResponse {
+    pub price: Decimal,
+}
+
+/// Query message expected to be supported by an attestation contract, such as a soulbound
+/// KYC token
+#[cw_serde]
+pub enum AttestationQueryMsg {
+    /// Whether the address holds a valid attestation, i.e. neither revoked nor expired
+    Attested { address: String },
+}
+
+/// Response expected from an attestation contract
+#[cw_serde]
+pub struct AttestedResponse {
+    pub attested: bool,
+}
+
+/// IBC route for delivering a native payout to another chain. Should the transfer fail or time
+/// out, the funds are returned to this contract rather than to the swap's source.
+#[cw_serde]
+pub struct IbcPayout {
+    /// The ICS-20 transfer channel on this chain
+    pub channel: String,
+    /// The recipient's address on the other chain
+    pub remote_recipient: String,
+    /// Seconds after the release at which the transfer times out
+    pub timeout_seconds: u64,
 }
 
 /// Check whether human-readable smart contract's id is valid or not
@@ -69,15 +408,390 @@ pub fn is_valid_name(name: &str) -> bool {
 #[cw_serde]
 #[derive(QueryResponses)]
 pub enum QueryMsg {
-    /// Show all open swaps. Return type is ListResponse.
+    /// Show all open swaps, or only the swaps of the given status, settled swaps included. Return
+    /// type is ListResponse.
     #[returns(ListResponse)]
     List {
         start_after: Option<String>,
         limit: Option<u32>,
+        status: Option<SwapStatus>,
+    },
+    /// Show the open swaps from `source` to `recipient`, through the pair index rather than scanning
+    /// every swap. Return type is ListResponse.
+    #[returns(ListResponse)]
+    ListByPair {
+        source: String,
+        recipient: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Show the open swaps created at least `age_seconds` ago, including those migrated from older
+    /// versions, whose creation is unknown. Return type is ListResponse.
+    #[returns(ListResponse)]
+    ListOlderThan {
+        age_seconds: u64,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Show the open swaps not expired yet, but expiring within `blocks` blocks (for swaps expiring
+    /// at a height) or `seconds` seconds (for swaps expiring at a time) of the current block. Return
+    /// type is ExpiringWithinResponse.
+    #[returns(ExpiringWithinResponse)]
+    ExpiringWithin {
+        blocks: Option<u64>,
+        seconds: Option<u64>,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Show the open swaps giving `give_denom` (a native denom or Cw20 address) and asking
+    /// `want_denom` in exchange, cheapest implied price first, then by id. Return type is
+    /// OrderBookResponse.
+    #[returns(OrderBookResponse)]
+    OrderBook {
+        give_denom: String,
+        want_denom: String,
+        start_after: Option<OrderBookCursor>,
+        limit: Option<u32>,
     },
     /// Returns the details of the named swap, error if not created. Return type: DetailsResponse.
     #[returns(DetailsResponse)]
     Details { id: String },
+    /// Returns the details of each of the named swaps, error if any was not created or there are
+    /// more ids than the details batch limit. Return type: DetailsBatchResponse.
+    #[returns(DetailsBatchResponse)]
+    DetailsBatch { ids: Vec<String> },
+    /// Returns the hash of the given preimage under every supported hash algorithm, exactly as
+    /// release would compute it. Return type: ComputeHashResponse.
+    #[returns(ComputeHashResponse)]
+    ComputeHash { preimage: String },
+    /// Returns the contract configuration. Return type: ConfigResponse.
+    #[returns(ConfigResponse)]
+    Config {},
+    /// Returns the creation fees collected and not yet withdrawn. Return type: CollectedFeesResponse.
+    #[returns(CollectedFeesResponse)]
+    CollectedFees {},
+    /// Returns the open swaps with their raw stored fields and status, for off-chain audits.
+    /// Return type: ExportResponse.
+    #[returns(ExportResponse)]
+    Export {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Returns the open swaps as stored, to be replayed into another instance with the
+    /// `restore_state` migration. Return type: DumpStateResponse.
+    #[returns(DumpStateResponse)]
+    DumpState {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Show the failed Cw20 payouts waiting to be retried. Return type: PendingPayoutsResponse.
+    #[returns(PendingPayoutsResponse)]
+    PendingPayouts {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Show the recipients exempt from the release fee. Return type: FeeExemptionsResponse.
+    #[returns(FeeExemptionsResponse)]
+    FeeExemptions {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Show the gas sponsors, with their budget and usage. Return type: SponsorsResponse.
+    #[returns(SponsorsResponse)]
+    Sponsors {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Show the unread events of the swaps an address is a party of, oldest first. Return type:
+    /// InboxResponse.
+    #[returns(InboxResponse)]
+    Inbox {
+        address: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Show the public swaps whose state changed (created, released or refunded) after the change
+    /// `event_seq`, each once with its latest change, for light clients to sync incrementally.
+    /// Return type: ChangedSinceResponse.
+    #[returns(ChangedSinceResponse)]
+    ChangedSince {
+        event_seq: u64,
+        limit: Option<u32>,
+    },
+    /// Show the settlement metrics of the swaps - the average blocks from creation to release, and
+    /// the share of settled swaps refunded rather than released. Return type: MetricsResponse.
+    #[returns(MetricsResponse)]
+    Metrics {},
+}
+
+/// An order on the order book
+#[cw_serde]
+pub struct Order {
+    /// Id of the swap
+    pub id: String,
+    /// Amount given by the swap
+    pub give_amount: Uint128,
+    /// Amount asked in exchange
+    pub ask_amount: Uint128,
+    /// Implied price, the amount asked per unit given
+    pub price: Decimal,
+}
+
+/// Position in the order book, of an order by its price and id
+#[cw_serde]
+pub struct OrderBookCursor {
+    pub price: Decimal,
+    pub id: String,
+}
+
+/// The order book response
+#[cw_serde]
+pub struct OrderBookResponse {
+    pub orders: Vec<Order>,
+    /// Position of the last order of the page, to start the next page after - None once the book
+    /// is read
+    pub next: Option<OrderBookCursor>,
+}
+
+/// Status of a stored swap
+#[cw_serde]
+pub enum SwapStatus {
+    /// Can still be released
+    Open,
+    /// Can only be refunded
+    Expired,
+    /// Settled by releasing it
+    Released,
+    /// Settled by refunding it
+    Refunded,
+}
+
+/// A swap exactly as stored by the contract
+#[cw_serde]
+pub struct ExportedSwap {
+    pub id: String,
+    pub status: SwapStatus,
+    pub swap: AtomicSwap,
+}
+
+/// The export response
+#[cw_serde]
+pub struct ExportResponse {
+    pub swaps: Vec<ExportedSwap>,
+}
+
+/// A swap as stored, under its id
+#[cw_serde]
+pub struct SwapEntry {
+    pub id: String,
+    pub swap: AtomicSwap,
+}
+
+/// The state dump response
+#[cw_serde]
+pub struct DumpStateResponse {
+    pub entries: Vec<SwapEntry>,
+}
+
+/// The collected fees response
+#[cw_serde]
+pub struct CollectedFeesResponse {
+    pub fees: Vec<Coin>,
+}
+
+/// A failed Cw20 payout waiting to be retried
+#[cw_serde]
+pub struct PendingPayoutResponse {
+    /// Id to retry the payout with
+    pub id: u64,
+    /// The swap the payout releases
+    pub swap_id: String,
+    pub recipient: String,
+    pub token: Cw20Coin,
+    /// Number of failed attempts so far
+    pub attempts: u32,
+    /// Block height from which the payout can be retried
+    pub retry_at: u64,
+}
+
+/// The pending payouts response
+#[cw_serde]
+pub struct PendingPayoutsResponse {
+    pub payouts: Vec<PendingPayoutResponse>,
+}
+
+/// The fee exemptions response
+#[cw_serde]
+pub struct FeeExemptionsResponse {
+    /// Fee exempt addresses
+    pub addresses: Vec<String>,
+}
+
+/// A gas sponsor, with its budget and usage
+#[cw_serde]
+pub struct SponsorResponse {
+    pub address: String,
+    /// Spend limit of each fee allowance granted
+    pub grant: Coin,
+    /// What is left of the funded budget
+    pub budget: Uint128,
+    /// Number of fee allowances granted
+    pub grants: u64,
+    /// Total spend limit of the fee allowances granted
+    pub granted: Uint128,
+}
+
+/// The sponsors response
+#[cw_serde]
+pub struct SponsorsResponse {
+    pub sponsors: Vec<SponsorResponse>,
+}
+
+/// Kind of a swap event notified to a party of the swap
+#[cw_serde]
+pub enum InboxEventKind {
+    /// A swap was created for the recipient notified
+    Created,
+    /// The swap of the source notified was released, in whole or an installment of it
+    Released,
+    /// The swap expired and was refunded, notified to both its source and recipient
+    Expired,
+}
+
+/// Kind of a change of a swap's state
+#[cw_serde]
+pub enum SwapChangeKind {
+    /// The swap was created
+    Created,
+    /// The swap was released, in whole or an installment of it
+    Released,
+    /// The swap was refunded or cancelled
+    Refunded,
+}
+
+/// The latest change of a swap
+#[cw_serde]
+pub struct SwapChangeResponse {
+    /// Sequence number of the change
+    pub seq: u64,
+    pub id: String,
+    pub kind: SwapChangeKind,
+}
+
+/// The changed since response
+#[cw_serde]
+pub struct ChangedSinceResponse {
+    /// The swaps changed, by the sequence number of their latest change
+    pub changes: Vec<SwapChangeResponse>,
+    /// Sequence number of the latest change of all, to sync from next once every change is read
+    pub last_seq: u64,
+}
+
+/// An unread swap event
+#[cw_serde]
+pub struct InboxEventResponse {
+    /// Id to acknowledge the event with
+    pub id: u64,
+    pub kind: InboxEventKind,
+    pub swap_id: String,
+    /// Block height the event happened at
+    pub height: u64,
+}
+
+/// The inbox response
+#[cw_serde]
+pub struct InboxResponse {
+    pub events: Vec<InboxEventResponse>,
+}
+
+/// Hash algorithms supported for the hashlock
+#[cw_serde]
+pub enum HashAlgorithm {
+    Sha256,
+}
+
+impl HashAlgorithm {
+    /// All supported hash algorithms
+    pub fn all() -> Vec<HashAlgorithm> {
+        vec![HashAlgorithm::Sha256]
+    }
+}
+
+/// A preimage hash under a specific algorithm
+#[cw_serde]
+pub struct HashPreview {
+    /// The algorithm used
+    pub algorithm: HashAlgorithm,
+    /// Hex-encoded hash of the preimage (the value to be used as `hash` in create)
+    pub hash: String,
+}
+
+/// The compute hash response, one hash per supported algorithm
+#[cw_serde]
+pub struct ComputeHashResponse {
+    pub hashes: Vec<HashPreview>,
+}
+
+/// The configuration response
+#[cw_serde]
+pub struct ConfigResponse {
+    /// Admin of the contract
+    pub admin: String,
+    /// Maximum number of open swaps at a time, unlimited if None
+    pub max_open_swaps: Option<u64>,
+    /// Number of currently open swaps
+    pub open_swaps: u64,
+    /// Fee on released balances in basis points
+    pub release_fee_bps: u64,
+    /// Whether swaps that never expire can be created
+    pub allow_never_expires: bool,
+    /// Bounty paid to third parties triggering refunds, in basis points of the refunded balance
+    pub refund_bounty_bps: u64,
+    /// Flat fee taken from the native funds of every created native swap, if any - Cw20 swaps are
+    /// exempt
+    pub creation_fee: Option<Coin>,
+    /// Whether swaps can be locked with a hash whose preimage was already revealed
+    pub allow_revealed_hashes: bool,
+    /// Price oracle valuing the swap balances in the details query, if any
+    pub price_oracle: Option<String>,
+    /// Whether every execute response carries the swap counters as attributes
+    pub telemetry: bool,
+    /// Retry policy of failed Cw20 payouts, if any
+    pub payout_retry: Option<PayoutRetry>,
+    /// Payload size limits
+    pub limits: Limits,
+    /// Maximum duration funds can be locked in a swap for, if any
+    pub max_lock_duration: Option<LockDuration>,
+    /// Whether creates are paused
+    pub creates_paused: bool,
+    /// Attestation contract sources and recipients must be attested by, if any
+    pub attestation: Option<String>,
+    /// Whether Cw20 swaps lock the amount the contract received, rather than the amount sent
+    pub verify_received: bool,
+    /// Which attributes the execute responses carry
+    pub event_verbosity: EventVerbosity,
+    /// Gas limit of the Cw20 payouts of released swaps, if any
+    pub payout_gas_limit: Option<u64>,
+}
+
+/// The settlement metrics response, counted from the version introducing each on
+#[cw_serde]
+pub struct MetricsResponse {
+    /// Swaps created
+    pub created: u64,
+    /// Swaps released, in full
+    pub released: u64,
+    /// Swaps refunded
+    pub refunded: u64,
+    /// Blocks between the creation and the release of the released swaps whose creation is known,
+    /// summed
+    pub release_blocks: u64,
+    /// Released swaps whose creation is known
+    pub timed_releases: u64,
+    /// Average blocks between the creation and the release of a swap, None until one is released
+    pub avg_release_blocks: Option<Decimal>,
+    /// Share of the settled swaps which were refunded, None until one is settled
+    pub refund_rate: Option<Decimal>,
 }
 
 /// The list response, which is essentially just a vector of swap ids
@@ -102,15 +816,154 @@ pub struct DetailsResponse {
     pub expires: Expiration,
     /// Balance in native tokens or cw20 token, with human-readable address
     pub balance: BalanceHuman,
+    /// Message attached to the Cw20 payout on release, if any
+    pub payout_msg: Option<Binary>,
+    /// Commitment to the off-chain order this swap settles, if any
+    pub order_commitment: Option<Binary>,
+    /// IBC route the payout is delivered through on release, if any
+    pub ibc_payout: Option<IbcPayout>,
+    /// Chain id of the counterparty chain holding the matching swap, if provided by the source
+    pub counter_chain: Option<String>,
+    /// Transaction creating the matching swap on the counterparty chain, if provided by the source
+    pub counter_tx: Option<String>,
+    /// Block height and time the swap was created at, None for swaps migrated from older versions
+    pub created_at: Option<(u64, Timestamp)>,
+    /// What releasing the swap is conditioned on, None for a hashlock
+    pub lock_type: Option<LockType>,
+    /// What the source asks in exchange on the other side, if listed on the order book
+    pub ask: Option<Coin>,
+    /// The fee payer and what is left of its deposit, if any
+    pub fee_payer: Option<FeePayer>,
+    /// Whether the swap can be released in several installments
+    pub partial: bool,
+    /// Whether the swap can only be released by committing to its preimage, then revealing it
+    pub commit_reveal: bool,
+    /// Whether the recipient must accept the swap before it can be released
+    pub accept_required: bool,
+    /// Whether the recipient accepted the swap
+    pub accepted: bool,
+    /// Extension of the expiration on every installment released, if any
+    pub auto_extend: Option<ExpiryExtension>,
+    /// Total extension of the expiration so far
+    pub extended: u64,
+    /// Address the Cw20 payout is redirected to if the recipient cannot receive it, if any
+    pub fallback_recipient: Option<String>,
+    /// Whether the swap is left out of the listing queries
+    pub private: bool,
+    /// Blocks the swap must be released within once accepted or committed to, if any
+    pub reveal_deadline: Option<u64>,
+    /// Height by which the swap must be released, once its reveal deadline started
+    pub reveal_by: Option<u64>,
+    /// Order the assets are paid out in on release, if set
+    pub payout_order: Option<Vec<String>>,
+    /// Delivery guarantee of the payouts on release, if set
+    pub payout_mode: Option<PayoutMode>,
+    /// Hex-encoded sha-256 hash of the contract address followed by the swap id, identifying the
+    /// swap's share of the funds pooled in the contract
+    pub escrow_id: String,
+    /// Estimated value of the balance in the price oracle's base denom, at query time. None if no
+    /// price oracle is configured, or it does not price every asset of the balance
+    pub value: Option<Uint128>,
+}
+
+/// The details batch response, in the order of the ids queried
+#[cw_serde]
+pub struct DetailsBatchResponse {
+    pub swaps: Vec<DetailsResponse>,
 }
 
-/// Balance representation - either in Native or Cw20 tokens
+/// A swap about to expire
+#[cw_serde]
+pub struct ExpiringSwap {
+    /// Id of the swap
+    pub id: String,
+    /// When the swap becomes refundable
+    pub expires: Expiration,
+}
+
+/// The expiring swaps response, along with the current block the window starts from
+#[cw_serde]
+pub struct ExpiringWithinResponse {
+    /// Current block height
+    pub height: u64,
+    /// Current block time
+    pub time: Timestamp,
+    pub swaps: Vec<ExpiringSwap>,
+}
+
+/// Balance representation - either in Native or Cw20 tokens, or several kinds of both
 #[cw_serde]
 pub enum BalanceHuman {
     Native(Vec<Coin>),
     Cw20(Cw20Coin),
+    Generic { native: Vec<Coin>, cw20: Vec<Cw20Coin> },
+}
+
+/// A single Cw20 token is shown as such, native coins alone as such, and any mix as generic
+impl From<GenericBalance> for BalanceHuman {
+    fn from(balance: GenericBalance) -> Self {
+        let GenericBalance { native, cw20 } = balance;
+        let mut cw20 = cw20
+            .into_iter()
+            .map(|coin| Cw20Coin { address: coin.address.into(), amount: coin.amount })
+            .collect::<Vec<_>>();
+        match (native.is_empty(), cw20.len()) {
+            (true, 1) => BalanceHuman::Cw20(cw20.remove(0)),
+            (_, 0) => BalanceHuman::Native(native),
+            _ => BalanceHuman::Generic { native, cw20 },
+        }
+    }
+}
+
+/// Outcome of a release, set as the response data for contracts releasing through a submessage
+#[cw_serde]
+pub struct ReleaseResult {
+    pub id: String,
+    /// The recipient paid
+    pub recipient: String,
+    /// What the recipient is paid, net of the release fee
+    pub amount: BalanceHuman,
+}
+
+/// Outcome of a refund, set as the response data for contracts refunding through a submessage
+#[cw_serde]
+pub struct RefundResult {
+    pub id: String,
+    /// The source refunded
+    pub source: String,
+    /// What the source is refunded, net of the keeper bounty
+    pub amount: BalanceHuman,
 }
 
-/// Migrate message - as with Cw20 standard, it is for now empty, though open to extensibility
-#[derive(Serialize, Deserialize, JsonSchema)]
-pub struct MigrateMsg {}
\ No newline at end of file
+/// Migrate message - one of the migrations, or the empty message `{}` older versions migrated with,
+/// which upgrades as `Migration::Upgrade` does
+#[cw_serde]
+#[serde(untagged)]
+pub enum MigrateMsg {
+    /// One of the migrations
+    Migration(Migration),
+    /// The empty message older versions migrated with
+    Empty {},
+}
+
+impl From<Migration> for MigrateMsg {
+    fn from(migration: Migration) -> Self {
+        MigrateMsg::Migration(migration)
+    }
+}
+
+/// Migration - either an upgrade of this contract, or taking over a deployment of the upstream
+/// cw20-atomic-swap contract
+#[cw_serde]
+pub enum Migration {
+    /// Upgrade from an older version of this contract
+    Upgrade {},
+    /// Convert the swaps of an upstream cw20-atomic-swap deployment to this contract's schema
+    FromCw20AtomicSwap {},
+    /// Replay the swaps dumped from another instance, for disaster recovery. The contract must
+    /// hold enough funds to back every open swap once restored.
+    RestoreState { entries: Vec<SwapEntry> },
+    /// Close the swaps locked with the contract as their recipient or source, refunding their
+    /// source, or `refund_to` when the source is the contract
+    RescueSelfSwaps { refund_to: String },
+}
\ No newline at end of file
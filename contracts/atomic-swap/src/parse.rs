@@ -0,0 +1,38 @@
+/*
+Parsing of the hex-encoded inputs of the contract, such as swap hashes. Errors are structured, with
+the position of the offending character or the expected length, so that clients can point out
+exactly what is wrong with an input.
+*/
+
+use crate::error::ContractError;
+
+
+/// Parse a hex string of the given size, checking its characters before its length.
+/// # Arguments
+/// * `data`     - the hex string
+/// * `expected` - the expected number of bytes, i.e. half the number of characters
+/// # Returns
+/// * the decoded bytes on Ok
+/// * the error type on Err
+pub fn parse_hex(data: &str, expected: usize) -> Result<Vec<u8>, ContractError> {
+    let invalid = data.chars().enumerate().find(|(_, c)| !c.is_ascii_hexdigit());
+    if let Some((position, char)) = invalid {
+        return Err(ContractError::InvalidHexChar { position, char });
+    }
+    if data.len() != expected * 2 {
+        return Err(ContractError::WrongLength { got: data.len(), expected: expected * 2 });
+    }
+    // every character being a hex digit, decoding cannot fail
+    Ok(hex::decode(data).unwrap_or_default())
+}
+
+
+/// Parse a hex-encoded 32-byte hash, such as a sha-256 hashlock.
+/// # Arguments
+/// * `data` - the hex string, 64 characters long
+/// # Returns
+/// * the hash bytes on Ok
+/// * the error type on Err
+pub fn parse_hex_32(data: &str) -> Result<Vec<u8>, ContractError> {
+    parse_hex(data, 32)
+}
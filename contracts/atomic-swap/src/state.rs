@@ -5,10 +5,20 @@ said, one can still query the swap offer using block info, which would be perman
 */
 
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, Binary, BlockInfo, Order, StdResult, Storage};
+use cosmwasm_std::{
+    Addr, Binary, BlockInfo, Coin, Decimal, Empty, Order, StdResult, Storage, Timestamp, Uint128,
+    Uint64
+};
 
-use cw_storage_plus::{Bound, Map};
-use cw20::{Balance, Expiration};
+use cw_storage_plus::{Bound, Index, IndexList, IndexedMap, Item, Map, MultiIndex};
+use cw20::{Balance, Cw20CoinVerified, Expiration};
+use serde::{Deserialize, Deserializer};
+
+use crate::limits::Limits;
+use crate::msg::{
+    EventVerbosity, ExpiryExtension, FeePayer, IbcPayout, InboxEventKind, LockDuration, LockType,
+    PayoutMode, PayoutRetry, SwapChangeKind
+};
 
 
 /// Old Atomic swap offer representation.
@@ -23,6 +33,118 @@ pub struct OldAtomicSwap {
     pub balance   : Balance,
 }
 
+/// Balance of a swap, holding any number of native coins and Cw20 tokens - unlike `Balance`, which
+/// holds either, it can be topped up with other assets. Each denom and token has a single entry,
+/// sorted by denom and by address, so that equal balances always serialize the same.
+#[cw_serde]
+#[derive(Default)]
+pub struct GenericBalance {
+    pub native : Vec<Coin>,
+    pub cw20   : Vec<Cw20CoinVerified>,
+}
+
+impl GenericBalance {
+    /// Merge tokens into the balance, adding up amounts of the same denom or token.
+    /// # Arguments
+    /// * `add` - the tokens to merge in
+    /// # Returns
+    /// * the overflow error type Err if an amount would overflow, leaving the balance unchanged
+    pub fn add_tokens(&mut self, add: Balance) -> StdResult<()> {
+        let mut merged = self.clone();
+        match add {
+            Balance::Native(coins) => {
+                for coin in coins.into_vec() {
+                    match merged.native.iter_mut().find(|c| c.denom == coin.denom) {
+                        Some(held) => held.amount = held.amount.checked_add(coin.amount)?,
+                        None => merged.native.push(coin),
+                    }
+                }
+            }
+            Balance::Cw20(token) => {
+                match merged.cw20.iter_mut().find(|t| t.address == token.address) {
+                    Some(held) => held.amount = held.amount.checked_add(token.amount)?,
+                    None => merged.cw20.push(token),
+                }
+            }
+        }
+        merged.normalize()?;
+        *self = merged;
+        Ok(())
+    }
+
+    /// Merge the entries of the same denom or token, and sort them by denom and by address.
+    /// # Returns
+    /// * the overflow error type Err if a merged amount would overflow
+    pub fn normalize(&mut self) -> StdResult<()> {
+        self.native.sort_by(|a, b| a.denom.cmp(&b.denom));
+        let mut native: Vec<Coin> = Vec::with_capacity(self.native.len());
+        for coin in self.native.drain(..) {
+            match native.last_mut() {
+                Some(held) if held.denom == coin.denom => {
+                    held.amount = held.amount.checked_add(coin.amount)?
+                }
+                _ => native.push(coin),
+            }
+        }
+        self.cw20.sort_by(|a, b| a.address.cmp(&b.address));
+        let mut cw20: Vec<Cw20CoinVerified> = Vec::with_capacity(self.cw20.len());
+        for token in self.cw20.drain(..) {
+            match cw20.last_mut() {
+                Some(held) if held.address == token.address => {
+                    held.amount = held.amount.checked_add(token.amount)?
+                }
+                _ => cw20.push(token),
+            }
+        }
+        self.native = native;
+        self.cw20 = cw20;
+        Ok(())
+    }
+
+    /// Number of distinct assets the balance holds, ignoring zero amounts
+    pub fn asset_count(&self) -> usize {
+        self.native.iter().filter(|c| !c.amount.is_zero()).count()
+            + self.cw20.iter().filter(|t| !t.amount.is_zero()).count()
+    }
+
+    /// Whether the balance holds nothing, ignoring zero amounts
+    pub fn is_empty(&self) -> bool {
+        self.native.iter().all(|c| c.amount.is_zero())
+            && self.cw20.iter().all(|t| t.amount.is_zero())
+    }
+}
+
+impl From<Balance> for GenericBalance {
+    fn from(balance: Balance) -> Self {
+        match balance {
+            Balance::Native(mut coins) => {
+                coins.normalize();
+                GenericBalance { native: coins.into_vec(), cw20: vec![] }
+            }
+            Balance::Cw20(token) => GenericBalance { native: vec![], cw20: vec![token] },
+        }
+    }
+}
+
+/// Read a swap balance stored either as a generic balance, or as a single-kind `Balance` by the
+/// versions before it - sorted and merged, as versions before may not have stored it so
+fn deserialize_balance<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<GenericBalance, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StoredBalance {
+        Generic(GenericBalance),
+        Legacy(Balance),
+    }
+    let mut balance = match StoredBalance::deserialize(deserializer)? {
+        StoredBalance::Generic(balance) => balance,
+        StoredBalance::Legacy(balance) => balance.into(),
+    };
+    balance.normalize().map_err(serde::de::Error::custom)?;
+    Ok(balance)
+}
+
 /// Atomic swap offer representation.
 #[cw_serde]
 pub struct AtomicSwap {
@@ -30,8 +152,61 @@ pub struct AtomicSwap {
     pub recipient : Addr,
     pub source    : Addr,
     pub expires   : Expiration,
-    pub balance   : Balance,
+    /// Balance in native tokens and cw20 tokens, read from the older single-kind format as well
+    #[serde(deserialize_with = "deserialize_balance")]
+    pub balance   : GenericBalance,
     pub memo      : String,
+    /// Cw20 payout is done via `Send` with this message instead of `Transfer`, if set
+    pub payout_msg: Option<Binary>,
+    /// Commitment to an off-chain order, if any
+    pub order_commitment: Option<Binary>,
+    /// Native payout is delivered over IBC through this route, if set
+    pub ibc_payout: Option<IbcPayout>,
+    /// Chain id of the counterparty chain holding the matching swap, if provided by the source
+    pub counter_chain: Option<String>,
+    /// Transaction creating the matching swap on the counterparty chain, if provided by the source
+    pub counter_tx: Option<String>,
+    /// Block height and time the swap was created at, None for swaps migrated from older versions
+    pub created_at: Option<(u64, Timestamp)>,
+    /// What releasing the swap is conditioned on, None for a hashlock on `hash`
+    pub lock_type: Option<LockType>,
+    /// What the source asks in exchange on the other side, if listed on the order book
+    pub ask: Option<Coin>,
+    /// Covers the protocol fees out of its deposit instead of the principal, if set
+    pub fee_payer: Option<FeePayer>,
+    /// Whether the swap can be released in several installments, false for swaps stored before
+    #[serde(default)]
+    pub partial: bool,
+    /// Whether the swap can only be released by commit-reveal, false for swaps stored before
+    #[serde(default)]
+    pub commit_reveal: bool,
+    /// Whether the recipient must accept the swap before it can be released, false for swaps
+    /// stored before
+    #[serde(default)]
+    pub accept_required: bool,
+    /// Whether the recipient accepted the swap, if it requires acceptance
+    #[serde(default)]
+    pub accepted: bool,
+    /// Extension of the expiration on every installment released, if any
+    pub auto_extend: Option<ExpiryExtension>,
+    /// Total extension of the expiration so far, 0 for swaps stored before
+    #[serde(default)]
+    pub extended: u64,
+    /// Address the Cw20 payout is redirected to if the recipient cannot receive it, if any
+    pub fallback_recipient: Option<Addr>,
+    /// Whether the swap is left out of the listing queries, false for swaps stored before
+    #[serde(default)]
+    pub private: bool,
+    /// Blocks the swap must be released within once the recipient accepts it or commits to its
+    /// preimage, if any
+    pub reveal_deadline: Option<u64>,
+    /// Height by which the swap must be released, once its reveal deadline started - after which
+    /// the source can refund it before it expires
+    pub reveal_by: Option<u64>,
+    /// Order the assets are paid out in on release, by native denom or Cw20 contract address
+    pub payout_order: Option<Vec<String>>,
+    /// Delivery guarantee of the payouts on release, following the config if None
+    pub payout_mode: Option<PayoutMode>,
 }
 
 /// Original atomic swap
@@ -39,6 +214,39 @@ impl OldAtomicSwap {
     pub fn is_expired(&self, block: &BlockInfo) -> bool {
         self.expires.is_expired(block)
     }
+
+    /// Convert to the current swap representation, with none of the newer features set
+    pub fn upgrade(self, memo: String) -> AtomicSwap {
+        AtomicSwap {
+            hash      : self.hash,
+            recipient : self.recipient,
+            source    : self.source,
+            expires   : self.expires,
+            balance   : self.balance.into(),
+            memo,
+            payout_msg: None,
+            order_commitment: None,
+            ibc_payout: None,
+            counter_chain: None,
+            counter_tx: None,
+            created_at: None,
+            lock_type: None,
+            ask: None,
+            fee_payer: None,
+            partial: false,
+            commit_reveal: false,
+            accept_required: false,
+            accepted: false,
+            auto_extend: None,
+            extended: 0,
+            fallback_recipient: None,
+            private: false,
+            reveal_deadline: None,
+            reveal_by: None,
+            payout_order: None,
+            payout_mode: None,
+        }
+    }
 }
 
 
@@ -47,11 +255,336 @@ impl AtomicSwap {
     pub fn is_expired(&self, block: &BlockInfo) -> bool {
         self.expires.is_expired(block)
     }
+
+    /// The denom (native denom or Cw20 address) and amount given, and the coin asked in exchange,
+    /// if the swap is a public order giving a single denom for another
+    pub fn order(&self) -> Option<(String, Uint128, &Coin)> {
+        let ask = match &self.ask {
+            Some(ask) if !self.private => ask,
+            _ => return None,
+        };
+        match (self.balance.native.as_slice(), self.balance.cw20.as_slice()) {
+            ([coin], []) => Some((coin.denom.clone(), coin.amount, ask)),
+            ([], [coin]) => Some((coin.address.to_string(), coin.amount, ask)),
+            _ => None,
+        }
+    }
+
+    /// Key of the swap in the order book: the denoms given and asked, then the implied price as
+    /// big-endian bytes so that the book is ordered by price. Swaps which are not orders get the
+    /// empty key, out of any book.
+    pub fn book_key(&self) -> (String, String, Vec<u8>) {
+        let order = self.order().and_then(|(give_denom, give_amount, ask)| {
+            let price = Decimal::checked_from_ratio(ask.amount, give_amount).ok()?;
+            Some((give_denom, ask.denom.clone(), price.atomics().to_be_bytes().to_vec()))
+        });
+        order.unwrap_or_default()
+    }
+
+    /// Whether the swap was not released by its reveal deadline, if it started
+    pub fn reveal_missed(&self, block: &BlockInfo) -> bool {
+        self.reveal_by.is_some_and(|height| block.height >= height)
+    }
+
+    /// Start the reveal deadline, if the swap has one which did not start yet. Fails if the
+    /// deadline overflows the block height.
+    pub fn start_reveal_deadline(&mut self, block: &BlockInfo) -> StdResult<()> {
+        if let (Some(blocks), None) = (self.reveal_deadline, self.reveal_by) {
+            let reveal_by = Uint64::new(block.height).checked_add(Uint64::new(blocks))?;
+            self.reveal_by = Some(reveal_by.u64());
+        }
+        Ok(())
+    }
+
+    /// Extend the expiration after an installment was released, within the cap of the swap's
+    /// auto extension if it has one, and up to the latest expiration allowed if any. Returns the
+    /// extension.
+    pub fn auto_extend(&mut self, latest: Option<Expiration>) -> u64 {
+        let extension = match &self.auto_extend {
+            Some(ext) => ext.delta.min(ext.max_total.saturating_sub(self.extended)),
+            None => return 0,
+        };
+        let room = match (self.expires, latest) {
+            (Expiration::AtHeight(height), Some(Expiration::AtHeight(max))) => {
+                max.saturating_sub(height)
+            }
+            (Expiration::AtTime(time), Some(Expiration::AtTime(max))) => {
+                max.seconds().saturating_sub(time.seconds())
+            }
+            // otherwise up to the latest expiration that can be represented
+            (Expiration::AtHeight(height), _) => u64::MAX - height,
+            (Expiration::AtTime(time), _) => (u64::MAX - time.nanos()) / 1_000_000_000,
+            (Expiration::Never {}, _) => return 0,
+        };
+        let extension = extension.min(room);
+        self.expires = match self.expires {
+            Expiration::AtHeight(height) => Expiration::AtHeight(height + extension),
+            Expiration::AtTime(time) => Expiration::AtTime(time.plus_seconds(extension)),
+            Expiration::Never {} => return 0,
+        };
+        self.extended += extension;
+        extension
+    }
+}
+
+/// Contract-wide configuration, managed by the admin.
+#[cw_serde]
+pub struct Config {
+    /// The only address allowed to update the configuration
+    pub admin          : Addr,
+    /// Maximum number of swaps that can be open at the same time, unlimited if None
+    pub max_open_swaps : Option<u64>,
+    /// Fee on released balances in basis points, sent to the admin. 0 disables fees
+    pub release_fee_bps: u64,
+    /// Whether swaps that never expire can be created - these can only be refunded by the admin
+    pub allow_never_expires: bool,
+    /// Bounty in basis points of the refunded balance, paid to third parties triggering refunds
+    pub refund_bounty_bps: u64,
+    /// Flat fee taken from the native funds of every created native swap, if any - Cw20 swaps are
+    /// created by their token contract, which cannot attach native funds, and are exempt
+    pub creation_fee: Option<Coin>,
+    /// Whether swaps can be locked with a hash whose preimage was already revealed - anyone could
+    /// release these right away. Rejected unless allowed, including for configs stored before
+    #[serde(default)]
+    pub allow_revealed_hashes: bool,
+    /// Price oracle valuing the swap balances in its base denom for the details query, if any
+    pub price_oracle: Option<Addr>,
+    /// Whether every execute response carries the swap counters as attributes, for telemetry
+    #[serde(default)]
+    pub telemetry: bool,
+    /// Retry policy of failed Cw20 payouts, which otherwise fail the release
+    pub payout_retry: Option<PayoutRetry>,
+    /// Payload size limits, the defaults for configs stored before
+    #[serde(default)]
+    pub limits: Limits,
+    /// Maximum duration funds can be locked in a swap for, unbounded if None
+    pub max_lock_duration: Option<LockDuration>,
+    /// Whether creates are paused, which they are once the funds held drift from the ledger until
+    /// the admin resumes them. False for configs stored before
+    #[serde(default)]
+    pub creates_paused: bool,
+    /// Attestation contract the source of created swaps and the recipient of released ones must
+    /// hold a valid attestation of, if any
+    pub attestation: Option<Addr>,
+    /// Whether the Cw20 tokens received are checked against the contract's balance, so that swaps
+    /// of tokens taxing transfers lock what was received rather than what was sent. False for
+    /// configs stored before
+    #[serde(default)]
+    pub verify_received: bool,
+    /// Which attributes the execute responses carry, the standard ones for configs stored before
+    #[serde(default)]
+    pub event_verbosity: EventVerbosity,
+    /// Gas limit of the Cw20 payouts of released swaps, so that a token contract cannot use up the
+    /// gas of the release - unlimited if None
+    pub payout_gas_limit: Option<u64>,
+}
+
+/// Default configuration - no cap, no fee, and every swap must expire
+impl Config {
+    pub fn new(admin: Addr) -> Self {
+        Config {
+            admin,
+            max_open_swaps      : None,
+            release_fee_bps     : 0,
+            allow_never_expires : false,
+            refund_bounty_bps   : 0,
+            creation_fee        : None,
+            allow_revealed_hashes: false,
+            price_oracle: None,
+            telemetry: false,
+            payout_retry: None,
+            limits: Limits::default(),
+            max_lock_duration: None,
+            creates_paused: false,
+            attestation: None,
+            verify_received: false,
+            event_verbosity: EventVerbosity::Standard,
+            payout_gas_limit: None,
+        }
+    }
+
+    /// The latest a swap expiring as `expires` can expire, if locked at the given block - in
+    /// blocks or seconds as its expiration. None if unbounded.
+    pub fn latest_expiration(&self, block: &BlockInfo, expires: &Expiration) -> Option<Expiration> {
+        let max = self.max_lock_duration.as_ref()?;
+        match expires {
+            Expiration::AtHeight(_) if max.blocks > 0 => {
+                Some(Expiration::AtHeight(block.height + max.blocks))
+            }
+            Expiration::AtTime(_) if max.seconds > 0 => {
+                Some(Expiration::AtTime(block.time.plus_seconds(max.seconds)))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// The contract configuration
+pub const CONFIG: Item<Config> = Item::new("config");
+
+/// Counter of currently open swaps, so that the cap can be checked without iterating the swaps
+pub const OPEN_SWAPS: Item<u64> = Item::new("open_swaps");
+
+/// Lifetime totals of the swaps, by how they were settled
+#[cw_serde]
+#[derive(Default)]
+pub struct SwapTotals {
+    pub created  : u64,
+    pub released : u64,
+    pub refunded : u64,
+    /// Blocks between the creation and the release of the released swaps whose creation is known,
+    /// summed - none for totals stored before
+    #[serde(default)]
+    pub release_blocks : u64,
+    /// Released swaps whose creation is known, which `release_blocks` sums up
+    #[serde(default)]
+    pub timed_releases : u64,
+}
+
+/// Lifetime totals of the swaps, counted from the version introducing them on
+pub const SWAP_TOTALS: Item<SwapTotals> = Item::new("swap_totals");
+
+/// Creation fees collected so far and not yet withdrawn, per denom
+pub const COLLECTED_FEES: Map<&str, Uint128> = Map::new("collected_fees");
+
+/// The native funds the contract is expected to hold, per denom - every open swap's balance and fee
+/// payer deposit, collected fee and sponsor budget, kept up to date as they change
+pub const NATIVE_LEDGER: Map<&str, Uint128> = Map::new("native_ledger");
+
+/// The Cw20 tokens the contract is expected to hold, per token contract - every open swap's
+/// balance and pending payout, kept up to date as they change
+pub const CW20_LEDGER: Map<&Addr, Uint128> = Map::new("cw20_ledger");
+
+/// A commitment to the preimage of a swap, to be revealed to release it
+#[cw_serde]
+pub struct ReleaseCommit {
+    /// The sha-256 hash of the preimage followed by the salt
+    pub salted_hash : Binary,
+    /// Block height the commitment was made at
+    pub height      : u64,
 }
 
+/// Release commitments, by swap id and committer
+pub const RELEASE_COMMITS: Map<(&str, &Addr), ReleaseCommit> = Map::new("release_commits");
+
+/// A Cw20 payout of a released swap, in flight or waiting to be retried after failing
+#[cw_serde]
+pub struct PendingPayout {
+    /// The swap the payout releases
+    pub swap_id    : String,
+    pub recipient  : Addr,
+    pub token      : Cw20CoinVerified,
+    /// Cw20 payout is done via `Send` with this message instead of `Transfer`, if set
+    pub payout_msg : Option<Binary>,
+    /// Number of failed attempts so far
+    pub attempts   : u32,
+    /// Block height from which the payout can be retried
+    pub retry_at   : u64,
+    /// Address the payout is redirected to if it fails, rather than waiting to be retried
+    pub fallback   : Option<Addr>,
+}
+
+/// Cw20 payouts in flight or waiting to be retried, by payout id - which is also the reply id of
+/// their submessage
+pub const PENDING_PAYOUTS: Map<u64, PendingPayout> = Map::new("pending_payouts");
+
+/// Last payout id used
+pub const PAYOUT_COUNT: Item<u64> = Item::new("payout_count");
+
+/// Ids of the settled swaps, retained by how they were settled - "released" or "refunded" - so that
+/// they can be listed apart from the open ones
+pub const SETTLED_SWAPS: Map<(&str, &str), Empty> = Map::new("settled_swaps");
+
+/// Hashes whose preimage was revealed by releasing a swap locked with them
+pub const REVEALED_HASHES: Map<&[u8], Empty> = Map::new("revealed_hashes");
+
+/// Recipients whose releases are exempt from the release fee (market makers, treasury, etc.)
+pub const FEE_EXEMPTIONS: Map<&Addr, Empty> = Map::new("fee_exemptions");
+
+/// A gas sponsor, granting fee allowances to the users it sponsors out of the budget it funded
+#[cw_serde]
+pub struct Sponsor {
+    /// Spend limit of each fee allowance granted, whose denom the budget is funded in
+    pub grant   : Coin,
+    /// What is left of the funded budget
+    pub budget  : Uint128,
+    /// Number of fee allowances granted
+    pub grants  : u64,
+    /// Total spend limit of the fee allowances granted
+    pub granted : Uint128,
+}
+
+/// Gas sponsors, managed by the admin
+pub const SPONSORS: Map<&Addr, Sponsor> = Map::new("sponsors");
+
+/// Users granted a fee allowance, with the sponsor that granted it - each user is granted one once
+pub const SPONSORED_USERS: Map<&Addr, Addr> = Map::new("sponsored_users");
+
+/// An event of a swap, notified to one of its parties until they acknowledge it
+#[cw_serde]
+pub struct InboxEvent {
+    pub kind    : InboxEventKind,
+    pub swap_id : String,
+    /// Block height the event happened at
+    pub height  : u64,
+}
+
+/// Unread events, by address notified and event id
+pub const INBOX: Map<(&Addr, u64), InboxEvent> = Map::new("inbox");
+
+/// Last event id used
+pub const INBOX_COUNT: Item<u64> = Item::new("inbox_count");
+
+/// The latest change of a swap's state
+#[cw_serde]
+pub struct SwapChange {
+    pub swap_id : String,
+    pub kind    : SwapChangeKind,
+}
+
+/// The latest change of every public swap, by sequence number, so that light clients sync the
+/// swaps changed since the last sequence number they saw
+pub const SWAP_CHANGES: Map<u64, SwapChange> = Map::new("swap_changes");
+
+/// Sequence number of the latest change of each swap, whose previous change is dropped
+pub const LAST_CHANGES: Map<&str, u64> = Map::new("last_changes");
+
+/// Last change sequence number used
+pub const CHANGE_SEQ: Item<u64> = Item::new("change_seq");
+
 /// The cache storage on the smart contract to keep track of swap offers
 pub const OLD_SWAPS: Map<&str, OldAtomicSwap> = Map::new("atomic_swap");
-pub const SWAPS: Map<&str, AtomicSwap> = Map::new("new_atomic_swap");
+
+/// Secondary indexes of the swaps, to list the swaps between a source and a recipient, and the
+/// order book of a pair of denoms by price
+pub struct SwapIndexes<'a> {
+    pub pair : MultiIndex<'a, (Addr, Addr), AtomicSwap, String>,
+    pub book : MultiIndex<'a, (String, String, Vec<u8>), AtomicSwap, String>,
+}
+
+impl<'a> IndexList<AtomicSwap> for SwapIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<AtomicSwap>> + '_> {
+        let v: Vec<&dyn Index<AtomicSwap>> = vec![&self.pair, &self.book];
+        Box::new(v.into_iter())
+    }
+}
+
+/// The swap offers, by id, indexed by their (source, recipient) pair and by their order book key
+pub fn swaps<'a>() -> IndexedMap<'a, &'a str, AtomicSwap, SwapIndexes<'a>> {
+    let indexes = SwapIndexes {
+        pair: MultiIndex::new(
+            |_, swap| (swap.source.clone(), swap.recipient.clone()),
+            "new_atomic_swap",
+            "new_atomic_swap__pair",
+        ),
+        book: MultiIndex::new(
+            |_, swap| swap.book_key(),
+            "new_atomic_swap",
+            "new_atomic_swap__book",
+        ),
+    };
+    IndexedMap::new("new_atomic_swap", indexes)
+}
 
 /// This returns the list of ids for all active swaps
 pub fn all_swap_ids<'a>(
@@ -59,7 +592,33 @@ pub fn all_swap_ids<'a>(
     start: Option<Bound<'a, &'a str>>,
     limit: usize,
 ) -> StdResult<Vec<String>> {
-    SWAPS
+    swaps()
+        .keys(storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect()
+}
+
+/// This returns the list of ids of the swaps settled a given way, "released" or "refunded"
+pub fn settled_swap_ids<'a>(
+    storage: &dyn Storage,
+    settled: &str,
+    start: Option<Bound<'a, &'a str>>,
+    limit: usize,
+) -> StdResult<Vec<String>> {
+    SETTLED_SWAPS
+        .prefix(settled)
+        .keys(storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect()
+}
+
+/// This returns the list of fee exempt addresses
+pub fn all_fee_exemptions<'a>(
+    storage: &dyn Storage,
+    start: Option<Bound<'a, &'a Addr>>,
+    limit: usize,
+) -> StdResult<Vec<Addr>> {
+    FEE_EXEMPTIONS
         .keys(storage, start, None, Order::Ascending)
         .take(limit)
         .collect()
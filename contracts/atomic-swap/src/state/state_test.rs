@@ -5,8 +5,12 @@ Testing for state of contract.
 #[cfg(test)]
 mod tests {
     use crate::state::*;
-    use cosmwasm_std::testing::MockStorage;
-    use cosmwasm_std::{Binary, Addr};
+    use cosmwasm_std::testing::{mock_env, MockStorage};
+    use cosmwasm_std::{
+        coin, coins, to_vec, Addr, Binary, Coin, StdError, Storage, Timestamp, Uint128
+    };
+    use cw20::{Balance, Cw20CoinVerified, Expiration};
+    use crate::msg::ExpiryExtension;
 
     /// Dummy atomic swap entry
     fn dummy_swap() -> AtomicSwap {
@@ -17,6 +21,27 @@ mod tests {
             hash      : Binary("hash".into()),
             balance   : Default::default(),
             memo      : "Hello World".to_string(),
+            payout_msg: None,
+            order_commitment: None,
+            ibc_payout: None,
+            counter_chain: None,
+            counter_tx: None,
+            created_at: None,
+            lock_type: None,
+            ask: None,
+            fee_payer: None,
+            partial: false,
+            commit_reveal: false,
+            accept_required: false,
+            accepted: false,
+            auto_extend: None,
+            extended: 0,
+            fallback_recipient: None,
+            private: false,
+            reveal_deadline: None,
+            reveal_by: None,
+            payout_order: None,
+            payout_mode: None,
         }
     }
 
@@ -32,9 +57,9 @@ mod tests {
     #[test]
     fn test_all_swap_ids() {
         let mut storage = MockStorage::new();
-        SWAPS.save(&mut storage, "lazy", &dummy_swap()).unwrap();
-        SWAPS.save(&mut storage, "assign", &dummy_swap()).unwrap();
-        SWAPS.save(&mut storage, "zen", &dummy_swap()).unwrap();
+        swaps().save(&mut storage, "lazy", &dummy_swap()).unwrap();
+        swaps().save(&mut storage, "assign", &dummy_swap()).unwrap();
+        swaps().save(&mut storage, "zen", &dummy_swap()).unwrap();
 
         let ids = all_swap_ids(&storage, None, 10).unwrap();
         assert_eq!(3, ids.len());
@@ -43,4 +68,151 @@ mod tests {
             ids
         )
     }
-}
\ No newline at end of file
+
+    /// Testing merging tokens into a balance, by denom and by token
+    #[test]
+    fn test_add_tokens() {
+        let token = Addr::unchecked("token");
+        let mut balance = GenericBalance::default();
+        balance.add_tokens(Balance::from(coins(100, "atom"))).unwrap();
+        balance.add_tokens(Balance::from(vec![coin(50, "atom"), coin(7, "osmo")])).unwrap();
+        balance.add_tokens(Balance::Cw20(Cw20CoinVerified {
+            address : token.clone(),
+            amount  : Uint128::new(20),
+        })).unwrap();
+        balance.add_tokens(Balance::Cw20(Cw20CoinVerified {
+            address : token.clone(),
+            amount  : Uint128::new(5),
+        })).unwrap();
+
+        assert_eq!(balance.native, vec![coin(150, "atom"), coin(7, "osmo")]);
+        let merged = Cw20CoinVerified { address: token, amount: Uint128::new(25) };
+        assert_eq!(balance.cw20, vec![merged]);
+        assert!(!balance.is_empty());
+    }
+
+    /// Testing an overflowing merge fails, and leaves the balance as it was
+    #[test]
+    fn test_add_tokens_overflow() {
+        let mut balance = GenericBalance::from(Balance::from(coins(1, "osmo")));
+        balance.add_tokens(Balance::from(coins(u128::MAX, "atom"))).unwrap();
+        let before = balance.clone();
+
+        let err = balance
+            .add_tokens(Balance::from(vec![coin(1, "osmo"), coin(1, "atom")]))
+            .unwrap_err();
+        assert!(matches!(err, StdError::Overflow { .. }));
+        assert_eq!(balance, before);
+
+        let token = Cw20CoinVerified { address: Addr::unchecked("token"), amount: Uint128::MAX };
+        let mut balance = GenericBalance::from(Balance::Cw20(token.clone()));
+        let one = Cw20CoinVerified { amount: Uint128::one(), ..token.clone() };
+        balance.add_tokens(Balance::Cw20(one)).unwrap_err();
+        assert_eq!(balance.cw20, vec![token]);
+    }
+
+    /// Testing a reveal deadline overflowing the block height fails, and does not start
+    #[test]
+    fn test_reveal_deadline_overflow() {
+        let mut block = mock_env().block;
+        let mut swap = AtomicSwap { reveal_deadline: Some(u64::MAX - 10), ..dummy_swap() };
+        block.height = 11;
+        let err = swap.start_reveal_deadline(&block).unwrap_err();
+        assert!(matches!(err, StdError::Overflow { .. }));
+        assert_eq!(swap.reveal_by, None);
+
+        block.height = 10;
+        swap.start_reveal_deadline(&block).unwrap();
+        assert_eq!(swap.reveal_by, Some(u64::MAX));
+    }
+
+    /// Testing an auto extension stops at the latest expiration that can be represented
+    #[test]
+    fn test_auto_extend_overflow() {
+        let auto_extend = Some(ExpiryExtension { delta: u64::MAX, max_total: u64::MAX });
+        let mut swap = AtomicSwap {
+            expires: Expiration::AtHeight(u64::MAX - 5),
+            auto_extend: auto_extend.clone(),
+            ..dummy_swap()
+        };
+        assert_eq!(swap.auto_extend(None), 5);
+        assert_eq!(swap.expires, Expiration::AtHeight(u64::MAX));
+        assert_eq!(swap.auto_extend(None), 0);
+
+        let expires = Timestamp::from_nanos(u64::MAX - 2_500_000_000);
+        let mut swap = AtomicSwap {
+            expires: Expiration::AtTime(expires),
+            auto_extend,
+            ..dummy_swap()
+        };
+        assert_eq!(swap.auto_extend(None), 2);
+        assert_eq!(swap.expires, Expiration::AtTime(expires.plus_seconds(2)));
+    }
+
+    /// Testing swaps stored with a legacy single-kind balance still load
+    #[test]
+    fn test_legacy_balance() {
+        let mut storage = MockStorage::new();
+        let mut swap = to_vec(&dummy_swap()).unwrap();
+        let legacy = to_vec(&Balance::from(coins(100, "atom"))).unwrap();
+        let generic = to_vec(&GenericBalance::default()).unwrap();
+        let at = swap.windows(generic.len()).position(|w| w == generic.as_slice()).unwrap();
+        swap.splice(at..at + generic.len(), legacy);
+        storage.set(&swaps().key("legacy"), &swap);
+
+        let loaded = swaps().load(&storage, "legacy").unwrap();
+        assert_eq!(loaded.balance.native, coins(100, "atom"));
+        assert!(loaded.balance.cw20.is_empty());
+    }
+
+    /// Testing balances are sorted and merged, whatever order their tokens were added in
+    #[test]
+    fn test_normalized_balance() {
+        let cw20 = |address: &str, amount: u128| Cw20CoinVerified {
+            address : Addr::unchecked(address),
+            amount  : Uint128::new(amount),
+        };
+        let mut balance = GenericBalance::default();
+        balance.add_tokens(Balance::from(vec![coin(7, "osmo"), coin(3, "atom")])).unwrap();
+        balance.add_tokens(Balance::Cw20(cw20("tokenb", 2))).unwrap();
+        balance.add_tokens(Balance::Cw20(cw20("tokena", 1))).unwrap();
+        balance.add_tokens(Balance::from(coins(4, "atom"))).unwrap();
+
+        assert_eq!(balance.native, vec![coin(7, "atom"), coin(7, "osmo")]);
+        assert_eq!(balance.cw20, vec![cw20("tokena", 1), cw20("tokenb", 2)]);
+        assert_eq!(balance.asset_count(), 4);
+
+        let mut unsorted = GenericBalance {
+            native : vec![coin(2, "osmo"), coin(3, "atom"), coin(5, "osmo"), coin(4, "atom")],
+            cw20   : vec![cw20("tokenb", 2), cw20("tokena", 1)],
+        };
+        unsorted.normalize().unwrap();
+        assert_eq!(unsorted, balance);
+    }
+
+    /// Testing swaps round-trip through storage, equal balances serializing the same and balances
+    /// stored unsorted loading sorted
+    #[test]
+    fn test_balance_round_trip() {
+        let mut storage = MockStorage::new();
+        let with_balance = |native: Vec<Coin>| {
+            let mut balance = GenericBalance::default();
+            balance.add_tokens(Balance::from(native)).unwrap();
+            AtomicSwap { balance, ..dummy_swap() }
+        };
+        let swap = with_balance(vec![coin(1, "osmo"), coin(2, "atom")]);
+        let same = with_balance(vec![coin(2, "atom"), coin(1, "osmo")]);
+        assert_eq!(to_vec(&swap).unwrap(), to_vec(&same).unwrap());
+        swaps().save(&mut storage, "swap", &swap).unwrap();
+        assert_eq!(swaps().load(&storage, "swap").unwrap(), swap);
+
+        // stored unsorted by a version before
+        let unsorted = GenericBalance {
+            native : vec![coin(1, "osmo"), coin(2, "atom")],
+            cw20   : vec![],
+        };
+        let stored = to_vec(&AtomicSwap { balance: unsorted, ..dummy_swap() }).unwrap();
+        storage.set(&swaps().key("unsorted"), &stored);
+        assert_eq!(swaps().load(&storage, "unsorted").unwrap().balance, swap.balance);
+    }
+}
@@ -0,0 +1,158 @@
+/*
+Testing module - a scenario builder over the mock environment, so that tests of swaps interacting
+with expiry read as a sequence of steps rather than a wall of env manipulation. The clock only moves
+when told to, each block lasting `BLOCK_SECONDS`, so scenarios are deterministic. Only compiled for
+tests, or with the "testing" feature for downstream test suites.
+*/
+
+use cosmwasm_std::testing::{
+    mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage
+};
+use cosmwasm_std::{from_binary, Coin, Empty, Env, OwnedDeps, Response, Uint128};
+use serde::de::DeserializeOwned;
+
+use crate::contract::{execute, instantiate, query};
+use crate::msg::{
+    CreateMsg, DetailsResponse, ExecuteMsg, InstantiateMsg, QueryMsg, UpdateConfigMsg
+};
+use crate::ContractError;
+
+/// The address instantiating the contract, and so its admin
+pub const ADMIN: &str = "admin";
+
+/// Duration of a block, in seconds
+pub const BLOCK_SECONDS: u64 = 5;
+
+/// A swap scenario, holding the contract's state and the clock
+pub struct Scenario {
+    pub deps : OwnedDeps<MockStorage, MockApi, MockQuerier, Empty>,
+    pub env  : Env,
+    /// Response of the last step that succeeded
+    pub last : Response,
+}
+
+impl Default for Scenario {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Scenario {
+    /// A contract instantiated by `ADMIN` at the mock block
+    pub fn new() -> Self {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let admin = mock_info(ADMIN, &[]);
+        let last = instantiate(deps.as_mut(), env.clone(), admin, InstantiateMsg {})
+            .expect("instantiate");
+        Scenario { deps, env, last }
+    }
+
+    // clock
+
+    /// Move the clock `blocks` blocks forward
+    pub fn advance_blocks(mut self, blocks: u64) -> Self {
+        self.env.block.height += blocks;
+        self.env.block.time = self.env.block.time.plus_seconds(blocks * BLOCK_SECONDS);
+        self
+    }
+
+    /// Move the clock to the given height, which must not be in the past
+    pub fn at_height(self, height: u64) -> Self {
+        let blocks = height.checked_sub(self.env.block.height).expect("height in the past");
+        self.advance_blocks(blocks)
+    }
+
+    /// Current block height
+    pub fn height(&self) -> u64 {
+        self.env.block.height
+    }
+
+    // steps
+
+    /// Execute a message at the current block, returning its result
+    pub fn try_execute(
+        &mut self,
+        sender : &str,
+        funds  : &[Coin],
+        msg    : ExecuteMsg,
+    ) -> Result<Response, ContractError> {
+        let res = execute(self.deps.as_mut(), self.env.clone(), mock_info(sender, funds), msg)?;
+        self.last = res.clone();
+        Ok(res)
+    }
+
+    /// Execute a message at the current block, which must succeed
+    pub fn execute(mut self, sender: &str, funds: &[Coin], msg: ExecuteMsg) -> Self {
+        let step = format!("{:?}", msg);
+        if let Err(err) = self.try_execute(sender, funds, msg) {
+            panic!("{} failed: {}", step, err);
+        }
+        self
+    }
+
+    /// Execute a message at the current block, which must fail with the given error
+    pub fn execute_err(
+        mut self,
+        sender : &str,
+        funds  : &[Coin],
+        msg    : ExecuteMsg,
+        err    : ContractError,
+    ) -> Self {
+        let step = format!("{:?}", msg);
+        match self.try_execute(sender, funds, msg) {
+            Ok(_) => panic!("{} succeeded, expected {}", step, err),
+            Err(actual) => assert_eq!(actual, err, "{}", step),
+        }
+        self
+    }
+
+    /// Update the configuration as the admin
+    pub fn config(self, msg: UpdateConfigMsg) -> Self {
+        self.execute(ADMIN, &[], ExecuteMsg::UpdateConfig(msg))
+    }
+
+    /// Create a native swap, locking the funds
+    pub fn create(self, sender: &str, funds: &[Coin], msg: CreateMsg) -> Self {
+        self.execute(sender, funds, ExecuteMsg::Create(msg))
+    }
+
+    /// Release the whole swap with the preimage
+    pub fn release(self, id: &str, preimage: &str) -> Self {
+        self.release_partial(id, preimage, None)
+    }
+
+    /// Release an installment of a partial swap with the preimage, or all of it if None
+    pub fn release_partial(self, id: &str, preimage: &str, amount: Option<Uint128>) -> Self {
+        let msg = ExecuteMsg::Release {
+            id       : id.to_string(),
+            preimage : preimage.to_string(),
+            amount,
+        };
+        self.execute(ADMIN, &[], msg)
+    }
+
+    /// Refund the swap, on behalf of the sender
+    pub fn refund(self, sender: &str, id: &str) -> Self {
+        self.execute(sender, &[], ExecuteMsg::Refund { id: id.to_string() })
+    }
+
+    // checks
+
+    /// Query the contract at the current block
+    pub fn query<T: DeserializeOwned>(&self, msg: QueryMsg) -> T {
+        from_binary(&query(self.deps.as_ref(), self.env.clone(), msg).expect("query"))
+            .expect("query response")
+    }
+
+    /// Details of the swap, which must exist
+    pub fn details(&self, id: &str) -> DetailsResponse {
+        self.query(QueryMsg::Details { id: id.to_string() })
+    }
+
+    /// Whether the swap exists, i.e. was neither released nor refunded
+    pub fn exists(&self, id: &str) -> bool {
+        query(self.deps.as_ref(), self.env.clone(), QueryMsg::Details { id: id.to_string() })
+            .is_ok()
+    }
+}
@@ -0,0 +1,187 @@
+/*
+Basket module - the token as a redeemable wrapper for a basket of assets held by the contract. The
+admin lists the native denoms and Cw20 tokens making up the basket, and any holder burns tokens for
+their pro-rata share of each: the burnt fraction of the supply, of what the contract holds. Native
+coins kept as converter reserves are left out, being redeemed at their rate instead.
+*/
+
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{
+    coin, to_binary, BankMsg, CosmosMsg, Decimal, Deps, DepsMut, Env, MessageInfo, Response,
+    StdError, StdResult, Uint128, WasmMsg
+};
+use cw_storage_plus::Item;
+use cw20::{BalanceResponse, Cw20ExecuteMsg, Cw20QueryMsg};
+use cw20_base::contract::execute_burn;
+use cw20_base::state::TOKEN_INFO;
+
+use crate::converter::RESERVES;
+use crate::error::ContractError;
+use crate::msg::{RedemptionAsset, RedemptionRateResponse};
+use crate::state::{add_burned, assert_admin};
+
+/// Maximum number of assets in the basket, all of them being paid out on every redemption
+pub const MAX_BASKET_ASSETS: usize = 10;
+
+/// An asset of the basket
+#[cw_serde]
+pub enum BasketAsset {
+    /// Native coins of the denom
+    Native(String),
+    /// Cw20 tokens of the contract
+    Cw20(String),
+}
+
+/// The assets of the basket, in the order they were listed
+pub const BASKET: Item<Vec<BasketAsset>> = Item::new("basket");
+
+
+/// List or unlist an asset of the basket - admin only.
+/// # Arguments
+/// * `deps`   - mutable dependency which has the storage (state) of the chain
+/// * `info`   - message info, the sender must be the admin
+/// * `asset`  - the asset
+/// * `listed` - whether the asset is redeemed out of the contract's holdings
+/// # Returns
+/// * the execute response on Ok
+/// * the error type on Err
+pub fn execute_set_basket_asset(
+    deps   : DepsMut,
+    info   : MessageInfo,
+    asset  : BasketAsset,
+    listed : bool,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.storage, &info.sender)?;
+    // Cw20 tokens are stored by their validated address, so that they are listed once
+    let asset = match asset {
+        BasketAsset::Cw20(addr) => BasketAsset::Cw20(deps.api.addr_validate(&addr)?.into()),
+        native => native,
+    };
+    let mut basket = BASKET.may_load(deps.storage)?.unwrap_or_default();
+    basket.retain(|listed| *listed != asset);
+    if listed {
+        if basket.len() >= MAX_BASKET_ASSETS {
+            return Err(ContractError::BasketFull { max: MAX_BASKET_ASSETS });
+        }
+        basket.push(asset.clone());
+    }
+    BASKET.save(deps.storage, &basket)?;
+
+    let name = match &asset {
+        BasketAsset::Native(denom) => denom,
+        BasketAsset::Cw20(addr) => addr,
+    };
+    Ok(Response::new()
+        .add_attribute("action", "set_basket_asset")
+        .add_attribute("asset", name)
+        .add_attribute("listed", listed.to_string()))
+}
+
+
+/// What the contract holds of a basket asset, converter reserves excluded.
+fn held(deps: Deps, env: &Env, asset: &BasketAsset) -> StdResult<Uint128> {
+    let contract = env.contract.address.to_string();
+    match asset {
+        BasketAsset::Native(denom) => {
+            let balance = deps.querier.query_balance(&contract, denom)?.amount;
+            let reserve = RESERVES.may_load(deps.storage, denom)?.unwrap_or_default();
+            Ok(balance.saturating_sub(reserve))
+        }
+        BasketAsset::Cw20(addr) => {
+            let query = Cw20QueryMsg::Balance { address: contract };
+            let res: BalanceResponse = deps.querier.query_wasm_smart(addr, &query)?;
+            Ok(res.balance)
+        }
+    }
+}
+
+
+/// Redeem tokens for their pro-rata share of the basket. The tokens are burnt.
+/// # Arguments
+/// * `deps`   - mutable dependency which has the storage (state) of the chain
+/// * `env`    - environment variables, with this contract's address
+/// * `info`   - message info, the sender is redeeming
+/// * `amount` - the amount of tokens redeemed
+/// # Returns
+/// * the execute response on Ok
+/// * the error type on Err
+pub fn execute_redeem_basket(
+    deps   : DepsMut,
+    env    : Env,
+    info   : MessageInfo,
+    amount : Uint128,
+) -> Result<Response, ContractError> {
+    let supply = TOKEN_INFO.load(deps.storage)?.total_supply;
+    if amount.is_zero() || amount > supply {
+        return Err(ContractError::NoFunds {});
+    }
+
+    // the share of each asset is rounded down, so that the basket always covers the supply left
+    let mut msgs: Vec<CosmosMsg> = vec![];
+    let mut redeemed = vec![];
+    for asset in BASKET.may_load(deps.storage)?.unwrap_or_default() {
+        let share = held(deps.as_ref(), &env, &asset)?
+            .checked_multiply_ratio(amount, supply)
+            .map_err(|_| StdError::generic_err("basket share overflow"))?;
+        if share.is_zero() {
+            continue;
+        }
+        match asset {
+            BasketAsset::Native(denom) => {
+                redeemed.push(coin(share.u128(), &denom).to_string());
+                msgs.push(BankMsg::Send {
+                    to_address : info.sender.to_string(),
+                    amount     : vec![coin(share.u128(), denom)],
+                }.into());
+            }
+            BasketAsset::Cw20(addr) => {
+                redeemed.push(format!("{}{}", share, addr));
+                let transfer = Cw20ExecuteMsg::Transfer {
+                    recipient : info.sender.to_string(),
+                    amount    : share,
+                };
+                msgs.push(WasmMsg::Execute {
+                    contract_addr : addr,
+                    msg           : to_binary(&transfer)?,
+                    funds         : vec![],
+                }.into());
+            }
+        }
+    }
+    if msgs.is_empty() {
+        return Err(ContractError::NoFunds {});
+    }
+
+    add_burned(deps.storage, amount)?;
+    let res = execute_burn(deps, env, info, amount)?;
+    Ok(res
+        .add_attribute("redeemed", redeemed.join(","))
+        .add_messages(msgs))
+}
+
+
+/// Query the redemption rate of the basket - what each asset the contract holds is redeemed for,
+/// per token.
+/// # Arguments
+/// * `deps` - dependency which has the storage (state) of the chain
+/// * `env`  - environment variables, with this contract's address
+/// # Returns
+///   The holdings of every asset in the basket, and their share per token
+pub fn query_redemption_rate(deps: Deps, env: Env) -> StdResult<RedemptionRateResponse> {
+    let supply = TOKEN_INFO.load(deps.storage)?.total_supply;
+    let assets = BASKET
+        .may_load(deps.storage)?
+        .unwrap_or_default()
+        .into_iter()
+        .map(|asset| {
+            let held = held(deps, &env, &asset)?;
+            let per_token = match supply.is_zero() {
+                true => Decimal::zero(),
+                false => Decimal::checked_from_ratio(held, supply)
+                    .map_err(|_| StdError::generic_err("redemption rate overflow"))?,
+            };
+            Ok(RedemptionAsset { asset, held, per_token })
+        })
+        .collect::<StdResult<_>>()?;
+    Ok(RedemptionRateResponse { supply, assets })
+}
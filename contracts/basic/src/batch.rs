@@ -0,0 +1,109 @@
+/*
+Batch module - allowance-based sends fanned out to several contracts at once. The spender's allowance
+is deducted once for the total, rather than once per contract, which saves automation bots both gas
+and allowance writes. Each target is validated as a single send to it would be.
+*/
+
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Uint128};
+use cw20::Cw20ReceiveMsg;
+use cw20_base::allowances::deduct_allowance;
+use cw20_base::state::BALANCES;
+
+use crate::corridors::assert_corridor;
+use crate::error::ContractError;
+use crate::limits::spend_limit;
+use crate::sinks::{burn_debited, is_burn_sink};
+use crate::transfers::query_transfer_policy;
+
+/// A contract to send to within a batch, with the message triggered on it
+#[cw_serde]
+pub struct SendTarget {
+    pub contract : String,
+    pub amount   : Uint128,
+    pub msg      : Binary,
+}
+
+
+/// Validate a target of a batch as a single `SendFrom` to it would be: it must move tokens, go to a
+/// valid address along an allowed corridor, and only back to the owner if the policy allows it.
+/// # Arguments
+/// * `deps`   - dependency which has the storage (state) of the chain
+/// * `owner`  - the owner of the tokens sent
+/// * `target` - the target
+/// # Returns
+/// * the target's address on Ok
+/// * the error type on Err
+fn validate_target(deps: Deps, owner: &Addr, target: &SendTarget) -> Result<Addr, ContractError> {
+    if target.amount.is_zero() {
+        return Err(ContractError::ZeroAmountTransfer {});
+    }
+    let recipient = deps.api.addr_validate(&target.contract)?;
+    if recipient == owner && !query_transfer_policy(deps)?.allow_self {
+        return Err(ContractError::SelfTransfer {});
+    }
+    assert_corridor(deps, owner, recipient.as_str())?;
+    Ok(recipient)
+}
+
+
+/// Send from batch - send from the owner's balance to each target contract, triggering their
+/// receive hook, with a single allowance deduction for the total. Targets that are burn sinks
+/// have their amount burnt instead.
+/// # Arguments
+/// * `deps`    - mutable dependency which has the storage (state) of the chain
+/// * `env`     - environment variables which include block information
+/// * `info`    - message info, the sender is the spender
+/// * `owner`   - the owner of the tokens sent
+/// * `targets` - the contracts to send to
+/// # Returns
+/// * the execute response on Ok
+/// * the error type on Err
+pub fn execute_send_from_batch(
+    deps    : DepsMut,
+    env     : Env,
+    info    : MessageInfo,
+    owner   : String,
+    targets : Vec<SendTarget>,
+) -> Result<Response, ContractError> {
+    let owner_addr = deps.api.addr_validate(&owner)?;
+    if targets.is_empty() {
+        return Err(ContractError::EmptyBatch {});
+    }
+    let recipients = targets
+        .iter()
+        .map(|target| validate_target(deps.as_ref(), &owner_addr, target))
+        .collect::<Result<Vec<_>, _>>()?;
+    let total = targets.iter().map(|target| target.amount).sum::<Uint128>();
+
+    // deduct the allowance and debit the owner once for the total
+    spend_limit(deps.storage, &env.block, &owner_addr, total)?;
+    deduct_allowance(deps.storage, &owner_addr, &info.sender, &env.block, total)?;
+    BALANCES.update(deps.storage, &owner_addr, |balance| -> StdResult<_> {
+        Ok(balance.unwrap_or_default().checked_sub(total)?)
+    })?;
+
+    let mut res = Response::new()
+        .add_attribute("action", "send_from_batch")
+        .add_attribute("from", &owner)
+        .add_attribute("by", &info.sender)
+        .add_attribute("amount", total);
+    for (target, rcpt_addr) in targets.into_iter().zip(recipients) {
+        if is_burn_sink(deps.as_ref(), rcpt_addr.as_str())? {
+            burn_debited(deps.storage, target.amount)?;
+            res = res.add_attribute("burn_sink", rcpt_addr);
+            continue;
+        }
+        BALANCES.update(deps.storage, &rcpt_addr, |balance| -> StdResult<_> {
+            Ok(balance.unwrap_or_default() + target.amount)
+        })?;
+        let msg = Cw20ReceiveMsg {
+            sender : info.sender.to_string(),
+            amount : target.amount,
+            msg    : target.msg,
+        }
+        .into_cosmos_msg(target.contract)?;
+        res = res.add_message(msg);
+    }
+    Ok(res)
+}
@@ -2,7 +2,7 @@ use std::env::current_dir;
 use std::fs::create_dir_all;
 
 use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
-use cw20_base::msg::{InstantiateMsg, QueryMsg, ExecuteMsg};
+use basic::msg::{InstantiateMsg, QueryMsg, ExecuteMsg};
 
 
 /// This will create the json schemas for the different types of messages, including Instantiate,
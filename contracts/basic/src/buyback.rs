@@ -0,0 +1,137 @@
+/*
+Buyback module - native funds sent to the token are swapped for the token itself through a DEX
+router configured by the admin, and whatever is bought is burnt. The swap is a submessage, and the
+burn happens in its reply, once the router has delivered the tokens to this contract.
+*/
+
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{
+    to_binary, Addr, DepsMut, Env, MessageInfo, Response, SubMsg, Uint128, WasmMsg
+};
+use cw_storage_plus::Item;
+use cw20_base::contract::execute_burn;
+use cw20_base::state::BALANCES;
+
+use crate::error::ContractError;
+use crate::state::{add_burned, assert_admin, holders_snapshot, update_holder_count};
+
+/// Reply id of the router swap submessage
+pub const BUYBACK_REPLY_ID: u64 = 1;
+
+/// The DEX router the buybacks are swapped through, if any
+pub const BUYBACK_ROUTER: Item<Addr> = Item::new("buyback_router");
+
+/// Balance of this contract before the pending buyback, to tell what the swap bought
+pub const PENDING_BUYBACK: Item<Uint128> = Item::new("pending_buyback");
+
+/// Execute message expected to be supported by the router contract. The router swaps the funds
+/// attached for the ask token, and sends the proceeds to the caller.
+#[cw_serde]
+pub enum RouterExecuteMsg {
+    /// Swap the attached funds for the ask token, failing if less than `min_receive` is bought
+    Swap {
+        ask_token: String,
+        min_receive: Option<Uint128>,
+    },
+}
+
+
+/// Set the buyback router contract - admin only.
+/// # Arguments
+/// * `deps`   - mutable dependency which has the storage (state) of the chain
+/// * `info`   - message info, the sender must be the admin
+/// * `router` - the router contract, None to disable buybacks
+/// # Returns
+/// * the execute response on Ok
+/// * the error type on Err
+pub fn execute_set_buyback_router(
+    deps   : DepsMut,
+    info   : MessageInfo,
+    router : Option<String>,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.storage, &info.sender)?;
+    let router = router.map(|r| deps.api.addr_validate(&r)).transpose()?;
+    match &router {
+        Some(router) => BUYBACK_ROUTER.save(deps.storage, router)?,
+        None => BUYBACK_ROUTER.remove(deps.storage),
+    }
+    Ok(Response::new()
+        .add_attribute("action", "set_buyback_router")
+        .add_attribute("router", router.map_or("none".to_string(), String::from)))
+}
+
+
+/// Buyback and burn - swap the native funds sent for this token through the router. The tokens
+/// bought are burnt in the reply.
+/// # Arguments
+/// * `deps`        - mutable dependency which has the storage (state) of the chain
+/// * `env`         - environment variables which include block information
+/// * `info`        - message info, with the native funds to buy back with
+/// * `min_receive` - the minimum amount of tokens to buy, passed on to the router
+/// # Returns
+/// * the execute response on Ok
+/// * the error type on Err
+pub fn execute_buyback(
+    deps        : DepsMut,
+    env         : Env,
+    info        : MessageInfo,
+    min_receive : Option<Uint128>,
+) -> Result<Response, ContractError> {
+    let router = BUYBACK_ROUTER.may_load(deps.storage)?.ok_or(ContractError::BuybackDisabled {})?;
+    if info.funds.iter().all(|coin| coin.amount.is_zero()) {
+        return Err(ContractError::NoFunds {});
+    }
+
+    let contract = env.contract.address;
+    let before = BALANCES.may_load(deps.storage, &contract)?.unwrap_or_default();
+    PENDING_BUYBACK.save(deps.storage, &before)?;
+
+    let swap = WasmMsg::Execute {
+        contract_addr : router.into(),
+        msg           : to_binary(&RouterExecuteMsg::Swap {
+            ask_token: contract.into(),
+            min_receive,
+        })?,
+        funds         : info.funds.clone(),
+    };
+    let offered = info.funds
+        .iter()
+        .map(|coin| coin.to_string())
+        .collect::<Vec<_>>()
+        .join(",");
+    Ok(Response::new()
+        .add_submessage(SubMsg::reply_on_success(swap, BUYBACK_REPLY_ID))
+        .add_attribute("action", "buyback")
+        .add_attribute("offered", offered))
+}
+
+
+/// Buyback reply - burn everything the router swap added to this contract's balance.
+/// # Arguments
+/// * `deps` - mutable dependency which has the storage (state) of the chain
+/// * `env`  - environment variables which include block information
+/// # Returns
+/// * the response on Ok
+/// * the error type on Err
+pub fn reply_buyback(mut deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let before = PENDING_BUYBACK.load(deps.storage)?;
+    PENDING_BUYBACK.remove(deps.storage);
+
+    let contract = env.contract.address.clone();
+    let after = BALANCES.may_load(deps.storage, &contract)?.unwrap_or_default();
+    let bought = after.saturating_sub(before);
+
+    let res = Response::new()
+        .add_attribute("action", "buyback_burn")
+        .add_attribute("bought", bought);
+    if bought.is_zero() {
+        return Ok(res.add_attribute("burned", bought));
+    }
+
+    let snapshot = holders_snapshot(deps.storage, vec![contract.clone()])?;
+    add_burned(deps.storage, bought)?;
+    let info = MessageInfo { sender: contract, funds: vec![] };
+    execute_burn(deps.branch(), env, info, bought)?;
+    update_holder_count(deps.storage, snapshot)?;
+    Ok(res.add_attribute("burned", bought))
+}
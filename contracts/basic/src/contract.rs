@@ -1,27 +1,128 @@
 use cosmwasm_std::{
-    Deps, DepsMut, Env, MessageInfo, Response, StdResult, Binary, to_binary, entry_point
+    Addr, Deps, DepsMut, Empty, Env, MessageInfo, Order, Reply, Response, StdError, StdResult,
+    Uint128, Binary, to_binary, entry_point
 };
 use cw2::set_contract_version;
 use cw20_base::allowances::{
-    execute_transfer_from, execute_send_from, execute_burn_from,
-    execute_increase_allowance, execute_decrease_allowance, query_allowance
+    execute_send_from, execute_burn_from, query_allowance
 };
 use cw20_base::contract::{
-    execute_transfer, execute_burn, execute_send, execute_mint, execute_update_marketing,
-    execute_upload_logo, execute_update_minter, query_balance, query_token_info, query_minter, 
-    query_marketing_info, query_download_logo
+    execute_burn, execute_mint, execute_update_marketing,
+    query_balance, query_token_info, query_download_logo
 };
-use cw20_base::ContractError;
 use cw20_base::enumerable::{query_owner_allowances, query_all_accounts, query_spender_allowances};
-use cw20_base::msg::{
-    InstantiateMsg, ExecuteMsg, QueryMsg, MigrateMsg
+use cw20_base::state::BALANCES;
+
+use crate::basket::{execute_redeem_basket, execute_set_basket_asset, query_redemption_rate};
+use crate::batch::execute_send_from_batch;
+use crate::buyback::{
+    execute_buyback, execute_set_buyback_router, reply_buyback, BUYBACK_REPLY_ID
+};
+use crate::converter::{execute_deposit, execute_redeem, execute_set_deposit_rate, query_reserves};
+use crate::dependents::{
+    execute_set_dependent, notify_dependents, query_dependents, reply_notify, NOTIFY_REPLY_ID
+};
+use crate::error::ContractError;
+use crate::faucet::{execute_faucet_mint, FAUCET};
+use crate::flash::{
+    execute_flash_mint, execute_set_flash_mint_fee, query_flash_mint_fee, reply_flash_mint,
+    FLASH_MINT_REPLY_ID
+};
+use crate::guardian::{
+    execute_action, execute_cancel_action, execute_propose_action, execute_set_paused,
+    query_guardian, query_pending_actions, PAUSED
+};
+use crate::history::{query_supply_history, record_supply};
+use crate::interfaces::{declare_interfaces, query_supported_interfaces};
+#[cfg(any(debug_assertions, feature = "invariants"))]
+use crate::invariants::query_check_invariants;
+use crate::middleware::run_pipeline;
+use crate::ledger::{
+    execute_set_ledger_retention, ledger_snapshot, query_ledger_delta, record_ledger
+};
+use crate::limits::{
+    execute_set_daily_limit, execute_set_default_daily_limit, query_remaining_limit,
+    DEFAULT_DAILY_LIMIT
+};
+use crate::locks::{execute_lock, execute_unlock, query_locked_balance, query_locks};
+use crate::logo::{execute_set_logo_policy, execute_upload_logo_checked, query_logo_policy};
+use crate::minters::query_revoked_minters;
+use crate::msg::{
+    InstantiateMsg, ExecuteMsg, QueryMsg, MigrateMsg, TokenInfoExtendedResponse
+};
+use crate::names::{execute_set_name_service, execute_transfer_to_name, query_resolve_name};
+use crate::permits::{execute_set_permit_key, query_with_permit};
+use crate::pool::{
+    execute_mint_to_pool, execute_set_liquidity_pool, reply_mint_to_pool, POOL_REPLY_ID
+};
+use crate::receipts::query_receipt;
+use crate::recipients::{assert_not_contract, execute_set_contract_recipient_check};
+use crate::roles::{execute_set_role, grant_all, minting_info, query_has_role, Role, ROLES};
+use crate::scheduled::{
+    execute_cancel_scheduled, execute_schedule_transfer, execute_scheduled,
+    query_scheduled_transfers
+};
+use crate::renounce::{
+    execute_cancel_renounce, execute_renounce_admin, is_due, query_admin_status,
+    query_marketing_info, query_minter, RENOUNCE_AT
+};
+use crate::sends::{
+    execute_send_refundable, pending_send_accounts, reply_send, SEND_REPLY_ID
+};
+use crate::sessions::{
+    execute_register_session, execute_revoke_session, execute_session_transfer, query_sessions
+};
+use crate::corridors::{
+    execute_set_corridor, execute_set_corridor_mode, execute_set_group_member, query_corridor,
+    query_group_members, query_groups
+};
+use crate::display::{query_format_amount, query_parse_amount};
+use crate::purposes::{
+    execute_decrease_allowance_with_purpose, execute_increase_allowance_with_purpose,
+    query_all_allowances_extended, query_allowance_extended
+};
+use crate::labels::{execute_set_label, query_all_accounts_extended, query_balance_extended};
+use crate::siblings::{
+    execute_convert, execute_set_sibling, pending_converter, query_siblings, reply_convert,
+    CONVERT_REPLY_ID
+};
+#[cfg(any(debug_assertions, feature = "snapshot"))]
+use crate::snapshot::query_raw_balance_range;
+use crate::sinks::{
+    execute_set_burn_sink, execute_transfer_from_or_burn, execute_transfer_or_burn,
+    query_burn_sinks
+};
+use crate::streams::{
+    execute_cancel_stream_allowance, execute_create_stream_allowance, execute_stream_transfer,
+    draws_on_stream, query_stream_allowances
+};
+use crate::subscriptions::{
+    execute_cancel_subscription, execute_collect, execute_subscribe, query_subscription,
+    query_subscriptions
+};
+use crate::transfers::{execute_set_transfer_policy, query_transfer_policy, transfer_noop};
+use crate::treasury::{
+    execute_approve_spend, execute_propose_spend, init_treasury, query_pending_spends
+};
+use crate::validation::{query_validate_address, query_validate_amount};
+use crate::vesting::{execute_claim_vested, execute_mint_vested, query_vesting, VESTING_GRANTS};
+use crate::state::{
+    add_burned, holders_snapshot, update_holder_count, ADMIN, ESCROWED, HOLDER_COUNT, JURISDICTION,
+    TOTAL_BURNED
 };
 
 const CONTRACT_NAME: &str = "crates.io::eames-token";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+// maximum size of a transfer memo, in bytes
+pub(crate) const MAX_MEMO_LEN: usize = 256;
+
+// tickers of well-known assets, which the token cannot impersonate
+const RESERVED_TICKERS: [&str; 8] = ["ATOM", "OSMO", "USDC", "USDT", "BTC", "ETH", "DAI", "ORAI"];
+
 
-/// Instantiate - calling cw20_base instantiation
+/// Instantiate - calling cw20_base instantiation, the instantiator becomes the token's admin and
+/// holds every role. The compliance settings are applied first, and the ticker must not be reserved
 /// # Arguments
 /// * `deps` - mutable dependency which has the storage (state) of the chain
 /// * `env`  - environment variables which include block information
@@ -32,17 +133,49 @@ const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 /// * the error type on Err
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
-    deps : DepsMut,
-    env  : Env,
-    info : MessageInfo,
-    msg  : InstantiateMsg
+    mut deps : DepsMut,
+    env      : Env,
+    info     : MessageInfo,
+    msg      : InstantiateMsg
 ) -> Result<Response, ContractError> {
+    if RESERVED_TICKERS.contains(&msg.symbol.to_uppercase().as_str()) {
+        return Err(ContractError::ReservedTicker { symbol: msg.symbol });
+    }
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
-    cw20_base::contract::instantiate(deps, env, info, msg)
+    ADMIN.save(deps.storage, &info.sender)?;
+    grant_all(deps.storage, &info.sender)?;
+
+    // compliance settings
+    if let Some(jurisdiction) = &msg.jurisdiction {
+        JURISDICTION.save(deps.storage, jurisdiction)?;
+    }
+    if let Some(compliance_admin) = &msg.compliance_admin {
+        let addr = deps.api.addr_validate(compliance_admin)?;
+        ROLES.save(deps.storage, (Role::Compliance.as_str(), &addr), &Empty {})?;
+    }
+    if let Some(limit) = msg.default_daily_limit {
+        DEFAULT_DAILY_LIMIT.save(deps.storage, &limit)?;
+    }
+    if let Some(treasury) = msg.treasury.clone() {
+        init_treasury(deps.branch(), treasury)?;
+    }
+    if msg.test_faucet {
+        FAUCET.save(deps.storage, &true)?;
+    }
+    declare_interfaces(deps.storage)?;
+
+    // cw20 base rejects duplicate initial balances, so each non-zero one is a distinct holder
+    let holders = msg.initial_balances.iter().filter(|c| !c.amount.is_zero()).count() as u64;
+    HOLDER_COUNT.save(deps.storage, &holders)?;
+    let block = env.block.clone();
+    let res = cw20_base::contract::instantiate(deps.branch(), env, info, msg.into())?;
+    record_supply(deps.storage, &block)?;
+    Ok(res)
 }
 
 
-/// Execute - calling cw20_base execute functions. Arguments are identical to that of Instantiate.
+/// Execute - calling cw20_base execute functions, along with the token's own modules, through the
+/// pipeline of the cross-cutting ones. Arguments are identical to that of Instantiate.
 /// # Arguments
 /// * `deps` - mutable dependency which has the storage (state) of the chain
 /// * `env`  - environment variables which include block information
@@ -57,50 +190,122 @@ pub fn execute(
     env  : Env,
     info : MessageInfo,
     msg  : ExecuteMsg
+) -> Result<Response, ContractError> {
+    // the cross-cutting modules run around the handler (see the middleware module)
+    run_pipeline(deps, env, info, msg, dispatch)
+}
+
+
+/// Dispatch an execute message to its handler.
+/// # Arguments
+/// * `deps` - mutable dependency which has the storage (state) of the chain
+/// * `env`  - environment variables which include block information
+/// * `info` - message info, such as sender/initiator and denomination
+/// * `msg`  - the execute message
+/// # Returns
+/// * the execute response on Ok
+/// * the error type on Err
+fn dispatch(
+    deps : DepsMut,
+    env  : Env,
+    info : MessageInfo,
+    msg  : ExecuteMsg
 ) -> Result<Response, ContractError> {
     // pattern matching message type
     match msg {
 
-        // transfer action (initiator is sender)
+        // transfer action (initiator is sender), burning if the recipient is a burn sink
+        // (rejecting contract recipients with the check on, unless allowed), and a no-op if it
+        // moves nothing and the transfer policy allows it
         ExecuteMsg::Transfer {
             recipient,
-            amount
-        } => execute_transfer(deps, env, info, recipient, amount),
+            amount,
+            allow_contract
+        } => {
+            let noop = transfer_noop(deps.as_ref(), "transfer", &info.sender, &recipient, amount)?;
+            if let Some(res) = noop {
+                return Ok(res);
+            }
+            assert_not_contract(deps.as_ref(), &recipient, allow_contract.unwrap_or_default())?;
+            execute_transfer_or_burn(deps, env, info, recipient, amount)
+        }
+
+        // transfer with memo action - a transfer, with the memo only kept in the events
+        ExecuteMsg::TransferWithMemo {
+            recipient,
+            amount,
+            memo
+        } => {
+            if memo.len() > MAX_MEMO_LEN {
+                return Err(ContractError::MemoTooLong { max: MAX_MEMO_LEN });
+            }
+            let noop = transfer_noop(deps.as_ref(), "transfer", &info.sender, &recipient, amount)?;
+            if let Some(res) = noop {
+                return Ok(res.add_attribute("memo", memo));
+            }
+            let res = execute_transfer_or_burn(deps, env, info, recipient, amount)?;
+            Ok(res.add_attribute("memo", memo))
+        }
 
         // burn action (initiator's amount will get burnt)
         ExecuteMsg::Burn {
             amount
-        } => execute_burn(deps, env, info, amount),
+        } => {
+            add_burned(deps.storage, amount)?;
+            Ok(execute_burn(deps, env, info, amount)?)
+        }
 
-        // send action - transfer with an extra message as instruction for the smart contract
+        // send action - transfer with an extra message as instruction for the smart contract,
+        // a no-op without calling the contract if it moves nothing and the policy allows it
         ExecuteMsg::Send {
             contract,
             amount,
             msg
-        } => execute_send(deps, env, info, contract, amount, msg),
+        } => {
+            let noop = transfer_noop(deps.as_ref(), "send", &info.sender, &contract, amount)?;
+            if let Some(res) = noop {
+                return Ok(res);
+            }
+            execute_send_refundable(deps, env, info, contract, amount, msg)
+        }
 
         // increase allowance action - initiator increases another contract's total allowance to spend
-        // on behalf of them
+        // on behalf of them, stating what it is for if the purpose is set
         ExecuteMsg::IncreaseAllowance {
             spender,
             amount, 
-            expires 
-        } => execute_increase_allowance(deps, env, info, spender, amount, expires),
+            expires,
+            purpose
+        } => execute_increase_allowance_with_purpose(
+            deps, env, info, spender, amount, expires, purpose
+        ),
         
         // decrease allownace action (similar to increase)
         ExecuteMsg::DecreaseAllowance { 
             spender, 
             amount, 
-            expires 
-        } => execute_decrease_allowance(deps, env, info, spender, amount, expires),
+            expires,
+            purpose
+        } => execute_decrease_allowance_with_purpose(
+            deps, env, info, spender, amount, expires, purpose
+        ),
 
         // transfer from action - uses allowance to let another transfer their money
         // as such, sender (initiator) is the allowed party, and owner is the true token owner
+        // (burning instead if the recipient is a burn sink), drawing on a stream allowance if any
+        // covers it, or else on the regular allowance
         ExecuteMsg::TransferFrom {
             owner,
             recipient,
             amount
-        } => execute_transfer_from(deps, env, info, owner, recipient, amount),
+        } => {
+            let owner_addr = deps.api.addr_validate(&owner)?;
+            if draws_on_stream(deps.as_ref(), &env.block, &owner_addr, &info.sender, amount)? {
+                execute_stream_transfer(deps, env, info, owner_addr, recipient, amount)
+            } else {
+                execute_transfer_from_or_burn(deps, env, info, owner, recipient, amount)
+            }
+        }
 
         // send from action - similar to transfer from but with send
         ExecuteMsg::SendFrom {
@@ -108,48 +313,385 @@ pub fn execute(
             contract,
             amount,
             msg 
-        } => execute_send_from(deps, env, info, owner, contract, amount, msg),
+        } => Ok(execute_send_from(deps, env, info, owner, contract, amount, msg)?),
+
+        // send from batch action - send from to several contracts, with a single allowance deduction
+        ExecuteMsg::SendFromBatch {
+            owner,
+            targets
+        } => execute_send_from_batch(deps, env, info, owner, targets),
 
         // burn from action - similar to transfer from but with burn
         ExecuteMsg::BurnFrom { 
             owner, 
             amount 
-        } => execute_burn_from(deps, env, info, owner, amount),
+        } => {
+            add_burned(deps.storage, amount)?;
+            Ok(execute_burn_from(deps, env, info, owner, amount)?)
+        }
 
         // mint action - the recipient is one to get the award with amount
         ExecuteMsg::Mint { 
             recipient, 
             amount 
-        } => execute_mint(deps, env, info, recipient, amount),
+        } => {
+            let info = minting_info(deps.storage, info)?;
+            Ok(execute_mint(deps, env, info, recipient, amount)?)
+        }
 
-        // update minter (probably to update the forefront minter on the block)
-        ExecuteMsg::UpdateMinter {
-            new_minter
-        } => execute_update_minter(deps, env, info, new_minter),
+        // update minter - only through the timelock (see the guardian module)
+        ExecuteMsg::UpdateMinter { .. } => Err(ContractError::Timelocked {}),
 
         // marketing stuffs (not important)
         ExecuteMsg::UpdateMarketing {
             project,
             description,
             marketing
-        } => execute_update_marketing(deps, env, info, project, description, marketing),
+        } => Ok(execute_update_marketing(deps, env, info, project, description, marketing)?),
+
+        ExecuteMsg::UploadLogo(logo) => execute_upload_logo_checked(deps, env, info, logo),
+
+        // logo module - metadata sets the logo policy
+        ExecuteMsg::SetLogoPolicy {
+            policy
+        } => execute_set_logo_policy(deps, info, policy),
+
+        // roles - admin grants and revokes roles
+        ExecuteMsg::GrantRole {
+            role,
+            address
+        } => execute_set_role(deps, env, info, role, address, true),
+
+        ExecuteMsg::RevokeRole {
+            role,
+            address
+        } => execute_set_role(deps, env, info, role, address, false),
+
+        // limits module - compliance sets the default daily outbound transfer limit
+        ExecuteMsg::SetDefaultDailyLimit {
+            limit
+        } => execute_set_default_daily_limit(deps, info, limit),
+
+        // limits module - compliance sets a specific address' daily outbound transfer limit
+        ExecuteMsg::SetDailyLimit {
+            address,
+            limit
+        } => execute_set_daily_limit(deps, info, address, limit),
+
+        // subscriptions module - the sender authorizes a payee to pull payments
+        ExecuteMsg::Subscribe {
+            payee,
+            amount,
+            interval,
+            end
+        } => execute_subscribe(deps, env, info, payee, amount, interval, end),
+
+        // subscriptions module - the payee pulls what is due
+        ExecuteMsg::Collect {
+            subscription_id
+        } => execute_collect(deps, env, info, subscription_id),
+
+        // subscriptions module - either side cancels
+        ExecuteMsg::CancelSubscription {
+            subscription_id
+        } => execute_cancel_subscription(deps, info, subscription_id),
+
+        // names module - metadata sets the name service
+        ExecuteMsg::SetNameService {
+            contract
+        } => execute_set_name_service(deps, info, contract),
+
+        // names module - transfer to a name resolved by the name service
+        ExecuteMsg::TransferToName {
+            recipient_name,
+            amount
+        } => execute_transfer_to_name(deps, env, info, recipient_name, amount),
+
+        // locks module - the sender locks part of their balance as collateral
+        ExecuteMsg::Lock {
+            amount,
+            unlocker,
+            until
+        } => execute_lock(deps, env, info, amount, unlocker, until),
+
+        // locks module - the unlocker releases a lock, or anyone clears an expired one
+        ExecuteMsg::Unlock {
+            lock_id
+        } => execute_unlock(deps, env, info, lock_id),
+
+        // buyback module - admin sets the DEX router
+        ExecuteMsg::SetBuybackRouter {
+            router
+        } => execute_set_buyback_router(deps, info, router),
+
+        // buyback module - swap the funds sent for the token, and burn it in the reply
+        ExecuteMsg::BuybackAndBurn {
+            min_receive
+        } => execute_buyback(deps, env, info, min_receive),
+
+        // vesting module - the minter mints a vesting grant
+        ExecuteMsg::MintVested {
+            recipient,
+            amount,
+            schedule
+        } => execute_mint_vested(deps, env, info, recipient, amount, schedule),
+
+        // vesting module - the recipient claims what has vested
+        ExecuteMsg::ClaimVested {} => execute_claim_vested(deps, env, info),
+
+        // pool module - the admin sets the pools, the minter mints into them
+        ExecuteMsg::SetLiquidityPool {
+            pool_contract,
+            lp_token
+        } => execute_set_liquidity_pool(deps, info, pool_contract, lp_token),
+
+        ExecuteMsg::MintToPool {
+            pool_contract,
+            amount,
+            min_lp_out
+        } => execute_mint_to_pool(deps, env, info, pool_contract, amount, min_lp_out),
+
+        // sessions module - the owner registers and revokes session keys
+        ExecuteMsg::RegisterSession {
+            pubkey,
+            max_amount,
+            expires
+        } => execute_register_session(deps, env, info, pubkey, max_amount, expires),
+
+        ExecuteMsg::RevokeSession {
+            session_id
+        } => execute_revoke_session(deps, info, session_id),
+
+        // sessions module - anyone submits a transfer signed by a session key
+        ExecuteMsg::SessionTransfer {
+            owner,
+            session_id,
+            recipient,
+            amount,
+            signature
+        } => execute_session_transfer(deps, env, owner, session_id, recipient, amount, signature),
+
+        // guardian module - the guardian or pauser pauses, the pauser unpauses
+        ExecuteMsg::Pause {} => execute_set_paused(deps, info, true),
+
+        ExecuteMsg::Unpause {} => execute_set_paused(deps, info, false),
+
+        // guardian module - the admin proposes and executes timelocked actions
+        ExecuteMsg::ProposeAction {
+            action
+        } => execute_propose_action(deps, env, info, action),
+
+        ExecuteMsg::ExecuteAction {
+            action_id
+        } => execute_action(deps, env, info, action_id),
+
+        // guardian module - the admin or the guardian cancels a pending action
+        ExecuteMsg::CancelAction {
+            action_id
+        } => execute_cancel_action(deps, info, action_id),
+
+        // treasury module - approvers propose and approve spends of the contract's funds
+        ExecuteMsg::ProposeSpend {
+            to,
+            amount,
+            memo
+        } => execute_propose_spend(deps, env, info, to, amount, memo),
+
+        ExecuteMsg::ApproveSpend {
+            id
+        } => execute_approve_spend(deps, env, info, id),
+
+        // sinks module - the admin sets the burn sinks
+        ExecuteMsg::SetBurnSink {
+            address,
+            sink
+        } => execute_set_burn_sink(deps, info, address, sink),
+
+        // scheduled module - the sender escrows a transfer, delivered by anyone once due
+        ExecuteMsg::ScheduleTransfer {
+            recipient,
+            amount,
+            execute_after
+        } => execute_schedule_transfer(deps, env, info, recipient, amount, execute_after),
+
+        ExecuteMsg::ExecuteScheduled {
+            id
+        } => execute_scheduled(deps, env, id),
+
+        // scheduled module - the sender cancels before it is due
+        ExecuteMsg::CancelScheduled {
+            id
+        } => execute_cancel_scheduled(deps, env, info, id),
+
+        // converter module - native coins deposited for tokens, at the rate of their denom
+        ExecuteMsg::SetDepositRate {
+            denom,
+            rate
+        } => execute_set_deposit_rate(deps, info, denom, rate),
+        ExecuteMsg::Deposit {} => execute_deposit(deps, info),
+        ExecuteMsg::Redeem {
+            denom: Some(denom),
+            amount
+        } => execute_redeem(deps, env, info, denom, amount),
+
+        // basket module - tokens redeemed for their share of the assets held by the contract
+        ExecuteMsg::Redeem {
+            denom: None,
+            amount
+        } => execute_redeem_basket(deps, env, info, amount),
+        ExecuteMsg::SetBasketAsset {
+            asset,
+            listed
+        } => execute_set_basket_asset(deps, info, asset, listed),
+
+        // permits module - the key signing the sender's query permits
+        ExecuteMsg::SetPermitKey {
+            pubkey
+        } => execute_set_permit_key(deps, info, pubkey),
+
+        // siblings module - conversions into sibling tokens, at their rate
+        ExecuteMsg::SetSibling {
+            token,
+            rate
+        } => execute_set_sibling(deps, info, token, rate),
+        ExecuteMsg::Convert {
+            to_token,
+            amount,
+            min_out
+        } => execute_convert(deps, env, info, to_token, amount, min_out),
+
+        // corridors module - compliance restricts transfers to whitelisted corridors
+        ExecuteMsg::SetCorridorMode {
+            enabled
+        } => execute_set_corridor_mode(deps, info, enabled),
+        ExecuteMsg::SetGroupMember {
+            group,
+            address,
+            member
+        } => execute_set_group_member(deps, info, group, address, member),
+        ExecuteMsg::SetCorridor {
+            corridor,
+            allowed
+        } => execute_set_corridor(deps, info, corridor, allowed),
+
+        // labels module - the sender labels their account
+        ExecuteMsg::SetLabel {
+            label
+        } => execute_set_label(deps, info, label),
+
+        // faucet module - anyone mints to themselves, in test deployments only
+        ExecuteMsg::FaucetMint {
+            amount
+        } => execute_faucet_mint(deps, env, info, amount),
+
+        // renounce module - admin only, schedules or cancels the removal of every privilege
+        ExecuteMsg::RenounceAdmin {
+            delay
+        } => execute_renounce_admin(deps, env, info, delay),
+
+        ExecuteMsg::CancelRenounce {} => execute_cancel_renounce(deps, info),
 
-        ExecuteMsg::UploadLogo(logo) => execute_upload_logo(deps, env, info, logo),
+        // streams module - allowances accruing every second, which transfer from draws on
+        ExecuteMsg::CreateStreamAllowance {
+            spender,
+            rate_per_second,
+            end
+        } => execute_create_stream_allowance(deps, env, info, spender, rate_per_second, end),
+
+        ExecuteMsg::CancelStreamAllowance {
+            spender
+        } => execute_cancel_stream_allowance(deps, info, spender),
+
+        // recipients module - admin only, guards plain transfers against contract recipients
+        ExecuteMsg::SetContractRecipientCheck {
+            enabled
+        } => execute_set_contract_recipient_check(deps, info, enabled),
+
+        // flash module - admin only, sets the flash mint fee
+        ExecuteMsg::SetFlashMintFee {
+            fee_bps
+        } => execute_set_flash_mint_fee(deps, info, fee_bps),
+
+        // flash module - mint to the callback contract, checking in the reply it burnt it back
+        ExecuteMsg::FlashMint {
+            amount,
+            callback_contract,
+            msg
+        } => execute_flash_mint(deps, info, amount, callback_contract, msg),
+
+        // transfers module - admin only, sets the policy on zero-amount and self-transfers
+        ExecuteMsg::SetTransferPolicy {
+            policy
+        } => execute_set_transfer_policy(deps, info, policy),
+
+        // dependents module - admin only, sets the contracts notified of migrations
+        ExecuteMsg::SetDependent {
+            contract,
+            registered
+        } => execute_set_dependent(deps, info, contract, registered),
+
+        // ledger module - admin only, sets how long balance changes are recorded for
+        ExecuteMsg::SetLedgerRetention {
+            blocks
+        } => execute_set_ledger_retention(deps, info, blocks),
     }
 }
 
 
-/// Query - calling cw20_base functions.
+/// Reply - handling the results of the submessages sent by the token's modules.
 /// # Arguments
 /// * `deps` - mutable dependency which has the storage (state) of the chain
-/// * `_env` - environment variables which include block information
+/// * `env`  - environment variables which include block information
+/// * `msg`  - the reply, with the id of the submessage
+/// # Returns
+/// * the response on Ok
+/// * the error type on Err
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(mut deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+    // replies only move this contract's own tokens, e.g. burning what a buyback bought - but for
+    // a failed conversion, restored to the account converting, and a failed send, reverted
+    let accounts = match msg.id {
+        CONVERT_REPLY_ID => pending_converter(deps.as_ref())?.into_iter().collect(),
+        SEND_REPLY_ID => pending_send_accounts(deps.as_ref())?,
+        _ => vec![],
+    };
+    let snapshot = holders_snapshot(deps.storage, accounts.clone())?;
+    let touched: Vec<Addr> =
+        std::iter::once(env.contract.address.clone()).chain(accounts).collect();
+    let balances = ledger_snapshot(deps.storage, &touched)?;
+    let res = match msg.id {
+        // buyback module - the router swap succeeded, burn what it bought
+        BUYBACK_REPLY_ID => reply_buyback(deps.branch(), env.clone()),
+        // pool module - the pool deposit succeeded, check the LP tokens it minted
+        POOL_REPLY_ID => reply_mint_to_pool(deps.branch()),
+        // flash module - the callback returned, check it burnt the flash mint back
+        FLASH_MINT_REPLY_ID => reply_flash_mint(deps.branch()),
+        // dependents module - a dependent was notified of the migration, acknowledging it or not
+        NOTIFY_REPLY_ID => reply_notify(deps.branch(), msg.result),
+        // siblings module - the sibling minted the conversion, or the burnt tokens are restored
+        CONVERT_REPLY_ID => reply_convert(deps.branch(), msg.result),
+        // sends module - the receive hook returned, or the send is reverted
+        SEND_REPLY_ID => reply_send(deps.branch(), msg.result),
+        id => Err(StdError::generic_err(format!("Unknown reply id: {}", id)).into()),
+    }?;
+    update_holder_count(deps.storage, snapshot)?;
+    record_ledger(deps.storage, &env.block, balances)?;
+    record_supply(deps.storage, &env.block)?;
+    Ok(res)
+}
+
+
+/// Query - calling cw20_base functions, along with the token's own modules.
+/// # Arguments
+/// * `deps` - mutable dependency which has the storage (state) of the chain
+/// * `env`  - environment variables which include block information
 /// * `msg`  - the execute message
 /// # Returns
 /// Serialized binary representing the portable queried response
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(
     deps: Deps,
-    _env: Env,
+    env : Env,
     msg : QueryMsg
 ) -> StdResult<Binary> {
     match msg {
@@ -165,7 +707,7 @@ pub fn query(
 
         // querying the forefront minter (probably)
         QueryMsg::Minter {
-        } => to_binary(&query_minter(deps)?),
+        } => to_binary(&query_minter(deps, env)?),
 
         // querying a spender's allowance with a particular owner
         QueryMsg::Allowance {
@@ -195,25 +737,309 @@ pub fn query(
 
         // querying marketing information (not important)
         QueryMsg::MarketingInfo {
-        } => to_binary(&query_marketing_info(deps)?),
+        } => to_binary(&query_marketing_info(deps, env)?),
 
         QueryMsg::DownloadLogo {
         } => to_binary(&query_download_logo(deps)?),
+
+        // logo module - the policy uploaded logos must comply with
+        QueryMsg::LogoPolicy {
+        } => to_binary(&query_logo_policy(deps)?),
+
+        // whether an address holds a role
+        QueryMsg::HasRole {
+            role,
+            address
+        } => to_binary(&query_has_role(deps, env, role, address)?),
+
+        // token info, minter, total burnt and holder count all at once
+        QueryMsg::TokenInfoExtended {
+        } => to_binary(&query_token_info_extended(deps, env)?),
+
+        // interfaces module - what this deployment supports, for generic tooling
+        QueryMsg::SupportedInterfaces {
+        } => to_binary(&query_supported_interfaces(deps)?),
+
+        // limits module - the daily limit of an address and what remains of it today
+        QueryMsg::RemainingLimit {
+            address
+        } => to_binary(&query_remaining_limit(deps, env, address)?),
+
+        // names module - what a recipient name resolves to
+        QueryMsg::ResolveName {
+            name
+        } => to_binary(&query_resolve_name(deps, name)?),
+
+        // subscriptions module - a single subscription, or those of either side
+        QueryMsg::Subscription {
+            subscription_id
+        } => to_binary(&query_subscription(deps, env, subscription_id)?),
+
+        QueryMsg::SubscriptionsByOwner {
+            owner,
+            start_after,
+            limit
+        } => to_binary(&query_subscriptions(deps, env, owner, false, start_after, limit)?),
+
+        QueryMsg::SubscriptionsByPayee {
+            payee,
+            start_after,
+            limit
+        } => to_binary(&query_subscriptions(deps, env, payee, true, start_after, limit)?),
+
+        // vesting module - the vesting grants of an address
+        QueryMsg::Vesting {
+            address
+        } => to_binary(&query_vesting(deps, env, address)?),
+
+        // locks module - the locked and free balance of an address, and the locks of an owner
+        QueryMsg::LockedBalance {
+            address
+        } => to_binary(&query_locked_balance(deps, env, address)?),
+
+        QueryMsg::Locks {
+            owner,
+            start_after,
+            limit
+        } => to_binary(&query_locks(deps, env, owner, start_after, limit)?),
+
+        // receipts module - the deposit receipts of an address
+        QueryMsg::Receipt {
+            address
+        } => to_binary(&query_receipt(deps, address)?),
+
+        // sessions module - the sessions of an owner
+        QueryMsg::Sessions {
+            owner,
+            start_after,
+            limit
+        } => to_binary(&query_sessions(deps, owner, start_after, limit)?),
+
+        // guardian module - the guardian and pause status, and the pending timelocked actions
+        QueryMsg::Guardian {
+        } => to_binary(&query_guardian(deps, env)?),
+
+        QueryMsg::PendingActions {
+            start_after,
+            limit
+        } => to_binary(&query_pending_actions(deps, env, start_after, limit)?),
+
+        // treasury module - the spends waiting for approvals
+        QueryMsg::PendingSpends {
+            start_after,
+            limit
+        } => to_binary(&query_pending_spends(deps, start_after, limit)?),
+
+        // scheduled module - the transfers scheduled by a sender
+        QueryMsg::ScheduledTransfers {
+            sender,
+            start_after,
+            limit
+        } => to_binary(&query_scheduled_transfers(deps, env, sender, start_after, limit)?),
+
+        // converter module - the reserves backing the token
+        QueryMsg::Reserves {} => to_binary(&query_reserves(deps)?),
+
+        // streams module - the allowances streamed by an owner, with what accrued up to now
+        QueryMsg::StreamAllowances {
+            owner,
+            start_after,
+            limit
+        } => to_binary(&query_stream_allowances(deps, env, owner, start_after, limit)?),
+
+        // history module - the daily total supply checkpoints
+        QueryMsg::SupplyHistory {
+            start_after,
+            limit
+        } => to_binary(&query_supply_history(deps, start_after, limit)?),
+
+        // flash module - the flash mint fee
+        QueryMsg::FlashMintFee {} => to_binary(&query_flash_mint_fee(deps)?),
+
+        // transfers module - the policy on zero-amount and self-transfers
+        QueryMsg::TransferPolicy {} => to_binary(&query_transfer_policy(deps)?),
+
+        // dependents module - the contracts notified of migrations, and whether they acknowledged
+        QueryMsg::Dependents {
+            start_after,
+            limit
+        } => to_binary(&query_dependents(deps, start_after, limit)?),
+
+        // ledger module - the balance changes within a block range
+        QueryMsg::LedgerDelta {
+            from_height,
+            to_height,
+            start_after,
+            limit
+        } => to_binary(&query_ledger_delta(deps, from_height, to_height, start_after, limit)?),
+
+        // validation module - an address or an amount validated as the contract does
+        QueryMsg::ValidateAddress {
+            address
+        } => to_binary(&query_validate_address(deps, address)?),
+        QueryMsg::ValidateAmount {
+            amount_str
+        } => to_binary(&query_validate_amount(deps, amount_str)?),
+
+        // basket module - what each token is redeemed for
+        QueryMsg::RedemptionRate {} => to_binary(&query_redemption_rate(deps, env)?),
+
+        // permits module - private data of the account which signed the permit
+        QueryMsg::WithPermit {
+            permit,
+            query
+        } => query_with_permit(deps, env, permit, query),
+
+        // siblings module - the sibling tokens and their rates
+        QueryMsg::Siblings {
+            start_after,
+            limit
+        } => to_binary(&query_siblings(deps, start_after, limit)?),
+
+        // corridors module - the corridors and groups transfers are restricted to
+        QueryMsg::Corridor {
+            from,
+            to
+        } => to_binary(&query_corridor(deps, from, to)?),
+        QueryMsg::Groups {
+            address
+        } => to_binary(&query_groups(deps, address)?),
+        QueryMsg::GroupMembers {
+            group,
+            start_after,
+            limit
+        } => to_binary(&query_group_members(deps, group, start_after, limit)?),
+
+        // labels module - balances along with the labels of the accounts
+        QueryMsg::BalanceExtended {
+            address
+        } => to_binary(&query_balance_extended(deps, address)?),
+        QueryMsg::AllAccountsExtended {
+            start_after,
+            limit
+        } => to_binary(&query_all_accounts_extended(deps, start_after, limit)?),
+
+        // display module - conversions between raw units and decimal strings
+        QueryMsg::FormatAmount {
+            amount
+        } => to_binary(&query_format_amount(deps, amount)?),
+        QueryMsg::ParseAmount {
+            display
+        } => to_binary(&query_parse_amount(deps, display)?),
+
+        // purposes module - allowances along with what they are for
+        QueryMsg::AllowanceExtended {
+            owner,
+            spender
+        } => to_binary(&query_allowance_extended(deps, owner, spender)?),
+        QueryMsg::AllAllowancesExtended {
+            owner,
+            start_after,
+            limit
+        } => to_binary(&query_all_allowances_extended(deps, owner, start_after, limit)?),
+
+        // renounce module - the admin and its scheduled renouncement
+        QueryMsg::AdminStatus {} => to_binary(&query_admin_status(deps, env)?),
+
+        // minters module - the revoked minters
+        QueryMsg::RevokedMinters {
+            start_after,
+            limit
+        } => to_binary(&query_revoked_minters(deps, start_after, limit)?),
+
+        // sinks module - the addresses transfers to are burnt
+        QueryMsg::BurnSinks {
+            start_after,
+            limit
+        } => to_binary(&query_burn_sinks(deps, start_after, limit)?),
+
+        // invariants module - the proof harness, in debug and testnet builds only
+        #[cfg(any(debug_assertions, feature = "invariants"))]
+        QueryMsg::CheckInvariants {
+            cursor,
+            limit
+        } => to_binary(&query_check_invariants(deps, cursor, limit)?),
+
+        // snapshot module - the raw balances export, in debug and snapshot builds only
+        #[cfg(any(debug_assertions, feature = "snapshot"))]
+        QueryMsg::RawBalanceRange {
+            cursor,
+            limit
+        } => to_binary(&query_raw_balance_range(deps, cursor, limit)?),
     }
 }
 
 
+/// Querying the extended token info, so that integrators need a single query instead of several.
+/// # Arguments
+/// * `deps` - dependency which has the storage (state) of the chain
+/// * `env`  - environment variables which include block information
+/// # Returns
+///   The extended token info
+fn query_token_info_extended(deps: Deps, env: Env) -> StdResult<TokenInfoExtendedResponse> {
+    // a due renouncement removed the minter and unpaused the token, even if not yet settled
+    let renounced = is_due(deps, &env.block)?;
+    Ok(TokenInfoExtendedResponse {
+        token_info   : query_token_info(deps)?,
+        minter       : query_minter(deps, env)?,
+        total_burned : TOTAL_BURNED.may_load(deps.storage)?.unwrap_or_default(),
+        holder_count : HOLDER_COUNT.load(deps.storage)?,
+        jurisdiction : JURISDICTION.may_load(deps.storage)?,
+        paused       : !renounced && PAUSED.may_load(deps.storage)?.unwrap_or_default(),
+    })
+}
+
+
 /// Migrate - contract migration; allows contract to have its ID changed (internal logic of contract
 /// without having to create a new one. CosmWasm, unlike Ethereum - most contracts implement the same
 /// standard (i.e. Cw20) so no need to upload the whole thing (same standard - same core logic).
+/// The registered dependents are notified of the new version, and of the changes listed.
 /// # Arguments
 /// * `deps` - mutable dependency which has the storage (state) of the chain
-/// * `_env` - environment variables which include block information
-/// * `_msg` - the execute message
+/// * `env`  - environment variables which include block information
+/// * `msg`  - the migrate message
 /// # Returns
 /// * the execute response on Ok
 /// * the error type on Err
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
-    cw20_base::contract::migrate(deps, _env, _msg)
+pub fn migrate(mut deps: DepsMut, env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    // holders were not counted before, so count them once
+    if HOLDER_COUNT.may_load(deps.storage)?.is_none() {
+        let holders = BALANCES
+            .range(deps.storage, None, None, Order::Ascending)
+            .filter(|item| matches!(item, Ok((_, balance)) if !balance.is_zero()))
+            .count() as u64;
+        HOLDER_COUNT.save(deps.storage, &holders)?;
+    }
+    // there was no admin before either, so the contract admin (who is migrating) becomes the
+    // token's admin - unless the privileges were renounced, which removed the admin for good
+    if !ADMIN.exists(deps.storage) && !RENOUNCE_AT.exists(deps.storage) {
+        let admin = deps.querier
+            .query_wasm_contract_info(&env.contract.address)?
+            .admin
+            .ok_or_else(|| StdError::generic_err("Contract has no admin"))?;
+        ADMIN.save(deps.storage, &deps.api.addr_validate(&admin)?)?;
+    }
+    // roles did not exist before, so the admin, who used to manage everything, gets every role
+    let no_roles = ROLES.keys_raw(deps.storage, None, None, Order::Ascending).next().is_none();
+    if let (true, Some(admin)) = (no_roles, ADMIN.may_load(deps.storage)?) {
+        grant_all(deps.storage, &admin)?;
+    }
+    // escrowed tokens were not tracked before, so add up what the vesting grants still hold once
+    if ESCROWED.may_load(deps.storage)?.is_none() {
+        let escrowed = VESTING_GRANTS
+            .range(deps.storage, None, None, Order::Ascending)
+            .try_fold(Uint128::zero(), |total, item| -> StdResult<_> {
+                let (_, grant) = item?;
+                Ok(total + grant.amount - grant.claimed)
+            })?;
+        ESCROWED.save(deps.storage, &escrowed)?;
+    }
+    // modules may have been added or removed since, so declare the interfaces again
+    declare_interfaces(deps.storage)?;
+    let res = cw20_base::contract::migrate(deps.branch(), env, cw20_base::msg::MigrateMsg {})?;
+    let notifications = notify_dependents(deps.storage, CONTRACT_VERSION, msg.changes)?;
+    Ok(res
+        .add_attribute("dependents_notified", notifications.len().to_string())
+        .add_submessages(notifications))
 }
@@ -0,0 +1,189 @@
+/*
+Converter module - the token wraps native coins. Any of several backing denoms can be deposited,
+each minting tokens at its own exchange rate, maintained by the holders of the rates role (the
+admin, or a rate oracle). The coins deposited are kept as reserves per denom, from which tokens are
+redeemed at the current rate of the denom asked for.
+*/
+
+use cosmwasm_std::{
+    Addr, BankMsg, Coin, Decimal, Deps, DepsMut, Env, MessageInfo, Order, Response, StdError,
+    StdResult, Storage, Uint128, coin
+};
+use cw_storage_plus::Map;
+use cw20_base::contract::execute_burn;
+use cw20_base::state::{BALANCES, TOKEN_INFO};
+
+use crate::error::ContractError;
+use crate::msg::{ReserveResponse, ReservesResponse};
+use crate::roles::{assert_role, Role};
+use crate::state::add_burned;
+
+/// Tokens minted per unit of each backing denom
+pub const DEPOSIT_RATES: Map<&str, Decimal> = Map::new("deposit_rates");
+
+/// Coins deposited and not redeemed yet, per denom
+pub const RESERVES: Map<&str, Uint128> = Map::new("reserves");
+
+
+/// Set the exchange rate of a backing denom - holders of the rates role only.
+/// # Arguments
+/// * `deps`  - mutable dependency which has the storage (state) of the chain
+/// * `info`  - message info, the sender must hold the rates role
+/// * `denom` - the backing denom
+/// * `rate`  - tokens minted per unit of the denom, None to stop accepting it
+/// # Returns
+/// * the execute response on Ok
+/// * the error type on Err
+pub fn execute_set_deposit_rate(
+    deps  : DepsMut,
+    info  : MessageInfo,
+    denom : String,
+    rate  : Option<Decimal>,
+) -> Result<Response, ContractError> {
+    assert_role(deps.storage, Role::Rates, &info.sender)?;
+    let res = Response::new()
+        .add_attribute("action", "set_deposit_rate")
+        .add_attribute("denom", &denom);
+    match rate {
+        Some(rate) if rate.is_zero() => Err(ContractError::InvalidRate {}),
+        Some(rate) => {
+            DEPOSIT_RATES.save(deps.storage, &denom, &rate)?;
+            Ok(res.add_attribute("rate", rate.to_string()))
+        }
+        None => {
+            DEPOSIT_RATES.remove(deps.storage, &denom);
+            Ok(res.add_attribute("rate", "none"))
+        }
+    }
+}
+
+
+/// Deposit the native coins sent, minting tokens to the sender at the rate of each denom.
+/// # Arguments
+/// * `deps` - mutable dependency which has the storage (state) of the chain
+/// * `info` - message info, with the coins deposited
+/// # Returns
+/// * the execute response on Ok
+/// * the error type on Err
+pub fn execute_deposit(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let coins: Vec<Coin> = info.funds.into_iter().filter(|c| !c.amount.is_zero()).collect();
+    if coins.is_empty() {
+        return Err(ContractError::NoFunds {});
+    }
+
+    // every denom must be accepted before anything is minted
+    let mut minted = Uint128::zero();
+    for coin in &coins {
+        let rate = DEPOSIT_RATES
+            .may_load(deps.storage, &coin.denom)?
+            .ok_or_else(|| ContractError::UnsupportedDenom { denom: coin.denom.clone() })?;
+        let amount = coin.amount.checked_multiply_ratio(rate.atomics(), Decimal::one().atomics())
+            .map_err(|_| ContractError::InvalidRate {})?;
+        minted = minted.checked_add(amount).map_err(StdError::from)?;
+    }
+    if minted.is_zero() {
+        return Err(ContractError::NoFunds {});
+    }
+
+    for coin in &coins {
+        RESERVES.update(deps.storage, &coin.denom, |reserve| -> StdResult<_> {
+            Ok(reserve.unwrap_or_default().checked_add(coin.amount)?)
+        })?;
+    }
+    mint_backed(deps.storage, &info.sender, minted)?;
+
+    let deposited: Vec<String> = coins.iter().map(Coin::to_string).collect();
+    Ok(Response::new()
+        .add_attribute("action", "deposit")
+        .add_attribute("to", info.sender)
+        .add_attribute("deposited", deposited.join(","))
+        .add_attribute("amount", minted))
+}
+
+
+/// Mint tokens backed by deposited coins, within the minter's cap if there is one.
+fn mint_backed(
+    storage   : &mut dyn Storage,
+    recipient : &Addr,
+    amount    : Uint128,
+) -> Result<(), ContractError> {
+    let mut token_info = TOKEN_INFO.load(storage)?;
+    token_info.total_supply = token_info.total_supply.checked_add(amount).map_err(StdError::from)?;
+    if let Some(cap) = token_info.get_cap() {
+        if token_info.total_supply > cap {
+            return Err(cw20_base::ContractError::CannotExceedCap {}.into());
+        }
+    }
+    TOKEN_INFO.save(storage, &token_info)?;
+    BALANCES.update(storage, recipient, |balance| -> StdResult<_> {
+        Ok(balance.unwrap_or_default().checked_add(amount)?)
+    })?;
+    Ok(())
+}
+
+
+/// Redeem tokens for coins of a backing denom, at its current rate. The tokens are burnt.
+/// # Arguments
+/// * `deps`   - mutable dependency which has the storage (state) of the chain
+/// * `env`    - environment variables which include block information
+/// * `info`   - message info, the sender is redeeming
+/// * `denom`  - the backing denom to redeem for
+/// * `amount` - the amount of tokens redeemed
+/// # Returns
+/// * the execute response on Ok
+/// * the error type on Err
+pub fn execute_redeem(
+    deps   : DepsMut,
+    env    : Env,
+    info   : MessageInfo,
+    denom  : String,
+    amount : Uint128,
+) -> Result<Response, ContractError> {
+    let rate = DEPOSIT_RATES
+        .may_load(deps.storage, &denom)?
+        .ok_or_else(|| ContractError::UnsupportedDenom { denom: denom.clone() })?;
+    let out = amount.checked_multiply_ratio(Decimal::one().atomics(), rate.atomics())
+        .map_err(|_| ContractError::InvalidRate {})?;
+    if out.is_zero() {
+        return Err(ContractError::NoFunds {});
+    }
+    let reserve = RESERVES.may_load(deps.storage, &denom)?.unwrap_or_default();
+    if out > reserve {
+        return Err(ContractError::InsufficientReserve { available: reserve });
+    }
+
+    RESERVES.save(deps.storage, &denom, &(reserve - out))?;
+    add_burned(deps.storage, amount)?;
+    let to = info.sender.to_string();
+    let res = execute_burn(deps, env, info, amount)?;
+    Ok(res
+        .add_attribute("redeemed", coin(out.u128(), &denom).to_string())
+        .add_message(BankMsg::Send { to_address: to, amount: vec![coin(out.u128(), denom)] }))
+}
+
+
+/// Query the reserves backing the token, with the current rate of each denom.
+/// # Arguments
+/// * `deps` - dependency which has the storage (state) of the chain
+/// # Returns
+///   The reserve of every denom deposited or accepted
+pub fn query_reserves(deps: Deps) -> StdResult<ReservesResponse> {
+    let mut denoms = RESERVES
+        .keys(deps.storage, None, None, Order::Ascending)
+        .chain(DEPOSIT_RATES.keys(deps.storage, None, None, Order::Ascending))
+        .collect::<StdResult<Vec<_>>>()?;
+    denoms.sort();
+    denoms.dedup();
+
+    let reserves = denoms
+        .into_iter()
+        .map(|denom| {
+            Ok(ReserveResponse {
+                amount : RESERVES.may_load(deps.storage, &denom)?.unwrap_or_default(),
+                rate   : DEPOSIT_RATES.may_load(deps.storage, &denom)?,
+                denom,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+    Ok(ReservesResponse { reserves })
+}
@@ -0,0 +1,255 @@
+/*
+Corridors module - permissioned transfers, for security-token style deployments. Compliance turns
+the corridor mode on, in which outbound transfers (transfer, send, and their allowance-based
+variants) are only allowed along a whitelisted corridor: between two addresses, or between two
+groups of addresses, which compliance manages the members of. A group can have a corridor with
+itself, allowing transfers among its members. Corridors go both ways. Off by default, in which
+case tokens move freely.
+*/
+
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, Deps, DepsMut, Empty, MessageInfo, Order, Response, StdResult};
+use cw_storage_plus::{Bound, Item, Map};
+
+use crate::error::ContractError;
+use crate::msg::{CorridorResponse, GroupMembersResponse, GroupsResponse};
+use crate::roles::{assert_role, Role};
+
+// Settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+
+/// Maximum number of groups an address is a member of, all of them being checked on transfers
+pub const MAX_GROUPS: usize = 10;
+
+/// A whitelisted corridor, in either direction
+#[cw_serde]
+pub enum Corridor {
+    /// Between two addresses
+    Addresses(String, String),
+    /// Between the members of two groups, or among the members of one
+    Groups(String, String),
+}
+
+/// Whether transfers are restricted to the corridors, off unless set by compliance
+pub const CORRIDOR_MODE: Item<bool> = Item::new("corridor_mode");
+
+/// Members of each group, by group and address
+pub const GROUP_MEMBERS: Map<(&str, &Addr), Empty> = Map::new("group_members");
+
+/// Groups of each address, by address and group
+pub const MEMBER_GROUPS: Map<(&Addr, &str), Empty> = Map::new("member_groups");
+
+/// Corridors between addresses, the lower address first
+pub const ADDRESS_CORRIDORS: Map<(&Addr, &Addr), Empty> = Map::new("address_corridors");
+
+/// Corridors between groups, the lower group first
+pub const GROUP_CORRIDORS: Map<(&str, &str), Empty> = Map::new("group_corridors");
+
+
+/// Set whether transfers are restricted to the corridors - compliance role only.
+/// # Arguments
+/// * `deps`    - mutable dependency which has the storage (state) of the chain
+/// * `info`    - message info, the sender must hold the compliance role
+/// * `enabled` - whether the corridor mode is on
+/// # Returns
+/// * the execute response on Ok
+/// * the error type on Err
+pub fn execute_set_corridor_mode(
+    deps    : DepsMut,
+    info    : MessageInfo,
+    enabled : bool,
+) -> Result<Response, ContractError> {
+    assert_role(deps.storage, Role::Compliance, &info.sender)?;
+    CORRIDOR_MODE.save(deps.storage, &enabled)?;
+    Ok(Response::new()
+        .add_attribute("action", "set_corridor_mode")
+        .add_attribute("enabled", enabled.to_string()))
+}
+
+
+/// Set whether an address is a member of a group - compliance role only.
+/// # Arguments
+/// * `deps`    - mutable dependency which has the storage (state) of the chain
+/// * `info`    - message info, the sender must hold the compliance role
+/// * `group`   - the group
+/// * `address` - the address
+/// * `member`  - whether the address is a member of the group
+/// # Returns
+/// * the execute response on Ok
+/// * the error type on Err
+pub fn execute_set_group_member(
+    deps    : DepsMut,
+    info    : MessageInfo,
+    group   : String,
+    address : String,
+    member  : bool,
+) -> Result<Response, ContractError> {
+    assert_role(deps.storage, Role::Compliance, &info.sender)?;
+    let addr = deps.api.addr_validate(&address)?;
+    if !member {
+        GROUP_MEMBERS.remove(deps.storage, (&group, &addr));
+        MEMBER_GROUPS.remove(deps.storage, (&addr, &group));
+    } else if !MEMBER_GROUPS.has(deps.storage, (&addr, &group)) {
+        let count = MEMBER_GROUPS
+            .prefix(&addr)
+            .keys_raw(deps.storage, None, None, Order::Ascending)
+            .count();
+        if count >= MAX_GROUPS {
+            return Err(ContractError::TooManyGroups { max: MAX_GROUPS });
+        }
+        GROUP_MEMBERS.save(deps.storage, (&group, &addr), &Empty {})?;
+        MEMBER_GROUPS.save(deps.storage, (&addr, &group), &Empty {})?;
+    }
+    Ok(Response::new()
+        .add_attribute("action", "set_group_member")
+        .add_attribute("group", group)
+        .add_attribute("address", addr)
+        .add_attribute("member", member.to_string()))
+}
+
+
+/// Set whether a corridor is whitelisted - compliance role only.
+/// # Arguments
+/// * `deps`     - mutable dependency which has the storage (state) of the chain
+/// * `info`     - message info, the sender must hold the compliance role
+/// * `corridor` - the corridor
+/// * `allowed`  - whether transfers are allowed along the corridor
+/// # Returns
+/// * the execute response on Ok
+/// * the error type on Err
+pub fn execute_set_corridor(
+    deps     : DepsMut,
+    info     : MessageInfo,
+    corridor : Corridor,
+    allowed  : bool,
+) -> Result<Response, ContractError> {
+    assert_role(deps.storage, Role::Compliance, &info.sender)?;
+    let (a, b) = match corridor {
+        Corridor::Addresses(a, b) => {
+            let (a, b) = (deps.api.addr_validate(&a)?, deps.api.addr_validate(&b)?);
+            let key = if a <= b { (&a, &b) } else { (&b, &a) };
+            match allowed {
+                true => ADDRESS_CORRIDORS.save(deps.storage, key, &Empty {})?,
+                false => ADDRESS_CORRIDORS.remove(deps.storage, key),
+            }
+            (a.to_string(), b.to_string())
+        }
+        Corridor::Groups(a, b) => {
+            let key = if a <= b { (a.as_str(), b.as_str()) } else { (b.as_str(), a.as_str()) };
+            match allowed {
+                true => GROUP_CORRIDORS.save(deps.storage, key, &Empty {})?,
+                false => GROUP_CORRIDORS.remove(deps.storage, key),
+            }
+            (a, b)
+        }
+    };
+    Ok(Response::new()
+        .add_attribute("action", "set_corridor")
+        .add_attribute("between", format!("{}/{}", a, b))
+        .add_attribute("allowed", allowed.to_string()))
+}
+
+
+/// Whether a transfer between two addresses is along a whitelisted corridor.
+fn in_corridor(deps: Deps, from: &Addr, to: &Addr) -> StdResult<bool> {
+    let key = if from <= to { (from, to) } else { (to, from) };
+    if ADDRESS_CORRIDORS.has(deps.storage, key) {
+        return Ok(true);
+    }
+    let groups = |addr: &Addr| {
+        MEMBER_GROUPS
+            .prefix(addr)
+            .keys(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<String>>>()
+    };
+    let (from_groups, to_groups) = (groups(from)?, groups(to)?);
+    for a in &from_groups {
+        for b in &to_groups {
+            let key = if a <= b { (a.as_str(), b.as_str()) } else { (b.as_str(), a.as_str()) };
+            if GROUP_CORRIDORS.has(deps.storage, key) {
+                return Ok(true);
+            }
+        }
+    }
+    Ok(false)
+}
+
+
+/// Ensure that a transfer is along a whitelisted corridor, when the corridor mode is on.
+/// # Arguments
+/// * `deps` - dependency which has the storage (state) of the chain
+/// * `from` - the owner of the tokens transferred
+/// * `to`   - the recipient of the transfer
+/// # Returns
+/// * the error type on Err, if the transfer is not along a corridor
+pub fn assert_corridor(deps: Deps, from: &Addr, to: &str) -> Result<(), ContractError> {
+    if !CORRIDOR_MODE.may_load(deps.storage)?.unwrap_or_default() {
+        return Ok(());
+    }
+    let to = deps.api.addr_validate(to)?;
+    if !in_corridor(deps, from, &to)? {
+        return Err(ContractError::NoCorridor { from: from.to_string(), to: to.into() });
+    }
+    Ok(())
+}
+
+
+/// Query whether transfers between two addresses are allowed.
+/// # Arguments
+/// * `deps` - dependency which has the storage (state) of the chain
+/// * `from` - the owner of the tokens
+/// * `to`   - the recipient
+/// # Returns
+///   Whether the corridor mode is on, and whether the addresses are along a corridor
+pub fn query_corridor(deps: Deps, from: String, to: String) -> StdResult<CorridorResponse> {
+    let (from, to) = (deps.api.addr_validate(&from)?, deps.api.addr_validate(&to)?);
+    Ok(CorridorResponse {
+        enabled : CORRIDOR_MODE.may_load(deps.storage)?.unwrap_or_default(),
+        allowed : in_corridor(deps, &from, &to)?,
+    })
+}
+
+
+/// Query the groups an address is a member of.
+/// # Arguments
+/// * `deps`    - dependency which has the storage (state) of the chain
+/// * `address` - the address
+/// # Returns
+///   The groups of the address
+pub fn query_groups(deps: Deps, address: String) -> StdResult<GroupsResponse> {
+    let addr = deps.api.addr_validate(&address)?;
+    let groups = MEMBER_GROUPS
+        .prefix(&addr)
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<_>>()?;
+    Ok(GroupsResponse { groups })
+}
+
+
+/// Query the members of a group.
+/// # Arguments
+/// * `deps`        - dependency which has the storage (state) of the chain
+/// * `group`       - the group
+/// * `start_after` - the address to start after
+/// * `limit`       - the list size limit
+/// # Returns
+///   The members of the group
+pub fn query_group_members(
+    deps        : Deps,
+    group       : String,
+    start_after : Option<String>,
+    limit       : Option<u32>,
+) -> StdResult<GroupMembersResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_addr = start_after.map(Addr::unchecked);
+    let start = start_addr.as_ref().map(Bound::exclusive);
+
+    let members = GROUP_MEMBERS
+        .prefix(&group)
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(String::from))
+        .collect::<StdResult<_>>()?;
+    Ok(GroupMembersResponse { members })
+}
@@ -0,0 +1,197 @@
+/*
+Dependents module - contracts built on top of the token, such as staking, vesting or bridges, which
+the admin registers to be notified whenever the token is migrated: each is sent the new version and
+the semantics that changed with it. Notifications are submessages replying always, so that a
+dependent failing to handle one does not block the migration, and whether each dependent
+acknowledged the last one is kept for the admin to follow up on.
+*/
+
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{
+    to_binary, Addr, Deps, DepsMut, MessageInfo, Order, Response, StdError, StdResult, Storage,
+    SubMsg, SubMsgResult, WasmMsg
+};
+use cw_storage_plus::{Bound, Item, Map};
+
+use crate::error::ContractError;
+use crate::msg::{DependentResponse, DependentsResponse};
+use crate::state::assert_admin;
+
+// Settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+
+/// Maximum number of dependents, all of them being notified in the migration
+pub const MAX_DEPENDENTS: usize = 30;
+
+/// Reply id of the migration notification submessages
+pub const NOTIFY_REPLY_ID: u64 = 4;
+
+/// Outcome of the last notification sent to a dependent
+#[cw_serde]
+pub enum NotifyStatus {
+    /// The dependent handled the notification
+    Acked,
+    /// The dependent failed to handle the notification, with the error
+    Failed { error: String },
+}
+
+/// A registered dependent
+#[cw_serde]
+#[derive(Default)]
+pub struct Dependent {
+    /// Version of the token the dependent was last notified of, None if it never was
+    pub version : Option<String>,
+    /// Outcome of that notification
+    pub status  : Option<NotifyStatus>,
+}
+
+/// The registered dependents
+pub const DEPENDENTS: Map<&Addr, Dependent> = Map::new("dependents");
+
+/// Dependents notified in the ongoing migration whose reply is still to come, in the order of the
+/// notifications, which is the order of their replies
+pub const PENDING_NOTIFICATIONS: Item<Vec<Addr>> = Item::new("pending_notifications");
+
+/// Execute message expected to be supported by the dependents.
+#[cw_serde]
+pub enum DependentExecuteMsg {
+    /// The token was migrated to `version`, with the semantics listed in `changes`
+    TokenMigrated {
+        version : String,
+        changes : Vec<String>,
+    },
+}
+
+
+/// Set whether a contract is a registered dependent - admin only.
+/// # Arguments
+/// * `deps`       - mutable dependency which has the storage (state) of the chain
+/// * `info`       - message info, the sender must be the admin
+/// * `contract`   - the dependent contract
+/// * `registered` - whether the contract is notified of migrations
+/// # Returns
+/// * the execute response on Ok
+/// * the error type on Err
+pub fn execute_set_dependent(
+    deps       : DepsMut,
+    info       : MessageInfo,
+    contract   : String,
+    registered : bool,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.storage, &info.sender)?;
+    let addr = deps.api.addr_validate(&contract)?;
+    if !registered {
+        DEPENDENTS.remove(deps.storage, &addr);
+    } else if !DEPENDENTS.has(deps.storage, &addr) {
+        let count = DEPENDENTS.keys_raw(deps.storage, None, None, Order::Ascending).count();
+        if count >= MAX_DEPENDENTS {
+            return Err(ContractError::TooManyDependents { max: MAX_DEPENDENTS });
+        }
+        DEPENDENTS.save(deps.storage, &addr, &Dependent::default())?;
+    }
+    Ok(Response::new()
+        .add_attribute("action", "set_dependent")
+        .add_attribute("contract", addr)
+        .add_attribute("registered", registered.to_string()))
+}
+
+
+/// Notify every dependent of the migration.
+/// # Arguments
+/// * `storage` - the contract storage
+/// * `version` - the version migrated to
+/// * `changes` - the semantics that changed with the version
+/// # Returns
+/// * the notification submessages on Ok
+/// * the standard error type on Err
+pub fn notify_dependents(
+    storage : &mut dyn Storage,
+    version : &str,
+    changes : Vec<String>,
+) -> StdResult<Vec<SubMsg>> {
+    let dependents = DEPENDENTS
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    let notification = to_binary(&DependentExecuteMsg::TokenMigrated {
+        version : version.to_string(),
+        changes,
+    })?;
+
+    let mut notifications = vec![];
+    for addr in &dependents {
+        let dependent = Dependent { version: Some(version.to_string()), status: None };
+        DEPENDENTS.save(storage, addr, &dependent)?;
+        let msg = WasmMsg::Execute {
+            contract_addr : addr.to_string(),
+            msg           : notification.clone(),
+            funds         : vec![],
+        };
+        notifications.push(SubMsg::reply_always(msg, NOTIFY_REPLY_ID));
+    }
+    PENDING_NOTIFICATIONS.save(storage, &dependents)?;
+    Ok(notifications)
+}
+
+
+/// Notification reply - record whether the dependent acknowledged the notification. A failure does
+/// not fail the migration.
+/// # Arguments
+/// * `deps`   - mutable dependency which has the storage (state) of the chain
+/// * `result` - the result of the notification
+/// # Returns
+/// * the response on Ok
+/// * the error type on Err
+pub fn reply_notify(deps: DepsMut, result: SubMsgResult) -> Result<Response, ContractError> {
+    let mut pending = PENDING_NOTIFICATIONS.load(deps.storage)?;
+    if pending.is_empty() {
+        return Err(StdError::generic_err("No pending notification").into());
+    }
+    let addr = pending.remove(0);
+    PENDING_NOTIFICATIONS.save(deps.storage, &pending)?;
+
+    let status = match result {
+        SubMsgResult::Ok(_) => NotifyStatus::Acked,
+        SubMsgResult::Err(error) => NotifyStatus::Failed { error },
+    };
+    let acked = status == NotifyStatus::Acked;
+    // the dependent may have been removed by the admin since, in which case it is not tracked
+    if let Some(dependent) = DEPENDENTS.may_load(deps.storage, &addr)? {
+        DEPENDENTS.save(deps.storage, &addr, &Dependent { status: Some(status), ..dependent })?;
+    }
+    Ok(Response::new()
+        .add_attribute("action", "dependent_notified")
+        .add_attribute("contract", addr)
+        .add_attribute("acked", acked.to_string()))
+}
+
+
+/// Query the registered dependents.
+/// # Arguments
+/// * `deps`        - dependency which has the storage (state) of the chain
+/// * `start_after` - the address to start after
+/// * `limit`       - the list size limit
+/// # Returns
+///   The list of dependents, with the outcome of their last notification
+pub fn query_dependents(
+    deps        : Deps,
+    start_after : Option<String>,
+    limit       : Option<u32>,
+) -> StdResult<DependentsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_addr = start_after.map(Addr::unchecked);
+    let start = start_addr.as_ref().map(Bound::exclusive);
+
+    let dependents = DEPENDENTS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            item.map(|(contract, dependent)| DependentResponse {
+                contract : contract.into(),
+                version  : dependent.version,
+                status   : dependent.status,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+    Ok(DependentsResponse { dependents })
+}
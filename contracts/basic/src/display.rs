@@ -0,0 +1,89 @@
+/*
+Display module - conversions between raw token units and the decimal strings shown to users,
+according to the token's decimals. Front-ends query the contract rather than converting on their
+own, so that they can never disagree with it on rounding: formatting is exact, and parsing rejects
+any amount more precise than the token's decimals instead of rounding it.
+*/
+
+use cosmwasm_std::{Deps, StdError, StdResult, Uint128};
+use cw20_base::state::TOKEN_INFO;
+
+use crate::msg::{FormatAmountResponse, ParseAmountResponse};
+
+
+/// Format an amount of raw units as a decimal string, without trailing zeros in its fraction.
+/// # Arguments
+/// * `amount`   - the amount, in raw units
+/// * `decimals` - the decimals of the token
+/// # Returns
+///   The decimal string, such as "1.5" for 1500000 units of a token with 6 decimals
+pub fn format_amount(amount: Uint128, decimals: u8) -> String {
+    let digits = amount.to_string();
+    let decimals = decimals as usize;
+    if decimals == 0 {
+        return digits;
+    }
+    let digits = format!("{:0>width$}", digits, width = decimals + 1);
+    let (whole, fraction) = digits.split_at(digits.len() - decimals);
+    match fraction.trim_end_matches('0') {
+        "" => whole.to_string(),
+        fraction => format!("{}.{}", whole, fraction),
+    }
+}
+
+
+/// Parse a decimal string into an amount of raw units.
+/// # Arguments
+/// * `display`  - the decimal string, digits with at most one decimal point
+/// * `decimals` - the decimals of the token
+/// # Returns
+/// * the amount, in raw units, on Ok
+/// * the error type on Err, if the string is malformed, has more fraction digits than the token's
+///   decimals, or overflows
+pub fn parse_amount(display: &str, decimals: u8) -> StdResult<Uint128> {
+    let invalid = || StdError::generic_err(format!("Invalid display amount: {}", display));
+    let (whole, fraction) = match display.split_once('.') {
+        Some((whole, fraction)) if !fraction.is_empty() => (whole, fraction),
+        Some(_) => return Err(invalid()),
+        None => (display, ""),
+    };
+    let is_digits = |part: &str| part.bytes().all(|byte| byte.is_ascii_digit());
+    if whole.is_empty() || !is_digits(whole) || !is_digits(fraction) {
+        return Err(invalid());
+    }
+    if fraction.len() > decimals as usize {
+        return Err(StdError::generic_err(format!(
+            "Display amount more precise than the token's {} decimals: {}", decimals, display
+        )));
+    }
+
+    // the fraction is padded to the token's decimals, making the whole string its raw units
+    let units = format!("{}{:0<width$}", whole, fraction, width = decimals as usize);
+    units.parse::<u128>()
+        .map(Uint128::new)
+        .map_err(|_| StdError::generic_err(format!("Display amount overflows: {}", display)))
+}
+
+
+/// Query the decimal string of an amount of raw units, according to the token's decimals.
+/// # Arguments
+/// * `deps`   - dependency which has the storage (state) of the chain
+/// * `amount` - the amount, in raw units
+/// # Returns
+///   The decimal string of the amount
+pub fn query_format_amount(deps: Deps, amount: Uint128) -> StdResult<FormatAmountResponse> {
+    let decimals = TOKEN_INFO.load(deps.storage)?.decimals;
+    Ok(FormatAmountResponse { display: format_amount(amount, decimals) })
+}
+
+
+/// Query the amount of raw units of a decimal string, according to the token's decimals.
+/// # Arguments
+/// * `deps`    - dependency which has the storage (state) of the chain
+/// * `display` - the decimal string
+/// # Returns
+///   The amount of raw units, or an error if the string is malformed or too precise
+pub fn query_parse_amount(deps: Deps, display: String) -> StdResult<ParseAmountResponse> {
+    let decimals = TOKEN_INFO.load(deps.storage)?.decimals;
+    Ok(ParseAmountResponse { amount: parse_amount(&display, decimals)? })
+}
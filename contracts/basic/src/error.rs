@@ -0,0 +1,264 @@
+/*
+Error types to handle failed eames token operations.
+*/
+
+use cosmwasm_std::{StdError, Timestamp, Uint128};
+use thiserror::Error;
+
+/// Eames token smart contract error type
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    /// Standard error
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    /// Error coming from the Cw20 base implementation
+    #[error("{0}")]
+    Base(#[from] cw20_base::ContractError),
+
+    /// Sender is not allowed to perform an admin-only operation
+    #[error("Unauthorized")]
+    Unauthorized {},
+
+    /// The outbound transfer exceeds what the sender can still transfer within the daily window
+    #[error("Daily transfer limit exceeded: limit {limit}, remaining {remaining}")]
+    DailyLimitExceeded { limit: Uint128, remaining: Uint128 },
+
+    /// The transfer memo is longer than allowed
+    #[error("Memo too long: at most {max} bytes")]
+    MemoTooLong { max: usize },
+
+    /// Subscription must have a non-zero amount and interval, an end in the future, and a payee
+    /// other than the owner
+    #[error("Invalid subscription")]
+    InvalidSubscription {},
+
+    /// Nothing is due on the subscription yet
+    #[error("Nothing due on this subscription")]
+    NothingDue {},
+
+    /// Lock must have a non-zero amount, an expiration in the future, and an unlocker other than
+    /// the owner
+    #[error("Invalid lock")]
+    InvalidLock {},
+
+    /// The operation would move tokens that are locked
+    #[error("Locked tokens cannot be moved: locked {locked}, balance {balance}")]
+    LockedBalance { locked: Uint128, balance: Uint128 },
+
+    /// The ticker is reserved for a well-known asset
+    #[error("Reserved ticker: {symbol}")]
+    ReservedTicker { symbol: String },
+
+    /// No buyback router is configured
+    #[error("Buyback is disabled")]
+    BuybackDisabled {},
+
+    /// Native funds are required
+    #[error("No funds sent")]
+    NoFunds {},
+
+    /// The pool is not configured to be minted into
+    #[error("Pool not configured")]
+    PoolNotConfigured {},
+
+    /// The pool deposit minted fewer LP tokens than the minimum
+    #[error("Pool slippage: {lp_out} LP tokens minted, {min_lp_out} at least")]
+    PoolSlippage { lp_out: Uint128, min_lp_out: Uint128 },
+
+    /// Vesting grant must have a non-zero amount, and a schedule ending after it starts
+    #[error("Invalid vesting")]
+    InvalidVesting {},
+
+    /// Nothing has vested since the last claim
+    #[error("Nothing vested to claim")]
+    NothingVested {},
+
+    /// Tokens cannot be moved while the token is paused
+    #[error("Token is paused")]
+    Paused {},
+
+    /// The action must be proposed through the timelock rather than executed directly
+    #[error("This action must go through the timelock")]
+    Timelocked {},
+
+    /// The pending action's delay has not passed yet
+    #[error("Action cannot be executed before {eta}")]
+    TimelockPending { eta: Timestamp },
+
+    /// The action cannot be applied to the token, e.g. a cap below the total supply
+    #[error("Invalid action")]
+    InvalidAction {},
+
+    /// A batch must have at least one target
+    #[error("Empty batch")]
+    EmptyBatch {},
+
+    /// The logo's type is not allowed by the logo policy, or only URLs are
+    #[error("Logo not allowed by the logo policy")]
+    LogoNotAllowed {},
+
+    /// The embedded logo is bigger than allowed
+    #[error("Logo too big: at most {max} bytes")]
+    LogoTooBig { max: u64 },
+
+    /// Session must have a valid secp256k1 public key, a non-zero cap, and not be expired
+    #[error("Invalid session")]
+    InvalidSession {},
+
+    /// The transfer exceeds what the session can still transfer
+    #[error("Session cap exceeded: remaining {remaining}")]
+    SessionCapExceeded { remaining: Uint128 },
+
+    /// The signature is not the session key's signature of the transfer
+    #[error("Invalid signature")]
+    InvalidSignature {},
+
+    /// Permit key must be a valid secp256k1 public key
+    #[error("Invalid permit key")]
+    InvalidPermitKey {},
+
+    /// Treasury threshold must be at least one, and at most the number of distinct approvers
+    #[error("Invalid treasury")]
+    InvalidTreasury {},
+
+    /// Spend must have a non-zero amount
+    #[error("Invalid spend")]
+    InvalidSpend {},
+
+    /// The approver already approved the spend
+    #[error("Spend already approved")]
+    AlreadyApproved {},
+
+    /// The treasury holds less than the spend, escrowed tokens aside
+    #[error("Insufficient treasury: {available} available")]
+    InsufficientTreasury { available: Uint128 },
+
+    /// Scheduled transfer must have a non-zero amount, and be due at a height or time to come
+    #[error("Invalid schedule")]
+    InvalidSchedule {},
+
+    /// The scheduled transfer is not due yet
+    #[error("Scheduled transfer is not due yet")]
+    NotDue {},
+
+    /// The scheduled transfer is due, and can no longer be cancelled
+    #[error("Scheduled transfer is already due")]
+    AlreadyDue {},
+
+    /// Deposit rates must be non-zero, and convert amounts without overflowing
+    #[error("Invalid deposit rate")]
+    InvalidRate {},
+
+    /// The denom is not a backing denom of the token
+    #[error("Unsupported denom: {denom}")]
+    UnsupportedDenom { denom: String },
+
+    /// The reserve of the denom holds less than the redemption
+    #[error("Insufficient reserve: {available} available")]
+    InsufficientReserve { available: Uint128 },
+
+    /// Plain transfers to contracts are rejected, unless the sender allows them
+    #[error("Recipient {recipient} is a contract: use send, or allow_contract")]
+    ContractRecipient { recipient: String },
+
+    /// Stream allowance must be to another address, and accrue a non-zero total before its end
+    #[error("Invalid stream allowance")]
+    InvalidStream {},
+
+    /// The transfer exceeds what accrued on the stream and was not transferred yet
+    #[error("Stream allowance exceeded: {available} available")]
+    StreamExceeded { available: Uint128 },
+
+    /// No flash mint fee is set
+    #[error("Flash mints are disabled")]
+    FlashMintDisabled {},
+
+    /// The flash mint fee is at most 10000 basis points
+    #[error("Invalid flash mint fee")]
+    InvalidFlashMintFee {},
+
+    /// A flash mint cannot start while another one is not burnt back yet
+    #[error("Flash mint already in progress")]
+    FlashMintInProgress {},
+
+    /// The callback did not burn back the flash minted amount and the fee
+    #[error("Flash mint not repaid: {owed} still owed")]
+    FlashMintNotRepaid { owed: Uint128 },
+
+    /// The address was revoked as a minter, and must be restored through the timelock first
+    #[error("Minter {address} is revoked")]
+    MinterRevoked { address: String },
+
+    /// A revoked minter attempted to mint, which monitoring should alert on
+    #[error("Alert: revoked minter {address} attempted to mint")]
+    RevokedMinterMint { address: String },
+
+    /// No more dependents can be registered
+    #[error("Too many dependents: at most {max}")]
+    TooManyDependents { max: usize },
+
+    /// No more assets can be listed in the basket
+    #[error("Basket full: at most {max} assets")]
+    BasketFull { max: usize },
+
+    /// The amount is not a valid amount in the token's display units
+    #[error("Invalid amount {amount}: {reason}")]
+    InvalidAmount { amount: String, reason: String },
+
+    /// The ledger retention must be at least a block
+    #[error("Invalid ledger retention")]
+    InvalidLedgerRetention {},
+
+    /// The transfer policy rejects zero-amount transfers
+    #[error("Zero-amount transfers are not allowed")]
+    ZeroAmountTransfer {},
+
+    /// The transfer policy rejects transfers to the sender itself
+    #[error("Self-transfers are not allowed")]
+    SelfTransfer {},
+
+    /// The token is not a sibling the token can be converted into
+    #[error("Unknown sibling token: {token}")]
+    UnknownSibling { token: String },
+
+    /// The conversion would mint less than the minimum asked for
+    #[error("Conversion slippage: {out} minted, {min_out} at least")]
+    ConversionSlippage { out: Uint128, min_out: Uint128 },
+
+    /// With the corridor mode on, the transfer is not along a whitelisted corridor
+    #[error("No corridor from {from} to {to}")]
+    NoCorridor { from: String, to: String },
+
+    /// The address is a member of too many groups
+    #[error("Too many groups: at most {max}")]
+    TooManyGroups { max: usize },
+
+    /// The account label is longer than allowed
+    #[error("Label too long: at most {max} bytes")]
+    LabelTooLong { max: usize },
+
+    /// The allowance purpose is longer than allowed
+    #[error("Purpose too long: at most {max} bytes")]
+    PurposeTooLong { max: usize },
+
+    /// The faucet is only on in test deployments
+    #[error("Faucet disabled")]
+    FaucetDisabled {},
+
+    /// The faucet mints at most a fixed amount per address per day
+    #[error("Faucet cap exceeded: remaining {remaining}")]
+    FaucetCapExceeded { remaining: Uint128 },
+
+    /// The renouncement of the admin's privileges is already scheduled
+    #[error("Renouncement already scheduled at {renounce_at}")]
+    RenounceScheduled { renounce_at: Timestamp },
+
+    /// There is no renouncement of the admin's privileges to cancel
+    #[error("No renouncement scheduled")]
+    NoRenounceScheduled {},
+
+    /// The renouncement delay goes past the latest time representable
+    #[error("Invalid renouncement delay")]
+    InvalidRenounceDelay {},
+}
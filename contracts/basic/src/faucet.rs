@@ -0,0 +1,95 @@
+/*
+Faucet module - a built-in faucet for test deployments, so that a testnet token does not need a
+separate faucet contract. Turned on at instantiation only, in which case anyone can have tokens
+minted to themselves, up to a fixed number of whole tokens per address per day, within the minter's
+cap if there is one. Entirely disabled otherwise, which production deployments must leave it.
+*/
+
+use cosmwasm_std::{
+    Addr, DepsMut, Env, MessageInfo, Response, StdError, StdResult, Storage, Uint128
+};
+use cw_storage_plus::{Item, Map};
+use cw20_base::state::{BALANCES, TOKEN_INFO};
+
+use crate::error::ContractError;
+use crate::limits::DailySpent;
+
+/// Whole tokens an address can have minted by the faucet per day
+pub const FAUCET_DAILY_TOKENS: u128 = 1_000;
+
+/// Whether the faucet is on, which it only is if turned on at instantiation
+pub const FAUCET: Item<bool> = Item::new("test_faucet");
+
+/// Per-address faucet mints in the current window
+pub const FAUCET_MINTED: Map<&Addr, DailySpent> = Map::new("faucet_minted");
+
+
+/// Faucet mint - mint tokens to the sender, up to the faucet's daily cap. Test deployments only.
+/// # Arguments
+/// * `deps`   - mutable dependency which has the storage (state) of the chain
+/// * `env`    - environment variables which include block information
+/// * `info`   - message info, the sender receives the tokens
+/// * `amount` - the amount minted
+/// # Returns
+/// * the execute response on Ok
+/// * the error type on Err
+pub fn execute_faucet_mint(
+    deps   : DepsMut,
+    env    : Env,
+    info   : MessageInfo,
+    amount : Uint128,
+) -> Result<Response, ContractError> {
+    if !FAUCET.may_load(deps.storage)?.unwrap_or_default() {
+        return Err(ContractError::FaucetDisabled {});
+    }
+
+    // start a new window if there is none active
+    let mut minted = match FAUCET_MINTED.may_load(deps.storage, &info.sender)? {
+        Some(minted) if minted.is_active(&env.block) => minted,
+        _ => DailySpent { window_start: env.block.time, spent: Uint128::zero() },
+    };
+    let remaining = daily_cap(deps.storage)?.saturating_sub(minted.spent);
+    if amount > remaining {
+        return Err(ContractError::FaucetCapExceeded { remaining });
+    }
+    minted.spent += amount;
+    FAUCET_MINTED.save(deps.storage, &info.sender, &minted)?;
+
+    mint_faucet(deps.storage, &info.sender, amount)?;
+    Ok(Response::new()
+        .add_attribute("action", "faucet_mint")
+        .add_attribute("to", info.sender)
+        .add_attribute("amount", amount))
+}
+
+
+/// The raw units an address can have minted by the faucet per day, following the token's decimals.
+fn daily_cap(storage: &dyn Storage) -> StdResult<Uint128> {
+    let decimals = TOKEN_INFO.load(storage)?.decimals;
+    10u128
+        .checked_pow(decimals.into())
+        .and_then(|unit| unit.checked_mul(FAUCET_DAILY_TOKENS))
+        .map(Uint128::new)
+        .ok_or_else(|| StdError::generic_err("faucet cap overflow"))
+}
+
+
+/// Mint the faucet tokens, within the minter's cap if there is one.
+fn mint_faucet(
+    storage   : &mut dyn Storage,
+    recipient : &Addr,
+    amount    : Uint128,
+) -> Result<(), ContractError> {
+    let mut token_info = TOKEN_INFO.load(storage)?;
+    token_info.total_supply = token_info.total_supply.checked_add(amount).map_err(StdError::from)?;
+    if let Some(cap) = token_info.get_cap() {
+        if token_info.total_supply > cap {
+            return Err(cw20_base::ContractError::CannotExceedCap {}.into());
+        }
+    }
+    TOKEN_INFO.save(storage, &token_info)?;
+    BALANCES.update(storage, recipient, |balance| -> StdResult<_> {
+        Ok(balance.unwrap_or_default().checked_add(amount)?)
+    })?;
+    Ok(())
+}
@@ -0,0 +1,185 @@
+/*
+Flash module - flash mints. Anyone can have tokens minted to a contract for the span of a single
+transaction: the tokens are minted, the contract is called back through a submessage, and the reply
+checks that the contract burnt them back along with the flash mint fee, reverting everything
+otherwise. Arbitrage and liquidation tooling can thus use the token without holding it. Flash mints
+are off until the admin sets the fee.
+*/
+
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{
+    to_binary, Addr, Binary, Deps, DepsMut, MessageInfo, Response, StdError, StdResult, Storage,
+    SubMsg, Uint128, WasmMsg
+};
+use cw_storage_plus::Item;
+use cw20_base::state::{BALANCES, TOKEN_INFO};
+
+use crate::error::ContractError;
+use crate::msg::FlashMintFeeResponse;
+use crate::state::{assert_admin, TOTAL_BURNED};
+
+/// Reply id of the flash mint callback submessage
+pub const FLASH_MINT_REPLY_ID: u64 = 3;
+
+/// Flash mint fee in basis points of the amount minted, flash mints being off if unset
+pub const FLASH_MINT_FEE_BPS: Item<u16> = Item::new("flash_mint_fee_bps");
+
+/// A flash mint waiting for its reply, to tell whether it was burnt back
+#[cw_serde]
+pub struct PendingFlashMint {
+    /// The amount minted
+    pub amount        : Uint128,
+    /// The fee to burn on top of the amount
+    pub fee           : Uint128,
+    /// Total supply before the flash mint
+    pub supply_before : Uint128,
+}
+
+/// The pending flash mint
+pub const PENDING_FLASH_MINT: Item<PendingFlashMint> = Item::new("pending_flash_mint");
+
+/// Execute message expected to be supported by the callback contract. It holds the minted tokens
+/// while handling it, and must burn `amount` plus `fee` of them before it returns.
+#[cw_serde]
+pub enum FlashMintHookMsg {
+    /// Use the flash minted tokens, then burn them back along with the fee
+    OnFlashMint {
+        initiator : String,
+        amount    : Uint128,
+        fee       : Uint128,
+        msg       : Binary,
+    },
+}
+
+
+/// Set the flash mint fee - admin only.
+/// # Arguments
+/// * `deps`    - mutable dependency which has the storage (state) of the chain
+/// * `info`    - message info, the sender must be the admin
+/// * `fee_bps` - the fee in basis points of the amount minted, None to turn flash mints off
+/// # Returns
+/// * the execute response on Ok
+/// * the error type on Err
+pub fn execute_set_flash_mint_fee(
+    deps    : DepsMut,
+    info    : MessageInfo,
+    fee_bps : Option<u16>,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.storage, &info.sender)?;
+    match fee_bps {
+        Some(fee_bps) if fee_bps > 10_000 => return Err(ContractError::InvalidFlashMintFee {}),
+        Some(fee_bps) => FLASH_MINT_FEE_BPS.save(deps.storage, &fee_bps)?,
+        None => FLASH_MINT_FEE_BPS.remove(deps.storage),
+    }
+    Ok(Response::new()
+        .add_attribute("action", "set_flash_mint_fee")
+        .add_attribute("fee_bps", fee_bps.map_or("none".to_string(), |fee| fee.to_string())))
+}
+
+
+/// Flash mint - mint tokens to the callback contract, and call it back. The reply checks that the
+/// amount and the fee were burnt by the time the callback returns.
+/// # Arguments
+/// * `deps`              - mutable dependency which has the storage (state) of the chain
+/// * `info`              - message info, the sender initiates the flash mint
+/// * `amount`            - the amount minted
+/// * `callback_contract` - the contract receiving the tokens, and called back
+/// * `msg`               - the message passed on to the callback contract
+/// # Returns
+/// * the execute response on Ok
+/// * the error type on Err
+pub fn execute_flash_mint(
+    deps              : DepsMut,
+    info              : MessageInfo,
+    amount            : Uint128,
+    callback_contract : String,
+    msg               : Binary,
+) -> Result<Response, ContractError> {
+    let fee_bps = FLASH_MINT_FEE_BPS
+        .may_load(deps.storage)?
+        .ok_or(ContractError::FlashMintDisabled {})?;
+    // a callback cannot flash mint again before its own flash mint is burnt back
+    if PENDING_FLASH_MINT.exists(deps.storage) {
+        return Err(ContractError::FlashMintInProgress {});
+    }
+    let borrower = deps.api.addr_validate(&callback_contract)?;
+    let fee = amount
+        .checked_multiply_ratio(fee_bps, 10_000u128)
+        .map_err(|_| StdError::generic_err("flash mint fee overflow"))?;
+
+    let supply_before = TOKEN_INFO.load(deps.storage)?.total_supply;
+    mint_flash(deps.storage, &borrower, amount)?;
+    PENDING_FLASH_MINT.save(deps.storage, &PendingFlashMint { amount, fee, supply_before })?;
+
+    let callback = WasmMsg::Execute {
+        contract_addr : borrower.to_string(),
+        msg           : to_binary(&FlashMintHookMsg::OnFlashMint {
+            initiator : info.sender.to_string(),
+            amount,
+            fee,
+            msg,
+        })?,
+        funds         : vec![],
+    };
+    Ok(Response::new()
+        .add_submessage(SubMsg::reply_on_success(callback, FLASH_MINT_REPLY_ID))
+        .add_attribute("action", "flash_mint")
+        .add_attribute("to", borrower)
+        .add_attribute("amount", amount)
+        .add_attribute("fee", fee))
+}
+
+
+/// Mint the flash minted tokens, within the minter's cap if there is one.
+fn mint_flash(
+    storage  : &mut dyn Storage,
+    borrower : &Addr,
+    amount   : Uint128,
+) -> Result<(), ContractError> {
+    let mut token_info = TOKEN_INFO.load(storage)?;
+    token_info.total_supply = token_info.total_supply.checked_add(amount).map_err(StdError::from)?;
+    if let Some(cap) = token_info.get_cap() {
+        if token_info.total_supply > cap {
+            return Err(cw20_base::ContractError::CannotExceedCap {}.into());
+        }
+    }
+    TOKEN_INFO.save(storage, &token_info)?;
+    BALANCES.update(storage, borrower, |balance| -> StdResult<_> {
+        Ok(balance.unwrap_or_default().checked_add(amount)?)
+    })?;
+    Ok(())
+}
+
+
+/// Flash mint reply - ensure the callback burnt the amount back along with the fee, the total
+/// supply having dropped by at least the fee. Only the fee counts as burnt.
+/// # Arguments
+/// * `deps` - mutable dependency which has the storage (state) of the chain
+/// # Returns
+/// * the response on Ok
+/// * the error type on Err
+pub fn reply_flash_mint(deps: DepsMut) -> Result<Response, ContractError> {
+    let pending = PENDING_FLASH_MINT.load(deps.storage)?;
+    PENDING_FLASH_MINT.remove(deps.storage);
+
+    let supply = TOKEN_INFO.load(deps.storage)?.total_supply;
+    let owed = (supply + pending.fee).saturating_sub(pending.supply_before);
+    if !owed.is_zero() {
+        return Err(ContractError::FlashMintNotRepaid { owed });
+    }
+    let burned = TOTAL_BURNED.may_load(deps.storage)?.unwrap_or_default();
+    TOTAL_BURNED.save(deps.storage, &burned.saturating_sub(pending.amount))?;
+    Ok(Response::new()
+        .add_attribute("action", "flash_mint_repaid")
+        .add_attribute("fee", pending.fee))
+}
+
+
+/// Query the flash mint fee.
+/// # Arguments
+/// * `deps` - dependency which has the storage (state) of the chain
+/// # Returns
+///   The fee in basis points, None if flash mints are off
+pub fn query_flash_mint_fee(deps: Deps) -> StdResult<FlashMintFeeResponse> {
+    Ok(FlashMintFeeResponse { fee_bps: FLASH_MINT_FEE_BPS.may_load(deps.storage)? })
+}
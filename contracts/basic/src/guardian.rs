@@ -0,0 +1,278 @@
+/*
+Guardian module - emergency brake and timelock. A guardian address can pause the token instantly,
+while the privileged changes to the token itself (minter, cap, guardian) must be proposed by the
+admin and can only be executed once a delay has passed, leaving holders time to react. The guardian
+can also cancel a pending action.
+*/
+
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{
+    Addr, Deps, DepsMut, Env, MessageInfo, Order, Response, StdResult, Storage, Timestamp, Uint128
+};
+use cw_storage_plus::{Bound, Item, Map};
+use cw20_base::state::{MinterData, TOKEN_INFO};
+
+use crate::error::ContractError;
+use crate::minters::{assert_not_revoked, revoke_minter, REVOKED_MINTERS};
+use crate::msg::{GuardianResponse, PendingActionResponse, PendingActionsResponse};
+use crate::renounce::is_due;
+use crate::roles::{assert_role, Role};
+use crate::state::assert_admin;
+
+// Settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+
+/// Delay between proposing an action and being able to execute it, in seconds
+pub const TIMELOCK_DELAY: u64 = 24 * 60 * 60;
+
+/// Privileged actions that must go through the timelock
+#[cw_serde]
+pub enum AdminAction {
+    /// Set the token's minter, keeping its cap. None removes the minter forever.
+    UpdateMinter { new_minter: Option<String> },
+    /// Set the minter's cap, None for no cap. Cannot be below the total supply.
+    UpdateCap { cap: Option<Uint128> },
+    /// Set the guardian, None to remove it
+    SetGuardian { guardian: Option<String> },
+    /// Remove a minter from the revocation list, so that it can become a minter again
+    RestoreMinter { minter: String },
+}
+
+/// An action proposed and waiting for its delay to pass
+#[cw_serde]
+pub struct PendingAction {
+    /// The proposed action
+    pub action : AdminAction,
+    /// The action can be executed from this time on
+    pub eta    : Timestamp,
+}
+
+/// The guardian, able to pause the token and cancel pending actions, if any
+pub const GUARDIAN: Item<Addr> = Item::new("guardian");
+
+/// Whether the token is paused
+pub const PAUSED: Item<bool> = Item::new("paused");
+
+/// Pending actions, by id
+pub const PENDING_ACTIONS: Map<u64, PendingAction> = Map::new("pending_actions");
+
+/// Last action id used
+pub const ACTION_COUNT: Item<u64> = Item::new("action_count");
+
+
+/// Ensure that the token is not paused
+pub fn assert_not_paused(storage: &dyn Storage) -> Result<(), ContractError> {
+    if PAUSED.may_load(storage)?.unwrap_or_default() {
+        return Err(ContractError::Paused {});
+    }
+    Ok(())
+}
+
+
+/// Whether the sender is the guardian
+fn is_guardian(storage: &dyn Storage, sender: &Addr) -> StdResult<bool> {
+    Ok(GUARDIAN.may_load(storage)?.as_ref() == Some(sender))
+}
+
+
+/// Pause or unpause the token. The guardian and holders of the pauser role pause, but only the
+/// latter unpause.
+/// # Arguments
+/// * `deps`   - mutable dependency which has the storage (state) of the chain
+/// * `info`   - message info, the sender must be the guardian or hold the pauser role
+/// * `paused` - whether to pause, or unpause, the token
+/// # Returns
+/// * the execute response on Ok
+/// * the error type on Err
+pub fn execute_set_paused(
+    deps   : DepsMut,
+    info   : MessageInfo,
+    paused : bool,
+) -> Result<Response, ContractError> {
+    if !paused || !is_guardian(deps.storage, &info.sender)? {
+        assert_role(deps.storage, Role::Pauser, &info.sender)?;
+    }
+    PAUSED.save(deps.storage, &paused)?;
+    Ok(Response::new()
+        .add_attribute("action", if paused { "pause" } else { "unpause" })
+        .add_attribute("by", info.sender))
+}
+
+
+/// Propose a privileged action - admin only. It can be executed once the timelock delay passed.
+/// # Arguments
+/// * `deps`   - mutable dependency which has the storage (state) of the chain
+/// * `env`    - environment variables which include block information
+/// * `info`   - message info, the sender must be the admin
+/// * `action` - the action proposed
+/// # Returns
+/// * the execute response on Ok
+/// * the error type on Err
+pub fn execute_propose_action(
+    deps   : DepsMut,
+    env    : Env,
+    info   : MessageInfo,
+    action : AdminAction,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.storage, &info.sender)?;
+    match &action {
+        AdminAction::UpdateMinter { new_minter: Some(addr) } => {
+            assert_not_revoked(deps.storage, &deps.api.addr_validate(addr)?)?;
+        }
+        AdminAction::SetGuardian { guardian: Some(addr) }
+        | AdminAction::RestoreMinter { minter: addr } => {
+            deps.api.addr_validate(addr)?;
+        }
+        _ => {}
+    }
+
+    let id = ACTION_COUNT.may_load(deps.storage)?.unwrap_or_default() + 1;
+    ACTION_COUNT.save(deps.storage, &id)?;
+    let eta = env.block.time.plus_seconds(TIMELOCK_DELAY);
+    PENDING_ACTIONS.save(deps.storage, id, &PendingAction { action, eta })?;
+    Ok(Response::new()
+        .add_attribute("action", "propose_action")
+        .add_attribute("action_id", id.to_string())
+        .add_attribute("eta", eta.seconds().to_string()))
+}
+
+
+/// Execute a pending action - admin only, once its delay has passed.
+/// # Arguments
+/// * `deps`      - mutable dependency which has the storage (state) of the chain
+/// * `env`       - environment variables which include block information
+/// * `info`      - message info, the sender must be the admin
+/// * `action_id` - the pending action
+/// # Returns
+/// * the execute response on Ok
+/// * the error type on Err
+pub fn execute_action(
+    deps      : DepsMut,
+    env       : Env,
+    info      : MessageInfo,
+    action_id : u64,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.storage, &info.sender)?;
+    let pending = PENDING_ACTIONS.load(deps.storage, action_id)?;
+    if env.block.time < pending.eta {
+        return Err(ContractError::TimelockPending { eta: pending.eta });
+    }
+
+    let mut token_info = TOKEN_INFO.load(deps.storage)?;
+    match pending.action {
+        AdminAction::UpdateMinter { new_minter } => {
+            let new_minter = new_minter.map(|m| deps.api.addr_validate(&m)).transpose()?;
+            if let Some(minter) = &new_minter {
+                assert_not_revoked(deps.storage, minter)?;
+            }
+            // the minter rotated out is revoked
+            let old = token_info.mint.take();
+            if let Some(old) = &old {
+                if new_minter.as_ref() != Some(&old.minter) {
+                    revoke_minter(deps.storage, &old.minter, env.block.time)?;
+                }
+            }
+            let cap = old.and_then(|mint| mint.cap);
+            token_info.mint = new_minter.map(|minter| MinterData { minter, cap });
+            TOKEN_INFO.save(deps.storage, &token_info)?;
+        }
+        AdminAction::UpdateCap { cap } => {
+            let mint = token_info.mint.as_mut().ok_or(ContractError::InvalidAction {})?;
+            if cap.is_some_and(|cap| cap < token_info.total_supply) {
+                return Err(ContractError::InvalidAction {});
+            }
+            mint.cap = cap;
+            TOKEN_INFO.save(deps.storage, &token_info)?;
+        }
+        AdminAction::SetGuardian { guardian } => match guardian {
+            Some(guardian) => GUARDIAN.save(deps.storage, &deps.api.addr_validate(&guardian)?)?,
+            None => GUARDIAN.remove(deps.storage),
+        },
+        AdminAction::RestoreMinter { minter } => {
+            REVOKED_MINTERS.remove(deps.storage, &deps.api.addr_validate(&minter)?);
+        }
+    }
+    PENDING_ACTIONS.remove(deps.storage, action_id);
+    Ok(Response::new()
+        .add_attribute("action", "execute_action")
+        .add_attribute("action_id", action_id.to_string()))
+}
+
+
+/// Cancel a pending action - by the admin, or the guardian.
+/// # Arguments
+/// * `deps`      - mutable dependency which has the storage (state) of the chain
+/// * `info`      - message info, the sender must be the admin or the guardian
+/// * `action_id` - the pending action
+/// # Returns
+/// * the execute response on Ok
+/// * the error type on Err
+pub fn execute_cancel_action(
+    deps      : DepsMut,
+    info      : MessageInfo,
+    action_id : u64,
+) -> Result<Response, ContractError> {
+    if !is_guardian(deps.storage, &info.sender)? {
+        assert_admin(deps.storage, &info.sender)?;
+    }
+    PENDING_ACTIONS.load(deps.storage, action_id)?;
+    PENDING_ACTIONS.remove(deps.storage, action_id);
+    Ok(Response::new()
+        .add_attribute("action", "cancel_action")
+        .add_attribute("action_id", action_id.to_string())
+        .add_attribute("by", info.sender))
+}
+
+
+/// Query the guardian, and whether the token is paused - neither once the privileges are renounced.
+/// # Arguments
+/// * `deps` - dependency which has the storage (state) of the chain
+/// * `env`  - environment variables which include block information
+/// # Returns
+///   The guardian and pause status
+pub fn query_guardian(deps: Deps, env: Env) -> StdResult<GuardianResponse> {
+    if is_due(deps, &env.block)? {
+        return Ok(GuardianResponse { guardian: None, paused: false });
+    }
+    Ok(GuardianResponse {
+        guardian : GUARDIAN.may_load(deps.storage)?.map(String::from),
+        paused   : PAUSED.may_load(deps.storage)?.unwrap_or_default(),
+    })
+}
+
+
+/// Query the pending actions, none once the privileges are renounced.
+/// # Arguments
+/// * `deps`        - dependency which has the storage (state) of the chain
+/// * `env`         - environment variables which include block information
+/// * `start_after` - the action id to start after
+/// * `limit`       - the list size limit
+/// # Returns
+///   The list of pending actions
+pub fn query_pending_actions(
+    deps        : Deps,
+    env         : Env,
+    start_after : Option<u64>,
+    limit       : Option<u32>,
+) -> StdResult<PendingActionsResponse> {
+    if is_due(deps, &env.block)? {
+        return Ok(PendingActionsResponse { actions: vec![] });
+    }
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let actions = PENDING_ACTIONS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            item.map(|(id, pending)| PendingActionResponse {
+                id,
+                ready  : env.block.time >= pending.eta,
+                action : pending.action,
+                eta    : pending.eta,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+    Ok(PendingActionsResponse { actions })
+}
@@ -0,0 +1,222 @@
+/*
+Helpers module - a typed wrapper around the token's address, in the style of cw20's `Cw20Contract`,
+for downstream contracts integrating the token from Rust. Executes return ready-to-send `WasmMsg`s,
+and queries are made through the querier and parsed into their response types.
+*/
+
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{
+    to_binary, Addr, Binary, Coin, QuerierWrapper, StdResult, Uint128, WasmMsg
+};
+use cw20::{BalanceResponse, Expiration};
+use serde::de::DeserializeOwned;
+
+use crate::msg::{
+    ExecuteMsg, GuardianResponse, HasRoleResponse, LockedBalanceResponse, QueryMsg,
+    TokenInfoExtendedResponse, VestingResponse
+};
+use crate::roles::Role;
+use crate::vesting::VestingSchedule;
+
+/// The token contract, by its address
+#[cw_serde]
+pub struct EamesToken(pub Addr);
+
+impl EamesToken {
+    /// The token's address
+    pub fn addr(&self) -> Addr {
+        self.0.clone()
+    }
+
+    /// Execute message to the token, with no funds attached
+    pub fn call(&self, msg: ExecuteMsg) -> StdResult<WasmMsg> {
+        self.call_with_funds(msg, vec![])
+    }
+
+    /// Execute message to the token, with the funds attached
+    pub fn call_with_funds(&self, msg: ExecuteMsg, funds: Vec<Coin>) -> StdResult<WasmMsg> {
+        Ok(WasmMsg::Execute {
+            contract_addr : self.addr().into(),
+            msg           : to_binary(&msg)?,
+            funds,
+        })
+    }
+
+    /// Query the token, parsing the response
+    pub fn query<T: DeserializeOwned>(
+        &self,
+        querier : &QuerierWrapper,
+        msg     : QueryMsg,
+    ) -> StdResult<T> {
+        querier.query_wasm_smart(self.addr(), &msg)
+    }
+
+    // executes
+
+    /// Transfer to the recipient, which must not be a contract if the recipient check is on
+    pub fn transfer<T: Into<String>>(&self, recipient: T, amount: Uint128) -> StdResult<WasmMsg> {
+        self.call(ExecuteMsg::Transfer {
+            recipient      : recipient.into(),
+            amount,
+            allow_contract : None,
+        })
+    }
+
+    /// Transfer with a memo emitted in the events
+    pub fn transfer_with_memo<T: Into<String>, M: Into<String>>(
+        &self,
+        recipient : T,
+        amount    : Uint128,
+        memo      : M,
+    ) -> StdResult<WasmMsg> {
+        self.call(ExecuteMsg::TransferWithMemo {
+            recipient : recipient.into(),
+            amount,
+            memo      : memo.into(),
+        })
+    }
+
+    /// Send to a contract, triggering its receive hook with the message
+    pub fn send<T: Into<String>>(
+        &self,
+        contract : T,
+        amount   : Uint128,
+        msg      : Binary,
+    ) -> StdResult<WasmMsg> {
+        self.call(ExecuteMsg::Send { contract: contract.into(), amount, msg })
+    }
+
+    /// Burn the sender's tokens
+    pub fn burn(&self, amount: Uint128) -> StdResult<WasmMsg> {
+        self.call(ExecuteMsg::Burn { amount })
+    }
+
+    /// Mint to the recipient, as the minter or a holder of the minter role
+    pub fn mint<T: Into<String>>(&self, recipient: T, amount: Uint128) -> StdResult<WasmMsg> {
+        self.call(ExecuteMsg::Mint { recipient: recipient.into(), amount })
+    }
+
+    /// Pause every token movement, as the guardian or a holder of the pauser role
+    pub fn pause(&self) -> StdResult<WasmMsg> {
+        self.call(ExecuteMsg::Pause {})
+    }
+
+    /// Resume token movements, as a holder of the pauser role
+    pub fn unpause(&self) -> StdResult<WasmMsg> {
+        self.call(ExecuteMsg::Unpause {})
+    }
+
+    /// Lock part of the sender's balance until `until`, releasable early by the unlocker
+    pub fn lock<T: Into<String>>(
+        &self,
+        amount   : Uint128,
+        unlocker : T,
+        until    : Expiration,
+    ) -> StdResult<WasmMsg> {
+        self.call(ExecuteMsg::Lock { amount, unlocker: unlocker.into(), until })
+    }
+
+    /// Release a lock, as its unlocker or once expired
+    pub fn unlock(&self, lock_id: u64) -> StdResult<WasmMsg> {
+        self.call(ExecuteMsg::Unlock { lock_id })
+    }
+
+    /// Mint a grant vesting to the recipient over the schedule
+    pub fn mint_vested<T: Into<String>>(
+        &self,
+        recipient : T,
+        amount    : Uint128,
+        schedule  : VestingSchedule,
+    ) -> StdResult<WasmMsg> {
+        self.call(ExecuteMsg::MintVested { recipient: recipient.into(), amount, schedule })
+    }
+
+    /// Claim what has vested of the sender's grants
+    pub fn claim_vested(&self) -> StdResult<WasmMsg> {
+        self.call(ExecuteMsg::ClaimVested {})
+    }
+
+    /// Flash mint to the callback contract, which must burn the amount and the fee back
+    pub fn flash_mint<T: Into<String>>(
+        &self,
+        amount            : Uint128,
+        callback_contract : T,
+        msg               : Binary,
+    ) -> StdResult<WasmMsg> {
+        self.call(ExecuteMsg::FlashMint {
+            amount,
+            callback_contract : callback_contract.into(),
+            msg,
+        })
+    }
+
+    /// Buy back the token with the native funds, and burn it
+    pub fn buyback_and_burn(
+        &self,
+        min_receive : Option<Uint128>,
+        funds       : Vec<Coin>,
+    ) -> StdResult<WasmMsg> {
+        self.call_with_funds(ExecuteMsg::BuybackAndBurn { min_receive }, funds)
+    }
+
+    // queries
+
+    /// Balance of the address
+    pub fn balance<T: Into<String>>(
+        &self,
+        querier : &QuerierWrapper,
+        address : T,
+    ) -> StdResult<Uint128> {
+        let res: BalanceResponse =
+            self.query(querier, QueryMsg::Balance { address: address.into() })?;
+        Ok(res.balance)
+    }
+
+    /// Token info, minter and cap, total burnt and holder count
+    pub fn token_info_extended(
+        &self,
+        querier : &QuerierWrapper,
+    ) -> StdResult<TokenInfoExtendedResponse> {
+        self.query(querier, QueryMsg::TokenInfoExtended {})
+    }
+
+    /// The guardian, and whether the token is paused
+    pub fn guardian(&self, querier: &QuerierWrapper) -> StdResult<GuardianResponse> {
+        self.query(querier, QueryMsg::Guardian {})
+    }
+
+    /// Whether the token is paused
+    pub fn is_paused(&self, querier: &QuerierWrapper) -> StdResult<bool> {
+        Ok(self.guardian(querier)?.paused)
+    }
+
+    /// Whether the address holds the role
+    pub fn has_role<T: Into<String>>(
+        &self,
+        querier : &QuerierWrapper,
+        role    : Role,
+        address : T,
+    ) -> StdResult<bool> {
+        let res: HasRoleResponse =
+            self.query(querier, QueryMsg::HasRole { role, address: address.into() })?;
+        Ok(res.has_role)
+    }
+
+    /// Balance of the address, split between locked and free
+    pub fn locked_balance<T: Into<String>>(
+        &self,
+        querier : &QuerierWrapper,
+        address : T,
+    ) -> StdResult<LockedBalanceResponse> {
+        self.query(querier, QueryMsg::LockedBalance { address: address.into() })
+    }
+
+    /// Vesting grants of the address
+    pub fn vesting<T: Into<String>>(
+        &self,
+        querier : &QuerierWrapper,
+        address : T,
+    ) -> StdResult<VestingResponse> {
+        self.query(querier, QueryMsg::Vesting { address: address.into() })
+    }
+}
@@ -0,0 +1,73 @@
+/*
+History module - daily checkpoints of the total supply, so that supply charts can be drawn from the
+contract state rather than by replaying every mint and burn. Each day holds the total supply as of
+the last change made that day, and days without changes have no checkpoint.
+*/
+
+use cosmwasm_std::{BlockInfo, Deps, Order, StdResult, Storage, Timestamp, Uint128};
+use cw_storage_plus::{Bound, Map};
+use cw20_base::state::TOKEN_INFO;
+
+use crate::msg::{SupplyCheckpoint, SupplyHistoryResponse};
+
+// Settings for pagination
+const MAX_LIMIT: u32 = 100;
+const DEFAULT_LIMIT: u32 = 30;
+
+/// Length of a checkpoint bucket - a day
+pub const SUPPLY_BUCKET_SECONDS: u64 = 86_400;
+
+/// Total supply checkpoints, by day since the unix epoch
+pub const SUPPLY_HISTORY: Map<u64, Uint128> = Map::new("supply_history");
+
+
+/// Checkpoint the total supply in the bucket of the block's day, if it changed since the latest
+/// checkpoint.
+/// # Arguments
+/// * `storage` - the contract storage, with the token info
+/// * `block`   - the current block, whose time sets the bucket
+/// # Returns
+/// * unit on Ok
+/// * the standard error type on Err
+pub fn record_supply(storage: &mut dyn Storage, block: &BlockInfo) -> StdResult<()> {
+    let supply = TOKEN_INFO.load(storage)?.total_supply;
+    let latest = SUPPLY_HISTORY
+        .range(storage, None, None, Order::Descending)
+        .next()
+        .transpose()?;
+    if latest.map(|(_, checkpoint)| checkpoint) != Some(supply) {
+        let day = block.time.seconds() / SUPPLY_BUCKET_SECONDS;
+        SUPPLY_HISTORY.save(storage, day, &supply)?;
+    }
+    Ok(())
+}
+
+
+/// Query the total supply checkpoints, oldest first.
+/// # Arguments
+/// * `deps`        - dependency which has the storage (state) of the chain
+/// * `start_after` - the day to start after
+/// * `limit`       - the list size limit
+/// # Returns
+///   The list of checkpoints
+pub fn query_supply_history(
+    deps        : Deps,
+    start_after : Option<u64>,
+    limit       : Option<u32>,
+) -> StdResult<SupplyHistoryResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let checkpoints = SUPPLY_HISTORY
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            item.map(|(day, total_supply)| SupplyCheckpoint {
+                day,
+                time : Timestamp::from_seconds(day * SUPPLY_BUCKET_SECONDS),
+                total_supply,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+    Ok(SupplyHistoryResponse { checkpoints })
+}
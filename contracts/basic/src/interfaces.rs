@@ -0,0 +1,106 @@
+/*
+Interfaces module - a cw22 style declaration of the interfaces this deployment supports, so that
+generic tooling can adapt to the modules it finds. The declaration is stored at instantiation and
+refreshed on migration, under the same storage layout as cw22: interface name to version.
+*/
+
+use cosmwasm_std::{Deps, Order, StdResult, Storage};
+use cw_storage_plus::Map;
+
+use crate::msg::{SupportedInterface, SupportedInterfacesResponse};
+use crate::faucet::FAUCET;
+use crate::treasury::TREASURY;
+
+/// The supported interfaces, name to version
+pub const SUPPORTED_INTERFACES: Map<&str, String> = Map::new("supported_interfaces");
+
+// version of the token's own module interfaces
+const MODULES_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Modules every deployment has
+const MODULES: [&str; 34] = [
+    "eames-token:basket",
+    "eames-token:batch",
+    "eames-token:buyback",
+    "eames-token:converter",
+    "eames-token:corridors",
+    "eames-token:dependents",
+    "eames-token:display",
+    "eames-token:flash",
+    "eames-token:history",
+    "eames-token:labels",
+    "eames-token:ledger",
+    "eames-token:limits",
+    "eames-token:locks",
+    "eames-token:logo",
+    "eames-token:minters",
+    "eames-token:names",
+    "eames-token:pausable",
+    "eames-token:permits",
+    "eames-token:pool",
+    "eames-token:purposes",
+    "eames-token:receipts",
+    "eames-token:recipients",
+    "eames-token:renounce",
+    "eames-token:roles",
+    "eames-token:scheduled",
+    "eames-token:sends",
+    "eames-token:sessions",
+    "eames-token:siblings",
+    "eames-token:sinks",
+    "eames-token:streams",
+    "eames-token:subscriptions",
+    "eames-token:transfers",
+    "eames-token:validation",
+    "eames-token:vesting",
+];
+
+
+/// Declare the interfaces of this deployment - cw20 itself, the modules every deployment has, and
+/// those depending on its settings or build. Interfaces no longer supported are removed.
+/// # Arguments
+/// * `storage` - the contract storage, with the modules' settings
+/// # Returns
+/// * unit on Ok
+/// * the standard error type on Err
+pub fn declare_interfaces(storage: &mut dyn Storage) -> StdResult<()> {
+    let mut interfaces = vec![("cw20", "1.1.0")];
+    interfaces.extend(MODULES.iter().map(|module| (*module, MODULES_VERSION)));
+    if TREASURY.may_load(storage)?.is_some() {
+        interfaces.push(("eames-token:treasury", MODULES_VERSION));
+    }
+    if FAUCET.may_load(storage)?.unwrap_or_default() {
+        interfaces.push(("eames-token:faucet", MODULES_VERSION));
+    }
+    if cfg!(any(debug_assertions, feature = "invariants")) {
+        interfaces.push(("eames-token:invariants", MODULES_VERSION));
+    }
+    if cfg!(any(debug_assertions, feature = "snapshot")) {
+        interfaces.push(("eames-token:snapshot", MODULES_VERSION));
+    }
+
+    let declared = SUPPORTED_INTERFACES
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for name in declared {
+        SUPPORTED_INTERFACES.remove(storage, &name);
+    }
+    for (name, version) in interfaces {
+        SUPPORTED_INTERFACES.save(storage, name, &version.to_string())?;
+    }
+    Ok(())
+}
+
+
+/// Query the interfaces this deployment supports.
+/// # Arguments
+/// * `deps` - dependency which has the storage (state) of the chain
+/// # Returns
+///   The supported interfaces with their version, by name
+pub fn query_supported_interfaces(deps: Deps) -> StdResult<SupportedInterfacesResponse> {
+    let interfaces = SUPPORTED_INTERFACES
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(name, version)| SupportedInterface { name, version }))
+        .collect::<StdResult<_>>()?;
+    Ok(SupportedInterfacesResponse { interfaces })
+}
@@ -0,0 +1,153 @@
+/*
+Invariants module - a proof harness for integration tests and auditors. It checks that the balances
+sum up to the total supply and match the holder count, and that both allowance maps (by owner and by
+spender) mirror each other. The state is walked a bounded number of entries at a time, each check
+returning a cursor to continue from, so that it stays within the query gas limit on large tokens.
+Only built in debug builds, or in release builds with the "invariants" feature (testnets).
+*/
+
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, Deps, Order, StdResult, Uint128};
+use cw_storage_plus::Bound;
+use cw20_base::state::{ALLOWANCES, ALLOWANCES_SPENDER, BALANCES, TOKEN_INFO};
+
+use crate::msg::InvariantsResponse;
+use crate::state::HOLDER_COUNT;
+
+// Settings for pagination
+const MAX_LIMIT: u32 = 100;
+const DEFAULT_LIMIT: u32 = 30;
+
+/// Where a check left off, carrying what was tallied so far
+#[cw_serde]
+pub enum InvariantsCursor {
+    /// Summing up the balances
+    Balances {
+        start_after : Option<String>,
+        sum         : Uint128,
+        holders     : u64,
+    },
+    /// Checking that each allowance by owner is mirrored by spender
+    Allowances { start_after: Option<(String, String)> },
+    /// Checking that each allowance by spender is mirrored by owner
+    SpenderAllowances { start_after: Option<(String, String)> },
+}
+
+impl Default for InvariantsCursor {
+    fn default() -> Self {
+        InvariantsCursor::Balances { start_after: None, sum: Uint128::zero(), holders: 0 }
+    }
+}
+
+
+/// Check the invariants over up to `limit` entries, from the cursor on.
+/// # Arguments
+/// * `deps`   - dependency which has the storage (state) of the chain
+/// * `cursor` - where the previous check left off, None to start over
+/// * `limit`  - the number of entries to walk
+/// # Returns
+///   The violations found in the entries walked, and the cursor to continue from
+pub fn query_check_invariants(
+    deps   : Deps,
+    cursor : Option<InvariantsCursor>,
+    limit  : Option<u32>,
+) -> StdResult<InvariantsResponse> {
+    let mut budget = limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT) as usize;
+    let mut violations = vec![];
+    let mut cursor = cursor.unwrap_or_default();
+
+    // each phase walks what the budget allows, and returns early if it could not finish
+    loop {
+        cursor = match cursor {
+            InvariantsCursor::Balances { start_after, mut sum, mut holders } => {
+                let start_addr = start_after.map(Addr::unchecked);
+                let start = start_addr.as_ref().map(Bound::exclusive);
+                let page = BALANCES
+                    .range(deps.storage, start, None, Order::Ascending)
+                    .take(budget)
+                    .collect::<StdResult<Vec<_>>>()?;
+                for (_, balance) in &page {
+                    sum += *balance;
+                    holders += u64::from(!balance.is_zero());
+                }
+                if page.len() == budget {
+                    let start_after = page.last().map(|(addr, _)| addr.to_string());
+                    let next = InvariantsCursor::Balances { start_after, sum, holders };
+                    return Ok(InvariantsResponse { violations, next: Some(next) });
+                }
+                budget -= page.len();
+
+                // all balances summed up
+                let total_supply = TOKEN_INFO.load(deps.storage)?.total_supply;
+                if sum != total_supply {
+                    violations.push(format!(
+                        "balances sum to {} but the total supply is {}", sum, total_supply
+                    ));
+                }
+                let holder_count = HOLDER_COUNT.may_load(deps.storage)?.unwrap_or_default();
+                if holders != holder_count {
+                    violations.push(format!(
+                        "{} accounts hold tokens but the holder count is {}", holders, holder_count
+                    ));
+                }
+                InvariantsCursor::Allowances { start_after: None }
+            }
+
+            InvariantsCursor::Allowances { start_after } => {
+                let start_keys = start_after.map(unchecked_pair);
+                let start = start_keys.as_ref().map(|(first, second)| Bound::exclusive((first, second)));
+                let page = ALLOWANCES
+                    .range(deps.storage, start, None, Order::Ascending)
+                    .take(budget)
+                    .collect::<StdResult<Vec<_>>>()?;
+                for ((owner, spender), allowance) in &page {
+                    let mirrored = ALLOWANCES_SPENDER.may_load(deps.storage, (spender, owner))?;
+                    if mirrored.as_ref() != Some(allowance) {
+                        violations.push(format!(
+                            "allowance of {} to {} is not mirrored by spender", owner, spender
+                        ));
+                    }
+                }
+                if page.len() == budget {
+                    let start_after = page.last().map(|(keys, _)| string_pair(keys));
+                    let next = InvariantsCursor::Allowances { start_after };
+                    return Ok(InvariantsResponse { violations, next: Some(next) });
+                }
+                budget -= page.len();
+                InvariantsCursor::SpenderAllowances { start_after: None }
+            }
+
+            InvariantsCursor::SpenderAllowances { start_after } => {
+                let start_keys = start_after.map(unchecked_pair);
+                let start = start_keys.as_ref().map(|(first, second)| Bound::exclusive((first, second)));
+                let page = ALLOWANCES_SPENDER
+                    .range(deps.storage, start, None, Order::Ascending)
+                    .take(budget)
+                    .collect::<StdResult<Vec<_>>>()?;
+                for ((spender, owner), _) in &page {
+                    if !ALLOWANCES.has(deps.storage, (owner, spender)) {
+                        violations.push(format!(
+                            "allowance of {} to {} is not mirrored by owner", owner, spender
+                        ));
+                    }
+                }
+                let next = (page.len() == budget).then(|| InvariantsCursor::SpenderAllowances {
+                    start_after: page.last().map(|(keys, _)| string_pair(keys)),
+                });
+                return Ok(InvariantsResponse { violations, next });
+            }
+        };
+    }
+}
+
+
+/// An allowance key from its cursor representation
+fn unchecked_pair((first, second): (String, String)) -> (Addr, Addr) {
+    (Addr::unchecked(first), Addr::unchecked(second))
+}
+
+
+/// The cursor representation of an allowance key
+fn string_pair((first, second): &(Addr, Addr)) -> (String, String) {
+    (first.to_string(), second.to_string())
+}
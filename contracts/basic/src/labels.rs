@@ -0,0 +1,97 @@
+/*
+Labels module - account nicknames. Any account sets a short label on itself, such as the name of a
+treasury or operations wallet, which the extended balance queries return alongside the balances so
+that internal tooling and explorers show human-friendly names. Labels are set by the accounts
+themselves, so they are not verified in any way.
+*/
+
+use cosmwasm_std::{Addr, Deps, DepsMut, MessageInfo, Order, Response, StdResult};
+use cw_storage_plus::{Bound, Map};
+use cw20_base::state::BALANCES;
+
+use crate::error::ContractError;
+use crate::msg::{AccountExtendedResponse, AccountsExtendedResponse, BalanceExtendedResponse};
+
+// Settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+
+/// Maximum size of a label, in bytes
+pub const MAX_LABEL_LEN: usize = 64;
+
+/// The label of each account
+pub const LABELS: Map<&Addr, String> = Map::new("labels");
+
+
+/// Set label - the sender sets the label of their account, or removes it with an empty one.
+/// # Arguments
+/// * `deps`  - mutable dependency which has the storage (state) of the chain
+/// * `info`  - message info, the sender is the account
+/// * `label` - the label
+/// # Returns
+/// * the execute response on Ok
+/// * the error type on Err
+pub fn execute_set_label(
+    deps  : DepsMut,
+    info  : MessageInfo,
+    label : String,
+) -> Result<Response, ContractError> {
+    if label.len() > MAX_LABEL_LEN {
+        return Err(ContractError::LabelTooLong { max: MAX_LABEL_LEN });
+    }
+    match label.is_empty() {
+        true => LABELS.remove(deps.storage, &info.sender),
+        false => LABELS.save(deps.storage, &info.sender, &label)?,
+    }
+    Ok(Response::new()
+        .add_attribute("action", "set_label")
+        .add_attribute("account", info.sender)
+        .add_attribute("label", label))
+}
+
+
+/// Query the balance of an address, along with its label.
+/// # Arguments
+/// * `deps`    - dependency which has the storage (state) of the chain
+/// * `address` - the address
+/// # Returns
+///   The balance and the label of the address
+pub fn query_balance_extended(deps: Deps, address: String) -> StdResult<BalanceExtendedResponse> {
+    let addr = deps.api.addr_validate(&address)?;
+    Ok(BalanceExtendedResponse {
+        balance : BALANCES.may_load(deps.storage, &addr)?.unwrap_or_default(),
+        label   : LABELS.may_load(deps.storage, &addr)?,
+    })
+}
+
+
+/// Query the accounts holding tokens, with their balance and label.
+/// # Arguments
+/// * `deps`        - dependency which has the storage (state) of the chain
+/// * `start_after` - the address to start after
+/// * `limit`       - the list size limit
+/// # Returns
+///   The list of accounts, with their balance and label
+pub fn query_all_accounts_extended(
+    deps        : Deps,
+    start_after : Option<String>,
+    limit       : Option<u32>,
+) -> StdResult<AccountsExtendedResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_addr = start_after.map(Addr::unchecked);
+    let start = start_addr.as_ref().map(Bound::exclusive);
+
+    let accounts = BALANCES
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (address, balance) = item?;
+            Ok(AccountExtendedResponse {
+                label   : LABELS.may_load(deps.storage, &address)?,
+                address : address.into(),
+                balance,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+    Ok(AccountsExtendedResponse { accounts })
+}
@@ -0,0 +1,227 @@
+/*
+Ledger module - a record of every balance change, by block height, so that auditors can reconstruct
+the balance changes over a range of blocks from the contract state rather than by replaying every
+transaction. Recording is off until the admin sets the retention, the number of blocks records are
+kept for. Older records are pruned a few at a time as new ones are written, keeping the ledger
+bounded.
+*/
+
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{
+    Addr, BlockInfo, Deps, DepsMut, MessageInfo, Order, Response, StdResult, Storage, Uint128
+};
+use cw_storage_plus::{Bound, Item, Map};
+use cw20_base::state::BALANCES;
+
+use crate::error::ContractError;
+use crate::msg::{LedgerDeltaResponse, LedgerRecordResponse};
+use crate::state::assert_admin;
+
+// Settings for pagination
+const MAX_LIMIT: u32 = 100;
+const DEFAULT_LIMIT: u32 = 30;
+
+// most records pruned with every write, so that pruning never costs much gas at once
+const PRUNE_PER_WRITE: usize = 10;
+
+/// Number of blocks the records are kept for, recording being off if unset
+pub const LEDGER_RETENTION: Item<u64> = Item::new("ledger_retention");
+
+/// A balance change
+#[cw_serde]
+pub struct LedgerRecord {
+    pub address : Addr,
+    /// Balance before the change
+    pub before  : Uint128,
+    /// Balance after the change
+    pub after   : Uint128,
+}
+
+/// The balance changes, by block height and order within the block
+pub const LEDGER: Map<(u64, u32), LedgerRecord> = Map::new("ledger");
+
+/// Position of a record in the ledger, to page through it
+#[cw_serde]
+pub struct LedgerCursor {
+    pub height : u64,
+    pub index  : u32,
+}
+
+
+/// Set the ledger retention - admin only. Records are pruned once older than the retention, and
+/// recording stops without one, the records kept being pruned as soon as it is set again.
+/// # Arguments
+/// * `deps`   - mutable dependency which has the storage (state) of the chain
+/// * `info`   - message info, the sender must be the admin
+/// * `blocks` - the number of blocks records are kept for, None to stop recording
+/// # Returns
+/// * the execute response on Ok
+/// * the error type on Err
+pub fn execute_set_ledger_retention(
+    deps   : DepsMut,
+    info   : MessageInfo,
+    blocks : Option<u64>,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.storage, &info.sender)?;
+    match blocks {
+        Some(0) => return Err(ContractError::InvalidLedgerRetention {}),
+        Some(blocks) => LEDGER_RETENTION.save(deps.storage, &blocks)?,
+        None => LEDGER_RETENTION.remove(deps.storage),
+    }
+    Ok(Response::new()
+        .add_attribute("action", "set_ledger_retention")
+        .add_attribute("blocks", blocks.map_or("none".to_string(), |b| b.to_string())))
+}
+
+
+/// The balances of the given accounts, to be compared after they changed with `record_ledger`.
+/// None if recording is off.
+pub fn ledger_snapshot(
+    storage  : &dyn Storage,
+    accounts : &[Addr],
+) -> StdResult<Option<Vec<(Addr, Uint128)>>> {
+    if !LEDGER_RETENTION.exists(storage) {
+        return Ok(None);
+    }
+    accounts
+        .iter()
+        .map(|addr| Ok((addr.clone(), BALANCES.may_load(storage, addr)?.unwrap_or_default())))
+        .collect::<StdResult<_>>()
+        .map(Some)
+}
+
+
+/// Record the balances that changed since the snapshot was taken, and prune the records past the
+/// retention.
+/// # Arguments
+/// * `storage`  - the contract storage
+/// * `block`    - the current block, whose height the records are kept at
+/// * `snapshot` - the balances before the change, None if recording is off
+/// # Returns
+/// * unit on Ok
+/// * the standard error type on Err
+pub fn record_ledger(
+    storage  : &mut dyn Storage,
+    block    : &BlockInfo,
+    snapshot : Option<Vec<(Addr, Uint128)>>,
+) -> StdResult<()> {
+    let (snapshot, retention) = match (snapshot, LEDGER_RETENTION.may_load(storage)?) {
+        (Some(snapshot), Some(retention)) => (snapshot, retention),
+        _ => return Ok(()),
+    };
+
+    // several transactions may change balances within the same block
+    let mut index = LEDGER
+        .prefix(block.height)
+        .keys(storage, None, None, Order::Descending)
+        .next()
+        .transpose()?
+        .map_or(0, |last| last + 1);
+    for (address, before) in snapshot {
+        let after = BALANCES.may_load(storage, &address)?.unwrap_or_default();
+        if after != before {
+            LEDGER.save(storage, (block.height, index), &LedgerRecord { address, before, after })?;
+            index += 1;
+        }
+    }
+
+    let cutoff = block.height.saturating_sub(retention);
+    let expired = LEDGER
+        .keys(storage, None, Some(Bound::exclusive((cutoff, 0))), Order::Ascending)
+        .take(PRUNE_PER_WRITE)
+        .collect::<StdResult<Vec<_>>>()?;
+    for key in expired {
+        LEDGER.remove(storage, key);
+    }
+    Ok(())
+}
+
+
+/// Query the balance changes within a block range, in the order they were made.
+/// # Arguments
+/// * `deps`        - dependency which has the storage (state) of the chain
+/// * `from_height` - the first block of the range
+/// * `to_height`   - the last block of the range
+/// * `start_after` - the record to start after
+/// * `limit`       - the list size limit
+/// # Returns
+///   The list of balance changes
+pub fn query_ledger_delta(
+    deps        : Deps,
+    from_height : u64,
+    to_height   : u64,
+    start_after : Option<LedgerCursor>,
+    limit       : Option<u32>,
+) -> StdResult<LedgerDeltaResponse> {
+    ledger_records(deps, None, from_height, to_height, start_after, limit)
+}
+
+
+/// Query the balance changes of an account within a block range, in the order they were made. The
+/// history of an account is private data, only queried with a permit it signed.
+/// # Arguments
+/// * `deps`        - dependency which has the storage (state) of the chain
+/// * `address`     - the account, as verified from the permit
+/// * `from_height` - the first block of the range
+/// * `to_height`   - the last block of the range
+/// * `start_after` - the record to start after
+/// * `limit`       - the list size limit
+/// # Returns
+///   The list of balance changes of the account
+pub fn query_account_ledger(
+    deps        : Deps,
+    address     : &Addr,
+    from_height : u64,
+    to_height   : u64,
+    start_after : Option<LedgerCursor>,
+    limit       : Option<u32>,
+) -> StdResult<LedgerDeltaResponse> {
+    ledger_records(deps, Some(address), from_height, to_height, start_after, limit)
+}
+
+
+/// The records within a block range, of a single account if given.
+fn ledger_records(
+    deps        : Deps,
+    address     : Option<&Addr>,
+    from_height : u64,
+    to_height   : u64,
+    start_after : Option<LedgerCursor>,
+    limit       : Option<u32>,
+) -> StdResult<LedgerDeltaResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = match start_after {
+        Some(LedgerCursor { height, index }) if height >= from_height => {
+            (height, index.saturating_add(1))
+        }
+        _ => (from_height, 0),
+    };
+    let end = (to_height.saturating_add(1), 0);
+    if start >= end {
+        return Ok(LedgerDeltaResponse { records: vec![] });
+    }
+
+    let records = LEDGER
+        .range(
+            deps.storage,
+            Some(Bound::inclusive(start)),
+            Some(Bound::exclusive(end)),
+            Order::Ascending,
+        )
+        .filter(|item| match (item, address) {
+            (Ok((_, record)), Some(address)) => record.address == *address,
+            _ => true,
+        })
+        .take(limit)
+        .map(|item| {
+            item.map(|((height, index), record)| LedgerRecordResponse {
+                height,
+                index,
+                address : record.address.into(),
+                before  : record.before,
+                after   : record.after,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+    Ok(LedgerDeltaResponse { records })
+}
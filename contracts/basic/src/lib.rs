@@ -1,2 +1,50 @@
+pub mod basket;
+pub mod batch;
+pub mod buyback;
 pub mod contract;
+pub mod converter;
+pub mod corridors;
+pub mod dependents;
+pub mod display;
+pub mod faucet;
+pub mod flash;
+pub mod guardian;
+pub mod helpers;
+pub mod history;
+pub mod interfaces;
+#[cfg(any(debug_assertions, feature = "invariants"))]
+pub mod invariants;
+pub mod msg;
+pub mod state;
+pub mod labels;
+pub mod ledger;
+pub mod limits;
+pub mod locks;
+pub mod logo;
+pub mod middleware;
+pub mod minters;
+pub mod subscriptions;
+pub mod vesting;
+pub mod names;
+pub mod permits;
+pub mod pool;
+pub mod purposes;
+pub mod receipts;
+pub mod recipients;
+pub mod renounce;
+pub mod roles;
+pub mod scheduled;
+pub mod sends;
+pub mod sessions;
+pub mod siblings;
+#[cfg(any(debug_assertions, feature = "snapshot"))]
+pub mod snapshot;
+pub mod sinks;
+pub mod streams;
+pub mod transfers;
+pub mod treasury;
+pub mod validation;
+mod error;
 mod test;
+
+pub use error::ContractError;
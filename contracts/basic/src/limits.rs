@@ -0,0 +1,173 @@
+/*
+Daily transfer limits module. The compliance role can set a default daily outbound transfer limit
+applied to every address, and per-address limits overriding it. Without either, an address is unlimited, so
+the module is opt-in. Outbound transfers (transfer, send, and their allowance-based variants) are
+accumulated in a window of a day, starting at the first outbound transfer after the previous
+window has ended.
+*/
+
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{
+    Addr, BlockInfo, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Storage, Timestamp,
+    Uint128
+};
+use cw_storage_plus::{Item, Map};
+
+use crate::error::ContractError;
+use crate::msg::RemainingLimitResponse;
+use crate::roles::{assert_role, Role};
+
+/// Length of a limit window, in seconds
+pub const DAY_SECONDS: u64 = 86_400;
+
+/// Amount transferred out by an address within the current window
+#[cw_serde]
+pub struct DailySpent {
+    /// When the window started
+    pub window_start : Timestamp,
+    /// Total transferred out since the window started
+    pub spent        : Uint128,
+}
+
+impl DailySpent {
+    /// Whether the window is still open at the given block
+    pub fn is_active(&self, block: &BlockInfo) -> bool {
+        block.time < self.window_start.plus_seconds(DAY_SECONDS)
+    }
+}
+
+/// Default daily limit for addresses without their own limit
+pub const DEFAULT_DAILY_LIMIT: Item<Uint128> = Item::new("default_daily_limit");
+/// Per-address daily limits, overriding the default
+pub const DAILY_LIMITS: Map<&Addr, Uint128> = Map::new("daily_limits");
+/// Per-address spending in the current window
+pub const DAILY_SPENT: Map<&Addr, DailySpent> = Map::new("daily_spent");
+
+
+/// Get the daily limit of an address - its own limit, or the default one.
+/// # Arguments
+/// * `storage` - the contract storage
+/// * `addr`    - the address
+/// # Returns
+///   The limit, None if the address is unlimited
+pub fn daily_limit(storage: &dyn Storage, addr: &Addr) -> StdResult<Option<Uint128>> {
+    match DAILY_LIMITS.may_load(storage, addr)? {
+        Some(limit) => Ok(Some(limit)),
+        None => DEFAULT_DAILY_LIMIT.may_load(storage),
+    }
+}
+
+
+/// Record an outbound transfer against the daily limit of an address, failing if it would go
+/// over the limit. Does nothing if the address is unlimited.
+/// # Arguments
+/// * `storage` - the contract storage
+/// * `block`   - the current block
+/// * `addr`    - the address transferring out
+/// * `amount`  - the amount transferred out
+/// # Returns
+/// * the error type Err if the limit is exceeded
+pub fn spend_limit(
+    storage : &mut dyn Storage,
+    block   : &BlockInfo,
+    addr    : &Addr,
+    amount  : Uint128,
+) -> Result<(), ContractError> {
+    let limit = match daily_limit(storage, addr)? {
+        Some(limit) => limit,
+        None => return Ok(()),
+    };
+
+    // start a new window if there is none active
+    let mut spent = match DAILY_SPENT.may_load(storage, addr)? {
+        Some(spent) if spent.is_active(block) => spent,
+        _ => DailySpent { window_start: block.time, spent: Uint128::zero() },
+    };
+
+    let remaining = limit.saturating_sub(spent.spent);
+    if amount > remaining {
+        return Err(ContractError::DailyLimitExceeded { limit, remaining });
+    }
+    spent.spent += amount;
+    DAILY_SPENT.save(storage, addr, &spent)?;
+    Ok(())
+}
+
+
+/// Set the default daily limit - compliance role only.
+/// # Arguments
+/// * `deps`  - mutable dependency which has the storage (state) of the chain
+/// * `info`  - message info, the sender must hold the compliance role
+/// * `limit` - the new default limit, None to remove it
+/// # Returns
+/// * the execute response on Ok
+/// * the error type on Err
+pub fn execute_set_default_daily_limit(
+    deps  : DepsMut,
+    info  : MessageInfo,
+    limit : Option<Uint128>,
+) -> Result<Response, ContractError> {
+    assert_role(deps.storage, Role::Compliance, &info.sender)?;
+    match limit {
+        Some(limit) => DEFAULT_DAILY_LIMIT.save(deps.storage, &limit)?,
+        None => DEFAULT_DAILY_LIMIT.remove(deps.storage),
+    }
+    Ok(Response::new()
+        .add_attribute("action", "set_default_daily_limit")
+        .add_attribute("limit", limit.map_or("none".to_string(), |l| l.to_string())))
+}
+
+
+/// Set the daily limit of a specific address - compliance role only.
+/// # Arguments
+/// * `deps`    - mutable dependency which has the storage (state) of the chain
+/// * `info`    - message info, the sender must hold the compliance role
+/// * `address` - the address to set the limit of
+/// * `limit`   - the new limit, None to fall back to the default
+/// # Returns
+/// * the execute response on Ok
+/// * the error type on Err
+pub fn execute_set_daily_limit(
+    deps    : DepsMut,
+    info    : MessageInfo,
+    address : String,
+    limit   : Option<Uint128>,
+) -> Result<Response, ContractError> {
+    assert_role(deps.storage, Role::Compliance, &info.sender)?;
+    let addr = deps.api.addr_validate(&address)?;
+    match limit {
+        Some(limit) => DAILY_LIMITS.save(deps.storage, &addr, &limit)?,
+        None => DAILY_LIMITS.remove(deps.storage, &addr),
+    }
+    Ok(Response::new()
+        .add_attribute("action", "set_daily_limit")
+        .add_attribute("address", addr)
+        .add_attribute("limit", limit.map_or("default".to_string(), |l| l.to_string())))
+}
+
+
+/// Query the daily limit of an address, and what it can still transfer out today.
+/// # Arguments
+/// * `deps`    - dependency which has the storage (state) of the chain
+/// * `env`     - environment variables which include block information
+/// * `address` - the queried address
+/// # Returns
+///   The remaining limit response
+pub fn query_remaining_limit(
+    deps    : Deps,
+    env     : Env,
+    address : String,
+) -> StdResult<RemainingLimitResponse> {
+    let addr = deps.api.addr_validate(&address)?;
+    let limit = daily_limit(deps.storage, &addr)?;
+    let active = DAILY_SPENT
+        .may_load(deps.storage, &addr)?
+        .filter(|spent| spent.is_active(&env.block));
+
+    let spent = active.as_ref().map(|s| s.spent).unwrap_or_default();
+    Ok(RemainingLimitResponse {
+        limit,
+        remaining : limit.map(|l| l.saturating_sub(spent)),
+        resets_at : active.map(|s| s.window_start.plus_seconds(DAY_SECONDS)),
+    })
+}
@@ -0,0 +1,250 @@
+/*
+Locks module - collateral for third-party contracts. An owner locks part of their balance until an
+expiration, designating an unlocker (typically a lending protocol) who alone can release it early.
+Locked tokens stay in the owner's balance but cannot be moved out of it while the lock is active,
+so protocols can rely on them without taking custody. Locks mint deposit receipts to their owner,
+burnt as they are released.
+*/
+
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{
+    Addr, BlockInfo, Deps, DepsMut, Env, MessageInfo, Order, Response, StdResult, Storage, Uint128
+};
+use cw_storage_plus::{Bound, Index, IndexList, IndexedMap, Item, MultiIndex};
+use cw20::Expiration;
+use cw20_base::state::BALANCES;
+
+use crate::error::ContractError;
+use crate::msg::{LockResponse, LockedBalanceResponse, LocksResponse};
+use crate::receipts::{burn_receipt, mint_receipt};
+
+// Settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+
+/// A portion of an owner's balance locked as collateral
+#[cw_serde]
+pub struct Lock {
+    /// The account whose tokens are locked
+    pub owner    : Addr,
+    /// Amount locked
+    pub amount   : Uint128,
+    /// The only account able to release the lock before it expires
+    pub unlocker : Addr,
+    /// The lock stops applying once expired
+    pub until    : Expiration,
+}
+
+/// Secondary indexes of the locks, to query them by owner
+pub struct LockIndexes<'a> {
+    pub owner : MultiIndex<'a, Addr, Lock, u64>,
+}
+
+impl<'a> IndexList<Lock> for LockIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Lock>> + '_> {
+        let v: Vec<&dyn Index<Lock>> = vec![&self.owner];
+        Box::new(v.into_iter())
+    }
+}
+
+/// All locks, indexed by id
+pub fn locks<'a>() -> IndexedMap<'a, u64, Lock, LockIndexes<'a>> {
+    let indexes = LockIndexes {
+        owner: MultiIndex::new(|_, l| l.owner.clone(), "locks", "locks__owner"),
+    };
+    IndexedMap::new("locks", indexes)
+}
+
+/// Last lock id used
+pub const LOCK_COUNT: Item<u64> = Item::new("lock_count");
+
+
+/// Total amount locked by the active (non-expired) locks of an owner.
+/// # Arguments
+/// * `storage` - the contract storage
+/// * `block`   - the current block
+/// * `owner`   - the owner
+/// # Returns
+///   The locked amount
+pub fn locked_amount(storage: &dyn Storage, block: &BlockInfo, owner: &Addr) -> StdResult<Uint128> {
+    locks()
+        .idx
+        .owner
+        .prefix(owner.clone())
+        .range(storage, None, None, Order::Ascending)
+        .try_fold(Uint128::zero(), |total, item| {
+            let (_, lock) = item?;
+            Ok(if lock.until.is_expired(block) { total } else { total + lock.amount })
+        })
+}
+
+
+/// Ensure that an account still holds at least what it has locked - to be called after its balance
+/// has decreased.
+/// # Arguments
+/// * `storage` - the contract storage
+/// * `block`   - the current block
+/// * `owner`   - the account
+/// # Returns
+/// * the error type Err if locked tokens were moved
+pub fn assert_unlocked(
+    storage : &dyn Storage,
+    block   : &BlockInfo,
+    owner   : &Addr,
+) -> Result<(), ContractError> {
+    let locked = locked_amount(storage, block, owner)?;
+    if locked.is_zero() {
+        return Ok(());
+    }
+    let balance = BALANCES.may_load(storage, owner)?.unwrap_or_default();
+    if balance < locked {
+        return Err(ContractError::LockedBalance { locked, balance });
+    }
+    Ok(())
+}
+
+
+/// Lock - the sender locks part of their free balance until `until`, releasable early by the
+/// unlocker only. As many receipts are minted to the sender.
+/// # Arguments
+/// * `deps`     - mutable dependency which has the storage (state) of the chain
+/// * `env`      - environment variables which include block information
+/// * `info`     - message info, the sender is the owner locking
+/// * `amount`   - the amount to lock
+/// * `unlocker` - the address able to release the lock early
+/// * `until`    - when the lock stops applying
+/// # Returns
+/// * the execute response on Ok
+/// * the error type on Err
+pub fn execute_lock(
+    deps     : DepsMut,
+    env      : Env,
+    info     : MessageInfo,
+    amount   : Uint128,
+    unlocker : String,
+    until    : Expiration,
+) -> Result<Response, ContractError> {
+    let unlocker = deps.api.addr_validate(&unlocker)?;
+    if amount.is_zero() || unlocker == info.sender || until.is_expired(&env.block) {
+        return Err(ContractError::InvalidLock {});
+    }
+
+    // only the free balance can be locked
+    let locked = locked_amount(deps.storage, &env.block, &info.sender)? + amount;
+    let balance = BALANCES.may_load(deps.storage, &info.sender)?.unwrap_or_default();
+    if balance < locked {
+        return Err(ContractError::LockedBalance { locked, balance });
+    }
+
+    let id = LOCK_COUNT.may_load(deps.storage)?.unwrap_or_default() + 1;
+    LOCK_COUNT.save(deps.storage, &id)?;
+    let lock = Lock {
+        owner: info.sender,
+        amount,
+        unlocker,
+        until,
+    };
+    locks().save(deps.storage, id, &lock)?;
+    mint_receipt(deps.storage, &lock.owner, amount)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "lock")
+        .add_attribute("lock_id", id.to_string())
+        .add_attribute("owner", lock.owner)
+        .add_attribute("unlocker", lock.unlocker)
+        .add_attribute("amount", amount))
+}
+
+
+/// Unlock - the unlocker releases a lock at any time, and anyone can clear an expired one. The
+/// receipts of the lock are burnt.
+/// # Arguments
+/// * `deps`    - mutable dependency which has the storage (state) of the chain
+/// * `env`     - environment variables which include block information
+/// * `info`    - message info, the sender must be the unlocker unless the lock expired
+/// * `lock_id` - the lock to release
+/// # Returns
+/// * the execute response on Ok
+/// * the error type on Err
+pub fn execute_unlock(
+    deps    : DepsMut,
+    env     : Env,
+    info    : MessageInfo,
+    lock_id : u64,
+) -> Result<Response, ContractError> {
+    let lock = locks().load(deps.storage, lock_id)?;
+    if info.sender != lock.unlocker && !lock.until.is_expired(&env.block) {
+        return Err(ContractError::Unauthorized {});
+    }
+    locks().remove(deps.storage, lock_id)?;
+    burn_receipt(deps.storage, &lock.owner, lock.amount)?;
+    Ok(Response::new()
+        .add_attribute("action", "unlock")
+        .add_attribute("lock_id", lock_id.to_string())
+        .add_attribute("owner", lock.owner)
+        .add_attribute("amount", lock.amount))
+}
+
+
+/// Query the locked and free balance of an address.
+/// # Arguments
+/// * `deps`    - dependency which has the storage (state) of the chain
+/// * `env`     - environment variables which include block information
+/// * `address` - the address
+/// # Returns
+///   The balance, split between locked and free
+pub fn query_locked_balance(
+    deps    : Deps,
+    env     : Env,
+    address : String,
+) -> StdResult<LockedBalanceResponse> {
+    let addr = deps.api.addr_validate(&address)?;
+    let balance = BALANCES.may_load(deps.storage, &addr)?.unwrap_or_default();
+    let locked = locked_amount(deps.storage, &env.block, &addr)?;
+    Ok(LockedBalanceResponse {
+        balance,
+        locked,
+        free: balance.saturating_sub(locked),
+    })
+}
+
+
+/// Query the locks of an owner, including expired ones not cleared yet.
+/// # Arguments
+/// * `deps`        - dependency which has the storage (state) of the chain
+/// * `env`         - environment variables which include block information
+/// * `owner`       - the owner address
+/// * `start_after` - the lock id to start after
+/// * `limit`       - the list size limit
+/// # Returns
+///   The list of locks
+pub fn query_locks(
+    deps        : Deps,
+    env         : Env,
+    owner       : String,
+    start_after : Option<u64>,
+    limit       : Option<u32>,
+) -> StdResult<LocksResponse> {
+    let addr = deps.api.addr_validate(&owner)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let locks = locks()
+        .idx
+        .owner
+        .prefix(addr)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            item.map(|(id, lock)| LockResponse {
+                id,
+                active   : !lock.until.is_expired(&env.block),
+                owner    : lock.owner.into(),
+                amount   : lock.amount,
+                unlocker : lock.unlocker.into(),
+                until    : lock.until,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+    Ok(LocksResponse { locks })
+}
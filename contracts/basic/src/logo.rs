@@ -0,0 +1,116 @@
+/*
+Logo module - a configurable policy on the marketing logo, enforced on top of the Cw20 base checks.
+The metadata role sets the maximum size of embedded logos, the image types allowed, or restricts
+logos to off-chain URLs altogether. Without a policy set, the Cw20 base limits apply as they are.
+*/
+
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+use cw_storage_plus::Item;
+use cw20::{EmbeddedLogo, Logo};
+use cw20_base::contract::execute_upload_logo;
+
+use crate::error::ContractError;
+use crate::roles::{assert_role, Role};
+
+/// Maximum size of embedded logos enforced by Cw20 base, in bytes
+pub const BASE_LOGO_SIZE_CAP: u64 = 5 * 1024;
+
+/// Embedded logo image types
+#[cw_serde]
+#[derive(Copy)]
+pub enum LogoType {
+    Svg,
+    Png,
+}
+
+/// Policy the marketing logo must comply with
+#[cw_serde]
+pub struct LogoPolicy {
+    /// Maximum size of an embedded logo, in bytes - cannot exceed the Cw20 base cap
+    pub max_bytes     : u64,
+    /// Embedded image types allowed
+    pub allowed_types : Vec<LogoType>,
+    /// Whether only off-chain URLs are allowed, rejecting any embedded logo
+    pub url_only      : bool,
+}
+
+impl Default for LogoPolicy {
+    fn default() -> Self {
+        LogoPolicy {
+            max_bytes     : BASE_LOGO_SIZE_CAP,
+            allowed_types : vec![LogoType::Svg, LogoType::Png],
+            url_only      : false,
+        }
+    }
+}
+
+/// The logo policy, the default one if unset
+pub const LOGO_POLICY: Item<LogoPolicy> = Item::new("logo_policy");
+
+
+/// Set the logo policy - metadata role only. It applies to the next logo uploads, not the current
+/// logo.
+/// # Arguments
+/// * `deps`   - mutable dependency which has the storage (state) of the chain
+/// * `info`   - message info, the sender must hold the metadata role
+/// * `policy` - the new policy
+/// # Returns
+/// * the execute response on Ok
+/// * the error type on Err
+pub fn execute_set_logo_policy(
+    deps   : DepsMut,
+    info   : MessageInfo,
+    policy : LogoPolicy,
+) -> Result<Response, ContractError> {
+    assert_role(deps.storage, Role::Metadata, &info.sender)?;
+    if policy.max_bytes > BASE_LOGO_SIZE_CAP {
+        return Err(ContractError::LogoTooBig { max: BASE_LOGO_SIZE_CAP });
+    }
+    LOGO_POLICY.save(deps.storage, &policy)?;
+    Ok(Response::new()
+        .add_attribute("action", "set_logo_policy")
+        .add_attribute("max_bytes", policy.max_bytes.to_string())
+        .add_attribute("url_only", policy.url_only.to_string()))
+}
+
+
+/// Upload logo - checked against the logo policy, then uploaded as with Cw20 base.
+/// # Arguments
+/// * `deps` - mutable dependency which has the storage (state) of the chain
+/// * `env`  - environment variables which include block information
+/// * `info` - message info, the sender must be the marketing address
+/// * `logo` - the logo
+/// # Returns
+/// * the execute response on Ok
+/// * the error type on Err
+pub fn execute_upload_logo_checked(
+    deps : DepsMut,
+    env  : Env,
+    info : MessageInfo,
+    logo : Logo,
+) -> Result<Response, ContractError> {
+    let policy = LOGO_POLICY.may_load(deps.storage)?.unwrap_or_default();
+    let (logo_type, data) = match &logo {
+        Logo::Url(_) => return Ok(execute_upload_logo(deps, env, info, logo)?),
+        Logo::Embedded(EmbeddedLogo::Svg(data)) => (LogoType::Svg, data),
+        Logo::Embedded(EmbeddedLogo::Png(data)) => (LogoType::Png, data),
+    };
+    if policy.url_only || !policy.allowed_types.contains(&logo_type) {
+        return Err(ContractError::LogoNotAllowed {});
+    }
+    if data.len() as u64 > policy.max_bytes {
+        return Err(ContractError::LogoTooBig { max: policy.max_bytes });
+    }
+    Ok(execute_upload_logo(deps, env, info, logo)?)
+}
+
+
+/// Query the logo policy.
+/// # Arguments
+/// * `deps` - dependency which has the storage (state) of the chain
+/// # Returns
+///   The logo policy
+pub fn query_logo_policy(deps: Deps) -> StdResult<LogoPolicy> {
+    Ok(LOGO_POLICY.may_load(deps.storage)?.unwrap_or_default())
+}
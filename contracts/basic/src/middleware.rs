@@ -0,0 +1,383 @@
+/*
+Middleware module - the pipeline every execute message goes through around its handler. The
+cross-cutting modules, those applying to any message moving tokens rather than to messages of their
+own, are stages of the pipeline instead of checks spread across the handlers: each stage runs in
+order before the message is handled, to reject it or take what it needs, and again in the same order
+once it is handled, to reject it or complete its response. A new cross-cutting module only needs
+registering its stage in `pipeline`.
+*/
+
+use cosmwasm_std::{
+    Addr, Deps, DepsMut, Env, Event, MessageInfo, Response, Uint128
+};
+
+use crate::corridors::assert_corridor;
+use crate::error::ContractError;
+use crate::guardian::assert_not_paused;
+use crate::history::record_supply;
+use crate::ledger::{ledger_snapshot, record_ledger};
+use crate::limits::spend_limit;
+use crate::locks::assert_unlocked;
+use crate::msg::ExecuteMsg;
+use crate::names::resolve_recipient;
+use crate::renounce::settle_renounce;
+use crate::scheduled::scheduled_transfers;
+use crate::state::{holders_snapshot, update_holder_count};
+use crate::subscriptions::subscriptions;
+use crate::treasury::SPEND_PROPOSALS;
+
+/// The handler of an execute message, at the end of the pipeline
+pub type Handler = fn(DepsMut, Env, MessageInfo, ExecuteMsg) -> Result<Response, ContractError>;
+
+/// An execute message going through the pipeline
+pub struct ExecuteContext<'a> {
+    pub env     : &'a Env,
+    pub info    : &'a MessageInfo,
+    pub msg     : &'a ExecuteMsg,
+    /// The accounts whose balance may change with the message
+    pub touched : Vec<Addr>,
+}
+
+/// A stage of the pipeline. Either step does nothing unless the stage implements it.
+pub trait Middleware {
+    /// Run before the message is handled, rejecting it on Err
+    fn before(&mut self, _deps: DepsMut, _ctx: &ExecuteContext) -> Result<(), ContractError> {
+        Ok(())
+    }
+
+    /// Run once the message is handled, with its response, rejecting it on Err
+    fn after(
+        &mut self,
+        _deps : DepsMut,
+        _ctx  : &ExecuteContext,
+        res   : Response,
+    ) -> Result<Response, ContractError> {
+        Ok(res)
+    }
+}
+
+
+/// The stages of the pipeline, in the order they run in.
+pub fn pipeline() -> Vec<Box<dyn Middleware>> {
+    vec![
+        Box::new(RenounceStage::default()),
+        Box::new(PauseStage),
+        Box::new(LimitsStage),
+        Box::new(HoldersStage::default()),
+        Box::new(LedgerStage::default()),
+        Box::new(LocksStage),
+        Box::new(SupplyStage),
+    ]
+}
+
+
+/// Run an execute message through the pipeline, to its handler.
+/// # Arguments
+/// * `deps`    - mutable dependency which has the storage (state) of the chain
+/// * `env`     - environment variables which include block information
+/// * `info`    - message info, such as sender/initiator and denomination
+/// * `msg`     - the execute message
+/// * `handler` - the handler of the message
+/// # Returns
+/// * the execute response on Ok
+/// * the error type on Err
+pub fn run_pipeline(
+    mut deps : DepsMut,
+    env      : Env,
+    info     : MessageInfo,
+    msg      : ExecuteMsg,
+    handler  : Handler,
+) -> Result<Response, ContractError> {
+    let ctx = ExecuteContext {
+        touched : touched_accounts(deps.as_ref(), &env, &info, &msg),
+        env     : &env,
+        info    : &info,
+        msg     : &msg,
+    };
+    let mut stages = pipeline();
+    for stage in stages.iter_mut() {
+        stage.before(deps.branch(), &ctx)?;
+    }
+    let mut res = handler(deps.branch(), env.clone(), info.clone(), msg.clone())?;
+    for stage in stages.iter_mut() {
+        res = stage.after(deps.branch(), &ctx, res)?;
+    }
+    Ok(res)
+}
+
+
+/// Renounce stage - a renouncement that came due goes through before anything else, with its
+/// privileges (see the renounce module).
+#[derive(Default)]
+struct RenounceStage {
+    event: Option<Event>,
+}
+
+impl Middleware for RenounceStage {
+    fn before(&mut self, deps: DepsMut, ctx: &ExecuteContext) -> Result<(), ContractError> {
+        self.event = settle_renounce(deps, &ctx.env.block)?;
+        Ok(())
+    }
+
+    fn after(
+        &mut self,
+        _deps : DepsMut,
+        _ctx  : &ExecuteContext,
+        res   : Response,
+    ) -> Result<Response, ContractError> {
+        Ok(match self.event.take() {
+            Some(event) => res.add_event(event),
+            None => res,
+        })
+    }
+}
+
+
+/// Pause stage - nothing moves while the token is paused (see the guardian module).
+struct PauseStage;
+
+impl Middleware for PauseStage {
+    fn before(&mut self, deps: DepsMut, ctx: &ExecuteContext) -> Result<(), ContractError> {
+        if moves_tokens(ctx.msg) {
+            assert_not_paused(deps.storage)?;
+        }
+        Ok(())
+    }
+}
+
+
+/// Limits stage - outbound transfers must be along a whitelisted corridor, if the corridor mode is
+/// on (see the corridors module), and within the owner's daily limit (see the limits module).
+/// Messages whose owner or amount only their handler knows, such as transfers signed by a session
+/// key or to a resolved name, are checked by their handler.
+struct LimitsStage;
+
+impl Middleware for LimitsStage {
+    fn before(&mut self, deps: DepsMut, ctx: &ExecuteContext) -> Result<(), ContractError> {
+        if let Some((owner, recipient, amount)) = outbound(deps.as_ref(), ctx.info, ctx.msg) {
+            assert_corridor(deps.as_ref(), &owner, recipient)?;
+            spend_limit(deps.storage, &ctx.env.block, &owner, amount)?;
+        }
+        Ok(())
+    }
+}
+
+
+/// Holders stage - keep track of the accounts starting or stopping to hold tokens with the message.
+#[derive(Default)]
+struct HoldersStage {
+    snapshot: Vec<(Addr, bool)>,
+}
+
+impl Middleware for HoldersStage {
+    fn before(&mut self, deps: DepsMut, ctx: &ExecuteContext) -> Result<(), ContractError> {
+        self.snapshot = holders_snapshot(deps.storage, ctx.touched.clone())?;
+        Ok(())
+    }
+
+    fn after(
+        &mut self,
+        deps : DepsMut,
+        _ctx : &ExecuteContext,
+        res  : Response,
+    ) -> Result<Response, ContractError> {
+        update_holder_count(deps.storage, std::mem::take(&mut self.snapshot))?;
+        Ok(res)
+    }
+}
+
+
+/// Ledger stage - record the balance changes of the message, if recording (see the ledger module).
+#[derive(Default)]
+struct LedgerStage {
+    balances: Option<Vec<(Addr, Uint128)>>,
+}
+
+impl Middleware for LedgerStage {
+    fn before(&mut self, deps: DepsMut, ctx: &ExecuteContext) -> Result<(), ContractError> {
+        self.balances = ledger_snapshot(deps.storage, &ctx.touched)?;
+        Ok(())
+    }
+
+    fn after(
+        &mut self,
+        deps : DepsMut,
+        ctx  : &ExecuteContext,
+        res  : Response,
+    ) -> Result<Response, ContractError> {
+        record_ledger(deps.storage, &ctx.env.block, self.balances.take())?;
+        Ok(res)
+    }
+}
+
+
+/// Locks stage - none of the accounts may have moved their locked tokens (see the locks module).
+struct LocksStage;
+
+impl Middleware for LocksStage {
+    fn after(
+        &mut self,
+        deps : DepsMut,
+        ctx  : &ExecuteContext,
+        res  : Response,
+    ) -> Result<Response, ContractError> {
+        for addr in &ctx.touched {
+            assert_unlocked(deps.storage, &ctx.env.block, addr)?;
+        }
+        Ok(res)
+    }
+}
+
+
+/// Supply stage - record the total supply, should the message have changed it (see the history
+/// module).
+struct SupplyStage;
+
+impl Middleware for SupplyStage {
+    fn after(
+        &mut self,
+        deps : DepsMut,
+        ctx  : &ExecuteContext,
+        res  : Response,
+    ) -> Result<Response, ContractError> {
+        record_supply(deps.storage, &ctx.env.block)?;
+        Ok(res)
+    }
+}
+
+
+/// The owner, recipient and amount of an outbound transfer the limits stage checks, if the message
+/// is one. Transfers moving nothing, or to the sender itself, are left to the transfer policy, and
+/// owners that are not valid to the handler, which fails on them.
+fn outbound<'a>(
+    deps : Deps,
+    info : &MessageInfo,
+    msg  : &'a ExecuteMsg,
+) -> Option<(Addr, &'a str, Uint128)> {
+    let (owner, recipient, amount) = match msg {
+        ExecuteMsg::Transfer { recipient, amount, .. }
+        | ExecuteMsg::TransferWithMemo { recipient, amount, .. }
+        | ExecuteMsg::Send { contract: recipient, amount, .. } => {
+            if amount.is_zero() || info.sender.as_str() == recipient {
+                return None;
+            }
+            (info.sender.clone(), recipient, amount)
+        }
+        ExecuteMsg::TransferFrom { owner, recipient, amount }
+        | ExecuteMsg::SendFrom { owner, contract: recipient, amount, .. } => {
+            (deps.api.addr_validate(owner).ok()?, recipient, amount)
+        }
+        ExecuteMsg::ScheduleTransfer { recipient, amount, .. } => {
+            (info.sender.clone(), recipient, amount)
+        }
+        _ => return None,
+    };
+    Some((owner, recipient.as_str(), *amount))
+}
+
+
+
+/// Whether an execute message moves, mints or burns tokens - which pausing stops.
+fn moves_tokens(msg: &ExecuteMsg) -> bool {
+    matches!(
+        msg,
+        ExecuteMsg::Transfer { .. }
+            | ExecuteMsg::TransferWithMemo { .. }
+            | ExecuteMsg::Send { .. }
+            | ExecuteMsg::TransferFrom { .. }
+            | ExecuteMsg::SendFrom { .. }
+            | ExecuteMsg::SendFromBatch { .. }
+            | ExecuteMsg::Burn { .. }
+            | ExecuteMsg::BurnFrom { .. }
+            | ExecuteMsg::Mint { .. }
+            | ExecuteMsg::TransferToName { .. }
+            | ExecuteMsg::SessionTransfer { .. }
+            | ExecuteMsg::Collect { .. }
+            | ExecuteMsg::BuybackAndBurn { .. }
+            | ExecuteMsg::MintVested { .. }
+            | ExecuteMsg::ClaimVested {}
+            | ExecuteMsg::MintToPool { .. }
+            | ExecuteMsg::ProposeSpend { .. }
+            | ExecuteMsg::ApproveSpend { .. }
+            | ExecuteMsg::ScheduleTransfer { .. }
+            | ExecuteMsg::ExecuteScheduled { .. }
+            | ExecuteMsg::CancelScheduled { .. }
+            | ExecuteMsg::Deposit {}
+            | ExecuteMsg::Redeem { .. }
+            | ExecuteMsg::FlashMint { .. }
+            | ExecuteMsg::Convert { .. }
+            | ExecuteMsg::FaucetMint { .. }
+    )
+}
+
+
+/// The accounts whose balance may change with an execute message. Addresses that are not valid
+/// are skipped, since the message itself will fail on them.
+/// # Arguments
+/// * `deps` - dependency which has the storage (state) of the chain
+/// * `env`  - environment variables, with this contract's address
+/// * `info` - message info, such as sender/initiator and denomination
+/// * `msg`  - the execute message
+/// # Returns
+///   The list of accounts
+fn touched_accounts(deps: Deps, env: &Env, info: &MessageInfo, msg: &ExecuteMsg) -> Vec<Addr> {
+    let contract = env.contract.address.as_str();
+    let accounts: Vec<&str> = match msg {
+        ExecuteMsg::Transfer { recipient, .. } => vec![info.sender.as_str(), recipient],
+        ExecuteMsg::TransferWithMemo { recipient, .. } => vec![info.sender.as_str(), recipient],
+        ExecuteMsg::Send { contract, .. } => vec![info.sender.as_str(), contract],
+        ExecuteMsg::TransferFrom { owner, recipient, .. } => vec![owner, recipient],
+        ExecuteMsg::SendFrom { owner, contract, .. } => vec![owner, contract],
+        ExecuteMsg::SendFromBatch { owner, targets } => std::iter::once(owner.as_str())
+            .chain(targets.iter().map(|target| target.contract.as_str()))
+            .collect(),
+        ExecuteMsg::Burn { .. } => vec![info.sender.as_str()],
+        ExecuteMsg::BurnFrom { owner, .. } => vec![owner],
+        ExecuteMsg::Mint { recipient, .. } => vec![recipient],
+        ExecuteMsg::SessionTransfer { owner, recipient, .. } => vec![owner, recipient],
+        ExecuteMsg::MintVested { .. } => vec![contract],
+        ExecuteMsg::ClaimVested {} => vec![contract, info.sender.as_str()],
+        ExecuteMsg::MintToPool { pool_contract, .. } => vec![pool_contract],
+        ExecuteMsg::ProposeSpend { to, .. } => vec![contract, to],
+        ExecuteMsg::ScheduleTransfer { .. } => vec![info.sender.as_str(), contract],
+        ExecuteMsg::CancelScheduled { .. } => vec![contract, info.sender.as_str()],
+        ExecuteMsg::Deposit {} => vec![info.sender.as_str()],
+        ExecuteMsg::Redeem { .. } => vec![info.sender.as_str()],
+        ExecuteMsg::Convert { .. } => vec![info.sender.as_str()],
+        ExecuteMsg::FaucetMint { .. } => vec![info.sender.as_str()],
+        ExecuteMsg::FlashMint { callback_contract, .. } => vec![callback_contract],
+        ExecuteMsg::ExecuteScheduled { id } => {
+            return scheduled_transfers()
+                .load(deps.storage, *id)
+                .map(|transfer| vec![env.contract.address.clone(), transfer.recipient])
+                .unwrap_or_default();
+        }
+        ExecuteMsg::ApproveSpend { id } => {
+            return SPEND_PROPOSALS
+                .load(deps.storage, *id)
+                .map(|proposal| vec![env.contract.address.clone(), proposal.to])
+                .unwrap_or_default();
+        }
+        ExecuteMsg::TransferToName { recipient_name, .. } => {
+            let recipient = resolve_recipient(deps, recipient_name).ok();
+            return [Some(info.sender.clone()), recipient.map(|(addr, _)| addr)]
+                .into_iter()
+                .flatten()
+                .collect();
+        }
+        ExecuteMsg::Collect { subscription_id } => {
+            return subscriptions()
+                .load(deps.storage, *subscription_id)
+                .map(|s| vec![s.owner, s.payee])
+                .unwrap_or_default();
+        }
+        _ => vec![],
+    };
+    let mut addrs: Vec<Addr> = accounts
+        .into_iter()
+        .filter_map(|addr| deps.api.addr_validate(addr).ok())
+        .collect();
+    addrs.sort();
+    addrs.dedup();
+    addrs
+}
@@ -0,0 +1,74 @@
+/*
+Minters module - revocation list of the minters rotated out, or stripped of the minter role, e.g.
+after their key was compromised. A revoked address can never mint again, nor become the token's
+minter or be granted the minter role, unless the admin restores it through the timelock. Since a
+failed transaction keeps no events, its mint attempts fail with a dedicated error for monitoring to
+alert on.
+*/
+
+use cosmwasm_std::{Addr, Deps, Order, StdResult, Storage, Timestamp};
+use cw_storage_plus::{Bound, Map};
+
+use crate::error::ContractError;
+use crate::msg::{RevokedMinter, RevokedMintersResponse};
+
+// Settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+
+/// Revoked minters, with the time they were revoked at
+pub const REVOKED_MINTERS: Map<&Addr, Timestamp> = Map::new("revoked_minters");
+
+
+/// Revoke a minter, for good unless restored through the timelock.
+pub fn revoke_minter(storage: &mut dyn Storage, minter: &Addr, time: Timestamp) -> StdResult<()> {
+    REVOKED_MINTERS.save(storage, minter, &time)
+}
+
+
+/// Ensure that an address about to become a minter was not revoked
+pub fn assert_not_revoked(storage: &dyn Storage, addr: &Addr) -> Result<(), ContractError> {
+    if REVOKED_MINTERS.has(storage, addr) {
+        return Err(ContractError::MinterRevoked { address: addr.to_string() });
+    }
+    Ok(())
+}
+
+
+/// Ensure that the sender minting was not revoked, failing with the alert error otherwise
+pub fn assert_not_revoked_mint(storage: &dyn Storage, sender: &Addr) -> Result<(), ContractError> {
+    if REVOKED_MINTERS.has(storage, sender) {
+        return Err(ContractError::RevokedMinterMint { address: sender.to_string() });
+    }
+    Ok(())
+}
+
+
+/// Query the revoked minters.
+/// # Arguments
+/// * `deps`        - dependency which has the storage (state) of the chain
+/// * `start_after` - the address to start after
+/// * `limit`       - the list size limit
+/// # Returns
+///   The list of revoked minters
+pub fn query_revoked_minters(
+    deps        : Deps,
+    start_after : Option<String>,
+    limit       : Option<u32>,
+) -> StdResult<RevokedMintersResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_after = start_after.map(|addr| deps.api.addr_validate(&addr)).transpose()?;
+    let start = start_after.as_ref().map(Bound::exclusive);
+
+    let minters = REVOKED_MINTERS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            item.map(|(address, revoked_at)| RevokedMinter {
+                address : address.into(),
+                revoked_at,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+    Ok(RevokedMintersResponse { minters })
+}
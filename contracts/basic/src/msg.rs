@@ -0,0 +1,1316 @@
+/*
+The request messages sent to the eames token smart contract. These mirror the Cw20 base messages,
+and extend them with the token's own modules.
+*/
+
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::{Binary, Decimal, Timestamp, Uint128};
+use cw20::{Cw20Coin, Expiration, Logo, MinterResponse, TokenInfoResponse};
+
+pub use cw20_base::msg::InstantiateMarketingInfo;
+
+use crate::basket::BasketAsset;
+use crate::batch::SendTarget;
+use crate::corridors::Corridor;
+use crate::dependents::NotifyStatus;
+use crate::guardian::AdminAction;
+#[cfg(any(debug_assertions, feature = "invariants"))]
+use crate::invariants::InvariantsCursor;
+use crate::ledger::LedgerCursor;
+use crate::logo::LogoPolicy;
+use crate::permits::Permit;
+use crate::roles::Role;
+use crate::transfers::TransferPolicy;
+use crate::treasury::{SpendAmount, TreasuryInit};
+use crate::vesting::VestingSchedule;
+
+
+/// The Instantiate message. It includes every Cw20 base field, along with the token's compliance
+/// settings, which can all be left unset.
+#[cw_serde]
+pub struct InstantiateMsg {
+    pub name: String,
+    /// Cannot be one of the reserved tickers of well-known assets, regardless of case
+    pub symbol: String,
+    pub decimals: u8,
+    pub initial_balances: Vec<Cw20Coin>,
+    pub mint: Option<MinterResponse>,
+    pub marketing: Option<InstantiateMarketingInfo>,
+    /// Optional jurisdiction the token is issued under, e.g. an ISO 3166 country code
+    pub jurisdiction: Option<String>,
+    /// Optional address granted the compliance role, along with the instantiator
+    pub compliance_admin: Option<String>,
+    /// Optional default daily outbound transfer limit of every address (see the "limits" module)
+    pub default_daily_limit: Option<Uint128>,
+    /// Optional treasury approvers (see the "treasury" module), without which nothing can be spent
+    pub treasury: Option<TreasuryInit>,
+    /// Whether anyone can mint tokens to themselves (see the "faucet" module), for test deployments
+    /// only. Off if unset
+    #[serde(default)]
+    pub test_faucet: bool,
+}
+
+impl From<InstantiateMsg> for cw20_base::msg::InstantiateMsg {
+    fn from(msg: InstantiateMsg) -> Self {
+        cw20_base::msg::InstantiateMsg {
+            name             : msg.name,
+            symbol           : msg.symbol,
+            decimals         : msg.decimals,
+            initial_balances : msg.initial_balances,
+            mint             : msg.mint,
+            marketing        : msg.marketing,
+        }
+    }
+}
+
+
+/// The Migrate message. Every field can be left unset, as with the Cw20 base one.
+#[cw_serde]
+#[derive(Default)]
+pub struct MigrateMsg {
+    /// Semantics that changed with the version migrated to, for the dependents to be notified of
+    #[serde(default)]
+    pub changes: Vec<String>,
+}
+
+
+/// The Execute message. It includes every Cw20 base message, along with:
+/// * `TransferWithMemo`     - transfer with a memo emitted in the transfer's events
+/// * `SendFromBatch`        - sends from the owner's account to several contracts at once
+/// * `SetLogoPolicy`        - sets the policy uploaded logos must comply with
+/// * `RegisterSession`      - registers a session key allowed to transfer up to a cap
+/// * `RevokeSession`        - revokes a session key
+/// * `SessionTransfer`      - transfer signed by a session key, submitted by anyone
+/// * `GrantRole`            - grants a role to an address
+/// * `RevokeRole`           - revokes a role from an address
+/// * `SetDefaultDailyLimit` - sets the daily outbound transfer limit for every address
+/// * `SetDailyLimit`        - sets the daily outbound transfer limit for a specific address
+/// * `Subscribe`            - authorizes a payee to pull a fixed amount every interval
+/// * `Collect`              - the payee pulls what is due on a subscription
+/// * `CancelSubscription`   - either side cancels a subscription
+/// * `SetNameService`       - sets the name service contract used to resolve recipient names
+/// * `TransferToName`       - transfer to a recipient name resolved by the name service
+/// * `Lock`                 - locks part of the sender's balance as collateral, minting receipts
+/// * `Unlock`               - releases a lock, burning its receipts
+/// * `SetBuybackRouter`     - sets the DEX router used to buy back the token
+/// * `BuybackAndBurn`       - buys back the token with the native funds sent, and burns it
+/// * `MintVested`           - mints a grant vesting to the recipient over a schedule
+/// * `ClaimVested`          - the recipient claims what has vested of their grants
+/// * `SetLiquidityPool`     - sets an AMM pool the token can be minted into
+/// * `MintToPool`           - the minter mints into a pool, receiving its LP tokens
+/// * `Pause`                - the guardian or pauser stops all token movements
+/// * `Unpause`              - the pauser resumes token movements
+/// * `ProposeAction`        - the admin proposes a timelocked privileged action
+/// * `ExecuteAction`        - the admin executes a pending action once its delay passed
+/// * `CancelAction`         - the admin or the guardian cancels a pending action
+/// * `ProposeSpend`         - a treasury approver proposes to spend the contract's funds
+/// * `ApproveSpend`         - a treasury approver approves a spend, made once enough approved
+/// * `SetBurnSink`          - sets whether transfers to an address are burnt instead
+/// * `ScheduleTransfer`     - escrows a transfer until a height or time
+/// * `ExecuteScheduled`     - anyone delivers a scheduled transfer once due
+/// * `CancelScheduled`      - the sender cancels a scheduled transfer before it is due
+/// * `SetDepositRate`       - sets the exchange rate of a backing denom
+/// * `Deposit`              - deposits native coins, minting tokens at their rates
+/// * `Redeem`               - redeems tokens for coins of a backing denom, or a share of the basket
+/// * `CreateStreamAllowance` - streams an allowance to a spender, accruing every second
+/// * `CancelStreamAllowance` - the owner stops a stream allowance
+/// * `SetContractRecipientCheck` - sets whether plain transfers to contracts are rejected
+/// * `SetFlashMintFee`      - sets the flash mint fee, turning flash mints on or off
+/// * `FlashMint`            - mints tokens to a contract, which must burn them back in the same tx
+/// * `SetTransferPolicy`    - sets whether zero-amount and self-transfers are no-ops or rejected
+/// * `SetDependent`         - sets whether a contract is notified of the token's migrations
+/// * `SetLedgerRetention`   - sets how long balance changes are recorded for, or stops recording
+/// * `SetBasketAsset`       - sets whether an asset held by the contract is redeemable
+/// * `SetPermitKey`         - the sender sets the key signing their query permits
+/// * `SetSibling`           - sets the rate of a sibling token the token converts into
+/// * `Convert`              - burns tokens, the sibling token minting them converted to the sender
+/// * `SetCorridorMode`      - sets whether transfers are restricted to whitelisted corridors
+/// * `SetGroupMember`       - sets whether an address is a member of a group
+/// * `SetCorridor`          - sets whether a corridor between addresses or groups is whitelisted
+/// * `SetLabel`             - the sender sets the label of their account
+/// * `FaucetMint`           - mints tokens to the sender, in test deployments only
+/// * `RenounceAdmin`        - schedules the removal of every privilege, unless cancelled before
+/// * `CancelRenounce`       - the admin cancels the scheduled removal of every privilege
+#[cw_serde]
+pub enum ExecuteMsg {
+    /// Transfer is a base message to move tokens to another account without triggering actions.
+    /// Transfers to a burn sink burn the tokens instead. With the contract recipient check on,
+    /// transfers to a contract are rejected unless `allow_contract` is true.
+    Transfer {
+        recipient: String,
+        amount: Uint128,
+        allow_contract: Option<bool>,
+    },
+    /// Transfer with a memo, e.g. a deposit reference, which is only emitted in the events and
+    /// capped in size.
+    TransferWithMemo {
+        recipient: String,
+        amount: Uint128,
+        memo: String,
+    },
+    /// Burn is a base message to destroy tokens forever
+    Burn { amount: Uint128 },
+    /// Send is a base message to transfer tokens to a contract and trigger an action
+    /// on the receiving contract. With the "sends" module, the transfer is reverted should the
+    /// receiving contract reject it, rather than the whole transaction failing.
+    Send {
+        contract: String,
+        amount: Uint128,
+        msg: Binary,
+    },
+    /// Allows spender to access an additional amount tokens from the owner's (env.sender) account.
+    /// If expires is Some(), overwrites current allowance expiration with this one. With the
+    /// "purposes" module, if purpose is Some(), overwrites what the allowance is for, an empty one
+    /// removing it.
+    IncreaseAllowance {
+        spender: String,
+        amount: Uint128,
+        expires: Option<Expiration>,
+        purpose: Option<String>,
+    },
+    /// Lowers the spender's access of tokens from the owner's (env.sender) account by amount.
+    /// If expires is Some(), overwrites current allowance expiration with this one. With the
+    /// "purposes" module, if purpose is Some(), overwrites what the allowance is for, an empty one
+    /// removing it.
+    DecreaseAllowance {
+        spender: String,
+        amount: Uint128,
+        expires: Option<Expiration>,
+        purpose: Option<String>,
+    },
+    /// Transfers amount tokens from owner -> recipient if `env.sender` has sufficient pre-approval.
+    /// Transfers to a burn sink burn the tokens instead. If the owner streams an allowance to
+    /// `env.sender`, the transfer draws on what accrued on the stream instead.
+    TransferFrom {
+        owner: String,
+        recipient: String,
+        amount: Uint128,
+    },
+    /// Sends amount tokens from owner -> contract if `env.sender` has sufficient pre-approval.
+    SendFrom {
+        owner: String,
+        contract: String,
+        amount: Uint128,
+        msg: Binary,
+    },
+    /// Sends from owner -> each target contract if `env.sender` has sufficient pre-approval for
+    /// the total, which is deducted from the allowance at once.
+    SendFromBatch {
+        owner: String,
+        targets: Vec<SendTarget>,
+    },
+    /// Destroys tokens forever, from owner's account if `env.sender` has sufficient pre-approval.
+    BurnFrom { owner: String, amount: Uint128 },
+    /// If authorized, creates amount new tokens and adds to the recipient balance. Holders of the
+    /// minter role mint on behalf of the minter.
+    Mint { recipient: String, amount: Uint128 },
+    /// Disabled - the minter is changed through the timelock, see `ProposeAction`.
+    UpdateMinter { new_minter: Option<String> },
+    /// If authorized, updates marketing metadata. Setting None/null for any of these will leave it
+    /// unchanged. Setting Some("") will clear this field on the contract storage.
+    UpdateMarketing {
+        /// A URL pointing to the project behind this token.
+        project: Option<String>,
+        /// A longer description of the token and it's utility. Designed for tooltips or such
+        description: Option<String>,
+        /// The address (if any) who can update this data structure
+        marketing: Option<String>,
+    },
+    /// If set as the "marketing" role on the contract, upload a new URL, SVG, or PNG for the token.
+    /// It must comply with the logo policy.
+    UploadLogo(Logo),
+    /// Only with the "logo" module. Metadata sets the policy uploaded logos must comply with.
+    SetLogoPolicy { policy: LogoPolicy },
+    /// Admin grants a role to an address.
+    GrantRole { role: Role, address: String },
+    /// Admin revokes a role from an address.
+    RevokeRole { role: Role, address: String },
+    /// Only with the "limits" module. Compliance sets the daily outbound transfer limit applied to
+    /// every address without its own limit. None removes the default limit.
+    SetDefaultDailyLimit { limit: Option<Uint128> },
+    /// Only with the "limits" module. Compliance sets the daily outbound transfer limit of a specific
+    /// address, overriding the default. None removes the override.
+    SetDailyLimit {
+        address: String,
+        limit: Option<Uint128>,
+    },
+    /// Only with the "subscriptions" module. Authorizes the payee to collect `amount` from the
+    /// sender's account every `interval` seconds, starting now, until `end` if set.
+    Subscribe {
+        payee: String,
+        amount: Uint128,
+        interval: u64,
+        end: Option<Timestamp>,
+    },
+    /// Only with the "subscriptions" module. The payee collects everything due on the subscription.
+    Collect { subscription_id: u64 },
+    /// Only with the "subscriptions" module. The owner or the payee cancels the subscription.
+    CancelSubscription { subscription_id: u64 },
+    /// Only with the "names" module. Metadata sets the name service contract, None to unset it.
+    SetNameService { contract: Option<String> },
+    /// Only with the "names" module. Transfer to a human-readable name, resolved through the name
+    /// service, falling back to a plain address if the name is not registered.
+    TransferToName {
+        recipient_name: String,
+        amount: Uint128,
+    },
+    /// Only with the "locks" module. Locks `amount` of the sender's free balance until `until`, so
+    /// that it cannot be moved. Only the `unlocker` can release the lock before it expires.
+    Lock {
+        amount: Uint128,
+        unlocker: String,
+        until: Expiration,
+    },
+    /// Only with the "locks" module. The unlocker releases the lock, or anyone clears it once
+    /// expired.
+    Unlock { lock_id: u64 },
+    /// Only with the "buyback" module. Admin sets the DEX router contract, None to unset it.
+    SetBuybackRouter { router: Option<String> },
+    /// Only with the "buyback" module. Swaps the native funds sent for this token through the
+    /// router, and burns the tokens bought. Fails if less than `min_receive` is bought.
+    BuybackAndBurn { min_receive: Option<Uint128> },
+    /// Only with the "vesting" module. If authorized, mints `amount` into a grant vesting linearly
+    /// to the recipient over the schedule, instead of into the recipient's balance.
+    MintVested {
+        recipient: String,
+        amount: Uint128,
+        schedule: VestingSchedule,
+    },
+    /// Only with the "vesting" module. Claims everything vested so far of the sender's grants.
+    ClaimVested {},
+    /// Only with the "pool" module. Admin sets an AMM pool contract the token can be minted into,
+    /// along with its LP token, None to remove it.
+    SetLiquidityPool {
+        pool_contract: String,
+        lp_token: Option<String>,
+    },
+    /// Only with the "pool" module. If authorized, mints `amount` into the pool as liquidity, the
+    /// LP tokens going to the sender. Fails if less than `min_lp_out` LP tokens are minted.
+    MintToPool {
+        pool_contract: String,
+        amount: Uint128,
+        min_lp_out: Uint128,
+    },
+    /// Only with the "sessions" module. Allows the secp256k1 `pubkey` to sign transfers of up to
+    /// `max_amount` in total from the sender's account until `expires`.
+    RegisterSession {
+        pubkey: Binary,
+        max_amount: Uint128,
+        expires: Expiration,
+    },
+    /// Only with the "sessions" module. The owner revokes one of their sessions.
+    RevokeSession { session_id: u64 },
+    /// Only with the "sessions" module. Transfer from the owner's account, signed by the session key
+    /// over the `session_digest` of the transfer. The session is revoked once its cap is spent.
+    SessionTransfer {
+        owner: String,
+        session_id: u64,
+        recipient: String,
+        amount: Uint128,
+        signature: Binary,
+    },
+    /// Only with the "guardian" module. The guardian or a holder of the pauser role stops every
+    /// transfer, send, burn and mint.
+    Pause {},
+    /// Only with the "guardian" module. A holder of the pauser role resumes token movements.
+    Unpause {},
+    /// Only with the "guardian" module. Admin proposes a privileged action, executable once the
+    /// timelock delay has passed.
+    ProposeAction { action: AdminAction },
+    /// Only with the "guardian" module. Admin executes a pending action whose delay has passed.
+    ExecuteAction { action_id: u64 },
+    /// Only with the "guardian" module. Admin or guardian cancels a pending action.
+    CancelAction { action_id: u64 },
+    /// Only with the "treasury" module. An approver proposes to spend the contract's own tokens or
+    /// native coins, approving it at the same time.
+    ProposeSpend {
+        to: String,
+        amount: SpendAmount,
+        memo: String,
+    },
+    /// Only with the "treasury" module. An approver approves a spend, which is made once it has as
+    /// many approvals as the threshold.
+    ApproveSpend { id: u64 },
+    /// Only with the "sinks" module. Admin sets whether an address is a burn sink, transfers to
+    /// which burn the tokens and reduce the total supply.
+    SetBurnSink { address: String, sink: bool },
+    /// Only with the "scheduled" module. Escrows `amount` of the sender's tokens, to be delivered
+    /// to the recipient from `execute_after` on.
+    ScheduleTransfer {
+        recipient: String,
+        amount: Uint128,
+        execute_after: Expiration,
+    },
+    /// Only with the "scheduled" module. Anyone delivers the scheduled transfer, once due.
+    ExecuteScheduled { id: u64 },
+    /// Only with the "scheduled" module. The sender cancels the scheduled transfer before it is
+    /// due, getting the tokens back.
+    CancelScheduled { id: u64 },
+    /// Only with the "converter" module. A holder of the rates role sets how many tokens a unit of
+    /// the denom mints, None to stop accepting it.
+    SetDepositRate { denom: String, rate: Option<Decimal> },
+    /// Only with the "converter" module. Deposits the native coins sent, minting tokens to the
+    /// sender at the rate of each denom.
+    Deposit {},
+    /// Only with the "converter" module, given a denom. Burns `amount` tokens for coins of the
+    /// denom out of its reserve, at its current rate.
+    /// Only with the "basket" module, without one. Burns `amount` tokens for their share of every
+    /// asset of the basket, the burnt fraction of the supply.
+    Redeem { denom: Option<String>, amount: Uint128 },
+    /// Only with the "streams" module. Streams an allowance to the spender, accruing
+    /// `rate_per_second` from now until `end`, which their `TransferFrom` draws on. Any stream to
+    /// the spender is replaced.
+    CreateStreamAllowance {
+        spender: String,
+        rate_per_second: Uint128,
+        end: Timestamp,
+    },
+    /// Only with the "streams" module. The owner stops the stream to the spender, including what
+    /// accrued and was not transferred yet.
+    CancelStreamAllowance { spender: String },
+    /// Only with the "recipients" module. Admin sets whether plain transfers to contracts are
+    /// rejected, unless their sender allows them.
+    SetContractRecipientCheck { enabled: bool },
+    /// Only with the "flash" module. Admin sets the flash mint fee in basis points of the amount
+    /// minted, None to turn flash mints off.
+    SetFlashMintFee { fee_bps: Option<u16> },
+    /// Only with the "flash" module. Mints `amount` to the callback contract and calls it back with
+    /// `msg`. Everything is reverted unless the callback burns `amount` plus the fee before it
+    /// returns.
+    FlashMint {
+        amount: Uint128,
+        callback_contract: String,
+        msg: Binary,
+    },
+    /// Only with the "transfers" module. Admin sets whether zero-amount transfers and
+    /// self-transfers by the owner are accepted as no-ops, or rejected.
+    SetTransferPolicy { policy: TransferPolicy },
+    /// Only with the "dependents" module. Admin sets whether a dependent contract is notified of
+    /// the token's migrations.
+    SetDependent {
+        contract: String,
+        registered: bool,
+    },
+    /// Only with the "ledger" module. Admin sets the number of blocks balance changes are recorded
+    /// for, None to stop recording.
+    SetLedgerRetention { blocks: Option<u64> },
+    /// Only with the "basket" module. Admin sets whether an asset held by the contract is part of
+    /// the basket redeemed for tokens.
+    SetBasketAsset {
+        asset: BasketAsset,
+        listed: bool,
+    },
+    /// Only with the "permits" module. The sender sets the secp256k1 public key signing their
+    /// query permits, None to stop accepting them. Either revokes the permits signed before.
+    SetPermitKey { pubkey: Option<Binary> },
+    /// Only with the "siblings" module. A holder of the rates role sets how many tokens of the
+    /// sibling Cw20 contract a token converts into, None to unpair it. This contract must be a
+    /// minter of the sibling.
+    SetSibling { token: String, rate: Option<Decimal> },
+    /// Only with the "siblings" module. Burns `amount` tokens of the sender, and has the sibling
+    /// mint them the converted amount, at least `min_out`. Should the sibling fail to mint, the
+    /// tokens are restored.
+    Convert {
+        to_token: String,
+        amount: Uint128,
+        min_out: Uint128,
+    },
+    /// Only with the "corridors" module. Compliance sets whether outbound transfers are restricted
+    /// to the whitelisted corridors.
+    SetCorridorMode { enabled: bool },
+    /// Only with the "corridors" module. Compliance sets whether the address is a member of the
+    /// group.
+    SetGroupMember {
+        group: String,
+        address: String,
+        member: bool,
+    },
+    /// Only with the "corridors" module. Compliance sets whether transfers are allowed along the
+    /// corridor, in either direction.
+    SetCorridor { corridor: Corridor, allowed: bool },
+    /// Only with the "labels" module. The sender sets the label of their account, shown alongside
+    /// its balance in the extended queries, or removes it with an empty label.
+    SetLabel { label: String },
+    /// Only with the "faucet" module, turned on at instantiation in test deployments. Mints tokens
+    /// to the sender, up to a fixed number of whole tokens per address per day.
+    FaucetMint { amount: Uint128 },
+    /// Only with the "renounce" module. Admin schedules the removal of the admin, every role
+    /// holder, the guardian, the minter and the marketing address once `delay` seconds have
+    /// passed, after which the token is immutable. It goes through on its own unless cancelled.
+    RenounceAdmin { delay: u64 },
+    /// Only with the "renounce" module. Admin cancels the scheduled renouncement before it is due.
+    CancelRenounce {},
+}
+
+
+/// Query message
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum QueryMsg {
+    /// Returns the current balance of the given address, 0 if unset.
+    #[returns(cw20::BalanceResponse)]
+    Balance { address: String },
+    /// Returns metadata on the contract - name, decimals, supply, etc.
+    #[returns(cw20::TokenInfoResponse)]
+    TokenInfo {},
+    /// Returns who can mint and the hard cap on maximum tokens after minting.
+    #[returns(cw20::MinterResponse)]
+    Minter {},
+    /// Returns how much spender can use from owner account, 0 if unset.
+    #[returns(cw20::AllowanceResponse)]
+    Allowance { owner: String, spender: String },
+    /// Returns all allowances this owner has approved. Supports pagination.
+    #[returns(cw20::AllAllowancesResponse)]
+    AllAllowances {
+        owner: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Returns all allowances this spender has been granted. Supports pagination.
+    #[returns(cw20::AllSpenderAllowancesResponse)]
+    AllSpenderAllowances {
+        spender: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Returns all accounts that have balances. Supports pagination.
+    #[returns(cw20::AllAccountsResponse)]
+    AllAccounts {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Returns more metadata on the contract to display in the client:
+    /// - description, logo, project url, etc.
+    #[returns(cw20::MarketingInfoResponse)]
+    MarketingInfo {},
+    /// Downloads the embedded logo data (if stored on chain). Errors if no logo data is stored.
+    #[returns(cw20::DownloadLogoResponse)]
+    DownloadLogo {},
+    /// Only with the "logo" module. Returns the policy uploaded logos must comply with.
+    #[returns(LogoPolicy)]
+    LogoPolicy {},
+    /// Returns whether the given address holds the role.
+    #[returns(HasRoleResponse)]
+    HasRole { role: Role, address: String },
+    /// Returns the token info, minter and cap, total burnt and holder count in a single response.
+    #[returns(TokenInfoExtendedResponse)]
+    TokenInfoExtended {},
+    /// Returns the interfaces this deployment supports, cw22 style: cw20 and the token's modules,
+    /// by name with their version.
+    #[returns(SupportedInterfacesResponse)]
+    SupportedInterfaces {},
+    /// Only with the "limits" module. Returns the daily limit of the given address, and how much
+    /// it can still transfer out in the current window.
+    #[returns(RemainingLimitResponse)]
+    RemainingLimit { address: String },
+    /// Only with the "names" module. Returns the address a recipient name resolves to.
+    #[returns(ResolveNameResponse)]
+    ResolveName { name: String },
+    /// Only with the "subscriptions" module. Returns a subscription, with what is currently due.
+    #[returns(SubscriptionResponse)]
+    Subscription { subscription_id: u64 },
+    /// Only with the "subscriptions" module. Returns the subscriptions paid by the owner.
+    #[returns(SubscriptionsResponse)]
+    SubscriptionsByOwner {
+        owner: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Only with the "subscriptions" module. Returns the subscriptions paid to the payee.
+    #[returns(SubscriptionsResponse)]
+    SubscriptionsByPayee {
+        payee: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Only with the "locks" module. Returns the balance of the given address, split between
+    /// locked and free.
+    #[returns(LockedBalanceResponse)]
+    LockedBalance { address: String },
+    /// Only with the "vesting" module. Returns the vesting grants of the given address.
+    #[returns(VestingResponse)]
+    Vesting { address: String },
+    /// Only with the "locks" module. Returns the locks of the owner.
+    #[returns(LocksResponse)]
+    Locks {
+        owner: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Only with the "receipts" module. Returns the deposit receipts of the given address, minted
+    /// for the tokens it locked, as proof of deposit.
+    #[returns(ReceiptResponse)]
+    Receipt { address: String },
+    /// Only with the "sessions" module. Returns the sessions of the owner.
+    #[returns(SessionsResponse)]
+    Sessions {
+        owner: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Only with the "guardian" module. Returns the guardian, and whether the token is paused.
+    #[returns(GuardianResponse)]
+    Guardian {},
+    /// Only with the "guardian" module. Returns the actions waiting for their timelock delay.
+    #[returns(PendingActionsResponse)]
+    PendingActions {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Only with the "treasury" module. Returns the spend proposals waiting for approvals.
+    #[returns(SpendProposalsResponse)]
+    PendingSpends {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Only with the "scheduled" module. Returns the scheduled transfers of the sender.
+    #[returns(ScheduledTransfersResponse)]
+    ScheduledTransfers {
+        sender: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Only with the "converter" module. Returns the reserve and rate of every backing denom.
+    #[returns(ReservesResponse)]
+    Reserves {},
+    /// Only with the "streams" module. Returns the allowances streamed by the owner.
+    #[returns(StreamAllowancesResponse)]
+    StreamAllowances {
+        owner: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Only with the "history" module. Returns the daily total supply checkpoints, oldest first,
+    /// from the day after `start_after` on.
+    #[returns(SupplyHistoryResponse)]
+    SupplyHistory {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Only with the "flash" module. Returns the flash mint fee, None if flash mints are off.
+    #[returns(FlashMintFeeResponse)]
+    FlashMintFee {},
+    /// Only with the "transfers" module. Returns the policy on zero-amount and self-transfers.
+    #[returns(TransferPolicy)]
+    TransferPolicy {},
+    /// Only with the "dependents" module. Returns the dependents notified of migrations, with the
+    /// outcome of their last notification.
+    #[returns(DependentsResponse)]
+    Dependents {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Only with the "ledger" module. Returns the balance changes recorded from `from_height` to
+    /// `to_height` included, in the order they were made.
+    #[returns(LedgerDeltaResponse)]
+    LedgerDelta {
+        from_height: u64,
+        to_height: u64,
+        start_after: Option<LedgerCursor>,
+        limit: Option<u32>,
+    },
+    /// Only with the "validation" module. Returns whether the address is valid, as the contract
+    /// validates the addresses it is given.
+    #[returns(ValidateAddressResponse)]
+    ValidateAddress { address: String },
+    /// Only with the "validation" module. Returns whether the amount, in display units, is valid
+    /// given the token's decimals, and what it is in base units.
+    #[returns(ValidateAmountResponse)]
+    ValidateAmount { amount_str: String },
+    /// Only with the "basket" module. Returns what the contract holds of every asset of the basket,
+    /// and what each token is redeemed for.
+    #[returns(RedemptionRateResponse)]
+    RedemptionRate {},
+    /// Only with the "permits" module. Returns private data of the account named by the permit,
+    /// which it must have signed.
+    #[returns(LedgerDeltaResponse)]
+    WithPermit {
+        permit: Permit,
+        query: PermitQueryMsg,
+    },
+    /// Only with the "siblings" module. Returns the sibling tokens the token converts into, with
+    /// their rate.
+    #[returns(SiblingsResponse)]
+    Siblings {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Only with the "corridors" module. Returns whether the corridor mode is on, and whether
+    /// transfers between the addresses are along a whitelisted corridor.
+    #[returns(CorridorResponse)]
+    Corridor { from: String, to: String },
+    /// Only with the "corridors" module. Returns the groups the address is a member of.
+    #[returns(GroupsResponse)]
+    Groups { address: String },
+    /// Only with the "corridors" module. Returns the members of the group.
+    #[returns(GroupMembersResponse)]
+    GroupMembers {
+        group: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Only with the "labels" module. Returns the balance of the given address, with its label.
+    #[returns(BalanceExtendedResponse)]
+    BalanceExtended { address: String },
+    /// Only with the "labels" module. Returns the accounts holding tokens, with their balance and
+    /// label.
+    #[returns(AccountsExtendedResponse)]
+    AllAccountsExtended {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Only with the "display" module. Returns the decimal string of an amount of raw units,
+    /// according to the token's decimals.
+    #[returns(FormatAmountResponse)]
+    FormatAmount { amount: Uint128 },
+    /// Only with the "display" module. Returns the raw units of a decimal string, according to the
+    /// token's decimals. Strings more precise than the decimals are rejected rather than rounded.
+    #[returns(ParseAmountResponse)]
+    ParseAmount { display: String },
+    /// Only with the "purposes" module. Returns how much spender can use from owner account, with
+    /// what the allowance is for.
+    #[returns(AllowanceExtendedResponse)]
+    AllowanceExtended { owner: String, spender: String },
+    /// Only with the "purposes" module. Returns all allowances this owner has approved, with what
+    /// each is for.
+    #[returns(AllowancesExtendedResponse)]
+    AllAllowancesExtended {
+        owner: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Only with the "renounce" module. Returns the admin, and when its privileges are renounced
+    /// if scheduled.
+    #[returns(AdminStatusResponse)]
+    AdminStatus {},
+    /// Only with the "minters" module. Returns the minters revoked, which cannot mint again.
+    #[returns(RevokedMintersResponse)]
+    RevokedMinters {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Only with the "sinks" module. Returns the burn sinks.
+    #[returns(BurnSinksResponse)]
+    BurnSinks {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Only in debug builds, or with the "invariants" feature. Checks the token's invariants over
+    /// up to `limit` entries from the cursor on, returning the violations found and the cursor to
+    /// continue from.
+    #[cfg(any(debug_assertions, feature = "invariants"))]
+    #[returns(InvariantsResponse)]
+    CheckInvariants {
+        cursor: Option<InvariantsCursor>,
+        limit: Option<u32>,
+    },
+    /// Only in debug builds, or with the "snapshot" feature. Returns up to `limit` (address,
+    /// balance) pairs in storage key order, from the opaque cursor of the previous page on.
+    #[cfg(any(debug_assertions, feature = "snapshot"))]
+    #[returns(RawBalanceRangeResponse)]
+    RawBalanceRange {
+        cursor: Option<Binary>,
+        limit: Option<u32>,
+    },
+}
+
+
+/// Query of an account's private data, made with a permit it signed
+#[cw_serde]
+pub enum PermitQueryMsg {
+    /// Returns the balance changes of the account recorded from `from_height` to `to_height`
+    /// included, in the order they were made. Return type: LedgerDeltaResponse.
+    BalanceHistory {
+        from_height: u64,
+        to_height: u64,
+        start_after: Option<LedgerCursor>,
+        limit: Option<u32>,
+    },
+}
+
+
+/// The has role response
+#[cw_serde]
+pub struct HasRoleResponse {
+    pub has_role: bool,
+}
+
+
+/// An interface supported by this deployment
+#[cw_serde]
+pub struct SupportedInterface {
+    pub name: String,
+    pub version: String,
+}
+
+
+/// The supported interfaces response
+#[cw_serde]
+pub struct SupportedInterfacesResponse {
+    pub interfaces: Vec<SupportedInterface>,
+}
+
+
+/// The extended token info response
+#[cw_serde]
+pub struct TokenInfoExtendedResponse {
+    /// Name, symbol, decimals and total supply
+    pub token_info: TokenInfoResponse,
+    /// The minter and its cap, None if the token cannot be minted
+    pub minter: Option<MinterResponse>,
+    /// Total amount of tokens ever burnt
+    pub total_burned: Uint128,
+    /// Number of accounts holding a non-zero balance
+    pub holder_count: u64,
+    /// Jurisdiction the token is issued under, if set
+    pub jurisdiction: Option<String>,
+    /// Whether transfers are paused
+    pub paused: bool,
+}
+
+
+/// The remaining daily limit response
+#[cw_serde]
+pub struct RemainingLimitResponse {
+    /// The daily outbound transfer limit of the address, None if unlimited
+    pub limit: Option<Uint128>,
+    /// What can still be transferred out in the current window, None if unlimited
+    pub remaining: Option<Uint128>,
+    /// When the current window ends, None if no window is currently open
+    pub resets_at: Option<Timestamp>,
+}
+
+
+/// The resolve name response
+#[cw_serde]
+pub struct ResolveNameResponse {
+    /// The address the name resolves to
+    pub address: String,
+    /// Whether it was resolved through the name service, or is a plain address
+    pub from_name_service: bool,
+}
+
+
+/// The subscription response
+#[cw_serde]
+pub struct SubscriptionResponse {
+    /// Id of the subscription
+    pub id: u64,
+    /// The account paying
+    pub owner: String,
+    /// The account collecting the payments
+    pub payee: String,
+    /// Amount due every interval
+    pub amount: Uint128,
+    /// Seconds between two payments
+    pub interval: u64,
+    /// When the first payment was due
+    pub start: Timestamp,
+    /// No payment is due at or after this time, if set
+    pub end: Option<Timestamp>,
+    /// Number of payments collected so far
+    pub collected: u64,
+    /// Amount the payee can currently collect
+    pub due: Uint128,
+}
+
+
+/// The subscriptions list response
+#[cw_serde]
+pub struct SubscriptionsResponse {
+    pub subscriptions: Vec<SubscriptionResponse>,
+}
+
+
+/// The locked balance response
+#[cw_serde]
+pub struct LockedBalanceResponse {
+    /// The whole balance
+    pub balance: Uint128,
+    /// The part of the balance locked by active locks
+    pub locked: Uint128,
+    /// The part of the balance that can be moved
+    pub free: Uint128,
+}
+
+
+/// The lock response
+#[cw_serde]
+pub struct LockResponse {
+    /// Id of the lock
+    pub id: u64,
+    /// The account whose tokens are locked
+    pub owner: String,
+    /// Amount locked
+    pub amount: Uint128,
+    /// The only account able to release the lock before it expires
+    pub unlocker: String,
+    /// The lock stops applying once expired
+    pub until: Expiration,
+    /// Whether the lock still applies
+    pub active: bool,
+}
+
+
+/// The locks list response
+#[cw_serde]
+pub struct LocksResponse {
+    pub locks: Vec<LockResponse>,
+}
+
+
+/// The deposit receipt response
+#[cw_serde]
+pub struct ReceiptResponse {
+    /// Symbol of the receipts, the token's prefixed with "st"
+    pub symbol: String,
+    /// Receipts of the address, as many as the tokens it locked
+    pub balance: Uint128,
+    /// Total receipts
+    pub total_supply: Uint128,
+}
+
+
+/// The vesting grant response
+#[cw_serde]
+pub struct VestingGrantResponse {
+    /// Id of the grant
+    pub id: u64,
+    /// Total amount granted
+    pub amount: Uint128,
+    /// How the amount vests
+    pub schedule: VestingSchedule,
+    /// Amount vested so far
+    pub vested: Uint128,
+    /// Amount claimed so far
+    pub claimed: Uint128,
+}
+
+
+/// The vesting grants response
+#[cw_serde]
+pub struct VestingResponse {
+    pub grants: Vec<VestingGrantResponse>,
+}
+
+
+/// The guardian response
+#[cw_serde]
+pub struct GuardianResponse {
+    /// The guardian, None if not set
+    pub guardian: Option<String>,
+    /// Whether the token is paused
+    pub paused: bool,
+}
+
+
+/// The pending action response
+#[cw_serde]
+pub struct PendingActionResponse {
+    /// Id of the action
+    pub id: u64,
+    /// The proposed action
+    pub action: AdminAction,
+    /// The action can be executed from this time on
+    pub eta: Timestamp,
+    /// Whether the delay has passed
+    pub ready: bool,
+}
+
+
+/// The pending actions list response
+#[cw_serde]
+pub struct PendingActionsResponse {
+    pub actions: Vec<PendingActionResponse>,
+}
+
+
+/// The session response
+#[cw_serde]
+pub struct SessionResponse {
+    /// Id of the session
+    pub id: u64,
+    /// Public key signing the session's transfers
+    pub pubkey: Binary,
+    /// Total amount the session can transfer
+    pub max_amount: Uint128,
+    /// Amount transferred so far
+    pub spent: Uint128,
+    /// The session cannot transfer once expired
+    pub expires: Expiration,
+    /// Nonce the next transfer must sign
+    pub nonce: u64,
+}
+
+
+/// The sessions list response
+#[cw_serde]
+pub struct SessionsResponse {
+    pub sessions: Vec<SessionResponse>,
+}
+
+
+/// The spend proposal response
+#[cw_serde]
+pub struct SpendProposalResponse {
+    /// Id of the proposal
+    pub id: u64,
+    /// The recipient of the spend
+    pub to: String,
+    /// The amount spent
+    pub amount: SpendAmount,
+    /// What the spend is for
+    pub memo: String,
+    /// The approvers who approved it so far
+    pub approvals: Vec<String>,
+    /// Number of approvals the spend needs
+    pub threshold: u32,
+}
+
+
+/// The spend proposals list response
+#[cw_serde]
+pub struct SpendProposalsResponse {
+    pub proposals: Vec<SpendProposalResponse>,
+}
+
+
+/// The scheduled transfer response
+#[cw_serde]
+pub struct ScheduledTransferResponse {
+    /// Id of the scheduled transfer
+    pub id: u64,
+    /// The account the tokens come from
+    pub sender: String,
+    /// The account the tokens are delivered to
+    pub recipient: String,
+    /// Amount transferred
+    pub amount: Uint128,
+    /// The transfer can be delivered from then on
+    pub execute_after: Expiration,
+    /// Whether the transfer can be delivered
+    pub due: bool,
+}
+
+
+/// The scheduled transfers list response
+#[cw_serde]
+pub struct ScheduledTransfersResponse {
+    pub transfers: Vec<ScheduledTransferResponse>,
+}
+
+
+/// The reserve of a backing denom
+#[cw_serde]
+pub struct ReserveResponse {
+    pub denom: String,
+    /// Coins deposited and not redeemed yet
+    pub amount: Uint128,
+    /// Tokens minted per unit of the denom, None if no longer accepted
+    pub rate: Option<Decimal>,
+}
+
+
+/// The reserves response
+#[cw_serde]
+pub struct ReservesResponse {
+    pub reserves: Vec<ReserveResponse>,
+}
+
+
+/// The stream allowance response
+#[cw_serde]
+pub struct StreamAllowanceResponse {
+    /// The spender drawing on the stream
+    pub spender: String,
+    /// Amount accruing every second
+    pub rate_per_second: Uint128,
+    /// The stream accrues from then on
+    pub start: Timestamp,
+    /// The stream stops accruing then
+    pub end: Timestamp,
+    /// Amount transferred so far
+    pub spent: Uint128,
+    /// Amount accrued and not transferred yet, which the spender can transfer now
+    pub available: Uint128,
+}
+
+
+/// The stream allowances list response
+#[cw_serde]
+pub struct StreamAllowancesResponse {
+    pub streams: Vec<StreamAllowanceResponse>,
+}
+
+
+/// A total supply checkpoint
+#[cw_serde]
+pub struct SupplyCheckpoint {
+    /// Day of the checkpoint, since the unix epoch
+    pub day: u64,
+    /// Start of the day
+    pub time: Timestamp,
+    /// Total supply as of the last change made that day
+    pub total_supply: Uint128,
+}
+
+
+/// The supply history response
+#[cw_serde]
+pub struct SupplyHistoryResponse {
+    pub checkpoints: Vec<SupplyCheckpoint>,
+}
+
+
+/// The flash mint fee response
+#[cw_serde]
+pub struct FlashMintFeeResponse {
+    /// Fee in basis points of the amount minted, None if flash mints are off
+    pub fee_bps: Option<u16>,
+}
+
+
+/// A dependent notified of migrations
+#[cw_serde]
+pub struct DependentResponse {
+    pub contract: String,
+    /// Version the dependent was last notified of, None if it never was
+    pub version: Option<String>,
+    /// Outcome of that notification
+    pub status: Option<NotifyStatus>,
+}
+
+
+/// The dependents list response
+#[cw_serde]
+pub struct DependentsResponse {
+    pub dependents: Vec<DependentResponse>,
+}
+
+
+/// A balance change, at its position in the ledger
+#[cw_serde]
+pub struct LedgerRecordResponse {
+    pub height: u64,
+    /// Order of the change within the block
+    pub index: u32,
+    pub address: String,
+    /// Balance before the change
+    pub before: Uint128,
+    /// Balance after the change
+    pub after: Uint128,
+}
+
+
+/// The ledger delta response
+#[cw_serde]
+pub struct LedgerDeltaResponse {
+    pub records: Vec<LedgerRecordResponse>,
+}
+
+
+/// The validate address response
+#[cw_serde]
+pub struct ValidateAddressResponse {
+    pub valid: bool,
+    /// The validated address, if valid
+    pub address: Option<String>,
+    /// Why the address is invalid, if not
+    pub error: Option<String>,
+}
+
+
+/// The validate amount response
+#[cw_serde]
+pub struct ValidateAmountResponse {
+    pub valid: bool,
+    /// The amount in base units, if valid
+    pub amount: Option<Uint128>,
+    /// Why the amount is invalid, if not
+    pub error: Option<String>,
+}
+
+
+/// An asset of the basket, with what is redeemed of it
+#[cw_serde]
+pub struct RedemptionAsset {
+    pub asset: BasketAsset,
+    /// Amount held by the contract, converter reserves excluded
+    pub held: Uint128,
+    /// Amount each base unit of the token is redeemed for
+    pub per_token: Decimal,
+}
+
+
+/// The redemption rate response
+#[cw_serde]
+pub struct RedemptionRateResponse {
+    pub supply: Uint128,
+    pub assets: Vec<RedemptionAsset>,
+}
+
+
+/// A sibling token
+#[cw_serde]
+pub struct SiblingResponse {
+    /// The sibling Cw20 contract
+    pub token: String,
+    /// Sibling tokens minted per token converted
+    pub rate: Decimal,
+}
+
+
+/// The siblings list response
+#[cw_serde]
+pub struct SiblingsResponse {
+    pub siblings: Vec<SiblingResponse>,
+}
+
+
+/// The corridor response
+#[cw_serde]
+pub struct CorridorResponse {
+    /// Whether transfers are restricted to the corridors
+    pub enabled: bool,
+    /// Whether the addresses are along a whitelisted corridor
+    pub allowed: bool,
+}
+
+
+/// The groups of an address response
+#[cw_serde]
+pub struct GroupsResponse {
+    pub groups: Vec<String>,
+}
+
+
+/// The group members list response
+#[cw_serde]
+pub struct GroupMembersResponse {
+    pub members: Vec<String>,
+}
+
+
+/// The extended balance response
+#[cw_serde]
+pub struct BalanceExtendedResponse {
+    pub balance: Uint128,
+    /// Label the account set on itself, if any
+    pub label: Option<String>,
+}
+
+
+/// An account holding tokens
+#[cw_serde]
+pub struct AccountExtendedResponse {
+    pub address: String,
+    pub balance: Uint128,
+    /// Label the account set on itself, if any
+    pub label: Option<String>,
+}
+
+
+/// The extended accounts list response
+#[cw_serde]
+pub struct AccountsExtendedResponse {
+    pub accounts: Vec<AccountExtendedResponse>,
+}
+
+
+/// The formatted amount response
+#[cw_serde]
+pub struct FormatAmountResponse {
+    pub display: String,
+}
+
+
+/// The parsed amount response
+#[cw_serde]
+pub struct ParseAmountResponse {
+    pub amount: Uint128,
+}
+
+
+/// The extended allowance response
+#[cw_serde]
+pub struct AllowanceExtendedResponse {
+    pub allowance: Uint128,
+    pub expires: Expiration,
+    /// What the allowance is for, if the owner stated it
+    pub purpose: Option<String>,
+}
+
+
+/// An allowance approved by an owner
+#[cw_serde]
+pub struct AllowanceExtendedInfo {
+    pub spender: String,
+    pub allowance: Uint128,
+    pub expires: Expiration,
+    /// What the allowance is for, if the owner stated it
+    pub purpose: Option<String>,
+}
+
+
+/// The extended allowances list response
+#[cw_serde]
+pub struct AllowancesExtendedResponse {
+    pub allowances: Vec<AllowanceExtendedInfo>,
+}
+
+
+/// The admin status response
+#[cw_serde]
+pub struct AdminStatusResponse {
+    /// The admin, None once renounced
+    pub admin: Option<String>,
+    /// When the privileges are renounced, if scheduled
+    pub renounce_at: Option<Timestamp>,
+    /// Whether the privileges are renounced, i.e. the renouncement is due
+    pub renounced: bool,
+}
+
+
+/// A revoked minter
+#[cw_serde]
+pub struct RevokedMinter {
+    pub address: String,
+    /// Time the minter was revoked at
+    pub revoked_at: Timestamp,
+}
+
+
+/// The revoked minters list response
+#[cw_serde]
+pub struct RevokedMintersResponse {
+    pub minters: Vec<RevokedMinter>,
+}
+
+
+/// The burn sinks list response
+#[cw_serde]
+pub struct BurnSinksResponse {
+    pub sinks: Vec<String>,
+}
+
+
+/// The invariants check response
+#[cfg(any(debug_assertions, feature = "invariants"))]
+#[cw_serde]
+pub struct InvariantsResponse {
+    /// Invariants violated within the entries walked
+    pub violations: Vec<String>,
+    /// Cursor to continue checking from, None once everything was checked
+    pub next: Option<InvariantsCursor>,
+}
+
+
+/// The raw balance range response
+#[cfg(any(debug_assertions, feature = "snapshot"))]
+#[cw_serde]
+pub struct RawBalanceRangeResponse {
+    /// The (address, balance) pairs, in storage key order
+    pub balances: Vec<(String, Uint128)>,
+    /// Cursor of the next page, None once every balance was returned
+    pub next: Option<Binary>,
+}
@@ -0,0 +1,118 @@
+/*
+Name registry integration. The metadata role can configure a name service contract, so that transfers can
+be made to human-readable names instead of raw addresses. A name is first resolved through the name
+service, and only if it is not registered there, is it validated as a plain bech32 address.
+*/
+
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Uint128};
+use cw_storage_plus::Item;
+
+use crate::corridors::assert_corridor;
+use crate::error::ContractError;
+use crate::limits::spend_limit;
+use crate::msg::ResolveNameResponse;
+use crate::roles::{assert_role, Role};
+use crate::sinks::execute_transfer_or_burn;
+
+/// The name service contract names are resolved with, if any
+pub const NAME_SERVICE: Item<Addr> = Item::new("name_service");
+
+/// Query message expected to be supported by the name service contract
+#[cw_serde]
+pub enum NameServiceQueryMsg {
+    /// Resolve a name to the address it is registered to
+    ResolveRecord { name: String },
+}
+
+/// Response expected from the name service contract
+#[cw_serde]
+pub struct ResolveRecordResponse {
+    /// The address the name is registered to, None if not registered
+    pub address: Option<String>,
+}
+
+
+/// Resolve a recipient name - through the name service if configured and the name is registered
+/// there, and as a plain address otherwise.
+/// # Arguments
+/// * `deps` - dependency which has the storage (state) of the chain
+/// * `name` - the name, or address, of the recipient
+/// # Returns
+///   The resolved address, and whether it was resolved through the name service
+pub fn resolve_recipient(deps: Deps, name: &str) -> StdResult<(Addr, bool)> {
+    if let Some(name_service) = NAME_SERVICE.may_load(deps.storage)? {
+        let query = NameServiceQueryMsg::ResolveRecord { name: name.to_string() };
+        let res: ResolveRecordResponse = deps.querier.query_wasm_smart(name_service, &query)?;
+        if let Some(address) = res.address {
+            return Ok((deps.api.addr_validate(&address)?, true));
+        }
+    }
+    Ok((deps.api.addr_validate(name)?, false))
+}
+
+
+/// Set the name service contract - metadata role only.
+/// # Arguments
+/// * `deps`     - mutable dependency which has the storage (state) of the chain
+/// * `info`     - message info, the sender must hold the metadata role
+/// * `contract` - the name service contract, None to stop resolving names
+/// # Returns
+/// * the execute response on Ok
+/// * the error type on Err
+pub fn execute_set_name_service(
+    deps     : DepsMut,
+    info     : MessageInfo,
+    contract : Option<String>,
+) -> Result<Response, ContractError> {
+    assert_role(deps.storage, Role::Metadata, &info.sender)?;
+    let contract = contract.map(|c| deps.api.addr_validate(&c)).transpose()?;
+    match &contract {
+        Some(contract) => NAME_SERVICE.save(deps.storage, contract)?,
+        None => NAME_SERVICE.remove(deps.storage),
+    }
+    Ok(Response::new()
+        .add_attribute("action", "set_name_service")
+        .add_attribute("contract", contract.map_or("none".to_string(), String::from)))
+}
+
+
+/// Transfer to a name - identical to a transfer once the name is resolved, burning if it resolves
+/// to a burn sink.
+/// # Arguments
+/// * `deps`           - mutable dependency which has the storage (state) of the chain
+/// * `env`            - environment variables which include block information
+/// * `info`           - message info, the sender is transferring
+/// * `recipient_name` - the name, or address, of the recipient
+/// * `amount`         - the amount to transfer
+/// # Returns
+/// * the execute response on Ok
+/// * the error type on Err
+pub fn execute_transfer_to_name(
+    deps           : DepsMut,
+    env            : Env,
+    info           : MessageInfo,
+    recipient_name : String,
+    amount         : Uint128,
+) -> Result<Response, ContractError> {
+    let (recipient, _) = resolve_recipient(deps.as_ref(), &recipient_name)?;
+    assert_corridor(deps.as_ref(), &info.sender, recipient.as_str())?;
+    spend_limit(deps.storage, &env.block, &info.sender, amount)?;
+    let res = execute_transfer_or_burn(deps, env, info, recipient.into(), amount)?;
+    Ok(res.add_attribute("recipient_name", recipient_name))
+}
+
+
+/// Query what a name resolves to, so that senders can double check it before transferring.
+/// # Arguments
+/// * `deps` - dependency which has the storage (state) of the chain
+/// * `name` - the name, or address, to resolve
+/// # Returns
+///   The resolved address
+pub fn query_resolve_name(deps: Deps, name: String) -> StdResult<ResolveNameResponse> {
+    let (address, from_name_service) = resolve_recipient(deps, &name)?;
+    Ok(ResolveNameResponse {
+        address: address.into(),
+        from_name_service,
+    })
+}
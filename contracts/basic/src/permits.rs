@@ -0,0 +1,138 @@
+/*
+Permits module - signed query permits, letting an account read its private data, such as its
+balance history, without sending a transaction. The account registers a permit key, whose signed
+permits are then accepted by the queries. Permits are signed ADR-36 style, as arbitrary data in an
+off-chain sign doc which no chain would execute, so that wallets sign them like any other message.
+A permit names the account and when it expires, and is verified in the query itself. Registering
+another key, or none, revokes every permit signed before.
+*/
+
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{
+    to_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdError, StdResult
+};
+use cw_storage_plus::Map;
+use cw20::Expiration;
+use sha2::{Digest, Sha256};
+
+use crate::error::ContractError;
+use crate::ledger::query_account_ledger;
+use crate::msg::PermitQueryMsg;
+
+/// A query permit, signed by the account's permit key
+#[cw_serde]
+pub struct Permit {
+    /// The account whose private data is queried
+    pub address   : String,
+    /// The permit is rejected once expired
+    pub expires   : Expiration,
+    /// The permit key's signature of the `permit_digest`
+    pub signature : Binary,
+}
+
+/// Secp256k1 public key signing the permits of an account, compressed or not
+pub const PERMIT_KEYS: Map<&Addr, Binary> = Map::new("permit_keys");
+
+
+/// The digest a permit key signs - the sha-256 hash of the ADR-36 sign doc, signed by `address`,
+/// whose data is `Query permit for {contract} until {expires}`.
+/// # Arguments
+/// * `contract` - this token contract's address
+/// * `address`  - the account the permit is for
+/// * `expires`  - when the permit expires
+/// # Returns
+///   The digest to sign
+pub fn permit_digest(contract: &Addr, address: &str, expires: &Expiration) -> Vec<u8> {
+    let data = Binary::from(format!("Query permit for {} until {}", contract, expires).as_bytes());
+    let sign_doc = format!(
+        concat!(
+            r#"{{"account_number":"0","chain_id":"","fee":{{"amount":[],"gas":"0"}},"memo":"","#,
+            r#""msgs":[{{"type":"sign/MsgSignData","value":{{"data":"{}","signer":"{}"}}}}],"#,
+            r#""sequence":"0"}}"#
+        ),
+        data.to_base64(),
+        address,
+    );
+    Sha256::digest(sign_doc.as_bytes()).to_vec()
+}
+
+
+/// Set permit key - the sender registers the key signing their permits, or none to stop accepting
+/// them. Either revokes every permit signed with the previous key.
+/// # Arguments
+/// * `deps`   - mutable dependency which has the storage (state) of the chain
+/// * `info`   - message info, the sender is the account
+/// * `pubkey` - the permit key's secp256k1 public key, None to remove it
+/// # Returns
+/// * the execute response on Ok
+/// * the error type on Err
+pub fn execute_set_permit_key(
+    deps   : DepsMut,
+    info   : MessageInfo,
+    pubkey : Option<Binary>,
+) -> Result<Response, ContractError> {
+    match pubkey {
+        Some(pubkey) if ![33, 65].contains(&pubkey.len()) => {
+            return Err(ContractError::InvalidPermitKey {});
+        }
+        Some(pubkey) => PERMIT_KEYS.save(deps.storage, &info.sender, &pubkey)?,
+        None => PERMIT_KEYS.remove(deps.storage, &info.sender),
+    }
+    Ok(Response::new()
+        .add_attribute("action", "set_permit_key")
+        .add_attribute("account", info.sender))
+}
+
+
+/// Verify a permit - it must not be expired, and be signed by the account's current permit key.
+/// # Arguments
+/// * `deps`   - dependency which has the storage (state) of the chain
+/// * `env`    - environment variables, with the current block and this contract's address
+/// * `permit` - the permit
+/// # Returns
+/// * the account the permit is for on Ok
+/// * the standard error type on Err
+pub fn verify_permit(deps: Deps, env: &Env, permit: &Permit) -> StdResult<Addr> {
+    let address = deps.api.addr_validate(&permit.address)?;
+    if permit.expires.is_expired(&env.block) {
+        return Err(StdError::generic_err("Permit expired"));
+    }
+    let pubkey = PERMIT_KEYS
+        .may_load(deps.storage, &address)?
+        .ok_or_else(|| StdError::generic_err("No permit key registered"))?;
+    let digest = permit_digest(&env.contract.address, &permit.address, &permit.expires);
+    // a malformed signature is as invalid as a wrong one
+    let verified = deps.api.secp256k1_verify(&digest, &permit.signature, &pubkey);
+    if !verified.unwrap_or(false) {
+        return Err(StdError::generic_err("Invalid permit signature"));
+    }
+    Ok(address)
+}
+
+
+/// Query private data of an account, with a permit it signed.
+/// # Arguments
+/// * `deps`   - dependency which has the storage (state) of the chain
+/// * `env`    - environment variables, with the current block and this contract's address
+/// * `permit` - the permit of the account
+/// * `query`  - the query of the account's private data
+/// # Returns
+///   The response of the query
+pub fn query_with_permit(
+    deps   : Deps,
+    env    : Env,
+    permit : Permit,
+    query  : PermitQueryMsg,
+) -> StdResult<Binary> {
+    let address = verify_permit(deps, &env, &permit)?;
+    match query {
+        PermitQueryMsg::BalanceHistory {
+            from_height,
+            to_height,
+            start_after,
+            limit
+        } => to_binary(&query_account_ledger(
+            deps, &address, from_height, to_height, start_after, limit
+        )?),
+    }
+}
@@ -0,0 +1,152 @@
+/*
+Pool module - liquidity bootstrapping. The admin configures the AMM pools the token can be minted
+into, along with their LP token. The minter then mints straight into a pool, which provides the
+liquidity and mints the LP tokens to the minter. The deposit is a submessage, and its reply checks
+how many LP tokens the minter received, reverting everything if less than the minimum.
+*/
+
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{
+    to_binary, Addr, Deps, DepsMut, Env, MessageInfo, Response, StdResult, SubMsg, Uint128
+};
+use cw_storage_plus::{Item, Map};
+use cw20::{BalanceResponse, Cw20QueryMsg, Cw20ReceiveMsg};
+use cw20_base::contract::execute_mint;
+
+use crate::error::ContractError;
+use crate::roles::minting_info;
+use crate::state::assert_admin;
+
+/// Reply id of the pool deposit submessage
+pub const POOL_REPLY_ID: u64 = 2;
+
+/// The AMM pools the token can be minted into, with their LP token
+pub const POOLS: Map<&Addr, Addr> = Map::new("pools");
+
+/// A deposit waiting for its reply, to tell how many LP tokens it minted
+#[cw_serde]
+pub struct PendingDeposit {
+    /// The LP token of the pool
+    pub lp_token   : Addr,
+    /// The address receiving the LP tokens
+    pub receiver   : Addr,
+    /// LP token balance of the receiver before the deposit
+    pub lp_before  : Uint128,
+    /// Minimum amount of LP tokens the deposit must mint
+    pub min_lp_out : Uint128,
+}
+
+/// The pending deposit
+pub const PENDING_DEPOSIT: Item<PendingDeposit> = Item::new("pending_deposit");
+
+/// Message expected to be supported by the pool contract, as the message of the Cw20 send of the
+/// tokens minted into it
+#[cw_serde]
+pub enum PoolHookMsg {
+    /// Provide the tokens sent as liquidity, and mint the LP tokens to the receiver
+    ProvideLiquidity { receiver: String },
+}
+
+
+/// LP token balance of an address.
+fn lp_balance(deps: Deps, lp_token: &Addr, address: &Addr) -> StdResult<Uint128> {
+    let query = Cw20QueryMsg::Balance { address: address.to_string() };
+    let res: BalanceResponse = deps.querier.query_wasm_smart(lp_token, &query)?;
+    Ok(res.balance)
+}
+
+
+/// Set a liquidity pool the token can be minted into - admin only.
+/// # Arguments
+/// * `deps`          - mutable dependency which has the storage (state) of the chain
+/// * `info`          - message info, the sender must be the admin
+/// * `pool_contract` - the pool contract
+/// * `lp_token`      - the pool's LP token, None to stop minting into the pool
+/// # Returns
+/// * the execute response on Ok
+/// * the error type on Err
+pub fn execute_set_liquidity_pool(
+    deps          : DepsMut,
+    info          : MessageInfo,
+    pool_contract : String,
+    lp_token      : Option<String>,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.storage, &info.sender)?;
+    let pool = deps.api.addr_validate(&pool_contract)?;
+    let lp_token = lp_token.map(|token| deps.api.addr_validate(&token)).transpose()?;
+    match &lp_token {
+        Some(lp_token) => POOLS.save(deps.storage, &pool, lp_token)?,
+        None => POOLS.remove(deps.storage, &pool),
+    }
+    Ok(Response::new()
+        .add_attribute("action", "set_liquidity_pool")
+        .add_attribute("pool", pool)
+        .add_attribute("lp_token", lp_token.map_or("none".to_string(), String::from)))
+}
+
+
+/// Mint to pool - the minter mints into a configured pool, which provides the liquidity and mints
+/// the LP tokens to the minter. The reply checks that at least `min_lp_out` were minted.
+/// # Arguments
+/// * `deps`          - mutable dependency which has the storage (state) of the chain
+/// * `env`           - environment variables which include block information
+/// * `info`          - message info, the sender must be the minter or hold the minter role
+/// * `pool_contract` - the pool contract
+/// * `amount`        - the amount minted into the pool
+/// * `min_lp_out`    - the minimum amount of LP tokens to receive
+/// # Returns
+/// * the execute response on Ok
+/// * the error type on Err
+pub fn execute_mint_to_pool(
+    mut deps      : DepsMut,
+    env           : Env,
+    info          : MessageInfo,
+    pool_contract : String,
+    amount        : Uint128,
+    min_lp_out    : Uint128,
+) -> Result<Response, ContractError> {
+    let pool = deps.api.addr_validate(&pool_contract)?;
+    let lp_token = POOLS
+        .may_load(deps.storage, &pool)?
+        .ok_or(ContractError::PoolNotConfigured {})?;
+    let receiver = info.sender.clone();
+    let lp_before = lp_balance(deps.as_ref(), &lp_token, &receiver)?;
+    let pending = PendingDeposit { lp_token, receiver: receiver.clone(), lp_before, min_lp_out };
+    PENDING_DEPOSIT.save(deps.storage, &pending)?;
+
+    // minting checks the sender is the minter, and the cap
+    let info = minting_info(deps.storage, info)?;
+    let res = execute_mint(deps.branch(), env, info, pool_contract.clone(), amount)?;
+
+    // then the pool is told about the tokens, as with a Cw20 send
+    let deposit = Cw20ReceiveMsg {
+        sender : receiver.to_string(),
+        amount,
+        msg    : to_binary(&PoolHookMsg::ProvideLiquidity { receiver: receiver.into() })?,
+    }
+    .into_cosmos_msg(pool_contract)?;
+    Ok(res
+        .add_submessage(SubMsg::reply_on_success(deposit, POOL_REPLY_ID))
+        .add_attribute("min_lp_out", min_lp_out))
+}
+
+
+/// Mint to pool reply - ensure the deposit minted enough LP tokens to the receiver.
+/// # Arguments
+/// * `deps` - mutable dependency which has the storage (state) of the chain
+/// # Returns
+/// * the response on Ok
+/// * the error type on Err
+pub fn reply_mint_to_pool(deps: DepsMut) -> Result<Response, ContractError> {
+    let pending = PENDING_DEPOSIT.load(deps.storage)?;
+    PENDING_DEPOSIT.remove(deps.storage);
+
+    let lp_after = lp_balance(deps.as_ref(), &pending.lp_token, &pending.receiver)?;
+    let lp_out = lp_after.saturating_sub(pending.lp_before);
+    if lp_out < pending.min_lp_out {
+        return Err(ContractError::PoolSlippage { lp_out, min_lp_out: pending.min_lp_out });
+    }
+    Ok(Response::new()
+        .add_attribute("action", "mint_to_pool_deposit")
+        .add_attribute("lp_out", lp_out))
+}
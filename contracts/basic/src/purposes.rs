@@ -0,0 +1,181 @@
+/*
+Purposes module - human-readable purposes of the allowances. Owners optionally state what an
+allowance is for when increasing or decreasing it, such as "DEX trading" or "monthly payroll",
+which the extended allowance queries return alongside the allowances so that wallets can show users
+what each allowance was granted for when prompting them to revoke it. A purpose is dropped along
+with its allowance.
+*/
+
+use cosmwasm_std::{Addr, Deps, DepsMut, Env, MessageInfo, Order, Response, StdResult, Uint128};
+use cw_storage_plus::{Bound, Map};
+use cw20::Expiration;
+use cw20_base::allowances::{execute_decrease_allowance, execute_increase_allowance};
+use cw20_base::state::ALLOWANCES;
+
+use crate::error::ContractError;
+use crate::msg::{AllowanceExtendedInfo, AllowanceExtendedResponse, AllowancesExtendedResponse};
+
+// Settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+
+/// Maximum size of a purpose, in bytes
+pub const MAX_PURPOSE_LEN: usize = 128;
+
+/// The purpose of each allowance, by owner and spender
+pub const PURPOSES: Map<(&Addr, &Addr), String> = Map::new("allowance_purposes");
+
+
+/// Set the purpose of an allowance once changed, an empty one removing it - as does the allowance
+/// being removed.
+fn set_purpose(
+    deps    : DepsMut,
+    owner   : &Addr,
+    spender : &str,
+    purpose : Option<String>,
+    res     : Response,
+) -> Result<Response, ContractError> {
+    let spender = deps.api.addr_validate(spender)?;
+    if !ALLOWANCES.has(deps.storage, (owner, &spender)) {
+        PURPOSES.remove(deps.storage, (owner, &spender));
+        return Ok(res);
+    }
+    match purpose {
+        Some(purpose) if purpose.is_empty() => {
+            PURPOSES.remove(deps.storage, (owner, &spender));
+            Ok(res.add_attribute("purpose", ""))
+        }
+        Some(purpose) => {
+            PURPOSES.save(deps.storage, (owner, &spender), &purpose)?;
+            Ok(res.add_attribute("purpose", purpose))
+        }
+        None => Ok(res),
+    }
+}
+
+
+/// Increase allowance - as the Cw20 base one, along with the purpose of the allowance if any.
+/// # Arguments
+/// * `deps`    - mutable dependency which has the storage (state) of the chain
+/// * `env`     - environment variables which include block information
+/// * `info`    - message info, the sender is the owner
+/// * `spender` - the spender
+/// * `amount`  - the amount the allowance increases by
+/// * `expires` - the expiration of the allowance, if replaced
+/// * `purpose` - the purpose of the allowance, if replaced, an empty one removing it
+/// # Returns
+/// * the execute response on Ok
+/// * the error type on Err
+pub fn execute_increase_allowance_with_purpose(
+    mut deps : DepsMut,
+    env      : Env,
+    info     : MessageInfo,
+    spender  : String,
+    amount   : Uint128,
+    expires  : Option<Expiration>,
+    purpose  : Option<String>,
+) -> Result<Response, ContractError> {
+    assert_purpose(&purpose)?;
+    let owner = info.sender.clone();
+    let res = execute_increase_allowance(deps.branch(), env, info, spender.clone(), amount, expires)?;
+    set_purpose(deps, &owner, &spender, purpose, res)
+}
+
+
+/// Decrease allowance - as the Cw20 base one, along with the purpose of the allowance if any.
+/// # Arguments
+/// * `deps`    - mutable dependency which has the storage (state) of the chain
+/// * `env`     - environment variables which include block information
+/// * `info`    - message info, the sender is the owner
+/// * `spender` - the spender
+/// * `amount`  - the amount the allowance decreases by
+/// * `expires` - the expiration of the allowance, if replaced
+/// * `purpose` - the purpose of the allowance, if replaced, an empty one removing it
+/// # Returns
+/// * the execute response on Ok
+/// * the error type on Err
+pub fn execute_decrease_allowance_with_purpose(
+    mut deps : DepsMut,
+    env      : Env,
+    info     : MessageInfo,
+    spender  : String,
+    amount   : Uint128,
+    expires  : Option<Expiration>,
+    purpose  : Option<String>,
+) -> Result<Response, ContractError> {
+    assert_purpose(&purpose)?;
+    let owner = info.sender.clone();
+    let res = execute_decrease_allowance(deps.branch(), env, info, spender.clone(), amount, expires)?;
+    set_purpose(deps, &owner, &spender, purpose, res)
+}
+
+
+/// Ensure that a purpose is not longer than allowed.
+fn assert_purpose(purpose: &Option<String>) -> Result<(), ContractError> {
+    match purpose {
+        Some(purpose) if purpose.len() > MAX_PURPOSE_LEN => {
+            Err(ContractError::PurposeTooLong { max: MAX_PURPOSE_LEN })
+        }
+        _ => Ok(()),
+    }
+}
+
+
+/// Query the allowance of a spender over an owner's tokens, along with its purpose.
+/// # Arguments
+/// * `deps`    - dependency which has the storage (state) of the chain
+/// * `owner`   - the owner
+/// * `spender` - the spender
+/// # Returns
+///   The allowance, its expiration and its purpose
+pub fn query_allowance_extended(
+    deps    : Deps,
+    owner   : String,
+    spender : String,
+) -> StdResult<AllowanceExtendedResponse> {
+    let owner = deps.api.addr_validate(&owner)?;
+    let spender = deps.api.addr_validate(&spender)?;
+    let allowance = ALLOWANCES.may_load(deps.storage, (&owner, &spender))?.unwrap_or_default();
+    Ok(AllowanceExtendedResponse {
+        allowance : allowance.allowance,
+        expires   : allowance.expires,
+        purpose   : PURPOSES.may_load(deps.storage, (&owner, &spender))?,
+    })
+}
+
+
+/// Query the allowances an owner approved, along with their purposes.
+/// # Arguments
+/// * `deps`        - dependency which has the storage (state) of the chain
+/// * `owner`       - the owner
+/// * `start_after` - the spender to start after
+/// * `limit`       - the list size limit
+/// # Returns
+///   The list of allowances, with their spender and purpose
+pub fn query_all_allowances_extended(
+    deps        : Deps,
+    owner       : String,
+    start_after : Option<String>,
+    limit       : Option<u32>,
+) -> StdResult<AllowancesExtendedResponse> {
+    let owner = deps.api.addr_validate(&owner)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_addr = start_after.map(Addr::unchecked);
+    let start = start_addr.as_ref().map(Bound::exclusive);
+
+    let allowances = ALLOWANCES
+        .prefix(&owner)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (spender, allowance) = item?;
+            Ok(AllowanceExtendedInfo {
+                purpose   : PURPOSES.may_load(deps.storage, (&owner, &spender))?,
+                spender   : spender.into(),
+                allowance : allowance.allowance,
+                expires   : allowance.expires,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+    Ok(AllowancesExtendedResponse { allowances })
+}
@@ -0,0 +1,73 @@
+/*
+Receipts module - deposit receipts for locked tokens. Locking tokens mints the owner as many receipt
+tokens, the token's symbol prefixed with "st", which are burnt as the lock is released. Receipts are
+a second ledger kept alongside the balances: they cannot be transferred, and only serve as proof of
+deposit, other contracts querying an account's receipt balance rather than walking its locks.
+*/
+
+use cosmwasm_std::{Addr, Deps, StdResult, Storage, Uint128};
+use cw_storage_plus::{Item, Map};
+use cw20_base::state::TOKEN_INFO;
+
+use crate::msg::ReceiptResponse;
+
+/// Receipt balances, by owner
+pub const RECEIPTS: Map<&Addr, Uint128> = Map::new("receipts");
+
+/// Total receipts minted and not burnt yet
+pub const RECEIPT_SUPPLY: Item<Uint128> = Item::new("receipt_supply");
+
+
+/// Mint receipts to the owner of tokens just locked.
+/// # Arguments
+/// * `storage` - the contract storage
+/// * `owner`   - the owner of the locked tokens
+/// * `amount`  - the amount locked
+/// # Returns
+/// * unit on Ok
+/// * the standard error type on Err
+pub fn mint_receipt(storage: &mut dyn Storage, owner: &Addr, amount: Uint128) -> StdResult<()> {
+    RECEIPTS.update(storage, owner, |balance| -> StdResult<_> {
+        Ok(balance.unwrap_or_default().checked_add(amount)?)
+    })?;
+    let supply = RECEIPT_SUPPLY.may_load(storage)?.unwrap_or_default();
+    RECEIPT_SUPPLY.save(storage, &supply.checked_add(amount)?)
+}
+
+
+/// Burn the receipts of tokens just unlocked. Locks made before receipts were minted have none to
+/// burn, so at most the owner's receipt balance is burnt.
+/// # Arguments
+/// * `storage` - the contract storage
+/// * `owner`   - the owner of the unlocked tokens
+/// * `amount`  - the amount unlocked
+/// # Returns
+/// * unit on Ok
+/// * the standard error type on Err
+pub fn burn_receipt(storage: &mut dyn Storage, owner: &Addr, amount: Uint128) -> StdResult<()> {
+    let balance = RECEIPTS.may_load(storage, owner)?.unwrap_or_default();
+    let burnt = balance.min(amount);
+    if balance == burnt {
+        RECEIPTS.remove(storage, owner);
+    } else {
+        RECEIPTS.save(storage, owner, &(balance - burnt))?;
+    }
+    let supply = RECEIPT_SUPPLY.may_load(storage)?.unwrap_or_default();
+    RECEIPT_SUPPLY.save(storage, &supply.saturating_sub(burnt))
+}
+
+
+/// Query the receipt balance of an address, as proof of the tokens it has locked.
+/// # Arguments
+/// * `deps`    - dependency which has the storage (state) of the chain
+/// * `address` - the address
+/// # Returns
+///   The receipt's symbol, the balance of the address, and the total receipts
+pub fn query_receipt(deps: Deps, address: String) -> StdResult<ReceiptResponse> {
+    let addr = deps.api.addr_validate(&address)?;
+    Ok(ReceiptResponse {
+        symbol       : format!("st{}", TOKEN_INFO.load(deps.storage)?.symbol),
+        balance      : RECEIPTS.may_load(deps.storage, &addr)?.unwrap_or_default(),
+        total_supply : RECEIPT_SUPPLY.may_load(deps.storage)?.unwrap_or_default(),
+    })
+}
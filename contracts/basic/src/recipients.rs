@@ -0,0 +1,66 @@
+/*
+Recipients module - guards against the classic mistake of transferring tokens to a contract, which
+never gets to know about them and, unlike `Send`, has no receive hook to act on them. With the check
+on, plain transfers to an address holding a contract are rejected, unless the sender explicitly
+allows it.
+*/
+
+use cosmwasm_std::{Deps, DepsMut, MessageInfo, Response, StdResult};
+use cw_storage_plus::Item;
+
+use crate::error::ContractError;
+use crate::state::assert_admin;
+
+/// Whether plain transfers to contracts are rejected, off unless set by the admin
+pub const CONTRACT_RECIPIENT_CHECK: Item<bool> = Item::new("contract_recipient_check");
+
+
+/// Set whether plain transfers to contracts are rejected - admin only.
+/// # Arguments
+/// * `deps`    - mutable dependency which has the storage (state) of the chain
+/// * `info`    - message info, the sender must be the admin
+/// * `enabled` - whether transfers to contracts are rejected
+/// # Returns
+/// * the execute response on Ok
+/// * the error type on Err
+pub fn execute_set_contract_recipient_check(
+    deps    : DepsMut,
+    info    : MessageInfo,
+    enabled : bool,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.storage, &info.sender)?;
+    CONTRACT_RECIPIENT_CHECK.save(deps.storage, &enabled)?;
+    Ok(Response::new()
+        .add_attribute("action", "set_contract_recipient_check")
+        .add_attribute("enabled", enabled.to_string()))
+}
+
+
+/// Whether the check is on
+pub fn contract_recipient_check(deps: Deps) -> StdResult<bool> {
+    Ok(CONTRACT_RECIPIENT_CHECK.may_load(deps.storage)?.unwrap_or_default())
+}
+
+
+/// Ensure that a plain transfer does not go to a contract, with the check on and unless the sender
+/// allows it. An address is a contract if the chain has contract info for it.
+/// # Arguments
+/// * `deps`           - dependency which has the storage (state) of the chain, and the querier
+/// * `recipient`      - the recipient of the transfer
+/// * `allow_contract` - whether the sender knowingly transfers to a contract
+/// # Returns
+/// * unit on Ok
+/// * the error type on Err
+pub fn assert_not_contract(
+    deps           : Deps,
+    recipient      : &str,
+    allow_contract : bool,
+) -> Result<(), ContractError> {
+    if allow_contract || !contract_recipient_check(deps)? {
+        return Ok(());
+    }
+    if deps.querier.query_wasm_contract_info(recipient).is_ok() {
+        return Err(ContractError::ContractRecipient { recipient: recipient.to_string() });
+    }
+    Ok(())
+}
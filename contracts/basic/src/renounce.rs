@@ -0,0 +1,174 @@
+/*
+Renounce module - the admin committing the token to immutability. The admin schedules the removal
+of every privilege after a delay, which goes through on its own unless the admin cancels it before
+then - a dead-man switch, so that a team can announce the renouncement at launch and holders can
+watch it come due. Once due, the next execute message removes the admin, every role holder, the
+guardian and its pending actions, the Cw20 minter and the marketing address, and unpauses the token,
+which no one could unpause afterwards. Queries report them removed as soon as it is due.
+*/
+
+use cosmwasm_std::{
+    BlockInfo, Deps, DepsMut, Env, Event, MessageInfo, Order, Response, StdResult, Timestamp
+};
+use cw_storage_plus::Item;
+use cw20::{MarketingInfoResponse, MinterResponse};
+use cw20_base::contract;
+use cw20_base::state::{MARKETING_INFO, TOKEN_INFO};
+
+use crate::error::ContractError;
+use crate::guardian::{GUARDIAN, PAUSED, PENDING_ACTIONS};
+use crate::msg::AdminStatusResponse;
+use crate::roles::ROLES;
+use crate::state::{assert_admin, ADMIN};
+
+/// When the privileges are renounced, if scheduled - kept once they are
+pub const RENOUNCE_AT: Item<Timestamp> = Item::new("renounce_at");
+
+
+/// Whether the scheduled renouncement, if any, is due - the privileges are renounced then, even
+/// before the next execute message removes them.
+pub fn is_due(deps: Deps, block: &BlockInfo) -> StdResult<bool> {
+    Ok(RENOUNCE_AT.may_load(deps.storage)?.is_some_and(|at| block.time >= at))
+}
+
+
+/// Schedule the renouncement of every privilege - admin only. It goes through on its own once the
+/// delay has passed, unless cancelled before.
+/// # Arguments
+/// * `deps`  - mutable dependency which has the storage (state) of the chain
+/// * `env`   - environment variables which include block information
+/// * `info`  - message info, the sender must be the admin
+/// * `delay` - the delay before the privileges are renounced, in seconds
+/// # Returns
+/// * the execute response on Ok
+/// * the error type on Err
+pub fn execute_renounce_admin(
+    deps  : DepsMut,
+    env   : Env,
+    info  : MessageInfo,
+    delay : u64,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.storage, &info.sender)?;
+    if let Some(renounce_at) = RENOUNCE_AT.may_load(deps.storage)? {
+        return Err(ContractError::RenounceScheduled { renounce_at });
+    }
+    // the renouncement time must be representable, however long the delay
+    let renounce_at = delay
+        .checked_mul(1_000_000_000)
+        .and_then(|nanos| env.block.time.nanos().checked_add(nanos))
+        .map(Timestamp::from_nanos)
+        .ok_or(ContractError::InvalidRenounceDelay {})?;
+    RENOUNCE_AT.save(deps.storage, &renounce_at)?;
+    Ok(Response::new()
+        .add_attribute("action", "renounce_admin")
+        .add_attribute("admin", info.sender)
+        .add_attribute("renounce_at", renounce_at.seconds().to_string()))
+}
+
+
+/// Cancel the scheduled renouncement - admin only, before it is due.
+/// # Arguments
+/// * `deps` - mutable dependency which has the storage (state) of the chain
+/// * `info` - message info, the sender must be the admin
+/// # Returns
+/// * the execute response on Ok
+/// * the error type on Err
+pub fn execute_cancel_renounce(
+    deps : DepsMut,
+    info : MessageInfo,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.storage, &info.sender)?;
+    if !RENOUNCE_AT.exists(deps.storage) {
+        return Err(ContractError::NoRenounceScheduled {});
+    }
+    RENOUNCE_AT.remove(deps.storage);
+    Ok(Response::new()
+        .add_attribute("action", "cancel_renounce")
+        .add_attribute("admin", info.sender))
+}
+
+
+/// Renounce every privilege if the scheduled renouncement is due and not yet gone through - run
+/// before each execute message, so that none is made with the privileges renounced.
+/// # Arguments
+/// * `deps`  - mutable dependency which has the storage (state) of the chain
+/// * `block` - the current block
+/// # Returns
+/// * the `admin_renounced` event if the privileges were renounced now, None otherwise, on Ok
+/// * the error type on Err
+pub fn settle_renounce(deps: DepsMut, block: &BlockInfo) -> StdResult<Option<Event>> {
+    if !is_due(deps.as_ref(), block)? || !ADMIN.exists(deps.storage) {
+        return Ok(None);
+    }
+    ADMIN.remove(deps.storage);
+    let members = ROLES
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for (role, addr) in &members {
+        ROLES.remove(deps.storage, (role, addr));
+    }
+    GUARDIAN.remove(deps.storage);
+    PAUSED.remove(deps.storage);
+    let actions = PENDING_ACTIONS
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for id in actions {
+        PENDING_ACTIONS.remove(deps.storage, id);
+    }
+    TOKEN_INFO.update(deps.storage, |mut token_info| -> StdResult<_> {
+        token_info.mint = None;
+        Ok(token_info)
+    })?;
+    if let Some(mut marketing_info) = MARKETING_INFO.may_load(deps.storage)? {
+        marketing_info.marketing = None;
+        MARKETING_INFO.save(deps.storage, &marketing_info)?;
+    }
+    Ok(Some(Event::new("admin_renounced")
+        .add_attribute("roles_removed", members.len().to_string())))
+}
+
+
+/// Query the admin, and the renouncement of its privileges if scheduled.
+/// # Arguments
+/// * `deps` - dependency which has the storage (state) of the chain
+/// * `env`  - environment variables which include block information
+/// # Returns
+///   The admin, None once renounced, when the renouncement is scheduled for, and whether it is due
+pub fn query_admin_status(deps: Deps, env: Env) -> StdResult<AdminStatusResponse> {
+    let renounced = is_due(deps, &env.block)?;
+    let admin = match renounced {
+        true => None,
+        false => ADMIN.may_load(deps.storage)?.map(String::from),
+    };
+    let renounce_at = RENOUNCE_AT.may_load(deps.storage)?;
+    Ok(AdminStatusResponse { admin, renounce_at, renounced })
+}
+
+
+/// Query the Cw20 minter, None once the privileges are renounced.
+/// # Arguments
+/// * `deps` - dependency which has the storage (state) of the chain
+/// * `env`  - environment variables which include block information
+/// # Returns
+///   The minter and its cap, if any
+pub fn query_minter(deps: Deps, env: Env) -> StdResult<Option<MinterResponse>> {
+    match is_due(deps, &env.block)? {
+        true => Ok(None),
+        false => contract::query_minter(deps),
+    }
+}
+
+
+/// Query the marketing info, without the marketing address once the privileges are renounced.
+/// # Arguments
+/// * `deps` - dependency which has the storage (state) of the chain
+/// * `env`  - environment variables which include block information
+/// # Returns
+///   The marketing info
+pub fn query_marketing_info(deps: Deps, env: Env) -> StdResult<MarketingInfoResponse> {
+    let mut marketing_info = contract::query_marketing_info(deps)?;
+    if is_due(deps, &env.block)? {
+        marketing_info.marketing = None;
+    }
+    Ok(marketing_info)
+}
@@ -0,0 +1,157 @@
+/*
+Roles module - access control for the token's modules. Rather than every module hanging off the
+admin, each privileged operation requires a role, which the admin grants to and revokes from any
+address. The instantiator starts with every role.
+*/
+
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, Deps, DepsMut, Empty, Env, MessageInfo, Response, StdResult, Storage};
+use cw_storage_plus::Map;
+use cw20_base::state::TOKEN_INFO;
+
+use crate::error::ContractError;
+use crate::minters::{assert_not_revoked, assert_not_revoked_mint, revoke_minter};
+use crate::msg::HasRoleResponse;
+use crate::renounce::is_due;
+use crate::state::assert_admin;
+
+/// Roles that can be granted
+#[cw_serde]
+#[derive(Copy)]
+pub enum Role {
+    /// Mints on behalf of the token's minter
+    Minter,
+    /// Pauses and unpauses the token
+    Pauser,
+    /// Manages transfer restrictions, such as daily limits
+    Compliance,
+    /// Manages the token's metadata and integrations, such as the name service
+    Metadata,
+    /// Maintains the exchange rates of the backing denoms, i.e. a rate oracle
+    Rates,
+}
+
+impl Role {
+    /// Every role
+    pub fn all() -> Vec<Role> {
+        vec![Role::Minter, Role::Pauser, Role::Compliance, Role::Metadata, Role::Rates]
+    }
+
+    /// Storage key of the role
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Minter => "minter",
+            Role::Pauser => "pauser",
+            Role::Compliance => "compliance",
+            Role::Metadata => "metadata",
+            Role::Rates => "rates",
+        }
+    }
+}
+
+/// Role members, by role and address
+pub const ROLES: Map<(&str, &Addr), Empty> = Map::new("roles");
+
+
+/// Ensure that the sender holds the role
+pub fn assert_role(storage: &dyn Storage, role: Role, sender: &Addr) -> Result<(), ContractError> {
+    if !ROLES.has(storage, (role.as_str(), sender)) {
+        return Err(ContractError::Unauthorized {});
+    }
+    Ok(())
+}
+
+
+/// Grant every role to an address, i.e. the instantiator
+pub fn grant_all(storage: &mut dyn Storage, addr: &Addr) -> StdResult<()> {
+    for role in Role::all() {
+        ROLES.save(storage, (role.as_str(), addr), &Empty {})?;
+    }
+    Ok(())
+}
+
+
+/// The message info to mint with - holders of the minter role mint as the token's minter, and
+/// anyone else as themselves, so that Cw20 base accepts or rejects them as usual. Revoked minters
+/// are rejected with their own error.
+/// # Arguments
+/// * `storage` - the contract storage
+/// * `info`    - message info, with the sender minting
+/// # Returns
+/// * the message info to pass on to Cw20 base on Ok
+/// * the error type on Err
+pub fn minting_info(
+    storage : &dyn Storage,
+    info    : MessageInfo,
+) -> Result<MessageInfo, ContractError> {
+    assert_not_revoked_mint(storage, &info.sender)?;
+    if !ROLES.has(storage, (Role::Minter.as_str(), &info.sender)) {
+        return Ok(info);
+    }
+    match TOKEN_INFO.load(storage)?.mint {
+        Some(mint) => Ok(MessageInfo { sender: mint.minter, funds: info.funds }),
+        None => Ok(info),
+    }
+}
+
+
+/// Grant or revoke a role - admin only. Revoking the minter role from a holder revokes them as a
+/// minter for good, and revoked minters cannot be granted it again.
+/// # Arguments
+/// * `deps`    - mutable dependency which has the storage (state) of the chain
+/// * `env`     - environment variables which include block information
+/// * `info`    - message info, the sender must be the admin
+/// * `role`    - the role
+/// * `address` - the address granted or revoked the role
+/// * `grant`   - whether to grant, or revoke, the role
+/// # Returns
+/// * the execute response on Ok
+/// * the error type on Err
+pub fn execute_set_role(
+    deps    : DepsMut,
+    env     : Env,
+    info    : MessageInfo,
+    role    : Role,
+    address : String,
+    grant   : bool,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.storage, &info.sender)?;
+    let addr = deps.api.addr_validate(&address)?;
+    let key = (role.as_str(), &addr);
+    if grant {
+        if role == Role::Minter {
+            assert_not_revoked(deps.storage, &addr)?;
+        }
+        ROLES.save(deps.storage, key, &Empty {})?;
+    } else {
+        if role == Role::Minter && ROLES.has(deps.storage, key) {
+            revoke_minter(deps.storage, &addr, env.block.time)?;
+        }
+        ROLES.remove(deps.storage, key);
+    }
+    Ok(Response::new()
+        .add_attribute("action", if grant { "grant_role" } else { "revoke_role" })
+        .add_attribute("role", role.as_str())
+        .add_attribute("address", addr))
+}
+
+
+/// Query whether an address holds a role - none does once the privileges are renounced.
+/// # Arguments
+/// * `deps`    - dependency which has the storage (state) of the chain
+/// * `env`     - environment variables which include block information
+/// * `role`    - the role
+/// * `address` - the address
+/// # Returns
+///   Whether the address holds the role
+pub fn query_has_role(
+    deps    : Deps,
+    env     : Env,
+    role    : Role,
+    address : String,
+) -> StdResult<HasRoleResponse> {
+    let addr = deps.api.addr_validate(&address)?;
+    Ok(HasRoleResponse {
+        has_role: !is_due(deps, &env.block)? && ROLES.has(deps.storage, (role.as_str(), &addr)),
+    })
+}
@@ -0,0 +1,199 @@
+/*
+Scheduled module - send-later transfers, e.g. for payroll and grants. The sender schedules a
+transfer, whose tokens are escrowed by the token contract until the given height or time. From then
+on anyone can deliver it to the recipient, while the sender can cancel it before, getting the tokens
+back.
+*/
+
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, Deps, DepsMut, Env, MessageInfo, Order, Response, StdResult, Uint128};
+use cw_storage_plus::{Bound, Index, IndexList, IndexedMap, Item, MultiIndex};
+use cw20::Expiration;
+use cw20_base::contract::execute_transfer;
+
+use crate::error::ContractError;
+use crate::msg::{ScheduledTransferResponse, ScheduledTransfersResponse};
+use crate::sinks::execute_transfer_or_burn;
+use crate::state::{add_escrowed, release_escrowed};
+
+// Settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+
+/// A transfer escrowed until it can be delivered
+#[cw_serde]
+pub struct ScheduledTransfer {
+    /// The account the tokens come from, able to cancel the transfer
+    pub sender        : Addr,
+    /// The account the tokens are delivered to
+    pub recipient     : Addr,
+    /// Amount transferred
+    pub amount        : Uint128,
+    /// The transfer can be delivered once expired, and no longer cancelled
+    pub execute_after : Expiration,
+}
+
+/// Secondary indexes of the scheduled transfers, to query them by sender
+pub struct ScheduledIndexes<'a> {
+    pub sender : MultiIndex<'a, Addr, ScheduledTransfer, u64>,
+}
+
+impl<'a> IndexList<ScheduledTransfer> for ScheduledIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<ScheduledTransfer>> + '_> {
+        let v: Vec<&dyn Index<ScheduledTransfer>> = vec![&self.sender];
+        Box::new(v.into_iter())
+    }
+}
+
+/// All scheduled transfers, indexed by id
+pub fn scheduled_transfers<'a>() -> IndexedMap<'a, u64, ScheduledTransfer, ScheduledIndexes<'a>> {
+    let indexes = ScheduledIndexes {
+        sender: MultiIndex::new(|_, t| t.sender.clone(), "scheduled", "scheduled__sender"),
+    };
+    IndexedMap::new("scheduled", indexes)
+}
+
+/// Last scheduled transfer id used
+pub const SCHEDULED_COUNT: Item<u64> = Item::new("scheduled_count");
+
+
+/// Schedule a transfer - the sender's tokens are escrowed by the contract until `execute_after`.
+/// # Arguments
+/// * `deps`          - mutable dependency which has the storage (state) of the chain
+/// * `env`           - environment variables which include block information
+/// * `info`          - message info, the sender is transferring
+/// * `recipient`     - the recipient of the transfer
+/// * `amount`        - the amount transferred
+/// * `execute_after` - the height or time the transfer can be delivered from
+/// # Returns
+/// * the execute response on Ok
+/// * the error type on Err
+pub fn execute_schedule_transfer(
+    deps          : DepsMut,
+    env           : Env,
+    info          : MessageInfo,
+    recipient     : String,
+    amount        : Uint128,
+    execute_after : Expiration,
+) -> Result<Response, ContractError> {
+    let recipient = deps.api.addr_validate(&recipient)?;
+    let never = matches!(execute_after, Expiration::Never {});
+    if amount.is_zero() || never || execute_after.is_expired(&env.block) {
+        return Err(ContractError::InvalidSchedule {});
+    }
+
+    let id = SCHEDULED_COUNT.may_load(deps.storage)?.unwrap_or_default() + 1;
+    SCHEDULED_COUNT.save(deps.storage, &id)?;
+    let transfer = ScheduledTransfer {
+        sender: info.sender.clone(),
+        recipient,
+        amount,
+        execute_after,
+    };
+    scheduled_transfers().save(deps.storage, id, &transfer)?;
+
+    // the contract holds the tokens in the meantime
+    add_escrowed(deps.storage, amount)?;
+    let contract = env.contract.address.to_string();
+    let res = execute_transfer(deps, env, info, contract, amount)?;
+    Ok(res
+        .add_attribute("scheduled_id", id.to_string())
+        .add_attribute("execute_after", execute_after.to_string()))
+}
+
+
+/// Execute a scheduled transfer - anyone delivers it to the recipient, once due.
+/// # Arguments
+/// * `deps` - mutable dependency which has the storage (state) of the chain
+/// * `env`  - environment variables which include block information
+/// * `id`   - the scheduled transfer
+/// # Returns
+/// * the execute response on Ok
+/// * the error type on Err
+pub fn execute_scheduled(deps: DepsMut, env: Env, id: u64) -> Result<Response, ContractError> {
+    let transfer = scheduled_transfers().load(deps.storage, id)?;
+    if !transfer.execute_after.is_expired(&env.block) {
+        return Err(ContractError::NotDue {});
+    }
+    scheduled_transfers().remove(deps.storage, id)?;
+    release_escrowed(deps.storage, transfer.amount)?;
+
+    let info = MessageInfo { sender: env.contract.address.clone(), funds: vec![] };
+    let recipient = transfer.recipient.into();
+    let res = execute_transfer_or_burn(deps, env, info, recipient, transfer.amount)?;
+    Ok(res.add_attribute("scheduled_id", id.to_string()))
+}
+
+
+/// Cancel a scheduled transfer - the sender gets the tokens back, as long as it is not due.
+/// # Arguments
+/// * `deps` - mutable dependency which has the storage (state) of the chain
+/// * `env`  - environment variables which include block information
+/// * `info` - message info, the sender must be the transfer's sender
+/// * `id`   - the scheduled transfer
+/// # Returns
+/// * the execute response on Ok
+/// * the error type on Err
+pub fn execute_cancel_scheduled(
+    deps : DepsMut,
+    env  : Env,
+    info : MessageInfo,
+    id   : u64,
+) -> Result<Response, ContractError> {
+    let transfer = scheduled_transfers().load(deps.storage, id)?;
+    if transfer.sender != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    if transfer.execute_after.is_expired(&env.block) {
+        return Err(ContractError::AlreadyDue {});
+    }
+    scheduled_transfers().remove(deps.storage, id)?;
+    release_escrowed(deps.storage, transfer.amount)?;
+
+    let contract_info = MessageInfo { sender: env.contract.address.clone(), funds: vec![] };
+    let res = execute_transfer(deps, env, contract_info, info.sender.into(), transfer.amount)?;
+    Ok(res
+        .add_attribute("cancelled", "true")
+        .add_attribute("scheduled_id", id.to_string()))
+}
+
+
+/// Query the scheduled transfers of a sender, with whether they are due.
+/// # Arguments
+/// * `deps`        - dependency which has the storage (state) of the chain
+/// * `env`         - environment variables which include block information
+/// * `sender`      - the sender address
+/// * `start_after` - the scheduled transfer id to start after
+/// * `limit`       - the list size limit
+/// # Returns
+///   The list of scheduled transfers
+pub fn query_scheduled_transfers(
+    deps        : Deps,
+    env         : Env,
+    sender      : String,
+    start_after : Option<u64>,
+    limit       : Option<u32>,
+) -> StdResult<ScheduledTransfersResponse> {
+    let addr = deps.api.addr_validate(&sender)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let transfers = scheduled_transfers()
+        .idx
+        .sender
+        .prefix(addr)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            item.map(|(id, transfer)| ScheduledTransferResponse {
+                id,
+                due           : transfer.execute_after.is_expired(&env.block),
+                sender        : transfer.sender.into(),
+                recipient     : transfer.recipient.into(),
+                amount        : transfer.amount,
+                execute_after : transfer.execute_after,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+    Ok(ScheduledTransfersResponse { transfers })
+}
@@ -0,0 +1,123 @@
+/*
+Sends module - sends refunded when their receive hook fails. A send moves the tokens to the
+receiving contract and calls its receive hook, as in cw20 base, but the hook is a submessage
+replying always: should the contract reject the tokens, the move is reverted in the reply and a
+`send_failed` event reports why, rather than the whole transaction failing deep in the call stack.
+Sends made from a receive hook stack up, each reply settling the latest pending send.
+*/
+
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{
+    Addr, Binary, Deps, DepsMut, Env, Event, MessageInfo, ReplyOn, Response, StdError, StdResult,
+    SubMsgResult, Uint128
+};
+use cw_storage_plus::Item;
+use cw20_base::contract::execute_send;
+use cw20_base::state::BALANCES;
+
+use crate::error::ContractError;
+
+/// Reply id of the receive hook submessage
+pub const SEND_REPLY_ID: u64 = 6;
+
+/// A send waiting for the reply of its receive hook, to be reverted should it fail
+#[cw_serde]
+pub struct PendingSend {
+    /// The address sending
+    pub owner    : Addr,
+    /// The receiving contract
+    pub contract : Addr,
+    /// The amount sent
+    pub amount   : Uint128,
+}
+
+/// The pending sends, the latest last
+pub const PENDING_SENDS: Item<Vec<PendingSend>> = Item::new("pending_sends");
+
+
+/// Send - as the Cw20 base one, with the receive hook replying to the contract so that the send
+/// is reverted should it fail.
+/// # Arguments
+/// * `deps`     - mutable dependency which has the storage (state) of the chain
+/// * `env`      - environment variables which include block information
+/// * `info`     - message info, the sender is sending
+/// * `contract` - the receiving contract
+/// * `amount`   - the amount sent
+/// * `msg`      - the message passed on to the receive hook
+/// # Returns
+/// * the execute response on Ok
+/// * the error type on Err
+pub fn execute_send_refundable(
+    mut deps : DepsMut,
+    env      : Env,
+    info     : MessageInfo,
+    contract : String,
+    amount   : Uint128,
+    msg      : Binary,
+) -> Result<Response, ContractError> {
+    let owner = info.sender.clone();
+    let mut res = execute_send(deps.branch(), env, info, contract.clone(), amount, msg)?;
+    let pending = PendingSend { owner, contract: deps.api.addr_validate(&contract)?, amount };
+    let mut pending_sends = PENDING_SENDS.may_load(deps.storage)?.unwrap_or_default();
+    pending_sends.push(pending);
+    PENDING_SENDS.save(deps.storage, &pending_sends)?;
+    for hook in res.messages.iter_mut() {
+        hook.id = SEND_REPLY_ID;
+        hook.reply_on = ReplyOn::Always;
+    }
+    Ok(res)
+}
+
+
+/// The accounts of the send waiting for its reply, if any - whose balances the reply reverts
+/// should the receive hook have failed.
+pub fn pending_send_accounts(deps: Deps) -> StdResult<Vec<Addr>> {
+    let pending_sends = PENDING_SENDS.may_load(deps.storage)?.unwrap_or_default();
+    Ok(pending_sends
+        .last()
+        .map(|pending| vec![pending.owner.clone(), pending.contract.clone()])
+        .unwrap_or_default())
+}
+
+
+/// Send reply - should the receive hook have failed, move the tokens back to the sender.
+/// # Arguments
+/// * `deps`   - mutable dependency which has the storage (state) of the chain
+/// * `result` - the result of the receive hook
+/// # Returns
+/// * the response on Ok
+/// * the error type on Err
+pub fn reply_send(deps: DepsMut, result: SubMsgResult) -> Result<Response, ContractError> {
+    let mut pending_sends = PENDING_SENDS.may_load(deps.storage)?.unwrap_or_default();
+    let pending = pending_sends
+        .pop()
+        .ok_or_else(|| StdError::generic_err("No pending send"))?;
+    match pending_sends.is_empty() {
+        true => PENDING_SENDS.remove(deps.storage),
+        false => PENDING_SENDS.save(deps.storage, &pending_sends)?,
+    }
+
+    let error = match result {
+        SubMsgResult::Ok(_) => return Ok(Response::new()),
+        SubMsgResult::Err(error) => error,
+    };
+
+    // the failed hook was rolled back, so the contract still holds the tokens sent
+    BALANCES.update(deps.storage, &pending.contract, |balance| -> StdResult<_> {
+        Ok(balance.unwrap_or_default().checked_sub(pending.amount)?)
+    })?;
+    BALANCES.update(deps.storage, &pending.owner, |balance| -> StdResult<_> {
+        Ok(balance.unwrap_or_default().checked_add(pending.amount)?)
+    })?;
+
+    let event = Event::new("send_failed")
+        .add_attribute("owner", &pending.owner)
+        .add_attribute("contract", &pending.contract)
+        .add_attribute("amount", pending.amount)
+        .add_attribute("error", error);
+    Ok(Response::new()
+        .add_event(event)
+        .add_attribute("action", "send_refunded")
+        .add_attribute("owner", pending.owner)
+        .add_attribute("amount", pending.amount))
+}
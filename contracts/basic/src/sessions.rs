@@ -0,0 +1,219 @@
+/*
+Sessions module - spend sessions for wallets that should not hold the owner's main key. An owner
+registers a session public key allowed to transfer up to a cap until an expiration. Transfers are
+signed off-chain by the session key and submitted by anyone (a relayer, or the wallet itself), and
+the session revokes itself once its cap is spent.
+*/
+
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{
+    Addr, Binary, Deps, DepsMut, Env, MessageInfo, Order, Response, StdResult, Uint128
+};
+use cw_storage_plus::{Bound, Item, Map};
+use cw20::Expiration;
+use cw20_base::contract::execute_transfer;
+use sha2::{Digest, Sha256};
+
+use crate::corridors::assert_corridor;
+use crate::error::ContractError;
+use crate::limits::spend_limit;
+use crate::msg::{SessionResponse, SessionsResponse};
+
+// Settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+
+/// A spend session of an owner
+#[cw_serde]
+pub struct Session {
+    /// Secp256k1 public key signing the session's transfers, compressed or not
+    pub pubkey     : Binary,
+    /// Total amount the session can transfer
+    pub max_amount : Uint128,
+    /// Amount transferred so far
+    pub spent      : Uint128,
+    /// The session cannot transfer once expired
+    pub expires    : Expiration,
+    /// Number of transfers made, which the next transfer must sign to prevent replays
+    pub nonce      : u64,
+}
+
+/// Sessions, by owner and session id
+pub const SESSIONS: Map<(&Addr, u64), Session> = Map::new("sessions");
+
+/// Last session id used
+pub const SESSION_COUNT: Item<u64> = Item::new("session_count");
+
+
+/// The digest a session key signs to transfer - the sha-256 hash of
+/// `{contract}/{owner}/{session_id}/{nonce}/{recipient}/{amount}`.
+/// # Arguments
+/// * `contract`   - this token contract's address
+/// * `owner`      - the session owner
+/// * `session_id` - the session
+/// * `nonce`      - the session's current nonce
+/// * `recipient`  - the recipient of the transfer
+/// * `amount`     - the amount transferred
+/// # Returns
+///   The digest to sign
+pub fn session_digest(
+    contract   : &Addr,
+    owner      : &Addr,
+    session_id : u64,
+    nonce      : u64,
+    recipient  : &str,
+    amount     : Uint128,
+) -> Vec<u8> {
+    let payload = format!("{}/{}/{}/{}/{}/{}", contract, owner, session_id, nonce, recipient, amount);
+    Sha256::digest(payload.as_bytes()).to_vec()
+}
+
+
+/// Register session - the sender allows a session key to transfer up to `max_amount` of their
+/// balance until `expires`.
+/// # Arguments
+/// * `deps`       - mutable dependency which has the storage (state) of the chain
+/// * `env`        - environment variables which include block information
+/// * `info`       - message info, the sender is the owner
+/// * `pubkey`     - the session's secp256k1 public key
+/// * `max_amount` - the total amount the session can transfer
+/// * `expires`    - when the session stops being usable
+/// # Returns
+/// * the execute response on Ok
+/// * the error type on Err
+pub fn execute_register_session(
+    deps       : DepsMut,
+    env        : Env,
+    info       : MessageInfo,
+    pubkey     : Binary,
+    max_amount : Uint128,
+    expires    : Expiration,
+) -> Result<Response, ContractError> {
+    if ![33, 65].contains(&pubkey.len()) || max_amount.is_zero() || expires.is_expired(&env.block) {
+        return Err(ContractError::InvalidSession {});
+    }
+    let id = SESSION_COUNT.may_load(deps.storage)?.unwrap_or_default() + 1;
+    SESSION_COUNT.save(deps.storage, &id)?;
+    let session = Session { pubkey, max_amount, spent: Uint128::zero(), expires, nonce: 0 };
+    SESSIONS.save(deps.storage, (&info.sender, id), &session)?;
+    Ok(Response::new()
+        .add_attribute("action", "register_session")
+        .add_attribute("session_id", id.to_string())
+        .add_attribute("owner", info.sender)
+        .add_attribute("max_amount", max_amount))
+}
+
+
+/// Revoke session - the owner revokes one of their sessions.
+/// # Arguments
+/// * `deps`       - mutable dependency which has the storage (state) of the chain
+/// * `info`       - message info, the sender is the owner
+/// * `session_id` - the session to revoke
+/// # Returns
+/// * the execute response on Ok
+/// * the error type on Err
+pub fn execute_revoke_session(
+    deps       : DepsMut,
+    info       : MessageInfo,
+    session_id : u64,
+) -> Result<Response, ContractError> {
+    SESSIONS.load(deps.storage, (&info.sender, session_id))?;
+    SESSIONS.remove(deps.storage, (&info.sender, session_id));
+    Ok(Response::new()
+        .add_attribute("action", "revoke_session")
+        .add_attribute("session_id", session_id.to_string()))
+}
+
+
+/// Session transfer - transfer from the owner's balance, signed by the session key. Anyone can
+/// submit it. The session is revoked once its cap is spent.
+/// # Arguments
+/// * `deps`       - mutable dependency which has the storage (state) of the chain
+/// * `env`        - environment variables which include block information
+/// * `owner`      - the session owner, whose tokens are transferred
+/// * `session_id` - the session
+/// * `recipient`  - the recipient of the transfer
+/// * `amount`     - the amount transferred
+/// * `signature`  - the session key's signature of the `session_digest`
+/// # Returns
+/// * the execute response on Ok
+/// * the error type on Err
+pub fn execute_session_transfer(
+    deps       : DepsMut,
+    env        : Env,
+    owner      : String,
+    session_id : u64,
+    recipient  : String,
+    amount     : Uint128,
+    signature  : Binary,
+) -> Result<Response, ContractError> {
+    let owner = deps.api.addr_validate(&owner)?;
+    let mut session = SESSIONS.load(deps.storage, (&owner, session_id))?;
+    if session.expires.is_expired(&env.block) {
+        return Err(ContractError::InvalidSession {});
+    }
+    let remaining = session.max_amount - session.spent;
+    if amount > remaining {
+        return Err(ContractError::SessionCapExceeded { remaining });
+    }
+    let digest = session_digest(
+        &env.contract.address, &owner, session_id, session.nonce, &recipient, amount
+    );
+    // a malformed signature is as invalid as a wrong one
+    let verified = deps.api.secp256k1_verify(&digest, &signature, &session.pubkey);
+    if !verified.unwrap_or(false) {
+        return Err(ContractError::InvalidSignature {});
+    }
+
+    // spend the session, revoking it once its cap is reached
+    session.nonce += 1;
+    session.spent += amount;
+    if session.spent == session.max_amount {
+        SESSIONS.remove(deps.storage, (&owner, session_id));
+    } else {
+        SESSIONS.save(deps.storage, (&owner, session_id), &session)?;
+    }
+
+    assert_corridor(deps.as_ref(), &owner, &recipient)?;
+    spend_limit(deps.storage, &env.block, &owner, amount)?;
+    let info = MessageInfo { sender: owner, funds: vec![] };
+    let res = execute_transfer(deps, env, info, recipient, amount)?;
+    Ok(res.add_attribute("session_id", session_id.to_string()))
+}
+
+
+/// Query the sessions of an owner, including expired ones.
+/// # Arguments
+/// * `deps`        - dependency which has the storage (state) of the chain
+/// * `owner`       - the owner address
+/// * `start_after` - the session id to start after
+/// * `limit`       - the list size limit
+/// # Returns
+///   The list of sessions
+pub fn query_sessions(
+    deps        : Deps,
+    owner       : String,
+    start_after : Option<u64>,
+    limit       : Option<u32>,
+) -> StdResult<SessionsResponse> {
+    let addr = deps.api.addr_validate(&owner)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let sessions = SESSIONS
+        .prefix(&addr)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            item.map(|(id, session)| SessionResponse {
+                id,
+                pubkey     : session.pubkey,
+                max_amount : session.max_amount,
+                spent      : session.spent,
+                expires    : session.expires,
+                nonce      : session.nonce,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+    Ok(SessionsResponse { sessions })
+}
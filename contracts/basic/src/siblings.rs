@@ -0,0 +1,199 @@
+/*
+Siblings module - conversions into sibling tokens, such as the next version of this token. Holders
+of the rates role (the admin, or a rate oracle) pair trusted sibling Cw20 contracts with the rate
+they are converted at, and this contract must be a minter of each. Converting burns the tokens, and
+has the sibling mint the converted amount to the sender. The mint is a submessage replying always,
+so that should the sibling fail to mint, the burnt tokens are restored to the sender.
+*/
+
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{
+    to_binary, Addr, Decimal, Deps, DepsMut, Env, MessageInfo, Order, Response, StdError,
+    StdResult, SubMsg, SubMsgResult, Uint128, WasmMsg
+};
+use cw_storage_plus::{Bound, Item, Map};
+use cw20::Cw20ExecuteMsg;
+use cw20_base::contract::execute_burn;
+use cw20_base::state::{BALANCES, TOKEN_INFO};
+
+use crate::error::ContractError;
+use crate::msg::{SiblingResponse, SiblingsResponse};
+use crate::roles::{assert_role, Role};
+use crate::state::{add_burned, TOTAL_BURNED};
+
+// Settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+
+/// Reply id of the sibling mint submessage
+pub const CONVERT_REPLY_ID: u64 = 5;
+
+/// Sibling tokens minted per token converted, by sibling contract
+pub const SIBLINGS: Map<&Addr, Decimal> = Map::new("siblings");
+
+/// A conversion waiting for the reply of its mint, to restore the tokens burnt should it fail
+#[cw_serde]
+pub struct PendingConversion {
+    /// The address converting
+    pub owner  : Addr,
+    /// The amount of tokens burnt
+    pub amount : Uint128,
+}
+
+/// The pending conversion
+pub const PENDING_CONVERSION: Item<PendingConversion> = Item::new("pending_conversion");
+
+
+/// Set the conversion rate of a sibling token - holders of the rates role only.
+/// # Arguments
+/// * `deps`  - mutable dependency which has the storage (state) of the chain
+/// * `info`  - message info, the sender must hold the rates role
+/// * `token` - the sibling Cw20 contract
+/// * `rate`  - sibling tokens minted per token converted, None to unpair it
+/// # Returns
+/// * the execute response on Ok
+/// * the error type on Err
+pub fn execute_set_sibling(
+    deps  : DepsMut,
+    info  : MessageInfo,
+    token : String,
+    rate  : Option<Decimal>,
+) -> Result<Response, ContractError> {
+    assert_role(deps.storage, Role::Rates, &info.sender)?;
+    let token = deps.api.addr_validate(&token)?;
+    let res = Response::new()
+        .add_attribute("action", "set_sibling")
+        .add_attribute("token", &token);
+    match rate {
+        Some(rate) if rate.is_zero() => Err(ContractError::InvalidRate {}),
+        Some(rate) => {
+            SIBLINGS.save(deps.storage, &token, &rate)?;
+            Ok(res.add_attribute("rate", rate.to_string()))
+        }
+        None => {
+            SIBLINGS.remove(deps.storage, &token);
+            Ok(res.add_attribute("rate", "none"))
+        }
+    }
+}
+
+
+/// Convert - burn tokens of the sender, and have the sibling token mint them the converted amount,
+/// at its current rate.
+/// # Arguments
+/// * `deps`     - mutable dependency which has the storage (state) of the chain
+/// * `env`      - environment variables which include block information
+/// * `info`     - message info, the sender is converting
+/// * `to_token` - the sibling Cw20 contract
+/// * `amount`   - the amount of tokens converted
+/// * `min_out`  - the minimum amount of sibling tokens to receive
+/// # Returns
+/// * the execute response on Ok
+/// * the error type on Err
+pub fn execute_convert(
+    deps     : DepsMut,
+    env      : Env,
+    info     : MessageInfo,
+    to_token : String,
+    amount   : Uint128,
+    min_out  : Uint128,
+) -> Result<Response, ContractError> {
+    let token = deps.api.addr_validate(&to_token)?;
+    let rate = SIBLINGS
+        .may_load(deps.storage, &token)?
+        .ok_or(ContractError::UnknownSibling { token: to_token })?;
+    let out = amount.checked_multiply_ratio(rate.atomics(), Decimal::one().atomics())
+        .map_err(|_| ContractError::InvalidRate {})?;
+    if out.is_zero() || out < min_out {
+        return Err(ContractError::ConversionSlippage { out, min_out });
+    }
+
+    let owner = info.sender.clone();
+    PENDING_CONVERSION.save(deps.storage, &PendingConversion { owner: owner.clone(), amount })?;
+    add_burned(deps.storage, amount)?;
+    let res = execute_burn(deps, env, info, amount)?;
+
+    let mint = WasmMsg::Execute {
+        contract_addr : token.to_string(),
+        msg           : to_binary(&Cw20ExecuteMsg::Mint { recipient: owner.into(), amount: out })?,
+        funds         : vec![],
+    };
+    Ok(res
+        .add_submessage(SubMsg::reply_always(mint, CONVERT_REPLY_ID))
+        .add_attribute("to_token", token)
+        .add_attribute("out", out))
+}
+
+
+/// The address of the conversion waiting for its reply, if any - whose balance the reply restores
+/// should the mint have failed.
+pub fn pending_converter(deps: Deps) -> StdResult<Option<Addr>> {
+    Ok(PENDING_CONVERSION.may_load(deps.storage)?.map(|pending| pending.owner))
+}
+
+
+/// Convert reply - should the sibling have failed to mint, restore the tokens burnt to the sender.
+/// # Arguments
+/// * `deps`   - mutable dependency which has the storage (state) of the chain
+/// * `result` - the result of the sibling mint
+/// # Returns
+/// * the response on Ok
+/// * the error type on Err
+pub fn reply_convert(deps: DepsMut, result: SubMsgResult) -> Result<Response, ContractError> {
+    let pending = PENDING_CONVERSION
+        .may_load(deps.storage)?
+        .ok_or_else(|| StdError::generic_err("No pending conversion"))?;
+    PENDING_CONVERSION.remove(deps.storage);
+
+    let error = match result {
+        SubMsgResult::Ok(_) => {
+            return Ok(Response::new()
+                .add_attribute("action", "convert_minted")
+                .add_attribute("owner", pending.owner));
+        }
+        SubMsgResult::Err(error) => error,
+    };
+
+    // the tokens were never meant to leave the supply, so they are not counted as burnt either
+    let mut token_info = TOKEN_INFO.load(deps.storage)?;
+    token_info.total_supply = token_info.total_supply
+        .checked_add(pending.amount)
+        .map_err(StdError::from)?;
+    TOKEN_INFO.save(deps.storage, &token_info)?;
+    BALANCES.update(deps.storage, &pending.owner, |balance| -> StdResult<_> {
+        Ok(balance.unwrap_or_default().checked_add(pending.amount)?)
+    })?;
+    let burned = TOTAL_BURNED.may_load(deps.storage)?.unwrap_or_default();
+    TOTAL_BURNED.save(deps.storage, &burned.saturating_sub(pending.amount))?;
+
+    Ok(Response::new()
+        .add_attribute("action", "convert_rolled_back")
+        .add_attribute("owner", pending.owner)
+        .add_attribute("amount", pending.amount)
+        .add_attribute("error", error))
+}
+
+
+/// Query the sibling tokens and their conversion rates.
+/// # Arguments
+/// * `deps`        - dependency which has the storage (state) of the chain
+/// * `start_after` - the sibling contract to start after
+/// * `limit`       - the list size limit
+/// # Returns
+///   The list of siblings, with their rate
+pub fn query_siblings(
+    deps        : Deps,
+    start_after : Option<String>,
+    limit       : Option<u32>,
+) -> StdResult<SiblingsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_addr = start_after.map(Addr::unchecked);
+    let start = start_addr.as_ref().map(Bound::exclusive);
+
+    let siblings = SIBLINGS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(token, rate)| SiblingResponse { token: token.into(), rate }))
+        .collect::<StdResult<_>>()?;
+    Ok(SiblingsResponse { siblings })
+}
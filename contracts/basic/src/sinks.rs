@@ -0,0 +1,159 @@
+/*
+Burn sinks module - "dead" addresses that users send tokens to, meaning to burn them. The admin
+configures them as burn sinks, and transfers to a sink are turned into real burns instead, so that
+the total supply reflects the tokens that can no longer move.
+*/
+
+use cosmwasm_std::{
+    Addr, Deps, DepsMut, Empty, Env, MessageInfo, Order, Response, StdResult, Storage, Uint128
+};
+use cw_storage_plus::{Bound, Map};
+use cw20_base::allowances::{execute_burn_from, execute_transfer_from};
+use cw20_base::contract::{execute_burn, execute_transfer};
+use cw20_base::state::TOKEN_INFO;
+
+use crate::error::ContractError;
+use crate::msg::BurnSinksResponse;
+use crate::state::{add_burned, assert_admin};
+
+// Settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+
+/// The burn sinks
+pub const BURN_SINKS: Map<&Addr, Empty> = Map::new("burn_sinks");
+
+
+/// Set whether an address is a burn sink - admin only.
+/// # Arguments
+/// * `deps`    - mutable dependency which has the storage (state) of the chain
+/// * `info`    - message info, the sender must be the admin
+/// * `address` - the address
+/// * `sink`    - whether transfers to the address are burnt
+/// # Returns
+/// * the execute response on Ok
+/// * the error type on Err
+pub fn execute_set_burn_sink(
+    deps    : DepsMut,
+    info    : MessageInfo,
+    address : String,
+    sink    : bool,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.storage, &info.sender)?;
+    let addr = deps.api.addr_validate(&address)?;
+    if sink {
+        BURN_SINKS.save(deps.storage, &addr, &Empty {})?;
+    } else {
+        BURN_SINKS.remove(deps.storage, &addr);
+    }
+    Ok(Response::new()
+        .add_attribute("action", "set_burn_sink")
+        .add_attribute("address", addr)
+        .add_attribute("sink", sink.to_string()))
+}
+
+
+/// Whether the recipient is a burn sink. Invalid addresses are not, the transfer failing on them.
+pub fn is_burn_sink(deps: Deps, recipient: &str) -> StdResult<bool> {
+    Ok(match deps.api.addr_validate(recipient) {
+        Ok(addr) => BURN_SINKS.has(deps.storage, &addr),
+        Err(_) => false,
+    })
+}
+
+
+/// Transfer, or burn if the recipient is a burn sink.
+/// # Arguments
+/// * `deps`      - mutable dependency which has the storage (state) of the chain
+/// * `env`       - environment variables which include block information
+/// * `info`      - message info, the sender is transferring
+/// * `recipient` - the recipient of the transfer
+/// * `amount`    - the amount to transfer
+/// # Returns
+/// * the execute response on Ok
+/// * the error type on Err
+pub fn execute_transfer_or_burn(
+    deps      : DepsMut,
+    env       : Env,
+    info      : MessageInfo,
+    recipient : String,
+    amount    : Uint128,
+) -> Result<Response, ContractError> {
+    if !is_burn_sink(deps.as_ref(), &recipient)? {
+        return Ok(execute_transfer(deps, env, info, recipient, amount)?);
+    }
+    add_burned(deps.storage, amount)?;
+    let res = execute_burn(deps, env, info, amount)?;
+    Ok(res.add_attribute("burn_sink", recipient))
+}
+
+
+/// Transfer from an owner, or burn from them if the recipient is a burn sink.
+/// # Arguments
+/// * `deps`      - mutable dependency which has the storage (state) of the chain
+/// * `env`       - environment variables which include block information
+/// * `info`      - message info, the sender is the spender
+/// * `owner`     - the owner of the tokens transferred
+/// * `recipient` - the recipient of the transfer
+/// * `amount`    - the amount to transfer
+/// # Returns
+/// * the execute response on Ok
+/// * the error type on Err
+pub fn execute_transfer_from_or_burn(
+    deps      : DepsMut,
+    env       : Env,
+    info      : MessageInfo,
+    owner     : String,
+    recipient : String,
+    amount    : Uint128,
+) -> Result<Response, ContractError> {
+    if !is_burn_sink(deps.as_ref(), &recipient)? {
+        return Ok(execute_transfer_from(deps, env, info, owner, recipient, amount)?);
+    }
+    add_burned(deps.storage, amount)?;
+    let res = execute_burn_from(deps, env, info, owner, amount)?;
+    Ok(res.add_attribute("burn_sink", recipient))
+}
+
+
+/// Burn tokens sent to a burn sink, already debited from their owner - taking them out of the
+/// total supply rather than crediting the sink.
+/// # Arguments
+/// * `storage` - the contract storage
+/// * `amount`  - the amount burnt
+/// # Returns
+/// * unit on Ok
+/// * the error type on Err
+pub fn burn_debited(storage: &mut dyn Storage, amount: Uint128) -> StdResult<()> {
+    add_burned(storage, amount)?;
+    TOKEN_INFO.update(storage, |mut info| -> StdResult<_> {
+        info.total_supply = info.total_supply.checked_sub(amount)?;
+        Ok(info)
+    })?;
+    Ok(())
+}
+
+
+/// Query the burn sinks.
+/// # Arguments
+/// * `deps`        - dependency which has the storage (state) of the chain
+/// * `start_after` - the address to start after
+/// * `limit`       - the list size limit
+/// # Returns
+///   The list of burn sinks
+pub fn query_burn_sinks(
+    deps        : Deps,
+    start_after : Option<String>,
+    limit       : Option<u32>,
+) -> StdResult<BurnSinksResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_addr = start_after.map(Addr::unchecked);
+    let start = start_addr.as_ref().map(Bound::exclusive);
+
+    let sinks = BURN_SINKS
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(String::from))
+        .collect::<StdResult<_>>()?;
+    Ok(BurnSinksResponse { sinks })
+}
@@ -0,0 +1,43 @@
+/*
+Snapshot module - a raw export of the balances for snapshot tools, which would otherwise need an
+AllAccounts query and then a Balance query per account. Balances are returned in storage key order,
+a large page at a time, along with an opaque cursor to continue from.
+Only built in debug builds, or in release builds with the "snapshot" feature.
+*/
+
+use cosmwasm_std::{Binary, Deps, Order, StdResult};
+use cw_storage_plus::Bound;
+use cw20_base::state::BALANCES;
+
+use crate::msg::RawBalanceRangeResponse;
+
+// Settings for pagination
+const MAX_LIMIT: u32 = 200;
+const DEFAULT_LIMIT: u32 = 100;
+
+
+/// Query the raw balances, in storage key order.
+/// # Arguments
+/// * `deps`   - dependency which has the storage (state) of the chain
+/// * `cursor` - the cursor returned by the previous page, None to start over
+/// * `limit`  - the page size
+/// # Returns
+///   The (address, balance) pairs, and the cursor of the next page if there may be more
+pub fn query_raw_balance_range(
+    deps   : Deps,
+    cursor : Option<Binary>,
+    limit  : Option<u32>,
+) -> StdResult<RawBalanceRangeResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT) as usize;
+    let start = cursor.map(|cursor| Bound::ExclusiveRaw(cursor.0));
+
+    let balances = BALANCES
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(addr, balance)| (addr.into_string(), balance)))
+        .collect::<StdResult<Vec<_>>>()?;
+    let next = (balances.len() == limit)
+        .then(|| balances.last().map(|(addr, _)| Binary::from(addr.as_bytes())))
+        .flatten();
+    Ok(RawBalanceRangeResponse { balances, next })
+}
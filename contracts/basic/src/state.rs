@@ -0,0 +1,86 @@
+/*
+The eames token keeps its Cw20 state (balances, allowances, token info) in the Cw20 base storage.
+This only holds the extra state shared by the token's own modules.
+*/
+
+use cosmwasm_std::{Addr, StdResult, Storage, Uint128};
+use cw_storage_plus::Item;
+use cw20_base::state::BALANCES;
+
+use crate::error::ContractError;
+
+
+/// The admin of the token, who manages the token's modules. Set to the instantiator, and removed
+/// once renounced.
+pub const ADMIN: Item<Addr> = Item::new("admin");
+
+/// Total amount of tokens ever burnt
+pub const TOTAL_BURNED: Item<Uint128> = Item::new("total_burned");
+
+/// Number of accounts holding a non-zero balance
+pub const HOLDER_COUNT: Item<u64> = Item::new("holder_count");
+
+/// Jurisdiction the token is issued under, if set at instantiation
+pub const JURISDICTION: Item<String> = Item::new("jurisdiction");
+
+/// Tokens the contract holds on behalf of others (vesting grants, scheduled transfers), which are
+/// not part of the treasury
+pub const ESCROWED: Item<Uint128> = Item::new("escrowed");
+
+/// Ensure that the sender is the admin - of which there is none once renounced
+pub fn assert_admin(storage: &dyn Storage, sender: &Addr) -> Result<(), ContractError> {
+    if ADMIN.may_load(storage)?.as_ref() != Some(sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+    Ok(())
+}
+
+
+/// Add a burnt amount to the total burnt
+pub fn add_burned(storage: &mut dyn Storage, amount: Uint128) -> StdResult<()> {
+    let burned = TOTAL_BURNED.may_load(storage)?.unwrap_or_default();
+    TOTAL_BURNED.save(storage, &(burned + amount))
+}
+
+
+/// Add tokens the contract now holds on behalf of others
+pub fn add_escrowed(storage: &mut dyn Storage, amount: Uint128) -> StdResult<()> {
+    let escrowed = ESCROWED.may_load(storage)?.unwrap_or_default();
+    ESCROWED.save(storage, &(escrowed + amount))
+}
+
+
+/// Remove tokens the contract no longer holds on behalf of others
+pub fn release_escrowed(storage: &mut dyn Storage, amount: Uint128) -> StdResult<()> {
+    let escrowed = ESCROWED.may_load(storage)?.unwrap_or_default();
+    ESCROWED.save(storage, &escrowed.checked_sub(amount)?)
+}
+
+
+/// Whether each of the given accounts currently holds tokens - to be compared after the balances
+/// have changed with `update_holder_count`.
+pub fn holders_snapshot(storage: &dyn Storage, accounts: Vec<Addr>) -> StdResult<Vec<(Addr, bool)>> {
+    accounts
+        .into_iter()
+        .map(|addr| {
+            let holds = !BALANCES.may_load(storage, &addr)?.unwrap_or_default().is_zero();
+            Ok((addr, holds))
+        })
+        .collect()
+}
+
+
+/// Update the holder count with the accounts that started or stopped holding tokens since the
+/// snapshot was taken.
+pub fn update_holder_count(storage: &mut dyn Storage, snapshot: Vec<(Addr, bool)>) -> StdResult<()> {
+    let mut count = HOLDER_COUNT.may_load(storage)?.unwrap_or_default();
+    for (addr, held) in snapshot {
+        let holds = !BALANCES.may_load(storage, &addr)?.unwrap_or_default().is_zero();
+        match (held, holds) {
+            (false, true) => count += 1,
+            (true, false) => count = count.saturating_sub(1),
+            _ => {}
+        }
+    }
+    HOLDER_COUNT.save(storage, &count)
+}
@@ -0,0 +1,225 @@
+/*
+Streams module - allowances streamed over time. An owner streams an allowance to a spender at a
+rate per second until an end time, and the spender's `TransferFrom` draws on what accrued so far
+rather than on a lump-sum allowance, like a payment stream paid out of the owner's balance. Should
+the stream not cover a transfer, it falls back on the spender's regular allowance if they hold one.
+*/
+
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{
+    Addr, BlockInfo, Deps, DepsMut, Env, MessageInfo, Order, Response, StdResult, Timestamp, Uint128
+};
+use cw_storage_plus::{Bound, Map};
+use cw20_base::state::ALLOWANCES;
+
+use crate::error::ContractError;
+use crate::msg::{StreamAllowanceResponse, StreamAllowancesResponse};
+use crate::sinks::execute_transfer_or_burn;
+
+// Settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+
+/// An allowance streamed from an owner to a spender
+#[cw_serde]
+pub struct StreamAllowance {
+    /// Amount accruing every second
+    pub rate_per_second : Uint128,
+    /// The stream accrues from then on
+    pub start           : Timestamp,
+    /// The stream stops accruing then
+    pub end             : Timestamp,
+    /// Amount transferred so far
+    pub spent           : Uint128,
+}
+
+impl StreamAllowance {
+    /// Amount accrued by the stream at the block's time, spent or not
+    pub fn accrued(&self, block: &BlockInfo) -> Uint128 {
+        let now = block.time.min(self.end).seconds();
+        self.rate_per_second * Uint128::from(now.saturating_sub(self.start.seconds()))
+    }
+
+    /// Amount the spender can still transfer at the block's time
+    pub fn available(&self, block: &BlockInfo) -> Uint128 {
+        self.accrued(block).saturating_sub(self.spent)
+    }
+}
+
+/// Stream allowances, by owner and spender
+pub const STREAM_ALLOWANCES: Map<(&Addr, &Addr), StreamAllowance> = Map::new("stream_allowances");
+
+
+/// Create stream allowance - the sender streams an allowance to the spender, accruing
+/// `rate_per_second` from now until `end`. Any stream to the spender is replaced.
+/// # Arguments
+/// * `deps`            - mutable dependency which has the storage (state) of the chain
+/// * `env`             - environment variables which include block information
+/// * `info`            - message info, the sender is the owner
+/// * `spender`         - the spender drawing on the stream
+/// * `rate_per_second` - the amount accruing every second
+/// * `end`             - when the stream stops accruing
+/// # Returns
+/// * the execute response on Ok
+/// * the error type on Err
+pub fn execute_create_stream_allowance(
+    deps            : DepsMut,
+    env             : Env,
+    info            : MessageInfo,
+    spender         : String,
+    rate_per_second : Uint128,
+    end             : Timestamp,
+) -> Result<Response, ContractError> {
+    let spender_addr = deps.api.addr_validate(&spender)?;
+    let start = env.block.time;
+    // the whole stream must be representable, so that what accrued never overflows
+    let duration = Uint128::from(end.seconds().saturating_sub(start.seconds()));
+    let total = rate_per_second.checked_mul(duration).unwrap_or_default();
+    if spender_addr == info.sender || total.is_zero() {
+        return Err(ContractError::InvalidStream {});
+    }
+
+    let stream = StreamAllowance { rate_per_second, start, end, spent: Uint128::zero() };
+    STREAM_ALLOWANCES.save(deps.storage, (&info.sender, &spender_addr), &stream)?;
+    Ok(Response::new()
+        .add_attribute("action", "create_stream_allowance")
+        .add_attribute("owner", info.sender)
+        .add_attribute("spender", spender)
+        .add_attribute("rate_per_second", rate_per_second)
+        .add_attribute("end", end.seconds().to_string()))
+}
+
+
+/// Cancel stream allowance - the owner stops the stream to the spender, including what accrued
+/// and was not transferred yet.
+/// # Arguments
+/// * `deps`    - mutable dependency which has the storage (state) of the chain
+/// * `info`    - message info, the sender is the owner
+/// * `spender` - the spender of the stream
+/// # Returns
+/// * the execute response on Ok
+/// * the error type on Err
+pub fn execute_cancel_stream_allowance(
+    deps    : DepsMut,
+    info    : MessageInfo,
+    spender : String,
+) -> Result<Response, ContractError> {
+    let spender_addr = deps.api.addr_validate(&spender)?;
+    STREAM_ALLOWANCES.load(deps.storage, (&info.sender, &spender_addr))?;
+    STREAM_ALLOWANCES.remove(deps.storage, (&info.sender, &spender_addr));
+    Ok(Response::new()
+        .add_attribute("action", "cancel_stream_allowance")
+        .add_attribute("owner", info.sender)
+        .add_attribute("spender", spender))
+}
+
+
+/// Whether the spender's `TransferFrom` of the amount draws on the stream from the owner - when
+/// there is one, unless what accrued does not cover the amount and the spender holds a regular
+/// allowance to fall back on.
+/// # Arguments
+/// * `deps`    - dependency which has the storage (state) of the chain
+/// * `block`   - the current block
+/// * `owner`   - the owner of the tokens transferred
+/// * `spender` - the spender
+/// * `amount`  - the amount to transfer
+/// # Returns
+/// * whether the transfer draws on the stream on Ok
+/// * the error type on Err
+pub fn draws_on_stream(
+    deps    : Deps,
+    block   : &BlockInfo,
+    owner   : &Addr,
+    spender : &Addr,
+    amount  : Uint128,
+) -> StdResult<bool> {
+    let stream = match STREAM_ALLOWANCES.may_load(deps.storage, (owner, spender))? {
+        Some(stream) => stream,
+        None => return Ok(false),
+    };
+    Ok(stream.available(block) >= amount || !ALLOWANCES.has(deps.storage, (owner, spender)))
+}
+
+
+/// Stream transfer - transfer from the owner on behalf of the spender, out of what accrued on
+/// their stream. The stream is removed once it ended and was fully spent.
+/// # Arguments
+/// * `deps`      - mutable dependency which has the storage (state) of the chain
+/// * `env`       - environment variables which include block information
+/// * `info`      - message info, the sender is the spender
+/// * `owner`     - the owner of the tokens transferred
+/// * `recipient` - the recipient of the transfer
+/// * `amount`    - the amount to transfer
+/// # Returns
+/// * the execute response on Ok
+/// * the error type on Err
+pub fn execute_stream_transfer(
+    deps      : DepsMut,
+    env       : Env,
+    info      : MessageInfo,
+    owner     : Addr,
+    recipient : String,
+    amount    : Uint128,
+) -> Result<Response, ContractError> {
+    let key = (&owner, &info.sender);
+    let mut stream = STREAM_ALLOWANCES.load(deps.storage, key)?;
+    let available = stream.available(&env.block);
+    if amount > available {
+        return Err(ContractError::StreamExceeded { available });
+    }
+
+    // spend the stream, removing it once there is nothing left to accrue nor to transfer
+    stream.spent += amount;
+    let total = stream.rate_per_second
+        * Uint128::from(stream.end.seconds().saturating_sub(stream.start.seconds()));
+    if stream.spent == total {
+        STREAM_ALLOWANCES.remove(deps.storage, key);
+    } else {
+        STREAM_ALLOWANCES.save(deps.storage, key, &stream)?;
+    }
+
+    let spender = info.sender;
+    let info = MessageInfo { sender: owner, funds: vec![] };
+    let res = execute_transfer_or_burn(deps, env, info, recipient, amount)?;
+    Ok(res.add_attribute("stream_spender", spender))
+}
+
+
+/// Query the allowances streamed by an owner.
+/// # Arguments
+/// * `deps`        - dependency which has the storage (state) of the chain
+/// * `env`         - environment variables, with the block time the streams accrued up to
+/// * `owner`       - the owner address
+/// * `start_after` - the spender address to start after
+/// * `limit`       - the list size limit
+/// # Returns
+///   The list of stream allowances
+pub fn query_stream_allowances(
+    deps        : Deps,
+    env         : Env,
+    owner       : String,
+    start_after : Option<String>,
+    limit       : Option<u32>,
+) -> StdResult<StreamAllowancesResponse> {
+    let owner_addr = deps.api.addr_validate(&owner)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_addr = start_after.map(Addr::unchecked);
+    let start = start_addr.as_ref().map(Bound::exclusive);
+
+    let streams = STREAM_ALLOWANCES
+        .prefix(&owner_addr)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            item.map(|(spender, stream)| StreamAllowanceResponse {
+                spender         : spender.into(),
+                available       : stream.available(&env.block),
+                rate_per_second : stream.rate_per_second,
+                start           : stream.start,
+                end             : stream.end,
+                spent           : stream.spent,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+    Ok(StreamAllowancesResponse { streams })
+}
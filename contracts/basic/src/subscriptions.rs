@@ -0,0 +1,271 @@
+/*
+Subscriptions module - pull payments without allowances. An owner authorizes a payee to collect a
+fixed amount every interval, starting right away, until an optional end. The payee pulls whatever
+is due with `Collect`, and either side can cancel the subscription at any time.
+*/
+
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{
+    Addr, Deps, DepsMut, Env, MessageInfo, Order, Response, StdResult, Timestamp, Uint128
+};
+use cw_storage_plus::{Bound, Index, IndexList, IndexedMap, Item, MultiIndex};
+use cw20_base::contract::execute_transfer;
+
+use crate::corridors::assert_corridor;
+use crate::error::ContractError;
+use crate::limits::spend_limit;
+use crate::msg::{SubscriptionResponse, SubscriptionsResponse};
+
+// Settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+
+/// A subscription from an owner to a payee
+#[cw_serde]
+pub struct Subscription {
+    /// The account paying
+    pub owner     : Addr,
+    /// The account collecting the payments
+    pub payee     : Addr,
+    /// Amount due every interval
+    pub amount    : Uint128,
+    /// Seconds between two payments
+    pub interval  : u64,
+    /// When the first payment was due
+    pub start     : Timestamp,
+    /// No payment is due at or after this time, if set
+    pub end       : Option<Timestamp>,
+    /// Number of payments collected so far
+    pub collected : u64,
+}
+
+impl Subscription {
+    /// Number of payments due by the given time since the start, including the collected ones
+    pub fn payments_due_by(&self, time: Timestamp) -> u64 {
+        let until = match self.end {
+            // the last payment is due strictly before the end
+            Some(end) if end <= time => {
+                if end <= self.start {
+                    return 0;
+                }
+                end.seconds() - 1
+            }
+            _ => time.seconds(),
+        };
+        if until < self.start.seconds() {
+            return 0;
+        }
+        (until - self.start.seconds()) / self.interval + 1
+    }
+
+    /// Amount the payee can collect at the given time
+    pub fn due(&self, time: Timestamp) -> Uint128 {
+        let periods = self.payments_due_by(time).saturating_sub(self.collected);
+        self.amount * Uint128::from(periods)
+    }
+}
+
+/// Secondary indexes of the subscriptions, to query them by either side
+pub struct SubscriptionIndexes<'a> {
+    pub owner : MultiIndex<'a, Addr, Subscription, u64>,
+    pub payee : MultiIndex<'a, Addr, Subscription, u64>,
+}
+
+impl<'a> IndexList<Subscription> for SubscriptionIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Subscription>> + '_> {
+        let v: Vec<&dyn Index<Subscription>> = vec![&self.owner, &self.payee];
+        Box::new(v.into_iter())
+    }
+}
+
+/// All subscriptions, indexed by id
+pub fn subscriptions<'a>() -> IndexedMap<'a, u64, Subscription, SubscriptionIndexes<'a>> {
+    let indexes = SubscriptionIndexes {
+        owner: MultiIndex::new(|_, s| s.owner.clone(), "subscriptions", "subscriptions__owner"),
+        payee: MultiIndex::new(|_, s| s.payee.clone(), "subscriptions", "subscriptions__payee"),
+    };
+    IndexedMap::new("subscriptions", indexes)
+}
+
+/// Last subscription id used
+pub const SUBSCRIPTION_COUNT: Item<u64> = Item::new("subscription_count");
+
+
+/// Subscribe - the sender authorizes the payee to collect `amount` every `interval` seconds.
+/// # Arguments
+/// * `deps`     - mutable dependency which has the storage (state) of the chain
+/// * `env`      - environment variables which include block information
+/// * `info`     - message info, the sender is the owner paying
+/// * `payee`    - the address collecting the payments
+/// * `amount`   - amount due every interval
+/// * `interval` - seconds between two payments
+/// * `end`      - no payment is due at or after this time, if set
+/// # Returns
+/// * the execute response on Ok
+/// * the error type on Err
+pub fn execute_subscribe(
+    deps     : DepsMut,
+    env      : Env,
+    info     : MessageInfo,
+    payee    : String,
+    amount   : Uint128,
+    interval : u64,
+    end      : Option<Timestamp>,
+) -> Result<Response, ContractError> {
+    let payee = deps.api.addr_validate(&payee)?;
+    if amount.is_zero() || interval == 0 || payee == info.sender {
+        return Err(ContractError::InvalidSubscription {});
+    }
+    if matches!(end, Some(end) if end <= env.block.time) {
+        return Err(ContractError::InvalidSubscription {});
+    }
+
+    let id = SUBSCRIPTION_COUNT.may_load(deps.storage)?.unwrap_or_default() + 1;
+    SUBSCRIPTION_COUNT.save(deps.storage, &id)?;
+    let subscription = Subscription {
+        owner     : info.sender,
+        payee,
+        amount,
+        interval,
+        start     : env.block.time,
+        end,
+        collected : 0,
+    };
+    subscriptions().save(deps.storage, id, &subscription)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "subscribe")
+        .add_attribute("subscription_id", id.to_string())
+        .add_attribute("owner", subscription.owner)
+        .add_attribute("payee", subscription.payee)
+        .add_attribute("amount", amount))
+}
+
+
+/// Collect - the payee pulls everything due on the subscription from the owner's balance.
+/// # Arguments
+/// * `deps`            - mutable dependency which has the storage (state) of the chain
+/// * `env`             - environment variables which include block information
+/// * `info`            - message info, the sender must be the payee
+/// * `subscription_id` - the subscription to collect
+/// # Returns
+/// * the execute response on Ok
+/// * the error type on Err
+pub fn execute_collect(
+    deps            : DepsMut,
+    env             : Env,
+    info            : MessageInfo,
+    subscription_id : u64,
+) -> Result<Response, ContractError> {
+    let mut subscription = subscriptions().load(deps.storage, subscription_id)?;
+    if info.sender != subscription.payee {
+        return Err(ContractError::Unauthorized {});
+    }
+    let due = subscription.due(env.block.time);
+    if due.is_zero() {
+        return Err(ContractError::NothingDue {});
+    }
+
+    subscription.collected = subscription.payments_due_by(env.block.time);
+    subscriptions().save(deps.storage, subscription_id, &subscription)?;
+
+    // the payment is a transfer from the owner, so it counts against the owner's limits
+    assert_corridor(deps.as_ref(), &subscription.owner, subscription.payee.as_str())?;
+    spend_limit(deps.storage, &env.block, &subscription.owner, due)?;
+    let owner_info = MessageInfo { sender: subscription.owner, funds: vec![] };
+    let res = execute_transfer(deps, env, owner_info, subscription.payee.into(), due)?;
+    Ok(res.add_attribute("subscription_id", subscription_id.to_string()))
+}
+
+
+/// Cancel a subscription - either the owner or the payee can cancel it. Anything due but not yet
+/// collected is forfeited.
+/// # Arguments
+/// * `deps`            - mutable dependency which has the storage (state) of the chain
+/// * `info`            - message info, the sender must be the owner or the payee
+/// * `subscription_id` - the subscription to cancel
+/// # Returns
+/// * the execute response on Ok
+/// * the error type on Err
+pub fn execute_cancel_subscription(
+    deps            : DepsMut,
+    info            : MessageInfo,
+    subscription_id : u64,
+) -> Result<Response, ContractError> {
+    let subscription = subscriptions().load(deps.storage, subscription_id)?;
+    if info.sender != subscription.owner && info.sender != subscription.payee {
+        return Err(ContractError::Unauthorized {});
+    }
+    subscriptions().remove(deps.storage, subscription_id)?;
+    Ok(Response::new()
+        .add_attribute("action", "cancel_subscription")
+        .add_attribute("subscription_id", subscription_id.to_string())
+        .add_attribute("by", info.sender))
+}
+
+
+/// Convert a subscription to its response, with what is due at the current block
+fn to_response(id: u64, subscription: Subscription, env: &Env) -> SubscriptionResponse {
+    SubscriptionResponse {
+        id,
+        due       : subscription.due(env.block.time),
+        owner     : subscription.owner.into(),
+        payee     : subscription.payee.into(),
+        amount    : subscription.amount,
+        interval  : subscription.interval,
+        start     : subscription.start,
+        end       : subscription.end,
+        collected : subscription.collected,
+    }
+}
+
+
+/// Query a single subscription.
+/// # Arguments
+/// * `deps`            - dependency which has the storage (state) of the chain
+/// * `env`             - environment variables which include block information
+/// * `subscription_id` - the subscription id
+/// # Returns
+///   The subscription, with what is currently due
+pub fn query_subscription(
+    deps            : Deps,
+    env             : Env,
+    subscription_id : u64,
+) -> StdResult<SubscriptionResponse> {
+    let subscription = subscriptions().load(deps.storage, subscription_id)?;
+    Ok(to_response(subscription_id, subscription, &env))
+}
+
+
+/// Query the subscriptions of an owner or of a payee.
+/// # Arguments
+/// * `deps`        - dependency which has the storage (state) of the chain
+/// * `env`         - environment variables which include block information
+/// * `address`     - the owner or payee address
+/// * `by_payee`    - whether to query the subscriptions paid to, rather than paid by, the address
+/// * `start_after` - the subscription id to start after
+/// * `limit`       - the list size limit
+/// # Returns
+///   The list of subscriptions
+pub fn query_subscriptions(
+    deps        : Deps,
+    env         : Env,
+    address     : String,
+    by_payee    : bool,
+    start_after : Option<u64>,
+    limit       : Option<u32>,
+) -> StdResult<SubscriptionsResponse> {
+    let addr = deps.api.addr_validate(&address)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let map = subscriptions();
+    let index = if by_payee { &map.idx.payee } else { &map.idx.owner };
+    let subscriptions = index
+        .prefix(addr)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(id, subscription)| to_response(id, subscription, &env)))
+        .collect::<StdResult<_>>()?;
+    Ok(SubscriptionsResponse { subscriptions })
+}
@@ -1,13 +1,85 @@
 /// Test module - we haven't implemented any logic here other than the Cw20 base so it might not matter a lot
 /// at the moment. But this can be extended however required.
 #[cfg(test)]
-mod test {
+mod tests {
     use crate::contract::*;
-    use cosmwasm_std::{Uint128, MessageInfo, Env, Response};
-    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
-    use cw20::{Cw20Coin, TokenInfoResponse};
-    use cw20_base::contract::query_token_info;
-    use cw20_base::msg::InstantiateMsg;
+    use crate::msg::{
+        ExecuteMsg, QueryMsg, RemainingLimitResponse, ResolveNameResponse, SubscriptionResponse,
+        SubscriptionsResponse, TokenInfoExtendedResponse, LockedBalanceResponse, VestingResponse,
+        HasRoleResponse, GuardianResponse, PendingActionsResponse, SessionsResponse,
+        InstantiateMsg, SpendProposalsResponse, BurnSinksResponse,
+        ScheduledTransfersResponse, SupportedInterfacesResponse,
+        ReservesResponse, ReserveResponse, StreamAllowancesResponse, SupplyHistoryResponse,
+        FlashMintFeeResponse, RevokedMinter, RevokedMintersResponse, MigrateMsg, DependentsResponse,
+        LedgerDeltaResponse, ValidateAddressResponse, ValidateAmountResponse, RedemptionAsset,
+        RedemptionRateResponse, PermitQueryMsg, ReceiptResponse, SiblingsResponse,
+        CorridorResponse, GroupMembersResponse, GroupsResponse, BalanceExtendedResponse,
+        AccountsExtendedResponse, FormatAmountResponse, ParseAmountResponse,
+        AllowanceExtendedResponse, AllowancesExtendedResponse, AdminStatusResponse
+    };
+    #[cfg(any(debug_assertions, feature = "invariants"))]
+    use crate::msg::InvariantsResponse;
+    #[cfg(any(debug_assertions, feature = "snapshot"))]
+    use crate::msg::RawBalanceRangeResponse;
+    use crate::guardian::{AdminAction, TIMELOCK_DELAY};
+    use crate::basket::BasketAsset;
+    use crate::corridors::Corridor;
+    use crate::sends::SEND_REPLY_ID;
+    use crate::siblings::CONVERT_REPLY_ID;
+    use crate::batch::SendTarget;
+    use crate::dependents::{DependentExecuteMsg, NotifyStatus, NOTIFY_REPLY_ID};
+    use crate::ledger::LedgerCursor;
+    use crate::middleware::{run_pipeline, Handler};
+    use crate::logo::{LogoPolicy, LogoType};
+    use crate::permits::{permit_digest, Permit};
+    use crate::sessions::session_digest;
+    use crate::treasury::{SpendAmount, Treasury, TreasuryInit, TREASURY};
+    use crate::interfaces::{declare_interfaces, SUPPORTED_INTERFACES};
+    use crate::converter::RESERVES;
+    use crate::flash::{FlashMintHookMsg, FLASH_MINT_REPLY_ID};
+    use crate::helpers::EamesToken;
+    use crate::roles::Role;
+    use crate::transfers::TransferPolicy;
+    use crate::vesting::VestingSchedule;
+    use crate::names::{NameServiceQueryMsg, ResolveRecordResponse};
+    use crate::ContractError;
+    use cosmwasm_std::{
+        Uint128, MessageInfo, Env, Response, DepsMut, from_binary, to_binary, ContractResult,
+        SystemResult, WasmQuery, Reply, SubMsgResponse, SubMsgResult, coins, Binary, Addr, Deps,
+        coin, BankMsg, CosmosMsg, Decimal, Timestamp, ContractInfoResponse, SystemError, SubMsg,
+        QuerierWrapper, WasmMsg, StdResult, ReplyOn
+    };
+    use cosmwasm_std::testing::{
+        mock_dependencies, mock_env, mock_info, MockStorage, MOCK_CONTRACT_ADDR
+    };
+    use cosmwasm_std::{Order, Storage};
+    use proptest::prelude::*;
+    use cw20::{Cw20Coin, EmbeddedLogo, Expiration, Logo, MinterResponse, TokenInfoResponse};
+    use cw20_base::contract::{query_balance, query_token_info};
+    use cw20_base::state::ALLOWANCES_SPENDER;
+
+    /// instantiate the GOLD token with the given initial balance for "sender", who is also the admin
+    fn do_instantiate(deps: DepsMut, amount: u128) {
+        let msg = InstantiateMsg {
+            name                : "GOLD".to_string(),
+            symbol              : "GLD".to_string(),
+            decimals            : 10,
+            initial_balances    : vec![
+                Cw20Coin {
+                    address  : String::from("sender"),
+                    amount   : Uint128::new(amount),
+                }
+            ],
+            mint                : None,
+            marketing           : None,
+            jurisdiction        : None,
+            compliance_admin    : None,
+            default_daily_limit : None,
+            treasury            : None,
+            test_faucet         : false,
+        };
+        instantiate(deps, mock_env(), mock_info("sender", &[]), msg).unwrap();
+    }
 
     /// basic instantiation test verifying instantiate response and the token's information
     /// by querying.
@@ -16,19 +88,24 @@ mod test {
         // instantiate and assert response
         let mut deps = mock_dependencies();
         let env : Env            = mock_env();
-        let info: MessageInfo    = mock_info(&"sender", &[]);
+        let info: MessageInfo    = mock_info("sender", &[]);
         let msg : InstantiateMsg = InstantiateMsg {
-            name             : "GOLD".to_string(),
-            symbol           : "GLD".to_string(),
-            decimals         : 10,
-            initial_balances : vec![
+            name                : "GOLD".to_string(),
+            symbol              : "GLD".to_string(),
+            decimals            : 10,
+            initial_balances    : vec![
                 Cw20Coin {
                     address  : String::from("sender"),
                     amount   : Uint128::new(1928334),
                 }
             ],
-            mint             : None,
-            marketing        : None,
+            mint                : None,
+            marketing           : None,
+            jurisdiction        : None,
+            compliance_admin    : None,
+            default_daily_limit : None,
+            treasury            : None,
+            test_faucet         : false,
         };
         let res: Response = instantiate(deps.as_mut(), env, info, msg).unwrap();
         assert_eq!(0, res.messages.len());
@@ -44,4 +121,2867 @@ mod test {
             }
         );
     }
+
+    /// daily limits test - default and per-address limits are enforced on outbound transfers,
+    /// and reset once the window has passed.
+    #[test]
+    fn daily_limit_test() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut(), 10_000);
+
+        // only the admin can set limits
+        let msg = ExecuteMsg::SetDefaultDailyLimit { limit: Some(Uint128::new(100)) };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("other", &[]), msg.clone())
+            .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), msg).unwrap();
+
+        // transfers within the limit are fine
+        let transfer = ExecuteMsg::Transfer {
+            recipient      : "rcpt".to_string(),
+            amount         : Uint128::new(60),
+            allow_contract : None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), transfer.clone()).unwrap();
+
+        // going over the limit is not
+        let err = execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), transfer.clone())
+            .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::DailyLimitExceeded {
+                limit     : Uint128::new(100),
+                remaining : Uint128::new(40),
+            }
+        );
+        let query_msg = QueryMsg::RemainingLimit { address: "sender".to_string() };
+        let res: RemainingLimitResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg.clone()).unwrap()).unwrap();
+        assert_eq!(res.limit, Some(Uint128::new(100)));
+        assert_eq!(res.remaining, Some(Uint128::new(40)));
+        assert_eq!(res.resets_at, Some(mock_env().block.time.plus_seconds(86_400)));
+
+        // a day later, the window has reset
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(86_400);
+        execute(deps.as_mut(), env.clone(), mock_info("sender", &[]), transfer).unwrap();
+
+        // per-address limit overrides the default
+        let msg = ExecuteMsg::SetDailyLimit {
+            address : "sender".to_string(),
+            limit   : Some(Uint128::new(1000)),
+        };
+        execute(deps.as_mut(), env.clone(), mock_info("sender", &[]), msg).unwrap();
+        let res: RemainingLimitResponse =
+            from_binary(&query(deps.as_ref(), env, query_msg).unwrap()).unwrap();
+        assert_eq!(res.remaining, Some(Uint128::new(940)));
+    }
+
+    /// extended token info test - holders and burnt tokens are tracked as balances change
+    #[test]
+    fn token_info_extended_test() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut(), 1000);
+
+        let query_extended = |deps: cosmwasm_std::Deps| -> TokenInfoExtendedResponse {
+            from_binary(&query(deps, mock_env(), QueryMsg::TokenInfoExtended {}).unwrap()).unwrap()
+        };
+        let res = query_extended(deps.as_ref());
+        assert_eq!(res.token_info.total_supply, Uint128::new(1000));
+        assert_eq!(res.minter, None);
+        assert_eq!(res.total_burned, Uint128::zero());
+        assert_eq!(res.holder_count, 1);
+        assert!(!res.paused);
+
+        // a new holder
+        let transfer = ExecuteMsg::Transfer {
+            recipient      : "rcpt".to_string(),
+            amount         : Uint128::new(400),
+            allow_contract : None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), transfer).unwrap();
+        assert_eq!(query_extended(deps.as_ref()).holder_count, 2);
+
+        // burning everything stops holding
+        let burn = ExecuteMsg::Burn { amount: Uint128::new(400) };
+        execute(deps.as_mut(), mock_env(), mock_info("rcpt", &[]), burn).unwrap();
+        let res = query_extended(deps.as_ref());
+        assert_eq!(res.holder_count, 1);
+        assert_eq!(res.total_burned, Uint128::new(400));
+        assert_eq!(res.token_info.total_supply, Uint128::new(600));
+
+        // pausing shows
+        execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), ExecuteMsg::Pause {}).unwrap();
+        assert!(query_extended(deps.as_ref()).paused);
+    }
+
+    /// subscriptions test - the payee pulls what is due every interval, and nothing more
+    #[test]
+    fn subscription_test() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut(), 1000);
+
+        // pay 100 to "payee" every day
+        let subscribe = ExecuteMsg::Subscribe {
+            payee    : "payee".to_string(),
+            amount   : Uint128::new(100),
+            interval : 86_400,
+            end      : None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), subscribe).unwrap();
+
+        // only the payee can collect, and the first payment is due right away
+        let collect = ExecuteMsg::Collect { subscription_id: 1 };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("other", &[]), collect.clone())
+            .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        execute(deps.as_mut(), mock_env(), mock_info("payee", &[]), collect.clone()).unwrap();
+
+        // nothing more due until the next interval
+        let err = execute(deps.as_mut(), mock_env(), mock_info("payee", &[]), collect.clone())
+            .unwrap_err();
+        assert_eq!(err, ContractError::NothingDue {});
+
+        // two days later, two more payments are due
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(2 * 86_400);
+        let query_msg = QueryMsg::Subscription { subscription_id: 1 };
+        let res: SubscriptionResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), query_msg).unwrap()).unwrap();
+        assert_eq!(res.due, Uint128::new(200));
+        execute(deps.as_mut(), env.clone(), mock_info("payee", &[]), collect.clone()).unwrap();
+        assert_eq!(
+            cw20_base::contract::query_balance(deps.as_ref(), "payee".to_string()).unwrap().balance,
+            Uint128::new(300)
+        );
+
+        // both sides can see the subscription
+        let query_msg = QueryMsg::SubscriptionsByPayee {
+            payee       : "payee".to_string(),
+            start_after : None,
+            limit       : None,
+        };
+        let res: SubscriptionsResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), query_msg).unwrap()).unwrap();
+        assert_eq!(res.subscriptions.len(), 1);
+        assert_eq!(res.subscriptions[0].owner, "sender");
+        assert_eq!(res.subscriptions[0].collected, 3);
+
+        // once cancelled, nothing can be collected anymore
+        let cancel = ExecuteMsg::CancelSubscription { subscription_id: 1 };
+        execute(deps.as_mut(), env.clone(), mock_info("sender", &[]), cancel).unwrap();
+        execute(deps.as_mut(), env, mock_info("payee", &[]), collect).unwrap_err();
+    }
+
+    /// name service test - names registered in the name service are resolved, and anything else
+    /// is treated as a plain address
+    #[test]
+    fn transfer_to_name_test() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut(), 1000);
+
+        // the name service only knows "alice.eames"
+        deps.querier.update_wasm(|query| match query {
+            WasmQuery::Smart { msg, .. } => {
+                let NameServiceQueryMsg::ResolveRecord { name } = from_binary(msg).unwrap();
+                let address = (name == "alice.eames").then(|| "alice".to_string());
+                SystemResult::Ok(ContractResult::Ok(
+                    to_binary(&ResolveRecordResponse { address }).unwrap()
+                ))
+            }
+            _ => panic!("unexpected query"),
+        });
+        let msg = ExecuteMsg::SetNameService { contract: Some("names".to_string()) };
+        execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), msg).unwrap();
+
+        let query_msg = QueryMsg::ResolveName { name: "alice.eames".to_string() };
+        let res: ResolveNameResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(res.address, "alice");
+        assert!(res.from_name_service);
+
+        // transfer to the name, and to a plain address
+        for (name, amount) in [("alice.eames", 100), ("bob", 50)] {
+            let transfer = ExecuteMsg::TransferToName {
+                recipient_name : name.to_string(),
+                amount         : Uint128::new(amount),
+            };
+            execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), transfer).unwrap();
+        }
+        let balance = |deps: cosmwasm_std::Deps, addr: &str| {
+            cw20_base::contract::query_balance(deps, addr.to_string()).unwrap().balance
+        };
+        assert_eq!(balance(deps.as_ref(), "alice"), Uint128::new(100));
+        assert_eq!(balance(deps.as_ref(), "bob"), Uint128::new(50));
+    }
+
+    /// locks test - locked tokens cannot be moved until the unlocker releases them or they expire
+    #[test]
+    fn lock_test() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut(), 1000);
+
+        // lock 600 for "lender" until some time after the current block
+        let expiry = mock_env().block.time.plus_seconds(1000);
+        let lock = ExecuteMsg::Lock {
+            amount   : Uint128::new(600),
+            unlocker : "lender".to_string(),
+            until    : Expiration::AtTime(expiry),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), lock.clone()).unwrap();
+
+        // cannot lock more than the free balance
+        let err = execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), lock).unwrap_err();
+        assert_eq!(err, ContractError::LockedBalance {
+            locked  : Uint128::new(1200),
+            balance : Uint128::new(1000),
+        });
+
+        let query_msg = QueryMsg::LockedBalance { address: "sender".to_string() };
+        let res: LockedBalanceResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg.clone()).unwrap()).unwrap();
+        assert_eq!(res.locked, Uint128::new(600));
+        assert_eq!(res.free, Uint128::new(400));
+
+        // only the free balance can be transferred
+        let transfer = |amount: u128| ExecuteMsg::Transfer {
+            recipient      : "other".to_string(),
+            amount         : Uint128::new(amount),
+            allow_contract : None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), transfer(300)).unwrap();
+
+        // only the unlocker can release the lock early
+        let unlock = ExecuteMsg::Unlock { lock_id: 1 };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), unlock.clone())
+            .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        // once expired, the lock no longer applies
+        let mut env = mock_env();
+        env.block.time = expiry;
+        execute(deps.as_mut(), env.clone(), mock_info("sender", &[]), transfer(200)).unwrap();
+        execute(deps.as_mut(), env.clone(), mock_info("sender", &[]), unlock).unwrap();
+        let res: LockedBalanceResponse =
+            from_binary(&query(deps.as_ref(), env, query_msg).unwrap()).unwrap();
+        assert_eq!(res.locked, Uint128::zero());
+        assert_eq!(res.free, Uint128::new(500));
+
+        // a lock that never expires holds until released, so moving more than the free balance fails
+        let lock = ExecuteMsg::Lock {
+            amount   : Uint128::new(400),
+            unlocker : "lender".to_string(),
+            until    : Expiration::Never {},
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), lock).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), transfer(200))
+            .unwrap_err();
+        assert_eq!(err, ContractError::LockedBalance {
+            locked  : Uint128::new(400),
+            balance : Uint128::new(300),
+        });
+    }
+
+    /// buyback test - the router swap is a submessage, and what it bought is burnt in the reply
+    #[test]
+    fn buyback_test() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut(), 1000);
+
+        // disabled until the admin sets a router
+        let buyback = ExecuteMsg::BuybackAndBurn { min_receive: None };
+        let info = mock_info("anyone", &coins(50, "uatom"));
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), buyback.clone()).unwrap_err();
+        assert_eq!(err, ContractError::BuybackDisabled {});
+        let msg = ExecuteMsg::SetBuybackRouter { router: Some("router".to_string()) };
+        execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), msg).unwrap();
+
+        let res = execute(deps.as_mut(), mock_env(), info, buyback).unwrap();
+        assert_eq!(res.messages.len(), 1);
+        assert_eq!(res.messages[0].id, crate::buyback::BUYBACK_REPLY_ID);
+
+        // the router delivers 200 tokens to the contract, which are burnt in the reply
+        let transfer = ExecuteMsg::Transfer {
+            recipient      : MOCK_CONTRACT_ADDR.to_string(),
+            amount         : Uint128::new(200),
+            allow_contract : None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), transfer).unwrap();
+        let reply_msg = Reply {
+            id     : crate::buyback::BUYBACK_REPLY_ID,
+            result : SubMsgResult::Ok(SubMsgResponse { events: vec![], data: None }),
+        };
+        let res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+        assert!(res.attributes.iter().any(|a| a.key == "burned" && a.value == "200"));
+
+        let query_msg = QueryMsg::TokenInfoExtended {};
+        let res: TokenInfoExtendedResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(res.token_info.total_supply, Uint128::new(800));
+        assert_eq!(res.total_burned, Uint128::new(200));
+        assert_eq!(res.holder_count, 1);
+    }
+
+    /// vesting test - a minted grant is held by the contract, and claimed as it vests
+    #[test]
+    fn mint_vested_test() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            name                : "GOLD".to_string(),
+            symbol              : "GLD".to_string(),
+            decimals            : 10,
+            initial_balances    : vec![],
+            mint                : Some(MinterResponse { minter: "minter".to_string(), cap: None }),
+            marketing           : None,
+            jurisdiction        : None,
+            compliance_admin    : None,
+            default_daily_limit : None,
+            treasury            : None,
+            test_faucet         : false,
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("sender", &[]), msg).unwrap();
+
+        // grant 1000 vesting over 1000 seconds from now
+        let start = mock_env().block.time;
+        let mint = ExecuteMsg::MintVested {
+            recipient : "contributor".to_string(),
+            amount    : Uint128::new(1000),
+            schedule  : VestingSchedule { start, end: start.plus_seconds(1000) },
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("other", &[]), mint.clone())
+            .unwrap_err();
+        assert_eq!(err, ContractError::Base(cw20_base::ContractError::Unauthorized {}));
+        execute(deps.as_mut(), mock_env(), mock_info("minter", &[]), mint).unwrap();
+
+        // nothing is liquid, nor vested yet
+        let balance = |deps: cosmwasm_std::Deps, addr: &str| {
+            cw20_base::contract::query_balance(deps, addr.to_string()).unwrap().balance
+        };
+        assert_eq!(balance(deps.as_ref(), "contributor"), Uint128::zero());
+        let claim = ExecuteMsg::ClaimVested {};
+        let err = execute(deps.as_mut(), mock_env(), mock_info("contributor", &[]), claim.clone())
+            .unwrap_err();
+        assert_eq!(err, ContractError::NothingVested {});
+
+        // a quarter through the schedule, a quarter is claimable
+        let mut env = mock_env();
+        env.block.time = start.plus_seconds(250);
+        execute(deps.as_mut(), env.clone(), mock_info("contributor", &[]), claim.clone()).unwrap();
+        assert_eq!(balance(deps.as_ref(), "contributor"), Uint128::new(250));
+
+        let query_msg = QueryMsg::Vesting { address: "contributor".to_string() };
+        let res: VestingResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), query_msg.clone()).unwrap()).unwrap();
+        assert_eq!(res.grants[0].vested, Uint128::new(250));
+        assert_eq!(res.grants[0].claimed, Uint128::new(250));
+
+        // once fully vested, the rest is claimable and the grant is done
+        env.block.time = start.plus_seconds(5000);
+        execute(deps.as_mut(), env.clone(), mock_info("contributor", &[]), claim).unwrap();
+        assert_eq!(balance(deps.as_ref(), "contributor"), Uint128::new(1000));
+        let res: VestingResponse =
+            from_binary(&query(deps.as_ref(), env, query_msg).unwrap()).unwrap();
+        assert!(res.grants.is_empty());
+    }
+
+    /// roles test - privileged operations require their role, which the admin grants and revokes
+    #[test]
+    fn roles_test() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            name                : "GOLD".to_string(),
+            symbol              : "GLD".to_string(),
+            decimals            : 10,
+            initial_balances    : vec![],
+            mint                : Some(MinterResponse { minter: "minter".to_string(), cap: None }),
+            marketing           : None,
+            jurisdiction        : None,
+            compliance_admin    : None,
+            default_daily_limit : None,
+            treasury            : None,
+            test_faucet         : false,
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("sender", &[]), msg).unwrap();
+
+        let has_role = |deps: cosmwasm_std::Deps, role: Role, address: &str| -> bool {
+            let query_msg = QueryMsg::HasRole { role, address: address.to_string() };
+            let res: HasRoleResponse =
+                from_binary(&query(deps, mock_env(), query_msg).unwrap()).unwrap();
+            res.has_role
+        };
+        assert!(has_role(deps.as_ref(), Role::Compliance, "sender"));
+        assert!(!has_role(deps.as_ref(), Role::Compliance, "officer"));
+
+        // only the admin grants roles
+        let grant = ExecuteMsg::GrantRole { role: Role::Compliance, address: "officer".to_string() };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("officer", &[]), grant.clone())
+            .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), grant).unwrap();
+
+        // the compliance officer sets limits, but cannot set the name service
+        let limit = ExecuteMsg::SetDefaultDailyLimit { limit: Some(Uint128::new(100)) };
+        execute(deps.as_mut(), mock_env(), mock_info("officer", &[]), limit).unwrap();
+        let names = ExecuteMsg::SetNameService { contract: None };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("officer", &[]), names).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        // holders of the minter role mint on behalf of the minter
+        let mint = ExecuteMsg::Mint { recipient: "rcpt".to_string(), amount: Uint128::new(50) };
+        execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), mint.clone()).unwrap();
+        let revoke = ExecuteMsg::RevokeRole { role: Role::Minter, address: "sender".to_string() };
+        execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), revoke).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), mint).unwrap_err();
+        assert_eq!(err, ContractError::RevokedMinterMint { address: "sender".to_string() });
+    }
+
+    /// minter revocation test - minters rotated out or stripped of the role can neither mint nor
+    /// come back, unless restored through the timelock
+    #[test]
+    fn minter_revocation_test() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            name                : "GOLD".to_string(),
+            symbol              : "GLD".to_string(),
+            decimals            : 10,
+            initial_balances    : vec![],
+            mint                : Some(MinterResponse { minter: "minter".to_string(), cap: None }),
+            marketing           : None,
+            jurisdiction        : None,
+            compliance_admin    : None,
+            default_daily_limit : None,
+            treasury            : None,
+            test_faucet         : false,
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("sender", &[]), msg).unwrap();
+        let admin = mock_info("sender", &[]);
+        let mut env = mock_env();
+        let run_action = |deps: DepsMut, env: &mut Env, action: AdminAction| {
+            let propose = ExecuteMsg::ProposeAction { action };
+            let res = execute(deps, env.clone(), mock_info("sender", &[]), propose)?;
+            env.block.time = env.block.time.plus_seconds(TIMELOCK_DELAY);
+            Ok::<_, ContractError>(res.attributes[1].value.parse::<u64>().unwrap())
+        };
+
+        // the minter is rotated out through the timelock, and revoked
+        let rotate = AdminAction::UpdateMinter { new_minter: Some("minter2".to_string()) };
+        let action_id = run_action(deps.as_mut(), &mut env, rotate).unwrap();
+        let run = ExecuteMsg::ExecuteAction { action_id };
+        execute(deps.as_mut(), env.clone(), admin.clone(), run).unwrap();
+        let msg = QueryMsg::RevokedMinters { start_after: None, limit: None };
+        let res: RevokedMintersResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(res.minters, vec![RevokedMinter {
+            address    : "minter".to_string(),
+            revoked_at : env.block.time,
+        }]);
+
+        // its mint attempts raise the alert error, and it cannot come back
+        let mint = ExecuteMsg::Mint { recipient: "rcpt".to_string(), amount: Uint128::new(50) };
+        let err = execute(deps.as_mut(), env.clone(), mock_info("minter", &[]), mint.clone())
+            .unwrap_err();
+        assert_eq!(err, ContractError::RevokedMinterMint { address: "minter".to_string() });
+        execute(deps.as_mut(), env.clone(), mock_info("minter2", &[]), mint.clone()).unwrap();
+        let revoked = ContractError::MinterRevoked { address: "minter".to_string() };
+        let back = AdminAction::UpdateMinter { new_minter: Some("minter".to_string()) };
+        let err = run_action(deps.as_mut(), &mut env, back.clone()).unwrap_err();
+        assert_eq!(err, revoked);
+        let grant = ExecuteMsg::GrantRole { role: Role::Minter, address: "minter".to_string() };
+        let err = execute(deps.as_mut(), env.clone(), admin.clone(), grant.clone()).unwrap_err();
+        assert_eq!(err, revoked);
+
+        // unless restored through the timelock
+        let restore = AdminAction::RestoreMinter { minter: "minter".to_string() };
+        let action_id = run_action(deps.as_mut(), &mut env, restore).unwrap();
+        let run = ExecuteMsg::ExecuteAction { action_id };
+        execute(deps.as_mut(), env.clone(), admin.clone(), run).unwrap();
+        execute(deps.as_mut(), env.clone(), admin, grant).unwrap();
+        execute(deps.as_mut(), env, mock_info("minter", &[]), mint).unwrap();
+    }
+
+    /// guardian test - the guardian pauses instantly, while privileged actions wait for the timelock
+    #[test]
+    fn guardian_test() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut(), 1000);
+
+        // the minter cannot be changed directly
+        let update = ExecuteMsg::UpdateMinter { new_minter: Some("minter".to_string()) };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), update).unwrap_err();
+        assert_eq!(err, ContractError::Timelocked {});
+
+        // only the admin proposes, and executes once the delay has passed
+        let propose = |action| ExecuteMsg::ProposeAction { action };
+        let guardian = AdminAction::SetGuardian { guardian: Some("guardian".to_string()) };
+        let msg = propose(guardian.clone());
+        let err = execute(deps.as_mut(), mock_env(), mock_info("other", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), propose(guardian)).unwrap();
+
+        let run = ExecuteMsg::ExecuteAction { action_id: 1 };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), run.clone())
+            .unwrap_err();
+        let eta = mock_env().block.time.plus_seconds(TIMELOCK_DELAY);
+        assert_eq!(err, ContractError::TimelockPending { eta });
+        let mut env = mock_env();
+        env.block.time = eta;
+        execute(deps.as_mut(), env.clone(), mock_info("sender", &[]), run).unwrap();
+
+        // the guardian pauses, stopping transfers, but only the pauser unpauses
+        let (pause, unpause) = (ExecuteMsg::Pause {}, ExecuteMsg::Unpause {});
+        execute(deps.as_mut(), env.clone(), mock_info("guardian", &[]), pause).unwrap();
+        let amount = Uint128::new(10);
+        let transfer = ExecuteMsg::Transfer {
+            recipient      : "rcpt".to_string(),
+            amount,
+            allow_contract : None,
+        };
+        let err = execute(deps.as_mut(), env.clone(), mock_info("sender", &[]), transfer.clone())
+            .unwrap_err();
+        assert_eq!(err, ContractError::Paused {});
+        let res: GuardianResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::Guardian {}).unwrap()).unwrap();
+        assert_eq!(res, GuardianResponse { guardian: Some("guardian".to_string()), paused: true });
+
+        let err = execute(deps.as_mut(), env.clone(), mock_info("guardian", &[]), unpause.clone())
+            .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        execute(deps.as_mut(), env.clone(), mock_info("sender", &[]), unpause).unwrap();
+        execute(deps.as_mut(), env.clone(), mock_info("sender", &[]), transfer).unwrap();
+
+        // the guardian cancels a pending minter change
+        let minter = AdminAction::UpdateMinter { new_minter: Some("minter".to_string()) };
+        execute(deps.as_mut(), env.clone(), mock_info("sender", &[]), propose(minter)).unwrap();
+        let cap = AdminAction::UpdateCap { cap: Some(Uint128::new(500)) };
+        execute(deps.as_mut(), env.clone(), mock_info("sender", &[]), propose(cap)).unwrap();
+
+        let query_msg = QueryMsg::PendingActions { start_after: None, limit: None };
+        let res: PendingActionsResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), query_msg).unwrap()).unwrap();
+        assert_eq!(res.actions.iter().map(|a| a.id).collect::<Vec<_>>(), vec![2, 3]);
+        assert!(!res.actions[0].ready);
+
+        let cancel = ExecuteMsg::CancelAction { action_id: 2 };
+        execute(deps.as_mut(), env.clone(), mock_info("guardian", &[]), cancel).unwrap();
+
+        // a cap cannot be set on a token without a minter
+        env.block.time = env.block.time.plus_seconds(TIMELOCK_DELAY);
+        let run = ExecuteMsg::ExecuteAction { action_id: 3 };
+        let err = execute(deps.as_mut(), env.clone(), mock_info("sender", &[]), run).unwrap_err();
+        assert_eq!(err, ContractError::InvalidAction {});
+        let run = ExecuteMsg::ExecuteAction { action_id: 2 };
+        execute(deps.as_mut(), env, mock_info("sender", &[]), run).unwrap_err();
+    }
+
+    /// send from batch test - a spender fans out to several contracts with a single allowance
+    #[test]
+    fn send_from_batch_test() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut(), 1000);
+
+        let allow = ExecuteMsg::IncreaseAllowance {
+            spender : "bot".to_string(),
+            amount  : Uint128::new(300),
+            expires : None,
+            purpose : None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), allow).unwrap();
+
+        let target = |contract: &str, amount: u128| SendTarget {
+            contract : contract.to_string(),
+            amount   : Uint128::new(amount),
+            msg      : to_binary("deposit").unwrap(),
+        };
+        let batch = |targets| ExecuteMsg::SendFromBatch { owner: "sender".to_string(), targets };
+
+        // an empty batch is rejected
+        let err = execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), batch(vec![]))
+            .unwrap_err();
+        assert_eq!(err, ContractError::EmptyBatch {});
+
+        // each contract is sent to and notified, and the allowance deducted by the total
+        let msg = batch(vec![target("vault", 100), target("pool", 150)]);
+        let res = execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), msg).unwrap();
+        assert_eq!(res.messages.len(), 2);
+        let balance = |deps: cosmwasm_std::Deps, addr: &str| {
+            cw20_base::contract::query_balance(deps, addr.to_string()).unwrap().balance
+        };
+        assert_eq!(balance(deps.as_ref(), "sender"), Uint128::new(750));
+        assert_eq!(balance(deps.as_ref(), "vault"), Uint128::new(100));
+        assert_eq!(balance(deps.as_ref(), "pool"), Uint128::new(150));
+        let query_msg = QueryMsg::Allowance { owner: "sender".to_string(), spender: "bot".to_string() };
+        let res: cw20::AllowanceResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(res.allowance, Uint128::new(50));
+
+        // the total may not exceed what is left of the allowance
+        let msg = batch(vec![target("vault", 30), target("pool", 30)]);
+        let err = execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::Base(cw20_base::ContractError::Std(_))));
+        assert_eq!(balance(deps.as_ref(), "sender"), Uint128::new(750));
+
+        // zero amounts are rejected, as by send
+        let msg = batch(vec![target("vault", 10), target("pool", 0)]);
+        let err = execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::ZeroAmountTransfer {});
+
+        // what goes to a burn sink is burnt, without notifying it
+        let set_sink = ExecuteMsg::SetBurnSink { address: "dead".to_string(), sink: true };
+        execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), set_sink).unwrap();
+        let msg = batch(vec![target("vault", 10), target("dead", 20)]);
+        let res = execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), msg).unwrap();
+        assert_eq!(res.messages.len(), 1);
+        assert_eq!(balance(deps.as_ref(), "sender"), Uint128::new(720));
+        assert_eq!(balance(deps.as_ref(), "dead"), Uint128::zero());
+        let info = query_token_info(deps.as_ref()).unwrap();
+        assert_eq!(info.total_supply, Uint128::new(980));
+    }
+
+    /// logo test - uploaded logos must comply with the policy set by the metadata role
+    #[test]
+    fn logo_test() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            name                : "GOLD".to_string(),
+            symbol              : "GLD".to_string(),
+            decimals            : 10,
+            initial_balances    : vec![],
+            mint                : None,
+            marketing           : Some(cw20_base::msg::InstantiateMarketingInfo {
+                project     : None,
+                description : None,
+                marketing   : Some("sender".to_string()),
+                logo        : None,
+            }),
+            jurisdiction        : None,
+            compliance_admin    : None,
+            default_daily_limit : None,
+            treasury            : None,
+            test_faucet         : false,
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("sender", &[]), msg).unwrap();
+
+        let res: LogoPolicy =
+            from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::LogoPolicy {}).unwrap()).unwrap();
+        assert_eq!(res, LogoPolicy::default());
+
+        // only the metadata role sets the policy, within the Cw20 base cap
+        let mut policy = LogoPolicy {
+            max_bytes     : 10,
+            allowed_types : vec![LogoType::Png],
+            url_only      : false,
+        };
+        let set = |policy: &LogoPolicy| ExecuteMsg::SetLogoPolicy { policy: policy.clone() };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("other", &[]), set(&policy))
+            .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let too_big = LogoPolicy { max_bytes: 10_000, ..policy.clone() };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), set(&too_big))
+            .unwrap_err();
+        assert_eq!(err, ContractError::LogoTooBig { max: 5 * 1024 });
+        execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), set(&policy)).unwrap();
+
+        // embedded logos must be of an allowed type, and small enough
+        let svg = Logo::Embedded(EmbeddedLogo::Svg(Binary::from(b"<svg></svg>".to_vec())));
+        let upload = ExecuteMsg::UploadLogo(svg);
+        let err = execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), upload).unwrap_err();
+        assert_eq!(err, ContractError::LogoNotAllowed {});
+        let png = Logo::Embedded(EmbeddedLogo::Png(Binary::from(vec![0u8; 11])));
+        let upload = ExecuteMsg::UploadLogo(png);
+        let err = execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), upload).unwrap_err();
+        assert_eq!(err, ContractError::LogoTooBig { max: 10 });
+
+        // URLs are always allowed, and are all that is allowed in URL only mode
+        policy.url_only = true;
+        execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), set(&policy)).unwrap();
+        let png = Logo::Embedded(EmbeddedLogo::Png(Binary::from(vec![0u8; 4])));
+        let upload = ExecuteMsg::UploadLogo(png);
+        let err = execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), upload).unwrap_err();
+        assert_eq!(err, ContractError::LogoNotAllowed {});
+        let url = Logo::Url("https://example.com/logo.png".to_string());
+        execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), ExecuteMsg::UploadLogo(url))
+            .unwrap();
+    }
+
+    /// sessions test - transfers signed by a session key are allowed up to the session's cap
+    #[test]
+    fn sessions_test() {
+        use k256::ecdsa::{signature::DigestSigner, Signature, SigningKey};
+        use sha2::{Digest, Sha256};
+
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut(), 1000);
+
+        let key = SigningKey::from_bytes(&[7u8; 32]).unwrap();
+        let pubkey = Binary::from(key.verifying_key().to_bytes().to_vec());
+        let register = ExecuteMsg::RegisterSession {
+            pubkey,
+            max_amount : Uint128::new(100),
+            expires    : Expiration::AtHeight(mock_env().block.height + 100),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), register).unwrap();
+
+        // signs the transfer of the given nonce with the session key, over the documented payload
+        let sign = |key: &SigningKey, nonce: u64, amount: u128| -> Binary {
+            let contract = mock_env().contract.address;
+            let payload = format!("{}/sender/1/{}/rcpt/{}", contract, nonce, amount);
+            let owner = cosmwasm_std::Addr::unchecked("sender");
+            assert_eq!(
+                Sha256::digest(&payload).to_vec(),
+                session_digest(&contract, &owner, 1, nonce, "rcpt", Uint128::new(amount))
+            );
+            let signature: Signature = key.sign_digest(Sha256::new().chain_update(payload));
+            Binary::from(signature.as_ref().to_vec())
+        };
+        let transfer = |amount: u128, signature: Binary| ExecuteMsg::SessionTransfer {
+            owner      : "sender".to_string(),
+            session_id : 1,
+            recipient  : "rcpt".to_string(),
+            amount     : Uint128::new(amount),
+            signature,
+        };
+
+        // a relayer submits a transfer signed by the session key
+        let msg = transfer(30, sign(&key, 0, 30));
+        execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), msg.clone()).unwrap();
+        let res = query_balance(deps.as_ref(), "rcpt".to_string()).unwrap();
+        assert_eq!(res.balance, Uint128::new(30));
+
+        // it cannot be replayed, nor signed by another key
+        let err = execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidSignature {});
+        let other = SigningKey::from_bytes(&[8u8; 32]).unwrap();
+        let msg = transfer(40, sign(&other, 1, 40));
+        let err = execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidSignature {});
+
+        // nor go over the cap
+        let msg = transfer(80, sign(&key, 1, 80));
+        let err = execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::SessionCapExceeded { remaining: Uint128::new(70) });
+
+        // spending the cap revokes the session
+        let msg = transfer(70, sign(&key, 1, 70));
+        execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), msg).unwrap();
+        let owner = "sender".to_string();
+        let query_msg = QueryMsg::Sessions { owner, start_after: None, limit: None };
+        let res: SessionsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert!(res.sessions.is_empty());
+    }
+
+    /// transfer with memo test - the memo is emitted with the transfer, up to a size cap
+    #[test]
+    fn transfer_with_memo_test() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut(), 1000);
+
+        let transfer = |memo: String| ExecuteMsg::TransferWithMemo {
+            recipient : "exchange".to_string(),
+            amount    : Uint128::new(100),
+            memo,
+        };
+        let msg = transfer("x".repeat(257));
+        let err = execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::MemoTooLong { max: 256 });
+
+        let msg = transfer("user-4242".to_string());
+        let res = execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), msg).unwrap();
+        assert_eq!(res.attributes.last().unwrap(), ("memo", "user-4242"));
+        let res = query_balance(deps.as_ref(), "exchange".to_string()).unwrap();
+        assert_eq!(res.balance, Uint128::new(100));
+    }
+
+    /// invariants test - the checks pass on a consistent state, across pages, and flag a corrupted
+    /// one
+    #[cfg(any(debug_assertions, feature = "invariants"))]
+    #[test]
+    fn invariants_test() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut(), 1000);
+        for recipient in ["alice", "bob"] {
+            let msg = ExecuteMsg::Transfer {
+                recipient      : recipient.to_string(),
+                amount         : Uint128::new(100),
+                allow_contract : None,
+            };
+            execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), msg).unwrap();
+        }
+        let msg = ExecuteMsg::IncreaseAllowance {
+            spender : "bot".to_string(),
+            amount  : Uint128::new(50),
+            expires : None,
+            purpose : None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), msg).unwrap();
+
+        // walk the whole state, a page at a time
+        let check = |deps: Deps, limit: u32| {
+            let mut violations = vec![];
+            let mut cursor = None;
+            let mut pages = 0;
+            loop {
+                let msg = QueryMsg::CheckInvariants { cursor, limit: Some(limit) };
+                let res: InvariantsResponse =
+                    from_binary(&query(deps, mock_env(), msg).unwrap()).unwrap();
+                violations.extend(res.violations);
+                pages += 1;
+                match res.next {
+                    Some(next) => cursor = Some(next),
+                    None => return (violations, pages),
+                }
+            }
+        };
+        assert_eq!(check(deps.as_ref(), 30), (vec![], 1));
+        assert_eq!(check(deps.as_ref(), 1), (vec![], 6));
+
+        // corrupt the balances and the allowances
+        let alice = Addr::unchecked("alice");
+        cw20_base::state::BALANCES.save(deps.as_mut().storage, &alice, &Uint128::new(150))
+            .unwrap();
+        let (bot, sender) = (Addr::unchecked("bot"), Addr::unchecked("sender"));
+        ALLOWANCES_SPENDER.remove(deps.as_mut().storage, (&bot, &sender));
+        let (violations, _) = check(deps.as_ref(), 1);
+        assert_eq!(violations, vec![
+            "balances sum to 1050 but the total supply is 1000".to_string(),
+            "allowance of sender to bot is not mirrored by spender".to_string(),
+        ]);
+    }
+
+    /// mint to pool test - the minter mints into a configured pool, and the reply enforces the
+    /// minimum LP tokens received
+    #[test]
+    fn mint_to_pool_test() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            name                : "GOLD".to_string(),
+            symbol              : "GLD".to_string(),
+            decimals            : 10,
+            initial_balances    : vec![],
+            mint                : Some(MinterResponse { minter: "minter".to_string(), cap: None }),
+            marketing           : None,
+            jurisdiction        : None,
+            compliance_admin    : None,
+            default_daily_limit : None,
+            treasury            : None,
+            test_faucet         : false,
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("sender", &[]), msg).unwrap();
+
+        // the LP token reports the given balance of the minter
+        let set_lp_balance = |querier: &mut cosmwasm_std::testing::MockQuerier, amount: u128| {
+            querier.update_wasm(move |query| match query {
+                WasmQuery::Smart { contract_addr, .. } if contract_addr == "lp_token" => {
+                    let res = cw20::BalanceResponse { balance: Uint128::new(amount) };
+                    SystemResult::Ok(ContractResult::Ok(to_binary(&res).unwrap()))
+                }
+                _ => panic!("unexpected query"),
+            });
+        };
+        set_lp_balance(&mut deps.querier, 10);
+
+        // only configured pools can be minted into
+        let mint = ExecuteMsg::MintToPool {
+            pool_contract : "pool".to_string(),
+            amount        : Uint128::new(500),
+            min_lp_out    : Uint128::new(100),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("minter", &[]), mint.clone())
+            .unwrap_err();
+        assert_eq!(err, ContractError::PoolNotConfigured {});
+        let msg = ExecuteMsg::SetLiquidityPool {
+            pool_contract : "pool".to_string(),
+            lp_token      : Some("lp_token".to_string()),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("minter", &[]), msg.clone())
+            .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), msg).unwrap();
+
+        // only the minter mints into the pool, which is sent the deposit
+        let err = execute(deps.as_mut(), mock_env(), mock_info("other", &[]), mint.clone())
+            .unwrap_err();
+        assert_eq!(err, ContractError::Base(cw20_base::ContractError::Unauthorized {}));
+        let res = execute(deps.as_mut(), mock_env(), mock_info("minter", &[]), mint).unwrap();
+        assert_eq!(res.messages.len(), 1);
+        assert_eq!(res.messages[0].id, crate::pool::POOL_REPLY_ID);
+        let res = query_balance(deps.as_ref(), "pool".to_string()).unwrap();
+        assert_eq!(res.balance, Uint128::new(500));
+
+        // the reply fails if the pool minted too few LP tokens, reverting the mint
+        let reply_msg = Reply {
+            id     : crate::pool::POOL_REPLY_ID,
+            result : SubMsgResult::Ok(SubMsgResponse { events: vec![], data: None }),
+        };
+        set_lp_balance(&mut deps.querier, 60);
+        let err = reply(deps.as_mut(), mock_env(), reply_msg.clone()).unwrap_err();
+        assert_eq!(err, ContractError::PoolSlippage {
+            lp_out     : Uint128::new(50),
+            min_lp_out : Uint128::new(100),
+        });
+
+        // and succeeds otherwise
+        let msg = ExecuteMsg::MintToPool {
+            pool_contract : "pool".to_string(),
+            amount        : Uint128::new(500),
+            min_lp_out    : Uint128::new(100),
+        };
+        set_lp_balance(&mut deps.querier, 10);
+        execute(deps.as_mut(), mock_env(), mock_info("minter", &[]), msg).unwrap();
+        set_lp_balance(&mut deps.querier, 130);
+        let res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+        assert!(res.attributes.iter().any(|a| a.key == "lp_out" && a.value == "120"));
+    }
+
+    /// compliance instantiation test - reserved tickers are rejected, and the compliance settings
+    /// are applied
+    #[test]
+    fn instantiate_compliance_test() {
+        let mut deps = mock_dependencies();
+        let msg = |symbol: &str| InstantiateMsg {
+            name                : "GOLD".to_string(),
+            symbol              : symbol.to_string(),
+            decimals            : 10,
+            initial_balances    : vec![],
+            mint                : None,
+            marketing           : None,
+            jurisdiction        : Some("SG".to_string()),
+            compliance_admin    : Some("officer".to_string()),
+            default_daily_limit : Some(Uint128::new(500)),
+            treasury            : None,
+            test_faucet         : false,
+        };
+        let err = instantiate(deps.as_mut(), mock_env(), mock_info("sender", &[]), msg("usdc"))
+            .unwrap_err();
+        assert_eq!(err, ContractError::ReservedTicker { symbol: "usdc".to_string() });
+        instantiate(deps.as_mut(), mock_env(), mock_info("sender", &[]), msg("GLD")).unwrap();
+
+        let query_msg = QueryMsg::HasRole { role: Role::Compliance, address: "officer".to_string() };
+        let res: HasRoleResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert!(res.has_role);
+        let query_msg = QueryMsg::RemainingLimit { address: "anyone".to_string() };
+        let res: RemainingLimitResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(res.limit, Some(Uint128::new(500)));
+        let query_msg = QueryMsg::TokenInfoExtended {};
+        let res: TokenInfoExtendedResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(res.jurisdiction, Some("SG".to_string()));
+    }
+
+    /// treasury test - spends of the contract's own tokens and native coins need 2 of 3 approvers
+    #[test]
+    fn treasury_test() {
+        let mut deps = mock_dependencies();
+        let msg = |threshold: u32| InstantiateMsg {
+            name                : "GOLD".to_string(),
+            symbol              : "GLD".to_string(),
+            decimals            : 10,
+            initial_balances    : vec![
+                Cw20Coin {
+                    address  : MOCK_CONTRACT_ADDR.to_string(),
+                    amount   : Uint128::new(1000),
+                }
+            ],
+            mint                : None,
+            marketing           : None,
+            jurisdiction        : None,
+            compliance_admin    : None,
+            default_daily_limit : None,
+            treasury            : Some(TreasuryInit {
+                approvers : vec!["alice".to_string(), "bob".to_string(), "carol".to_string()],
+                threshold,
+            }),
+            test_faucet         : false,
+        };
+        let err = instantiate(deps.as_mut(), mock_env(), mock_info("sender", &[]), msg(4))
+            .unwrap_err();
+        assert_eq!(err, ContractError::InvalidTreasury {});
+        instantiate(deps.as_mut(), mock_env(), mock_info("sender", &[]), msg(2)).unwrap();
+
+        // only approvers propose, and the proposal waits for a second approval
+        let propose = ExecuteMsg::ProposeSpend {
+            to     : "grantee".to_string(),
+            amount : SpendAmount::Token(Uint128::new(300)),
+            memo   : "grant".to_string(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), propose.clone())
+            .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), propose).unwrap();
+        let query_msg = QueryMsg::PendingSpends { start_after: None, limit: None };
+        let res: SpendProposalsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg.clone()).unwrap()).unwrap();
+        assert_eq!(res.proposals.len(), 1);
+        assert_eq!(res.proposals[0].approvals, vec!["alice".to_string()]);
+        assert_eq!(res.proposals[0].threshold, 2);
+
+        // approvers approve once each, and the second approval makes the spend
+        let approve = ExecuteMsg::ApproveSpend { id: 1 };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), approve.clone())
+            .unwrap_err();
+        assert_eq!(err, ContractError::AlreadyApproved {});
+        let res = execute(deps.as_mut(), mock_env(), mock_info("bob", &[]), approve).unwrap();
+        assert!(res.attributes.iter().any(|a| a.key == "executed" && a.value == "true"));
+        let res = query_balance(deps.as_ref(), "grantee".to_string()).unwrap();
+        assert_eq!(res.balance, Uint128::new(300));
+        let res = query_balance(deps.as_ref(), MOCK_CONTRACT_ADDR.to_string()).unwrap();
+        assert_eq!(res.balance, Uint128::new(700));
+        let res: SpendProposalsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert!(res.proposals.is_empty());
+
+        // nothing beyond the contract's balance can be spent
+        let propose = ExecuteMsg::ProposeSpend {
+            to     : "grantee".to_string(),
+            amount : SpendAmount::Token(Uint128::new(800)),
+            memo   : "too much".to_string(),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), propose).unwrap();
+        let approve = ExecuteMsg::ApproveSpend { id: 2 };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("bob", &[]), approve).unwrap_err();
+        assert_eq!(err, ContractError::InsufficientTreasury { available: Uint128::new(700) });
+
+        // native coins are sent with a bank message, the converter reserves aside
+        deps.querier.update_balance(MOCK_CONTRACT_ADDR, coins(80, "uatom"));
+        RESERVES.save(deps.as_mut().storage, "uatom", &Uint128::new(20)).unwrap();
+        let propose = ExecuteMsg::ProposeSpend {
+            to     : "grantee".to_string(),
+            amount : SpendAmount::Native(coin(50, "uatom")),
+            memo   : "gas".to_string(),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("carol", &[]), propose).unwrap();
+        let approve = ExecuteMsg::ApproveSpend { id: 3 };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), approve).unwrap();
+        assert_eq!(res.messages.len(), 1);
+        assert_eq!(res.messages[0].msg, CosmosMsg::Bank(BankMsg::Send {
+            to_address : "grantee".to_string(),
+            amount     : coins(50, "uatom"),
+        }));
+        let propose = ExecuteMsg::ProposeSpend {
+            to     : "grantee".to_string(),
+            amount : SpendAmount::Native(coin(70, "uatom")),
+            memo   : "reserve".to_string(),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("carol", &[]), propose).unwrap();
+        let approve = ExecuteMsg::ApproveSpend { id: 4 };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), approve).unwrap_err();
+        assert_eq!(err, ContractError::InsufficientTreasury { available: Uint128::new(60) });
+    }
+
+    /// burn sink test - transfers to a burn sink burn the tokens, reducing the total supply
+    #[test]
+    fn burn_sink_test() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut(), 1000);
+
+        // only the admin sets burn sinks
+        let set_sink = ExecuteMsg::SetBurnSink { address: "dead".to_string(), sink: true };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("other", &[]), set_sink.clone())
+            .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), set_sink).unwrap();
+        let query_msg = QueryMsg::BurnSinks { start_after: None, limit: None };
+        let res: BurnSinksResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(res.sinks, vec!["dead".to_string()]);
+
+        // a transfer to the sink is a burn
+        let transfer = |amount: u128| ExecuteMsg::Transfer {
+            recipient      : "dead".to_string(),
+            amount         : Uint128::new(amount),
+            allow_contract : None,
+        };
+        let balance_of_sink = |deps: Deps| query_balance(deps, "dead".to_string()).unwrap().balance;
+        let res = execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), transfer(100)).unwrap();
+        assert!(res.attributes.iter().any(|a| a.key == "action" && a.value == "burn"));
+        assert!(res.attributes.iter().any(|a| a.key == "burn_sink" && a.value == "dead"));
+        assert_eq!(balance_of_sink(deps.as_ref()), Uint128::zero());
+        assert_eq!(query_token_info(deps.as_ref()).unwrap().total_supply, Uint128::new(900));
+
+        // and so is a transfer from, which spends the allowance
+        let allow = ExecuteMsg::IncreaseAllowance {
+            spender : "spender".to_string(),
+            amount  : Uint128::new(50),
+            expires : None,
+            purpose : None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), allow).unwrap();
+        let transfer_from = ExecuteMsg::TransferFrom {
+            owner     : "sender".to_string(),
+            recipient : "dead".to_string(),
+            amount    : Uint128::new(50),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("spender", &[]), transfer_from).unwrap();
+        assert_eq!(query_token_info(deps.as_ref()).unwrap().total_supply, Uint128::new(850));
+        let res: TokenInfoExtendedResponse = from_binary(
+            &query(deps.as_ref(), mock_env(), QueryMsg::TokenInfoExtended {}).unwrap()
+        ).unwrap();
+        assert_eq!(res.total_burned, Uint128::new(150));
+
+        // no longer once the address is not a sink
+        let unset_sink = ExecuteMsg::SetBurnSink { address: "dead".to_string(), sink: false };
+        execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), unset_sink).unwrap();
+        execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), transfer(10)).unwrap();
+        assert_eq!(balance_of_sink(deps.as_ref()), Uint128::new(10));
+    }
+
+    /// scheduled transfer test - escrowed until due, then delivered by anyone, or cancelled before
+    #[test]
+    fn scheduled_transfer_test() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut(), 1000);
+        let env = mock_env();
+        let schedule = |amount: u128| ExecuteMsg::ScheduleTransfer {
+            recipient     : "payee".to_string(),
+            amount        : Uint128::new(amount),
+            execute_after : Expiration::AtTime(env.block.time.plus_seconds(100)),
+        };
+
+        // the transfer must be due later
+        let past = ExecuteMsg::ScheduleTransfer {
+            recipient     : "payee".to_string(),
+            amount        : Uint128::new(100),
+            execute_after : Expiration::AtHeight(env.block.height),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), past).unwrap_err();
+        assert_eq!(err, ContractError::InvalidSchedule {});
+
+        // the tokens are escrowed by the contract
+        execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), schedule(300)).unwrap();
+        execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), schedule(200)).unwrap();
+        let balance = |deps: Deps, address: &str| {
+            query_balance(deps, address.to_string()).unwrap().balance.u128()
+        };
+        assert_eq!(balance(deps.as_ref(), "sender"), 500);
+        assert_eq!(balance(deps.as_ref(), MOCK_CONTRACT_ADDR), 500);
+        let query_msg = QueryMsg::ScheduledTransfers {
+            sender      : "sender".to_string(),
+            start_after : None,
+            limit       : None,
+        };
+        let res: ScheduledTransfersResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(res.transfers.len(), 2);
+        assert!(!res.transfers[0].due);
+
+        // not delivered before it is due, and only cancelled by the sender
+        let run = ExecuteMsg::ExecuteScheduled { id: 1 };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), run.clone())
+            .unwrap_err();
+        assert_eq!(err, ContractError::NotDue {});
+        let cancel = ExecuteMsg::CancelScheduled { id: 2 };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), cancel.clone())
+            .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), cancel).unwrap();
+        assert_eq!(balance(deps.as_ref(), "sender"), 700);
+
+        // anyone delivers it once due, when it can no longer be cancelled
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(100);
+        let cancel = ExecuteMsg::CancelScheduled { id: 1 };
+        let err = execute(deps.as_mut(), env.clone(), mock_info("sender", &[]), cancel).unwrap_err();
+        assert_eq!(err, ContractError::AlreadyDue {});
+        execute(deps.as_mut(), env, mock_info("anyone", &[]), run).unwrap();
+        assert_eq!(balance(deps.as_ref(), "payee"), 300);
+        assert_eq!(balance(deps.as_ref(), MOCK_CONTRACT_ADDR), 0);
+    }
+
+
+    /// stream allowance test - transfer from draws on what accrued, up to the end of the stream
+    #[test]
+    fn stream_allowance_test() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut(), 1000);
+        let start = mock_env().block.time;
+        let at = |seconds: u64| {
+            let mut env = mock_env();
+            env.block.time = start.plus_seconds(seconds);
+            env
+        };
+        let balance = |deps: Deps, address: &str| {
+            query_balance(deps, address.to_string()).unwrap().balance.u128()
+        };
+        let transfer_from = |amount: u128| ExecuteMsg::TransferFrom {
+            owner     : "sender".to_string(),
+            recipient : "payee".to_string(),
+            amount    : Uint128::new(amount),
+        };
+
+        // the stream must be to another address, and accrue something before its end
+        let stream = |spender: &str, end: u64| ExecuteMsg::CreateStreamAllowance {
+            spender         : spender.to_string(),
+            rate_per_second : Uint128::new(2),
+            end             : start.plus_seconds(end),
+        };
+        let err = execute(deps.as_mut(), at(0), mock_info("sender", &[]), stream("sender", 100))
+            .unwrap_err();
+        assert_eq!(err, ContractError::InvalidStream {});
+        let err = execute(deps.as_mut(), at(0), mock_info("sender", &[]), stream("spender", 0))
+            .unwrap_err();
+        assert_eq!(err, ContractError::InvalidStream {});
+        execute(deps.as_mut(), at(0), mock_info("sender", &[]), stream("spender", 100)).unwrap();
+
+        // only what accrued can be transferred
+        let err = execute(deps.as_mut(), at(10), mock_info("spender", &[]), transfer_from(21))
+            .unwrap_err();
+        assert_eq!(err, ContractError::StreamExceeded { available: Uint128::new(20) });
+        execute(deps.as_mut(), at(10), mock_info("spender", &[]), transfer_from(15)).unwrap();
+        assert_eq!(balance(deps.as_ref(), "payee"), 15);
+        let query_msg = QueryMsg::StreamAllowances {
+            owner       : "sender".to_string(),
+            start_after : None,
+            limit       : None,
+        };
+        let res: StreamAllowancesResponse =
+            from_binary(&query(deps.as_ref(), at(30), query_msg.clone()).unwrap()).unwrap();
+        assert_eq!(res.streams.len(), 1);
+        assert_eq!(res.streams[0].spent, Uint128::new(15));
+        assert_eq!(res.streams[0].available, Uint128::new(45));
+
+        // nothing accrues past the end, and the stream is removed once fully spent
+        let err = execute(deps.as_mut(), at(500), mock_info("spender", &[]), transfer_from(186))
+            .unwrap_err();
+        assert_eq!(err, ContractError::StreamExceeded { available: Uint128::new(185) });
+        execute(deps.as_mut(), at(500), mock_info("spender", &[]), transfer_from(185)).unwrap();
+        assert_eq!(balance(deps.as_ref(), "payee"), 200);
+        assert_eq!(balance(deps.as_ref(), "sender"), 800);
+        let res: StreamAllowancesResponse =
+            from_binary(&query(deps.as_ref(), at(500), query_msg.clone()).unwrap()).unwrap();
+        assert!(res.streams.is_empty());
+
+        // the owner cancels a stream, after which the spender has no allowance left
+        execute(deps.as_mut(), at(0), mock_info("sender", &[]), stream("spender", 100)).unwrap();
+        let cancel = ExecuteMsg::CancelStreamAllowance { spender: "spender".to_string() };
+        execute(deps.as_mut(), at(10), mock_info("sender", &[]), cancel).unwrap();
+        let err = execute(deps.as_mut(), at(20), mock_info("spender", &[]), transfer_from(10))
+            .unwrap_err();
+        assert_eq!(err, ContractError::Base(cw20_base::ContractError::NoAllowance {}));
+
+        // with a regular allowance too, what the stream does not cover is drawn on the allowance
+        execute(deps.as_mut(), at(0), mock_info("sender", &[]), stream("spender", 100)).unwrap();
+        let allow = ExecuteMsg::IncreaseAllowance {
+            spender : "spender".to_string(),
+            amount  : Uint128::new(50),
+            expires : None,
+            purpose : None,
+        };
+        execute(deps.as_mut(), at(0), mock_info("sender", &[]), allow).unwrap();
+        execute(deps.as_mut(), at(10), mock_info("spender", &[]), transfer_from(20)).unwrap();
+        execute(deps.as_mut(), at(10), mock_info("spender", &[]), transfer_from(30)).unwrap();
+        assert_eq!(balance(deps.as_ref(), "payee"), 250);
+        let allowance = cw20_base::allowances::query_allowance(
+            deps.as_ref(), "sender".to_string(), "spender".to_string()
+        ).unwrap();
+        assert_eq!(allowance.allowance, Uint128::new(20));
+        let res: StreamAllowancesResponse =
+            from_binary(&query(deps.as_ref(), at(10), query_msg).unwrap()).unwrap();
+        assert_eq!(res.streams[0].spent, Uint128::new(20));
+    }
+
+
+    /// contract recipient test - with the check on, plain transfers to contracts need to be allowed
+    #[test]
+    fn contract_recipient_test() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut(), 1000);
+
+        // "vault" is the only contract
+        deps.querier.update_wasm(|query| match query {
+            WasmQuery::ContractInfo { contract_addr } if contract_addr == "vault" => {
+                let info = ContractInfoResponse::default();
+                SystemResult::Ok(ContractResult::Ok(to_binary(&info).unwrap()))
+            }
+            WasmQuery::ContractInfo { contract_addr } => {
+                SystemResult::Err(SystemError::NoSuchContract { addr: contract_addr.clone() })
+            }
+            _ => panic!("unexpected query"),
+        });
+        let transfer = |recipient: &str, allow_contract: Option<bool>| ExecuteMsg::Transfer {
+            recipient      : recipient.to_string(),
+            amount         : Uint128::new(100),
+            allow_contract,
+        };
+
+        // off by default, and only turned on by the admin
+        execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), transfer("vault", None))
+            .unwrap();
+        let check = ExecuteMsg::SetContractRecipientCheck { enabled: true };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("rcpt", &[]), check.clone())
+            .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), check).unwrap();
+
+        // transfers to contracts are rejected unless allowed, and others go through
+        let sender = mock_info("sender", &[]);
+        let err = execute(deps.as_mut(), mock_env(), sender, transfer("vault", None)).unwrap_err();
+        assert_eq!(err, ContractError::ContractRecipient { recipient: "vault".to_string() });
+        let allowed = transfer("vault", Some(true));
+        execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), allowed).unwrap();
+        execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), transfer("rcpt", None))
+            .unwrap();
+        let balance = |deps: Deps, address: &str| {
+            query_balance(deps, address.to_string()).unwrap().balance.u128()
+        };
+        assert_eq!(balance(deps.as_ref(), "vault"), 200);
+        assert_eq!(balance(deps.as_ref(), "rcpt"), 100);
+    }
+
+    /// flash mint test - the callback contract is minted the amount, and must burn it back along
+    /// with the fee before the reply
+    #[test]
+    fn flash_mint_test() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut(), 1000);
+        let flash_mint = ExecuteMsg::FlashMint {
+            amount            : Uint128::new(500),
+            callback_contract : "borrower".to_string(),
+            msg               : Binary::from(b"arbitrage"),
+        };
+
+        // off until the admin sets a fee
+        let err = execute(deps.as_mut(), mock_env(), mock_info("arb", &[]), flash_mint.clone())
+            .unwrap_err();
+        assert_eq!(err, ContractError::FlashMintDisabled {});
+        let set_fee = |fee_bps: u16| ExecuteMsg::SetFlashMintFee { fee_bps: Some(fee_bps) };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("rcpt", &[]), set_fee(100))
+            .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let err = execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), set_fee(10_001))
+            .unwrap_err();
+        assert_eq!(err, ContractError::InvalidFlashMintFee {});
+        execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), set_fee(100)).unwrap();
+        let res: FlashMintFeeResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::FlashMintFee {}).unwrap())
+                .unwrap();
+        assert_eq!(res.fee_bps, Some(100));
+
+        // the borrower holds enough for the fee
+        let transfer = ExecuteMsg::Transfer {
+            recipient      : "borrower".to_string(),
+            amount         : Uint128::new(10),
+            allow_contract : None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), transfer).unwrap();
+
+        // the amount is minted to the borrower, which is called back
+        let res = execute(deps.as_mut(), mock_env(), mock_info("arb", &[]), flash_mint.clone())
+            .unwrap();
+        let callback = cosmwasm_std::WasmMsg::Execute {
+            contract_addr : "borrower".to_string(),
+            msg           : to_binary(&FlashMintHookMsg::OnFlashMint {
+                initiator : "arb".to_string(),
+                amount    : Uint128::new(500),
+                fee       : Uint128::new(5),
+                msg       : Binary::from(b"arbitrage"),
+            }).unwrap(),
+            funds         : vec![],
+        };
+        assert_eq!(res.messages, vec![SubMsg::reply_on_success(callback, FLASH_MINT_REPLY_ID)]);
+        let balance = |deps: Deps| query_balance(deps, "borrower".to_string()).unwrap().balance;
+        assert_eq!(balance(deps.as_ref()), Uint128::new(510));
+        assert_eq!(query_token_info(deps.as_ref()).unwrap().total_supply, Uint128::new(1500));
+
+        // no flash mint within a flash mint
+        let err = execute(deps.as_mut(), mock_env(), mock_info("borrower", &[]), flash_mint.clone())
+            .unwrap_err();
+        assert_eq!(err, ContractError::FlashMintInProgress {});
+
+        // burning the amount and the fee repays it, only the fee counting as burnt
+        let burn = |amount: u128| ExecuteMsg::Burn { amount: Uint128::new(amount) };
+        execute(deps.as_mut(), mock_env(), mock_info("borrower", &[]), burn(505)).unwrap();
+        let reply_msg = Reply {
+            id     : FLASH_MINT_REPLY_ID,
+            result : SubMsgResult::Ok(SubMsgResponse { events: vec![], data: None }),
+        };
+        reply(deps.as_mut(), mock_env(), reply_msg.clone()).unwrap();
+        let res: TokenInfoExtendedResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::TokenInfoExtended {}).unwrap())
+                .unwrap();
+        assert_eq!(res.token_info.total_supply, Uint128::new(995));
+        assert_eq!(res.total_burned, Uint128::new(5));
+
+        // burning the amount alone does not
+        execute(deps.as_mut(), mock_env(), mock_info("arb", &[]), flash_mint).unwrap();
+        execute(deps.as_mut(), mock_env(), mock_info("borrower", &[]), burn(500)).unwrap();
+        let err = reply(deps.as_mut(), mock_env(), reply_msg).unwrap_err();
+        assert_eq!(err, ContractError::FlashMintNotRepaid { owed: Uint128::new(5) });
+    }
+
+
+    /// helpers test - the typed wrapper builds the token's messages, and parses its responses
+    #[test]
+    fn helpers_test() {
+        let token = EamesToken(Addr::unchecked("token"));
+        let msg = token.transfer("rcpt", Uint128::new(100)).unwrap();
+        let expected = ExecuteMsg::Transfer {
+            recipient      : "rcpt".to_string(),
+            amount         : Uint128::new(100),
+            allow_contract : None,
+        };
+        assert_eq!(msg, cosmwasm_std::WasmMsg::Execute {
+            contract_addr : "token".to_string(),
+            msg           : to_binary(&expected).unwrap(),
+            funds         : vec![],
+        });
+        let msg = token.buyback_and_burn(None, coins(10, "uatom")).unwrap();
+        assert_eq!(msg, cosmwasm_std::WasmMsg::Execute {
+            contract_addr : "token".to_string(),
+            msg           : to_binary(&ExecuteMsg::BuybackAndBurn { min_receive: None }).unwrap(),
+            funds         : coins(10, "uatom"),
+        });
+
+        // queries go to the token, with the responses parsed
+        let mut deps = mock_dependencies();
+        deps.querier.update_wasm(|query| match query {
+            WasmQuery::Smart { contract_addr, msg } if contract_addr == "token" => {
+                let res = match from_binary(msg).unwrap() {
+                    QueryMsg::Balance { .. } => {
+                        to_binary(&cw20::BalanceResponse { balance: Uint128::new(42) })
+                    }
+                    QueryMsg::Guardian {} => {
+                        to_binary(&GuardianResponse { guardian: None, paused: true })
+                    }
+                    _ => panic!("unexpected query"),
+                };
+                SystemResult::Ok(ContractResult::Ok(res.unwrap()))
+            }
+            _ => panic!("unexpected query"),
+        });
+        let querier = QuerierWrapper::new(&deps.querier);
+        assert_eq!(token.balance(&querier, "rcpt").unwrap(), Uint128::new(42));
+        assert!(token.is_paused(&querier).unwrap());
+    }
+
+
+    /// transfer policy test - zero-amount and self-transfers are rejected or no-ops, as the admin
+    /// sets it
+    #[test]
+    fn transfer_policy_test() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut(), 1000);
+        let transfer = |recipient: &str, amount: u128| ExecuteMsg::Transfer {
+            recipient      : recipient.to_string(),
+            amount         : Uint128::new(amount),
+            allow_contract : None,
+        };
+        let ping = ExecuteMsg::Send {
+            contract : "vault".to_string(),
+            amount   : Uint128::zero(),
+            msg      : Binary::from(b"ping"),
+        };
+
+        // by default as in cw20 base: zero-amount transfers rejected, self-transfers allowed
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::TransferPolicy {}).unwrap();
+        assert_eq!(from_binary::<TransferPolicy>(&res).unwrap(), TransferPolicy::default());
+        let err = execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), transfer("rcpt", 0))
+            .unwrap_err();
+        assert_eq!(err, ContractError::ZeroAmountTransfer {});
+        let sender = mock_info("sender", &[]);
+        let res = execute(deps.as_mut(), mock_env(), sender, transfer("sender", 10)).unwrap();
+        assert!(res.attributes.iter().any(|a| a.key == "noop" && a.value == "self"));
+
+        // only the admin sets the policy
+        let policy = ExecuteMsg::SetTransferPolicy {
+            policy: TransferPolicy { allow_zero_amount: true, allow_self: false },
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("rcpt", &[]), policy.clone())
+            .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), policy).unwrap();
+
+        // zero-amount pings emit their events, without calling the contract sent to
+        let res = execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), transfer("rcpt", 0))
+            .unwrap();
+        assert!(res.attributes.iter().any(|a| a.key == "to" && a.value == "rcpt"));
+        assert!(res.attributes.iter().any(|a| a.key == "noop" && a.value == "zero_amount"));
+        let res = execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), ping).unwrap();
+        assert!(res.messages.is_empty());
+        assert!(res.attributes.iter().any(|a| a.key == "action" && a.value == "send"));
+
+        // self-transfers are now rejected, and nothing moved
+        let sender = mock_info("sender", &[]);
+        let err = execute(deps.as_mut(), mock_env(), sender, transfer("sender", 10)).unwrap_err();
+        assert_eq!(err, ContractError::SelfTransfer {});
+        let balance = query_balance(deps.as_ref(), "sender".to_string()).unwrap().balance;
+        assert_eq!(balance, Uint128::new(1000));
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::TokenInfoExtended {}).unwrap();
+        assert_eq!(from_binary::<TokenInfoExtendedResponse>(&res).unwrap().holder_count, 1);
+    }
+
+
+    /// dependents test - the registered dependents are notified of a migration, and whether each
+    /// acknowledged it is tracked
+    #[test]
+    fn dependents_test() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut(), 1000);
+
+        // only the admin registers dependents
+        let register = |contract: &str| ExecuteMsg::SetDependent {
+            contract   : contract.to_string(),
+            registered : true,
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("rcpt", &[]), register("staking"))
+            .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        for contract in ["staking", "bridge"] {
+            let admin = mock_info("sender", &[]);
+            execute(deps.as_mut(), mock_env(), admin, register(contract)).unwrap();
+        }
+
+        // the migration notifies each of them, replying always
+        let msg = MigrateMsg { changes: vec!["transfers of zero are no-ops".to_string()] };
+        let res = migrate(deps.as_mut(), mock_env(), msg).unwrap();
+        let notification = to_binary(&DependentExecuteMsg::TokenMigrated {
+            version : env!("CARGO_PKG_VERSION").to_string(),
+            changes : vec!["transfers of zero are no-ops".to_string()],
+        }).unwrap();
+        let notify = |contract: &str| SubMsg::reply_always(WasmMsg::Execute {
+            contract_addr : contract.to_string(),
+            msg           : notification.clone(),
+            funds         : vec![],
+        }, NOTIFY_REPLY_ID);
+        assert_eq!(res.messages, vec![notify("bridge"), notify("staking")]);
+
+        // the bridge acknowledges, the staking contract fails without failing the migration
+        let acked = Reply {
+            id     : NOTIFY_REPLY_ID,
+            result : SubMsgResult::Ok(SubMsgResponse { events: vec![], data: None }),
+        };
+        reply(deps.as_mut(), mock_env(), acked).unwrap();
+        let failed = Reply {
+            id     : NOTIFY_REPLY_ID,
+            result : SubMsgResult::Err("unknown variant".to_string()),
+        };
+        reply(deps.as_mut(), mock_env(), failed).unwrap();
+
+        let msg = QueryMsg::Dependents { start_after: None, limit: None };
+        let res: DependentsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        let statuses: Vec<_> = res.dependents.into_iter().map(|d| (d.contract, d.status)).collect();
+        let failed = NotifyStatus::Failed { error: "unknown variant".to_string() };
+        assert_eq!(statuses, vec![
+            ("bridge".to_string(), Some(NotifyStatus::Acked)),
+            ("staking".to_string(), Some(failed)),
+        ]);
+    }
+
+
+    /// ledger test - once the retention is set, balance changes are recorded by block height and
+    /// pruned past the retention
+    #[test]
+    fn ledger_test() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut(), 1000);
+        let at = |height: u64| {
+            let mut env = mock_env();
+            env.block.height = height;
+            env
+        };
+        let transfer = |recipient: &str, amount: u128| ExecuteMsg::Transfer {
+            recipient      : recipient.to_string(),
+            amount         : Uint128::new(amount),
+            allow_contract : None,
+        };
+        let delta = |deps: Deps, from: u64, to: u64, start_after: Option<LedgerCursor>| {
+            let msg = QueryMsg::LedgerDelta {
+                from_height : from,
+                to_height   : to,
+                start_after,
+                limit       : Some(3),
+            };
+            from_binary::<LedgerDeltaResponse>(&query(deps, mock_env(), msg).unwrap())
+                .unwrap()
+                .records
+        };
+
+        // nothing is recorded by default
+        let sender = mock_info("sender", &[]);
+        execute(deps.as_mut(), at(90), sender, transfer("rcpt", 10)).unwrap();
+        assert!(delta(deps.as_ref(), 0, u64::MAX, None).is_empty());
+
+        // only the admin sets the retention, of at least a block
+        let retention = |blocks| ExecuteMsg::SetLedgerRetention { blocks };
+        let err = execute(deps.as_mut(), at(90), mock_info("rcpt", &[]), retention(Some(10)))
+            .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let err = execute(deps.as_mut(), at(90), mock_info("sender", &[]), retention(Some(0)))
+            .unwrap_err();
+        assert_eq!(err, ContractError::InvalidLedgerRetention {});
+        execute(deps.as_mut(), at(90), mock_info("sender", &[]), retention(Some(10))).unwrap();
+
+        // every balance changed is recorded, by order within the block
+        execute(deps.as_mut(), at(100), mock_info("sender", &[]), transfer("rcpt", 100)).unwrap();
+        execute(deps.as_mut(), at(100), mock_info("rcpt", &[]), transfer("sender", 40)).unwrap();
+        let burn = ExecuteMsg::Burn { amount: Uint128::new(50) };
+        execute(deps.as_mut(), at(105), mock_info("sender", &[]), burn).unwrap();
+
+        let records = delta(deps.as_ref(), 100, 100, None);
+        let changes: Vec<_> = records
+            .iter()
+            .map(|r| (r.index, r.address.as_str(), r.before.u128(), r.after.u128()))
+            .collect();
+        assert_eq!(changes, vec![
+            (0, "rcpt", 10, 110),
+            (1, "sender", 990, 890),
+            (2, "rcpt", 110, 70),
+        ]);
+        let cursor = LedgerCursor { height: 100, index: 2 };
+        let records = delta(deps.as_ref(), 100, 100, Some(cursor));
+        assert_eq!(records.len(), 1);
+        assert_eq!((records[0].address.as_str(), records[0].after.u128()), ("sender", 930));
+        let records = delta(deps.as_ref(), 101, 105, None);
+        assert_eq!(records.len(), 1);
+        assert_eq!((records[0].height, records[0].after.u128()), (105, 880));
+        assert!(delta(deps.as_ref(), 106, 100, None).is_empty());
+
+        // records past the retention are pruned as new ones are written
+        execute(deps.as_mut(), at(120), mock_info("sender", &[]), transfer("rcpt", 1)).unwrap();
+        let records = delta(deps.as_ref(), 0, u64::MAX, None);
+        assert_eq!(records.len(), 2);
+        assert!(records.iter().all(|r| r.height == 120));
+    }
+
+
+    /// validation test - addresses and amounts are validated as the contract does, with the
+    /// reason they are invalid
+    #[test]
+    fn validation_test() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut(), 1000);
+        let validate_address = |deps: Deps, address: &str| {
+            let msg = QueryMsg::ValidateAddress { address: address.to_string() };
+            from_binary::<ValidateAddressResponse>(&query(deps, mock_env(), msg).unwrap()).unwrap()
+        };
+        let validate_amount = |deps: Deps, amount: &str| {
+            let msg = QueryMsg::ValidateAmount { amount_str: amount.to_string() };
+            from_binary::<ValidateAmountResponse>(&query(deps, mock_env(), msg).unwrap()).unwrap()
+        };
+
+        // addresses as the api validates them, which rejects addresses not normalized
+        let res = validate_address(deps.as_ref(), "rcpt");
+        assert_eq!((res.valid, res.address), (true, Some("rcpt".to_string())));
+        let res = validate_address(deps.as_ref(), "Rcpt");
+        assert!(!res.valid && res.error.is_some());
+        let transfer = ExecuteMsg::Transfer {
+            recipient      : "Rcpt".to_string(),
+            amount         : Uint128::new(1),
+            allow_contract : None,
+        };
+        let sender = mock_info("sender", &[]);
+        let err = execute(deps.as_mut(), mock_env(), sender, transfer).unwrap_err();
+        assert_eq!(Some(err.to_string()), res.error);
+
+        // amounts in display units, with up to the token's 10 decimals
+        let amount = |amount: &str| validate_amount(deps.as_ref(), amount).amount;
+        assert_eq!(amount("12"), Some(Uint128::new(120_000_000_000)));
+        assert_eq!(amount("1.5"), Some(Uint128::new(15_000_000_000)));
+        assert_eq!(amount("0.0000000001"), Some(Uint128::new(1)));
+        for invalid in ["", ".5", "1.", "1.2.3", "-1", "1e3", " 1", "0.00000000001"] {
+            assert_eq!(amount(invalid), None, "{}", invalid);
+        }
+        let res = validate_amount(deps.as_ref(), &u128::MAX.to_string());
+        assert_eq!(res.error.unwrap(), format!("Invalid amount {}: too large", u128::MAX));
+    }
+
+
+    /// basket test - tokens are redeemed for their share of every asset of the basket, converter
+    /// reserves and unlisted assets excluded
+    #[test]
+    fn redeem_basket_test() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut(), 1000);
+        let transfer = ExecuteMsg::Transfer {
+            recipient      : "alice".to_string(),
+            amount         : Uint128::new(250),
+            allow_contract : None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), transfer).unwrap();
+
+        // the contract holds coins, part of them converter reserves, and Cw20 tokens
+        let held = vec![coin(1000, "uatom"), coin(40, "uosmo")];
+        deps.querier.update_balance(MOCK_CONTRACT_ADDR, held);
+        RESERVES.save(deps.as_mut().storage, "uatom", &Uint128::new(200)).unwrap();
+        deps.querier.update_wasm(|query| match query {
+            WasmQuery::Smart { contract_addr, .. } if contract_addr == "usdc" => {
+                let res = cw20::BalanceResponse { balance: Uint128::new(400) };
+                SystemResult::Ok(ContractResult::Ok(to_binary(&res).unwrap()))
+            }
+            _ => panic!("unexpected query"),
+        });
+
+        // only the admin lists assets
+        let list = |asset: BasketAsset| ExecuteMsg::SetBasketAsset { asset, listed: true };
+        let usdc = BasketAsset::Cw20("usdc".to_string());
+        let err = execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), list(usdc.clone()))
+            .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let atom = BasketAsset::Native("uatom".to_string());
+        for asset in [atom.clone(), usdc.clone()] {
+            execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), list(asset)).unwrap();
+        }
+
+        let res: RedemptionRateResponse = from_binary(
+            &query(deps.as_ref(), mock_env(), QueryMsg::RedemptionRate {}).unwrap()
+        ).unwrap();
+        assert_eq!(res.supply, Uint128::new(1000));
+        assert_eq!(res.assets, vec![
+            RedemptionAsset {
+                asset     : atom,
+                held      : Uint128::new(800),
+                per_token : Decimal::percent(80),
+            },
+            RedemptionAsset {
+                asset     : usdc,
+                held      : Uint128::new(400),
+                per_token : Decimal::percent(40),
+            },
+        ]);
+
+        // a quarter of the supply redeems a quarter of each
+        let redeem = |amount: u128| ExecuteMsg::Redeem {
+            denom  : None,
+            amount : Uint128::new(amount),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), redeem(0))
+            .unwrap_err();
+        assert_eq!(err, ContractError::NoFunds {});
+        let res = execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), redeem(250)).unwrap();
+        assert_eq!(res.messages.len(), 2);
+        assert_eq!(res.messages[0].msg, CosmosMsg::Bank(BankMsg::Send {
+            to_address : "alice".to_string(),
+            amount     : coins(200, "uatom"),
+        }));
+        let transfer = cw20::Cw20ExecuteMsg::Transfer {
+            recipient : "alice".to_string(),
+            amount    : Uint128::new(100),
+        };
+        assert_eq!(res.messages[1].msg, CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr : "usdc".to_string(),
+            msg           : to_binary(&transfer).unwrap(),
+            funds         : vec![],
+        }));
+        let balance = query_balance(deps.as_ref(), "alice".to_string()).unwrap().balance;
+        assert_eq!(balance, Uint128::zero());
+        let supply = query_token_info(deps.as_ref()).unwrap().total_supply;
+        assert_eq!(supply, Uint128::new(750));
+    }
+
+
+    /// permits test - an account's balance history is queried with a permit signed by its key,
+    /// until the permit expires or the key changes
+    #[test]
+    fn permits_test() {
+        use k256::ecdsa::{signature::DigestSigner, Signature, SigningKey};
+        use sha2::{Digest, Sha256};
+
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut(), 1000);
+        let retention = ExecuteMsg::SetLedgerRetention { blocks: Some(100) };
+        execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), retention).unwrap();
+        for (from, to) in [("sender", "rcpt"), ("sender", "other"), ("rcpt", "other")] {
+            let transfer = ExecuteMsg::Transfer {
+                recipient      : to.to_string(),
+                amount         : Uint128::new(10),
+                allow_contract : None,
+            };
+            execute(deps.as_mut(), mock_env(), mock_info(from, &[]), transfer).unwrap();
+        }
+
+        // signs the permit of "rcpt" with the key, over the documented ADR-36 sign doc
+        let sign = |key: &SigningKey, expires: Expiration| -> Permit {
+            let contract = mock_env().contract.address;
+            let data = format!("Query permit for {} until {}", contract, expires);
+            let sign_doc = format!(
+                concat!(
+                    r#"{{"account_number":"0","chain_id":"","fee":{{"amount":[],"gas":"0"}},"#,
+                    r#""memo":"","msgs":[{{"type":"sign/MsgSignData","value":{{"data":"{}","#,
+                    r#""signer":"rcpt"}}}}],"sequence":"0"}}"#
+                ),
+                Binary::from(data.as_bytes()).to_base64(),
+            );
+            assert_eq!(
+                Sha256::digest(&sign_doc).to_vec(),
+                permit_digest(&contract, "rcpt", &expires)
+            );
+            let signature: Signature = key.sign_digest(Sha256::new().chain_update(sign_doc));
+            Permit {
+                address   : "rcpt".to_string(),
+                expires,
+                signature : Binary::from(signature.as_ref().to_vec()),
+            }
+        };
+        let history = |deps: Deps, permit: Permit| {
+            let query_msg = QueryMsg::WithPermit {
+                permit,
+                query: PermitQueryMsg::BalanceHistory {
+                    from_height : 0,
+                    to_height   : u64::MAX,
+                    start_after : None,
+                    limit       : None,
+                },
+            };
+            query(deps, mock_env(), query_msg)
+                .map(|res| from_binary::<LedgerDeltaResponse>(&res).unwrap().records)
+        };
+        let key = SigningKey::from_bytes(&[7u8; 32]).unwrap();
+        let expires = Expiration::AtHeight(mock_env().block.height + 10);
+
+        // permits are only accepted once a valid key is registered
+        history(deps.as_ref(), sign(&key, expires)).unwrap_err();
+        let set_key = |pubkey: Option<Binary>| ExecuteMsg::SetPermitKey { pubkey };
+        let err = execute(
+            deps.as_mut(), mock_env(), mock_info("rcpt", &[]), set_key(Some(Binary::from(b"key")))
+        ).unwrap_err();
+        assert_eq!(err, ContractError::InvalidPermitKey {});
+        let pubkey = Binary::from(key.verifying_key().to_bytes().to_vec());
+        execute(deps.as_mut(), mock_env(), mock_info("rcpt", &[]), set_key(Some(pubkey))).unwrap();
+
+        // only the balance changes of the account are returned
+        let records = history(deps.as_ref(), sign(&key, expires)).unwrap();
+        let changes: Vec<_> = records
+            .iter()
+            .map(|r| (r.address.as_str(), r.before.u128(), r.after.u128()))
+            .collect();
+        assert_eq!(changes, vec![("rcpt", 0, 10), ("rcpt", 10, 0)]);
+
+        // nor expired permits, nor those signed by another key, are accepted
+        let expired = Expiration::AtHeight(mock_env().block.height);
+        history(deps.as_ref(), sign(&key, expired)).unwrap_err();
+        let other = SigningKey::from_bytes(&[8u8; 32]).unwrap();
+        history(deps.as_ref(), sign(&other, expires)).unwrap_err();
+
+        // and removing the key revokes the permits signed with it
+        execute(deps.as_mut(), mock_env(), mock_info("rcpt", &[]), set_key(None)).unwrap();
+        history(deps.as_ref(), sign(&key, expires)).unwrap_err();
+    }
+
+
+    /// receipts test - locks mint receipts to their owner, burnt as they are released
+    #[test]
+    fn receipts_test() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut(), 1000);
+        let receipt = |deps: Deps, address: &str| -> ReceiptResponse {
+            let query_msg = QueryMsg::Receipt { address: address.to_string() };
+            from_binary(&query(deps, mock_env(), query_msg).unwrap()).unwrap()
+        };
+        let lock = |amount: u128| ExecuteMsg::Lock {
+            amount   : Uint128::new(amount),
+            unlocker : "lender".to_string(),
+            until    : Expiration::Never {},
+        };
+
+        // every lock mints as many receipts to its owner
+        execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), lock(600)).unwrap();
+        execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), lock(100)).unwrap();
+        let res = receipt(deps.as_ref(), "sender");
+        assert_eq!(res.symbol, "stGLD");
+        assert_eq!(res.balance, Uint128::new(700));
+        assert_eq!(res.total_supply, Uint128::new(700));
+        assert_eq!(receipt(deps.as_ref(), "lender").balance, Uint128::zero());
+
+        // receipts are not tokens, the token balance is unchanged
+        let balance = query_balance(deps.as_ref(), "sender".to_string()).unwrap().balance;
+        assert_eq!(balance, Uint128::new(1000));
+
+        // releasing a lock burns its receipts
+        let unlock = ExecuteMsg::Unlock { lock_id: 1 };
+        execute(deps.as_mut(), mock_env(), mock_info("lender", &[]), unlock).unwrap();
+        let res = receipt(deps.as_ref(), "sender");
+        assert_eq!(res.balance, Uint128::new(100));
+        assert_eq!(res.total_supply, Uint128::new(100));
+    }
+
+
+    /// convert test - converting burns the tokens and has the sibling mint them at its rate, the
+    /// tokens being restored should the mint fail
+    #[test]
+    fn convert_test() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut(), 1000);
+        let convert = |min_out: u128| ExecuteMsg::Convert {
+            to_token : "gold_v2".to_string(),
+            amount   : Uint128::new(100),
+            min_out  : Uint128::new(min_out),
+        };
+
+        // only paired siblings, set by a holder of the rates role, are converted into
+        let err = execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), convert(0))
+            .unwrap_err();
+        assert_eq!(err, ContractError::UnknownSibling { token: "gold_v2".to_string() });
+        let set_sibling = ExecuteMsg::SetSibling {
+            token : "gold_v2".to_string(),
+            rate  : Some(Decimal::percent(250)),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("other", &[]), set_sibling.clone())
+            .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), set_sibling).unwrap();
+        let query_msg = QueryMsg::Siblings { start_after: None, limit: None };
+        let res: SiblingsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(res.siblings.len(), 1);
+        assert_eq!(res.siblings[0].rate, Decimal::percent(250));
+
+        // the conversion must mint at least the minimum
+        let err = execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), convert(251))
+            .unwrap_err();
+        assert_eq!(err, ContractError::ConversionSlippage {
+            out     : Uint128::new(250),
+            min_out : Uint128::new(251),
+        });
+
+        // the tokens are burnt, and the sibling mints the converted amount
+        let res = execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), convert(250))
+            .unwrap();
+        let mint = cw20::Cw20ExecuteMsg::Mint {
+            recipient : "sender".to_string(),
+            amount    : Uint128::new(250),
+        };
+        assert_eq!(res.messages, vec![SubMsg::reply_always(WasmMsg::Execute {
+            contract_addr : "gold_v2".to_string(),
+            msg           : to_binary(&mint).unwrap(),
+            funds         : vec![],
+        }, CONVERT_REPLY_ID)]);
+        let supply = |deps: Deps| query_token_info(deps).unwrap().total_supply;
+        assert_eq!(supply(deps.as_ref()), Uint128::new(900));
+        let minted = Reply {
+            id     : CONVERT_REPLY_ID,
+            result : SubMsgResult::Ok(SubMsgResponse { events: vec![], data: None }),
+        };
+        reply(deps.as_mut(), mock_env(), minted).unwrap();
+        assert_eq!(supply(deps.as_ref()), Uint128::new(900));
+
+        // should the sibling fail to mint, the burnt tokens are restored
+        execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), convert(0)).unwrap();
+        let failed = Reply {
+            id     : CONVERT_REPLY_ID,
+            result : SubMsgResult::Err("unauthorized".to_string()),
+        };
+        let res = reply(deps.as_mut(), mock_env(), failed).unwrap();
+        assert!(res.attributes.iter().any(|a| a.value == "convert_rolled_back"));
+        assert_eq!(supply(deps.as_ref()), Uint128::new(900));
+        let balance = query_balance(deps.as_ref(), "sender".to_string()).unwrap().balance;
+        assert_eq!(balance, Uint128::new(900));
+    }
+
+
+    /// corridors test - with the corridor mode on, transfers only go along whitelisted corridors,
+    /// between addresses or groups
+    #[test]
+    fn corridors_test() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut(), 1000);
+        let transfer = |recipient: &str| ExecuteMsg::Transfer {
+            recipient      : recipient.to_string(),
+            amount         : Uint128::new(10),
+            allow_contract : None,
+        };
+        let compliance = |deps: DepsMut, msg: ExecuteMsg| {
+            execute(deps, mock_env(), mock_info("sender", &[]), msg).unwrap();
+        };
+
+        // tokens move freely until the mode is on, which only compliance sets
+        execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), transfer("alice")).unwrap();
+        let mode = ExecuteMsg::SetCorridorMode { enabled: true };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), mode.clone())
+            .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        compliance(deps.as_mut(), mode);
+        let err = execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), transfer("bob"))
+            .unwrap_err();
+        assert_eq!(err, ContractError::NoCorridor {
+            from : "sender".to_string(),
+            to   : "bob".to_string(),
+        });
+
+        // a corridor between two addresses goes both ways
+        let corridor = Corridor::Addresses("bob".to_string(), "sender".to_string());
+        compliance(deps.as_mut(), ExecuteMsg::SetCorridor { corridor, allowed: true });
+        execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), transfer("bob")).unwrap();
+        execute(deps.as_mut(), mock_env(), mock_info("bob", &[]), transfer("sender")).unwrap();
+
+        // a corridor of a group with itself allows transfers among its members
+        for address in ["alice", "carol"] {
+            compliance(deps.as_mut(), ExecuteMsg::SetGroupMember {
+                group   : "investors".to_string(),
+                address : address.to_string(),
+                member  : true,
+            });
+        }
+        let corridor = |from: &str, to: &str| -> CorridorResponse {
+            let query_msg = QueryMsg::Corridor { from: from.to_string(), to: to.to_string() };
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap()
+        };
+        assert_eq!(corridor("alice", "carol"), CorridorResponse { enabled: true, allowed: false });
+        let investors = Corridor::Groups("investors".to_string(), "investors".to_string());
+        compliance(deps.as_mut(), ExecuteMsg::SetCorridor { corridor: investors, allowed: true });
+        execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), transfer("carol")).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), transfer("bob"))
+            .unwrap_err();
+        assert!(matches!(err, ContractError::NoCorridor { .. }));
+
+        // the groups of an address, and the members of a group
+        let query_msg = QueryMsg::Groups { address: "alice".to_string() };
+        let res: GroupsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(res.groups, vec!["investors"]);
+        let query_msg = QueryMsg::GroupMembers {
+            group       : "investors".to_string(),
+            start_after : None,
+            limit       : None,
+        };
+        let res: GroupMembersResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(res.members, vec!["alice", "carol"]);
+
+        // turning the mode off lifts the restrictions
+        compliance(deps.as_mut(), ExecuteMsg::SetCorridorMode { enabled: false });
+        execute(deps.as_mut(), mock_env(), mock_info("carol", &[]), transfer("bob")).unwrap();
+    }
+
+
+    /// labels test - accounts label themselves, shown alongside their balance
+    #[test]
+    fn labels_test() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut(), 1000);
+        let set_label = |label: &str| ExecuteMsg::SetLabel { label: label.to_string() };
+
+        // labels are size limited
+        let info = mock_info("sender", &[]);
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), set_label(&"x".repeat(65)))
+            .unwrap_err();
+        assert_eq!(err, ContractError::LabelTooLong { max: 64 });
+        execute(deps.as_mut(), mock_env(), info.clone(), set_label("treasury")).unwrap();
+
+        let balance = |deps: Deps, address: &str| -> BalanceExtendedResponse {
+            let query_msg = QueryMsg::BalanceExtended { address: address.to_string() };
+            from_binary(&query(deps, mock_env(), query_msg).unwrap()).unwrap()
+        };
+        assert_eq!(balance(deps.as_ref(), "sender"), BalanceExtendedResponse {
+            balance : Uint128::new(1000),
+            label   : Some("treasury".to_string()),
+        });
+        assert_eq!(balance(deps.as_ref(), "other").label, None);
+
+        // listed with every account holding tokens
+        let query_msg = QueryMsg::AllAccountsExtended { start_after: None, limit: None };
+        let res: AccountsExtendedResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(res.accounts.len(), 1);
+        assert_eq!(res.accounts[0].label, Some("treasury".to_string()));
+
+        // an empty label removes it
+        execute(deps.as_mut(), mock_env(), info, set_label("")).unwrap();
+        assert_eq!(balance(deps.as_ref(), "sender").label, None);
+    }
+
+
+    /// display test - amounts round-trip between raw units and decimal strings, never rounded
+    #[test]
+    fn display_test() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut(), 1000);
+
+        let format = |deps: Deps, amount: u128| -> String {
+            let query_msg = QueryMsg::FormatAmount { amount: Uint128::new(amount) };
+            let res: FormatAmountResponse =
+                from_binary(&query(deps, mock_env(), query_msg).unwrap()).unwrap();
+            res.display
+        };
+        let parse = |deps: Deps, display: &str| -> StdResult<Uint128> {
+            let query_msg = QueryMsg::ParseAmount { display: display.to_string() };
+            let res: ParseAmountResponse = from_binary(&query(deps, mock_env(), query_msg)?)?;
+            Ok(res.amount)
+        };
+
+        // the token has 10 decimals, trailing zeros are trimmed
+        assert_eq!(format(deps.as_ref(), 0), "0");
+        assert_eq!(format(deps.as_ref(), 15_000_000_000), "1.5");
+        assert_eq!(format(deps.as_ref(), 20_000_000_000), "2");
+        assert_eq!(format(deps.as_ref(), 1), "0.0000000001");
+        for amount in [0, 1, 15_000_000_000, 123_456_789_012_345, u128::MAX] {
+            let display = format(deps.as_ref(), amount);
+            assert_eq!(parse(deps.as_ref(), &display).unwrap(), Uint128::new(amount));
+        }
+
+        // malformed, too precise, or overflowing strings are rejected rather than rounded
+        assert_eq!(parse(deps.as_ref(), "1.25").unwrap(), Uint128::new(12_500_000_000));
+        for display in ["", ".5", "1.", "1.2.3", "-1", "+1", "1e3", "0.00000000001"] {
+            assert!(parse(deps.as_ref(), display).is_err(), "{}", display);
+        }
+        assert!(parse(deps.as_ref(), &format!("{}0", u128::MAX)).is_err());
+    }
+
+
+    /// purposes test - owners state what their allowances are for, dropped along with them
+    #[test]
+    fn purposes_test() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut(), 1000);
+        let info = mock_info("sender", &[]);
+        let allowance = |deps: Deps, spender: &str| -> AllowanceExtendedResponse {
+            let query_msg = QueryMsg::AllowanceExtended {
+                owner   : "sender".to_string(),
+                spender : spender.to_string(),
+            };
+            from_binary(&query(deps, mock_env(), query_msg).unwrap()).unwrap()
+        };
+
+        // purposes are size limited
+        let increase = |spender: &str, amount: u128, purpose: Option<&str>| {
+            ExecuteMsg::IncreaseAllowance {
+                spender : spender.to_string(),
+                amount  : Uint128::new(amount),
+                expires : None,
+                purpose : purpose.map(String::from),
+            }
+        };
+        let long = "x".repeat(129);
+        let msg = increase("dex", 100, Some(&long));
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+        assert_eq!(err, ContractError::PurposeTooLong { max: 128 });
+
+        // stated when granted, and kept when increased without one
+        execute(deps.as_mut(), mock_env(), info.clone(), increase("dex", 100, Some("DEX trading")))
+            .unwrap();
+        execute(deps.as_mut(), mock_env(), info.clone(), increase("dex", 50, None)).unwrap();
+        execute(deps.as_mut(), mock_env(), info.clone(), increase("payroll", 10, None)).unwrap();
+        assert_eq!(allowance(deps.as_ref(), "dex"), AllowanceExtendedResponse {
+            allowance : Uint128::new(150),
+            expires   : Expiration::Never {},
+            purpose   : Some("DEX trading".to_string()),
+        });
+
+        // listed with every allowance of the owner
+        let query_msg = QueryMsg::AllAllowancesExtended {
+            owner       : "sender".to_string(),
+            start_after : None,
+            limit       : None,
+        };
+        let res: AllowancesExtendedResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        let purposes: Vec<_> = res.allowances.iter().map(|a| a.purpose.clone()).collect();
+        assert_eq!(purposes, vec![Some("DEX trading".to_string()), None]);
+
+        // dropped along with the allowance once fully decreased
+        let decrease = ExecuteMsg::DecreaseAllowance {
+            spender : "dex".to_string(),
+            amount  : Uint128::new(150),
+            expires : None,
+            purpose : None,
+        };
+        execute(deps.as_mut(), mock_env(), info, decrease).unwrap();
+        assert_eq!(allowance(deps.as_ref(), "dex").purpose, None);
+    }
+
+
+    /// faucet test - anyone mints to themselves up to the daily cap, in test deployments only
+    #[test]
+    fn faucet_test() {
+        // off unless turned on at instantiation
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut(), 1000);
+        let mint = |amount: u128| ExecuteMsg::FaucetMint { amount: Uint128::new(amount) };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), mint(1)).unwrap_err();
+        assert_eq!(err, ContractError::FaucetDisabled {});
+
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            name                : "GOLD".to_string(),
+            symbol              : "GLD".to_string(),
+            decimals            : 2,
+            initial_balances    : vec![],
+            mint                : None,
+            marketing           : None,
+            jurisdiction        : None,
+            compliance_admin    : None,
+            default_daily_limit : None,
+            treasury            : None,
+            test_faucet         : true,
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("sender", &[]), msg).unwrap();
+        let res: SupportedInterfacesResponse = from_binary(
+            &query(deps.as_ref(), mock_env(), QueryMsg::SupportedInterfaces {}).unwrap()
+        ).unwrap();
+        assert!(res.interfaces.iter().any(|interface| interface.name == "eames-token:faucet"));
+
+        // 1000 whole tokens a day, of 2 decimals
+        let info = mock_info("alice", &[]);
+        execute(deps.as_mut(), mock_env(), info.clone(), mint(60_000)).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), mint(40_001)).unwrap_err();
+        assert_eq!(err, ContractError::FaucetCapExceeded { remaining: Uint128::new(40_000) });
+        execute(deps.as_mut(), mock_env(), info.clone(), mint(40_000)).unwrap();
+        let balance = query_balance(deps.as_ref(), "alice".to_string()).unwrap().balance;
+        assert_eq!(balance, Uint128::new(100_000));
+        assert_eq!(query_token_info(deps.as_ref()).unwrap().total_supply.u128(), 100_000);
+
+        // each address has its own cap, renewed the next day
+        execute(deps.as_mut(), mock_env(), mock_info("bob", &[]), mint(100_000)).unwrap();
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(86_400);
+        execute(deps.as_mut(), env, info, mint(100_000)).unwrap();
+    }
+
+
+    /// sends test - a send whose receive hook fails is reverted rather than failing the transaction
+    #[test]
+    fn sends_test() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut(), 1000);
+        let send = |amount: u128| ExecuteMsg::Send {
+            contract : "vault".to_string(),
+            amount   : Uint128::new(amount),
+            msg      : Binary::from(b"deposit"),
+        };
+        let balance = |deps: Deps, address: &str| {
+            query_balance(deps, address.to_string()).unwrap().balance.u128()
+        };
+        let hook = |result: SubMsgResult| Reply { id: SEND_REPLY_ID, result };
+
+        // the receive hook replies always
+        let res = execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), send(100)).unwrap();
+        assert_eq!(res.messages.len(), 1);
+        assert_eq!(res.messages[0].id, SEND_REPLY_ID);
+        assert_eq!(res.messages[0].reply_on, ReplyOn::Always);
+        assert_eq!((balance(deps.as_ref(), "sender"), balance(deps.as_ref(), "vault")), (900, 100));
+
+        // accepted, the tokens stay with the contract
+        let ok = SubMsgResult::Ok(SubMsgResponse { events: vec![], data: None });
+        reply(deps.as_mut(), mock_env(), hook(ok)).unwrap();
+        assert_eq!(balance(deps.as_ref(), "vault"), 100);
+
+        // rejected, they are moved back and the failure is reported
+        execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), send(300)).unwrap();
+        let res = reply(deps.as_mut(), mock_env(), hook(SubMsgResult::Err("paused".to_string())))
+            .unwrap();
+        assert_eq!((balance(deps.as_ref(), "sender"), balance(deps.as_ref(), "vault")), (900, 100));
+        let event = res.events.iter().find(|event| event.ty == "send_failed").unwrap();
+        assert!(event.attributes.iter().any(|attr| attr.key == "error" && attr.value == "paused"));
+
+        // nothing is left pending
+        let late = hook(SubMsgResult::Err("late".to_string()));
+        assert!(reply(deps.as_mut(), mock_env(), late).is_err());
+    }
+
+
+    /// renounce test - the admin schedules the removal of every privilege, which goes through on
+    /// its own once due unless cancelled before
+    #[test]
+    fn renounce_test() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            name                : "GOLD".to_string(),
+            symbol              : "GLD".to_string(),
+            decimals            : 10,
+            initial_balances    : vec![
+                Cw20Coin {
+                    address  : String::from("sender"),
+                    amount   : Uint128::new(1000),
+                }
+            ],
+            mint                : Some(MinterResponse { minter: "sender".into(), cap: None }),
+            marketing           : Some(cw20_base::msg::InstantiateMarketingInfo {
+                project     : None,
+                description : None,
+                marketing   : Some("sender".to_string()),
+                logo        : None,
+            }),
+            jurisdiction        : None,
+            compliance_admin    : Some("officer".to_string()),
+            default_daily_limit : None,
+            treasury            : None,
+            test_faucet         : false,
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("sender", &[]), msg).unwrap();
+        let admin = mock_info("sender", &[]);
+        let status = |deps: Deps, env: Env| -> AdminStatusResponse {
+            from_binary(&query(deps, env, QueryMsg::AdminStatus {}).unwrap()).unwrap()
+        };
+        let renounce = ExecuteMsg::RenounceAdmin { delay: 3600 };
+
+        // admin only, scheduled once
+        let err = execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), renounce.clone())
+            .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let overflow = ExecuteMsg::RenounceAdmin { delay: u64::MAX };
+        let err = execute(deps.as_mut(), mock_env(), admin.clone(), overflow).unwrap_err();
+        assert_eq!(err, ContractError::InvalidRenounceDelay {});
+        execute(deps.as_mut(), mock_env(), admin.clone(), renounce.clone()).unwrap();
+        let renounce_at = mock_env().block.time.plus_seconds(3600);
+        let err = execute(deps.as_mut(), mock_env(), admin.clone(), renounce.clone()).unwrap_err();
+        assert_eq!(err, ContractError::RenounceScheduled { renounce_at });
+        assert_eq!(status(deps.as_ref(), mock_env()), AdminStatusResponse {
+            admin       : Some("sender".to_string()),
+            renounce_at : Some(renounce_at),
+            renounced   : false,
+        });
+
+        // cancelled before it is due
+        execute(deps.as_mut(), mock_env(), admin.clone(), ExecuteMsg::CancelRenounce {}).unwrap();
+        assert_eq!(status(deps.as_ref(), mock_env()).renounce_at, None);
+        let err = execute(deps.as_mut(), mock_env(), admin.clone(), ExecuteMsg::CancelRenounce {})
+            .unwrap_err();
+        assert_eq!(err, ContractError::NoRenounceScheduled {});
+
+        // scheduled again, with the token paused and an action pending
+        execute(deps.as_mut(), mock_env(), admin.clone(), renounce).unwrap();
+        execute(deps.as_mut(), mock_env(), admin.clone(), ExecuteMsg::Pause {}).unwrap();
+        let action = AdminAction::UpdateCap { cap: Some(Uint128::new(5000)) };
+        let propose = ExecuteMsg::ProposeAction { action };
+        execute(deps.as_mut(), mock_env(), admin.clone(), propose).unwrap();
+
+        // due, though not yet gone through
+        let mut env = mock_env();
+        env.block.time = renounce_at;
+        let renounced = AdminStatusResponse {
+            admin       : None,
+            renounce_at : Some(renounce_at),
+            renounced   : true,
+        };
+        assert_eq!(status(deps.as_ref(), env.clone()), renounced);
+
+        // and the queries report the privileges renounced already, with no message in between
+        let query_at = |env: Env, msg: QueryMsg| query(deps.as_ref(), env, msg).unwrap();
+        let minter: Option<MinterResponse> =
+            from_binary(&query_at(mock_env(), QueryMsg::Minter {})).unwrap();
+        assert_eq!(minter.unwrap().minter, "sender");
+        let minter: Option<MinterResponse> =
+            from_binary(&query_at(env.clone(), QueryMsg::Minter {})).unwrap();
+        assert_eq!(minter, None);
+        let marketing: cw20::MarketingInfoResponse =
+            from_binary(&query_at(env.clone(), QueryMsg::MarketingInfo {})).unwrap();
+        assert_eq!(marketing.marketing, None);
+        let has_role = QueryMsg::HasRole { role: Role::Compliance, address: "officer".to_string() };
+        let res: HasRoleResponse = from_binary(&query_at(mock_env(), has_role.clone())).unwrap();
+        assert!(res.has_role);
+        let res: HasRoleResponse = from_binary(&query_at(env.clone(), has_role)).unwrap();
+        assert!(!res.has_role);
+        let guardian: GuardianResponse =
+            from_binary(&query_at(env.clone(), QueryMsg::Guardian {})).unwrap();
+        assert_eq!(guardian, GuardianResponse { guardian: None, paused: false });
+        let pending = QueryMsg::PendingActions { start_after: None, limit: None };
+        let res: PendingActionsResponse =
+            from_binary(&query_at(mock_env(), pending.clone())).unwrap();
+        assert_eq!(res.actions.len(), 1);
+        let res: PendingActionsResponse = from_binary(&query_at(env.clone(), pending)).unwrap();
+        assert!(res.actions.is_empty());
+        let extended: TokenInfoExtendedResponse =
+            from_binary(&query_at(env.clone(), QueryMsg::TokenInfoExtended {})).unwrap();
+        assert_eq!((extended.minter, extended.paused), (None, false));
+
+        // goes through with the next message, which moves tokens again
+        let transfer = ExecuteMsg::Transfer {
+            recipient      : "alice".to_string(),
+            amount         : Uint128::new(10),
+            allow_contract : None,
+        };
+        let res = execute(deps.as_mut(), env.clone(), admin.clone(), transfer).unwrap();
+        assert_eq!(res.events[0].ty, "admin_renounced");
+        assert_eq!(status(deps.as_ref(), env.clone()), renounced);
+        for (role, address) in [(Role::Minter, "sender"), (Role::Compliance, "officer")] {
+            let query_msg = QueryMsg::HasRole { role, address: address.to_string() };
+            let res: HasRoleResponse =
+                from_binary(&query(deps.as_ref(), env.clone(), query_msg).unwrap()).unwrap();
+            assert!(!res.has_role);
+        }
+        assert_eq!(query_token_info(deps.as_ref()).unwrap().total_supply, Uint128::new(1000));
+        let query_msg = QueryMsg::PendingActions { start_after: None, limit: None };
+        let res: PendingActionsResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), query_msg).unwrap()).unwrap();
+        assert!(res.actions.is_empty());
+
+        // nothing privileged can be done anymore
+        let cancel = ExecuteMsg::CancelRenounce {};
+        let err = execute(deps.as_mut(), env.clone(), admin.clone(), cancel).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let mint = ExecuteMsg::Mint { recipient: "sender".to_string(), amount: Uint128::new(1) };
+        execute(deps.as_mut(), env.clone(), admin.clone(), mint).unwrap_err();
+        let grant = ExecuteMsg::GrantRole { role: Role::Minter, address: "sender".to_string() };
+        let err = execute(deps.as_mut(), env.clone(), admin.clone(), grant).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let err = execute(deps.as_mut(), env, admin, ExecuteMsg::Pause {}).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+    }
+
+
+    /// middleware test - the cross-cutting stages run around any handler, the checks before it
+    /// rejecting messages it never sees
+    #[test]
+    fn middleware_test() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut(), 1000);
+        let sender = mock_info("sender", &[]);
+        let msg = ExecuteMsg::SetDefaultDailyLimit { limit: Some(Uint128::new(100)) };
+        execute(deps.as_mut(), mock_env(), sender.clone(), msg).unwrap();
+        let transfer = |recipient: &str, amount: u128| ExecuteMsg::Transfer {
+            recipient      : recipient.to_string(),
+            amount         : Uint128::new(amount),
+            allow_contract : None,
+        };
+        let unreachable: Handler = |_, _, _, _| unreachable!("rejected before the handler");
+        let accept: Handler = |_, _, _, _| Ok(Response::new());
+
+        // the limits stage checks the transfer, whichever handler there is
+        let err = run_pipeline(
+            deps.as_mut(), mock_env(), sender.clone(), transfer("alice", 101), unreachable
+        ).unwrap_err();
+        let remaining = Uint128::new(100);
+        assert_eq!(err, ContractError::DailyLimitExceeded { limit: remaining, remaining });
+        run_pipeline(deps.as_mut(), mock_env(), sender.clone(), transfer("alice", 60), accept)
+            .unwrap();
+
+        // self-transfers are left to the transfer policy, without spending the limit
+        execute(deps.as_mut(), mock_env(), sender.clone(), transfer("sender", 90)).unwrap();
+        execute(deps.as_mut(), mock_env(), sender.clone(), transfer("alice", 40)).unwrap();
+
+        // the pause stage stops anything moving
+        execute(deps.as_mut(), mock_env(), sender.clone(), ExecuteMsg::Pause {}).unwrap();
+        let err = run_pipeline(
+            deps.as_mut(), mock_env(), sender, transfer("alice", 1), unreachable
+        ).unwrap_err();
+        assert_eq!(err, ContractError::Paused {});
+    }
+
+
+    /// baseline migration test - a token instantiated by the baseline, with no admin stored, gets
+    /// the contract admin as its admin holding every role
+    #[test]
+    fn baseline_migrate_test() {
+        let mut deps = mock_dependencies();
+        let msg = cw20_base::msg::InstantiateMsg {
+            name             : "GOLD".to_string(),
+            symbol           : "GLD".to_string(),
+            decimals         : 6,
+            initial_balances : vec![Cw20Coin {
+                address : "sender".to_string(),
+                amount  : Uint128::new(1000),
+            }],
+            mint             : Some(MinterResponse { minter: "minter".to_string(), cap: None }),
+            marketing        : None,
+        };
+        let info = mock_info("sender", &[]);
+        cw20_base::contract::instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        deps.querier.update_wasm(|query| match query {
+            WasmQuery::ContractInfo { .. } => {
+                let mut info = ContractInfoResponse::default();
+                info.admin = Some("wasm_admin".to_string());
+                SystemResult::Ok(ContractResult::Ok(to_binary(&info).unwrap()))
+            }
+            _ => panic!("unexpected query"),
+        });
+
+        migrate(deps.as_mut(), mock_env(), MigrateMsg { changes: vec![] }).unwrap();
+        let res: AdminStatusResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::AdminStatus {}).unwrap())
+                .unwrap();
+        assert_eq!(res.admin, Some("wasm_admin".to_string()));
+        for role in [Role::Minter, Role::Pauser, Role::Compliance, Role::Metadata, Role::Rates] {
+            let query_msg = QueryMsg::HasRole { role, address: "wasm_admin".to_string() };
+            let res: HasRoleResponse =
+                from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+            assert!(res.has_role);
+        }
+
+        // the migrated token changes its minter through the timelock
+        let admin = mock_info("wasm_admin", &[]);
+        let action = AdminAction::UpdateMinter { new_minter: Some("minter2".to_string()) };
+        let propose = ExecuteMsg::ProposeAction { action };
+        execute(deps.as_mut(), mock_env(), admin.clone(), propose).unwrap();
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(TIMELOCK_DELAY);
+        let run = ExecuteMsg::ExecuteAction { action_id: 1 };
+        execute(deps.as_mut(), env, admin, run).unwrap();
+        let minter = cw20_base::contract::query_minter(deps.as_ref()).unwrap().unwrap();
+        assert_eq!(minter.minter, "minter2");
+    }
+
+
+    /// supply history test - a checkpoint per day the supply changed, as of its last change
+    #[test]
+    fn supply_history_test() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            name                : "GOLD".to_string(),
+            symbol              : "GLD".to_string(),
+            decimals            : 10,
+            initial_balances    : vec![Cw20Coin {
+                address : "sender".to_string(),
+                amount  : Uint128::new(1000),
+            }],
+            mint                : Some(MinterResponse { minter: "sender".to_string(), cap: None }),
+            marketing           : None,
+            jurisdiction        : None,
+            compliance_admin    : None,
+            default_daily_limit : None,
+            treasury            : None,
+            test_faucet         : false,
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("sender", &[]), msg).unwrap();
+        let day = mock_env().block.time.seconds() / 86_400;
+        let on_day = |offset: u64| {
+            let mut env = mock_env();
+            env.block.time = Timestamp::from_seconds((day + offset) * 86_400 + 3_600);
+            env
+        };
+        let mint = |amount: u128| ExecuteMsg::Mint {
+            recipient : "rcpt".to_string(),
+            amount    : Uint128::new(amount),
+        };
+
+        // changes on the same day override its checkpoint, and messages not changing the supply
+        // leave the history as is
+        execute(deps.as_mut(), on_day(0), mock_info("sender", &[]), mint(100)).unwrap();
+        execute(deps.as_mut(), on_day(1), mock_info("sender", &[]), mint(50)).unwrap();
+        let burn = ExecuteMsg::Burn { amount: Uint128::new(30) };
+        execute(deps.as_mut(), on_day(1), mock_info("sender", &[]), burn).unwrap();
+        let transfer = ExecuteMsg::Transfer {
+            recipient      : "rcpt".to_string(),
+            amount         : Uint128::new(10),
+            allow_contract : None,
+        };
+        execute(deps.as_mut(), on_day(2), mock_info("sender", &[]), transfer).unwrap();
+        execute(deps.as_mut(), on_day(4), mock_info("sender", &[]), mint(80)).unwrap();
+
+        let history = |start_after: Option<u64>, limit: Option<u32>| {
+            let msg = QueryMsg::SupplyHistory { start_after, limit };
+            let res: SupplyHistoryResponse =
+                from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+            res.checkpoints
+                .into_iter()
+                .map(|checkpoint| (checkpoint.day - day, checkpoint.total_supply.u128()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(history(None, None), vec![(0, 1100), (1, 1120), (4, 1200)]);
+        assert_eq!(history(Some(day), Some(1)), vec![(1, 1120)]);
+    }
+
+    /// raw balance range test - every balance is exported in key order, a page at a time
+    #[cfg(any(debug_assertions, feature = "snapshot"))]
+    #[test]
+    fn raw_balance_range_test() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut(), 1000);
+        for (recipient, amount) in [("carol", 300), ("alice", 100), ("bob", 200)] {
+            let msg = ExecuteMsg::Transfer {
+                recipient      : recipient.to_string(),
+                amount         : Uint128::new(amount),
+                allow_contract : None,
+            };
+            execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), msg).unwrap();
+        }
+
+        let mut balances = vec![];
+        let mut cursor = None;
+        let mut pages = 0;
+        loop {
+            let msg = QueryMsg::RawBalanceRange { cursor, limit: Some(2) };
+            let res: RawBalanceRangeResponse =
+                from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+            balances.extend(res.balances);
+            pages += 1;
+            match res.next {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+        let expected = [("alice", 100), ("bob", 200), ("carol", 300), ("sender", 400)]
+            .map(|(addr, amount)| (addr.to_string(), Uint128::new(amount)));
+        assert_eq!(balances, expected);
+        assert_eq!(pages, 3);
+    }
+
+
+    /// supported interfaces test - the declaration follows the deployment's modules, and is
+    /// refreshed from scratch
+    #[test]
+    fn supported_interfaces_test() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut(), 1000);
+        let interfaces = |deps: Deps| {
+            let msg = QueryMsg::SupportedInterfaces {};
+            let res: SupportedInterfacesResponse =
+                from_binary(&query(deps, mock_env(), msg).unwrap()).unwrap();
+            res.interfaces.into_iter().map(|i| (i.name, i.version)).collect::<Vec<_>>()
+        };
+        let declared = interfaces(deps.as_ref());
+        assert!(declared.contains(&("cw20".to_string(), "1.1.0".to_string())));
+        assert!(declared.iter().any(|(name, _)| name == "eames-token:pausable"));
+        assert!(!declared.iter().any(|(name, _)| name == "eames-token:treasury"));
+
+        // a treasury is declared, and interfaces no longer supported are dropped
+        let treasury = Treasury { approvers: vec![Addr::unchecked("alice")], threshold: 1 };
+        TREASURY.save(deps.as_mut().storage, &treasury).unwrap();
+        SUPPORTED_INTERFACES.save(deps.as_mut().storage, "eames-token:old", &"0.1.0".to_string())
+            .unwrap();
+        declare_interfaces(deps.as_mut().storage).unwrap();
+        let declared = interfaces(deps.as_ref());
+        assert!(declared.iter().any(|(name, _)| name == "eames-token:treasury"));
+        assert!(!declared.iter().any(|(name, _)| name == "eames-token:old"));
+    }
+
+
+    /// converter test - deposits of several denoms mint at their own rates, and tokens are
+    /// redeemed out of the reserve of a denom
+    #[test]
+    fn converter_test() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut(), 1000);
+
+        // only holders of the rates role set rates
+        let set_rate = |denom: &str, percent: u64| ExecuteMsg::SetDepositRate {
+            denom : denom.to_string(),
+            rate  : Some(Decimal::percent(percent)),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), set_rate("uatom", 200))
+            .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let err = execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), set_rate("uatom", 0))
+            .unwrap_err();
+        assert_eq!(err, ContractError::InvalidRate {});
+        for (denom, percent) in [("uatom", 200), ("uosmo", 50)] {
+            execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), set_rate(denom, percent))
+                .unwrap();
+        }
+
+        // every denom deposited must be accepted
+        let info = mock_info("alice", &[coin(100, "uatom"), coin(100, "ujuno")]);
+        let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Deposit {}).unwrap_err();
+        assert_eq!(err, ContractError::UnsupportedDenom { denom: "ujuno".to_string() });
+        let info = mock_info("alice", &[coin(100, "uatom"), coin(100, "uosmo")]);
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Deposit {}).unwrap();
+        let balance = query_balance(deps.as_ref(), "alice".to_string()).unwrap().balance;
+        assert_eq!(balance, Uint128::new(250));
+        let supply = query_token_info(deps.as_ref()).unwrap().total_supply;
+        assert_eq!(supply, Uint128::new(1250));
+
+        // redeemed out of the reserve of the denom
+        let redeem = |amount: u128| ExecuteMsg::Redeem {
+            denom  : Some("uatom".to_string()),
+            amount : Uint128::new(amount),
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), redeem(100)).unwrap();
+        assert_eq!(res.messages.len(), 1);
+        assert_eq!(res.messages[0].msg, CosmosMsg::Bank(BankMsg::Send {
+            to_address : "alice".to_string(),
+            amount     : coins(50, "uatom"),
+        }));
+        let err = execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), redeem(150))
+            .unwrap_err();
+        assert_eq!(err, ContractError::InsufficientReserve { available: Uint128::new(50) });
+
+        let res: ReservesResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Reserves {}).unwrap()).unwrap();
+        assert_eq!(res.reserves, vec![
+            ReserveResponse {
+                denom  : "uatom".to_string(),
+                amount : Uint128::new(50),
+                rate   : Some(Decimal::percent(200)),
+            },
+            ReserveResponse {
+                denom  : "uosmo".to_string(),
+                amount : Uint128::new(100),
+                rate   : Some(Decimal::percent(50)),
+            },
+        ]);
+    }
+
+
+    /// Token operations generated by the fuzz suite, between the fuzzed accounts
+    #[derive(Debug, Clone)]
+    enum FuzzOp {
+        Transfer { from: usize, to: usize, amount: u128 },
+        Mint { to: usize, amount: u128 },
+        Burn { from: usize, amount: u128 },
+        IncreaseAllowance { owner: usize, spender: usize, amount: u128 },
+        TransferFrom { spender: usize, owner: usize, to: usize, amount: u128 },
+    }
+
+    /// Accounts of the fuzz suite, "sender" being the admin and minter
+    const FUZZ_ACCOUNTS: [&str; 4] = ["alice", "bob", "carol", "sender"];
+
+    /// Cap of the token minted by the fuzz suite
+    const FUZZ_CAP: u128 = 20_000;
+
+    fn fuzz_op() -> impl Strategy<Value = FuzzOp> {
+        let account = 0..FUZZ_ACCOUNTS.len();
+        let amount = 1..1500u128;
+        prop_oneof![
+            (account.clone(), account.clone(), amount.clone())
+                .prop_map(|(from, to, amount)| FuzzOp::Transfer { from, to, amount }),
+            (account.clone(), amount.clone()).prop_map(|(to, amount)| FuzzOp::Mint { to, amount }),
+            (account.clone(), amount.clone())
+                .prop_map(|(from, amount)| FuzzOp::Burn { from, amount }),
+            (account.clone(), account.clone(), amount.clone()).prop_map(|(owner, spender, amount)| {
+                FuzzOp::IncreaseAllowance { owner, spender, amount }
+            }),
+            (account.clone(), account.clone(), account, amount).prop_map(
+                |(spender, owner, to, amount)| FuzzOp::TransferFrom { spender, owner, to, amount }
+            ),
+        ]
+    }
+
+    /// The token as the fuzz suite expects it to be
+    #[derive(Default)]
+    struct FuzzModel {
+        balances   : [u128; 4],
+        allowances : std::collections::HashMap<(usize, usize), u128>,
+    }
+
+    impl FuzzModel {
+        /// Apply an operation, returning whether the token must accept it
+        fn apply(&mut self, op: &FuzzOp) -> bool {
+            let supply: u128 = self.balances.iter().sum();
+            match *op {
+                FuzzOp::Transfer { from, to, amount } => {
+                    if self.balances[from] < amount {
+                        return false;
+                    }
+                    self.balances[from] -= amount;
+                    self.balances[to] += amount;
+                }
+                FuzzOp::Mint { to, amount } => {
+                    if supply + amount > FUZZ_CAP {
+                        return false;
+                    }
+                    self.balances[to] += amount;
+                }
+                FuzzOp::Burn { from, amount } => {
+                    if self.balances[from] < amount {
+                        return false;
+                    }
+                    self.balances[from] -= amount;
+                }
+                FuzzOp::IncreaseAllowance { owner, spender, amount } => {
+                    if owner == spender {
+                        return false;
+                    }
+                    *self.allowances.entry((owner, spender)).or_default() += amount;
+                }
+                FuzzOp::TransferFrom { spender, owner, to, amount } => {
+                    let allowance = self.allowances.get(&(owner, spender)).copied();
+                    if allowance.unwrap_or_default() < amount || self.balances[owner] < amount {
+                        return false;
+                    }
+                    self.allowances.insert((owner, spender), allowance.unwrap() - amount);
+                    self.balances[owner] -= amount;
+                    self.balances[to] += amount;
+                }
+            }
+            true
+        }
+    }
+
+    /// The execute message and sender of an operation
+    fn fuzz_msg(op: &FuzzOp) -> (&'static str, ExecuteMsg) {
+        let account = |i: usize| FUZZ_ACCOUNTS[i].to_string();
+        match *op {
+            FuzzOp::Transfer { from, to, amount } => (FUZZ_ACCOUNTS[from], ExecuteMsg::Transfer {
+                recipient      : account(to),
+                amount         : Uint128::new(amount),
+                allow_contract : None,
+            }),
+            FuzzOp::Mint { to, amount } => ("sender", ExecuteMsg::Mint {
+                recipient : account(to),
+                amount    : Uint128::new(amount),
+            }),
+            FuzzOp::Burn { from, amount } => (FUZZ_ACCOUNTS[from], ExecuteMsg::Burn {
+                amount : Uint128::new(amount),
+            }),
+            FuzzOp::IncreaseAllowance { owner, spender, amount } => {
+                (FUZZ_ACCOUNTS[owner], ExecuteMsg::IncreaseAllowance {
+                    spender : account(spender),
+                    amount  : Uint128::new(amount),
+                    expires : None,
+                    purpose : None,
+                })
+            }
+            FuzzOp::TransferFrom { spender, owner, to, amount } => {
+                (FUZZ_ACCOUNTS[spender], ExecuteMsg::TransferFrom {
+                    owner     : account(owner),
+                    recipient : account(to),
+                    amount    : Uint128::new(amount),
+                })
+            }
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        /// balance invariants fuzz test - after any sequence of transfers, mints, burns and
+        /// allowance operations, the token matches the model: balances, allowances and supply,
+        /// and its own invariants hold. A failed operation is rolled back, as it would be on chain.
+        #[test]
+        fn balance_invariants_fuzz_test(ops in prop::collection::vec(fuzz_op(), 1..40)) {
+            let mut deps = mock_dependencies();
+            let msg = InstantiateMsg {
+                name                : "GOLD".to_string(),
+                symbol              : "GLD".to_string(),
+                decimals            : 10,
+                initial_balances    : vec![
+                    Cw20Coin {
+                        address  : String::from("sender"),
+                        amount   : Uint128::new(5000),
+                    }
+                ],
+                mint                : Some(MinterResponse {
+                    minter : "sender".to_string(),
+                    cap    : Some(Uint128::new(FUZZ_CAP)),
+                }),
+                marketing           : None,
+                jurisdiction        : None,
+                compliance_admin    : None,
+                default_daily_limit : None,
+                treasury            : None,
+                test_faucet         : false,
+            };
+            instantiate(deps.as_mut(), mock_env(), mock_info("sender", &[]), msg).unwrap();
+            let mut model = FuzzModel::default();
+            model.balances[3] = 5000;
+
+            for op in ops {
+                let snapshot: Vec<_> = deps.storage.range(None, None, Order::Ascending).collect();
+                let (sender, msg) = fuzz_msg(&op);
+                let res = execute(deps.as_mut(), mock_env(), mock_info(sender, &[]), msg);
+                let accepted = model.apply(&op);
+                prop_assert_eq!(res.is_ok(), accepted, "{:?}: {:?}", op, res);
+                if res.is_err() {
+                    deps.storage = MockStorage::new();
+                    for (key, value) in snapshot {
+                        deps.storage.set(&key, &value);
+                    }
+                }
+
+                // the balances, allowances and supply are the expected ones
+                for (i, account) in FUZZ_ACCOUNTS.iter().enumerate() {
+                    let balance = query_balance(deps.as_ref(), account.to_string()).unwrap();
+                    prop_assert_eq!(balance.balance.u128(), model.balances[i]);
+                }
+                for ((owner, spender), allowance) in &model.allowances {
+                    let owner = Addr::unchecked(FUZZ_ACCOUNTS[*owner]);
+                    let spender = Addr::unchecked(FUZZ_ACCOUNTS[*spender]);
+                    let stored = ALLOWANCES_SPENDER.load(&deps.storage, (&spender, &owner)).unwrap();
+                    prop_assert_eq!(stored.allowance.u128(), *allowance);
+                }
+                let supply = query_token_info(deps.as_ref()).unwrap().total_supply;
+                prop_assert_eq!(supply.u128(), model.balances.iter().sum::<u128>());
+
+                // and so do the token's own invariants, holder count included
+                #[cfg(any(debug_assertions, feature = "invariants"))]
+                {
+                    let msg = QueryMsg::CheckInvariants { cursor: None, limit: Some(100) };
+                    let res: InvariantsResponse =
+                        from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+                    prop_assert_eq!(res.violations, Vec::<String>::new());
+                    prop_assert!(res.next.is_none());
+                }
+            }
+        }
+    }
 }
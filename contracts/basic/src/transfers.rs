@@ -0,0 +1,120 @@
+/*
+Transfers module - a configurable policy on zero-amount transfers and self-transfers, made by the
+owner of the tokens. Cw20 base rejects the former and moves the tokens back and forth for the
+latter; with the policy, the admin decides whether each is rejected, or accepted as a no-op that
+emits the transfer's events without touching any balance. Some integrators rely on zero-amount
+"ping" transfers to verify an address.
+*/
+
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{
+    Addr, Deps, DepsMut, MessageInfo, Response, StdError, StdResult, Uint128
+};
+use cw_storage_plus::Item;
+use cw20_base::state::BALANCES;
+
+use crate::error::ContractError;
+use crate::state::assert_admin;
+
+/// Policy on transfers that move nothing
+#[cw_serde]
+pub struct TransferPolicy {
+    /// Whether zero-amount transfers are accepted as no-ops, rather than rejected
+    pub allow_zero_amount : bool,
+    /// Whether transfers to the sender itself are accepted as no-ops, rather than rejected
+    pub allow_self        : bool,
+}
+
+impl Default for TransferPolicy {
+    fn default() -> Self {
+        // as in Cw20 base
+        TransferPolicy {
+            allow_zero_amount : false,
+            allow_self        : true,
+        }
+    }
+}
+
+/// The transfer policy, the default one if unset
+pub const TRANSFER_POLICY: Item<TransferPolicy> = Item::new("transfer_policy");
+
+
+/// Set the transfer policy - admin only.
+/// # Arguments
+/// * `deps`   - mutable dependency which has the storage (state) of the chain
+/// * `info`   - message info, the sender must be the admin
+/// * `policy` - the new policy
+/// # Returns
+/// * the execute response on Ok
+/// * the error type on Err
+pub fn execute_set_transfer_policy(
+    deps   : DepsMut,
+    info   : MessageInfo,
+    policy : TransferPolicy,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.storage, &info.sender)?;
+    TRANSFER_POLICY.save(deps.storage, &policy)?;
+    Ok(Response::new()
+        .add_attribute("action", "set_transfer_policy")
+        .add_attribute("allow_zero_amount", policy.allow_zero_amount.to_string())
+        .add_attribute("allow_self", policy.allow_self.to_string()))
+}
+
+
+/// Apply the transfer policy to a transfer by the owner of the tokens.
+/// # Arguments
+/// * `deps`      - dependency which has the storage (state) of the chain
+/// * `action`    - the action of the transfer, for its events
+/// * `sender`    - the owner of the tokens transferred
+/// * `recipient` - the recipient of the transfer
+/// * `amount`    - the amount to transfer
+/// # Returns
+/// * the no-op response if the transfer moves nothing and is allowed, None if it moves tokens
+/// * the error type on Err, if the policy rejects it
+pub fn transfer_noop(
+    deps      : Deps,
+    action    : &str,
+    sender    : &Addr,
+    recipient : &str,
+    amount    : Uint128,
+) -> Result<Option<Response>, ContractError> {
+    let noop = if amount.is_zero() {
+        "zero_amount"
+    } else if sender.as_str() == recipient {
+        "self"
+    } else {
+        return Ok(None);
+    };
+
+    let policy = query_transfer_policy(deps)?;
+    if amount.is_zero() && !policy.allow_zero_amount {
+        return Err(ContractError::ZeroAmountTransfer {});
+    }
+    if sender.as_str() == recipient && !policy.allow_self {
+        return Err(ContractError::SelfTransfer {});
+    }
+    // a self-transfer still fails on a balance short of the amount, as it would in Cw20 base
+    BALANCES
+        .may_load(deps.storage, sender)?
+        .unwrap_or_default()
+        .checked_sub(amount)
+        .map_err(StdError::from)?;
+    // the recipient of a zero-amount ping must still be a valid address
+    let recipient = deps.api.addr_validate(recipient)?;
+    Ok(Some(Response::new()
+        .add_attribute("action", action)
+        .add_attribute("from", sender)
+        .add_attribute("to", recipient)
+        .add_attribute("amount", amount)
+        .add_attribute("noop", noop)))
+}
+
+
+/// Query the transfer policy.
+/// # Arguments
+/// * `deps` - dependency which has the storage (state) of the chain
+/// # Returns
+///   The transfer policy
+pub fn query_transfer_policy(deps: Deps) -> StdResult<TransferPolicy> {
+    Ok(TRANSFER_POLICY.may_load(deps.storage)?.unwrap_or_default())
+}
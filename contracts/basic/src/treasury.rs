@@ -0,0 +1,268 @@
+/*
+Treasury module - the token's own funds, and the native coins held by the contract, spent by N-of-M
+approvers set at instantiation rather than through an external multisig. An approver proposes a
+spend, which counts as their approval, and the spend is made as soon as enough approvers approved.
+*/
+
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{
+    Addr, BankMsg, Coin, Deps, DepsMut, Env, MessageInfo, Order, Response, StdResult, Storage,
+    Uint128
+};
+use cw_storage_plus::{Bound, Item, Map};
+use cw20_base::contract::execute_transfer;
+use cw20_base::state::BALANCES;
+
+use crate::contract::MAX_MEMO_LEN;
+use crate::converter::RESERVES;
+use crate::error::ContractError;
+use crate::msg::{SpendProposalResponse, SpendProposalsResponse};
+use crate::state::ESCROWED;
+
+// Settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+
+/// The treasury approvers, set at instantiation
+#[cw_serde]
+pub struct TreasuryInit {
+    /// Addresses able to propose and approve spends
+    pub approvers : Vec<String>,
+    /// Number of approvals a spend needs to be made
+    pub threshold : u32,
+}
+
+/// The treasury approvers
+#[cw_serde]
+pub struct Treasury {
+    pub approvers : Vec<Addr>,
+    pub threshold : u32,
+}
+
+/// What a spend pays out
+#[cw_serde]
+pub enum SpendAmount {
+    /// The token itself, out of the contract's balance
+    Token(Uint128),
+    /// Native coins held by the contract
+    Native(Coin),
+}
+
+/// A spend waiting for enough approvals
+#[cw_serde]
+pub struct SpendProposal {
+    /// The recipient of the spend
+    pub to        : Addr,
+    /// The amount spent
+    pub amount    : SpendAmount,
+    /// What the spend is for
+    pub memo      : String,
+    /// The approvers who approved it, the proposer first
+    pub approvals : Vec<Addr>,
+}
+
+/// The treasury, if set at instantiation
+pub const TREASURY: Item<Treasury> = Item::new("treasury");
+
+/// Pending spend proposals, by id
+pub const SPEND_PROPOSALS: Map<u64, SpendProposal> = Map::new("spend_proposals");
+
+/// Last spend proposal id used
+pub const SPEND_COUNT: Item<u64> = Item::new("spend_count");
+
+
+/// Set the treasury approvers at instantiation. The threshold must be reachable, and at least one.
+/// # Arguments
+/// * `deps` - mutable dependency which has the storage (state) of the chain
+/// * `init` - the approvers and threshold
+/// # Returns
+/// * unit on Ok
+/// * the error type on Err
+pub fn init_treasury(deps: DepsMut, init: TreasuryInit) -> Result<(), ContractError> {
+    let mut approvers = init.approvers
+        .iter()
+        .map(|addr| deps.api.addr_validate(addr))
+        .collect::<StdResult<Vec<_>>>()?;
+    approvers.sort();
+    approvers.dedup();
+    if init.threshold == 0 || init.threshold as usize > approvers.len() {
+        return Err(ContractError::InvalidTreasury {});
+    }
+    TREASURY.save(deps.storage, &Treasury { approvers, threshold: init.threshold })?;
+    Ok(())
+}
+
+
+/// Ensure that the sender is one of the treasury approvers
+fn assert_approver(storage: &dyn Storage, sender: &Addr) -> Result<Treasury, ContractError> {
+    let treasury = TREASURY.may_load(storage)?.ok_or(ContractError::Unauthorized {})?;
+    if !treasury.approvers.contains(sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+    Ok(treasury)
+}
+
+
+/// Propose a spend - approvers only. The proposal counts as the proposer's approval, so that the
+/// spend is made right away with a threshold of one.
+/// # Arguments
+/// * `deps`   - mutable dependency which has the storage (state) of the chain
+/// * `env`    - environment variables, with this contract's address
+/// * `info`   - message info, the sender must be an approver
+/// * `to`     - the recipient of the spend
+/// * `amount` - the amount spent
+/// * `memo`   - what the spend is for, capped in size
+/// # Returns
+/// * the execute response on Ok
+/// * the error type on Err
+pub fn execute_propose_spend(
+    deps   : DepsMut,
+    env    : Env,
+    info   : MessageInfo,
+    to     : String,
+    amount : SpendAmount,
+    memo   : String,
+) -> Result<Response, ContractError> {
+    let treasury = assert_approver(deps.storage, &info.sender)?;
+    if memo.len() > MAX_MEMO_LEN {
+        return Err(ContractError::MemoTooLong { max: MAX_MEMO_LEN });
+    }
+    let zero = match &amount {
+        SpendAmount::Token(amount) => amount.is_zero(),
+        SpendAmount::Native(coin) => coin.amount.is_zero(),
+    };
+    if zero {
+        return Err(ContractError::InvalidSpend {});
+    }
+
+    let id = SPEND_COUNT.may_load(deps.storage)?.unwrap_or_default() + 1;
+    SPEND_COUNT.save(deps.storage, &id)?;
+    let proposal = SpendProposal {
+        to        : deps.api.addr_validate(&to)?,
+        amount,
+        memo,
+        approvals : vec![info.sender.clone()],
+    };
+    let res = Response::new()
+        .add_attribute("action", "propose_spend")
+        .add_attribute("spend_id", id.to_string())
+        .add_attribute("by", info.sender);
+    settle_spend(deps, env, &treasury, id, proposal, res)
+}
+
+
+/// Approve a spend - approvers only, once each. The spend is made with the last approval needed.
+/// # Arguments
+/// * `deps` - mutable dependency which has the storage (state) of the chain
+/// * `env`  - environment variables, with this contract's address
+/// * `info` - message info, the sender must be an approver
+/// * `id`   - the spend proposal
+/// # Returns
+/// * the execute response on Ok
+/// * the error type on Err
+pub fn execute_approve_spend(
+    deps : DepsMut,
+    env  : Env,
+    info : MessageInfo,
+    id   : u64,
+) -> Result<Response, ContractError> {
+    let treasury = assert_approver(deps.storage, &info.sender)?;
+    let mut proposal = SPEND_PROPOSALS.load(deps.storage, id)?;
+    if proposal.approvals.contains(&info.sender) {
+        return Err(ContractError::AlreadyApproved {});
+    }
+    proposal.approvals.push(info.sender.clone());
+    let res = Response::new()
+        .add_attribute("action", "approve_spend")
+        .add_attribute("spend_id", id.to_string())
+        .add_attribute("by", info.sender);
+    settle_spend(deps, env, &treasury, id, proposal, res)
+}
+
+
+/// Make the spend if it has enough approvals, keep it pending otherwise.
+fn settle_spend(
+    deps     : DepsMut,
+    env      : Env,
+    treasury : &Treasury,
+    id       : u64,
+    proposal : SpendProposal,
+    res      : Response,
+) -> Result<Response, ContractError> {
+    let approvals = proposal.approvals.len();
+    let res = res.add_attribute("approvals", approvals.to_string());
+    if approvals < treasury.threshold as usize {
+        SPEND_PROPOSALS.save(deps.storage, id, &proposal)?;
+        return Ok(res.add_attribute("executed", "false"));
+    }
+
+    SPEND_PROPOSALS.remove(deps.storage, id);
+    let res = res
+        .add_attribute("executed", "true")
+        .add_attribute("to", &proposal.to)
+        .add_attribute("memo", proposal.memo);
+    match proposal.amount {
+        // the token is transferred out of the contract's own balance, except for what it holds on
+        // behalf of others
+        SpendAmount::Token(amount) => {
+            let contract = &env.contract.address;
+            let balance = BALANCES.may_load(deps.storage, contract)?.unwrap_or_default();
+            let escrowed = ESCROWED.may_load(deps.storage)?.unwrap_or_default();
+            let available = balance.saturating_sub(escrowed);
+            if amount > available {
+                return Err(ContractError::InsufficientTreasury { available });
+            }
+            let info = MessageInfo { sender: env.contract.address.clone(), funds: vec![] };
+            execute_transfer(deps, env, info, proposal.to.into(), amount)?;
+            Ok(res.add_attribute("amount", amount))
+        }
+        // native coins are sent out of the contract's balance, except for the converter reserves
+        // backing the token
+        SpendAmount::Native(coin) => {
+            let contract = &env.contract.address;
+            let balance = deps.querier.query_balance(contract, &coin.denom)?.amount;
+            let reserve = RESERVES.may_load(deps.storage, &coin.denom)?.unwrap_or_default();
+            let available = balance.saturating_sub(reserve);
+            if coin.amount > available {
+                return Err(ContractError::InsufficientTreasury { available });
+            }
+            Ok(res
+                .add_attribute("amount", coin.to_string())
+                .add_message(BankMsg::Send { to_address: proposal.to.into(), amount: vec![coin] }))
+        }
+    }
+}
+
+
+/// Query the spend proposals waiting for approvals.
+/// # Arguments
+/// * `deps`        - dependency which has the storage (state) of the chain
+/// * `start_after` - the proposal id to start after
+/// * `limit`       - the list size limit
+/// # Returns
+///   The list of pending spend proposals
+pub fn query_pending_spends(
+    deps        : Deps,
+    start_after : Option<u64>,
+    limit       : Option<u32>,
+) -> StdResult<SpendProposalsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+    let threshold = TREASURY.may_load(deps.storage)?.map_or(0, |treasury| treasury.threshold);
+
+    let proposals = SPEND_PROPOSALS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            item.map(|(id, proposal)| SpendProposalResponse {
+                id,
+                to        : proposal.to.into(),
+                amount    : proposal.amount,
+                memo      : proposal.memo,
+                approvals : proposal.approvals.into_iter().map(String::from).collect(),
+                threshold,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+    Ok(SpendProposalsResponse { proposals })
+}
@@ -0,0 +1,91 @@
+/*
+Validation module - the contract's own validation of addresses and amounts, exposed as queries so
+that front-ends check user input exactly as the contract would rather than re-implementing it. An
+address is validated by the chain's api, which checks its bech32 prefix and checksum and that it is
+normalized. An amount is parsed from the token's display units, with up to as many fractional
+digits as the token has decimals, into its base units.
+*/
+
+use cosmwasm_std::{Deps, StdResult, Uint128};
+use cw20_base::state::TOKEN_INFO;
+
+use crate::error::ContractError;
+use crate::msg::{ValidateAddressResponse, ValidateAmountResponse};
+
+
+/// Parse an amount in display units into base units, given the token's decimals - e.g. "1.5" is
+/// 1500000 base units of a token with 6 decimals.
+/// # Arguments
+/// * `amount`   - the amount in display units, digits with an optional fractional part
+/// * `decimals` - the decimals of the token
+/// # Returns
+/// * the amount in base units on Ok
+/// * the error type on Err, if the amount is malformed, too precise or too large
+pub fn parse_amount(amount: &str, decimals: u8) -> Result<Uint128, ContractError> {
+    let invalid = |reason: &str| ContractError::InvalidAmount {
+        amount : amount.to_string(),
+        reason : reason.to_string(),
+    };
+    let (whole, fraction) = match amount.split_once('.') {
+        Some((whole, fraction)) => (whole, fraction),
+        None => (amount, ""),
+    };
+    let is_digits = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+    if whole.is_empty() || !is_digits(whole) || !is_digits(fraction) || amount.ends_with('.') {
+        return Err(invalid("not a decimal number"));
+    }
+    if fraction.len() > decimals as usize {
+        return Err(invalid("more fractional digits than the token's decimals"));
+    }
+
+    // the fraction padded to the decimals makes the base units along with the whole part
+    let fraction = format!("{:0<width$}", fraction, width = decimals as usize);
+    let digits = format!("{}{}", whole, fraction);
+    digits.parse::<u128>().map(Uint128::new).map_err(|_| invalid("too large"))
+}
+
+
+/// Query whether an address is valid, as the contract validates the addresses it is given.
+/// # Arguments
+/// * `deps`    - dependency which has the storage (state) of the chain
+/// * `address` - the address to validate
+/// # Returns
+///   Whether it is valid, with the address on success and the reason on failure
+pub fn query_validate_address(deps: Deps, address: String) -> StdResult<ValidateAddressResponse> {
+    Ok(match deps.api.addr_validate(&address) {
+        Ok(address) => ValidateAddressResponse {
+            valid   : true,
+            address : Some(address.into()),
+            error   : None,
+        },
+        Err(err) => ValidateAddressResponse {
+            valid   : false,
+            address : None,
+            error   : Some(err.to_string()),
+        },
+    })
+}
+
+
+/// Query whether an amount in display units is valid, as parsed with the token's decimals.
+/// # Arguments
+/// * `deps`       - dependency which has the storage (state) of the chain
+/// * `amount_str` - the amount in display units
+/// # Returns
+///   Whether it is valid, with the amount in base units on success and the reason on failure
+pub fn query_validate_amount(deps: Deps, amount_str: String) -> StdResult<ValidateAmountResponse> {
+    let decimals = TOKEN_INFO.load(deps.storage)?.decimals;
+    Ok(match parse_amount(&amount_str, decimals) {
+        Ok(amount) => ValidateAmountResponse {
+            valid  : true,
+            amount : Some(amount),
+            error  : None,
+        },
+        Err(err) => ValidateAmountResponse {
+            valid  : false,
+            amount : None,
+            error  : Some(err.to_string()),
+        },
+    })
+}
+
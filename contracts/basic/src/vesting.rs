@@ -0,0 +1,165 @@
+/*
+Vesting module - contributor grants. The minter mints a grant straight into a vesting entry rather
+than a liquid balance: the tokens are held by the token contract itself, and vest linearly over
+the grant's schedule. The recipient claims whatever has vested so far, at any time.
+*/
+
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{
+    Addr, Deps, DepsMut, Env, MessageInfo, Order, Response, StdResult, Timestamp, Uint128
+};
+use cw_storage_plus::{Item, Map};
+use cw20_base::contract::{execute_mint, execute_transfer};
+
+use crate::error::ContractError;
+use crate::msg::{VestingGrantResponse, VestingResponse};
+use crate::roles::minting_info;
+use crate::state::{add_escrowed, release_escrowed};
+
+/// Linear vesting schedule - nothing is vested before the start, everything is at the end
+#[cw_serde]
+pub struct VestingSchedule {
+    pub start : Timestamp,
+    pub end   : Timestamp,
+}
+
+impl VestingSchedule {
+    /// Amount of a grant vested at the given time
+    pub fn vested(&self, amount: Uint128, time: Timestamp) -> Uint128 {
+        if time <= self.start {
+            return Uint128::zero();
+        }
+        if time >= self.end {
+            return amount;
+        }
+        let elapsed = time.seconds() - self.start.seconds();
+        let duration = self.end.seconds() - self.start.seconds();
+        amount.multiply_ratio(elapsed, duration)
+    }
+}
+
+/// A vesting grant
+#[cw_serde]
+pub struct VestingGrant {
+    /// Total amount granted
+    pub amount   : Uint128,
+    /// How the amount vests
+    pub schedule : VestingSchedule,
+    /// Amount claimed so far
+    pub claimed  : Uint128,
+}
+
+/// Vesting grants, by recipient and grant id
+pub const VESTING_GRANTS: Map<(&Addr, u64), VestingGrant> = Map::new("vesting_grants");
+
+/// Last grant id used
+pub const VESTING_COUNT: Item<u64> = Item::new("vesting_count");
+
+
+/// Mint vested - the minter mints a grant into a vesting entry for the recipient. The tokens are
+/// minted to the token contract, which holds them until claimed.
+/// # Arguments
+/// * `deps`      - mutable dependency which has the storage (state) of the chain
+/// * `env`       - environment variables which include block information
+/// * `info`      - message info, the sender must be the minter or hold the minter role
+/// * `recipient` - the address the grant vests to
+/// * `amount`    - the amount granted
+/// * `schedule`  - how the amount vests
+/// # Returns
+/// * the execute response on Ok
+/// * the error type on Err
+pub fn execute_mint_vested(
+    mut deps  : DepsMut,
+    env       : Env,
+    info      : MessageInfo,
+    recipient : String,
+    amount    : Uint128,
+    schedule  : VestingSchedule,
+) -> Result<Response, ContractError> {
+    let recipient = deps.api.addr_validate(&recipient)?;
+    if amount.is_zero() || schedule.end <= schedule.start {
+        return Err(ContractError::InvalidVesting {});
+    }
+
+    // minting checks the sender is the minter, and the cap
+    let contract = env.contract.address.to_string();
+    let info = minting_info(deps.storage, info)?;
+    let res = execute_mint(deps.branch(), env, info, contract, amount)?;
+
+    let id = VESTING_COUNT.may_load(deps.storage)?.unwrap_or_default() + 1;
+    VESTING_COUNT.save(deps.storage, &id)?;
+    let grant = VestingGrant { amount, schedule, claimed: Uint128::zero() };
+    VESTING_GRANTS.save(deps.storage, (&recipient, id), &grant)?;
+    add_escrowed(deps.storage, amount)?;
+    Ok(res
+        .add_attribute("vesting_id", id.to_string())
+        .add_attribute("vesting_recipient", recipient))
+}
+
+
+/// Claim vested - the sender claims everything vested and not yet claimed across their grants.
+/// Fully claimed grants are removed.
+/// # Arguments
+/// * `deps` - mutable dependency which has the storage (state) of the chain
+/// * `env`  - environment variables which include block information
+/// * `info` - message info, the sender is the grants' recipient
+/// # Returns
+/// * the execute response on Ok
+/// * the error type on Err
+pub fn execute_claim_vested(
+    deps : DepsMut,
+    env  : Env,
+    info : MessageInfo,
+) -> Result<Response, ContractError> {
+    let grants = VESTING_GRANTS
+        .prefix(&info.sender)
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let mut total = Uint128::zero();
+    for (id, mut grant) in grants {
+        let claimable = grant.schedule.vested(grant.amount, env.block.time) - grant.claimed;
+        total += claimable;
+        grant.claimed += claimable;
+        if grant.claimed == grant.amount {
+            VESTING_GRANTS.remove(deps.storage, (&info.sender, id));
+        } else {
+            VESTING_GRANTS.save(deps.storage, (&info.sender, id), &grant)?;
+        }
+    }
+    if total.is_zero() {
+        return Err(ContractError::NothingVested {});
+    }
+
+    // the vested tokens are held by the contract
+    release_escrowed(deps.storage, total)?;
+    let contract_info = MessageInfo { sender: env.contract.address.clone(), funds: vec![] };
+    let res = execute_transfer(deps, env, contract_info, info.sender.into(), total)?;
+    Ok(res.add_attribute("claimed", total))
+}
+
+
+/// Query the vesting grants of an address, with what is vested at the current block.
+/// # Arguments
+/// * `deps`    - dependency which has the storage (state) of the chain
+/// * `env`     - environment variables which include block information
+/// * `address` - the grants' recipient
+/// # Returns
+///   The vesting grants
+pub fn query_vesting(deps: Deps, env: Env, address: String) -> StdResult<VestingResponse> {
+    let addr = deps.api.addr_validate(&address)?;
+    let grants = VESTING_GRANTS
+        .prefix(&addr)
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| {
+            item.map(|(id, grant)| VestingGrantResponse {
+                id,
+                vested   : grant.schedule.vested(grant.amount, env.block.time),
+                amount   : grant.amount,
+                claimed  : grant.claimed,
+                schedule : grant.schedule,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+    Ok(VestingResponse { grants })
+}